@@ -0,0 +1,60 @@
+//! Criterion suite over the real HTTP stack
+//!
+//! Each case sends JSON-RPC requests to an in-process server backed by the
+//! mock daemon, so routing, validation, middleware and the daemon adapter are
+//! all on the measured path.
+//!
+//! ```text
+//! cargo bench --features bench --bench http
+//! ```
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use verus_rpc_server::bench::{load, mixes, BenchServer, BenchServerOptions};
+
+/// Concurrent requests per iteration in the throughput group
+const BATCH: usize = 32;
+
+fn http_benchmarks(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let server = runtime
+        .block_on(BenchServer::start(BenchServerOptions::default()))
+        .expect("benchmark server");
+    let client = load::client(BATCH);
+    let target = server.url().to_string();
+
+    // Latency of single calls, per method
+    let mut single = c.benchmark_group("rpc");
+    for mix in mixes() {
+        for call in &mix.calls {
+            let id = BenchmarkId::new(mix.name, call.method);
+            single.bench_with_input(id, call, |b, call| {
+                b.to_async(&runtime).iter(|| async {
+                    load::send(&client, &target, call, 1).await.expect("request failed");
+                });
+            });
+        }
+    }
+    single.finish();
+
+    // Throughput of each mix with BATCH requests in flight
+    let mut concurrent = c.benchmark_group("mix");
+    concurrent.throughput(Throughput::Elements(BATCH as u64));
+    for mix in mixes() {
+        let calls: Vec<_> = mix.calls.iter().cycle().take(BATCH).cloned().collect();
+        concurrent.bench_with_input(BenchmarkId::from_parameter(mix.name), &calls, |b, calls| {
+            b.to_async(&runtime).iter(|| async {
+                let requests = calls
+                    .iter()
+                    .enumerate()
+                    .map(|(id, call)| load::send(&client, &target, call, id as u64));
+                for result in futures::future::join_all(requests).await {
+                    result.expect("request failed");
+                }
+            });
+        });
+    }
+    concurrent.finish();
+}
+
+criterion_group!(benches, http_benchmarks);
+criterion_main!(benches);
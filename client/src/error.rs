@@ -0,0 +1,56 @@
+//! Client error type
+
+use serde_json::Value;
+use thiserror::Error;
+
+/// Errors returned by [`VerusClient`](super::VerusClient)
+#[derive(Error, Debug, Clone)]
+pub enum ClientError {
+    #[error("Transport error: {0}")]
+    Transport(String),
+
+    #[error("HTTP {status}: {body}")]
+    Http { status: u16, body: String },
+
+    #[error("RPC error {code}: {message}")]
+    Rpc { code: i64, message: String, data: Option<Value> },
+
+    #[error("Invalid response: {0}")]
+    Decode(String),
+
+    #[error("Token acquisition failed: {0}")]
+    Token(String),
+
+    #[error("No proof of work solution within {0} attempts")]
+    PowExhausted(u64),
+
+    #[error("Payment {payment_id} ended as {status}")]
+    Payment { payment_id: String, status: String },
+
+    #[error("Payment proof rejected: {0}")]
+    PaymentProof(String),
+}
+
+impl ClientError {
+    /// Whether the request may succeed if sent again unchanged
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ClientError::Transport(_) => true,
+            ClientError::Http { status, .. } => matches!(status, 429 | 502 | 503 | 504),
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_decode() {
+            ClientError::Decode(error.to_string())
+        } else {
+            ClientError::Transport(error.to_string())
+        }
+    }
+}
+
+/// Client result type
+pub type ClientResult<T> = Result<T, ClientError>;
@@ -0,0 +1,501 @@
+//! Typed async client for the RPC proxy
+//!
+//! Wraps the JSON-RPC endpoint, the token service and the payments API so
+//! Rust integrators don't hand-roll HTTP code against the proxy:
+//!
+//! - bearer tokens are acquired on first use (anonymously or by solving a
+//!   proof-of-work challenge), renewed before they expire and once more when
+//!   the proxy answers 401;
+//! - rate-limited and unavailable responses (429/502/503/504) and transport
+//!   errors are retried, honouring `Retry-After`;
+//! - `batch` sends several calls concurrently and returns results in order;
+//! - common daemon methods have typed wrappers (see `methods.rs`);
+//! - tokens bought through the payments API can be audited against the chain
+//!   (see `payment_proof`).
+//!
+//! The crate builds for native targets (on tokio) and for
+//! `wasm32-unknown-unknown`, where requests go through the browser's `fetch`
+//! and proof-of-work challenges are solved in slices that yield to the event
+//! loop. The server crate re-exports it as `verus_rpc_server::client` under
+//! the `client` feature.
+//!
+//! ```no_run
+//! # async fn example() -> verus_rpc_client::ClientResult<()> {
+//! use verus_rpc_client::{TokenSource, VerusClient};
+//!
+//! let client = VerusClient::builder("https://rpc.example.com/")
+//!     .token_service("https://tokens.example.com")
+//!     .token_source(TokenSource::ProofOfWork { max_attempts: 50_000_000 })
+//!     .build()?;
+//! let height = client.get_block_count().await?;
+//! let block = client.get_block(&client.get_block_hash(height).await?).await?;
+//! # Ok(()) }
+//! ```
+
+pub mod error;
+mod methods;
+pub mod payment_proof;
+pub mod pow;
+pub mod retry;
+mod runtime;
+pub mod types;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use futures::lock::Mutex;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+pub use error::{ClientError, ClientResult};
+pub use payment_proof::{payment_claim, PaymentVerification};
+pub use retry::RetryPolicy;
+pub use types::{
+    AddressBalance, AddressUtxo, Block, Info, IssuedToken, PaymentClaim, PaymentQuote, PaymentQuoteRequest,
+    PaymentState, PaymentStatus, PowChallenge, ShieldedAddressType, Transaction,
+};
+
+use types::{IssueMode, IssueRequest};
+
+/// Tokens are renewed this long before they expire
+const RENEW_BEFORE_EXPIRY: Duration = Duration::from_secs(30);
+
+/// How the client obtains its bearer token
+#[derive(Debug, Clone, Default)]
+pub enum TokenSource {
+    /// Send no token
+    #[default]
+    None,
+    /// Use this token as is; it is never renewed
+    Static(String),
+    /// Ask the token service for an anonymous token
+    Anonymous,
+    /// Solve a proof-of-work challenge from the token service, trying at most `max_attempts` nonces
+    ProofOfWork { max_attempts: u64 },
+}
+
+impl TokenSource {
+    fn renewable(&self) -> bool {
+        matches!(self, TokenSource::Anonymous | TokenSource::ProofOfWork { .. })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    value: String,
+    renew_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl CachedToken {
+    fn issued(token: &IssuedToken) -> Self {
+        let lifetime = Duration::from_secs(token.expires_in).saturating_sub(RENEW_BEFORE_EXPIRY);
+        Self {
+            value: token.token.clone(),
+            renew_at: chrono::Duration::from_std(lifetime).ok().map(|lifetime| chrono::Utc::now() + lifetime),
+        }
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.renew_at.is_none_or(|renew_at| chrono::Utc::now() < renew_at)
+    }
+}
+
+/// Builder for [`VerusClient`]
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    rpc_url: String,
+    token_url: Option<String>,
+    token_source: TokenSource,
+    api_key: Option<String>,
+    forwarded_for: Option<String>,
+    retry: RetryPolicy,
+    timeout: Duration,
+    batch_concurrency: usize,
+}
+
+impl ClientBuilder {
+    /// Base URL of the token service (`token-service` binary); required for anonymous and PoW tokens
+    pub fn token_service(mut self, url: impl Into<String>) -> Self {
+        self.token_url = Some(url.into());
+        self
+    }
+
+    pub fn token_source(mut self, source: TokenSource) -> Self {
+        self.token_source = source;
+        self
+    }
+
+    /// Send `X-API-Key` with every proxy request
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Send `X-Forwarded-For`; only needed when no reverse proxy sits in front of the proxy
+    pub fn forwarded_for(mut self, address: impl Into<String>) -> Self {
+        self.forwarded_for = Some(address.into());
+        self
+    }
+
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Timeout for each HTTP request (default: 30 seconds)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Calls of one `batch` in flight at once (default: 8)
+    pub fn batch_concurrency(mut self, concurrency: usize) -> Self {
+        self.batch_concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn build(self) -> ClientResult<VerusClient> {
+        if self.token_source.renewable() && self.token_url.is_none() {
+            return Err(ClientError::Token("this token source needs a token service URL".to_string()));
+        }
+        // Browsers set the user agent themselves and reject overriding it
+        #[cfg(not(target_arch = "wasm32"))]
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("verus-rpc-client/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        #[cfg(target_arch = "wasm32")]
+        let http = reqwest::Client::builder().build()?;
+        let token = match &self.token_source {
+            TokenSource::Static(value) => Some(CachedToken { value: value.clone(), renew_at: None }),
+            _ => None,
+        };
+        Ok(VerusClient {
+            http,
+            rpc_url: self.rpc_url,
+            token_url: self.token_url.map(|url| url.trim_end_matches('/').to_string()),
+            token_source: self.token_source,
+            api_key: self.api_key,
+            forwarded_for: self.forwarded_for,
+            retry: self.retry,
+            timeout: self.timeout,
+            batch_concurrency: self.batch_concurrency,
+            token: Mutex::new(token),
+            next_id: AtomicU64::new(1),
+        })
+    }
+}
+
+/// Async client for the proxy
+pub struct VerusClient {
+    http: reqwest::Client,
+    rpc_url: String,
+    token_url: Option<String>,
+    token_source: TokenSource,
+    api_key: Option<String>,
+    forwarded_for: Option<String>,
+    retry: RetryPolicy,
+    timeout: Duration,
+    batch_concurrency: usize,
+    token: Mutex<Option<CachedToken>>,
+    next_id: AtomicU64,
+}
+
+impl VerusClient {
+    /// Builder for a client of the JSON-RPC endpoint at `rpc_url` (e.g. `https://rpc.example.com/`)
+    pub fn builder(rpc_url: impl Into<String>) -> ClientBuilder {
+        ClientBuilder {
+            rpc_url: rpc_url.into(),
+            token_url: None,
+            token_source: TokenSource::None,
+            api_key: None,
+            forwarded_for: None,
+            retry: RetryPolicy::default(),
+            timeout: Duration::from_secs(30),
+            batch_concurrency: 8,
+        }
+    }
+
+    /// Call `method` and decode its result
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> ClientResult<T> {
+        let result = self.call_raw(method, params).await?;
+        serde_json::from_value(result).map_err(|e| ClientError::Decode(format!("{}: {}", method, e)))
+    }
+
+    /// Call `method` and return its result as JSON
+    pub async fn call_raw(&self, method: &str, params: Value) -> ClientResult<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({"jsonrpc": "2.0", "method": method, "params": params, "id": id});
+        let mut renewed = false;
+        loop {
+            let token = self.token().await?;
+            let response = self
+                .send(|| {
+                    let mut request = self.http.post(&self.rpc_url).timeout(self.timeout).json(&body);
+                    if let Some(token) = &token {
+                        request = request.bearer_auth(token);
+                    }
+                    self.with_proxy_headers(request)
+                })
+                .await?;
+            if response.status().as_u16() == 401 && !renewed && self.token_source.renewable() {
+                // Revoked or expired early; try once with a fresh token
+                *self.token.lock().await = None;
+                renewed = true;
+                continue;
+            }
+            return rpc_result(response).await;
+        }
+    }
+
+    /// Send `calls` concurrently; results are in the order of `calls`
+    ///
+    /// The proxy takes one call per HTTP request, so this bounds how many are
+    /// in flight (see `ClientBuilder::batch_concurrency`) rather than sending
+    /// a JSON-RPC batch array.
+    pub async fn batch(&self, calls: Vec<(String, Value)>) -> Vec<ClientResult<Value>> {
+        futures::stream::iter(calls)
+            .map(|(method, params)| async move { self.call_raw(&method, params).await })
+            .buffered(self.batch_concurrency)
+            .collect()
+            .await
+    }
+
+    /// Current bearer token, acquiring or renewing it first when the token source allows
+    pub async fn token(&self) -> ClientResult<Option<String>> {
+        let mut cached = self.token.lock().await;
+        if let Some(token) = cached.as_ref().filter(|token| token.is_fresh() || !self.token_source.renewable()) {
+            return Ok(Some(token.value.clone()));
+        }
+        let issued = match self.token_source {
+            TokenSource::None | TokenSource::Static(_) => return Ok(cached.as_ref().map(|token| token.value.clone())),
+            TokenSource::Anonymous => self.acquire_anonymous_token().await?,
+            TokenSource::ProofOfWork { max_attempts } => self.acquire_pow_token(max_attempts).await?,
+        };
+        *cached = Some(CachedToken::issued(&issued));
+        Ok(Some(issued.token))
+    }
+
+    /// Use `token` for subsequent calls, e.g. one bought through the payments API
+    pub async fn set_token(&self, token: impl Into<String>) {
+        *self.token.lock().await = Some(CachedToken { value: token.into(), renew_at: None });
+    }
+
+    /// Request an anonymous token from the token service
+    pub async fn acquire_anonymous_token(&self) -> ClientResult<IssuedToken> {
+        self.issue(IssueMode::Anonymous, None).await
+    }
+
+    /// Fetch a proof-of-work challenge, solve it and exchange the proof for a token
+    pub async fn acquire_pow_token(&self, max_attempts: u64) -> ClientResult<IssuedToken> {
+        let challenge: PowChallenge = self.post_json(&self.token_endpoint("pow/challenge")?, &json!({})).await?;
+        let proof = pow::solve_yielding(&challenge, max_attempts).await?;
+        self.issue(IssueMode::ProofOfWork(proof), Some(challenge)).await
+    }
+
+    async fn issue(&self, mode: IssueMode, pow_challenge: Option<PowChallenge>) -> ClientResult<IssuedToken> {
+        let request = IssueRequest {
+            user_id: String::new(),
+            permissions: vec!["read".to_string()],
+            client_ip: None,
+            user_agent: None,
+            custom_expiration: None,
+            mode,
+            pow_challenge,
+        };
+        self.post_json(&self.token_endpoint("issue")?, &request)
+            .await
+            .map_err(|e| ClientError::Token(e.to_string()))
+    }
+
+    /// Quote a payment for a token tier
+    pub async fn request_payment(&self, request: &PaymentQuoteRequest) -> ClientResult<PaymentQuote> {
+        self.post_json(&self.proxy_endpoint("payments/request"), request).await
+    }
+
+    /// Submit the raw transaction paying `payment_id`; returns its txid
+    pub async fn submit_payment(&self, payment_id: &str, rawtx_hex: &str) -> ClientResult<String> {
+        let body = json!({"payment_id": payment_id, "rawtx_hex": rawtx_hex});
+        let response: Value = self.post_json(&self.proxy_endpoint("payments/submit"), &body).await?;
+        response
+            .get("txid")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| ClientError::Decode("payment submission without txid".to_string()))
+    }
+
+    pub async fn payment_status(&self, payment_id: &str) -> ClientResult<PaymentState> {
+        let url = self.proxy_endpoint(&format!("payments/status/{}", payment_id));
+        let response = self.send(|| self.with_proxy_headers(self.http.get(&url).timeout(self.timeout))).await?;
+        json_body(response).await
+    }
+
+    /// Poll a submitted payment until it is finalized, then use its token for subsequent calls
+    pub async fn wait_for_payment(&self, payment_id: &str, poll_interval: Duration, timeout: Duration) -> ClientResult<String> {
+        let deadline = chrono::Duration::from_std(timeout)
+            .ok()
+            .and_then(|timeout| chrono::Utc::now().checked_add_signed(timeout));
+        loop {
+            let state = self.payment_status(payment_id).await?;
+            if let Some(token) = state.final_token {
+                self.set_token(token.clone()).await;
+                return Ok(token);
+            }
+            if matches!(state.status, PaymentStatus::Failed | PaymentStatus::Expired) || deadline.is_some_and(|deadline| chrono::Utc::now() >= deadline) {
+                let status = serde_json::to_value(state.status).ok().and_then(|s| s.as_str().map(str::to_string));
+                return Err(ClientError::Payment { payment_id: payment_id.to_string(), status: status.unwrap_or_default() });
+            }
+            runtime::sleep(poll_interval).await;
+        }
+    }
+
+    fn token_endpoint(&self, path: &str) -> ClientResult<String> {
+        let base = self
+            .token_url
+            .as_ref()
+            .ok_or_else(|| ClientError::Token("no token service URL configured".to_string()))?;
+        Ok(format!("{}/{}", base, path))
+    }
+
+    fn proxy_endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.rpc_url.trim_end_matches('/'), path)
+    }
+
+    fn with_proxy_headers(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-api-key", api_key);
+        }
+        if let Some(address) = &self.forwarded_for {
+            request = request.header("x-forwarded-for", address);
+        }
+        request
+    }
+
+    async fn post_json<B: Serialize + ?Sized, T: DeserializeOwned>(&self, url: &str, body: &B) -> ClientResult<T> {
+        let response = self
+            .send(|| self.with_proxy_headers(self.http.post(url).timeout(self.timeout).json(body)))
+            .await?;
+        json_body(response).await
+    }
+
+    /// Send the request built by `build`, retrying per the retry policy
+    async fn send(&self, build: impl Fn() -> reqwest::RequestBuilder) -> ClientResult<reqwest::Response> {
+        let mut retry = 0;
+        loop {
+            let (error, retry_after) = match build().send().await {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    let retry_after = response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(retry::parse_retry_after);
+                    let error = ClientError::Http { status, body: String::new() };
+                    if !error.is_retryable() || retry >= self.retry.max_retries {
+                        return Ok(response);
+                    }
+                    (error, retry_after)
+                }
+                Err(e) => (ClientError::from(e), None),
+            };
+            if !error.is_retryable() || retry >= self.retry.max_retries {
+                return Err(error);
+            }
+            runtime::sleep(self.retry.delay(retry, retry_after)).await;
+            retry += 1;
+        }
+    }
+}
+
+/// Decode a non-JSON-RPC response body, mapping error statuses to `ClientError::Http`
+async fn json_body<T: DeserializeOwned>(response: reqwest::Response) -> ClientResult<T> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(ClientError::Http { status: status.as_u16(), body });
+    }
+    response.json().await.map_err(ClientError::from)
+}
+
+/// Extract the result of a JSON-RPC response
+async fn rpc_result(response: reqwest::Response) -> ClientResult<Value> {
+    let status = response.status().as_u16();
+    let text = response.text().await?;
+    let Ok(mut body) = serde_json::from_str::<Value>(&text) else {
+        return Err(ClientError::Http { status, body: text });
+    };
+    if let Some(error) = body.get("error").filter(|error| !error.is_null()) {
+        return Err(ClientError::Rpc {
+            code: error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+            message: error.get("message").and_then(Value::as_str).unwrap_or_default().to_string(),
+            data: error.get("data").cloned(),
+        });
+    }
+    match body.get_mut("result") {
+        Some(result) if (200..300).contains(&status) => Ok(result.take()),
+        _ => Err(ClientError::Http { status, body: text }),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use warp::Filter;
+
+    /// Proxy that answers 503 with `Retry-After: 0` to the first call and
+    /// requires the token issued by its `/issue` route
+    async fn start_stub() -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let issue = warp::path("issue").and(warp::post()).map(|| {
+            warp::reply::json(&json!({"token": "t1", "token_type": "Bearer", "expires_in": 3600, "token_id": "id1"}))
+        });
+        let rpc = warp::path::end()
+            .and(warp::post())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::body::json())
+            .map(move |authorization: Option<String>, body: Value| {
+                let reply = if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    warp::reply::with_status(warp::reply::json(&json!({"error": "busy"})), warp::http::StatusCode::SERVICE_UNAVAILABLE)
+                } else if authorization.as_deref() != Some("Bearer t1") {
+                    warp::reply::with_status(warp::reply::json(&json!({})), warp::http::StatusCode::UNAUTHORIZED)
+                } else {
+                    let result = if body["method"] == "getblockcount" { json!(42) } else { json!(null) };
+                    warp::reply::with_status(warp::reply::json(&json!({"result": result, "error": null, "id": body["id"]})), warp::http::StatusCode::OK)
+                };
+                warp::reply::with_header(reply, "retry-after", "0")
+            });
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        tokio::spawn(warp::serve(issue.or(rpc)).run(address));
+        while tokio::net::TcpStream::connect(address).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        (format!("http://{}", address), hits)
+    }
+
+    #[tokio::test]
+    async fn test_acquires_token_and_retries_unavailable() {
+        let (url, hits) = start_stub().await;
+        let client = VerusClient::builder(format!("{}/", url))
+            .token_service(&url)
+            .token_source(TokenSource::Anonymous)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get_block_count().await.unwrap(), 42);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        let results = client.batch(vec![("getblockcount".to_string(), json!([])), ("getinfo".to_string(), json!([]))]).await;
+        assert_eq!(results[0].as_ref().unwrap(), &json!(42));
+        assert_eq!(results[1].as_ref().unwrap(), &Value::Null);
+    }
+
+    #[test]
+    fn test_renewable_sources_need_a_token_service() {
+        assert!(VerusClient::builder("http://127.0.0.1/").token_source(TokenSource::Anonymous).build().is_err());
+        assert!(VerusClient::builder("http://127.0.0.1/").token_source(TokenSource::Static("t".into())).build().is_ok());
+    }
+}
@@ -0,0 +1,74 @@
+//! Typed wrappers for common daemon methods
+//!
+//! Parameters follow the shapes the proxy's method registry validates. Any
+//! other method is available through `call` and `call_raw`.
+
+use serde_json::{json, Value};
+
+use super::error::ClientResult;
+use super::types::{AddressBalance, AddressUtxo, Block, Info, Transaction};
+use super::VerusClient;
+
+impl VerusClient {
+    pub async fn get_info(&self) -> ClientResult<Info> {
+        self.call("getinfo", json!([])).await
+    }
+
+    pub async fn get_block_count(&self) -> ClientResult<u64> {
+        self.call("getblockcount", json!([])).await
+    }
+
+    pub async fn get_best_block_hash(&self) -> ClientResult<String> {
+        self.call("getbestblockhash", json!([])).await
+    }
+
+    pub async fn get_block_hash(&self, height: u64) -> ClientResult<String> {
+        self.call("getblockhash", json!([height])).await
+    }
+
+    pub async fn get_block(&self, hash: &str) -> ClientResult<Block> {
+        self.call("getblock", json!([hash, true])).await
+    }
+
+    /// Serialized block as hex
+    pub async fn get_block_hex(&self, hash: &str) -> ClientResult<String> {
+        self.call("getblock", json!([hash, false])).await
+    }
+
+    pub async fn get_raw_transaction(&self, txid: &str) -> ClientResult<Transaction> {
+        self.call("getrawtransaction", json!([txid, 1])).await
+    }
+
+    /// Serialized transaction as hex
+    pub async fn get_raw_transaction_hex(&self, txid: &str) -> ClientResult<String> {
+        self.call("getrawtransaction", json!([txid, 0])).await
+    }
+
+    /// Combined balance of `addresses`, in satoshis
+    pub async fn get_address_balance(&self, addresses: &[&str]) -> ClientResult<AddressBalance> {
+        self.call("getaddressbalance", json!([{"addresses": addresses}])).await
+    }
+
+    pub async fn get_address_utxos(&self, addresses: &[&str]) -> ClientResult<Vec<AddressUtxo>> {
+        self.call("getaddressutxos", json!([{"addresses": addresses}])).await
+    }
+
+    /// Identity by name (`name@`) or i-address
+    pub async fn get_identity(&self, identity: &str) -> ClientResult<Value> {
+        self.call("getidentity", json!([identity])).await
+    }
+
+    /// Currency definition by name or i-address
+    pub async fn get_currency(&self, currency: &str) -> ClientResult<Value> {
+        self.call("getcurrency", json!([currency])).await
+    }
+
+    pub async fn get_raw_mempool(&self) -> ClientResult<Vec<String>> {
+        self.call("getrawmempool", json!([])).await
+    }
+
+    /// Broadcast a signed transaction; returns its txid
+    pub async fn send_raw_transaction(&self, rawtx_hex: &str) -> ClientResult<String> {
+        self.call("sendrawtransaction", json!([rawtx_hex])).await
+    }
+}
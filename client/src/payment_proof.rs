@@ -0,0 +1,191 @@
+//! Audit of payment tokens against the chain
+//!
+//! Tokens bought through the payments API carry a `payment` claim naming the
+//! transactions that paid for them, the tier, the amount received and the
+//! height of the block holding the newest payment. A relying party handed such
+//! a token can check those facts through any proxy, without trusting the one
+//! that issued it.
+//!
+//! The HS256 signature of the token can only be checked with the issuer's
+//! secret. Relying parties holding it should verify the signature first: the
+//! checks here prove that the claimed payment is on chain, not that the token
+//! was issued for it.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_json::Value;
+
+use super::error::{ClientError, ClientResult};
+use super::types::{PaymentClaim, Transaction};
+use super::VerusClient;
+
+/// Rounding slack when summing output values
+const AMOUNT_EPSILON: f64 = 1e-8;
+
+/// Result of [`VerusClient::verify_payment_token`]
+#[derive(Debug, Clone)]
+pub struct PaymentVerification {
+    pub claim: PaymentClaim,
+    /// Confirmations of the newest payment transaction
+    pub confirmations: u64,
+    /// Native amount the transactions paid to the claimed address; `None` for
+    /// shielded addresses and PBaaS currency payments, whose amounts are not
+    /// visible in transparent outputs
+    pub amount_on_chain: Option<f64>,
+}
+
+/// `payment` claim of a token, decoded without checking the signature
+pub fn payment_claim(token: &str) -> ClientResult<PaymentClaim> {
+    let token = token.strip_prefix("Bearer ").unwrap_or(token);
+    let payload = token.split('.').nth(1).ok_or_else(|| rejected("not a JWT"))?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|e| rejected(format!("token payload: {}", e)))?;
+    let mut claims: Value = serde_json::from_slice(&payload).map_err(|e| rejected(format!("token payload: {}", e)))?;
+    let payment = claims
+        .get_mut("payment")
+        .map(Value::take)
+        .filter(|payment| !payment.is_null())
+        .ok_or_else(|| rejected("token carries no payment claim"))?;
+    serde_json::from_value(payment).map_err(|e| rejected(format!("payment claim: {}", e)))
+}
+
+impl VerusClient {
+    /// Check the `payment` claim of a token against the chain
+    ///
+    /// Every claimed transaction must be mined at or below `block_height`, the
+    /// newest exactly there, with at least `min_confirmations`. For native
+    /// payments to a transparent address the outputs must also cover the
+    /// claimed amount.
+    pub async fn verify_payment_token(&self, token: &str, min_confirmations: u64) -> ClientResult<PaymentVerification> {
+        let claim = payment_claim(token)?;
+        let block_height = claim.block_height.ok_or_else(|| rejected("payment was unconfirmed when the token was issued"))?;
+        if claim.txids.is_empty() {
+            return Err(rejected("payment claim lists no transactions"));
+        }
+
+        let mut newest = 0;
+        let mut confirmations = u64::MAX;
+        let mut paid = 0.0;
+        for txid in &claim.txids {
+            let tx = self.get_raw_transaction(txid).await?;
+            let tx_confirmations = tx.confirmations.unwrap_or(0).max(0) as u64;
+            let height = tx
+                .height
+                .filter(|_| tx_confirmations > 0)
+                .ok_or_else(|| rejected(format!("{} is not mined", txid)))?;
+            if height > block_height {
+                return Err(rejected(format!("{} is mined at {}, above the claimed {}", txid, height, block_height)));
+            }
+            newest = newest.max(height);
+            confirmations = confirmations.min(tx_confirmations);
+            paid += paid_to(&tx, &claim.address);
+        }
+        if newest != block_height {
+            return Err(rejected(format!("newest payment is mined at {}, not the claimed {}", newest, block_height)));
+        }
+        if confirmations < min_confirmations {
+            return Err(rejected(format!("{} confirmations, {} required", confirmations, min_confirmations)));
+        }
+
+        let transparent = claim.currency.is_none() && !claim.address.starts_with('z');
+        let amount_on_chain = transparent.then_some(paid);
+        if amount_on_chain.is_some_and(|paid| paid + AMOUNT_EPSILON < claim.amount_vrsc) {
+            return Err(rejected(format!("outputs pay {} of the claimed {}", paid, claim.amount_vrsc)));
+        }
+        Ok(PaymentVerification { claim, confirmations, amount_on_chain })
+    }
+}
+
+/// Native value of the transparent outputs of `tx` paying `address`
+fn paid_to(tx: &Transaction, address: &str) -> f64 {
+    tx.extra
+        .get("vout")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|output| {
+            output
+                .pointer("/scriptPubKey/addresses")
+                .and_then(Value::as_array)
+                .is_some_and(|addresses| addresses.iter().any(|a| a.as_str() == Some(address)))
+        })
+        .filter_map(|output| output.get("value").and_then(Value::as_f64))
+        .sum()
+}
+
+fn rejected(reason: impl Into<String>) -> ClientError {
+    ClientError::PaymentProof(reason.into())
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+    use warp::Filter;
+
+    fn token(payment: Value) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&json!({"sub": "pay_p1", "payment": payment})).unwrap());
+        format!("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.{}.signature", payload)
+    }
+
+    fn claim(amount_vrsc: f64, block_height: Option<u64>) -> Value {
+        json!({
+            "payment_id": "p1",
+            "tier_id": "basic",
+            "amount_vrsc": amount_vrsc,
+            "address": "RPayee",
+            "txids": ["tx1"],
+            "block_height": block_height,
+        })
+    }
+
+    /// Daemon stand-in knowing `tx1`, mined at height 100 and paying 1 to `RPayee`
+    async fn start_chain() -> String {
+        let rpc = warp::path::end().and(warp::post()).and(warp::body::json()).map(|body: Value| {
+            let result = json!({
+                "txid": body["params"][0],
+                "confirmations": 6,
+                "height": 100,
+                "vout": [{"value": 1.0, "scriptPubKey": {"addresses": ["RPayee"]}}],
+            });
+            warp::reply::json(&json!({"result": result, "error": null, "id": body["id"]}))
+        });
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        tokio::spawn(warp::serve(rpc).run(address));
+        while tokio::net::TcpStream::connect(address).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        format!("http://{}/", address)
+    }
+
+    #[test]
+    fn test_payment_claim_decoded_from_token() {
+        let claim = payment_claim(&format!("Bearer {}", token(claim(1.0, Some(100))))).unwrap();
+        assert_eq!(claim.txids, vec!["tx1".to_string()]);
+        assert_eq!(claim.block_height, Some(100));
+
+        assert!(matches!(payment_claim(&token(Value::Null)), Err(ClientError::PaymentProof(_))));
+        assert!(payment_claim("not-a-token").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_payment_token_against_chain() {
+        let client = VerusClient::builder(start_chain().await).build().unwrap();
+
+        let verified = client.verify_payment_token(&token(claim(1.0, Some(100))), 6).await.unwrap();
+        assert_eq!(verified.confirmations, 6);
+        assert_eq!(verified.amount_on_chain, Some(1.0));
+
+        // Wrong height, more than was paid, too few confirmations, never confirmed
+        for (payment, min_confirmations) in [
+            (claim(1.0, Some(99)), 1),
+            (claim(2.0, Some(100)), 1),
+            (claim(1.0, Some(100)), 10),
+            (claim(1.0, None), 1),
+        ] {
+            assert!(client.verify_payment_token(&token(payment), min_confirmations).await.is_err());
+        }
+    }
+}
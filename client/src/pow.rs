@@ -0,0 +1,108 @@
+//! Client-side proof-of-work solving
+//!
+//! Mirrors the token service check: the hex digest of `challenge + nonce`
+//! must have its first eight hex digits, read as a number, at or below
+//! `target_difficulty`.
+//!
+//! In the browser everything runs on one thread, so the client uses
+//! `solve_yielding`, which hashes in slices and yields between them to keep
+//! the page responsive.
+
+use std::ops::Range;
+
+use sha2::{Digest, Sha256};
+
+use super::error::{ClientError, ClientResult};
+use super::runtime;
+use super::types::{PowAlgorithm, PowChallenge, PowProof};
+
+/// Nonces tried between yields in `solve_yielding`
+const SLICE: u64 = 20_000;
+
+/// Search nonces `0..max_attempts` for a solution to `challenge`
+pub fn solve(challenge: &PowChallenge, max_attempts: u64) -> ClientResult<PowProof> {
+    let target = target(challenge)?;
+    search(challenge, target, 0..max_attempts).ok_or(ClientError::PowExhausted(max_attempts))
+}
+
+/// Like `solve`, but yields to the executor (or browser event loop) every few thousand nonces
+pub async fn solve_yielding(challenge: &PowChallenge, max_attempts: u64) -> ClientResult<PowProof> {
+    let target = target(challenge)?;
+    let mut start = 0;
+    while start < max_attempts {
+        let end = start.saturating_add(SLICE).min(max_attempts);
+        if let Some(proof) = search(challenge, target, start..end) {
+            return Ok(proof);
+        }
+        start = end;
+        runtime::yield_now().await;
+    }
+    Err(ClientError::PowExhausted(max_attempts))
+}
+
+fn target(challenge: &PowChallenge) -> ClientResult<u64> {
+    u64::from_str_radix(&challenge.target_difficulty, 16)
+        .map_err(|_| ClientError::Token(format!("invalid difficulty {:?}", challenge.target_difficulty)))
+}
+
+fn search(challenge: &PowChallenge, target: u64, nonces: Range<u64>) -> Option<PowProof> {
+    nonces.map(|nonce| nonce.to_string()).find_map(|nonce| {
+        let hash = digest(challenge.algorithm, &format!("{}{}", challenge.challenge, nonce));
+        meets_target(&hash, target).then(|| PowProof {
+            challenge_id: challenge.id.clone(),
+            nonce,
+            solution: hash,
+            difficulty: challenge.target_difficulty.clone(),
+            submitted_at: chrono::Utc::now(),
+            client_ip: String::new(),
+        })
+    })
+}
+
+fn digest(algorithm: PowAlgorithm, input: &str) -> String {
+    match algorithm {
+        PowAlgorithm::Sha256 => hex::encode(Sha256::digest(input.as_bytes())),
+        PowAlgorithm::Blake3 => hex::encode(blake3::hash(input.as_bytes()).as_bytes()),
+    }
+}
+
+fn meets_target(hash: &str, target: u64) -> bool {
+    hash.get(..8)
+        .and_then(|prefix| u64::from_str_radix(prefix, 16).ok())
+        .is_some_and(|value| value <= target)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    fn challenge(algorithm: PowAlgorithm, target: &str) -> PowChallenge {
+        PowChallenge {
+            id: "c1".to_string(),
+            challenge: "verus_rpc_c1_1700000000".to_string(),
+            target_difficulty: target.to_string(),
+            algorithm,
+            expires_at: chrono::Utc::now(),
+            token_duration: 3600,
+            rate_limit_multiplier: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_solutions_meet_the_target() {
+        for algorithm in [PowAlgorithm::Sha256, PowAlgorithm::Blake3] {
+            let challenge = challenge(algorithm, "00ffffff");
+            let proof = solve(&challenge, 100_000).unwrap();
+            assert_eq!(proof.solution, digest(algorithm, &format!("{}{}", challenge.challenge, proof.nonce)));
+            assert!(proof.solution.starts_with("00"));
+        }
+        assert!(matches!(solve(&challenge(PowAlgorithm::Sha256, "00000000"), 10), Err(ClientError::PowExhausted(10))));
+    }
+
+    #[tokio::test]
+    async fn test_yielding_solver_finds_the_same_nonce() {
+        let challenge = challenge(PowAlgorithm::Sha256, "000fffff");
+        let proof = solve_yielding(&challenge, 1_000_000).await.unwrap();
+        assert_eq!(proof.nonce, solve(&challenge, 1_000_000).unwrap().nonce);
+    }
+}
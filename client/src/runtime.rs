@@ -0,0 +1,24 @@
+//! Timers for native targets (tokio) and wasm32 (browser `setTimeout`)
+
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Let other tasks (or, in the browser, rendering and input) run
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn yield_now() {
+    tokio::task::yield_now().await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn yield_now() {
+    gloo_timers::future::TimeoutFuture::new(0).await;
+}
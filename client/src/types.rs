@@ -0,0 +1,215 @@
+//! Wire types for the proxy, token service and payments endpoints
+//!
+//! These mirror the server's types field for field so the client does not
+//! depend on the server modules. Daemon results carry only the fields most
+//! integrators need; everything else is kept in `extra`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Token returned by `POST /issue` and the payments flow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedToken {
+    pub token: String,
+    pub token_type: String,
+    /// Lifetime in seconds
+    pub expires_in: u64,
+    pub token_id: String,
+    #[serde(default)]
+    pub user_id: Option<String>,
+}
+
+/// Proof-of-work hash algorithm
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PowAlgorithm {
+    Sha256,
+    Blake3,
+}
+
+/// Challenge from `POST /pow/challenge`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowChallenge {
+    pub id: String,
+    pub challenge: String,
+    /// Hex upper bound for the first eight hex digits of the hash
+    pub target_difficulty: String,
+    pub algorithm: PowAlgorithm,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub token_duration: u64,
+    pub rate_limit_multiplier: f64,
+}
+
+/// Solution submitted with a proof-of-work token request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowProof {
+    pub challenge_id: String,
+    pub nonce: String,
+    pub solution: String,
+    pub difficulty: String,
+    pub submitted_at: chrono::DateTime<chrono::Utc>,
+    pub client_ip: String,
+}
+
+/// Body of `POST /issue`
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct IssueRequest {
+    pub user_id: String,
+    pub permissions: Vec<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub custom_expiration: Option<u64>,
+    pub mode: IssueMode,
+    pub pow_challenge: Option<PowChallenge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) enum IssueMode {
+    Anonymous,
+    ProofOfWork(PowProof),
+}
+
+/// Shielded address type for payment quotes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShieldedAddressType {
+    Orchard,
+    Sapling,
+}
+
+/// Body of `POST /payments/request`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PaymentQuoteRequest {
+    pub tier_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_type: Option<ShieldedAddressType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// Where the proxy returns late, rejected or excess payments, when refunds are enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund_address: Option<String>,
+    /// Receives upgraded tokens as the payment confirms, when the proxy accepts webhooks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+}
+
+/// Quote returned by `POST /payments/request`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentQuote {
+    pub payment_id: String,
+    pub tier_id: String,
+    pub amount_vrsc: f64,
+    /// Address to pay
+    pub address: String,
+    pub address_type: ShieldedAddressType,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Payment session state
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    Pending,
+    Submitted,
+    Verified,
+    Confirmed1,
+    Finalized,
+    Underpaid,
+    Failed,
+    Expired,
+}
+
+/// Response of `GET /payments/status/{payment_id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentState {
+    pub status: PaymentStatus,
+    pub confirmations: u32,
+    pub amount_vrsc: f64,
+    pub paid_amount_vrsc: f64,
+    pub txid: Option<String>,
+    pub provisional_token: Option<String>,
+    pub final_token: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `payment` claim of a token bought through the payments API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentClaim {
+    pub payment_id: String,
+    pub tier_id: String,
+    /// Amount received, in the native currency
+    pub amount_vrsc: f64,
+    /// i-address of the PBaaS currency paid in, for quotes not in the native currency
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Address the payment was made to
+    pub address: String,
+    pub txids: Vec<String>,
+    /// Height of the block holding the newest payment transaction; `None` while unconfirmed
+    pub block_height: Option<u64>,
+}
+
+/// Result of `getinfo`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Info {
+    pub version: u64,
+    pub blocks: u64,
+    pub connections: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Result of `getblock` with verbosity 1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    pub hash: String,
+    pub height: u64,
+    pub confirmations: i64,
+    pub time: i64,
+    #[serde(default)]
+    pub tx: Vec<String>,
+    #[serde(default)]
+    pub previousblockhash: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Result of `getrawtransaction` with verbose output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transaction {
+    pub txid: String,
+    #[serde(default)]
+    pub confirmations: Option<i64>,
+    #[serde(default)]
+    pub blockhash: Option<String>,
+    #[serde(default)]
+    pub height: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Result of `getaddressbalance` (satoshis)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressBalance {
+    pub balance: i64,
+    pub received: i64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// One entry of `getaddressutxos`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressUtxo {
+    pub address: String,
+    pub txid: String,
+    pub output_index: u32,
+    pub satoshis: i64,
+    pub height: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
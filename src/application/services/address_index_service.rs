@@ -0,0 +1,490 @@
+//! Embedded address index
+//!
+//! Tails blocks from the daemon and maintains an address → txids index so
+//! `/api/address/{addr}/txs` can page through history without relying on the
+//! daemon's `addressindex`. Indexed blocks are appended to a JSON lines log in
+//! the storage directory and replayed at startup.
+//!
+//! Spends are attributed to an address when the daemon reports `vin[].address`
+//! or when the spent output was created inside the indexed range; the index
+//! tracks addresses of unspent outputs it has seen for that purpose. Blocks are
+//! only indexed `confirmations` below the tip, so short reorgs never reach it.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::pagination::{ListQuery, ListSpec, Page};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, warn};
+
+const LOG_FILE: &str = "address_index.jsonl";
+
+/// One transaction touching an address
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AddressTx {
+    pub txid: String,
+    pub height: u64,
+}
+
+/// A page of an address's transactions
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressTxPage {
+    pub address: String,
+    /// Highest indexed block
+    pub indexed_height: Option<u64>,
+    #[serde(flatten)]
+    pub page: Page<AddressTx>,
+}
+
+/// Query parameters of `/api/address/{addr}/txs`; newest first by default
+pub const ADDRESS_TXS_LIST: ListSpec = ListSpec {
+    sort_fields: &["height"],
+    descending: true,
+    filters: &["min_height", "max_height"],
+};
+
+/// What a block contributed to the index; one line of the log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct IndexedBlock {
+    height: u64,
+    hash: String,
+    /// (txid, addresses touched)
+    txs: Vec<(String, Vec<String>)>,
+    /// Outputs created, as ("txid:n", address)
+    created: Vec<(String, String)>,
+    /// Outpoints spent ("txid:n")
+    spent: Vec<String>,
+}
+
+#[derive(Default)]
+struct IndexState {
+    tip: Option<(u64, String)>,
+    /// Transactions per address in chain order, so positions are stable cursors
+    by_address: HashMap<String, Vec<(u64, String)>>,
+    /// Address of each unspent output seen so far
+    outputs: HashMap<String, String>,
+}
+
+impl IndexState {
+    fn apply(&mut self, block: &IndexedBlock) {
+        for (txid, addresses) in &block.txs {
+            for address in addresses {
+                self.by_address.entry(address.clone()).or_default().push((block.height, txid.clone()));
+            }
+        }
+        for (outpoint, address) in &block.created {
+            self.outputs.insert(outpoint.clone(), address.clone());
+        }
+        for outpoint in &block.spent {
+            self.outputs.remove(outpoint);
+        }
+        self.tip = Some((block.height, block.hash.clone()));
+    }
+}
+
+/// Block tailer and address → txids index
+pub struct AddressIndexService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    state: RwLock<IndexState>,
+    /// Serializes polls so blocks are appended in order
+    poll_lock: Mutex<()>,
+}
+
+impl AddressIndexService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        Self { config, rpc, state: RwLock::new(IndexState::default()), poll_lock: Mutex::new(()) }
+    }
+
+    /// Highest indexed block
+    pub async fn indexed_height(&self) -> Option<u64> {
+        self.state.read().await.tip.as_ref().map(|(height, _)| *height)
+    }
+
+    /// Transactions of `address`, paged per `query`
+    ///
+    /// The cursor is the transaction's position in the address's history,
+    /// which only grows as blocks are indexed.
+    pub async fn address_txs(&self, address: &str, query: &ListQuery) -> AppResult<AddressTxPage> {
+        let max = self.config.indexer.max_page_size;
+        let limit = query.limit(max.min(100), max);
+        let min_height = query.filter::<u64>("min_height")?;
+        let max_height = query.filter::<u64>("max_height")?;
+        let state = self.state.read().await;
+        let entries = state.by_address.get(address).map(|v| v.as_slice()).unwrap_or(&[]);
+        let matching: Vec<(usize, &(u64, String))> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (height, _))| min_height.is_none_or(|min| *height >= min) && max_height.is_none_or(|max| *height <= max))
+            .collect();
+        let page = query.page(matching, limit, |(position, _)| *position as u64)?;
+        Ok(AddressTxPage {
+            address: address.to_string(),
+            indexed_height: state.tip.as_ref().map(|(height, _)| *height),
+            page: page.map(|(_, (height, txid))| AddressTx { txid: txid.clone(), height: *height }),
+        })
+    }
+
+    /// Replay the index log from the storage directory
+    pub async fn load(&self) -> AppResult<()> {
+        self.replay(None).await
+    }
+
+    /// Drop indexed blocks above `fork_height` after a reorg
+    ///
+    /// The orphaned blocks are removed from the log and re-indexed from the
+    /// new branch on the next poll.
+    pub async fn rollback(&self, fork_height: u64) -> AppResult<()> {
+        let _guard = self.poll_lock.lock().await;
+        if self.indexed_height().await.is_none_or(|height| height <= fork_height) {
+            return Ok(());
+        }
+        self.replay(Some(fork_height)).await?;
+        info!(fork_height, "Rolled back address index");
+        Ok(())
+    }
+
+    /// Rebuild the in-memory index from the log, keeping blocks up to `max_height`
+    async fn replay(&self, max_height: Option<u64>) -> AppResult<()> {
+        let path = self.log_path();
+        let contents = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(AppError::Internal(format!("failed to read {}: {}", path.display(), e))),
+        };
+        let mut state = self.state.write().await;
+        *state = IndexState::default();
+        let mut valid_len = 0;
+        for line in contents.split_inclusive('\n') {
+            if line.trim().is_empty() {
+                valid_len += line.len();
+                continue;
+            }
+            match serde_json::from_str::<IndexedBlock>(line) {
+                Ok(block) if max_height.is_some_and(|max| block.height > max) => break,
+                Ok(block) if state.tip.as_ref().is_none_or(|(h, _)| block.height == h + 1) => {
+                    state.apply(&block);
+                    valid_len += line.len();
+                }
+                Ok(block) => {
+                    warn!(height = block.height, "Index log is not contiguous; ignoring the remainder");
+                    break;
+                }
+                Err(e) => {
+                    warn!("Stopping index replay at malformed entry: {}", e);
+                    break;
+                }
+            }
+        }
+        if valid_len < contents.len() {
+            // Drop the unusable or orphaned tail so new blocks append after the last good one;
+            // the dropped blocks are fetched from the daemon again
+            let file = tokio::fs::OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .await
+                .map_err(|e| AppError::Internal(format!("failed to open {}: {}", path.display(), e)))?;
+            file.set_len(valid_len as u64)
+                .await
+                .map_err(|e| AppError::Internal(format!("failed to truncate {}: {}", path.display(), e)))?;
+        }
+        info!(height = ?state.tip.as_ref().map(|(h, _)| *h), addresses = state.by_address.len(), "Loaded address index");
+        Ok(())
+    }
+
+    /// Index confirmed blocks up to `max_blocks_per_poll`
+    ///
+    /// Returns the number of blocks indexed.
+    pub async fn poll(&self) -> AppResult<u64> {
+        let _guard = self.poll_lock.lock().await;
+        let settings = &self.config.indexer;
+        let tip = self
+            .call("getblockcount", json!([]))
+            .await?
+            .as_u64()
+            .ok_or_else(|| AppError::Rpc("getblockcount returned a non-numeric height".into()))?;
+        let target = tip.saturating_sub(settings.confirmations);
+        let (mut next, mut prev_hash) = match self.state.read().await.tip.clone() {
+            Some((height, hash)) => (height + 1, Some(hash)),
+            None => (settings.start_height.unwrap_or(target), None),
+        };
+
+        let mut indexed = 0;
+        while next <= target && indexed < settings.max_blocks_per_poll {
+            let hash = self.call("getblockhash", json!([next])).await?;
+            let hash = hash.as_str().ok_or_else(|| AppError::Rpc("getblockhash returned no hash".into()))?;
+            let block = self.call("getblock", json!([hash, 2])).await?;
+            if let Some(expected) = &prev_hash {
+                if block.get("previousblockhash").and_then(|h| h.as_str()) != Some(expected.as_str()) {
+                    return Err(AppError::Rpc(format!(
+                        "block {} does not extend the indexed chain; reindex from a lower height",
+                        next
+                    )));
+                }
+            }
+            let entry = {
+                let state = self.state.read().await;
+                extract_block(&block, next, &state.outputs)?
+            };
+            self.append(&entry).await?;
+            prev_hash = Some(entry.hash.clone());
+            self.state.write().await.apply(&entry);
+            next += 1;
+            indexed += 1;
+        }
+        Ok(indexed)
+    }
+
+    /// Load the index, then tail new blocks in the background
+    pub fn start_indexer(self: Arc<Self>) {
+        let interval = std::time::Duration::from_secs(self.config.indexer.poll_interval_seconds.max(1));
+        tokio::spawn(async move {
+            if let Err(e) = self.load().await {
+                warn!("Failed to load address index: {}", e);
+            }
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.poll().await {
+                    Ok(0) => {}
+                    Ok(blocks) => {
+                        let height = self.indexed_height().await;
+                        debug!(blocks, height = ?height, "Indexed blocks");
+                    }
+                    Err(e) => warn!("Address indexing failed: {}", e),
+                }
+            }
+        });
+    }
+
+    async fn append(&self, block: &IndexedBlock) -> AppResult<()> {
+        let dir = PathBuf::from(&self.config.indexer.storage_path);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| AppError::Internal(format!("failed to create {}: {}", dir.display(), e)))?;
+        let path = self.log_path();
+        let mut line = serde_json::to_string(block)?;
+        line.push('\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(|e| AppError::Internal(format!("failed to open {}: {}", path.display(), e)))?;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| AppError::Internal(format!("failed to append to {}: {}", path.display(), e)))
+    }
+
+    fn log_path(&self) -> PathBuf {
+        PathBuf::from(&self.config.indexer.storage_path).join(LOG_FILE)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("indexer_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("address-indexer".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+/// Index entry for a `getblock <hash> 2` result
+fn extract_block(block: &Value, height: u64, outputs: &HashMap<String, String>) -> AppResult<IndexedBlock> {
+    let hash = block
+        .get("hash")
+        .and_then(|h| h.as_str())
+        .ok_or_else(|| AppError::Rpc(format!("block {} has no hash", height)))?;
+    let txs = block
+        .get("tx")
+        .and_then(|t| t.as_array())
+        .ok_or_else(|| AppError::Rpc(format!("block {} has no decoded transactions", height)))?;
+
+    let mut entry = IndexedBlock { height, hash: hash.to_string(), txs: Vec::new(), created: Vec::new(), spent: Vec::new() };
+    // Outputs created earlier in this block can be spent later in it
+    let mut created_here: HashMap<String, String> = HashMap::new();
+    for tx in txs {
+        let Some(txid) = tx.get("txid").and_then(|t| t.as_str()) else { continue };
+        let mut addresses: Vec<String> = Vec::new();
+
+        for input in tx.get("vin").and_then(|v| v.as_array()).into_iter().flatten() {
+            let (Some(prev), Some(n)) = (input.get("txid").and_then(|t| t.as_str()), input.get("vout").and_then(|n| n.as_u64())) else {
+                continue; // coinbase
+            };
+            let outpoint = format!("{}:{}", prev, n);
+            let address = input
+                .get("address")
+                .and_then(|a| a.as_str())
+                .map(|a| a.to_string())
+                .or_else(|| created_here.get(&outpoint).or_else(|| outputs.get(&outpoint)).cloned());
+            if let Some(address) = address {
+                push_unique(&mut addresses, address);
+            }
+            entry.spent.push(outpoint);
+        }
+
+        for output in tx.get("vout").and_then(|v| v.as_array()).into_iter().flatten() {
+            let n = output.get("n").and_then(|n| n.as_u64()).unwrap_or(0);
+            let output_addresses = output
+                .get("scriptPubKey")
+                .and_then(|s| s.get("addresses"))
+                .and_then(|a| a.as_array())
+                .map(|a| a.iter().filter_map(|a| a.as_str()).map(|a| a.to_string()).collect::<Vec<_>>())
+                .unwrap_or_default();
+            if let Some(first) = output_addresses.first() {
+                let outpoint = format!("{}:{}", txid, n);
+                created_here.insert(outpoint.clone(), first.clone());
+                entry.created.push((outpoint, first.clone()));
+            }
+            for address in output_addresses {
+                push_unique(&mut addresses, address);
+            }
+        }
+
+        if !addresses.is_empty() {
+            entry.txs.push((txid.to_string(), addresses));
+        }
+    }
+    // Outputs created and spent within the block never need to be remembered
+    let spent_here: std::collections::HashSet<&String> = entry.spent.iter().collect();
+    entry.created.retain(|(outpoint, _)| !spent_here.contains(outpoint));
+    Ok(entry)
+}
+
+fn push_unique(addresses: &mut Vec<String>, address: String) {
+    if !addresses.contains(&address) {
+        addresses.push(address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(storage_path: String) -> AddressIndexService {
+        let mut config = AppConfig::default();
+        config.indexer.storage_path = storage_path;
+        config.indexer.max_page_size = 2;
+        let config = Arc::new(config);
+        AddressIndexService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)))
+    }
+
+    fn block(height: u64) -> Value {
+        json!({
+            "hash": format!("hash{}", height),
+            "tx": [
+                { "txid": "cb", "vin": [{ "coinbase": "00" }], "vout": [
+                    { "n": 0, "scriptPubKey": { "addresses": ["RMiner"] } }
+                ] },
+                { "txid": "pay", "vin": [{ "txid": "cb", "vout": 0 }], "vout": [
+                    { "n": 0, "scriptPubKey": { "addresses": ["RAlice"] } },
+                    { "n": 1, "scriptPubKey": { "type": "nulldata" } }
+                ] },
+                { "txid": "spend", "vin": [{ "txid": "old", "vout": 1 }], "vout": [] }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_extract_block_attributes_inputs_and_outputs() {
+        let outputs = HashMap::from([("old:1".to_string(), "RBob".to_string())]);
+        let entry = extract_block(&block(7), 7, &outputs).unwrap();
+
+        assert_eq!(entry.hash, "hash7");
+        assert_eq!(entry.txs, vec![
+            ("cb".to_string(), vec!["RMiner".to_string()]),
+            ("pay".to_string(), vec!["RMiner".to_string(), "RAlice".to_string()]),
+            ("spend".to_string(), vec!["RBob".to_string()]),
+        ]);
+        // cb:0 was spent in the same block
+        assert_eq!(entry.created, vec![("pay:0".to_string(), "RAlice".to_string())]);
+        assert_eq!(entry.spent, vec!["cb:0".to_string(), "old:1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_address_txs_pages_newest_first() {
+        let service = service(std::env::temp_dir().to_string_lossy().into_owned());
+        {
+            let mut state = service.state.write().await;
+            for height in 1..=3 {
+                let entry = IndexedBlock {
+                    height,
+                    hash: format!("h{}", height),
+                    txs: vec![(format!("tx{}", height), vec!["RAlice".to_string()])],
+                    created: Vec::new(),
+                    spent: Vec::new(),
+                };
+                state.apply(&entry);
+            }
+        }
+
+        let newest = ListQuery::parse(&HashMap::new(), &ADDRESS_TXS_LIST).unwrap();
+        let first = service.address_txs("RAlice", &newest).await.unwrap();
+        assert_eq!(first.page.total, 3);
+        assert_eq!(first.indexed_height, Some(3));
+        assert_eq!(first.page.items.iter().map(|t| t.txid.as_str()).collect::<Vec<_>>(), vec!["tx3", "tx2"]);
+
+        let cursor = HashMap::from([("cursor".to_string(), first.page.next_cursor.clone().unwrap())]);
+        let second = service.address_txs("RAlice", &ListQuery::parse(&cursor, &ADDRESS_TXS_LIST).unwrap()).await.unwrap();
+        assert_eq!(second.page.items, vec![AddressTx { txid: "tx1".to_string(), height: 1 }]);
+        assert_eq!(second.page.next_cursor, None);
+
+        let oldest = HashMap::from([("sort".to_string(), "height".to_string()), ("min_height".to_string(), "2".to_string())]);
+        let oldest = service.address_txs("RAlice", &ListQuery::parse(&oldest, &ADDRESS_TXS_LIST).unwrap()).await.unwrap();
+        assert_eq!(oldest.page.total, 2);
+        assert_eq!(oldest.page.items.iter().map(|t| t.height).collect::<Vec<_>>(), vec![2, 3]);
+
+        let stale = HashMap::from([("cursor".to_string(), "9".to_string())]);
+        assert!(service.address_txs("RAlice", &ListQuery::parse(&stale, &ADDRESS_TXS_LIST).unwrap()).await.is_err());
+        assert_eq!(service.address_txs("RNobody", &newest).await.unwrap().page.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_index_log_replays_contiguous_blocks() {
+        let dir = std::env::temp_dir().join(format!("address-index-test-{}", uuid::Uuid::new_v4()));
+        let writer = service(dir.to_string_lossy().into_owned());
+        for height in [10, 11, 13] {
+            let entry = extract_block(&block(height), height, &HashMap::new()).unwrap();
+            writer.append(&entry).await.unwrap();
+        }
+
+        let reader = service(dir.to_string_lossy().into_owned());
+        reader.load().await.unwrap();
+        assert_eq!(reader.indexed_height().await, Some(11));
+        assert_eq!(reader.address_txs("RAlice", &ListQuery::parse(&HashMap::new(), &ADDRESS_TXS_LIST).unwrap()).await.unwrap().page.total, 2);
+        assert!(reader.state.read().await.outputs.contains_key("pay:0"));
+
+        // The non-contiguous tail is dropped so the next append continues the chain
+        let log = tokio::fs::read_to_string(dir.join(LOG_FILE)).await.unwrap();
+        assert_eq!(log.lines().count(), 2);
+
+        reader.rollback(10).await.unwrap();
+        assert_eq!(reader.indexed_height().await, Some(10));
+        assert_eq!(reader.address_txs("RAlice", &ListQuery::parse(&HashMap::new(), &ADDRESS_TXS_LIST).unwrap()).await.unwrap().page.total, 1);
+        let log = tokio::fs::read_to_string(dir.join(LOG_FILE)).await.unwrap();
+        assert_eq!(log.lines().count(), 1);
+
+        tokio::fs::remove_dir_all(&dir).await.ok();
+    }
+}
@@ -0,0 +1,156 @@
+//! Pre-generated shielded payment addresses
+//!
+//! Calling `z_getnewaddress` while a client waits for a quote adds a wallet
+//! round trip and fails outright when the daemon is busy. With
+//! `[payments.address_pool]` enabled, a background task keeps
+//! `size_per_type` unused addresses per allowed type in the payments store,
+//! and quotes take one from there. An empty pool falls back to generating the
+//! address inline, so the pool only ever removes latency.
+
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use crate::domain::payments::ShieldedAddressType;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::{ExternalRpcAdapter, PaymentsStore};
+use crate::shared::error::{AppError, AppResult};
+use chrono::Utc;
+use serde_json::json;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+pub struct AddressPoolService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    store: Arc<PaymentsStore>,
+}
+
+impl AddressPoolService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>, store: Arc<PaymentsStore>) -> Self {
+        Self { config, rpc, store }
+    }
+
+    /// An unused address for a new session: pooled if available, otherwise fresh
+    pub async fn acquire(&self, address_type: &ShieldedAddressType, client_info: &ClientInfo) -> AppResult<String> {
+        if self.config.payments.address_pool.enabled {
+            match self.store.pool_pop(address_type.as_str()).await {
+                Ok(Some(address)) => return Ok(address),
+                Ok(None) => debug!(address_type = address_type.as_str(), "Address pool empty; generating inline"),
+                Err(e) => warn!("Address pool unavailable: {}", e),
+            }
+        }
+        self.generate(address_type, client_info).await
+    }
+
+    /// Top up every allowed address type to `size_per_type`; returns how many were added
+    pub async fn refill(&self) -> AppResult<usize> {
+        let target = self.config.payments.address_pool.size_per_type as usize;
+        let client_info = Self::internal_client();
+        let mut added = 0;
+        for address_type in self.address_types() {
+            let available = self.store.pool_len(address_type.as_str()).await?;
+            for _ in available..target {
+                let address = self.generate(&address_type, &client_info).await?;
+                self.store.pool_push(address_type.as_str(), &address).await?;
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Spawn the background refill loop
+    pub fn start_refill(self: Arc<Self>) {
+        let interval = std::time::Duration::from_secs(self.config.payments.address_pool.refill_interval_seconds.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.refill().await {
+                    Ok(0) => {}
+                    Ok(added) => info!(added, "Refilled payment address pool"),
+                    Err(e) => warn!("Payment address pool refill failed: {}", e),
+                }
+            }
+        });
+    }
+
+    fn address_types(&self) -> Vec<ShieldedAddressType> {
+        let mut types: Vec<ShieldedAddressType> = Vec::new();
+        for address_type in self.config.payments.address_types.iter().filter_map(|t| t.parse().ok()) {
+            if !types.contains(&address_type) {
+                types.push(address_type);
+            }
+        }
+        types
+    }
+
+    /// Ask the daemon for a new shielded address (z_getnewaddress "orchard" | "sapling")
+    async fn generate(&self, address_type: &ShieldedAddressType, client_info: &ClientInfo) -> AppResult<String> {
+        let request = RpcRequest::new(
+            "z_getnewaddress".to_string(),
+            Some(json!([address_type.as_str()])),
+            Some(json!(Uuid::new_v4().to_string())),
+            client_info.clone(),
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .ok_or_else(|| AppError::Rpc("invalid z_getnewaddress result".into()))
+    }
+
+    fn internal_client() -> ClientInfo {
+        ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: Some("address-pool".to_string()),
+            auth_token: None,
+            timestamp: Utc::now(),
+            request_id: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_service(enabled: bool) -> (AddressPoolService, Arc<PaymentsStore>) {
+        let mut config = AppConfig::default();
+        config.verus.rpc_url = "http://127.0.0.1:1".to_string();
+        config.verus.max_retries = 0;
+        config.payments.address_pool.enabled = enabled;
+        config.payments.address_types = vec!["orchard".to_string(), "sapling".to_string(), "orchard".to_string()];
+        let config = Arc::new(config);
+        let rpc = Arc::new(ExternalRpcAdapter::new(config.clone()));
+        let store = Arc::new(PaymentsStore::new(None));
+        (AddressPoolService::new(config, rpc, store.clone()), store)
+    }
+
+    #[tokio::test]
+    async fn test_acquire_prefers_pooled_address() {
+        let (service, store) = create_test_service(true);
+        store.pool_push("orchard", "u1pooled").await.unwrap();
+
+        let address = service.acquire(&ShieldedAddressType::Orchard, &AddressPoolService::internal_client()).await;
+        assert_eq!(address.unwrap(), "u1pooled");
+        assert_eq!(store.pool_len("orchard").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_pool_is_not_used() {
+        let (service, store) = create_test_service(false);
+        store.pool_push("orchard", "u1pooled").await.unwrap();
+
+        // Falls through to the (unreachable) daemon instead of the pool
+        let address = service.acquire(&ShieldedAddressType::Orchard, &AddressPoolService::internal_client()).await;
+        assert!(address.is_err());
+        assert_eq!(store.pool_len("orchard").await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_address_types_deduplicated() {
+        let (service, _) = create_test_service(true);
+        assert_eq!(service.address_types(), vec![ShieldedAddressType::Orchard, ShieldedAddressType::Sapling]);
+    }
+}
@@ -0,0 +1,348 @@
+//! Daemon version and capability detection
+//!
+//! At startup (`[capabilities] enabled`) the daemon is asked for its version
+//! (`getinfo`, `getnetworkinfo`) and each optional index is probed with a
+//! call that fails only when the index is off:
+//!
+//! - `-timestampindex`: `getblockhashes` over an empty time range;
+//! - `-addressindex`: `getaddressbalance` for an address from a recent block;
+//! - `-spentindex`: `getspentinfo` for an output spent in a recent block.
+//!
+//! An index is reported as `null` when its probe was inconclusive (no
+//! suitable recent block, daemon error unrelated to the index). With
+//! `disable_unsupported`, registry methods needing an index found to be off
+//! are refused with "method not found" instead of failing upstream. The
+//! result is kept for `/capabilities` and `/health`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::domain::validation::{DaemonIndex, MethodRegistry};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+/// Detected once at startup
+static CAPABILITIES: OnceLock<DaemonCapabilities> = OnceLock::new();
+
+/// What the daemon reported and supports
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DaemonCapabilities {
+    /// False until detection has succeeded
+    pub detected: bool,
+    pub detected_at: Option<DateTime<Utc>>,
+    /// Numeric client version (`getinfo.version`)
+    pub version: Option<u64>,
+    /// Verus release (`getinfo.VRSCversion`)
+    pub vrsc_version: Option<String>,
+    /// User agent (`getnetworkinfo.subversion`)
+    pub subversion: Option<String>,
+    pub protocol_version: Option<u64>,
+    /// `false` when the daemon is older than `min_daemon_version`
+    pub meets_min_version: Option<bool>,
+    /// `None` when the probe was inconclusive
+    pub indexes: BTreeMap<DaemonIndex, Option<bool>>,
+    /// Methods refused because their index is off
+    pub disabled_methods: Vec<String>,
+}
+
+impl DaemonCapabilities {
+    /// Capabilities detected at startup, if detection ran and succeeded
+    pub fn current() -> Option<&'static DaemonCapabilities> {
+        CAPABILITIES.get()
+    }
+
+    /// Whether the daemon is known to run with `index`
+    pub fn has_index(&self, index: DaemonIndex) -> Option<bool> {
+        self.indexes.get(&index).copied().flatten()
+    }
+}
+
+/// Output and address used to probe the address and spent indexes
+#[derive(Debug, Default, PartialEq)]
+struct ProbeSample {
+    address: Option<String>,
+    spent_output: Option<(String, u64)>,
+}
+
+pub struct CapabilityService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+}
+
+impl CapabilityService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        Self { config, rpc }
+    }
+
+    /// Detect capabilities, disable unsupported methods and keep the result
+    ///
+    /// Failures are logged and leave every method enabled.
+    pub async fn detect_and_install(&self) -> Option<&'static DaemonCapabilities> {
+        let timeout = Duration::from_secs(self.config.capabilities.timeout_seconds);
+        let capabilities = match tokio::time::timeout(timeout, self.detect()).await {
+            Ok(Ok(capabilities)) => capabilities,
+            Ok(Err(e)) => {
+                warn!("daemon capability detection failed: {} - all methods stay enabled", e);
+                return None;
+            }
+            Err(_) => {
+                warn!("daemon capability detection timed out - all methods stay enabled");
+                return None;
+            }
+        };
+
+        if let Some(false) = capabilities.meets_min_version {
+            warn!(
+                version = capabilities.vrsc_version.as_deref().unwrap_or("unknown"),
+                minimum = self.config.capabilities.min_daemon_version.as_deref().unwrap_or(""),
+                "Daemon is older than capabilities.min_daemon_version"
+            );
+        }
+        for (index, enabled) in &capabilities.indexes {
+            match enabled {
+                Some(false) => warn!(index = index.as_str(), "Daemon runs without optional index"),
+                None => info!(index = index.as_str(), "Could not determine whether the daemon runs with index"),
+                Some(true) => {}
+            }
+        }
+        if !capabilities.disabled_methods.is_empty() {
+            let reasons: HashMap<String, String> = MethodRegistry::new()
+                .list_methods()
+                .filter(|method| capabilities.disabled_methods.contains(&method.name))
+                .filter_map(|method| {
+                    let index = method.required_index()?;
+                    Some((method.name.clone(), format!("daemon runs without -{}", index.as_str())))
+                })
+                .collect();
+            MethodRegistry::install_unavailable(reasons);
+            warn!(methods = ?capabilities.disabled_methods, "Methods disabled for missing daemon indexes");
+        }
+        info!(
+            version = capabilities.vrsc_version.as_deref().unwrap_or("unknown"),
+            subversion = capabilities.subversion.as_deref().unwrap_or("unknown"),
+            "Daemon capabilities detected"
+        );
+
+        if CAPABILITIES.set(capabilities).is_err() {
+            warn!("Daemon capabilities were already detected; keeping the first result");
+        }
+        CAPABILITIES.get()
+    }
+
+    /// Query version information and probe the optional indexes
+    pub async fn detect(&self) -> AppResult<DaemonCapabilities> {
+        let info = self.call("getinfo", json!([])).await?;
+        // Older daemons lack getnetworkinfo; getinfo carries the protocol version too
+        let network = self.call("getnetworkinfo", json!([])).await.unwrap_or(Value::Null);
+
+        let vrsc_version = info.get("VRSCversion").and_then(Value::as_str).map(str::to_string);
+        let meets_min_version = self
+            .config
+            .capabilities
+            .min_daemon_version
+            .as_deref()
+            .and_then(|minimum| Some(parse_version(vrsc_version.as_deref()?)? >= parse_version(minimum)?));
+
+        let sample = self.probe_sample().await;
+        let mut indexes = BTreeMap::new();
+        indexes.insert(
+            DaemonIndex::TimestampIndex,
+            probe_outcome(self.call("getblockhashes", json!([1, 0])).await),
+        );
+        let address_probe = match &sample.address {
+            Some(address) => probe_outcome(self.call("getaddressbalance", json!([{ "addresses": [address] }])).await),
+            None => None,
+        };
+        indexes.insert(DaemonIndex::AddressIndex, address_probe);
+        let spent_probe = match &sample.spent_output {
+            Some((txid, index)) => probe_outcome(self.call("getspentinfo", json!([{ "txid": txid, "index": index }])).await),
+            None => None,
+        };
+        indexes.insert(DaemonIndex::SpentIndex, spent_probe);
+
+        let disabled_methods = if self.config.capabilities.disable_unsupported {
+            unsupported_methods(&MethodRegistry::new(), &indexes)
+        } else {
+            Vec::new()
+        };
+
+        Ok(DaemonCapabilities {
+            detected: true,
+            detected_at: Some(Utc::now()),
+            version: info.get("version").and_then(Value::as_u64),
+            vrsc_version,
+            subversion: network.get("subversion").and_then(Value::as_str).map(str::to_string),
+            protocol_version: network
+                .get("protocolversion")
+                .or_else(|| info.get("protocolversion"))
+                .and_then(Value::as_u64),
+            meets_min_version,
+            indexes,
+            disabled_methods,
+        })
+    }
+
+    /// Walk back from the tip until an address and a spent output are found
+    async fn probe_sample(&self) -> ProbeSample {
+        let mut sample = ProbeSample::default();
+        let Ok(tip) = self.call("getblockcount", json!([])).await else {
+            return sample;
+        };
+        let tip = tip.as_u64().unwrap_or(0);
+        let lowest = tip.saturating_sub(self.config.capabilities.probe_blocks.saturating_sub(1));
+        for height in (lowest..=tip).rev() {
+            let Ok(block) = self.call("getblock", json!([height.to_string(), 2])).await else {
+                break;
+            };
+            sample_block(&block, &mut sample);
+            if sample.address.is_some() && sample.spent_output.is_some() {
+                break;
+            }
+        }
+        sample
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("capabilities_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("capabilities".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+/// Fill in whatever `sample` still lacks from a verbosity-2 block
+fn sample_block(block: &Value, sample: &mut ProbeSample) {
+    let Some(transactions) = block.get("tx").and_then(Value::as_array) else {
+        return;
+    };
+    for tx in transactions {
+        if sample.address.is_none() {
+            sample.address = tx
+                .get("vout")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|output| output.pointer("/scriptPubKey/addresses/0").and_then(Value::as_str))
+                .next()
+                .map(str::to_string);
+        }
+        if sample.spent_output.is_none() {
+            sample.spent_output = tx
+                .get("vin")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .find_map(|input| Some((input.get("txid")?.as_str()?.to_string(), input.get("vout")?.as_u64()?)));
+        }
+    }
+}
+
+/// Index state implied by a probe call's outcome
+///
+/// Index lookups fail with "... not enabled" or "No information available" /
+/// "Unable to get spent info" when the index is off; other errors say nothing
+/// about the index.
+fn probe_outcome(result: AppResult<Value>) -> Option<bool> {
+    match result {
+        Ok(_) => Some(true),
+        Err(AppError::Rpc(message)) => {
+            let message = message.to_lowercase();
+            let index_off = message.contains("not enabled")
+                || message.contains("no information available")
+                || message.contains("unable to get spent info");
+            index_off.then_some(false)
+        }
+        Err(_) => None,
+    }
+}
+
+/// Registry methods needing an index found to be off, sorted
+fn unsupported_methods(registry: &MethodRegistry, indexes: &BTreeMap<DaemonIndex, Option<bool>>) -> Vec<String> {
+    let mut methods: Vec<String> = registry
+        .list_methods()
+        .filter(|method| {
+            method
+                .required_index()
+                .is_some_and(|index| indexes.get(&index).copied().flatten() == Some(false))
+        })
+        .map(|method| method.name.clone())
+        .collect();
+    methods.sort();
+    methods
+}
+
+/// Numeric components of a dotted version ("1.2.5-3" -> [1, 2, 5])
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let core = version.trim().trim_start_matches('v').split(['-', ' ']).next()?;
+    core.split('.').map(|part| part.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_outcome() {
+        assert_eq!(probe_outcome(Ok(json!([]))), Some(true));
+        let off = AppError::Rpc(r#"RPC error: {"code":-5,"message":"No information available for address"}"#.to_string());
+        assert_eq!(probe_outcome(Err(off)), Some(false));
+        assert_eq!(probe_outcome(Err(AppError::Rpc("RPC error: Timestamp index not enabled".to_string()))), Some(false));
+        assert_eq!(probe_outcome(Err(AppError::Rpc("Request failed: connection refused".to_string()))), None);
+    }
+
+    #[test]
+    fn test_sample_block_finds_address_and_spent_output() {
+        let block = json!({
+            "tx": [
+                { "vin": [{ "coinbase": "03" }], "vout": [{ "scriptPubKey": { "addresses": ["RAddress"] } }] },
+                { "vin": [{ "txid": "ab", "vout": 1 }], "vout": [] },
+            ]
+        });
+        let mut sample = ProbeSample::default();
+        sample_block(&block, &mut sample);
+        assert_eq!(sample.address.as_deref(), Some("RAddress"));
+        assert_eq!(sample.spent_output, Some(("ab".to_string(), 1)));
+    }
+
+    #[test]
+    fn test_unsupported_methods_follow_indexes() {
+        let registry = MethodRegistry::new();
+        let mut indexes = BTreeMap::new();
+        indexes.insert(DaemonIndex::AddressIndex, Some(false));
+        indexes.insert(DaemonIndex::SpentIndex, None);
+        indexes.insert(DaemonIndex::TimestampIndex, Some(true));
+        let methods = unsupported_methods(&registry, &indexes);
+        assert!(methods.contains(&"getaddressbalance".to_string()));
+        assert!(!methods.contains(&"getspentinfo".to_string()));
+        assert!(!methods.contains(&"getblockhashes".to_string()));
+        assert!(!methods.contains(&"getblockcount".to_string()));
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.2.5-3"), Some(vec![1, 2, 5]));
+        assert_eq!(parse_version("v1.2"), Some(vec![1, 2]));
+        assert!(parse_version("1.2.10").unwrap() > parse_version("1.2.9").unwrap());
+        assert_eq!(parse_version("unknown"), None);
+    }
+}
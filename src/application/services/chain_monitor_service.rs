@@ -0,0 +1,340 @@
+//! Chain reorganization monitor
+//!
+//! Tracks the hashes of the most recent blocks and compares them against the
+//! daemon's active chain on every poll. When a tracked block is no longer on
+//! the active chain, a `reorg` event is published on the [`ChainEventBus`] and
+//! POSTed to the configured webhooks; in-process subscribers (cache, indexer,
+//! payments, WebSocket clients) react to it from there. Other services publish
+//! their own events on the same bus.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, Mutex};
+use tracing::{debug, warn};
+
+/// A block on the tracked chain
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BlockRef {
+    pub height: u64,
+    pub hash: String,
+}
+
+/// The active chain switched branches
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReorgEvent {
+    /// Blocks orphaned from the previous tip
+    pub depth: u64,
+    /// Highest block shared by the old and new branch
+    pub fork_height: u64,
+    pub old_tip: BlockRef,
+    pub new_tip: BlockRef,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Events published on the chain event bus
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChainEvent {
+    Reorg(ReorgEvent),
+    /// A tracked transaction reached its target or was dropped
+    TxStatus(crate::application::services::tx_tracking_service::TxStatusEvent),
+}
+
+/// In-process broadcast channel for chain events
+pub struct ChainEventBus {
+    sender: broadcast::Sender<ChainEvent>,
+    /// Open WebSocket subscriptions
+    streams: AtomicUsize,
+}
+
+impl ChainEventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender, streams: AtomicUsize::new(0) }
+    }
+
+    /// Publish an event; returns the number of subscribers it reached
+    pub fn publish(&self, event: ChainEvent) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ChainEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Reserve a WebSocket stream slot; `false` when `max` are already open
+    pub fn try_open_stream(&self, max: usize) -> bool {
+        if self.streams.fetch_add(1, Ordering::SeqCst) >= max {
+            self.streams.fetch_sub(1, Ordering::SeqCst);
+            return false;
+        }
+        true
+    }
+
+    pub fn close_stream(&self) {
+        self.streams.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Hashes of the most recent blocks, oldest first and contiguous
+pub(crate) struct ChainTracker {
+    blocks: VecDeque<BlockRef>,
+    depth: u64,
+}
+
+impl ChainTracker {
+    pub(crate) fn new(depth: u64) -> Self {
+        Self { blocks: VecDeque::new(), depth: depth.max(1) }
+    }
+
+    /// Bring the tracked chain up to `tip`, reporting a reorg if tracked blocks were orphaned
+    ///
+    /// `hash_at` returns the active chain's block hash at a height.
+    pub(crate) async fn reconcile<F, Fut>(&mut self, tip: u64, mut hash_at: F) -> AppResult<Option<ReorgEvent>>
+    where
+        F: FnMut(u64) -> Fut,
+        Fut: Future<Output = AppResult<String>>,
+    {
+        let Some(old_tip) = self.blocks.back().cloned() else {
+            self.extend(tip, &mut hash_at).await?;
+            return Ok(None);
+        };
+        if old_tip.height == tip && hash_at(tip).await? == old_tip.hash {
+            return Ok(None);
+        }
+
+        // Highest tracked block that is still on the active chain
+        let mut fork = None;
+        for block in self.blocks.iter().rev().filter(|b| b.height <= tip) {
+            if hash_at(block.height).await? == block.hash {
+                fork = Some(block.height);
+                break;
+            }
+        }
+        let oldest = self.blocks.front().map(|b| b.height).unwrap_or(0);
+        // Deeper than the tracked window: everything tracked was orphaned
+        let fork_height = fork.unwrap_or_else(|| oldest.saturating_sub(1));
+        self.blocks.retain(|b| fork.is_some_and(|f| b.height <= f));
+        self.extend(tip, &mut hash_at).await?;
+
+        if fork == Some(old_tip.height) {
+            return Ok(None); // the chain only grew
+        }
+        let new_tip = self.blocks.back().cloned().unwrap_or(BlockRef { height: tip, hash: String::new() });
+        Ok(Some(ReorgEvent {
+            depth: old_tip.height - fork_height,
+            fork_height,
+            old_tip,
+            new_tip,
+            detected_at: Utc::now(),
+        }))
+    }
+
+    async fn extend<F, Fut>(&mut self, tip: u64, hash_at: &mut F) -> AppResult<()>
+    where
+        F: FnMut(u64) -> Fut,
+        Fut: Future<Output = AppResult<String>>,
+    {
+        let window_start = tip.saturating_sub(self.depth - 1);
+        let next = self.blocks.back().map(|b| b.height + 1).unwrap_or(window_start);
+        if next < window_start {
+            // Fell behind by more than the window; the tracked blocks are no longer contiguous
+            self.blocks.clear();
+        }
+        for height in next.max(window_start)..=tip {
+            let hash = hash_at(height).await?;
+            self.blocks.push_back(BlockRef { height, hash });
+        }
+        while self.blocks.len() as u64 > self.depth {
+            self.blocks.pop_front();
+        }
+        Ok(())
+    }
+}
+
+/// Polls the daemon for reorgs and publishes them
+pub struct ChainMonitorService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    bus: Arc<ChainEventBus>,
+    tracker: Mutex<ChainTracker>,
+    http: reqwest::Client,
+}
+
+impl ChainMonitorService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>, bus: Arc<ChainEventBus>) -> Self {
+        let tracker = Mutex::new(ChainTracker::new(config.chain_events.track_depth));
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.chain_events.webhook_timeout_seconds))
+            .build()
+            .unwrap_or_default();
+        Self { config, rpc, bus, tracker, http }
+    }
+
+    /// Compare the tracked chain with the daemon, publishing a reorg if one happened
+    pub async fn poll(&self) -> AppResult<Option<ReorgEvent>> {
+        let tip = self
+            .call("getblockcount", json!([]))
+            .await?
+            .as_u64()
+            .ok_or_else(|| AppError::Rpc("getblockcount returned a non-numeric height".into()))?;
+        let event = self
+            .tracker
+            .lock()
+            .await
+            .reconcile(tip, |height| self.block_hash(height))
+            .await?;
+        if let Some(event) = &event {
+            warn!(
+                depth = event.depth,
+                fork_height = event.fork_height,
+                old_tip = %event.old_tip.hash,
+                new_tip = %event.new_tip.hash,
+                "Chain reorganization detected"
+            );
+            let reached = self.bus.publish(ChainEvent::Reorg(event.clone()));
+            debug!(subscribers = reached, "Published reorg event");
+        }
+        Ok(event)
+    }
+
+    /// Poll for reorgs in the background, forwarding them to the webhooks
+    pub fn start_monitor(self: Arc<Self>) {
+        let interval = std::time::Duration::from_secs(self.config.chain_events.poll_interval_seconds.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.poll().await {
+                    Ok(Some(event)) => {
+                        let monitor = self.clone();
+                        tokio::spawn(async move { monitor.deliver_webhooks(&ChainEvent::Reorg(event)).await });
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Reorg monitor poll failed: {}", e),
+                }
+            }
+        });
+    }
+
+    /// POST an event to every configured webhook; failures are logged, not retried
+    pub async fn deliver_webhooks(&self, event: &ChainEvent) {
+        for url in &self.config.chain_events.webhook_urls {
+            match self.http.post(url).json(event).send().await {
+                Ok(response) if response.status().is_success() => debug!(url = %url, "Delivered chain event webhook"),
+                Ok(response) => warn!(url = %url, status = %response.status(), "Chain event webhook rejected"),
+                Err(e) => warn!(url = %url, "Chain event webhook failed: {}", e),
+            }
+        }
+    }
+
+    async fn block_hash(&self, height: u64) -> AppResult<String> {
+        self.call("getblockhash", json!([height]))
+            .await?
+            .as_str()
+            .map(|hash| hash.to_string())
+            .ok_or_else(|| AppError::Rpc("getblockhash returned no hash".into()))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("chain_monitor_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("chain-monitor".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn chain(prefix: &str, heights: std::ops::RangeInclusive<u64>) -> HashMap<u64, String> {
+        heights.map(|h| (h, format!("{}{}", prefix, h))).collect()
+    }
+
+    async fn reconcile(tracker: &mut ChainTracker, tip: u64, chain: &HashMap<u64, String>) -> Option<ReorgEvent> {
+        tracker
+            .reconcile(tip, |height| {
+                let hash = chain.get(&height).cloned();
+                async move { hash.ok_or_else(|| AppError::Rpc("unknown height".into())) }
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_growth_is_not_a_reorg() {
+        let mut tracker = ChainTracker::new(5);
+        let main = chain("a", 0..=20);
+        assert_eq!(reconcile(&mut tracker, 10, &main).await, None);
+        assert_eq!(reconcile(&mut tracker, 10, &main).await, None);
+        assert_eq!(reconcile(&mut tracker, 13, &main).await, None);
+        assert_eq!(tracker.blocks.len(), 5);
+        assert_eq!(tracker.blocks.back().unwrap().height, 13);
+    }
+
+    #[tokio::test]
+    async fn test_detects_reorg_depth_and_fork() {
+        let mut tracker = ChainTracker::new(10);
+        let mut main = chain("a", 0..=12);
+        reconcile(&mut tracker, 12, &main).await;
+
+        // Blocks 11 and 12 replaced, chain extended to 13
+        main.extend(chain("b", 11..=13));
+        let event = reconcile(&mut tracker, 13, &main).await.unwrap();
+        assert_eq!(event.depth, 2);
+        assert_eq!(event.fork_height, 10);
+        assert_eq!(event.old_tip, BlockRef { height: 12, hash: "a12".into() });
+        assert_eq!(event.new_tip, BlockRef { height: 13, hash: "b13".into() });
+        assert_eq!(reconcile(&mut tracker, 13, &main).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_reorg_deeper_than_window() {
+        let mut tracker = ChainTracker::new(3);
+        reconcile(&mut tracker, 12, &chain("a", 0..=12)).await;
+
+        let event = reconcile(&mut tracker, 12, &chain("b", 0..=12)).await.unwrap();
+        assert_eq!(event.fork_height, 9);
+        assert_eq!(event.depth, 3);
+        assert_eq!(tracker.blocks.iter().map(|b| b.hash.as_str()).collect::<Vec<_>>(), vec!["b10", "b11", "b12"]);
+    }
+
+    #[test]
+    fn test_reorg_event_serialization() {
+        let event = ChainEvent::Reorg(ReorgEvent {
+            depth: 1,
+            fork_height: 9,
+            old_tip: BlockRef { height: 10, hash: "a".into() },
+            new_tip: BlockRef { height: 10, hash: "b".into() },
+            detected_at: Utc::now(),
+        });
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["type"], "reorg");
+        assert_eq!(value["old_tip"]["hash"], "a");
+    }
+}
@@ -0,0 +1,207 @@
+//! Batch conversion estimates
+//!
+//! Portfolio rebalancing UIs quote several conversions at once and need the
+//! quotes to agree with each other. Calling `estimateconversion` once per
+//! pair lets a block land between calls, so some quotes would price against
+//! the old currency states and some against the new. Here every estimate goes
+//! to the daemon in one JSON-RPC batch, bracketed by `getbestblockhash`; the
+//! daemon runs a batch in order, so when both hashes match every estimate was
+//! computed against the same block. If the tip moved, the batch is sent again.
+
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::debug;
+
+/// One conversion to estimate
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConversionRequest {
+    /// Source currency name or i-address
+    pub from: String,
+    /// Destination currency name or i-address
+    pub to: String,
+    /// Amount of `from` to convert
+    pub amount: f64,
+    /// Fractional basket to convert through, for reserve-to-reserve conversions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+}
+
+/// Estimate for one conversion, in request order
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ConversionEstimate {
+    #[serde(flatten)]
+    pub conversion: ConversionRequest,
+    /// `estimatedcurrencyout` of the daemon's estimate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_out: Option<f64>,
+    /// The daemon's `estimateconversion` result, unchanged
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Value>,
+    /// Why the daemon could not estimate this conversion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Estimates computed against one chain tip
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ConversionBatch {
+    /// Block hash every estimate was computed at
+    pub block_hash: String,
+    pub block_height: u64,
+    pub estimates: Vec<ConversionEstimate>,
+}
+
+/// Estimates several conversions against a single snapshot of currency states
+pub struct ConversionService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+}
+
+impl ConversionService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        Self { config, rpc }
+    }
+
+    /// Estimate every conversion at the same chain tip
+    ///
+    /// Per-conversion daemon errors are reported alongside the estimates
+    /// rather than failing the whole request.
+    pub async fn estimate_batch(&self, conversions: Vec<ConversionRequest>) -> AppResult<ConversionBatch> {
+        self.validate(&conversions)?;
+
+        let mut requests = vec![Self::request("getbestblockhash", json!([])), Self::request("getblockcount", json!([]))];
+        requests.extend(conversions.iter().map(|conversion| {
+            let mut params = json!({ "currency": conversion.from, "convertto": conversion.to, "amount": conversion.amount });
+            if let Some(via) = &conversion.via {
+                params["via"] = json!(via);
+            }
+            Self::request("estimateconversion", json!([params]))
+        }));
+        requests.push(Self::request("getbestblockhash", json!([])));
+
+        let attempts = self.config.conversion_estimates.max_attempts.max(1);
+        for attempt in 1..=attempts {
+            let mut results = self.rpc.send_batch(&requests).await?;
+            let after = results.pop().unwrap_or_else(|| Err(AppError::Rpc("missing batch response".into())))?;
+            let mut results = results.into_iter();
+            let before = results.next().unwrap_or_else(|| Err(AppError::Rpc("missing batch response".into())))?;
+            let height = results.next().unwrap_or_else(|| Err(AppError::Rpc("missing batch response".into())))?;
+            if before != after {
+                debug!(attempt, "Chain tip moved during conversion estimates, retrying");
+                continue;
+            }
+
+            let block_hash = before
+                .as_str()
+                .ok_or_else(|| AppError::Rpc("getbestblockhash returned no hash".into()))?
+                .to_string();
+            let block_height = height
+                .as_u64()
+                .ok_or_else(|| AppError::Rpc("getblockcount returned a non-numeric height".into()))?;
+            let estimates = conversions
+                .into_iter()
+                .zip(results)
+                .map(|(conversion, result)| match result {
+                    Ok(estimate) => ConversionEstimate {
+                        conversion,
+                        estimated_out: estimate.get("estimatedcurrencyout").and_then(Value::as_f64),
+                        estimate: Some(estimate),
+                        error: None,
+                    },
+                    Err(e) => ConversionEstimate { conversion, estimated_out: None, estimate: None, error: Some(e.to_string()) },
+                })
+                .collect();
+            return Ok(ConversionBatch { block_hash, block_height, estimates });
+        }
+        Err(AppError::Rpc(format!("chain tip moved during each of {} estimate attempts", attempts)))
+    }
+
+    fn validate(&self, conversions: &[ConversionRequest]) -> AppResult<()> {
+        let max = self.config.conversion_estimates.max_conversions;
+        if conversions.is_empty() {
+            return Err(AppError::Validation("conversions must not be empty".into()));
+        }
+        if conversions.len() > max {
+            return Err(AppError::Validation(format!("{} conversions requested, limit is {}", conversions.len(), max)));
+        }
+        for conversion in conversions {
+            if !(conversion.amount.is_finite() && conversion.amount > 0.0) {
+                return Err(AppError::Validation(format!("amount must be positive: {}", conversion.amount)));
+            }
+            let names = [Some(&conversion.from), Some(&conversion.to), conversion.via.as_ref()];
+            if let Some(bad) = names.into_iter().flatten().find(|name| !is_plausible_currency(name)) {
+                return Err(AppError::Validation(format!("invalid currency: {}", bad)));
+            }
+        }
+        Ok(())
+    }
+
+    fn request(method: &str, params: Value) -> RpcRequest {
+        RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("convert_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("conversion-estimator".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        )
+    }
+}
+
+/// Currency names (`VRSC`, `Bridge.vETH`) and i-addresses
+fn is_plausible_currency(name: &str) -> bool {
+    (1..=128).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversion(from: &str, to: &str, amount: f64) -> ConversionRequest {
+        ConversionRequest { from: from.to_string(), to: to.to_string(), amount, via: None }
+    }
+
+    #[tokio::test]
+    async fn test_estimate_batch_validates_input() {
+        let mut config = AppConfig::default();
+        config.conversion_estimates.max_conversions = 2;
+        let config = Arc::new(config);
+        let service = ConversionService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)));
+
+        for conversions in [
+            vec![],
+            vec![conversion("VRSC", "vETH", 1.0); 3],
+            vec![conversion("VRSC", "vETH", 0.0)],
+            vec![conversion("VRSC", "vETH", f64::NAN)],
+            vec![conversion("VRSC", "bad name", 1.0)],
+        ] {
+            assert!(matches!(service.estimate_batch(conversions).await, Err(AppError::Validation(_))));
+        }
+    }
+
+    #[test]
+    fn test_estimate_serialization() {
+        let estimate = ConversionEstimate {
+            conversion: ConversionRequest { via: Some("Bridge.vETH".to_string()), ..conversion("VRSC", "vETH", 10.0) },
+            estimated_out: Some(0.05),
+            estimate: Some(json!({ "estimatedcurrencyout": 0.05 })),
+            error: None,
+        };
+        let value = serde_json::to_value(&estimate).unwrap();
+        assert_eq!(value["from"], "VRSC");
+        assert_eq!(value["via"], "Bridge.vETH");
+        assert_eq!(value["estimated_out"], 0.05);
+        assert!(value.get("error").is_none());
+    }
+}
@@ -0,0 +1,396 @@
+//! Currency state history service
+//!
+//! Records `getcurrencystate` for configured currencies as new blocks arrive
+//! and serves the series over a time range, so DEX front ends can chart
+//! reserves and prices without running their own indexer. Snapshots are kept
+//! in memory and, when a storage path is configured, appended to one JSON
+//! lines file per currency that is reloaded at startup.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::pagination::{ListQuery, ListSpec, Page};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, warn};
+
+/// Currency state at one block
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CurrencySnapshot {
+    pub height: u64,
+    /// Block time (unix seconds)
+    pub time: i64,
+    /// `currencystate` as returned by the daemon
+    pub state: Value,
+}
+
+/// A page of one currency's snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrencyHistory {
+    pub currency: String,
+    pub currency_id: Option<String>,
+    #[serde(flatten)]
+    pub page: Page<CurrencySnapshot>,
+}
+
+/// Query parameters of `/api/currency/{id}/history`; newest first by default
+///
+/// `from` and `to` bound the block time in unix seconds.
+pub const CURRENCY_HISTORY_LIST: ListSpec = ListSpec {
+    sort_fields: &["time", "height"],
+    descending: true,
+    filters: &["from", "to", "min_height", "max_height"],
+};
+
+#[derive(Default)]
+struct Series {
+    currency_id: Option<String>,
+    points: VecDeque<CurrencySnapshot>,
+    /// Lines in the backing file, including ones already trimmed from memory
+    lines_on_disk: usize,
+}
+
+/// Currency state sampler and history store
+pub struct CurrencyHistoryService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    series: RwLock<HashMap<String, Series>>,
+    last_height: Mutex<Option<u64>>,
+}
+
+impl CurrencyHistoryService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        let series = config
+            .currency_history
+            .currencies
+            .iter()
+            .map(|c| (c.to_lowercase(), Series::default()))
+            .collect();
+        Self { config, rpc, series: RwLock::new(series), last_height: Mutex::new(None) }
+    }
+
+    /// Snapshots for a configured currency (by name or i-address), paged per `query`
+    ///
+    /// Returns `None` for currencies that are not sampled. The cursor is the
+    /// snapshot's height.
+    pub async fn history(&self, id: &str, query: &ListQuery) -> AppResult<Option<CurrencyHistory>> {
+        let max = self.config.currency_history.max_points_per_request;
+        let limit = query.limit(max, max);
+        let from = query.filter::<i64>("from")?;
+        let to = query.filter::<i64>("to")?;
+        let min_height = query.filter::<u64>("min_height")?;
+        let max_height = query.filter::<u64>("max_height")?;
+        let series = self.series.read().await;
+        let key = id.to_lowercase();
+        let Some((name, entry)) = series.get_key_value(&key).or_else(|| {
+            series
+                .iter()
+                .find(|(_, s)| s.currency_id.as_deref().is_some_and(|cid| cid.eq_ignore_ascii_case(id)))
+        }) else {
+            return Ok(None);
+        };
+
+        let matching: Vec<&CurrencySnapshot> = entry
+            .points
+            .iter()
+            .filter(|p| from.is_none_or(|f| p.time >= f) && to.is_none_or(|t| p.time <= t))
+            .filter(|p| min_height.is_none_or(|h| p.height >= h) && max_height.is_none_or(|h| p.height <= h))
+            .collect();
+        let page = query.page(matching, limit, |p| p.height)?;
+        let currency = self
+            .config
+            .currency_history
+            .currencies
+            .iter()
+            .find(|c| c.to_lowercase() == *name)
+            .cloned()
+            .unwrap_or_else(|| name.clone());
+
+        Ok(Some(CurrencyHistory {
+            currency,
+            currency_id: entry.currency_id.clone(),
+            page: page.map(CurrencySnapshot::clone),
+        }))
+    }
+
+    /// Load persisted snapshots from the storage directory
+    pub async fn load(&self) -> AppResult<()> {
+        let Some(dir) = self.storage_dir() else { return Ok(()) };
+        let max = self.config.currency_history.max_points_per_currency;
+        let mut series = self.series.write().await;
+        for (name, entry) in series.iter_mut() {
+            let path = dir.join(file_name(name));
+            let contents = match tokio::fs::read_to_string(&path).await {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(AppError::Internal(format!("failed to read {}: {}", path.display(), e))),
+            };
+            for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+                match serde_json::from_str::<CurrencySnapshot>(line) {
+                    Ok(snapshot) => {
+                        entry.lines_on_disk += 1;
+                        if entry.currency_id.is_none() {
+                            entry.currency_id = currency_id(&snapshot.state);
+                        }
+                        entry.points.push_back(snapshot);
+                    }
+                    Err(e) => warn!(currency = %name, "Skipping malformed currency snapshot: {}", e),
+                }
+            }
+            while entry.points.len() > max {
+                entry.points.pop_front();
+            }
+            info!(currency = %name, points = entry.points.len(), "Loaded currency history");
+        }
+        Ok(())
+    }
+
+    /// Record a snapshot of every currency if the tip has advanced far enough
+    ///
+    /// Returns the number of snapshots recorded.
+    pub async fn sample(&self) -> AppResult<usize> {
+        let height = self
+            .call("getblockcount", json!([]))
+            .await?
+            .as_u64()
+            .ok_or_else(|| AppError::Rpc("getblockcount returned a non-numeric height".into()))?;
+        {
+            let mut last = self.last_height.lock().await;
+            if last.is_some_and(|h| height < h + self.config.currency_history.block_interval) {
+                return Ok(0);
+            }
+            *last = Some(height);
+        }
+
+        let mut recorded = 0;
+        for currency in &self.config.currency_history.currencies {
+            match self.call("getcurrencystate", json!([currency])).await {
+                Ok(result) => match parse_currency_state(&result, height, Utc::now().timestamp()) {
+                    Some(snapshot) => {
+                        self.record(&currency.to_lowercase(), snapshot).await?;
+                        recorded += 1;
+                    }
+                    None => warn!(currency = %currency, "getcurrencystate returned no state"),
+                },
+                Err(e) => warn!(currency = %currency, "Currency state sampling failed: {}", e),
+            }
+        }
+        Ok(recorded)
+    }
+
+    /// Add a snapshot to memory and the backing file
+    async fn record(&self, name: &str, snapshot: CurrencySnapshot) -> AppResult<()> {
+        let max = self.config.currency_history.max_points_per_currency;
+        let mut series = self.series.write().await;
+        let entry = series.entry(name.to_string()).or_default();
+        if entry.points.back().is_some_and(|p| p.height >= snapshot.height) {
+            return Ok(());
+        }
+        if entry.currency_id.is_none() {
+            entry.currency_id = currency_id(&snapshot.state);
+        }
+        entry.points.push_back(snapshot.clone());
+        while entry.points.len() > max {
+            entry.points.pop_front();
+        }
+
+        let Some(dir) = self.storage_dir() else { return Ok(()) };
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| AppError::Internal(format!("failed to create {}: {}", dir.display(), e)))?;
+        let path = dir.join(file_name(name));
+        if entry.lines_on_disk >= max.saturating_mul(2) {
+            // Rewrite the file from memory so it doesn't grow without bound
+            let mut contents = String::new();
+            for point in &entry.points {
+                contents.push_str(&serde_json::to_string(point)?);
+                contents.push('\n');
+            }
+            tokio::fs::write(&path, contents)
+                .await
+                .map_err(|e| AppError::Internal(format!("failed to write {}: {}", path.display(), e)))?;
+            entry.lines_on_disk = entry.points.len();
+        } else {
+            let mut line = serde_json::to_string(&snapshot)?;
+            line.push('\n');
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await
+                .map_err(|e| AppError::Internal(format!("failed to open {}: {}", path.display(), e)))?;
+            file.write_all(line.as_bytes())
+                .await
+                .map_err(|e| AppError::Internal(format!("failed to append to {}: {}", path.display(), e)))?;
+            entry.lines_on_disk += 1;
+        }
+        Ok(())
+    }
+
+    /// Load persisted history, then sample in the background
+    pub fn start_sampler(self: Arc<Self>) {
+        let interval = std::time::Duration::from_secs(self.config.currency_history.poll_interval_seconds.max(1));
+        tokio::spawn(async move {
+            if let Err(e) = self.load().await {
+                warn!("Failed to load currency history: {}", e);
+            }
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.sample().await {
+                    Ok(0) => {}
+                    Ok(recorded) => debug!(recorded, "Currency state snapshots recorded"),
+                    Err(e) => warn!("Currency state sampling failed: {}", e),
+                }
+            }
+        });
+    }
+
+    fn storage_dir(&self) -> Option<PathBuf> {
+        self.config.currency_history.storage_path.as_ref().map(PathBuf::from)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("currency_history_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("currency-history-sampler".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+/// Snapshot from a `getcurrencystate` result (an array of per-height entries)
+fn parse_currency_state(result: &Value, tip: u64, now: i64) -> Option<CurrencySnapshot> {
+    let entry = match result {
+        Value::Array(entries) => entries.last()?,
+        other => other,
+    };
+    let state = entry.get("currencystate")?.clone();
+    Some(CurrencySnapshot {
+        height: entry.get("height").and_then(|h| h.as_u64()).unwrap_or(tip),
+        time: entry.get("blocktime").and_then(|t| t.as_i64()).unwrap_or(now),
+        state,
+    })
+}
+
+fn currency_id(state: &Value) -> Option<String> {
+    state.get("currencyid").and_then(|id| id.as_str()).map(|id| id.to_string())
+}
+
+/// File name for a currency, safe for any configured name
+fn file_name(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}.jsonl", safe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(storage_path: Option<String>, max_points: usize) -> CurrencyHistoryService {
+        let mut config = AppConfig::default();
+        config.currency_history.currencies = vec!["Bridge.vETH".to_string()];
+        config.currency_history.storage_path = storage_path;
+        config.currency_history.max_points_per_currency = max_points;
+        let config = Arc::new(config);
+        CurrencyHistoryService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)))
+    }
+
+    fn list(pairs: &[(&str, &str)]) -> ListQuery {
+        let params = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        ListQuery::parse(&params, &CURRENCY_HISTORY_LIST).unwrap()
+    }
+
+    fn snapshot(height: u64) -> CurrencySnapshot {
+        CurrencySnapshot {
+            height,
+            time: 1_700_000_000 + height as i64 * 60,
+            state: json!({ "currencyid": "i3f7tSctFkiPpiedY8QR5Tep9p4qDVebDx", "supply": height }),
+        }
+    }
+
+    #[test]
+    fn test_parse_currency_state_uses_latest_entry() {
+        let result = json!([
+            { "height": 10, "blocktime": 1000, "currencystate": { "supply": 1 } },
+            { "height": 11, "blocktime": 1060, "currencystate": { "supply": 2 } }
+        ]);
+        let parsed = parse_currency_state(&result, 12, 2000).unwrap();
+        assert_eq!(parsed.height, 11);
+        assert_eq!(parsed.time, 1060);
+        assert_eq!(parsed.state["supply"], 2);
+        assert!(parse_currency_state(&json!([]), 12, 2000).is_none());
+        assert_eq!(file_name("bridge.veth"), "bridge_veth.jsonl");
+    }
+
+    #[tokio::test]
+    async fn test_history_filters_by_time_and_id() {
+        let service = service(None, 3);
+        for height in 1..=4 {
+            service.record("bridge.veth", snapshot(height)).await.unwrap();
+        }
+        // Only the newest three are kept; re-recording a height is ignored
+        service.record("bridge.veth", snapshot(4)).await.unwrap();
+
+        let all = service.history("Bridge.vETH", &list(&[])).await.unwrap().unwrap();
+        assert_eq!(all.currency, "Bridge.vETH");
+        assert_eq!(all.page.items.iter().map(|p| p.height).collect::<Vec<_>>(), vec![4, 3, 2]);
+
+        let from = snapshot(3).time.to_string();
+        let by_id = service
+            .history("i3f7tSctFkiPpiedY8QR5Tep9p4qDVebDx", &list(&[("from", from.as_str()), ("limit", "1")]))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_id.page.items, vec![snapshot(4)]);
+        assert_eq!(by_id.page.total, 2);
+        assert_eq!(by_id.page.next_cursor.as_deref(), Some("4"));
+
+        let oldest = service.history("bridge.veth", &list(&[("sort", "height"), ("cursor", "2")])).await.unwrap().unwrap();
+        assert_eq!(oldest.page.items.iter().map(|p| p.height).collect::<Vec<_>>(), vec![3, 4]);
+        assert!(service.history("bridge.veth", &list(&[("to", "soon")])).await.is_err());
+        assert!(service.history("VRSC", &list(&[])).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_history_survives_reload() {
+        let dir = std::env::temp_dir().join(format!("currency-history-{}", uuid::Uuid::new_v4()));
+        let path = dir.to_string_lossy().to_string();
+
+        let first = service(Some(path.clone()), 2);
+        for height in 1..=5 {
+            first.record("bridge.veth", snapshot(height)).await.unwrap();
+        }
+
+        let second = service(Some(path), 2);
+        second.load().await.unwrap();
+        let history = second.history("bridge.veth", &list(&[("sort", "height")])).await.unwrap().unwrap();
+        assert_eq!(history.page.items.iter().map(|p| p.height).collect::<Vec<_>>(), vec![4, 5]);
+        assert_eq!(history.currency_id.as_deref(), Some("i3f7tSctFkiPpiedY8QR5Tep9p4qDVebDx"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,312 @@
+//! Block explorer aggregation service
+//!
+//! Returns a block together with its decoded transactions in one call,
+//! fetching transactions from the daemon with bounded concurrency and caching
+//! them so explorers don't have to issue N+1 requests through the proxy.
+//! Bulk address balances work the same way, sending `getaddressbalance` calls
+//! to the daemon as JSON-RPC batches.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::Utc;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+/// Bounded FIFO cache for decoded transactions and address balances
+struct BoundedCache<V> {
+    entries: HashMap<String, V>,
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl<V: Clone> BoundedCache<V> {
+    fn new(capacity: usize) -> Self {
+        Self { entries: HashMap::new(), order: VecDeque::new(), capacity }
+    }
+
+    fn get(&self, key: &str) -> Option<V> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: String, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(existing) = self.entries.get_mut(&key) {
+            *existing = value;
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => { self.entries.remove(&oldest); }
+                None => break,
+            }
+        }
+        self.order.push_back(key.clone());
+        self.entries.insert(key, value);
+    }
+}
+
+/// Explorer aggregation service
+pub struct ExplorerService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    tx_cache: Mutex<BoundedCache<Value>>,
+    balance_cache: Mutex<BoundedCache<(Instant, Value)>>,
+}
+
+impl ExplorerService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        let tx_cache = Mutex::new(BoundedCache::new(config.explorer.tx_cache_entries));
+        let balance_cache = Mutex::new(BoundedCache::new(config.explorer.balance_cache_entries));
+        Self { config, rpc, tx_cache, balance_cache }
+    }
+
+    /// Get a block with its decoded transactions in place of txids
+    pub async fn get_full_block(&self, hash: &str) -> AppResult<Value> {
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(AppError::Validation("block hash must be 64 hex characters".into()));
+        }
+
+        let mut block = self.call("getblock", json!([hash, 1])).await?;
+        let txids: Vec<String> = block
+            .get("tx")
+            .and_then(|t| t.as_array())
+            .ok_or_else(|| AppError::Rpc("getblock returned no transaction list".into()))?
+            .iter()
+            .filter_map(|t| t.as_str().map(|s| s.to_string()))
+            .collect();
+
+        if txids.len() > self.config.explorer.max_block_transactions {
+            return Err(AppError::Validation(format!(
+                "block has {} transactions, limit is {}",
+                txids.len(),
+                self.config.explorer.max_block_transactions
+            )));
+        }
+
+        let confirmations = block.get("confirmations").cloned();
+        let transactions: Vec<Value> = stream::iter(txids)
+            .map(|txid| self.get_transaction(txid))
+            .buffered(self.config.explorer.max_concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        // Cached transactions may carry stale confirmation counts; use the block's
+        let transactions = transactions
+            .into_iter()
+            .map(|mut tx| {
+                if let (Some(obj), Some(conf)) = (tx.as_object_mut(), confirmations.clone()) {
+                    obj.insert("confirmations".to_string(), conf);
+                }
+                tx
+            })
+            .collect();
+
+        if let Some(obj) = block.as_object_mut() {
+            obj.insert("tx".to_string(), Value::Array(transactions));
+        }
+        Ok(block)
+    }
+
+    /// Fetch a decoded transaction, using the cache when possible
+    async fn get_transaction(&self, txid: String) -> AppResult<Value> {
+        if let Some(tx) = self.tx_cache.lock().await.get(&txid) {
+            return Ok(tx);
+        }
+        let tx = self.call("getrawtransaction", json!([txid, 1])).await?;
+        self.tx_cache.lock().await.insert(txid, tx.clone());
+        Ok(tx)
+    }
+
+    /// Get `getaddressbalance` for each address, merged into one response
+    ///
+    /// Addresses are de-duplicated; cached balances are reused and the rest
+    /// are fetched in JSON-RPC batches of `balance_chunk_size`. Per-address
+    /// daemon errors are reported alongside the balances rather than failing
+    /// the whole request.
+    pub async fn get_address_balances(&self, addresses: Vec<String>) -> AppResult<Value> {
+        let explorer = &self.config.explorer;
+        if addresses.is_empty() {
+            return Err(AppError::Validation("addresses must not be empty".into()));
+        }
+        if addresses.len() > explorer.max_balance_addresses {
+            return Err(AppError::Validation(format!(
+                "{} addresses requested, limit is {}",
+                addresses.len(),
+                explorer.max_balance_addresses
+            )));
+        }
+        if let Some(bad) = addresses.iter().find(|a| !is_plausible_address(a)) {
+            return Err(AppError::Validation(format!("invalid address: {}", bad)));
+        }
+
+        let mut seen = HashSet::new();
+        let addresses: Vec<String> = addresses.into_iter().filter(|a| seen.insert(a.clone())).collect();
+
+        let ttl = Duration::from_secs(explorer.balance_cache_seconds);
+        let mut found: HashMap<String, Value> = HashMap::new();
+        if !ttl.is_zero() {
+            let cache = self.balance_cache.lock().await;
+            for address in &addresses {
+                if let Some((at, balance)) = cache.get(address) {
+                    if at.elapsed() < ttl {
+                        found.insert(address.clone(), balance);
+                    }
+                }
+            }
+        }
+        let cached = found.len();
+
+        let missing: Vec<String> = addresses.iter().filter(|a| !found.contains_key(*a)).cloned().collect();
+        let chunks: Vec<Vec<String>> = missing.chunks(explorer.balance_chunk_size.max(1)).map(|c| c.to_vec()).collect();
+        let fetched: Vec<Vec<(String, AppResult<Value>)>> = stream::iter(chunks)
+            .map(|chunk| self.fetch_balance_chunk(chunk))
+            .buffer_unordered(explorer.max_concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        let mut errors = Vec::new();
+        {
+            let mut cache = self.balance_cache.lock().await;
+            for (address, result) in fetched.into_iter().flatten() {
+                match result {
+                    Ok(balance) => {
+                        if !ttl.is_zero() {
+                            cache.insert(address.clone(), (Instant::now(), balance.clone()));
+                        }
+                        found.insert(address, balance);
+                    }
+                    Err(e) => errors.push(json!({ "address": address, "error": e.to_string() })),
+                }
+            }
+        }
+
+        let mut total_balance: i64 = 0;
+        let mut total_received: i64 = 0;
+        let balances: Vec<Value> = addresses
+            .iter()
+            .filter_map(|address| {
+                let balance = found.get(address)?;
+                let sats = balance.get("balance").and_then(|v| v.as_i64()).unwrap_or(0);
+                let received = balance.get("received").and_then(|v| v.as_i64()).unwrap_or(0);
+                total_balance += sats;
+                total_received += received;
+                Some(json!({ "address": address, "balance": sats, "received": received }))
+            })
+            .collect();
+
+        Ok(json!({
+            "balances": balances,
+            "errors": errors,
+            "total": { "balance": total_balance, "received": total_received },
+            "cached": cached,
+        }))
+    }
+
+    /// Fetch one chunk of balances as a single JSON-RPC batch
+    async fn fetch_balance_chunk(&self, chunk: Vec<String>) -> AppResult<Vec<(String, AppResult<Value>)>> {
+        let requests: Vec<RpcRequest> = chunk
+            .iter()
+            .map(|address| Self::request("getaddressbalance", json!([{ "addresses": [address] }])))
+            .collect();
+        let results = self.rpc.send_batch(&requests).await?;
+        Ok(chunk.into_iter().zip(results).collect())
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = Self::request(method, params);
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+
+    fn request(method: &str, params: Value) -> RpcRequest {
+        RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("explorer_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("explorer".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        )
+    }
+}
+
+/// Cheap sanity check before anything reaches the daemon: transparent
+/// addresses, identity addresses and `name@` identities
+fn is_plausible_address(address: &str) -> bool {
+    (1..=128).contains(&address.len())
+        && address.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '-' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_cache_evicts_oldest() {
+        let mut cache = BoundedCache::new(2);
+        cache.insert("a".to_string(), json!(1));
+        cache.insert("b".to_string(), json!(2));
+        cache.insert("c".to_string(), json!(3));
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.get("c"), Some(json!(3)));
+    }
+
+    #[tokio::test]
+    async fn test_get_full_block_rejects_invalid_hash() {
+        let config = Arc::new(AppConfig::default());
+        let service = ExplorerService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)));
+        let result = service.get_full_block("not-a-hash").await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get_address_balances_validates_input() {
+        let mut config = AppConfig::default();
+        config.explorer.max_balance_addresses = 2;
+        let config = Arc::new(config);
+        let service = ExplorerService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)));
+
+        assert!(matches!(service.get_address_balances(vec![]).await, Err(AppError::Validation(_))));
+        let too_many = vec!["RA".to_string(), "RB".to_string(), "RC".to_string()];
+        assert!(matches!(service.get_address_balances(too_many).await, Err(AppError::Validation(_))));
+        let malformed = vec!["R bad".to_string()];
+        assert!(matches!(service.get_address_balances(malformed).await, Err(AppError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get_address_balances_serves_cached_partials() {
+        let config = Arc::new(AppConfig::default());
+        let service = ExplorerService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)));
+        for (address, sats) in [("RAlice", 150), ("RBob", 50)] {
+            service.balance_cache.lock().await.insert(
+                address.to_string(),
+                (Instant::now(), json!({ "balance": sats, "received": sats * 2 })),
+            );
+        }
+
+        let result = service
+            .get_address_balances(vec!["RBob".to_string(), "RAlice".to_string(), "RBob".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(result["cached"], 2);
+        assert_eq!(result["balances"][0]["address"], "RBob");
+        assert_eq!(result["total"]["balance"], 200);
+        assert_eq!(result["total"]["received"], 400);
+    }
+}
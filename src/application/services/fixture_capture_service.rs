@@ -0,0 +1,170 @@
+//! Mock daemon fixtures captured from a live daemon
+//!
+//! The `capture-fixtures` command makes each call listed in
+//! `[mock_daemon] capture` against the configured daemon and saves the
+//! response as `<fixtures_dir>/<method>.json`, in the same call format as
+//! `[recording]`. Values of `[recording] scrub_keys` are replaced before
+//! anything is written. `--mock-daemon` answers those methods with the saved
+//! results, so local development sees real response shapes. A call that
+//! fails is reported and leaves any earlier fixture for its method in place.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::config::app_config::FixtureCallConfig;
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::daemon_recording::{scrub, RecordedCall};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+
+/// Outcome of one capture run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CaptureReport {
+    /// Directory the fixtures were written to
+    pub fixtures_dir: String,
+    /// Methods whose fixture was written
+    pub captured: Vec<String>,
+    /// Methods that failed, with the reason
+    pub failed: Vec<CaptureFailure>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureFailure {
+    pub method: String,
+    pub error: String,
+}
+
+impl CaptureReport {
+    /// Process exit code: 0 when every call was captured
+    pub fn exit_code(&self) -> i32 {
+        if self.failed.is_empty() {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+pub struct FixtureCaptureService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+}
+
+impl FixtureCaptureService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        Self { config, rpc }
+    }
+
+    /// Make every configured call and write the responses to the fixtures directory
+    pub async fn run(&self) -> AppResult<CaptureReport> {
+        let dir = PathBuf::from(&self.config.mock_daemon.fixtures_dir);
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| AppError::Config(format!("Cannot create fixtures directory {}: {}", dir.display(), e)))?;
+
+        let mut report = CaptureReport { fixtures_dir: dir.display().to_string(), ..Default::default() };
+        for call in &self.config.mock_daemon.capture {
+            match self.capture(&dir, call).await {
+                Ok(()) => report.captured.push(call.method.clone()),
+                Err(e) => {
+                    warn!(method = %call.method, error = %e, "Fixture capture failed");
+                    report.failed.push(CaptureFailure { method: call.method.clone(), error: e.to_string() });
+                }
+            }
+        }
+
+        info!(captured = report.captured.len(), failed = report.failed.len(), dir = %report.fixtures_dir, "Fixture capture finished");
+        Ok(report)
+    }
+
+    async fn capture(&self, dir: &Path, call: &FixtureCallConfig) -> AppResult<()> {
+        let request = RpcRequest::new(
+            call.method.clone(),
+            Some(Value::Array(call.params.clone())),
+            Some(json!(format!("capture_fixtures_{}", call.method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("capture-fixtures".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        let result = self
+            .rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", call.method)))?;
+        write_fixture(dir, call, result, &self.config.recording.scrub_keys)
+    }
+}
+
+/// Save `result` for `call` as `<dir>/<method>.json`, scrubbing `scrub_keys` first
+fn write_fixture(dir: &Path, call: &FixtureCallConfig, mut result: Value, scrub_keys: &[String]) -> AppResult<()> {
+    let keys: Vec<String> = scrub_keys.iter().map(|key| key.to_lowercase()).collect();
+    let mut params = Value::Array(call.params.clone());
+    scrub(&mut params, &keys);
+    scrub(&mut result, &keys);
+    let fixture = RecordedCall { method: call.method.clone(), params, result: Some(result), error: None, recorded_at: Utc::now() };
+    let text = serde_json::to_string_pretty(&fixture)
+        .map_err(|e| AppError::Internal(format!("Failed to encode fixture: {}", e)))?;
+    let path = dir.join(format!("{}.json", call.method));
+    std::fs::write(&path, text + "\n")
+        .map_err(|e| AppError::Internal(format!("Failed to write fixture {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::app_config::DaemonAuthMethod;
+    use crate::infrastructure::adapters::mock_daemon::load_fixtures;
+    use crate::infrastructure::adapters::MockDaemon;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("verus-rpc-{}-{}", name, std::process::id()))
+    }
+
+    fn call(method: &str) -> FixtureCallConfig {
+        FixtureCallConfig { method: method.to_string(), params: Vec::new() }
+    }
+
+    #[test]
+    fn test_written_fixtures_are_scrubbed() {
+        let dir = temp_dir("fixture-scrub");
+        std::fs::create_dir_all(&dir).unwrap();
+        let result = json!({"address": "RAddress", "privkey": "secret"});
+        write_fixture(&dir, &call("getaddressinfo"), result, &["PrivKey".to_string()]).unwrap();
+
+        let fixtures = load_fixtures(&dir).unwrap();
+        assert_eq!(fixtures["getaddressinfo"], json!({"address": "RAddress", "privkey": "[scrubbed]"}));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_capture_reports_each_call() {
+        let daemon = MockDaemon::start(std::time::Duration::ZERO).await.unwrap();
+        let dir = temp_dir("fixture-capture");
+        let mut config = AppConfig::default();
+        config.verus.rpc_url = daemon.url();
+        config.verus.auth_method = DaemonAuthMethod::Password;
+        config.verus.rpc_user = "mock".to_string();
+        config.verus.rpc_password = "mock".to_string();
+        config.mock_daemon.fixtures_dir = dir.display().to_string();
+        config.mock_daemon.capture = vec![call("getblockcount"), call("notamethod")];
+        let config = Arc::new(config);
+
+        let service = FixtureCaptureService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)));
+        let report = service.run().await.unwrap();
+        assert_eq!(report.captured, vec!["getblockcount".to_string()]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.exit_code(), 1);
+        assert!(load_fixtures(&dir).unwrap().contains_key("getblockcount"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,278 @@
+//! Rolling health-check history and uptime reporting
+//!
+//! A background prober checks the daemon (`getblockcount`), Redis (`PING`,
+//! when caching is enabled) and the mining pool (`/api/v1/health`, when
+//! configured) every `check_interval_seconds`. Only state changes are kept, so
+//! memory is bounded by `max_transitions` rather than by the probe rate, and
+//! uptime over the 1h/24h/7d windows is computed from those transitions.
+
+use crate::{
+    config::AppConfig,
+    domain::rpc::{ClientInfo, RpcRequest},
+    infrastructure::adapters::{ExternalRpcAdapter, MiningPoolClient},
+    middleware::cache::CacheMiddleware,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// Uptime windows reported by `/health/history`
+pub const UPTIME_WINDOWS: [(&str, i64); 3] = [("1h", 3600), ("24h", 86400), ("7d", 604800)];
+
+/// Result of one probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentState {
+    Up,
+    Down,
+}
+
+/// A component changing state (its first observation counts as one)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthTransition {
+    pub component: String,
+    pub state: ComponentState,
+    pub at: DateTime<Utc>,
+    /// Probe error, for transitions to `down`
+    pub detail: Option<String>,
+}
+
+/// Current state and uptime of one component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentUptime {
+    pub component: String,
+    pub state: ComponentState,
+    pub since: DateTime<Utc>,
+    /// Percentage of each window the component was up; `null` until the window has any coverage
+    pub uptime_percent: BTreeMap<String, Option<f64>>,
+}
+
+/// Body of `/health/history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthHistory {
+    pub started_at: DateTime<Utc>,
+    pub checked_at: Option<DateTime<Utc>>,
+    pub components: Vec<ComponentUptime>,
+    /// Most recent first
+    pub transitions: Vec<HealthTransition>,
+}
+
+struct HistoryState {
+    started_at: DateTime<Utc>,
+    checked_at: Option<DateTime<Utc>>,
+    current: HashMap<String, (ComponentState, DateTime<Utc>)>,
+    transitions: VecDeque<HealthTransition>,
+}
+
+/// Background prober and transition log
+pub struct HealthHistoryService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    cache: Option<Arc<CacheMiddleware>>,
+    mining_pool: Option<Arc<MiningPoolClient>>,
+    state: RwLock<HistoryState>,
+}
+
+impl HealthHistoryService {
+    /// Create a new history service; Redis is probed through `cache` when given
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>, cache: Option<Arc<CacheMiddleware>>) -> Self {
+        Self {
+            config,
+            rpc,
+            cache,
+            mining_pool: None,
+            state: RwLock::new(HistoryState {
+                started_at: Utc::now(),
+                checked_at: None,
+                current: HashMap::new(),
+                transitions: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Probe the pool through `client` when `[security.mining_pool]` is enabled
+    pub fn with_mining_pool(mut self, client: Arc<MiningPoolClient>) -> Self {
+        self.mining_pool = Some(client);
+        self
+    }
+
+    /// Record a probe result at `at`, logging a transition when the state changed
+    pub async fn record(&self, component: &str, state: ComponentState, detail: Option<String>, at: DateTime<Utc>) {
+        let mut history = self.state.write().await;
+        history.checked_at = Some(at);
+        if history.current.get(component).is_some_and(|(current, _)| *current == state) {
+            return;
+        }
+        if history.current.contains_key(component) {
+            match state {
+                ComponentState::Up => info!(component, "Component recovered"),
+                ComponentState::Down => warn!(component, detail = detail.as_deref().unwrap_or(""), "Component went down"),
+            }
+        }
+        history.current.insert(component.to_string(), (state, at));
+        history.transitions.push_back(HealthTransition { component: component.to_string(), state, at, detail });
+        while history.transitions.len() > self.config.health_history.max_transitions {
+            history.transitions.pop_front();
+        }
+    }
+
+    /// Probe every configured component once
+    pub async fn check(&self) {
+        let timeout = std::time::Duration::from_secs(self.config.health_history.probe_timeout_seconds);
+        let daemon = tokio::time::timeout(timeout, self.rpc.send_request(&probe_request())).await;
+        let (state, detail) = match daemon {
+            Ok(Ok(_)) => (ComponentState::Up, None),
+            Ok(Err(e)) => (ComponentState::Down, Some(e.to_string())),
+            Err(_) => (ComponentState::Down, Some("probe timed out".to_string())),
+        };
+        self.record("daemon", state, detail, Utc::now()).await;
+
+        if let Some(cache) = self.cache.as_ref().filter(|_| self.config.cache.enabled) {
+            let up = tokio::time::timeout(timeout, cache.ping()).await.unwrap_or(false);
+            let detail = (!up).then(|| "redis did not answer PING".to_string());
+            self.record("redis", if up { ComponentState::Up } else { ComponentState::Down }, detail, Utc::now()).await;
+        }
+
+        let pool_enabled = self.config.security.mining_pool.as_ref().is_some_and(|pool| pool.enabled);
+        if let Some(pool) = self.mining_pool.as_ref().filter(|_| pool_enabled) {
+            let up = matches!(tokio::time::timeout(timeout, pool.health_check()).await, Ok(Ok(true)));
+            let detail = (!up).then(|| "pool health check failed".to_string());
+            self.record("pool", if up { ComponentState::Up } else { ComponentState::Down }, detail, Utc::now()).await;
+        }
+    }
+
+    /// Current states, uptime percentages and the `limit` most recent transitions
+    pub async fn history(&self, limit: usize) -> HealthHistory {
+        self.history_at(limit, Utc::now()).await
+    }
+
+    async fn history_at(&self, limit: usize, now: DateTime<Utc>) -> HealthHistory {
+        let history = self.state.read().await;
+        let mut components: Vec<ComponentUptime> = history
+            .current
+            .iter()
+            .map(|(component, (state, since))| {
+                let timeline: Vec<(DateTime<Utc>, ComponentState)> = history
+                    .transitions
+                    .iter()
+                    .filter(|t| &t.component == component)
+                    .map(|t| (t.at, t.state))
+                    .collect();
+                let uptime_percent = UPTIME_WINDOWS
+                    .iter()
+                    .map(|(name, seconds)| (name.to_string(), uptime_percent(&timeline, now, Duration::seconds(*seconds))))
+                    .collect();
+                ComponentUptime { component: component.clone(), state: *state, since: *since, uptime_percent }
+            })
+            .collect();
+        components.sort_by(|a, b| a.component.cmp(&b.component));
+        HealthHistory {
+            started_at: history.started_at,
+            checked_at: history.checked_at,
+            components,
+            transitions: history.transitions.iter().rev().take(limit).cloned().collect(),
+        }
+    }
+
+    /// Spawn the background prober
+    pub fn start_prober(self: Arc<Self>) {
+        let interval = std::time::Duration::from_secs(self.config.health_history.check_interval_seconds.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.check().await;
+            }
+        });
+    }
+}
+
+/// Request sent to the daemon by the prober
+fn probe_request() -> RpcRequest {
+    RpcRequest {
+        method: "getblockcount".to_string(),
+        parameters: Some(serde_json::json!([])),
+        id: Some(serde_json::json!("health-history")),
+        client_info: ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: Some("health-history".to_string()),
+            auth_token: None,
+            timestamp: Utc::now(),
+            request_id: None,
+        },
+        upstream: None,
+    }
+}
+
+/// Share of `window` before `now` during which the component was up
+///
+/// `timeline` holds the component's transitions, oldest first. Time before the
+/// first one is not covered and is left out, so a component first seen ten
+/// minutes ago and up since reports 100% for every window.
+pub fn uptime_percent(timeline: &[(DateTime<Utc>, ComponentState)], now: DateTime<Utc>, window: Duration) -> Option<f64> {
+    let first = timeline.first()?.0;
+    let start = (now - window).max(first);
+    if start >= now {
+        return None;
+    }
+    let mut up = Duration::zero();
+    for (i, (at, state)) in timeline.iter().enumerate() {
+        let end = timeline.get(i + 1).map_or(now, |(next, _)| *next).min(now);
+        let begin = (*at).max(start);
+        if *state == ComponentState::Up && end > begin {
+            up += end - begin;
+        }
+    }
+    let covered = (now - start).num_milliseconds() as f64;
+    Some((up.num_milliseconds() as f64 / covered * 10000.0).round() / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(max_transitions: usize) -> HealthHistoryService {
+        let mut config = AppConfig::default();
+        config.health_history.max_transitions = max_transitions;
+        let config = Arc::new(config);
+        HealthHistoryService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)), None)
+    }
+
+    #[test]
+    fn test_uptime_percent() {
+        let now = Utc::now();
+        let timeline = [
+            (now - Duration::hours(2), ComponentState::Up),
+            (now - Duration::minutes(30), ComponentState::Down),
+            (now - Duration::minutes(15), ComponentState::Up),
+        ];
+        assert_eq!(uptime_percent(&timeline, now, Duration::hours(1)), Some(75.0));
+        assert_eq!(uptime_percent(&timeline, now, Duration::hours(24)), Some(87.5));
+        assert_eq!(uptime_percent(&[], now, Duration::hours(1)), None);
+        assert_eq!(uptime_percent(&[(now, ComponentState::Up)], now, Duration::hours(1)), None);
+    }
+
+    #[tokio::test]
+    async fn test_only_transitions_are_kept() {
+        let history = service(3);
+        let start = Utc::now() - Duration::hours(1);
+        history.record("daemon", ComponentState::Up, None, start).await;
+        history.record("daemon", ComponentState::Up, None, start + Duration::minutes(10)).await;
+        history.record("daemon", ComponentState::Down, Some("refused".into()), start + Duration::minutes(30)).await;
+        history.record("redis", ComponentState::Up, None, start).await;
+        history.record("daemon", ComponentState::Up, None, start + Duration::minutes(45)).await;
+
+        let report = history.history_at(10, start + Duration::hours(1)).await;
+        assert_eq!(report.transitions.len(), 3);
+        assert_eq!(report.transitions[0].state, ComponentState::Up);
+        assert_eq!(report.transitions[1].component, "redis");
+        let daemon = &report.components[0];
+        assert_eq!((daemon.component.as_str(), daemon.state), ("daemon", ComponentState::Up));
+        // The first daemon transition rolled off, so coverage starts at the outage
+        assert_eq!(daemon.uptime_percent["1h"], Some(50.0));
+        assert_eq!(report.components[1].uptime_percent["7d"], Some(100.0));
+    }
+}
@@ -0,0 +1,328 @@
+//! Asynchronous jobs for long-running aggregate queries
+//!
+//! A full block with hundreds of transactions or the history of a busy
+//! address can take longer than clients and load balancers wait for an HTTP
+//! response. `POST /jobs` starts such a query in the background and returns a
+//! job id at once; `GET /jobs/{id}` reports its status and, once it has
+//! succeeded, its result. Results are kept in the response cache (Redis, or
+//! its in-memory fallback) when `[cache]` is enabled and in process memory
+//! otherwise, for `result_ttl_seconds` after the job finishes. Each client IP
+//! may run `max_jobs_per_client` jobs at a time.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::application::services::ExplorerService;
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::middleware::cache::CacheMiddleware;
+use crate::shared::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Query a job runs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobQuery {
+    /// Block with decoded transactions, as `GET /api/block/{hash}/full`
+    FullBlock { hash: String },
+    /// Balances of many addresses, as `POST /api/addresses/balances`
+    AddressBalances { addresses: Vec<String> },
+    /// `getaddressdeltas` over the addresses, optionally between two heights
+    AddressHistory {
+        addresses: Vec<String>,
+        #[serde(default)]
+        start: Option<u64>,
+        #[serde(default)]
+        end: Option<u64>,
+    },
+}
+
+impl JobQuery {
+    fn kind(&self) -> &'static str {
+        match self {
+            JobQuery::FullBlock { .. } => "full_block",
+            JobQuery::AddressBalances { .. } => "address_balances",
+            JobQuery::AddressHistory { .. } => "address_history",
+        }
+    }
+}
+
+/// Where a job is in its lifecycle
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// Succeeded, but the cache evicted the result before it was collected
+    Expired,
+}
+
+/// A job as reported to clients
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Job {
+    pub id: String,
+    pub kind: &'static str,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct JobRecord {
+    client: String,
+    job: Job,
+}
+
+/// Runs aggregate queries in the background and keeps their results for collection
+pub struct JobService {
+    config: Arc<AppConfig>,
+    explorer: Arc<ExplorerService>,
+    rpc: Arc<ExternalRpcAdapter>,
+    cache: Arc<CacheMiddleware>,
+    jobs: Mutex<HashMap<String, JobRecord>>,
+}
+
+impl JobService {
+    pub fn new(
+        config: Arc<AppConfig>,
+        explorer: Arc<ExplorerService>,
+        rpc: Arc<ExternalRpcAdapter>,
+        cache: Arc<CacheMiddleware>,
+    ) -> Self {
+        Self { config, explorer, rpc, cache, jobs: Mutex::new(HashMap::new()) }
+    }
+
+    /// Start `query` for `client`; fails with `RateLimit` when the client or the server runs too many jobs
+    pub async fn submit(self: &Arc<Self>, client: &str, query: JobQuery) -> AppResult<Job> {
+        self.validate(&query)?;
+        let job = Job {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            kind: query.kind(),
+            status: JobStatus::Running,
+            created_at: Utc::now(),
+            finished_at: None,
+            result: None,
+            error: None,
+        };
+        {
+            let mut jobs = self.jobs.lock().await;
+            self.prune(&mut jobs);
+            let running = jobs.values().filter(|record| record.job.status == JobStatus::Running);
+            let (total, mine) = running.fold((0, 0), |(total, mine), record| {
+                (total + 1, mine + usize::from(record.client == client))
+            });
+            if mine >= self.config.jobs.max_jobs_per_client || total >= self.config.jobs.max_jobs {
+                return Err(AppError::RateLimit);
+            }
+            jobs.insert(job.id.clone(), JobRecord { client: client.to_string(), job: job.clone() });
+        }
+
+        let service = self.clone();
+        let id = job.id.clone();
+        tokio::spawn(async move {
+            let timeout = Duration::from_secs(service.config.jobs.timeout_seconds);
+            let result = match tokio::time::timeout(timeout, service.run(query)).await {
+                Ok(result) => result,
+                Err(_) => Err(AppError::Internal(format!("job timed out after {}s", timeout.as_secs()))),
+            };
+            service.finish(&id, result).await;
+        });
+        Ok(job)
+    }
+
+    /// Status of a job, with its result once it has succeeded; `None` for unknown or expired ids
+    pub async fn job(&self, id: &str) -> AppResult<Option<Job>> {
+        let job = {
+            let mut jobs = self.jobs.lock().await;
+            self.prune(&mut jobs);
+            match jobs.get(id) {
+                Some(record) => record.job.clone(),
+                None => return Ok(None),
+            }
+        };
+        if job.status != JobStatus::Succeeded || job.result.is_some() {
+            return Ok(Some(job));
+        }
+        let result = self
+            .cache
+            .get_cached_response(&result_key(id))
+            .await?
+            .and_then(|entry| serde_json::from_slice(&entry.data).ok());
+        Ok(Some(match result {
+            Some(result) => Job { result: Some(result), ..job },
+            None => Job { status: JobStatus::Expired, ..job },
+        }))
+    }
+
+    async fn run(&self, query: JobQuery) -> AppResult<Value> {
+        match query {
+            JobQuery::FullBlock { hash } => self.explorer.get_full_block(&hash).await,
+            JobQuery::AddressBalances { addresses } => self.explorer.get_address_balances(addresses).await,
+            JobQuery::AddressHistory { addresses, start, end } => {
+                let mut params = json!({ "addresses": addresses });
+                if let (Some(start), Some(end)) = (start, end) {
+                    params["start"] = json!(start);
+                    params["end"] = json!(end);
+                }
+                let request = RpcRequest::new(
+                    "getaddressdeltas".to_string(),
+                    Some(json!([params])),
+                    Some(json!("job_getaddressdeltas")),
+                    ClientInfo {
+                        ip_address: "127.0.0.1".to_string(),
+                        user_agent: Some("jobs".to_string()),
+                        auth_token: None,
+                        timestamp: Utc::now(),
+                        request_id: None,
+                    },
+                );
+                self.rpc
+                    .send_request(&request)
+                    .await?
+                    .result
+                    .ok_or_else(|| AppError::Rpc("getaddressdeltas returned no result".into()))
+            }
+        }
+    }
+
+    /// Record a job's outcome, moving a successful result into the cache when it is enabled
+    async fn finish(&self, id: &str, result: AppResult<Value>) {
+        let (status, result, error) = match result {
+            Ok(result) => {
+                let stored = self.config.cache.enabled && self.store_result(id, &result).await;
+                (JobStatus::Succeeded, (!stored).then_some(result), None)
+            }
+            Err(e) => (JobStatus::Failed, None, Some(e.to_string())),
+        };
+        if let Some(record) = self.jobs.lock().await.get_mut(id) {
+            record.job.status = status;
+            record.job.finished_at = Some(Utc::now());
+            record.job.result = result;
+            record.job.error = error;
+        }
+    }
+
+    async fn store_result(&self, id: &str, result: &Value) -> bool {
+        let data = serde_json::to_vec(result).unwrap_or_default();
+        let entry = self.cache.create_cache_entry(
+            result_key(id),
+            data,
+            "application/json".to_string(),
+            self.config.jobs.result_ttl_seconds,
+        );
+        match self.cache.cache_response(entry).await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Keeping job result in memory, cache write failed: {}", e);
+                false
+            }
+        }
+    }
+
+    fn validate(&self, query: &JobQuery) -> AppResult<()> {
+        let addresses = match query {
+            JobQuery::FullBlock { hash } => {
+                if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(AppError::Validation("block hash must be 64 hex characters".into()));
+                }
+                return Ok(());
+            }
+            JobQuery::AddressBalances { addresses } => addresses,
+            JobQuery::AddressHistory { addresses, start, end } => {
+                if start.is_some() != end.is_some() || start > end {
+                    return Err(AppError::Validation("start and end must be given together, start <= end".into()));
+                }
+                addresses
+            }
+        };
+        let max = self.config.explorer.max_balance_addresses;
+        if addresses.is_empty() {
+            return Err(AppError::Validation("addresses must not be empty".into()));
+        }
+        if addresses.len() > max {
+            return Err(AppError::Validation(format!("{} addresses requested, limit is {}", addresses.len(), max)));
+        }
+        Ok(())
+    }
+
+    /// Forget jobs that finished more than `result_ttl_seconds` ago
+    fn prune(&self, jobs: &mut HashMap<String, JobRecord>) {
+        let ttl = chrono::Duration::seconds(self.config.jobs.result_ttl_seconds as i64);
+        let now = Utc::now();
+        jobs.retain(|_, record| record.job.finished_at.is_none_or(|finished| now - finished < ttl));
+    }
+}
+
+fn result_key(id: &str) -> String {
+    format!("verus_rpc:job:{}", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn service(config: AppConfig) -> Arc<JobService> {
+        let cache = Arc::new(CacheMiddleware::new(&config).await.unwrap());
+        let config = Arc::new(config);
+        let rpc = Arc::new(ExternalRpcAdapter::new(config.clone()));
+        let explorer = Arc::new(ExplorerService::new(config.clone(), rpc.clone()));
+        Arc::new(JobService::new(config, explorer, rpc, cache))
+    }
+
+    #[tokio::test]
+    async fn test_submit_validates_and_limits_per_client() {
+        let mut config = AppConfig::default();
+        config.jobs.max_jobs_per_client = 1;
+        let service = service(config).await;
+        let bad_hash = JobQuery::FullBlock { hash: "xyz".to_string() };
+        assert!(matches!(service.submit("10.0.0.1", bad_hash).await, Err(AppError::Validation(_))));
+        let bad_range = JobQuery::AddressHistory { addresses: vec!["RAlice".to_string()], start: Some(10), end: None };
+        assert!(matches!(service.submit("10.0.0.1", bad_range).await, Err(AppError::Validation(_))));
+
+        let running = Job {
+            id: "a".to_string(),
+            kind: "full_block",
+            status: JobStatus::Running,
+            created_at: Utc::now(),
+            finished_at: None,
+            result: None,
+            error: None,
+        };
+        service.jobs.lock().await.insert("a".to_string(), JobRecord { client: "10.0.0.1".to_string(), job: running });
+        let query = JobQuery::AddressBalances { addresses: vec!["RAlice".to_string()] };
+        assert!(matches!(service.submit("10.0.0.1", query).await, Err(AppError::RateLimit)));
+    }
+
+    #[tokio::test]
+    async fn test_finished_job_reports_result_until_pruned() {
+        let service = service(AppConfig::default()).await;
+        let job = Job {
+            id: "b".to_string(),
+            kind: "address_balances",
+            status: JobStatus::Running,
+            created_at: Utc::now(),
+            finished_at: None,
+            result: None,
+            error: None,
+        };
+        service.jobs.lock().await.insert("b".to_string(), JobRecord { client: "10.0.0.1".to_string(), job });
+
+        service.finish("b", Ok(json!({ "total": { "balance": 5 } }))).await;
+        let job = service.job("b").await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Succeeded);
+        assert_eq!(job.result.unwrap()["total"]["balance"], 5);
+
+        service.jobs.lock().await.get_mut("b").unwrap().job.finished_at = Some(Utc::now() - chrono::Duration::days(2));
+        assert!(service.job("b").await.unwrap().is_none());
+    }
+}
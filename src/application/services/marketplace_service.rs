@@ -0,0 +1,330 @@
+//! Marketplace offer aggregation
+//!
+//! `getoffers` lists the open offers for a currency or identity, both those
+//! selling it and those bidding for it, in the daemon's raw form: each side of
+//! an offer is a currency map or an identity definition, and prices are not
+//! comparable across directions. This service resolves the target with the
+//! [`NameResolverService`], normalizes every offer to a direction (`sell` when
+//! the target is offered, `buy` when it is asked for) and a price in the
+//! other side's currency per unit of the target, and caches the result briefly
+//! so a marketplace page view costs at most one daemon call. Offers posted
+//! with `makeOffer` appear once the cached list expires.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::application::services::{NameKind, NameResolverService, Resolution};
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::pagination::{ListQuery, ListSpec, Page};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+/// Query parameters accepted by `GET /marketplace/offers/{name}`
+pub const MARKETPLACE_OFFERS_LIST: ListSpec = ListSpec {
+    sort_fields: &["price", "expiry"],
+    descending: false,
+    filters: &["direction", "quote"],
+};
+
+/// Which side of the offer the target is on
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OfferDirection {
+    /// The target is offered in exchange for the quote asset
+    Sell,
+    /// The quote asset is offered in exchange for the target
+    Buy,
+}
+
+/// One side of an offer
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OfferAsset {
+    Currency { currency: String, amount: f64 },
+    Identity { identity: String },
+}
+
+impl OfferAsset {
+    /// Parse a side of a raw offer: `{"currency": .., "amount": ..}`, `{"<currency id>": amount}` or an identity
+    fn parse(side: &Value) -> Option<Self> {
+        let side = side.as_object()?;
+        if let Some(identity) = side.get("identityaddress").and_then(Value::as_str) {
+            return Some(OfferAsset::Identity { identity: identity.to_string() });
+        }
+        if let (Some(currency), Some(amount)) = (side.get("currency").and_then(Value::as_str), side.get("amount").and_then(Value::as_f64)) {
+            return Some(OfferAsset::Currency { currency: currency.to_string(), amount });
+        }
+        match side.iter().next() {
+            Some((currency, amount)) if side.len() == 1 => {
+                Some(OfferAsset::Currency { currency: currency.clone(), amount: amount.as_f64()? })
+            }
+            _ => None,
+        }
+    }
+
+    fn id(&self) -> &str {
+        match self {
+            OfferAsset::Currency { currency, .. } => currency,
+            OfferAsset::Identity { identity } => identity,
+        }
+    }
+
+    /// Units of this asset; an identity counts as one
+    fn units(&self) -> f64 {
+        match self {
+            OfferAsset::Currency { amount, .. } => *amount,
+            OfferAsset::Identity { .. } => 1.0,
+        }
+    }
+}
+
+/// A normalized open offer
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Offer {
+    pub direction: OfferDirection,
+    /// i-address of the asset the target is traded against
+    pub quote: String,
+    /// Quote units per unit of the target; `None` when the quote side is an identity
+    pub price: Option<f64>,
+    pub offered: OfferAsset,
+    pub requested: OfferAsset,
+    /// Block after which the offer expires
+    pub expiry_height: Option<u64>,
+    pub txid: Option<String>,
+}
+
+impl Offer {
+    /// Normalize a raw `getoffers` entry against the target's i-address
+    fn normalize(entry: &Value, target: &str) -> Option<Self> {
+        let raw = entry.get("offer").unwrap_or(entry);
+        let offered = OfferAsset::parse(raw.get("offer")?)?;
+        let requested = OfferAsset::parse(raw.get("accept")?)?;
+        let (direction, target_side, quote_side) = if offered.id() == target {
+            (OfferDirection::Sell, &offered, &requested)
+        } else if requested.id() == target {
+            (OfferDirection::Buy, &requested, &offered)
+        } else {
+            return None;
+        };
+        let price = match quote_side {
+            OfferAsset::Currency { amount, .. } if target_side.units() > 0.0 => Some(amount / target_side.units()),
+            _ => None,
+        };
+        Some(Offer {
+            direction,
+            quote: quote_side.id().to_string(),
+            price,
+            expiry_height: raw.get("blockexpiry").and_then(Value::as_u64),
+            txid: raw.get("txid").and_then(Value::as_str).map(|txid| txid.to_string()),
+            offered,
+            requested,
+        })
+    }
+}
+
+/// One page of offers for a currency or identity
+#[derive(Debug, Clone, Serialize)]
+pub struct OfferPage {
+    pub target: Resolution,
+    #[serde(flatten)]
+    pub page: Page<Offer>,
+}
+
+/// Aggregates `getoffers` results for marketplace UIs
+pub struct MarketplaceService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    resolver: Arc<NameResolverService>,
+    cache: Mutex<HashMap<String, (Instant, Arc<Vec<Offer>>)>>,
+}
+
+impl MarketplaceService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>, resolver: Arc<NameResolverService>) -> Self {
+        Self { config, rpc, resolver, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Sorted, filtered page of the open offers for `name`; `None` when no currency or identity has that name
+    pub async fn offers(&self, name: &str, query: &ListQuery) -> AppResult<Option<OfferPage>> {
+        let direction = match query.filter::<String>("direction")?.as_deref() {
+            None => None,
+            Some("sell") => Some(OfferDirection::Sell),
+            Some("buy") => Some(OfferDirection::Buy),
+            Some(other) => return Err(AppError::Validation(format!("invalid direction: {}; expected buy or sell", other))),
+        };
+        let quote = query.filter::<String>("quote")?;
+        let Some(target) = self.resolver.resolve(name).await? else {
+            return Ok(None);
+        };
+        let quote = match quote {
+            Some(quote) => match self.resolver.resolve(&quote).await? {
+                Some(resolution) => Some(resolution.id),
+                None => return Err(AppError::Validation(format!("unknown quote currency: {}", quote))),
+            },
+            None => None,
+        };
+
+        let offers = self.all_offers(&target).await?;
+        let mut offers: Vec<Offer> = offers
+            .iter()
+            .filter(|offer| direction.is_none_or(|direction| offer.direction == direction))
+            .filter(|offer| quote.as_ref().is_none_or(|quote| &offer.quote == quote))
+            .cloned()
+            .collect();
+        sort_offers(&mut offers, &query.sort);
+
+        // The cursor is the offer's position in the sorted list
+        let limit = query.limit(self.config.marketplace.max_page_size.min(50), self.config.marketplace.max_page_size);
+        let indexed: Vec<(u64, Offer)> = offers.into_iter().enumerate().map(|(i, offer)| (i as u64, offer)).collect();
+        let page = query.page(indexed, limit, |(i, _)| *i)?.map(|(_, offer)| offer);
+        Ok(Some(OfferPage { target, page }))
+    }
+
+    /// Every open offer for `target`, from cache when fresh
+    async fn all_offers(&self, target: &Resolution) -> AppResult<Arc<Vec<Offer>>> {
+        let ttl = Duration::from_secs(self.config.marketplace.cache_seconds);
+        if let Some((at, offers)) = self.cache.lock().await.get(&target.id) {
+            if at.elapsed() < ttl {
+                return Ok(offers.clone());
+            }
+        }
+
+        let is_currency = target.kind == NameKind::Currency;
+        let result = self.call("getoffers", json!([target.id, is_currency, false])).await?;
+        let offers = Arc::new(normalize_offers(&result, &target.id));
+
+        let capacity = self.config.marketplace.cache_entries;
+        if !ttl.is_zero() && capacity > 0 {
+            let mut cache = self.cache.lock().await;
+            if cache.len() >= capacity {
+                cache.retain(|_, (at, _)| at.elapsed() < ttl);
+            }
+            if cache.len() < capacity {
+                cache.insert(target.id.clone(), (Instant::now(), offers.clone()));
+            }
+        }
+        Ok(offers)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("marketplace_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("marketplace".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+/// Normalize every offer in a `getoffers` result, which groups offers in arrays under descriptive keys
+fn normalize_offers(result: &Value, target: &str) -> Vec<Offer> {
+    let groups: Vec<&Value> = match result {
+        Value::Object(groups) => groups.values().collect(),
+        other => vec![other],
+    };
+    let mut seen = HashSet::new();
+    groups
+        .into_iter()
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(|entry| Offer::normalize(entry, target))
+        .filter(|offer| offer.txid.as_ref().is_none_or(|txid| seen.insert(txid.clone())))
+        .collect()
+}
+
+/// Ascending by `price` (unpriced offers last) or by `expiry`, ties broken by txid
+fn sort_offers(offers: &mut [Offer], sort: &str) {
+    offers.sort_by(|a, b| {
+        let primary = match sort {
+            "expiry" => a.expiry_height.unwrap_or(u64::MAX).cmp(&b.expiry_height.unwrap_or(u64::MAX)),
+            _ => match (a.price, b.price) {
+                (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        };
+        primary.then_with(|| a.txid.cmp(&b.txid))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VRSC: &str = "iJhCezBExJHvtyH3fGhNnt2NhU4Ztkf2yq";
+    const VETH: &str = "i9nwxtKuVYX4MSbeULLiK2ttVi6rUEhh4X";
+
+    fn raw_offers() -> Value {
+        json!({
+            "currency_iJhCezBExJHvtyH3fGhNnt2NhU4Ztkf2yq_offers_in_currencies": [
+                { "offer": { "offer": { VRSC: 100.0 }, "accept": { VETH: 2.0 }, "blockexpiry": 3100100, "txid": "a1" } },
+                { "offer": { "offer": { "currency": VRSC, "amount": 10.0 }, "accept": { "currency": VETH, "amount": 0.5 }, "blockexpiry": 3100050, "txid": "a2" } }
+            ],
+            "currency_iJhCezBExJHvtyH3fGhNnt2NhU4Ztkf2yq_offers_for_currencies": [
+                { "offer": { "offer": { VETH: 1.5 }, "accept": { VRSC: 50.0 }, "txid": "b1" } },
+                { "offer": { "offer": { VRSC: 100.0 }, "accept": { VETH: 2.0 }, "blockexpiry": 3100100, "txid": "a1" } }
+            ],
+            "currency_iJhCezBExJHvtyH3fGhNnt2NhU4Ztkf2yq_offers_for_ids": [
+                { "offer": { "offer": { VRSC: 500.0 }, "accept": { "name": "alice", "identityaddress": "iAlice" }, "txid": "c1" } }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_offers_normalized_across_directions() {
+        let mut offers = normalize_offers(&raw_offers(), VRSC);
+        assert_eq!(offers.len(), 4, "duplicate txid a1 listed once");
+        sort_offers(&mut offers, "price");
+
+        let prices: Vec<(Option<&str>, Option<f64>)> = offers.iter().map(|o| (o.txid.as_deref(), o.price)).collect();
+        assert_eq!(prices, vec![(Some("a1"), Some(0.02)), (Some("b1"), Some(0.03)), (Some("a2"), Some(0.05)), (Some("c1"), None)]);
+        assert_eq!(offers[0].direction, OfferDirection::Sell);
+        assert_eq!(offers[0].quote, VETH);
+        assert_eq!(offers[1].direction, OfferDirection::Buy);
+        assert_eq!(offers[3].requested, OfferAsset::Identity { identity: "iAlice".to_string() });
+
+        sort_offers(&mut offers, "expiry");
+        assert_eq!(offers[0].txid.as_deref(), Some("a2"));
+    }
+
+    #[tokio::test]
+    async fn test_offers_page_from_cache_with_filters() {
+        let config = Arc::new(AppConfig::default());
+        let rpc = Arc::new(ExternalRpcAdapter::new(config.clone()));
+        let resolver = Arc::new(NameResolverService::new(config.clone(), rpc.clone()));
+        let target = Resolution { kind: NameKind::Currency, name: "VRSC".to_string(), id: VRSC.to_string() };
+        resolver.remember(&target).await;
+        let service = MarketplaceService::new(config, rpc, resolver);
+        service.cache.lock().await.insert(VRSC.to_string(), (Instant::now(), Arc::new(normalize_offers(&raw_offers(), VRSC))));
+
+        let params = [("direction", "sell"), ("limit", "1")].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let query = ListQuery::parse(&params, &MARKETPLACE_OFFERS_LIST).unwrap();
+        let page = service.offers("vrsc", &query).await.unwrap().unwrap();
+        assert_eq!(page.target.id, VRSC);
+        assert_eq!(page.page.total, 3);
+        assert_eq!(page.page.items[0].txid.as_deref(), Some("a1"));
+        assert_eq!(page.page.next_cursor.as_deref(), Some("0"));
+
+        let params = [("direction", "sideways")].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let query = ListQuery::parse(&params, &MARKETPLACE_OFFERS_LIST).unwrap();
+        assert!(matches!(service.offers("vrsc", &query).await, Err(AppError::Validation(_))));
+    }
+}
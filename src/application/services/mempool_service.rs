@@ -0,0 +1,274 @@
+//! Mempool monitoring service
+//!
+//! Periodically samples `getrawmempool true` and keeps derived statistics
+//! (transaction count, total size, fee-rate percentiles, age distribution)
+//! so clients can read them without each issuing the expensive verbose call.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// Satoshis per coin, used to convert daemon fees to fee rates
+const SATS_PER_COIN: f64 = 100_000_000.0;
+
+/// Upper bounds (seconds) of the age distribution buckets
+const AGE_BUCKETS: [(&str, i64); 5] = [
+    ("lt_1m", 60),
+    ("1m_10m", 600),
+    ("10m_1h", 3600),
+    ("1h_6h", 21600),
+    ("gte_6h", i64::MAX),
+];
+
+/// Single mempool entry as tracked between samples
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolEntry {
+    /// Virtual size in bytes (falls back to `size`)
+    pub vsize: u64,
+    /// Fee in coins
+    pub fee: f64,
+    /// Time the transaction entered the mempool (unix seconds)
+    pub time: i64,
+}
+
+impl MempoolEntry {
+    /// Fee rate in sat/vB
+    pub fn fee_rate(&self) -> f64 {
+        if self.vsize == 0 {
+            0.0
+        } else {
+            self.fee * SATS_PER_COIN / self.vsize as f64
+        }
+    }
+
+    fn from_verbose(value: &Value, now: i64) -> Self {
+        let vsize = value
+            .get("vsize")
+            .or_else(|| value.get("size"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        Self {
+            vsize,
+            fee: value.get("fee").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            time: value.get("time").and_then(|v| v.as_i64()).unwrap_or(now),
+        }
+    }
+}
+
+/// Fee rate percentiles in sat/vB
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FeeRatePercentiles {
+    pub p10: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p90: f64,
+}
+
+/// Derived mempool statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolStats {
+    /// Number of transactions
+    pub tx_count: usize,
+    /// Sum of transaction virtual sizes in bytes
+    pub total_vsize: u64,
+    /// Sum of fees in coins
+    pub total_fee: f64,
+    /// Fee rate percentiles (sat/vB)
+    pub fee_rate_percentiles: FeeRatePercentiles,
+    /// Transaction counts per age bucket
+    pub age_distribution: HashMap<String, usize>,
+    /// Age of the oldest transaction in seconds
+    pub oldest_age_seconds: i64,
+    /// Transactions added since the previous sample
+    pub added_since_last_sample: usize,
+    /// Transactions removed since the previous sample
+    pub removed_since_last_sample: usize,
+    /// When the sample was taken
+    pub sampled_at: DateTime<Utc>,
+}
+
+impl MempoolStats {
+    /// Compute statistics from tracked entries
+    pub fn from_entries(entries: &HashMap<String, MempoolEntry>, now: DateTime<Utc>, added: usize, removed: usize) -> Self {
+        let mut fee_rates: Vec<f64> = entries.values().map(|e| e.fee_rate()).collect();
+        fee_rates.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut age_distribution: HashMap<String, usize> =
+            AGE_BUCKETS.iter().map(|(name, _)| (name.to_string(), 0)).collect();
+        let mut oldest_age_seconds = 0;
+        for entry in entries.values() {
+            let age = (now.timestamp() - entry.time).max(0);
+            oldest_age_seconds = oldest_age_seconds.max(age);
+            if let Some((name, _)) = AGE_BUCKETS.iter().find(|(_, upper)| age < *upper) {
+                *age_distribution.entry(name.to_string()).or_insert(0) += 1;
+            }
+        }
+
+        Self {
+            tx_count: entries.len(),
+            total_vsize: entries.values().map(|e| e.vsize).sum(),
+            total_fee: entries.values().map(|e| e.fee).sum(),
+            fee_rate_percentiles: FeeRatePercentiles {
+                p10: percentile(&fee_rates, 10.0),
+                p25: percentile(&fee_rates, 25.0),
+                p50: percentile(&fee_rates, 50.0),
+                p75: percentile(&fee_rates, 75.0),
+                p90: percentile(&fee_rates, 90.0),
+            },
+            age_distribution,
+            oldest_age_seconds,
+            added_since_last_sample: added,
+            removed_since_last_sample: removed,
+            sampled_at: now,
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Mempool monitoring service with a cached, periodically refreshed snapshot
+pub struct MempoolService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    entries: RwLock<HashMap<String, MempoolEntry>>,
+    stats: RwLock<Option<MempoolStats>>,
+}
+
+impl MempoolService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        Self {
+            config,
+            rpc,
+            entries: RwLock::new(HashMap::new()),
+            stats: RwLock::new(None),
+        }
+    }
+
+    /// Latest computed statistics, if a sample has been taken
+    pub async fn stats(&self) -> Option<MempoolStats> {
+        self.stats.read().await.clone()
+    }
+
+    /// Sample the daemon mempool and update the cached statistics
+    pub async fn refresh(&self) -> AppResult<MempoolStats> {
+        let request = RpcRequest::new(
+            "getrawmempool".to_string(),
+            Some(json!([true])),
+            Some(json!("mempool_sampler")),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("mempool-sampler".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        let response = self.rpc.send_request(&request).await?;
+        let sample = response
+            .result
+            .and_then(|v| v.as_object().cloned())
+            .ok_or_else(|| AppError::Rpc("getrawmempool returned invalid result".into()))?;
+
+        Ok(self.apply_sample(&sample, Utc::now()).await)
+    }
+
+    /// Merge a verbose mempool sample into tracked entries and recompute stats
+    async fn apply_sample(&self, sample: &serde_json::Map<String, Value>, now: DateTime<Utc>) -> MempoolStats {
+        let mut entries = self.entries.write().await;
+        let before = entries.len();
+        entries.retain(|txid, _| sample.contains_key(txid));
+        let removed = before - entries.len();
+
+        let mut added = 0;
+        for (txid, value) in sample {
+            if !entries.contains_key(txid) {
+                entries.insert(txid.clone(), MempoolEntry::from_verbose(value, now.timestamp()));
+                added += 1;
+            }
+        }
+
+        let stats = MempoolStats::from_entries(&entries, now, added, removed);
+        *self.stats.write().await = Some(stats.clone());
+        stats
+    }
+
+    /// Spawn the background sampler
+    pub fn start_sampler(self: Arc<Self>) {
+        let interval = std::time::Duration::from_secs(self.config.mempool.sample_interval_seconds.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.refresh().await {
+                    Ok(stats) => debug!(tx_count = stats.tx_count, "Mempool sample updated"),
+                    Err(e) => warn!("Mempool sampling failed: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_service() -> MempoolService {
+        let config = Arc::new(AppConfig::default());
+        let rpc = Arc::new(ExternalRpcAdapter::new(config.clone()));
+        MempoolService::new(config, rpc)
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+        assert_eq!(percentile(&values, 50.0), 5.0);
+        assert_eq!(percentile(&values, 90.0), 9.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_apply_sample_tracks_added_and_removed() {
+        let service = create_test_service();
+        let now = Utc::now();
+        let ts = now.timestamp();
+
+        let first = json!({
+            "aa": { "size": 250, "fee": 0.0001, "time": ts - 30 },
+            "bb": { "size": 500, "fee": 0.0005, "time": ts - 7200 }
+        });
+        let stats = service.apply_sample(first.as_object().unwrap(), now).await;
+        assert_eq!(stats.tx_count, 2);
+        assert_eq!(stats.total_vsize, 750);
+        assert_eq!(stats.added_since_last_sample, 2);
+        assert_eq!(stats.age_distribution["lt_1m"], 1);
+        assert_eq!(stats.age_distribution["1h_6h"], 1);
+        assert_eq!(stats.fee_rate_percentiles.p90, 100.0);
+
+        let second = json!({
+            "bb": { "size": 500, "fee": 0.0005, "time": ts - 7200 },
+            "cc": { "size": 100, "fee": 0.00001, "time": ts }
+        });
+        let stats = service.apply_sample(second.as_object().unwrap(), now).await;
+        assert_eq!(stats.tx_count, 2);
+        assert_eq!(stats.added_since_last_sample, 1);
+        assert_eq!(stats.removed_since_last_sample, 1);
+        assert!(service.stats().await.is_some());
+    }
+}
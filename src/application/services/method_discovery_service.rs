@@ -0,0 +1,284 @@
+//! Method discovery from the daemon's `help` output
+//!
+//! With `[method_discovery] enabled`, startup calls `help` and compares the
+//! daemon's methods with the registry. Methods only the daemon knows (new in
+//! a daemon upgrade) and methods only the registry knows (removed or renamed
+//! upstream) are logged. With `auto_register`, unknown methods that look
+//! read-only are served until the registry is updated. A method qualifies
+//! when its name starts with a read-only verb (`get`, `list`, `decode`,
+//! `estimate`, `verify`, `validate`), it is not listed under a wallet
+//! section, and its name mentions no keys. Auto-registered methods take any
+//! JSON parameters up to `max_params`. They need the `read` permission and
+//! are rate limited at the medium security level.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::application::services::rpc::dry_run;
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::domain::validation::{
+    MethodCategory, MethodRegistry, ParameterType, ParameterValidationRule, RpcMethodDefinition, SecurityLevel,
+};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+/// Name prefixes of methods considered read-only
+const READ_ONLY_PREFIXES: &[&str] = &["get", "list", "decode", "estimate", "verify", "validate"];
+
+/// Name fragments that rule a method out of auto-registration
+const DENIED_FRAGMENTS: &[&str] = &["new", "priv", "key", "dump", "export", "import", "seed"];
+
+/// Help sections never auto-registered from
+const DENIED_SECTIONS: &[&str] = &["wallet", "disclosure", "hidden"];
+
+/// One method listed by `help`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredMethod {
+    pub name: String,
+    /// `== Section ==` heading the method was listed under, lowercased
+    pub section: String,
+}
+
+/// Differences between the daemon and the registry
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DiscoveryReport {
+    /// Listed by the daemon, not registered
+    pub unknown: Vec<String>,
+    /// Registered, not listed by the daemon
+    pub missing: Vec<String>,
+    /// Unknown methods served with conservative defaults
+    pub registered: Vec<String>,
+}
+
+pub struct MethodDiscoveryService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+}
+
+impl MethodDiscoveryService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        Self { config, rpc }
+    }
+
+    /// Diff the daemon's methods against the registry, log the result and
+    /// auto-register eligible methods when configured
+    pub async fn run(&self) -> AppResult<DiscoveryReport> {
+        let help = self.call("help").await?;
+        let text = help
+            .as_str()
+            .ok_or_else(|| AppError::Rpc("help returned no text".to_string()))?;
+        let discovered = parse_help(text);
+        if discovered.is_empty() {
+            return Err(AppError::Rpc("help listed no methods".to_string()));
+        }
+
+        let registry = MethodRegistry::new();
+        let mut report = diff(&registry, &discovered);
+        if !report.unknown.is_empty() {
+            warn!(count = report.unknown.len(), methods = ?report.unknown, "Daemon methods missing from the registry");
+        }
+        if !report.missing.is_empty() {
+            warn!(count = report.missing.len(), methods = ?report.missing, "Registered methods the daemon does not list");
+        }
+
+        let discovery = &self.config.method_discovery;
+        if discovery.auto_register {
+            let definitions: Vec<RpcMethodDefinition> = discovered
+                .iter()
+                .filter(|method| report.unknown.contains(&method.name))
+                .filter(|method| !discovery.exclude.contains(&method.name))
+                .filter(|method| is_auto_registrable(method))
+                .map(|method| conservative_definition(method, discovery.max_params))
+                .collect();
+            report.registered = definitions.iter().map(|definition| definition.name.clone()).collect();
+            if !MethodRegistry::install_discovered(definitions) {
+                warn!("Discovered methods were already installed; keeping the first set");
+                report.registered.clear();
+            } else if !report.registered.is_empty() {
+                info!(methods = ?report.registered, "Auto-registered read-only daemon methods");
+            }
+        }
+
+        info!(
+            discovered = discovered.len(),
+            unknown = report.unknown.len(),
+            missing = report.missing.len(),
+            registered = report.registered.len(),
+            "Method discovery finished"
+        );
+        Ok(report)
+    }
+
+    async fn call(&self, method: &str) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(json!([])),
+            Some(json!(format!("method_discovery_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("method-discovery".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+/// Methods listed by `help`, in order
+///
+/// Each non-heading line starts with the method name, followed by its argument synopsis.
+pub fn parse_help(text: &str) -> Vec<DiscoveredMethod> {
+    let mut section = String::new();
+    let mut seen = HashSet::new();
+    let mut methods = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(heading) = line.strip_prefix("==").and_then(|l| l.strip_suffix("==")) {
+            section = heading.trim().to_lowercase();
+            continue;
+        }
+        let Some(name) = line.split_whitespace().next() else {
+            continue;
+        };
+        let valid = name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if valid && seen.insert(name.to_string()) {
+            methods.push(DiscoveredMethod { name: name.to_string(), section: section.clone() });
+        }
+    }
+    methods
+}
+
+/// Compare discovered methods with the registry
+///
+/// Methods the proxy answers itself are never reported missing.
+pub fn diff(registry: &MethodRegistry, discovered: &[DiscoveredMethod]) -> DiscoveryReport {
+    let listed: HashSet<&str> = discovered.iter().map(|method| method.name.as_str()).collect();
+    let mut unknown: Vec<String> = discovered
+        .iter()
+        .filter(|method| registry.get_method(&method.name).is_none())
+        .map(|method| method.name.clone())
+        .collect();
+    let mut missing: Vec<String> = registry
+        .list_methods()
+        .map(|definition| definition.name.as_str())
+        .filter(|name| !listed.contains(name) && *name != dry_run::METHOD)
+        .map(str::to_string)
+        .collect();
+    unknown.sort();
+    missing.sort();
+    DiscoveryReport { unknown, missing, registered: Vec::new() }
+}
+
+/// Whether an unknown method looks safe to serve without review
+pub fn is_auto_registrable(method: &DiscoveredMethod) -> bool {
+    READ_ONLY_PREFIXES.iter().any(|prefix| method.name.starts_with(prefix))
+        && !DENIED_FRAGMENTS.iter().any(|fragment| method.name.contains(fragment))
+        && !DENIED_SECTIONS.contains(&method.section.as_str())
+}
+
+/// Registry category for a `help` section
+fn section_category(section: &str) -> MethodCategory {
+    match section {
+        "addressindex" => MethodCategory::AddressIndex,
+        "identity" | "vdxf" => MethodCategory::Identity,
+        "multichain" | "crosschain" | "marketplace" => MethodCategory::Currency,
+        "mining" | "generating" => MethodCategory::Mining,
+        "wallet" | "disclosure" => MethodCategory::WalletZ,
+        "util" | "network" | "control" => MethodCategory::Utility,
+        _ => MethodCategory::Blockchain,
+    }
+}
+
+/// Read-only definition accepting up to `max_params` parameters of any type
+pub fn conservative_definition(method: &DiscoveredMethod, max_params: usize) -> RpcMethodDefinition {
+    RpcMethodDefinition {
+        name: method.name.clone(),
+        description: format!("Discovered from daemon help ({})", method.section),
+        read_only: true,
+        category: section_category(&method.section),
+        required_permissions: vec!["read".to_string()],
+        parameter_rules: (0..max_params)
+            .map(|index| ParameterValidationRule {
+                index,
+                name: format!("param{}", index),
+                param_type: ParameterType::Any,
+                required: false,
+                constraints: vec![],
+                default_value: None,
+            })
+            .collect(),
+        security_level: SecurityLevel::Medium,
+        enabled: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELP: &str = "== Addressindex ==\n\
+        getaddressbalance\n\
+        \n\
+        == Blockchain ==\n\
+        getblockcount\n\
+        getblock \"hash|height\" ( verbosity )\n\
+        getnewchaindata \"name\"\n\
+        \n\
+        == Multichain ==\n\
+        getcurrencyconverters2 \"currency\"\n\
+        \n\
+        == Wallet ==\n\
+        getbalance ( \"account\" minconf )\n\
+        z_getbalance \"address\"\n";
+
+    fn method(name: &str, section: &str) -> DiscoveredMethod {
+        DiscoveredMethod { name: name.to_string(), section: section.to_string() }
+    }
+
+    #[test]
+    fn test_parse_help_sections() {
+        let methods = parse_help(HELP);
+        assert_eq!(methods.len(), 7);
+        assert_eq!(methods[0], method("getaddressbalance", "addressindex"));
+        assert_eq!(methods[2], method("getblock", "blockchain"));
+        assert_eq!(methods[6], method("z_getbalance", "wallet"));
+    }
+
+    #[test]
+    fn test_diff_reports_unknown_and_missing() {
+        let registry = MethodRegistry::new();
+        let report = diff(&registry, &parse_help(HELP));
+        assert!(report.unknown.contains(&"getcurrencyconverters2".to_string()));
+        assert!(report.unknown.contains(&"getnewchaindata".to_string()));
+        assert!(!report.unknown.contains(&"getblockcount".to_string()));
+        assert!(report.missing.contains(&"getinfo".to_string()));
+        assert!(!report.missing.contains(&"getblock".to_string()));
+        assert!(!report.missing.contains(&dry_run::METHOD.to_string()));
+    }
+
+    #[test]
+    fn test_only_read_only_methods_auto_register() {
+        assert!(is_auto_registrable(&method("getcurrencyconverters2", "multichain")));
+        assert!(!is_auto_registrable(&method("getnewchaindata", "blockchain")));
+        assert!(!is_auto_registrable(&method("sendcurrency2", "multichain")));
+        assert!(!is_auto_registrable(&method("getbalance", "wallet")));
+        assert!(!is_auto_registrable(&method("getspendingkey", "util")));
+
+        let definition = conservative_definition(&method("getcurrencyconverters2", "multichain"), 2);
+        assert!(definition.read_only);
+        assert_eq!(definition.category, MethodCategory::Currency);
+        assert_eq!(definition.required_permissions, vec!["read".to_string()]);
+        assert_eq!(definition.parameter_rules.len(), 2);
+        assert!(definition.parameter_rules.iter().all(|rule| !rule.required));
+    }
+}
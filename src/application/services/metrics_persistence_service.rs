@@ -0,0 +1,199 @@
+//! Metrics snapshots and statsd/graphite push
+//!
+//! Request counters live in memory, so a restart zeroes every dashboard built
+//! on them. With `[metrics_persistence] snapshot_enabled = true` the counters
+//! are written to disk periodically and added back on start; with
+//! `push_enabled = true` they are sent as absolute gauges to statsd (UDP) or
+//! graphite (TCP). Prometheus pull is unaffected either way.
+
+use crate::{
+    application::services::metrics_service::{MetricsCounters, MetricsService},
+    config::{app_config::MetricsPushFormat, AppConfig},
+    shared::error::{AppError, AppResult},
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, info, warn};
+
+/// Contents of the snapshot file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    pub counters: MetricsCounters,
+}
+
+/// Writes and restores metrics snapshots and pushes metrics to statsd/graphite
+pub struct MetricsPersistenceService {
+    config: Arc<AppConfig>,
+    metrics: Arc<MetricsService>,
+}
+
+impl MetricsPersistenceService {
+    /// Create a new persistence service for `metrics`
+    pub fn new(config: Arc<AppConfig>, metrics: Arc<MetricsService>) -> Self {
+        Self { config, metrics }
+    }
+
+    /// Add the counters from the last snapshot; `Ok(false)` when there is none
+    pub fn restore(&self) -> AppResult<bool> {
+        let path = Path::new(&self.config.metrics_persistence.snapshot_path);
+        if !self.config.metrics_persistence.snapshot_enabled || !path.exists() {
+            return Ok(false);
+        }
+        let data = std::fs::read(path)
+            .map_err(|e| AppError::Internal(format!("reading metrics snapshot {}: {}", path.display(), e)))?;
+        let snapshot: MetricsSnapshot = serde_json::from_slice(&data).map_err(|e| AppError::Json(e.to_string()))?;
+        self.metrics.restore(&snapshot.counters);
+        info!(saved_at = %snapshot.saved_at, total_requests = snapshot.counters.total_requests, "Restored metrics snapshot");
+        Ok(true)
+    }
+
+    /// Write the current counters to the snapshot file (via a temporary file, so a crash cannot truncate it)
+    pub async fn write_snapshot(&self) -> AppResult<()> {
+        let path = Path::new(&self.config.metrics_persistence.snapshot_path);
+        let snapshot = MetricsSnapshot { saved_at: chrono::Utc::now(), counters: self.metrics.counters() };
+        let data = serde_json::to_vec_pretty(&snapshot).map_err(|e| AppError::Json(e.to_string()))?;
+        let io_error = |e: std::io::Error| AppError::Internal(format!("writing metrics snapshot {}: {}", path.display(), e));
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(dir).await.map_err(io_error)?;
+        }
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, data).await.map_err(io_error)?;
+        tokio::fs::rename(&tmp, path).await.map_err(io_error)
+    }
+
+    /// Metric lines in the configured push format
+    pub fn push_payload(&self, timestamp: i64) -> String {
+        let settings = &self.config.metrics_persistence;
+        let counters = self.metrics.counters();
+        let avg_response_time_ms = counters.total_response_time_ms.checked_div(counters.response_count).unwrap_or(0);
+        let values = [
+            ("requests.total", counters.total_requests),
+            ("requests.successful", counters.successful_requests),
+            ("requests.failed", counters.failed_requests),
+            ("requests.rate_limited", counters.rate_limited_requests),
+            ("response_time.avg_ms", avg_response_time_ms),
+        ];
+        values
+            .iter()
+            .map(|(name, value)| {
+                let name = if settings.push_prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}.{}", settings.push_prefix, name)
+                };
+                match settings.push_format {
+                    MetricsPushFormat::Statsd => format!("{}:{}|g\n", name, value),
+                    MetricsPushFormat::Graphite => format!("{} {} {}\n", name, value, timestamp),
+                }
+            })
+            .collect()
+    }
+
+    /// Send the current metrics to the push endpoint
+    pub async fn push(&self) -> AppResult<()> {
+        let settings = &self.config.metrics_persistence;
+        let payload = self.push_payload(chrono::Utc::now().timestamp());
+        let io_error = |e: std::io::Error| AppError::Internal(format!("pushing metrics to {}: {}", settings.push_address, e));
+        match settings.push_format {
+            MetricsPushFormat::Statsd => {
+                let socket = tokio::net::UdpSocket::bind("0.0.0.0:0").await.map_err(io_error)?;
+                socket.send_to(payload.as_bytes(), settings.push_address.as_str()).await.map_err(io_error)?;
+            }
+            MetricsPushFormat::Graphite => {
+                let mut stream = tokio::net::TcpStream::connect(settings.push_address.as_str()).await.map_err(io_error)?;
+                stream.write_all(payload.as_bytes()).await.map_err(io_error)?;
+                stream.shutdown().await.map_err(io_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Spawn the snapshot and push loops that are enabled
+    pub fn start(self: Arc<Self>) {
+        let settings = &self.config.metrics_persistence;
+        if settings.snapshot_enabled {
+            let service = self.clone();
+            let interval = Duration::from_secs(settings.snapshot_interval_seconds.max(1));
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                ticker.tick().await;
+                loop {
+                    ticker.tick().await;
+                    match service.write_snapshot().await {
+                        Ok(()) => debug!("Metrics snapshot written"),
+                        Err(e) => warn!("Metrics snapshot failed: {}", e),
+                    }
+                }
+            });
+        }
+        if settings.push_enabled {
+            let service = self.clone();
+            let interval = Duration::from_secs(settings.push_interval_seconds.max(1));
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = service.push().await {
+                        warn!("Metrics push failed: {}", e);
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(config: AppConfig) -> MetricsPersistenceService {
+        MetricsPersistenceService::new(Arc::new(config), Arc::new(MetricsService::new()))
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let mut config = AppConfig::default();
+        config.metrics_persistence.snapshot_enabled = true;
+        config.metrics_persistence.snapshot_path = std::env::temp_dir()
+            .join(format!("verus-metrics-{}", uuid::Uuid::new_v4()))
+            .join("snapshot.json")
+            .display()
+            .to_string();
+
+        let before = service(config.clone());
+        assert!(!before.restore().unwrap());
+        before.metrics.record_request(true);
+        before.metrics.record_request(false);
+        before.metrics.record_response_time(40);
+        before.write_snapshot().await.unwrap();
+
+        let after = service(config.clone());
+        after.metrics.record_request(true);
+        assert!(after.restore().unwrap());
+        let counters = after.metrics.counters();
+        assert_eq!((counters.total_requests, counters.successful_requests, counters.failed_requests), (3, 2, 1));
+        assert_eq!(counters.total_response_time_ms, 40);
+
+        let path = Path::new(&config.metrics_persistence.snapshot_path);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_push_payload_formats() {
+        let mut config = AppConfig::default();
+        let statsd = service(config.clone());
+        statsd.metrics.record_request(true);
+        let payload = statsd.push_payload(1700000000);
+        assert!(payload.starts_with("verus_rpc.requests.total:1|g\n"));
+        assert!(payload.contains("verus_rpc.requests.failed:0|g\n"));
+
+        config.metrics_persistence.push_format = MetricsPushFormat::Graphite;
+        config.metrics_persistence.push_prefix = String::new();
+        let graphite = service(config);
+        assert!(graphite.push_payload(1700000000).starts_with("requests.total 0 1700000000\n"));
+    }
+}
@@ -1,7 +1,19 @@
 //! Metrics service for collecting application metrics
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// Cumulative request counters, as carried across restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsCounters {
+    pub total_requests: u64,
+    pub successful_requests: u64,
+    pub failed_requests: u64,
+    pub rate_limited_requests: u64,
+    pub total_response_time_ms: u64,
+    pub response_count: u64,
+}
+
 /// Metrics service for collecting application metrics
 pub struct MetricsService {
     total_requests: std::sync::atomic::AtomicU64,
@@ -11,6 +23,8 @@ pub struct MetricsService {
     total_response_time: std::sync::atomic::AtomicU64,
     response_count: std::sync::atomic::AtomicU64,
     active_connections: std::sync::atomic::AtomicU32,
+    /// Requests carried over by `restore`, left out of the request rate
+    restored_requests: std::sync::atomic::AtomicU64,
     start_time: std::time::Instant,
 }
 
@@ -25,6 +39,7 @@ impl MetricsService {
             total_response_time: std::sync::atomic::AtomicU64::new(0),
             response_count: std::sync::atomic::AtomicU64::new(0),
             active_connections: std::sync::atomic::AtomicU32::new(0),
+            restored_requests: std::sync::atomic::AtomicU64::new(0),
             start_time: std::time::Instant::now(),
         }
     }
@@ -61,6 +76,29 @@ impl MetricsService {
         self.active_connections.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// Current cumulative counters
+    pub fn counters(&self) -> MetricsCounters {
+        MetricsCounters {
+            total_requests: self.total_requests.load(std::sync::atomic::Ordering::Relaxed),
+            successful_requests: self.successful_requests.load(std::sync::atomic::Ordering::Relaxed),
+            failed_requests: self.failed_requests.load(std::sync::atomic::Ordering::Relaxed),
+            rate_limited_requests: self.rate_limited_requests.load(std::sync::atomic::Ordering::Relaxed),
+            total_response_time_ms: self.total_response_time.load(std::sync::atomic::Ordering::Relaxed),
+            response_count: self.response_count.load(std::sync::atomic::Ordering::Relaxed),
+        }
+    }
+
+    /// Add counters carried over from a previous run
+    pub fn restore(&self, counters: &MetricsCounters) {
+        self.total_requests.fetch_add(counters.total_requests, std::sync::atomic::Ordering::Relaxed);
+        self.restored_requests.fetch_add(counters.total_requests, std::sync::atomic::Ordering::Relaxed);
+        self.successful_requests.fetch_add(counters.successful_requests, std::sync::atomic::Ordering::Relaxed);
+        self.failed_requests.fetch_add(counters.failed_requests, std::sync::atomic::Ordering::Relaxed);
+        self.rate_limited_requests.fetch_add(counters.rate_limited_requests, std::sync::atomic::Ordering::Relaxed);
+        self.total_response_time.fetch_add(counters.total_response_time_ms, std::sync::atomic::Ordering::Relaxed);
+        self.response_count.fetch_add(counters.response_count, std::sync::atomic::Ordering::Relaxed);
+    }
+
     /// Get current metrics
     pub fn get_metrics(&self) -> Value {
         let total = self.total_requests.load(std::sync::atomic::Ordering::Relaxed);
@@ -71,6 +109,8 @@ impl MetricsService {
         let response_count = self.response_count.load(std::sync::atomic::Ordering::Relaxed);
         let active_connections = self.active_connections.load(std::sync::atomic::Ordering::Relaxed);
         let uptime = self.start_time.elapsed().as_secs();
+        let requests_this_run = total.saturating_sub(self.restored_requests.load(std::sync::atomic::Ordering::Relaxed));
+        let requests_per_minute = requests_this_run as f64 * 60.0 / self.start_time.elapsed().as_secs_f64().max(1.0);
 
         let avg_response_time_ms = if response_count > 0 {
             total_response_time as f64 / response_count as f64
@@ -86,6 +126,7 @@ impl MetricsService {
             "avg_response_time_ms": avg_response_time_ms,
             "active_connections": active_connections,
             "uptime_seconds": uptime,
+            "requests_per_minute": (requests_per_minute * 100.0).round() / 100.0,
         })
     }
 }
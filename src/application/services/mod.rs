@@ -3,9 +3,55 @@
 pub mod rpc_service;
 pub mod rpc;
 pub mod metrics_service;
+pub mod metrics_persistence_service;
+pub mod health_history_service;
 pub mod payments_service;
+pub mod address_pool_service;
+pub mod viewing_key_service;
+pub mod mempool_service;
+pub mod explorer_service;
+pub mod currency_history_service;
+pub mod conversion_service;
+pub mod name_resolver_service;
+pub mod marketplace_service;
+pub mod shielded_tree_service;
+pub mod portfolio_service;
+pub mod job_service;
+pub mod report_service;
+pub mod chain_monitor_service;
+pub mod tx_tracking_service;
+#[cfg(feature = "indexer")]
+pub mod address_index_service;
+pub mod preflight_service;
+pub mod method_discovery_service;
+pub mod fixture_capture_service;
+pub mod capability_service;
+pub mod request_scheduler;
 
 pub use rpc_service::RpcService;
 pub use metrics_service::MetricsService;
+pub use metrics_persistence_service::MetricsPersistenceService;
+pub use health_history_service::{ComponentState, HealthHistory, HealthHistoryService, HealthTransition};
+pub use address_pool_service::AddressPoolService;
+pub use viewing_key_service::{ViewingKeyRecord, ViewingKeyRegistry, ViewingKeyService, ViewingKeyStatus};
+pub use mempool_service::MempoolService;
+pub use explorer_service::ExplorerService;
+pub use currency_history_service::{CurrencyHistoryService, CurrencyHistory, CurrencySnapshot, CURRENCY_HISTORY_LIST};
+pub use conversion_service::{ConversionBatch, ConversionEstimate, ConversionRequest, ConversionService};
+pub use name_resolver_service::{NameKind, NameResolverService, Resolution};
+pub use marketplace_service::{MarketplaceService, Offer, OfferAsset, OfferDirection, OfferPage, MARKETPLACE_OFFERS_LIST};
+pub use shielded_tree_service::{ShieldedTreeService, TreeState, SHIELDED_TREE_LIST};
+pub use portfolio_service::{CurrencyBalance, Portfolio, PortfolioService};
+pub use job_service::{Job, JobQuery, JobService, JobStatus};
+pub use report_service::{Report, ReportService};
+#[cfg(feature = "indexer")]
+pub use address_index_service::{AddressIndexService, AddressTx, AddressTxPage, ADDRESS_TXS_LIST};
+pub use chain_monitor_service::{BlockRef, ChainEvent, ChainEventBus, ChainMonitorService, ReorgEvent};
+pub use tx_tracking_service::{TrackTxRequest, TrackedTx, TrackingStatus, TxStatusEvent, TxTrackingService};
+pub use preflight_service::{PreflightService, PreflightReport};
+pub use method_discovery_service::{DiscoveryReport, MethodDiscoveryService};
+pub use fixture_capture_service::{CaptureFailure, CaptureReport, FixtureCaptureService};
+pub use capability_service::{CapabilityService, DaemonCapabilities};
+pub use request_scheduler::{RequestScheduler, RequestPriority, SchedulerPermit, SchedulerStats};
 
 
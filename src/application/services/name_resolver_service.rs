@@ -0,0 +1,249 @@
+//! Currency and identity name resolution
+//!
+//! Frontends show friendly names (`VRSC`, `Bridge.vETH`, `alice@`) but the
+//! daemon and most APIs speak i-addresses. The resolver maps either form to
+//! the other through `getcurrency` and `getidentity`, caching both directions.
+//! A name is tried as a currency first, then as an identity; names ending in
+//! `@` are identities only. Resolutions are dropped after a reorg, since the
+//! orphaned blocks may have defined the currency or registered the identity.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+/// What a name resolved to
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NameKind {
+    Currency,
+    Identity,
+}
+
+/// A name and the i-address it maps to
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Resolution {
+    pub kind: NameKind,
+    /// Fully qualified name, e.g. `Bridge.vETH` or `alice.VRSC@`
+    pub name: String,
+    /// i-address
+    pub id: String,
+}
+
+/// Cached resolutions keyed by lowercase name and by i-address, oldest evicted first
+struct ResolutionCache {
+    entries: HashMap<String, (Instant, Resolution)>,
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl ResolutionCache {
+    fn new(capacity: usize) -> Self {
+        Self { entries: HashMap::new(), order: VecDeque::new(), capacity }
+    }
+
+    fn get(&self, key: &str, ttl: Duration) -> Option<Resolution> {
+        self.entries
+            .get(key)
+            .filter(|(at, _)| at.elapsed() < ttl)
+            .map(|(_, resolution)| resolution.clone())
+    }
+
+    fn insert(&mut self, key: String, resolution: Resolution) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), (Instant::now(), resolution)).is_some() {
+            return;
+        }
+        self.order.push_back(key);
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => { self.entries.remove(&oldest); }
+                None => break,
+            }
+        }
+    }
+
+    fn clear(&mut self) -> usize {
+        let removed = self.entries.len();
+        self.entries.clear();
+        self.order.clear();
+        removed
+    }
+}
+
+/// Resolves currency and identity names to i-addresses and back
+pub struct NameResolverService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    cache: Mutex<ResolutionCache>,
+}
+
+impl NameResolverService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        let cache = Mutex::new(ResolutionCache::new(config.name_resolver.cache_entries));
+        Self { config, rpc, cache }
+    }
+
+    /// Resolve a friendly name or i-address; `None` when neither a currency nor an identity matches
+    pub async fn resolve(&self, query: &str) -> AppResult<Option<Resolution>> {
+        let query = query.trim();
+        if !is_plausible_name(query) {
+            return Err(AppError::Validation(format!("invalid name: {}", query)));
+        }
+        let key = cache_key(query);
+        let ttl = Duration::from_secs(self.config.name_resolver.cache_seconds);
+        if let Some(resolution) = self.cache.lock().await.get(&key, ttl) {
+            return Ok(Some(resolution));
+        }
+
+        let resolution = match query.strip_suffix('@') {
+            Some(_) => self.identity(query).await?,
+            None => match self.currency(query).await? {
+                Some(currency) => Some(currency),
+                None if is_i_address(query) => self.identity(query).await?,
+                None => self.identity(&format!("{}@", query)).await?,
+            },
+        };
+        if let Some(resolution) = &resolution {
+            if !ttl.is_zero() {
+                self.cache.lock().await.insert(key, resolution.clone());
+                self.remember(resolution).await;
+            }
+        }
+        Ok(resolution)
+    }
+
+    /// Cache a known resolution under its name and i-address
+    pub async fn remember(&self, resolution: &Resolution) {
+        let mut cache = self.cache.lock().await;
+        cache.insert(cache_key(&resolution.name), resolution.clone());
+        cache.insert(cache_key(&resolution.id), resolution.clone());
+    }
+
+    /// Drop every cached resolution; returns how many entries were removed
+    pub async fn invalidate(&self) -> usize {
+        self.cache.lock().await.clear()
+    }
+
+    async fn currency(&self, query: &str) -> AppResult<Option<Resolution>> {
+        let Some(currency) = self.lookup("getcurrency", query).await? else {
+            return Ok(None);
+        };
+        let id = currency.get("currencyid").and_then(Value::as_str);
+        let name = currency
+            .get("fullyqualifiedname")
+            .or_else(|| currency.get("name"))
+            .and_then(Value::as_str);
+        Ok(id.zip(name).map(|(id, name)| Resolution {
+            kind: NameKind::Currency,
+            name: name.to_string(),
+            id: id.to_string(),
+        }))
+    }
+
+    async fn identity(&self, query: &str) -> AppResult<Option<Resolution>> {
+        let Some(result) = self.lookup("getidentity", query).await? else {
+            return Ok(None);
+        };
+        let identity = result.get("identity").unwrap_or(&result);
+        let id = identity.get("identityaddress").and_then(Value::as_str);
+        let name = result
+            .get("fullyqualifiedname")
+            .and_then(Value::as_str)
+            .map(|name| name.to_string())
+            .or_else(|| identity.get("name").and_then(Value::as_str).map(|name| format!("{}@", name)));
+        Ok(id.zip(name).map(|(id, name)| Resolution {
+            kind: NameKind::Identity,
+            name,
+            id: id.to_string(),
+        }))
+    }
+
+    /// Daemon result for `method`, or `None` when it does not know the name
+    async fn lookup(&self, method: &str, query: &str) -> AppResult<Option<Value>> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(json!([query])),
+            Some(json!(format!("resolver_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("name-resolver".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        match self.rpc.send_request(&request).await {
+            Ok(response) => Ok(response.result.filter(|result| !result.is_null())),
+            Err(e) if is_unknown_name(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn cache_key(name: &str) -> String {
+    name.to_lowercase()
+}
+
+/// Friendly names and i-addresses: letters, digits and `.`, `-`, `_`, `@`
+fn is_plausible_name(name: &str) -> bool {
+    (1..=128).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '-' | '_'))
+}
+
+/// Base58 address with the i-address prefix
+fn is_i_address(name: &str) -> bool {
+    name.len() == 34 && name.starts_with('i') && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Daemon errors meaning the name is unknown (`RPC_INVALID_ADDRESS_OR_KEY`, `RPC_INVALID_PARAMETER`)
+fn is_unknown_name(error: &AppError) -> bool {
+    let message = error.to_string().replace(' ', "");
+    message.contains("\"code\":-5") || message.contains("\"code\":-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolution(name: &str, id: &str) -> Resolution {
+        Resolution { kind: NameKind::Currency, name: name.to_string(), id: id.to_string() }
+    }
+
+    #[test]
+    fn test_cache_expires_and_evicts_oldest() {
+        let mut cache = ResolutionCache::new(2);
+        cache.insert("vrsc".to_string(), resolution("VRSC", "iJhCezBExJHvtyH3fGhNnt2NhU4Ztkf2yq"));
+        cache.insert("veth".to_string(), resolution("vETH", "i9nwxtKuVYX4MSbeULLiK2ttVi6rUEhh4X"));
+        cache.insert("tbtc".to_string(), resolution("tBTC.vETH", "iS8TfRPfVpKo5FVfSUzfHBQxo9KuzpnqLU"));
+        assert!(cache.get("vrsc", Duration::from_secs(60)).is_none());
+        assert_eq!(cache.get("tbtc", Duration::from_secs(60)).unwrap().name, "tBTC.vETH");
+        assert!(cache.get("tbtc", Duration::ZERO).is_none());
+        assert_eq!(cache.clear(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_serves_cache_and_validates_names() {
+        let config = Arc::new(AppConfig::default());
+        let service = NameResolverService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)));
+        assert!(matches!(service.resolve("bad name").await, Err(AppError::Validation(_))));
+
+        service.cache.lock().await.insert("bridge.veth".to_string(), resolution("Bridge.vETH", "i3f7tSctFkiPpiedY8QR5Tep9p4qDVebDx"));
+        let resolved = service.resolve("Bridge.vETH").await.unwrap().unwrap();
+        assert_eq!(resolved.id, "i3f7tSctFkiPpiedY8QR5Tep9p4qDVebDx");
+        assert_eq!(service.invalidate().await, 1);
+
+        assert!(is_unknown_name(&AppError::Rpc(r#"RPC error: {"code": -5, "message": "Identity not found"}"#.to_string())));
+        assert!(!is_unknown_name(&AppError::Rpc("Request failed".to_string())));
+    }
+}
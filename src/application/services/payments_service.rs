@@ -2,12 +2,20 @@
 
 use std::sync::Arc;
 
+use crate::application::services::address_pool_service::AddressPoolService;
+use crate::application::services::viewing_key_service::ViewingKeyRegistry;
 use crate::config::AppConfig;
-use crate::domain::payments::{PaymentSession, PaymentStatus, PaymentTier, ShieldedAddressType};
+use crate::domain::payments::{
+    CurrencyQuote, OverpaymentPolicy, PaymentHistoryEntry, PaymentReceipt, PaymentRefund, PaymentResolution, PaymentSession,
+    PaymentStatus, PaymentTier, ReceiptBody, RefundReason, RefundStatus, ShieldedAddressType, UnderpaymentPolicy,
+};
+use crate::config::app_config::RefundApproval;
 use crate::domain::rpc::{ClientInfo, RpcRequest};
-use crate::infrastructure::adapters::{ExternalRpcAdapter, PaymentsStore, TokenIssuerAdapter, TokenIssuanceMode, TokenIssuanceRequest, RevocationStore};
+use crate::infrastructure::adapters::{ExternalRpcAdapter, IssuanceSource, PaymentsStore, TokenIssuerAdapter, TokenIssuanceMode, TokenIssuanceRequest, RevocationStore};
 use crate::shared::error::{AppError, AppResult};
 use chrono::{Duration, Utc};
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use uuid::Uuid;
@@ -23,6 +31,11 @@ pub struct PaymentsConfig {
     pub session_ttl_minutes: u32,
     pub tiers: Vec<PaymentTier>,
     pub require_viewing_key: bool,
+    pub underpayment_policy: UnderpaymentPolicy,
+    pub underpayment_tolerance_vrsc: f64,
+    pub overpayment_policy: OverpaymentPolicy,
+    pub native_currency: String,
+    pub exchange_lock_minutes: u32,
 }
 
 impl Default for PaymentsConfig {
@@ -34,10 +47,15 @@ impl Default for PaymentsConfig {
             min_confirmations: 1,
             session_ttl_minutes: 30,
             tiers: vec![
-                PaymentTier { id: "basic".to_string(), amount_vrsc: 1.0, description: Some("Basic access".to_string()), permissions: vec!["read".to_string()] },
-                PaymentTier { id: "pro".to_string(), amount_vrsc: 5.0, description: Some("Pro access".to_string()), permissions: vec!["read".to_string(), "write".to_string()] },
+                PaymentTier { id: "basic".to_string(), amount_vrsc: 1.0, description: Some("Basic access".to_string()), permissions: vec!["read".to_string()], accepted_currencies: vec![] },
+                PaymentTier { id: "pro".to_string(), amount_vrsc: 5.0, description: Some("Pro access".to_string()), permissions: vec!["read".to_string(), "write".to_string()], accepted_currencies: vec![] },
             ],
             require_viewing_key: false,
+            underpayment_policy: UnderpaymentPolicy::Reject,
+            underpayment_tolerance_vrsc: 0.0,
+            overpayment_policy: OverpaymentPolicy::Ignore,
+            native_currency: "VRSC".to_string(),
+            exchange_lock_minutes: 10,
         }
     }
 }
@@ -46,6 +64,18 @@ impl Default for PaymentsConfig {
 pub struct PaymentQuoteRequest {
     pub tier_id: String,
     pub address_type: Option<ShieldedAddressType>,
+    /// PBaaS currency to pay in; defaults to the native currency
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// VerusID to file the payment under for history, once it signs the payment id
+    #[serde(default)]
+    pub identity: Option<String>,
+    /// Where to return late, rejected or excess payments (see `[payments.refunds]`)
+    #[serde(default)]
+    pub refund_address: Option<String>,
+    /// Receives upgraded tokens as the payment confirms (see `[payments.zero_conf]`)
+    #[serde(default)]
+    pub webhook_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,12 +86,25 @@ pub struct PaymentQuoteResponse {
     pub address: String,
     pub address_type: ShieldedAddressType,
     pub expires_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency_quote: Option<CurrencyQuote>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentHistoryQuery {
+    pub identity: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentSubmitRequest {
     pub payment_id: String,
     pub rawtx_hex: String,
+    /// Where to return any overpayment (used with the "refund" overpayment policy)
+    #[serde(default)]
+    pub refund_address: Option<String>,
+    /// `signmessage` signature of the quote's identity over the payment's identity message
+    #[serde(default)]
+    pub identity_signature: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,19 +117,65 @@ pub struct PaymentStatusResponse {
     pub status: PaymentStatus,
     pub confirmations: u32,
     pub amount_vrsc: f64,
+    pub tier_id: String,
+    pub paid_amount_vrsc: f64,
+    pub resolution: Option<PaymentResolution>,
+    pub refund_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency_quote: Option<CurrencyQuote>,
     pub address: String,
     pub txid: Option<String>,
     pub provisional_token: Option<String>,
     pub final_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zero_conf_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund: Option<PaymentRefund>,
+}
+
+/// Which confirmation stage a payment token was issued at
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStage {
+    /// Payment seen in the mempool; low permissions, short lifetime
+    ZeroConf,
+    /// `min_confirmations` reached
+    Provisional,
+    /// Deeper confirmations reached
+    Final,
+}
+
+/// Token pushed to the session webhook and returned by `POST /payments/token/refresh`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentTokenEvent {
+    pub payment_id: String,
+    pub stage: TokenStage,
+    pub token: String,
+    pub confirmations: u32,
+}
+
+/// Refund listed by `GET /admin/refunds`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundEntry {
+    pub payment_id: String,
+    pub tier_id: String,
+    pub session_status: PaymentStatus,
+    pub paid_amount_vrsc: f64,
+    pub refund: PaymentRefund,
 }
 
 pub struct PaymentsService {
     config: Arc<AppConfig>,
     payments_config: PaymentsConfig,
     rpc: Arc<ExternalRpcAdapter>,
+    /// Isolated adapter for `[payments.rpc] methods`
+    payment_rpc: Arc<ExternalRpcAdapter>,
+    address_pool: Arc<AddressPoolService>,
     store: Arc<PaymentsStore>,
     token_issuer: Arc<TokenIssuerAdapter>,
     revocations: Arc<RevocationStore>,
+    /// Delivers upgraded tokens to session webhooks
+    http: reqwest::Client,
 }
 
 impl PaymentsService {
@@ -97,13 +186,19 @@ impl PaymentsService {
         self.payments_config.address_types = p.address_types.iter().filter_map(|s| s.parse().ok()).collect();
         self.payments_config.default_address_type = p.default_address_type.parse().unwrap_or(ShieldedAddressType::Orchard);
         self.payments_config.min_confirmations = p.min_confirmations;
-        self.payments_config.session_ttl_minutes = p.session_ttl_minutes as u32;
+        self.payments_config.session_ttl_minutes = p.session_ttl_minutes;
         self.payments_config.require_viewing_key = p.require_viewing_key;
+        self.payments_config.underpayment_policy = p.underpayment_policy.parse().unwrap_or(UnderpaymentPolicy::Reject);
+        self.payments_config.underpayment_tolerance_vrsc = p.underpayment_tolerance_vrsc;
+        self.payments_config.overpayment_policy = p.overpayment_policy.parse().unwrap_or(OverpaymentPolicy::Ignore);
+        self.payments_config.native_currency = p.native_currency.clone();
+        self.payments_config.exchange_lock_minutes = p.exchange_lock_minutes;
         self.payments_config.tiers = p.tiers.iter().map(|t| PaymentTier {
             id: t.id.clone(),
             amount_vrsc: t.amount_vrsc,
             description: t.description.clone(),
             permissions: t.permissions.clone(),
+            accepted_currencies: t.accepted_currencies.clone(),
         }).collect();
     }
     pub fn new(
@@ -115,15 +210,85 @@ impl PaymentsService {
         revocations: Arc<RevocationStore>,
     ) -> Self {
         // Always refresh from AppConfig to ensure runtime config is applied
-        let mut svc = Self { config, payments_config, rpc, store, token_issuer, revocations };
+        let payment_rpc = Arc::new(ExternalRpcAdapter::for_payments(config.clone()).with_guards(rpc.guards().clone()));
+        let pool_rpc = if config.payments.rpc.methods.iter().any(|m| m == "z_getnewaddress") {
+            payment_rpc.clone()
+        } else {
+            rpc.clone()
+        };
+        let address_pool = Arc::new(AddressPoolService::new(config.clone(), pool_rpc, store.clone()));
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.payments.zero_conf.webhook_timeout_seconds))
+            .build()
+            .unwrap_or_default();
+        let mut svc = Self { config, payments_config, rpc, payment_rpc, address_pool, store, token_issuer, revocations, http };
         svc.refresh_from_app_config();
         svc
     }
 
+    /// Pre-generated address pool used for new quotes
+    pub fn address_pool(&self) -> Arc<AddressPoolService> {
+        self.address_pool.clone()
+    }
+
+    /// Adapter for `method`: the isolated payments adapter for payment-critical calls
+    fn rpc_for(&self, method: &str) -> &ExternalRpcAdapter {
+        if self.config.payments.rpc.methods.iter().any(|m| m == method) {
+            &self.payment_rpc
+        } else {
+            &self.rpc
+        }
+    }
+
     fn find_tier(&self, id: &str) -> Option<PaymentTier> {
         self.payments_config.tiers.iter().find(|t| t.id == id).cloned()
     }
 
+    /// Existing wallet z-address of the requested type (z_listaddresses + z_validateaddress)
+    async fn select_wallet_address(&self, addr_type: &ShieldedAddressType, client_info: &ClientInfo) -> AppResult<String> {
+        // List available z-addresses
+        let list_req = RpcRequest::new(
+            "z_listaddresses".to_string(),
+            Some(serde_json::Value::Array(vec![])),
+            Some(json!(Uuid::new_v4().to_string())),
+            client_info.clone(),
+        );
+        let list_res = self.rpc_for(&list_req.method).send_request(&list_req).await?;
+        let candidates: Vec<String> = list_res
+            .result
+            .and_then(|v| v.as_array().cloned())
+            .ok_or_else(|| AppError::Rpc("z_listaddresses returned invalid result".into()))?
+            .into_iter()
+            .filter_map(|val| val.as_str().map(|s| s.to_string()))
+            .collect();
+
+        if candidates.is_empty() {
+            return Err(AppError::Security("No shielded addresses available under viewing keys".into()));
+        }
+
+        // Find an address matching the requested type via z_validateaddress
+        let mut selected: Option<String> = None;
+        for addr in candidates {
+            let validate_req = RpcRequest::new(
+                "z_validateaddress".to_string(),
+                Some(serde_json::Value::Array(vec![serde_json::Value::String(addr.clone())])),
+                Some(json!(Uuid::new_v4().to_string())),
+                client_info.clone(),
+            );
+            if let Ok(val_res) = self.rpc_for(&validate_req.method).send_request(&validate_req).await {
+                if let Some(obj) = val_res.result.and_then(|v| v.as_object().cloned()) {
+                    let addr_type_str = obj.get("type").and_then(|t| t.as_str()).unwrap_or("");
+                    if addr_type_str.eq_ignore_ascii_case(addr_type.as_str()) {
+                        selected = Some(addr);
+                        break;
+                    }
+                }
+            }
+        }
+
+        selected.ok_or_else(|| AppError::Security("No compatible shielded address for requested type".into()))
+    }
+
     pub async fn create_quote(
         &self,
         req: PaymentQuoteRequest,
@@ -140,69 +305,71 @@ impl PaymentsService {
             return Err(AppError::Validation("unsupported address type".into()));
         }
 
+        let identity = req.identity.as_deref().map(str::trim).filter(|i| !i.is_empty()).map(|i| i.to_string());
+        if identity.as_ref().is_some_and(|i| i.len() > 128) {
+            return Err(AppError::Validation("identity too long".into()));
+        }
+        let refund_address = req.refund_address.as_deref().map(str::trim).filter(|a| !a.is_empty()).map(|a| a.to_string());
+        if refund_address.as_ref().is_some_and(|a| a.len() > 256) {
+            return Err(AppError::Validation("refund address too long".into()));
+        }
+        let webhook_url = req.webhook_url.as_deref().map(str::trim).filter(|u| !u.is_empty()).map(|u| u.to_string());
+        if let Some(url) = &webhook_url {
+            if !self.config.payments.zero_conf.allow_webhooks {
+                return Err(AppError::Validation("webhook_url is not accepted by this server".into()));
+            }
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(AppError::Validation("webhook_url must start with http:// or https://".into()));
+            }
+        }
+
+        let currency = req
+            .currency
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty() && !c.eq_ignore_ascii_case(&self.payments_config.native_currency));
+        if let Some(currency) = currency {
+            if !tier.accepted_currencies.iter().any(|c| c.eq_ignore_ascii_case(currency)) {
+                return Err(AppError::Validation("currency not accepted for tier".into()));
+            }
+            if self.payments_config.require_viewing_key {
+                return Err(AppError::Security("PBaaS currency payments require a wallet address".into()));
+            }
+        }
+
         // If viewing-key-only mode is required, avoid creating a new address.
         // Instead, select a compatible existing shielded address from the wallet.
-        let address = if self.payments_config.require_viewing_key {
+        let address = if currency.is_some() {
+            // Shielded outputs only carry the native currency, so PBaaS payments go to a transparent address
+            self.call("getnewaddress", json!([]), client_info)
+                .await?
+                .as_str()
+                .map(|s| s.to_string())
+                .ok_or_else(|| AppError::Rpc("invalid getnewaddress result".into()))?
+        } else if self.payments_config.require_viewing_key {
             if self.config.payments.viewing_keys.is_empty() {
                 return Err(AppError::Security("Viewing key required but not configured".into()));
             }
 
-            // List available z-addresses
-            let list_req = RpcRequest::new(
-                "z_listaddresses".to_string(),
-                Some(serde_json::Value::Array(vec![])),
-                Some(json!(Uuid::new_v4().to_string())),
-                client_info.clone(),
-            );
-            let list_res = self.rpc.send_request(&list_req).await?;
-            let candidates: Vec<String> = list_res
-                .result
-                .and_then(|v| v.as_array().cloned())
-                .ok_or_else(|| AppError::Rpc("z_listaddresses returned invalid result".into()))?
-                .into_iter()
-                .filter_map(|val| val.as_str().map(|s| s.to_string()))
-                .collect();
-
-            if candidates.is_empty() {
-                return Err(AppError::Security("No shielded addresses available under viewing keys".into()));
-            }
-
-            // Find an address matching the requested type via z_validateaddress
-            let mut selected: Option<String> = None;
-            for addr in candidates {
-                let validate_req = RpcRequest::new(
-                    "z_validateaddress".to_string(),
-                    Some(serde_json::Value::Array(vec![serde_json::Value::String(addr.clone())])),
-                    Some(json!(Uuid::new_v4().to_string())),
-                    client_info.clone(),
-                );
-                if let Ok(val_res) = self.rpc.send_request(&validate_req).await {
-                    if let Some(obj) = val_res.result.and_then(|v| v.as_object().cloned()) {
-                        let addr_type_str = obj.get("type").and_then(|t| t.as_str()).unwrap_or("");
-                        if addr_type_str.eq_ignore_ascii_case(addr_type.as_str()) {
-                            selected = Some(addr);
-                            break;
-                        }
-                    }
-                }
+            // Prefer the watch-only addresses recorded when the keys were imported
+            match ViewingKeyRegistry::global().watch_only_addresses(&addr_type).into_iter().next() {
+                Some(address) => address,
+                None => self.select_wallet_address(&addr_type, client_info).await?,
             }
-
-            selected.ok_or_else(|| AppError::Security("No compatible shielded address for requested type".into()))?
         } else {
-            // Ask daemon for a new shielded address (z_getnewaddress "orchard" | "sapling")
-            let method = "z_getnewaddress".to_string();
-            let params = serde_json::Value::Array(vec![serde_json::Value::String(addr_type.as_str().to_string())]);
-            let rpc_req = RpcRequest::new(method, Some(params), Some(json!(Uuid::new_v4().to_string())), client_info.clone());
-            let rpc_res = self.rpc.send_request(&rpc_req).await?;
-            rpc_res
-                .result
-                .and_then(|v| v.as_str().map(|s| s.to_string()))
-                .ok_or_else(|| AppError::Rpc("invalid z_getnewaddress result".into()))?
+            self.address_pool.acquire(&addr_type, client_info).await?
         };
 
         let now = Utc::now();
         let expires_at = now + Duration::minutes(self.payments_config.session_ttl_minutes as i64);
         let payment_id = Uuid::new_v4().to_string();
+        let currency_quote = match currency {
+            Some(currency) => {
+                let lock = Duration::minutes(self.payments_config.exchange_lock_minutes as i64);
+                Some(self.quote_currency(currency, tier.amount_vrsc, (now + lock).min(expires_at), client_info).await?)
+            }
+            None => None,
+        };
 
         let session = PaymentSession {
             payment_id: payment_id.clone(),
@@ -219,7 +386,19 @@ impl PaymentsService {
             confirmations: 0,
             provisional_token: None,
             final_token: None,
+            submitted_txids: Vec::new(),
+            paid_amount_vrsc: 0.0,
+            resolution: None,
+            refund_address,
+            currency_quote: currency_quote.clone(),
+            identity,
+            identity_verified: false,
+            refund: None,
+            zero_conf_token: None,
+            webhook_url,
+            block_height: None,
         };
+        // The identity is filed for history only once it signs the payment id on submission
         self.store.put(&session).await?;
 
         Ok(PaymentQuoteResponse {
@@ -229,6 +408,7 @@ impl PaymentsService {
             address,
             address_type: addr_type,
             expires_at,
+            currency_quote,
         })
     }
 
@@ -240,15 +420,54 @@ impl PaymentsService {
             .ok_or_else(|| AppError::Validation("unknown payment_id".into()))?;
 
         if session.is_expired() { return Err(AppError::Validation("payment session expired".into())); }
-        if session.status != PaymentStatus::Pending && session.status != PaymentStatus::Submitted {
+        if !matches!(session.status, PaymentStatus::Pending | PaymentStatus::Submitted | PaymentStatus::Underpaid) {
             return Err(AppError::Validation("invalid state for submission".into()));
         }
 
+        if let Some(quote) = &session.currency_quote {
+            if Utc::now() > quote.rate_locked_until {
+                return Err(AppError::Validation("exchange rate lock expired; request a new quote".into()));
+            }
+        }
+        if let Some(signature) = req.identity_signature.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+            self.verify_identity(&mut session, signature, client_info).await?;
+        }
+
         // Basic sanity checks on hex
         if req.rawtx_hex.len() < 100 || !req.rawtx_hex.chars().all(|c| c.is_ascii_hexdigit()) {
             return Err(AppError::Validation("invalid raw tx hex".into()));
         }
 
+        // Decode first so replays and mismatched outputs never reach the network
+        let decode_req = RpcRequest::new(
+            "decoderawtransaction".to_string(),
+            Some(json!([req.rawtx_hex])),
+            Some(json!(Uuid::new_v4().to_string())),
+            client_info.clone(),
+        );
+        let decoded = self
+            .rpc_for(&decode_req.method)
+            .send_request(&decode_req)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Validation("undecodable raw tx".into()))?;
+        let txid = decoded
+            .get("txid")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string())
+            .ok_or_else(|| AppError::Rpc("invalid decoderawtransaction result".into()))?;
+
+        // Resubmitting the same tx for the same session is idempotent
+        if session.submitted_txids.contains(&txid) {
+            return Ok(PaymentSubmitResponse { txid });
+        }
+        if !outputs_match_quote(&decoded, &session, self.min_transparent_payment(&session)) {
+            return Err(AppError::Validation("transaction outputs do not match the payment quote".into()));
+        }
+        if self.store.claim_txid(&txid, &session.payment_id).await?.is_some() {
+            return Err(AppError::Validation("transaction already submitted for another payment".into()));
+        }
+
         // Broadcast raw tx
         let rpc_req = RpcRequest::new(
             "sendrawtransaction".to_string(),
@@ -256,19 +475,49 @@ impl PaymentsService {
             Some(json!(Uuid::new_v4().to_string())),
             client_info.clone(),
         );
-        let rpc_res = self.rpc.send_request(&rpc_req).await?;
-        let txid = rpc_res
-            .result
-            .and_then(|v| v.as_str().map(|s| s.to_string()))
-            .ok_or_else(|| AppError::Rpc("invalid sendrawtransaction result".into()))?;
+        let broadcast_txid = match self.rpc_for(&rpc_req.method).send_request(&rpc_req).await {
+            Ok(rpc_res) => rpc_res.result.and_then(|v| v.as_str().map(|s| s.to_string())),
+            Err(e) => {
+                let _ = self.store.release_txid(&txid, &session.payment_id).await;
+                return Err(e);
+            }
+        };
+        if broadcast_txid.as_deref() != Some(txid.as_str()) {
+            let _ = self.store.release_txid(&txid, &session.payment_id).await;
+            return Err(AppError::Rpc("invalid sendrawtransaction result".into()));
+        }
 
         session.txid = Some(txid.clone());
+        session.submitted_txids.push(txid.clone());
         session.status = PaymentStatus::Submitted;
+        if let Some(refund_address) = req.refund_address.filter(|a| !a.trim().is_empty()) {
+            session.refund_address = Some(refund_address);
+        }
         self.store.put(&session).await?;
 
         Ok(PaymentSubmitResponse { txid })
     }
 
+    /// Check the quote identity's signature over the payment and file the payment under it
+    async fn verify_identity(&self, session: &mut PaymentSession, signature: &str, client_info: &ClientInfo) -> AppResult<()> {
+        if session.identity_verified {
+            return Ok(());
+        }
+        let identity = session
+            .identity
+            .clone()
+            .ok_or_else(|| AppError::Validation("identity_signature given but the quote names no identity".into()))?;
+        let verified = self
+            .call("verifymessage", json!([identity, signature, session.identity_message()]), client_info)
+            .await?;
+        if verified != serde_json::Value::Bool(true) {
+            return Err(AppError::Validation("invalid identity signature".into()));
+        }
+        session.identity_verified = true;
+        self.store.put(session).await?;
+        self.store.index_identity(&identity, &session.payment_id).await
+    }
+
     pub async fn check_status(&self, payment_id: &str, client_info: &ClientInfo) -> AppResult<PaymentStatusResponse> {
         let mut session = self
             .store
@@ -281,98 +530,723 @@ impl PaymentsService {
             if let Some(token) = &session.provisional_token {
                 let _ = self.revoke_token_by_string(token).await;
             }
+            self.revoke_zero_conf_token(&mut session).await;
             session.status = PaymentStatus::Expired;
             self.store.put(&session).await?;
         }
 
-        // If we have a txid, verify receipt via z_viewtransaction
-        if let Some(txid) = session.txid.clone() {
-            // z_viewtransaction requires the wallet to have a viewing/spending key for the outputs
-            let rpc_req = RpcRequest::new(
-                "z_viewtransaction".to_string(),
-                Some(serde_json::Value::Array(vec![serde_json::Value::String(txid.clone())])),
-                Some(json!(Uuid::new_v4().to_string())),
-                client_info.clone(),
-            );
-            let rpc_res = self.rpc.send_request(&rpc_req).await?;
+        // Funds sent straight to the address after expiry never went through submit
+        if session.status == PaymentStatus::Expired
+            && session.submitted_txids.is_empty()
+            && session.txid.is_none()
+            && self.refund_possible(&session)
+        {
+            let received = self.received_by_address(&session.address, client_info).await?;
+            if received > 0.0 {
+                session.paid_amount_vrsc = received;
+                self.open_refund(&mut session, RefundReason::LatePayment, received, client_info).await;
+                self.store.put(&session).await?;
+            }
+        }
 
-            // We expect a structure containing received outputs; we conservatively search JSON
+        // Verify receipt of every submitted tx via z_viewtransaction
+        let txids = session.txids();
+        if !txids.is_empty() {
             let mut paid_amount = 0.0f64;
-            let mut matched = false;
-            if let Some(v) = rpc_res.result {
-                // Look for any output to our session.address and sum amounts
-                if let Some(outputs) = v.get("outputs").and_then(|o| o.as_array()) {
-                    for o in outputs {
-                        let addr_ok = o.get("address").and_then(|a| a.as_str()) == Some(session.address.as_str());
-                        let amt = o.get("amount").and_then(|a| a.as_f64()).unwrap_or(0.0);
-                        if addr_ok {
-                            paid_amount += amt;
-                            matched = true;
-                        }
+            let mut confirmations: Option<u32> = None;
+            let mut block_height: Option<u64> = None;
+            for txid in &txids {
+                let (received, tx_confirmations, tx_height) = match self.tx_payment(txid, &session, client_info).await {
+                    Ok(found) => found,
+                    // The daemon forgets a mempool tx that was dropped or double-spent
+                    Err(e) if session.zero_conf_token.is_some() && is_unknown_transaction(&e) => {
+                        tracing::warn!(payment_id = %session.payment_id, txid = %txid, "Zero-conf payment rolled back: {}", e);
+                        (0.0, 0, None)
                     }
+                    Err(e) => return Err(e),
+                };
+                if received <= 0.0 {
+                    continue;
                 }
+                paid_amount += received;
+                // A multi-tx payment is only as confirmed as its newest part
+                confirmations = Some(confirmations.map_or(tx_confirmations, |c| c.min(tx_confirmations)));
+                block_height = block_height.max(tx_height);
             }
 
-            if matched && paid_amount + 1e-12 >= session.amount_vrsc {
-                // Query confirmations via getrawtransaction verbose=true or gettransaction
-                // Fallback: use getrawtransaction <txid> 1 (verbose) for confirmations
-                let raw_req = RpcRequest::new(
-                    "getrawtransaction".to_string(),
-                    Some(serde_json::Value::Array(vec![serde_json::Value::String(txid.clone()), serde_json::Value::Number(1u64.into())])),
-                    Some(json!(Uuid::new_v4().to_string())),
-                    client_info.clone(),
+            if let Some(confirmations) = confirmations {
+                session.paid_amount_vrsc = paid_amount;
+                session.block_height = block_height;
+                let resolution = resolve_payment(
+                    paid_amount,
+                    &session,
+                    &self.payments_config.tiers,
+                    self.payments_config.underpayment_policy,
+                    self.payments_config.underpayment_tolerance_vrsc,
+                    self.payments_config.overpayment_policy,
                 );
-                let raw_res = self.rpc.send_request(&raw_req).await?;
-                let confirmations = raw_res
-                    .result
-                    .and_then(|r| r.get("confirmations").and_then(|c| c.as_u64()))
-                    .unwrap_or(0) as u32;
-                session.confirmations = confirmations;
-
-                // Issue provisional token at 1 conf if configured; then replace once finalized
-                if confirmations >= self.payments_config.min_confirmations {
-                    if session.provisional_token.is_none() {
-                        let token = self.issue_token(&session, true, client_info).await?;
-                        session.provisional_token = Some(token);
-                        session.status = PaymentStatus::Confirmed1;
+                session.resolution = Some(resolution.clone());
+                // Refunds only go out for confirmed funds
+                let refundable = confirmations >= self.payments_config.min_confirmations.max(1);
+                if let PaymentResolution::Overpaid { excess_vrsc, policy: OverpaymentPolicy::Refund } = resolution {
+                    if refundable {
+                        self.open_refund(&mut session, RefundReason::Overpayment, excess_vrsc, client_info).await;
                     }
-                } else {
-                    session.status = PaymentStatus::Verified;
                 }
 
-                // Optional second-check/finalization when deeper confirmations available (e.g., >=2)
-                if confirmations >= (self.payments_config.min_confirmations.max(2)) {
-                    if session.final_token.is_none() {
-                        let token = self.issue_token(&session, false, client_info).await?;
-                        session.final_token = Some(token);
-                        session.status = PaymentStatus::Finalized;
+                match resolution {
+                    PaymentResolution::Rejected { .. } => {
+                        if let Some(token) = session.provisional_token.take() {
+                            let _ = self.revoke_token_by_string(&token).await;
+                        }
+                        self.revoke_zero_conf_token(&mut session).await;
+                        session.status = PaymentStatus::Failed;
+                        if refundable {
+                            self.open_refund(&mut session, RefundReason::RejectedUnderpayment, paid_amount, client_info).await;
+                        }
+                    }
+                    PaymentResolution::AwaitingTopUp { .. } => {
+                        // Part of a payment that covered the quote was rolled back
+                        self.revoke_zero_conf_token(&mut session).await;
+                        session.status = PaymentStatus::Underpaid;
+                    }
+                    resolution => {
+                        if let PaymentResolution::ReducedTier { tier_id } = resolution {
+                            session.tier_id = tier_id;
+                        }
+                        session.confirmations = confirmations;
+
+                        // A reorg can take confirmations back below a threshold; tokens issued on
+                        // orphaned confirmations are revoked and reissued once they return
+                        if confirmations < self.payments_config.min_confirmations {
+                            for token in [session.provisional_token.take(), session.final_token.take()].into_iter().flatten() {
+                                let _ = self.revoke_token_by_string(&token).await;
+                            }
+                        } else if confirmations < self.payments_config.min_confirmations.max(2) {
+                            if let Some(token) = session.final_token.take() {
+                                let _ = self.revoke_token_by_string(&token).await;
+                                session.status = PaymentStatus::Confirmed1;
+                            }
+                        }
+
+                        // Issue provisional token at 1 conf if configured; then replace once finalized
+                        if confirmations >= self.payments_config.min_confirmations {
+                            if session.provisional_token.is_none() {
+                                let token = self.issue_token(&session, true, client_info).await?;
+                                session.status = PaymentStatus::Confirmed1;
+                                self.push_token(&session, TokenStage::Provisional, &token).await;
+                                session.provisional_token = Some(token);
+                            }
+                        } else {
+                            session.status = PaymentStatus::Verified;
+                            if session.zero_conf_token.is_none() && self.zero_conf_eligible(&session) {
+                                let token = self.issue_zero_conf_token(&session, client_info).await?;
+                                self.push_token(&session, TokenStage::ZeroConf, &token).await;
+                                session.zero_conf_token = Some(token);
+                            }
+                        }
+
+                        // Optional second-check/finalization when deeper confirmations available (e.g., >=2)
+                        if confirmations >= (self.payments_config.min_confirmations.max(2)) && session.final_token.is_none() {
+                            let token = self.issue_token(&session, false, client_info).await?;
+                            session.status = PaymentStatus::Finalized;
+                            self.push_token(&session, TokenStage::Final, &token).await;
+                            session.final_token = Some(token);
+                            if self.payments_config.require_viewing_key {
+                                ViewingKeyRegistry::global().record_payment(&session.address);
+                            }
+                        }
                     }
                 }
 
                 self.store.put(&session).await?;
-            } else if session.provisional_token.is_some() {
+            } else if session.provisional_token.is_some() || session.zero_conf_token.is_some() {
                 // If we can no longer validate recipient match but had issued a provisional token, revoke it
                 // Note: this requires the Authentication layer to check revocations; handled via RevocationStore
                 if let Some(token) = &session.provisional_token {
                     let _ = self.revoke_token_by_string(token).await;
                 }
                 session.provisional_token = None;
+                self.revoke_zero_conf_token(&mut session).await;
                 session.status = PaymentStatus::Failed;
                 self.store.put(&session).await?;
             }
         }
 
+        if session.refund.as_ref().is_some_and(|r| r.status == RefundStatus::Broadcast) && self.poll_refund(&mut session, client_info).await {
+            self.store.put(&session).await?;
+        }
+
         Ok(PaymentStatusResponse {
             status: session.status.clone(),
             confirmations: session.confirmations,
             amount_vrsc: session.amount_vrsc,
+            tier_id: session.tier_id.clone(),
+            paid_amount_vrsc: session.paid_amount_vrsc,
+            resolution: session.resolution.clone(),
+            refund_address: session.refund_address.clone(),
+            currency_quote: session.currency_quote.clone(),
             address: session.address.clone(),
             txid: session.txid.clone(),
             provisional_token: session.provisional_token.clone(),
             final_token: session.final_token.clone(),
+            zero_conf_token: session.zero_conf_token.clone(),
+            refund: session.refund.clone(),
         })
     }
 
+    /// Current token of the payment a bearer token was issued for
+    ///
+    /// Re-checks the payment first, so a client holding a zero-conf token gets
+    /// the provisional or final token once the payment has confirmed. Expired
+    /// tokens are accepted (a zero-conf token may lapse before the upgrade);
+    /// revoked ones are not.
+    pub async fn refresh_token(&self, auth_header: Option<&str>, client_info: &ClientInfo) -> AppResult<PaymentTokenEvent> {
+        let token = auth_header
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or_else(|| AppError::Authentication("Missing Authorization header".into()))?;
+        let claims = self.token_claims(token.trim(), false)?;
+        if self.revocations.is_token_revoked(&claims.jti, &claims.sub, claims.issued_at_ms()).await? {
+            return Err(AppError::Authentication("token revoked".into()));
+        }
+        // Only the issuer embeds the payment claim; the subject is chosen by whoever requested the token
+        let payment_id = claims
+            .payment
+            .map(|payment| payment.payment_id)
+            .ok_or_else(|| AppError::Authentication("not a payment token".into()))?;
+
+        let status = self.check_status(&payment_id, client_info).await?;
+        let (stage, token) = match (status.final_token, status.provisional_token, status.zero_conf_token) {
+            (Some(token), _, _) => (TokenStage::Final, token),
+            (None, Some(token), _) => (TokenStage::Provisional, token),
+            (None, None, Some(token)) => (TokenStage::ZeroConf, token),
+            (None, None, None) => return Err(AppError::Authentication("no token available for this payment".into())),
+        };
+        Ok(PaymentTokenEvent { payment_id, stage, token, confirmations: status.confirmations })
+    }
+
+    /// Re-check sessions whose confirmations may have been orphaned by a reorg of `depth` blocks
+    ///
+    /// Returns the number of sessions re-verified.
+    pub async fn reverify_after_reorg(&self, depth: u64) -> usize {
+        let client_info = ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: Some("reorg-reverify".to_string()),
+            auth_token: None,
+            timestamp: Utc::now(),
+            request_id: None,
+        };
+        let mut reverified = 0;
+        for session in self.store.active_sessions().await {
+            // Only transactions mined within the orphaned range can have lost confirmations
+            if session.confirmations as u64 > depth {
+                continue;
+            }
+            match self.check_status(&session.payment_id, &client_info).await {
+                Ok(status) => {
+                    reverified += 1;
+                    tracing::info!(payment_id = %session.payment_id, status = ?status.status, confirmations = status.confirmations, "Re-verified payment after reorg");
+                }
+                Err(e) => tracing::warn!(payment_id = %session.payment_id, "Payment re-verification failed: {}", e),
+            }
+        }
+        reverified
+    }
+
+    /// Refunds opened so far, oldest first; in-flight wallet operations are polled
+    pub async fn refunds(&self) -> AppResult<Vec<RefundEntry>> {
+        let client_info = Self::internal_client("refunds");
+        let mut entries = Vec::new();
+        for mut session in self.store.list_refunds().await? {
+            if session.refund.as_ref().is_some_and(|r| r.status == RefundStatus::Broadcast) && self.poll_refund(&mut session, &client_info).await {
+                self.store.put(&session).await?;
+            }
+            if let Some(refund) = session.refund {
+                entries.push(RefundEntry {
+                    payment_id: session.payment_id,
+                    tier_id: session.tier_id,
+                    session_status: session.status,
+                    paid_amount_vrsc: session.paid_amount_vrsc,
+                    refund,
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Approve (send) or decline a pending refund as operator `decided_by`; failed refunds can be approved again
+    pub async fn decide_refund(&self, payment_id: &str, approve: bool, decided_by: &str, note: Option<String>) -> AppResult<PaymentRefund> {
+        let mut session = self
+            .store
+            .get(payment_id)
+            .await?
+            .ok_or_else(|| AppError::Validation("unknown payment_id".into()))?;
+        let status = session
+            .refund
+            .as_ref()
+            .map(|r| r.status)
+            .ok_or_else(|| AppError::Validation("payment has no refund".into()))?;
+        if !matches!(status, RefundStatus::PendingApproval | RefundStatus::Failed) {
+            return Err(AppError::Validation("refund already decided".into()));
+        }
+
+        let sent = if approve {
+            self.send_refund(&mut session, decided_by, note, &Self::internal_client("refunds")).await
+        } else {
+            let refund = session.refund.as_mut().expect("refund checked above");
+            refund.status = RefundStatus::Declined;
+            refund.decided_by = Some(decided_by.to_string());
+            refund.decided_at = Some(Utc::now());
+            refund.note = note;
+            tracing::info!(
+                target: "audit",
+                event = "payment_refund_declined",
+                payment_id = %session.payment_id,
+                amount_vrsc = refund.amount_vrsc,
+                "Payment refund declined"
+            );
+            Ok(())
+        };
+        // Persist failed sends too, so the error is visible and the refund can be retried
+        self.store.put(&session).await?;
+        sent?;
+        session.refund.ok_or_else(|| AppError::Internal("refund disappeared".into()))
+    }
+
+    /// Refunds are enabled and the session can be refunded in the native currency
+    fn refund_possible(&self, session: &PaymentSession) -> bool {
+        self.config.payments.refunds.enabled
+            && !self.payments_config.require_viewing_key
+            && session.refund.is_none()
+            && session.refund_address.is_some()
+            && session.currency_quote.is_none()
+    }
+
+    /// Open a refund of `amount_vrsc` (before fee); sent at once with `approval = "auto"`
+    async fn open_refund(&self, session: &mut PaymentSession, reason: RefundReason, amount_vrsc: f64, client_info: &ClientInfo) {
+        if !self.refund_possible(session) {
+            return;
+        }
+        let refunds = &self.config.payments.refunds;
+        if amount_vrsc - refunds.fee_vrsc < refunds.min_refund_vrsc {
+            tracing::info!(payment_id = %session.payment_id, amount_vrsc, ?reason, "Refund below minimum; not opened");
+            return;
+        }
+        let Some(address) = session.refund_address.clone() else { return };
+        session.refund = Some(PaymentRefund {
+            reason,
+            status: RefundStatus::PendingApproval,
+            address: address.clone(),
+            amount_vrsc,
+            fee_vrsc: refunds.fee_vrsc,
+            created_at: Utc::now(),
+            decided_by: None,
+            decided_at: None,
+            note: None,
+            operation_id: None,
+            txid: None,
+            error: None,
+        });
+        tracing::info!(
+            target: "audit",
+            event = "payment_refund_opened",
+            payment_id = %session.payment_id,
+            ?reason,
+            amount_vrsc,
+            refund_address = %address,
+            "Payment refund opened"
+        );
+        if let Err(e) = self.store.index_refund(&session.payment_id).await {
+            tracing::warn!(payment_id = %session.payment_id, "Refund index update failed: {}", e);
+        }
+        if refunds.approval == RefundApproval::Auto {
+            if let Err(e) = self.send_refund(session, "auto", None, client_info).await {
+                tracing::warn!(payment_id = %session.payment_id, "Automatic refund failed: {}", e);
+            }
+        }
+    }
+
+    /// Hand the refund to the wallet with `z_sendmany` from the session address
+    async fn send_refund(&self, session: &mut PaymentSession, decided_by: &str, note: Option<String>, client_info: &ClientInfo) -> AppResult<()> {
+        let Some(refund) = session.refund.clone() else { return Ok(()) };
+        if !self.store.claim_refund_send(&session.payment_id).await? {
+            return Err(AppError::Validation("refund already sent".into()));
+        }
+        // Amounts go to the daemon with 8 decimals
+        let amount = ((refund.amount_vrsc - refund.fee_vrsc) * 1e8).floor() / 1e8;
+        let result = self
+            .call(
+                "z_sendmany",
+                json!([session.address, [{ "address": refund.address, "amount": amount }], 1, refund.fee_vrsc]),
+                client_info,
+            )
+            .await;
+
+        let outcome = match result {
+            Ok(value) => value.as_str().map(|s| s.to_string()).ok_or_else(|| "invalid z_sendmany result".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        let refund = session.refund.as_mut().expect("refund checked above");
+        refund.decided_by = Some(decided_by.to_string());
+        refund.decided_at = Some(Utc::now());
+        if note.is_some() {
+            refund.note = note;
+        }
+        match outcome {
+            Ok(operation_id) => {
+                tracing::info!(
+                    target: "audit",
+                    event = "payment_refund_sent",
+                    payment_id = %session.payment_id,
+                    decided_by,
+                    amount_vrsc = amount,
+                    operation_id = %operation_id,
+                    "Payment refund sent to wallet"
+                );
+                refund.status = RefundStatus::Broadcast;
+                refund.operation_id = Some(operation_id);
+                refund.error = None;
+                Ok(())
+            }
+            Err(error) => {
+                tracing::warn!(
+                    target: "audit",
+                    event = "payment_refund_failed",
+                    payment_id = %session.payment_id,
+                    decided_by,
+                    "Payment refund failed: {}",
+                    error
+                );
+                refund.status = RefundStatus::Failed;
+                refund.error = Some(error.clone());
+                self.store.release_refund_send(&session.payment_id).await?;
+                Err(AppError::Rpc(error))
+            }
+        }
+    }
+
+    /// Follow a sent refund's wallet operation; returns true when the refund changed
+    async fn poll_refund(&self, session: &mut PaymentSession, client_info: &ClientInfo) -> bool {
+        let Some(operation_id) = session.refund.as_ref().and_then(|r| r.operation_id.clone()) else { return false };
+        let status = match self.call("z_getoperationstatus", json!([[operation_id]]), client_info).await {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::warn!(payment_id = %session.payment_id, "Refund operation status unavailable: {}", e);
+                return false;
+            }
+        };
+        let Some(operation) = status.as_array().and_then(|ops| ops.first()) else { return false };
+        let Some(refund) = session.refund.as_mut() else { return false };
+        match operation.get("status").and_then(|s| s.as_str()) {
+            Some("success") => {
+                refund.status = RefundStatus::Completed;
+                refund.txid = operation.pointer("/result/txid").and_then(|t| t.as_str()).map(|t| t.to_string());
+                tracing::info!(
+                    target: "audit",
+                    event = "payment_refund_completed",
+                    payment_id = %session.payment_id,
+                    txid = refund.txid.as_deref().unwrap_or(""),
+                    "Payment refund completed"
+                );
+            }
+            Some("failed") | Some("cancelled") => {
+                refund.status = RefundStatus::Failed;
+                refund.error = Some(
+                    operation
+                        .pointer("/error/message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("wallet operation failed")
+                        .to_string(),
+                );
+                tracing::warn!(
+                    target: "audit",
+                    event = "payment_refund_failed",
+                    payment_id = %session.payment_id,
+                    "Payment refund failed: {}",
+                    refund.error.as_deref().unwrap_or("")
+                );
+                if let Err(e) = self.store.release_refund_send(&session.payment_id).await {
+                    tracing::warn!(payment_id = %session.payment_id, "Refund claim release failed: {}", e);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Native amount the wallet received at `address` (z_listreceivedbyaddress)
+    async fn received_by_address(&self, address: &str, client_info: &ClientInfo) -> AppResult<f64> {
+        let min_confirmations = self.payments_config.min_confirmations.max(1);
+        let received = self.call("z_listreceivedbyaddress", json!([address, min_confirmations]), client_info).await?;
+        Ok(received
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.get("amount").and_then(|a| a.as_f64()))
+            .sum())
+    }
+
+    fn internal_client(user_agent: &str) -> ClientInfo {
+        ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: Some(user_agent.to_string()),
+            auth_token: None,
+            timestamp: Utc::now(),
+            request_id: None,
+        }
+    }
+
+    /// Signed receipt for a finalized payment
+    pub async fn receipt(&self, payment_id: &str) -> AppResult<PaymentReceipt> {
+        let session = self
+            .store
+            .get(payment_id)
+            .await?
+            .ok_or_else(|| AppError::Validation("unknown payment_id".into()))?;
+        let final_token = match (&session.status, &session.final_token) {
+            (PaymentStatus::Finalized, Some(token)) => token,
+            _ => return Err(AppError::Validation("payment not finalized".into())),
+        };
+        let claims = self.token_claims(final_token, false)?;
+
+        let receipt = ReceiptBody {
+            payment_id: session.payment_id.clone(),
+            identity: session.verified_identity().map(str::to_string),
+            tier_id: session.tier_id.clone(),
+            amount_vrsc: session.amount_vrsc,
+            paid_amount_vrsc: session.paid_amount_vrsc,
+            currency_quote: session.currency_quote.clone(),
+            txids: session.txids(),
+            confirmations: session.confirmations,
+            token_id: claims.jti,
+            created_at: session.created_at,
+            issued_at: Utc::now(),
+        };
+        let payload = serde_json::to_vec(&receipt).map_err(|e| AppError::Internal(format!("serialize receipt: {}", e)))?;
+        let key = self.receipt_signing_key()?;
+        Ok(PaymentReceipt {
+            receipt,
+            public_key: hex::encode(key.verifying_key().to_bytes()),
+            signature: hex::encode(key.sign(&payload).to_bytes()),
+        })
+    }
+
+    /// Payment history for an identity
+    ///
+    /// The caller proves membership with any unexpired token issued for one of
+    /// the payments the identity has signed for; the payment is read from the
+    /// issuer's payment claim, never from the client-chosen subject.
+    pub async fn history(&self, identity: &str, auth_header: Option<&str>) -> AppResult<Vec<PaymentHistoryEntry>> {
+        let token = auth_header
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or_else(|| AppError::Authentication("Missing Authorization header".into()))?;
+        let claims = self.token_claims(token.trim(), true)?;
+        if self.revocations.is_token_revoked(&claims.jti, &claims.sub, claims.issued_at_ms()).await? {
+            return Err(AppError::Authentication("token revoked".into()));
+        }
+        let owner = match &claims.payment {
+            Some(payment) => self.store.get(&payment.payment_id).await?,
+            None => None,
+        };
+        if owner.as_ref().and_then(PaymentSession::verified_identity) != Some(identity) {
+            return Err(AppError::Authentication("token does not belong to this identity".into()));
+        }
+
+        Ok(self
+            .store
+            .list_by_identity(identity)
+            .await?
+            .into_iter()
+            .map(|s| PaymentHistoryEntry {
+                receipt_available: s.status == PaymentStatus::Finalized && s.final_token.is_some(),
+                payment_id: s.payment_id,
+                tier_id: s.tier_id,
+                amount_vrsc: s.amount_vrsc,
+                paid_amount_vrsc: s.paid_amount_vrsc,
+                status: s.status,
+                txid: s.txid,
+                created_at: s.created_at,
+            })
+            .collect())
+    }
+
+    fn receipt_signing_key(&self) -> AppResult<SigningKey> {
+        let seed: [u8; 32] = match &self.config.payments.receipt_signing_key {
+            Some(hex_seed) => hex::decode(hex_seed.trim())
+                .ok()
+                .and_then(|bytes| bytes.as_slice().try_into().ok())
+                .ok_or_else(|| AppError::Config("payments.receipt_signing_key must be a 32-byte hex seed".into()))?,
+            None => Sha256::new()
+                .chain_update(b"verus-rpc-payment-receipt:")
+                .chain_update(self.config.security.jwt.secret_key.as_bytes())
+                .finalize()
+                .into(),
+        };
+        Ok(SigningKey::from_bytes(&seed))
+    }
+
+    /// Amount a tx paid to the session address, its confirmations and block height (none when it paid nothing)
+    async fn tx_payment(&self, txid: &str, session: &PaymentSession, client_info: &ClientInfo) -> AppResult<(f64, u32, Option<u64>)> {
+        let received = self.received_amount(txid, session, client_info).await?;
+        if received <= 0.0 {
+            return Ok((0.0, 0, None));
+        }
+        let (confirmations, height) = self.confirmations(txid, client_info).await?;
+        Ok((received, confirmations, height))
+    }
+
+    /// Native-currency value a tx paid to the session address
+    async fn received_amount(&self, txid: &str, session: &PaymentSession, client_info: &ClientInfo) -> AppResult<f64> {
+        if session.currency_quote.is_some() {
+            let tx = self.call("getrawtransaction", json!([txid, 1]), client_info).await?;
+            return Ok(transparent_paid(&tx, session));
+        }
+
+        // z_viewtransaction requires the wallet to have a viewing/spending key for the outputs
+        let rpc_req = RpcRequest::new(
+            "z_viewtransaction".to_string(),
+            Some(serde_json::Value::Array(vec![serde_json::Value::String(txid.to_string())])),
+            Some(json!(Uuid::new_v4().to_string())),
+            client_info.clone(),
+        );
+        let rpc_res = self.rpc_for(&rpc_req.method).send_request(&rpc_req).await?;
+
+        // We expect a structure containing received outputs; we conservatively search JSON
+        Ok(rpc_res
+            .result
+            .as_ref()
+            .and_then(|v| v.get("outputs"))
+            .and_then(|o| o.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|o| o.get("address").and_then(|a| a.as_str()) == Some(session.address.as_str()))
+            .filter_map(|o| o.get("amount").and_then(|a| a.as_f64()))
+            .sum())
+    }
+
+    /// Price a tier in a PBaaS currency at the current conversion estimate
+    async fn quote_currency(
+        &self,
+        currency: &str,
+        amount_vrsc: f64,
+        rate_locked_until: chrono::DateTime<Utc>,
+        client_info: &ClientInfo,
+    ) -> AppResult<CurrencyQuote> {
+        let state = self.call("getcurrencystate", json!([currency]), client_info).await?;
+        let currency_id = state
+            .as_array()
+            .and_then(|states| states.last())
+            .and_then(|s| s.pointer("/currencystate/currencyid"))
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string())
+            .ok_or_else(|| AppError::Validation(format!("unknown currency {}", currency)))?;
+
+        let estimate = self
+            .call(
+                "estimateconversion",
+                json!([{ "currency": self.payments_config.native_currency, "convertto": currency, "amount": amount_vrsc }]),
+                client_info,
+            )
+            .await?;
+        let amount = estimate
+            .get("estimatedcurrencyout")
+            .and_then(|a| a.as_f64())
+            .filter(|a| *a > 0.0)
+            .ok_or_else(|| AppError::Rpc("invalid estimateconversion result".into()))?;
+
+        Ok(CurrencyQuote { currency: currency.to_string(), currency_id, amount, rate_locked_until })
+    }
+
+    async fn call(&self, method: &str, params: serde_json::Value, client_info: &ClientInfo) -> AppResult<serde_json::Value> {
+        let rpc_req = RpcRequest::new(method.to_string(), Some(params), Some(json!(Uuid::new_v4().to_string())), client_info.clone());
+        self.rpc_for(&rpc_req.method).send_request(&rpc_req)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+
+    /// Confirmations and block height via getrawtransaction <txid> 1 (verbose); no height while in the mempool
+    async fn confirmations(&self, txid: &str, client_info: &ClientInfo) -> AppResult<(u32, Option<u64>)> {
+        let raw_req = RpcRequest::new(
+            "getrawtransaction".to_string(),
+            Some(serde_json::Value::Array(vec![serde_json::Value::String(txid.to_string()), serde_json::Value::Number(1u64.into())])),
+            Some(json!(Uuid::new_v4().to_string())),
+            client_info.clone(),
+        );
+        let raw_res = self.rpc_for(&raw_req.method).send_request(&raw_req).await?;
+        let result = raw_res.result.unwrap_or_default();
+        let confirmations = result.get("confirmations").and_then(|c| c.as_u64()).unwrap_or(0) as u32;
+        let height = result.get("height").and_then(|h| h.as_u64()).filter(|_| confirmations > 0);
+        Ok((confirmations, height))
+    }
+
+    /// Smallest transparent amount worth broadcasting under the underpayment policy
+    fn min_transparent_payment(&self, session: &PaymentSession) -> f64 {
+        let tolerance = self.payments_config.underpayment_tolerance_vrsc;
+        match self.payments_config.underpayment_policy {
+            UnderpaymentPolicy::Reject => session.amount_vrsc - tolerance,
+            UnderpaymentPolicy::ReducedTier => self
+                .payments_config
+                .tiers
+                .iter()
+                .map(|t| t.amount_vrsc)
+                .fold(session.amount_vrsc, f64::min)
+                - tolerance,
+            // Any top-up counts toward the quote
+            UnderpaymentPolicy::HoldOpen => f64::MIN_POSITIVE,
+        }
+    }
+
+    /// Whether a payment below `min_confirmations` gets a zero-conf token
+    fn zero_conf_eligible(&self, session: &PaymentSession) -> bool {
+        let zero_conf = &self.config.payments.zero_conf;
+        zero_conf.enabled
+            && self.payments_config.min_confirmations > 0
+            && session.amount_vrsc <= zero_conf.max_amount_vrsc
+            && !session.is_expired()
+    }
+
+    /// Short-lived token limited to the tier permissions listed in `[payments.zero_conf]`
+    async fn issue_zero_conf_token(&self, session: &PaymentSession, client_info: &ClientInfo) -> AppResult<String> {
+        let tier = self
+            .find_tier(&session.tier_id)
+            .ok_or_else(|| AppError::Internal("tier not found".into()))?;
+        let zero_conf = &self.config.payments.zero_conf;
+        let mut permissions: Vec<String> = tier.permissions.into_iter().filter(|p| zero_conf.permissions.contains(p)).collect();
+        permissions.push("provisional".to_string());
+        permissions.push("zero_conf".to_string());
+        let token = self
+            .issue_payment_token(session, permissions, Some(zero_conf.token_ttl_seconds), true, client_info)
+            .await?;
+        tracing::info!(target: "audit", event = "payment_zero_conf_token", payment_id = %session.payment_id, tier_id = %session.tier_id);
+        Ok(token)
+    }
+
+    /// Revoke the zero-conf token once the payment behind it is rolled back or fails
+    async fn revoke_zero_conf_token(&self, session: &mut PaymentSession) {
+        if let Some(token) = session.zero_conf_token.take() {
+            let _ = self.revoke_token_by_string(&token).await;
+            tracing::info!(target: "audit", event = "payment_zero_conf_revoked", payment_id = %session.payment_id);
+        }
+    }
+
+    /// Deliver a newly issued token to the session webhook, if any
+    async fn push_token(&self, session: &PaymentSession, stage: TokenStage, token: &str) {
+        let Some(url) = &session.webhook_url else { return };
+        let event = PaymentTokenEvent {
+            payment_id: session.payment_id.clone(),
+            stage,
+            token: token.to_string(),
+            confirmations: session.confirmations,
+        };
+        match self.http.post(url).json(&event).send().await {
+            Ok(response) if response.status().is_success() => {}
+            Ok(response) => tracing::warn!(payment_id = %session.payment_id, status = %response.status(), "Payment token webhook rejected"),
+            Err(e) => tracing::warn!(payment_id = %session.payment_id, "Payment token webhook failed: {}", e),
+        }
+    }
+
     async fn issue_token(&self, session: &PaymentSession, provisional: bool, client_info: &ClientInfo) -> AppResult<String> {
         let tier = self
             .find_tier(&session.tier_id)
@@ -385,37 +1259,407 @@ impl PaymentsService {
         } else {
             permissions.push("paid".to_string());
         }
+        if let Some(PaymentResolution::Overpaid { policy: OverpaymentPolicy::Credit, .. }) = &session.resolution {
+            // Credit the excess as a proportionally higher rate limit
+            let multiplier = session.paid_amount_vrsc / session.amount_vrsc;
+            permissions.push(format!("rate_multiplier_{:.2}", multiplier));
+        }
+        self.issue_payment_token(session, permissions, None, provisional, client_info).await
+    }
 
+    async fn issue_payment_token(
+        &self,
+        session: &PaymentSession,
+        permissions: Vec<String>,
+        expiration_seconds: Option<u64>,
+        provisional: bool,
+        client_info: &ClientInfo,
+    ) -> AppResult<String> {
         let req = TokenIssuanceRequest {
             user_id: format!("pay_{}", session.payment_id),
             permissions,
-            custom_expiration: None,
+            custom_expiration: expiration_seconds,
             client_ip: session.client_ip.clone().or_else(|| Some(client_info.ip_address.clone())),
             user_agent: session.user_agent.clone(),
             mode: TokenIssuanceMode::Anonymous,
             pow_challenge: None,
         };
-        let token_res = self.token_issuer.issue_token(req).await?;
+        let source = IssuanceSource::Payment { payment: session.claim(), provisional };
+        let token_res = self.token_issuer.issue_reviewed_token(req, source).await?;
         Ok(token_res.token)
     }
 
-    async fn revoke_token_by_string(&self, token: &str) -> AppResult<()> {
-        // Decode to extract jti and exp
+    /// Decode one of our tokens; `validate_exp = false` also accepts expired tokens
+    fn token_claims(&self, token: &str, validate_exp: bool) -> AppResult<JwtClaims> {
         let mut validation = Validation::new(Algorithm::HS256);
         validation.set_audience(&[&self.config.security.jwt.audience]);
         validation.set_issuer(&[&self.config.security.jwt.issuer]);
-        let token_data = decode::<JwtClaims>(
+        validation.validate_exp = validate_exp;
+        decode::<JwtClaims>(
             token,
             &DecodingKey::from_secret(self.config.security.jwt.secret_key.as_ref()),
             &validation,
-        ).map_err(|e| AppError::Authentication(format!("JWT decode failed: {}", e)))?;
-        let claims = token_data.claims;
+        )
+        .map(|data| data.claims)
+        .map_err(|e| AppError::Authentication(format!("JWT decode failed: {}", e)))
+    }
+
+    async fn revoke_token_by_string(&self, token: &str) -> AppResult<()> {
+        let claims = self.token_claims(token, false)?;
         let now = Utc::now().timestamp() as u64;
-        let ttl = if (claims.exp as u64) > now { (claims.exp as u64) - now } else { 0 };
+        let ttl = (claims.exp as u64).saturating_sub(now);
         // Revoke with remaining TTL (fallback to 1h if expired)
         let ttl = if ttl == 0 { 3600 } else { ttl };
         self.revocations.revoke(&claims.jti, ttl).await.map_err(|e| AppError::Internal(format!("revocation failed: {}", e)))
     }
 }
 
+/// The daemon does not know the transaction (RPC error -5: dropped from the mempool, never mined)
+fn is_unknown_transaction(error: &AppError) -> bool {
+    let message = error.to_string().to_lowercase();
+    message.contains("\"code\":-5")
+        || message.contains("non-wallet transaction")
+        || message.contains("no such mempool or blockchain transaction")
+}
+
+/// Whether a decoded transaction pays the quoted address
+///
+/// Transparent outputs to the quote address must cover `min_transparent`.
+/// Shielded outputs are opaque until viewed, so for those we only require an
+/// output in the quoted pool; `check_status` verifies the amount later via
+/// `z_viewtransaction`.
+fn outputs_match_quote(decoded: &serde_json::Value, session: &PaymentSession, min_transparent: f64) -> bool {
+    let transparent = transparent_paid(decoded, session);
+    if transparent > 0.0 || session.currency_quote.is_some() {
+        return transparent > 0.0 && transparent + 1e-12 >= min_transparent;
+    }
+
+    let shielded = match session.address_type {
+        ShieldedAddressType::Sapling => decoded.get("vShieldedOutput"),
+        ShieldedAddressType::Orchard => decoded.pointer("/orchard/actions"),
+    };
+    shielded.and_then(|o| o.as_array()).map(|o| !o.is_empty()).unwrap_or(false)
+}
+
+/// Native-currency value of a tx's transparent outputs to the session address
+///
+/// PBaaS quotes count the quoted currency's reserve outputs, converted at the
+/// locked quote rate.
+fn transparent_paid(tx: &serde_json::Value, session: &PaymentSession) -> f64 {
+    let paid: f64 = tx
+        .get("vout")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|o| {
+            o.pointer("/scriptPubKey/addresses")
+                .and_then(|a| a.as_array())
+                .map(|a| a.iter().any(|a| a.as_str() == Some(session.address.as_str())))
+                .unwrap_or(false)
+        })
+        .filter_map(|o| match &session.currency_quote {
+            Some(quote) => o
+                .pointer("/scriptPubKey/reserveoutput/currencyvalues")
+                .and_then(|values| values.get(&quote.currency_id))
+                .and_then(|v| v.as_f64()),
+            None => o.get("value").and_then(|v| v.as_f64()),
+        })
+        .sum();
+    session.to_native_amount(paid)
+}
+
+/// Settle a received amount against the quote
+fn resolve_payment(
+    paid_vrsc: f64,
+    session: &PaymentSession,
+    tiers: &[PaymentTier],
+    underpayment_policy: UnderpaymentPolicy,
+    tolerance_vrsc: f64,
+    overpayment_policy: OverpaymentPolicy,
+) -> PaymentResolution {
+    const EPSILON: f64 = 1e-12;
+    let quoted = session.amount_vrsc;
+    if paid_vrsc > quoted + tolerance_vrsc.max(EPSILON) {
+        return PaymentResolution::Overpaid { excess_vrsc: paid_vrsc - quoted, policy: overpayment_policy };
+    }
+    if paid_vrsc + tolerance_vrsc + EPSILON >= quoted {
+        return PaymentResolution::Exact;
+    }
+
+    let shortfall_vrsc = quoted - paid_vrsc;
+    match underpayment_policy {
+        UnderpaymentPolicy::Reject => PaymentResolution::Rejected { shortfall_vrsc },
+        UnderpaymentPolicy::HoldOpen => PaymentResolution::AwaitingTopUp { shortfall_vrsc },
+        UnderpaymentPolicy::ReducedTier => tiers
+            .iter()
+            .filter(|t| t.amount_vrsc < quoted && paid_vrsc + tolerance_vrsc + EPSILON >= t.amount_vrsc)
+            .max_by(|a, b| a.amount_vrsc.total_cmp(&b.amount_vrsc))
+            .map(|t| PaymentResolution::ReducedTier { tier_id: t.id.clone() })
+            .unwrap_or(PaymentResolution::Rejected { shortfall_vrsc }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(address_type: ShieldedAddressType) -> PaymentSession {
+        PaymentSession {
+            payment_id: "p1".to_string(),
+            tier_id: "basic".to_string(),
+            address: "zs1quote".to_string(),
+            address_type,
+            amount_vrsc: 1.0,
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::minutes(30),
+            client_ip: None,
+            user_agent: None,
+            status: PaymentStatus::Pending,
+            txid: None,
+            confirmations: 0,
+            provisional_token: None,
+            final_token: None,
+            submitted_txids: Vec::new(),
+            paid_amount_vrsc: 0.0,
+            resolution: None,
+            refund_address: None,
+            currency_quote: None,
+            identity: None,
+            identity_verified: false,
+            refund: None,
+            zero_conf_token: None,
+            webhook_url: None,
+            block_height: None,
+        }
+    }
+
+    #[test]
+    fn test_transparent_outputs_must_cover_quote() {
+        let session = session(ShieldedAddressType::Sapling);
+        let pays = |value: f64, address: &str| json!({ "vout": [{ "value": value, "scriptPubKey": { "addresses": [address] } }] });
+        assert!(outputs_match_quote(&pays(1.0, "zs1quote"), &session, 1.0));
+        assert!(!outputs_match_quote(&pays(0.5, "zs1quote"), &session, 1.0));
+        assert!(!outputs_match_quote(&pays(1.0, "RSomeoneElse"), &session, 1.0));
+    }
+
+    #[test]
+    fn test_shielded_outputs_must_use_quoted_pool() {
+        let sapling_tx = json!({ "vout": [], "vShieldedOutput": [{ "cmu": "00" }] });
+        let orchard_tx = json!({ "vout": [], "orchard": { "actions": [{ "cmx": "00" }] } });
+        assert!(outputs_match_quote(&sapling_tx, &session(ShieldedAddressType::Sapling), 1.0));
+        assert!(!outputs_match_quote(&sapling_tx, &session(ShieldedAddressType::Orchard), 1.0));
+        assert!(outputs_match_quote(&orchard_tx, &session(ShieldedAddressType::Orchard), 1.0));
+        assert!(!outputs_match_quote(&json!({ "vout": [] }), &session(ShieldedAddressType::Orchard), 1.0));
+    }
+
+    #[test]
+    fn test_underpayment_policies() {
+        let session = session(ShieldedAddressType::Sapling);
+        let tiers = vec![
+            PaymentTier { id: "basic".into(), amount_vrsc: 1.0, description: None, permissions: vec![], accepted_currencies: vec![] },
+            PaymentTier { id: "lite".into(), amount_vrsc: 0.5, description: None, permissions: vec![], accepted_currencies: vec![] },
+        ];
+        let resolve = |paid, policy, tolerance| resolve_payment(paid, &session, &tiers, policy, tolerance, OverpaymentPolicy::Ignore);
+
+        assert_eq!(resolve(0.9999, UnderpaymentPolicy::Reject, 0.001), PaymentResolution::Exact);
+        assert!(matches!(resolve(0.8, UnderpaymentPolicy::Reject, 0.0), PaymentResolution::Rejected { .. }));
+        assert!(matches!(resolve(0.8, UnderpaymentPolicy::HoldOpen, 0.0), PaymentResolution::AwaitingTopUp { .. }));
+        assert_eq!(resolve(0.8, UnderpaymentPolicy::ReducedTier, 0.0), PaymentResolution::ReducedTier { tier_id: "lite".into() });
+        assert!(matches!(resolve(0.2, UnderpaymentPolicy::ReducedTier, 0.0), PaymentResolution::Rejected { .. }));
+    }
+
+    #[test]
+    fn test_overpayment_reports_excess() {
+        let session = session(ShieldedAddressType::Sapling);
+        let resolution = resolve_payment(1.5, &session, &[], UnderpaymentPolicy::Reject, 0.0, OverpaymentPolicy::Credit);
+        assert_eq!(resolution, PaymentResolution::Overpaid { excess_vrsc: 0.5, policy: OverpaymentPolicy::Credit });
+    }
+
+    #[test]
+    fn test_currency_outputs_convert_at_locked_rate() {
+        let mut session = session(ShieldedAddressType::Sapling);
+        session.address = "RQuote".to_string();
+        session.currency_quote = Some(CurrencyQuote {
+            currency: "vETH".to_string(),
+            currency_id: "iETH".to_string(),
+            amount: 0.002,
+            rate_locked_until: Utc::now() + Duration::minutes(10),
+        });
+        let tx = json!({ "vout": [
+            { "value": 0.0, "scriptPubKey": { "addresses": ["RQuote"], "reserveoutput": { "currencyvalues": { "iETH": 0.001 } } } },
+            { "value": 5.0, "scriptPubKey": { "addresses": ["RQuote"] } }
+        ] });
+
+        // Only the quoted currency counts: 0.001 of 0.002 vETH is half of 1 VRSC
+        assert!((transparent_paid(&tx, &session) - 0.5).abs() < 1e-9);
+        assert!(!outputs_match_quote(&tx, &session, 1.0));
+        assert!(outputs_match_quote(&tx, &session, 0.5));
+    }
+
+    #[tokio::test]
+    async fn test_receipt_signed_and_history_requires_identity_token() {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let config = Arc::new(AppConfig::default());
+        let store = Arc::new(PaymentsStore::new(None));
+        let svc = PaymentsService::new(
+            config.clone(),
+            PaymentsConfig::default(),
+            Arc::new(ExternalRpcAdapter::new(config.clone())),
+            store.clone(),
+            Arc::new(TokenIssuerAdapter::new(config.clone())),
+            Arc::new(RevocationStore::new(None)),
+        );
+
+        let mut paid = session(ShieldedAddressType::Sapling);
+        paid.identity = Some("alice@".to_string());
+        paid.identity_verified = true;
+        paid.status = PaymentStatus::Finalized;
+        paid.txid = Some("tx1".to_string());
+        paid.paid_amount_vrsc = 1.0;
+        paid.block_height = Some(1234);
+        let client_info = ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: None,
+            auth_token: None,
+            timestamp: Utc::now(),
+            request_id: None,
+        };
+        paid.final_token = Some(svc.issue_token(&paid, false, &client_info).await.unwrap());
+        store.put(&paid).await.unwrap();
 
+        // The token carries what a relying party needs to find the payment on chain
+        let payment = svc.token_claims(paid.final_token.as_ref().unwrap(), true).unwrap().payment.unwrap();
+        assert_eq!(payment, paid.claim());
+        assert_eq!(payment.txids, vec!["tx1".to_string()]);
+        assert_eq!(payment.block_height, Some(1234));
+        assert_eq!(payment.tier_id, "basic");
+        store.index_identity("alice@", &paid.payment_id).await.unwrap();
+
+        let receipt = svc.receipt(&paid.payment_id).await.unwrap();
+        assert_eq!(receipt.receipt.txids, vec!["tx1".to_string()]);
+        let key = VerifyingKey::from_bytes(&hex::decode(&receipt.public_key).unwrap().try_into().unwrap()).unwrap();
+        let signature = Signature::from_bytes(&hex::decode(&receipt.signature).unwrap().try_into().unwrap());
+        assert!(key.verify(&serde_json::to_vec(&receipt.receipt).unwrap(), &signature).is_ok());
+
+        let bearer = format!("Bearer {}", paid.final_token.clone().unwrap());
+        let history = svc.history("alice@", Some(&bearer)).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].receipt_available);
+        assert!(svc.history("bob@", Some(&bearer)).await.is_err());
+        assert!(svc.history("alice@", None).await.is_err());
+
+        // A token whose subject names the payment, but that the payment flow did not issue, proves nothing
+        let forged = TokenIssuerAdapter::new(config.clone())
+            .issue_reviewed_token(
+                TokenIssuanceRequest {
+                    user_id: format!("pay_{}", paid.payment_id),
+                    permissions: vec!["read".to_string()],
+                    custom_expiration: None,
+                    client_ip: None,
+                    user_agent: None,
+                    mode: TokenIssuanceMode::Anonymous,
+                    pow_challenge: None,
+                },
+                IssuanceSource::Pow { challenge_id: "c1".to_string() },
+            )
+            .await
+            .unwrap();
+        assert!(svc.history("alice@", Some(&format!("Bearer {}", forged.token))).await.is_err());
+
+        // An identity named in a quote but never signed for is not listed or receipted
+        let mut unproven = session(ShieldedAddressType::Sapling);
+        unproven.payment_id = "p2".to_string();
+        unproven.identity = Some("alice@".to_string());
+        unproven.status = PaymentStatus::Finalized;
+        unproven.final_token = Some(svc.issue_token(&unproven, false, &client_info).await.unwrap());
+        store.put(&unproven).await.unwrap();
+        assert_eq!(svc.receipt("p2").await.unwrap().receipt.identity, None);
+        let bearer = format!("Bearer {}", unproven.final_token.unwrap());
+        assert!(svc.history("alice@", Some(&bearer)).await.is_err());
+    }
+    #[tokio::test]
+    async fn test_manual_refund_opened_listed_and_declined() {
+        let mut config = AppConfig::default();
+        config.payments.refunds.enabled = true;
+        let config = Arc::new(config);
+        let store = Arc::new(PaymentsStore::new(None));
+        let svc = PaymentsService::new(
+            config.clone(),
+            PaymentsConfig::default(),
+            Arc::new(ExternalRpcAdapter::new(config.clone())),
+            store.clone(),
+            Arc::new(TokenIssuerAdapter::new(config.clone())),
+            Arc::new(RevocationStore::new(None)),
+        );
+        let client_info = PaymentsService::internal_client("test");
+
+        // No refund address, or too little to be worth the fee: nothing opened
+        let mut session = session(ShieldedAddressType::Sapling);
+        svc.open_refund(&mut session, RefundReason::RejectedUnderpayment, 0.5, &client_info).await;
+        assert!(session.refund.is_none());
+        session.refund_address = Some("zs1payer".to_string());
+        svc.open_refund(&mut session, RefundReason::RejectedUnderpayment, 0.0005, &client_info).await;
+        assert!(session.refund.is_none());
+
+        svc.open_refund(&mut session, RefundReason::RejectedUnderpayment, 0.5, &client_info).await;
+        let refund = session.refund.clone().unwrap();
+        assert_eq!(refund.status, RefundStatus::PendingApproval);
+        assert_eq!(refund.address, "zs1payer");
+        store.put(&session).await.unwrap();
+
+        let listed = svc.refunds().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].refund.reason, RefundReason::RejectedUnderpayment);
+
+        let declined = svc.decide_refund("p1", false, "ops", Some("payer asked to keep it".into())).await.unwrap();
+        assert_eq!(declined.status, RefundStatus::Declined);
+        assert_eq!(declined.decided_by.as_deref(), Some("ops"));
+        assert!(svc.decide_refund("p1", true, "ops", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_zero_conf_token_limited_refreshed_and_revoked() {
+        let mut config = AppConfig::default();
+        config.payments.zero_conf.enabled = true;
+        config.payments.zero_conf.max_amount_vrsc = 1.0;
+        let config = Arc::new(config);
+        let store = Arc::new(PaymentsStore::new(None));
+        let svc = PaymentsService::new(
+            config.clone(),
+            PaymentsConfig::default(),
+            Arc::new(ExternalRpcAdapter::new(config.clone())),
+            store.clone(),
+            Arc::new(TokenIssuerAdapter::new(config.clone())),
+            Arc::new(RevocationStore::new(None)),
+        );
+        let client_info = PaymentsService::internal_client("test");
+
+        let mut paid = session(ShieldedAddressType::Sapling);
+        assert!(svc.zero_conf_eligible(&paid));
+        paid.amount_vrsc = 5.0;
+        assert!(!svc.zero_conf_eligible(&paid));
+        paid.amount_vrsc = 1.0;
+
+        paid.tier_id = "pro".to_string();
+        let token = svc.issue_zero_conf_token(&paid, &client_info).await.unwrap();
+        let claims = svc.token_claims(&token, true).unwrap();
+        assert_eq!(claims.permissions, vec!["read", "provisional", "zero_conf"]);
+        assert!(claims.exp - claims.iat <= 600);
+
+        paid.status = PaymentStatus::Verified;
+        paid.zero_conf_token = Some(token.clone());
+        store.put(&paid).await.unwrap();
+        let bearer = format!("Bearer {}", token);
+        let refreshed = svc.refresh_token(Some(&bearer), &client_info).await.unwrap();
+        assert_eq!(refreshed.stage, TokenStage::ZeroConf);
+        assert_eq!(refreshed.token, token);
+
+        // Rolled back: the token is revoked and can no longer be refreshed
+        svc.revoke_zero_conf_token(&mut paid).await;
+        store.put(&paid).await.unwrap();
+        assert!(paid.zero_conf_token.is_none());
+        assert!(svc.refresh_token(Some(&bearer), &client_info).await.is_err());
+        assert!(svc.refresh_token(None, &client_info).await.is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! Watch-only portfolio balances
+//!
+//! Portfolio apps without a backend of their own track a set of transparent
+//! addresses and identities and want one total per currency. The daemon
+//! already sums `getaddressbalance` over every address in a request, so a
+//! portfolio costs one JSON-RPC batch: the balance call plus `getblockcount`
+//! for the height it reflects. Identities given as `name@` are resolved to
+//! i-addresses and currency ids to names through the [`NameResolverService`].
+//! Results are cached per address set for `cache_seconds` and carry the time
+//! they were fetched, so clients can tell how fresh a cached answer is.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::application::services::{NameKind, NameResolverService};
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+/// Balance of one currency across the portfolio
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CurrencyBalance {
+    /// Currency i-address
+    pub currency: String,
+    /// Fully qualified name, when the currency resolves
+    pub name: Option<String>,
+    pub balance: f64,
+    pub received: f64,
+}
+
+/// Aggregated balances of a set of addresses and identities
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Portfolio {
+    /// Addresses queried, identities as i-addresses, sorted
+    pub addresses: Vec<String>,
+    /// Chain height the balances were read at
+    pub height: u64,
+    /// When the balances were fetched from the daemon
+    pub as_of: DateTime<Utc>,
+    /// Whether this answer was served from cache
+    pub cached: bool,
+    pub currencies: Vec<CurrencyBalance>,
+}
+
+/// Sums balances per currency over transparent addresses and identities
+pub struct PortfolioService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    resolver: Arc<NameResolverService>,
+    cache: Mutex<HashMap<String, (Instant, Portfolio)>>,
+}
+
+impl PortfolioService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>, resolver: Arc<NameResolverService>) -> Self {
+        Self { config, rpc, resolver, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Balances per currency summed over `addresses`
+    pub async fn portfolio(&self, addresses: Vec<String>) -> AppResult<Portfolio> {
+        let max = self.config.portfolio.max_addresses;
+        if addresses.is_empty() {
+            return Err(AppError::Validation("addresses must not be empty".into()));
+        }
+        if addresses.len() > max {
+            return Err(AppError::Validation(format!("{} addresses requested, limit is {}", addresses.len(), max)));
+        }
+        if let Some(bad) = addresses.iter().find(|address| !is_plausible_address(address)) {
+            return Err(AppError::Validation(format!("invalid address: {}", bad)));
+        }
+
+        let mut resolved = BTreeSet::new();
+        for address in addresses {
+            resolved.insert(self.identity_address(address).await?);
+        }
+        let addresses: Vec<String> = resolved.into_iter().collect();
+        let key = addresses.join(",");
+
+        let ttl = Duration::from_secs(self.config.portfolio.cache_seconds);
+        if let Some((at, portfolio)) = self.cache.lock().await.get(&key) {
+            if at.elapsed() < ttl {
+                return Ok(Portfolio { cached: true, ..portfolio.clone() });
+            }
+        }
+
+        let requests = [
+            Self::request("getaddressbalance", json!([{ "addresses": addresses }])),
+            Self::request("getblockcount", json!([])),
+        ];
+        let mut results = self.rpc.send_batch(&requests).await?.into_iter();
+        let balance = results.next().unwrap_or_else(|| Err(AppError::Rpc("missing batch response".into())))?;
+        let height = results
+            .next()
+            .unwrap_or_else(|| Err(AppError::Rpc("missing batch response".into())))?
+            .as_u64()
+            .ok_or_else(|| AppError::Rpc("getblockcount returned a non-numeric height".into()))?;
+
+        let totals = currency_totals(&balance);
+        let names = join_all(totals.keys().map(|currency| self.resolver.resolve(currency))).await;
+        let currencies = totals
+            .into_iter()
+            .zip(names)
+            .map(|((currency, (balance, received)), name)| CurrencyBalance {
+                currency,
+                // An unresolvable currency still has a balance; report it without a name
+                name: name.ok().flatten().filter(|r| r.kind == NameKind::Currency).map(|r| r.name),
+                balance,
+                received,
+            })
+            .collect();
+        let portfolio = Portfolio { addresses, height, as_of: Utc::now(), cached: false, currencies };
+
+        if !ttl.is_zero() {
+            let capacity = self.config.portfolio.cache_entries;
+            let mut cache = self.cache.lock().await;
+            if cache.len() >= capacity {
+                cache.retain(|_, (at, _)| at.elapsed() < ttl);
+            }
+            if cache.len() < capacity {
+                cache.insert(key, (Instant::now(), portfolio.clone()));
+            }
+        }
+        Ok(portfolio)
+    }
+
+    /// i-address for a `name@` identity; other addresses unchanged
+    async fn identity_address(&self, address: String) -> AppResult<String> {
+        if !address.ends_with('@') {
+            return Ok(address);
+        }
+        match self.resolver.resolve(&address).await? {
+            Some(resolution) => Ok(resolution.id),
+            None => Err(AppError::Validation(format!("unknown identity: {}", address))),
+        }
+    }
+
+    fn request(method: &str, params: Value) -> RpcRequest {
+        RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("portfolio_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("portfolio".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        )
+    }
+}
+
+/// `(balance, received)` per currency id from a `getaddressbalance` result
+fn currency_totals(result: &Value) -> BTreeMap<String, (f64, f64)> {
+    let mut totals: BTreeMap<String, (f64, f64)> = BTreeMap::new();
+    for (field, received) in [("currencybalance", false), ("currencyreceived", true)] {
+        let Some(amounts) = result.get(field).and_then(Value::as_object) else { continue };
+        for (currency, amount) in amounts {
+            let entry = totals.entry(currency.clone()).or_default();
+            let amount = amount.as_f64().unwrap_or(0.0);
+            if received { entry.1 = amount } else { entry.0 = amount }
+        }
+    }
+    totals
+}
+
+/// Transparent addresses, i-addresses and `name@` identities
+fn is_plausible_address(address: &str) -> bool {
+    (1..=128).contains(&address.len())
+        && address.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '-' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(config: AppConfig) -> PortfolioService {
+        let config = Arc::new(config);
+        let rpc = Arc::new(ExternalRpcAdapter::new(config.clone()));
+        let resolver = Arc::new(NameResolverService::new(config.clone(), rpc.clone()));
+        PortfolioService::new(config, rpc, resolver)
+    }
+
+    #[test]
+    fn test_currency_totals_merge_balance_and_received() {
+        let result = json!({
+            "balance": 150000000,
+            "received": 250000000,
+            "currencybalance": { "iJhCezBExJHvtyH3fGhNnt2NhU4Ztkf2yq": 1.5, "i9nwxtKuVYX4MSbeULLiK2ttVi6rUEhh4X": 0.25 },
+            "currencyreceived": { "iJhCezBExJHvtyH3fGhNnt2NhU4Ztkf2yq": 2.5, "i9nwxtKuVYX4MSbeULLiK2ttVi6rUEhh4X": 0.25 }
+        });
+        let totals = currency_totals(&result);
+        assert_eq!(totals["iJhCezBExJHvtyH3fGhNnt2NhU4Ztkf2yq"], (1.5, 2.5));
+        assert_eq!(totals["i9nwxtKuVYX4MSbeULLiK2ttVi6rUEhh4X"], (0.25, 0.25));
+        assert!(currency_totals(&json!({ "balance": 0 })).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_portfolio_validates_and_serves_cache() {
+        let mut config = AppConfig::default();
+        config.portfolio.max_addresses = 2;
+        let service = service(config);
+        assert!(matches!(service.portfolio(vec![]).await, Err(AppError::Validation(_))));
+        let too_many = vec!["RA".to_string(), "RB".to_string(), "RC".to_string()];
+        assert!(matches!(service.portfolio(too_many).await, Err(AppError::Validation(_))));
+        assert!(matches!(service.portfolio(vec!["R bad".to_string()]).await, Err(AppError::Validation(_))));
+
+        let cached = Portfolio {
+            addresses: vec!["RAlice".to_string(), "RBob".to_string()],
+            height: 3000000,
+            as_of: Utc::now(),
+            cached: false,
+            currencies: Vec::new(),
+        };
+        service.cache.lock().await.insert("RAlice,RBob".to_string(), (Instant::now(), cached));
+        let portfolio = service
+            .portfolio(vec!["RBob".to_string(), "RAlice".to_string()])
+            .await
+            .unwrap();
+        assert!(portfolio.cached);
+        assert_eq!(portfolio.height, 3000000);
+    }
+}
@@ -0,0 +1,311 @@
+//! Startup preflight checks
+//!
+//! Runs a set of self-checks against the loaded configuration before the
+//! server starts serving (daemon connectivity, Redis, JWT key sanity, port
+//! availability and method registry consistency). Used by `--check` mode
+//! so CI/CD pipelines can gate deployments on a non-zero exit code.
+
+use crate::{
+    config::AppConfig,
+    domain::{
+        rpc::{ClientInfo, RpcRequest},
+        validation::MethodRegistry,
+    },
+    infrastructure::adapters::{
+        AuthenticationAdapter, ExternalRpcAdapter, TokenIssuanceMode, TokenIssuanceRequest, TokenIssuerAdapter,
+    },
+    shared::security::constant_time_str_eq,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Placeholder secret shipped in the example configuration files
+const EXAMPLE_JWT_SECRET: &str = "your-super-secret-jwt-key-that-is-at-least-32-characters-long";
+
+/// Outcome of a single preflight check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+/// Result of a single preflight check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    /// Check name
+    pub name: String,
+    /// Check outcome
+    pub status: CheckStatus,
+    /// Human readable detail
+    pub detail: String,
+    /// Time spent running the check
+    pub duration_ms: u64,
+}
+
+/// Structured preflight report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightReport {
+    /// Overall result (false if any check failed)
+    pub ok: bool,
+    /// Server version
+    pub version: String,
+    /// Report timestamp
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Individual check results
+    pub checks: Vec<CheckResult>,
+}
+
+impl PreflightReport {
+    /// Process exit code for this report (0 = success, 1 = failure)
+    pub fn exit_code(&self) -> i32 {
+        if self.ok { 0 } else { 1 }
+    }
+}
+
+/// Service that runs startup preflight checks
+pub struct PreflightService {
+    config: Arc<AppConfig>,
+}
+
+impl PreflightService {
+    /// Create a new preflight service
+    pub fn new(config: Arc<AppConfig>) -> Self {
+        Self { config }
+    }
+
+    /// Run all checks and build a report
+    pub async fn run(&self) -> PreflightReport {
+        let mut checks = Vec::new();
+
+        let start = Instant::now();
+        checks.push(Self::result("config", self.check_config(), start));
+        let start = Instant::now();
+        checks.push(Self::result("jwt", self.check_jwt_key().await, start));
+        let start = Instant::now();
+        checks.push(Self::result("port", self.check_port(), start));
+        let start = Instant::now();
+        checks.push(Self::result("method_registry", self.check_method_registry(), start));
+        let start = Instant::now();
+        checks.push(Self::result("daemon", self.check_daemon().await, start));
+        let start = Instant::now();
+        checks.push(Self::result("redis", self.check_redis().await, start));
+
+        PreflightReport {
+            ok: checks.iter().all(|c| c.status != CheckStatus::Fail),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: chrono::Utc::now(),
+            checks,
+        }
+    }
+
+    fn result(name: &str, outcome: (CheckStatus, String), start: Instant) -> CheckResult {
+        CheckResult {
+            name: name.to_string(),
+            status: outcome.0,
+            detail: outcome.1,
+            duration_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+
+    /// Validate configuration values
+    fn check_config(&self) -> (CheckStatus, String) {
+        // Cross-field checks first: their errors name the key to change and how
+        if let Err(e) = crate::config::ConfigValidator::validate_config(&self.config) {
+            return (CheckStatus::Fail, e.to_string());
+        }
+        if let Err(e) = self.config.validate_config() {
+            return (CheckStatus::Fail, format!("configuration validation failed: {}", e));
+        }
+        if self.config.security.development_mode {
+            return (CheckStatus::Warn, "development_mode is enabled".to_string());
+        }
+        (CheckStatus::Pass, "configuration is valid".to_string())
+    }
+
+    /// Check JWT algorithm, secret length and an issue/validate round-trip
+    async fn check_jwt_key(&self) -> (CheckStatus, String) {
+        let jwt = &self.config.security.jwt;
+        if jwt.algorithm != "HS256" {
+            return (CheckStatus::Fail, format!("JWT algorithm is {}, only HS256 is supported", jwt.algorithm));
+        }
+        if jwt.secret_key.len() < 32 {
+            return (CheckStatus::Fail, format!("JWT secret is {} bytes, minimum is 32", jwt.secret_key.len()));
+        }
+
+        let issuer = TokenIssuerAdapter::new(self.config.clone());
+        let request = TokenIssuanceRequest {
+            user_id: "preflight".to_string(),
+            permissions: vec!["read".to_string()],
+            client_ip: None,
+            user_agent: None,
+            custom_expiration: Some(60),
+            mode: TokenIssuanceMode::Anonymous,
+            pow_challenge: None,
+        };
+        let token = match issuer.issue_token(request).await {
+            Ok(res) => res.token,
+            Err(e) => return (CheckStatus::Fail, format!("HS256 token issuance failed: {}", e)),
+        };
+        let auth = AuthenticationAdapter::new(self.config.clone());
+        if let Err(e) = auth.validate_token(&format!("Bearer {}", token)).await {
+            return (CheckStatus::Fail, format!("HS256 token round-trip failed: {}", e));
+        }
+
+        if constant_time_str_eq(&jwt.secret_key, EXAMPLE_JWT_SECRET) {
+            return (CheckStatus::Warn, "JWT secret is the example value from the shipped configuration".to_string());
+        }
+        (CheckStatus::Pass, "HS256 key is usable".to_string())
+    }
+
+    /// Check that the listen address can be bound
+    fn check_port(&self) -> (CheckStatus, String) {
+        let addr = self.config.server_address();
+        match std::net::TcpListener::bind(&addr) {
+            Ok(listener) => {
+                drop(listener);
+                (CheckStatus::Pass, format!("{} is available", addr))
+            }
+            Err(e) => (CheckStatus::Fail, format!("cannot bind {}: {}", addr, e)),
+        }
+    }
+
+    /// Check that registered method parameter rules are well-formed
+    fn check_method_registry(&self) -> (CheckStatus, String) {
+        let registry = MethodRegistry::new();
+        let problems = Self::registry_problems(&registry);
+        let count = registry.list_methods().count();
+        if problems.is_empty() {
+            (CheckStatus::Pass, format!("{} methods registered", count))
+        } else {
+            (CheckStatus::Fail, problems.join("; "))
+        }
+    }
+
+    /// Collect consistency problems in the method registry
+    pub fn registry_problems(registry: &MethodRegistry) -> Vec<String> {
+        let mut problems = Vec::new();
+        for method in registry.list_methods() {
+            let mut seen_optional = false;
+            for (position, rule) in method.parameter_rules.iter().enumerate() {
+                if rule.index != position {
+                    problems.push(format!("{}: parameter '{}' has index {} at position {}", method.name, rule.name, rule.index, position));
+                }
+                if rule.required && seen_optional {
+                    problems.push(format!("{}: required parameter '{}' follows an optional one", method.name, rule.name));
+                }
+                seen_optional |= !rule.required;
+            }
+        }
+        problems.sort();
+        problems
+    }
+
+    /// Check daemon connectivity and RPC credentials with `getinfo`
+    async fn check_daemon(&self) -> (CheckStatus, String) {
+        let adapter = ExternalRpcAdapter::new(self.config.clone());
+        let request = RpcRequest::new(
+            "getinfo".to_string(),
+            Some(serde_json::Value::Array(vec![])),
+            Some(serde_json::json!("preflight")),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("preflight".to_string()),
+                auth_token: None,
+                timestamp: chrono::Utc::now(),
+                request_id: None,
+            },
+        );
+        match adapter.send_request(&request).await {
+            Ok(response) => {
+                let blocks = response.result.as_ref()
+                    .and_then(|r| r.get("blocks"))
+                    .and_then(|b| b.as_u64())
+                    .map(|b| b.to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                (CheckStatus::Pass, format!("daemon reachable at {} (blocks: {})", self.config.verus.rpc_url, blocks))
+            }
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("401") || msg.contains("403") {
+                    (CheckStatus::Fail, format!("daemon rejected RPC credentials: {}", msg))
+                } else {
+                    (CheckStatus::Fail, format!("daemon unreachable: {}", msg))
+                }
+            }
+        }
+    }
+
+    /// Check Redis reachability when caching is enabled
+    async fn check_redis(&self) -> (CheckStatus, String) {
+        if !self.config.cache.enabled {
+            return (CheckStatus::Skipped, "cache disabled".to_string());
+        }
+        let client = match redis::Client::open(self.config.cache.redis_url.clone()) {
+            Ok(client) => client,
+            Err(e) => return (CheckStatus::Fail, format!("invalid redis url: {}", e)),
+        };
+        let ping = async {
+            let mut conn = redis::aio::ConnectionManager::new(client).await?;
+            redis::cmd("PING").query_async::<String>(&mut conn).await
+        };
+        match tokio::time::timeout(Duration::from_secs(5), ping).await {
+            Ok(Ok(_)) => (CheckStatus::Pass, "redis responded to PING".to_string()),
+            Ok(Err(e)) => (CheckStatus::Fail, format!("redis error: {}", e)),
+            Err(_) => (CheckStatus::Fail, "redis PING timed out".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_registry_is_consistent() {
+        let registry = MethodRegistry::new();
+        assert!(PreflightService::registry_problems(&registry).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_jwt_check_rejects_short_secret() {
+        let mut config = AppConfig::default();
+        config.security.jwt.secret_key = "short".to_string();
+        let service = PreflightService::new(Arc::new(config));
+        let (status, _) = service.check_jwt_key().await;
+        assert_eq!(status, CheckStatus::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_jwt_check_rejects_other_algorithm() {
+        let mut config = AppConfig::default();
+        config.security.jwt.algorithm = "RS256".to_string();
+        let service = PreflightService::new(Arc::new(config));
+        let (status, detail) = service.check_jwt_key().await;
+        assert_eq!(status, CheckStatus::Fail);
+        assert!(detail.contains("RS256"));
+    }
+
+    #[tokio::test]
+    async fn test_redis_check_skipped_when_cache_disabled() {
+        let mut config = AppConfig::default();
+        config.cache.enabled = false;
+        let service = PreflightService::new(Arc::new(config));
+        let (status, _) = service.check_redis().await;
+        assert_eq!(status, CheckStatus::Skipped);
+    }
+
+    #[test]
+    fn test_report_exit_code() {
+        let report = PreflightReport {
+            ok: false,
+            version: "0".to_string(),
+            timestamp: chrono::Utc::now(),
+            checks: vec![],
+        };
+        assert_eq!(report.exit_code(), 1);
+    }
+}
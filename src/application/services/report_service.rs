@@ -0,0 +1,287 @@
+//! Scheduled reports
+//!
+//! Each report in `[reports]` runs a query template on a cron schedule (in
+//! UTC) and publishes the result to any of three outputs: a JSON file per run
+//! under `output_dir`, a JSON POST to a webhook, or the response cache, from
+//! which `GET /reports/{name}` serves the latest run without touching the
+//! daemon. Templates are a supply snapshot of configured currencies, mempool
+//! statistics, or any read-only method with fixed parameters. Runs that fail
+//! are logged and retried at the next scheduled time.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::config::app_config::{ReportConfig, ReportOutput, ReportTemplate};
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::domain::validation::DomainValidator;
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::middleware::cache::CacheMiddleware;
+use crate::shared::cron::CronSchedule;
+use crate::shared::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+/// One run of a report
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Report {
+    pub name: String,
+    pub template: ReportTemplate,
+    pub generated_at: DateTime<Utc>,
+    pub data: Value,
+}
+
+/// Runs the configured reports and publishes their results
+pub struct ReportService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    cache: Arc<CacheMiddleware>,
+    http: reqwest::Client,
+    /// Latest run of each report with a `cache` output, for when the response cache is off
+    latest: RwLock<HashMap<String, Report>>,
+}
+
+impl ReportService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>, cache: Arc<CacheMiddleware>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.reports.webhook_timeout_seconds))
+            .build()
+            .unwrap_or_default();
+        Self { config, rpc, cache, http, latest: RwLock::new(HashMap::new()) }
+    }
+
+    /// Schedule every configured report; `rpc` reports on write methods are skipped
+    pub fn start(self: Arc<Self>) {
+        let validator = DomainValidator::new();
+        for report in &self.config.reports.reports {
+            let Ok(schedule) = report.schedule.parse::<CronSchedule>() else {
+                warn!(report = %report.name, "Report schedule is invalid; skipping");
+                continue;
+            };
+            if let (ReportTemplate::Rpc, Some(method)) = (report.template, &report.method) {
+                let read_only = validator.get_method_definition(method).is_some_and(|m| m.read_only);
+                if !read_only {
+                    warn!(report = %report.name, method = %method, "Report method is unknown or not read-only; skipping");
+                    continue;
+                }
+            }
+
+            let service = self.clone();
+            let report = report.clone();
+            tokio::spawn(async move {
+                while let Some(next) = schedule.next_after(Utc::now()) {
+                    let wait = (next - Utc::now()).to_std().unwrap_or_default();
+                    tokio::time::sleep(wait).await;
+                    match service.run(&report).await {
+                        Ok(result) => service.publish(&report, &result).await,
+                        Err(e) => warn!(report = %report.name, "Report run failed: {}", e),
+                    }
+                }
+                warn!(report = %report.name, schedule = %schedule, "Report schedule never fires again");
+            });
+            info!(report = %report.name, schedule = %report.schedule, "Scheduled report");
+        }
+    }
+
+    /// Run a report's query now
+    pub async fn run(&self, report: &ReportConfig) -> AppResult<Report> {
+        let data = match report.template {
+            ReportTemplate::SupplySnapshot => self.supply_snapshot(&report.currencies).await?,
+            ReportTemplate::MempoolStats => {
+                let requests = [Self::request("getblockcount", json!([])), Self::request("getmempoolinfo", json!([]))];
+                let mut results = self.rpc.send_batch(&requests).await?.into_iter();
+                let height = results.next().unwrap_or_else(|| Err(AppError::Rpc("missing batch response".into())))?;
+                let mempool = results.next().unwrap_or_else(|| Err(AppError::Rpc("missing batch response".into())))?;
+                json!({ "height": height, "mempool": mempool })
+            }
+            ReportTemplate::Rpc => {
+                let method = report.method.as_deref().unwrap_or_default();
+                self.rpc
+                    .send_request(&Self::request(method, Value::Array(report.params.clone())))
+                    .await?
+                    .result
+                    .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))?
+            }
+        };
+        Ok(Report { name: report.name.clone(), template: report.template, generated_at: Utc::now(), data })
+    }
+
+    /// Latest run of a report published to the cache; `None` before its first run
+    pub async fn latest(&self, name: &str) -> AppResult<Option<Report>> {
+        let cached = self.config.reports.reports.iter().any(|r| r.name == name && r.outputs.contains(&ReportOutput::Cache));
+        if !cached {
+            return Ok(None);
+        }
+        if let Some(entry) = self.cache.get_cached_response(&cache_key(name)).await? {
+            if let Ok(report) = serde_json::from_slice(&entry.data) {
+                return Ok(Some(report));
+            }
+        }
+        Ok(self.latest.read().await.get(name).cloned())
+    }
+
+    /// Send a run to every output of its report; failures are logged per output
+    async fn publish(&self, report: &ReportConfig, result: &Report) {
+        for output in &report.outputs {
+            let published = match output {
+                ReportOutput::Disk => self.write_file(result).await,
+                ReportOutput::Webhook => self.post_webhook(report.webhook_url.as_deref().unwrap_or_default(), result).await,
+                ReportOutput::Cache => self.store(result).await,
+            };
+            match published {
+                Ok(()) => debug!(report = %report.name, output = ?output, "Published report"),
+                Err(e) => warn!(report = %report.name, output = ?output, "Report publishing failed: {}", e),
+            }
+        }
+    }
+
+    async fn write_file(&self, result: &Report) -> AppResult<()> {
+        let dir = PathBuf::from(&self.config.reports.output_dir);
+        let path = dir.join(file_name(result));
+        let data = serde_json::to_vec_pretty(result)?;
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to create {}: {}", dir.display(), e)))?;
+        tokio::fs::write(&path, data)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to write {}: {}", path.display(), e)))
+    }
+
+    async fn post_webhook(&self, url: &str, result: &Report) -> AppResult<()> {
+        let response = self
+            .http
+            .post(url)
+            .json(result)
+            .send()
+            .await
+            .map_err(|e| AppError::Http(format!("Report webhook failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Http(format!("Report webhook rejected with {}", response.status())));
+        }
+        Ok(())
+    }
+
+    async fn store(&self, result: &Report) -> AppResult<()> {
+        self.latest.write().await.insert(result.name.clone(), result.clone());
+        let entry = self.cache.create_cache_entry(
+            cache_key(&result.name),
+            serde_json::to_vec(result)?,
+            "application/json".to_string(),
+            self.config.reports.cache_ttl_seconds,
+        );
+        self.cache.cache_response(entry).await
+    }
+
+    /// Supply and reserves of each currency at one height
+    async fn supply_snapshot(&self, currencies: &[String]) -> AppResult<Value> {
+        let mut requests = vec![Self::request("getblockcount", json!([]))];
+        requests.extend(currencies.iter().map(|currency| Self::request("getcurrency", json!([currency]))));
+        let mut results = self.rpc.send_batch(&requests).await?.into_iter();
+        let height = results.next().unwrap_or_else(|| Err(AppError::Rpc("missing batch response".into())))?;
+        let snapshots: Vec<Value> = currencies
+            .iter()
+            .zip(results)
+            .map(|(name, result)| match result {
+                Ok(currency) => {
+                    let state = currency.get("bestcurrencystate");
+                    json!({
+                        "currency": name,
+                        "currencyid": currency.get("currencyid"),
+                        "supply": state.and_then(|s| s.get("supply")),
+                        "reserves": state.and_then(|s| s.get("reservecurrencies")),
+                    })
+                }
+                Err(e) => json!({ "currency": name, "error": e.to_string() }),
+            })
+            .collect();
+        Ok(json!({ "height": height, "currencies": snapshots }))
+    }
+
+    fn request(method: &str, params: Value) -> RpcRequest {
+        RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("report_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("reports".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        )
+    }
+}
+
+fn cache_key(name: &str) -> String {
+    format!("verus_rpc:report:{}", name)
+}
+
+/// `{name}-{YYYYMMDDTHHMMSSZ}.json`
+fn file_name(result: &Report) -> String {
+    format!("{}-{}.json", result.name, result.generated_at.format("%Y%m%dT%H%M%SZ"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn report_config(name: &str, outputs: Vec<ReportOutput>) -> ReportConfig {
+        ReportConfig {
+            name: name.to_string(),
+            schedule: "0 0 * * *".to_string(),
+            template: ReportTemplate::MempoolStats,
+            currencies: Vec::new(),
+            method: None,
+            params: Vec::new(),
+            outputs,
+            webhook_url: None,
+        }
+    }
+
+    fn report(name: &str) -> Report {
+        Report {
+            name: name.to_string(),
+            template: ReportTemplate::MempoolStats,
+            generated_at: Utc.with_ymd_and_hms(2026, 10, 16, 0, 0, 0).unwrap(),
+            data: json!({ "height": 3000000, "mempool": { "size": 12 } }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_latest_serves_cached_outputs_only() {
+        let mut config = AppConfig::default();
+        config.reports.reports = vec![report_config("mempool", vec![ReportOutput::Cache]), report_config("quiet", vec![ReportOutput::Disk])];
+        let cache = Arc::new(CacheMiddleware::new(&config).await.unwrap());
+        let config = Arc::new(config);
+        let service = ReportService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)), cache);
+
+        assert!(service.latest("mempool").await.unwrap().is_none());
+        service.publish(&report_config("mempool", vec![ReportOutput::Cache]), &report("mempool")).await;
+        assert_eq!(service.latest("mempool").await.unwrap(), Some(report("mempool")));
+
+        service.latest.write().await.insert("quiet".to_string(), report("quiet"));
+        assert!(service.latest("quiet").await.unwrap().is_none());
+        assert!(service.latest("unknown").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_report_config_validation() {
+        use validator::Validate;
+
+        assert!(report_config("daily-supply", vec![ReportOutput::Disk]).validate().is_ok());
+        assert!(report_config("../escape", vec![ReportOutput::Disk]).validate().is_err());
+        assert!(ReportConfig { schedule: "every day".to_string(), ..report_config("a", vec![]) }.validate().is_err());
+        assert!(ReportConfig { template: ReportTemplate::SupplySnapshot, ..report_config("a", vec![]) }.validate().is_err());
+        assert!(ReportConfig { template: ReportTemplate::Rpc, ..report_config("a", vec![]) }.validate().is_err());
+        assert!(report_config("a", vec![ReportOutput::Webhook]).validate().is_err());
+        let webhook = ReportConfig { webhook_url: Some("https://hooks.example.com/reports".to_string()), ..report_config("a", vec![ReportOutput::Webhook]) };
+        assert!(webhook.validate().is_ok());
+        assert_eq!(file_name(&report("daily")), "daily-20261016T000000Z.json");
+    }
+}
@@ -0,0 +1,248 @@
+//! Priority scheduling of upstream daemon calls
+//!
+//! Limits the number of concurrent calls to the daemon and, when the limit is
+//! reached, hands freed slots to verified clients (high priority) before
+//! everyone else. A request is high priority only when its token carries a
+//! marker the token service adds itself once a payment, proof of work,
+//! partner signature or stake proof checks out; free tokens from `/issue`
+//! queue with anonymous traffic. To keep that traffic from starving, a
+//! waiting low priority request is granted a slot after every
+//! `high_priority_burst` consecutive high priority grants.
+
+use crate::config::app_config::SchedulerConfig;
+use crate::shared::error::{AppError, AppResult};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Issuer-granted permissions that mark a verified client
+const PRIORITY_PERMISSIONS: &[&str] = &["paid", "pow_validated", "partner_validated", "stake_validated"];
+
+/// Scheduling priority for a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestPriority {
+    /// Paid, proof-of-work, partner and staking clients
+    High,
+    /// Anonymous traffic and unverified tokens
+    Low,
+}
+
+impl RequestPriority {
+    /// Priority of a request whose token was verified to carry `permissions`
+    pub fn for_permissions(permissions: &[String]) -> Self {
+        if permissions.iter().any(|permission| PRIORITY_PERMISSIONS.contains(&permission.as_str())) {
+            RequestPriority::High
+        } else {
+            RequestPriority::Low
+        }
+    }
+}
+
+/// Point-in-time view of the scheduler
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerStats {
+    pub capacity: usize,
+    pub in_flight: usize,
+    pub high_waiting: usize,
+    pub low_waiting: usize,
+}
+
+#[derive(Debug)]
+struct SchedulerState {
+    available: usize,
+    high: VecDeque<oneshot::Sender<()>>,
+    low: VecDeque<oneshot::Sender<()>>,
+    consecutive_high: u32,
+}
+
+/// Priority-aware limiter for upstream daemon calls
+#[derive(Debug)]
+pub struct RequestScheduler {
+    capacity: usize,
+    high_priority_burst: u32,
+    max_queue_wait: Duration,
+    state: Arc<Mutex<SchedulerState>>,
+}
+
+/// Slot held for the duration of an upstream call; released on drop
+#[derive(Debug)]
+pub struct SchedulerPermit {
+    state: Arc<Mutex<SchedulerState>>,
+    high_priority_burst: u32,
+}
+
+impl RequestScheduler {
+    /// Create a scheduler from configuration
+    pub fn new(config: &SchedulerConfig) -> Self {
+        let capacity = config.max_concurrent_upstream.max(1);
+        Self {
+            capacity,
+            high_priority_burst: config.high_priority_burst.max(1),
+            max_queue_wait: Duration::from_millis(config.max_queue_wait_ms),
+            state: Arc::new(Mutex::new(SchedulerState {
+                available: capacity,
+                high: VecDeque::new(),
+                low: VecDeque::new(),
+                consecutive_high: 0,
+            })),
+        }
+    }
+
+    /// Wait for an upstream slot at the given priority
+    ///
+    /// Fails with `AppError::RateLimit` if no slot is granted within the
+    /// configured maximum queue wait.
+    pub async fn acquire(&self, priority: RequestPriority) -> AppResult<SchedulerPermit> {
+        let mut rx = {
+            let mut state = self.lock_state()?;
+            if state.available > 0 && state.high.is_empty() && state.low.is_empty() {
+                state.available -= 1;
+                return Ok(self.permit());
+            }
+            let (tx, rx) = oneshot::channel();
+            match priority {
+                RequestPriority::High => state.high.push_back(tx),
+                RequestPriority::Low => state.low.push_back(tx),
+            }
+            rx
+        };
+
+        match tokio::time::timeout(self.max_queue_wait, &mut rx).await {
+            Ok(Ok(())) => Ok(self.permit()),
+            Ok(Err(_)) => Err(AppError::Internal("Request scheduler closed".to_string())),
+            Err(_) => {
+                // A slot may have been granted just as the wait expired; hand it back
+                rx.close();
+                if rx.try_recv().is_ok() {
+                    drop(self.permit());
+                }
+                tracing::warn!(priority = ?priority, "Timed out waiting for an upstream slot");
+                Err(AppError::RateLimit)
+            }
+        }
+    }
+
+    /// Current scheduler occupancy
+    pub fn stats(&self) -> SchedulerStats {
+        match self.state.lock() {
+            Ok(state) => SchedulerStats {
+                capacity: self.capacity,
+                in_flight: self.capacity - state.available,
+                high_waiting: state.high.len(),
+                low_waiting: state.low.len(),
+            },
+            Err(_) => SchedulerStats { capacity: self.capacity, in_flight: 0, high_waiting: 0, low_waiting: 0 },
+        }
+    }
+
+    fn permit(&self) -> SchedulerPermit {
+        SchedulerPermit { state: self.state.clone(), high_priority_burst: self.high_priority_burst }
+    }
+
+    fn lock_state(&self) -> AppResult<std::sync::MutexGuard<'_, SchedulerState>> {
+        self.state
+            .lock()
+            .map_err(|_| AppError::Internal("Request scheduler lock poisoned".to_string()))
+    }
+}
+
+impl Drop for SchedulerPermit {
+    fn drop(&mut self) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        // Hand the slot directly to the next waiter; skip waiters that gave up
+        loop {
+            let take_high = !state.high.is_empty()
+                && (state.low.is_empty() || state.consecutive_high < self.high_priority_burst);
+            let next = if take_high {
+                state.consecutive_high += 1;
+                state.high.pop_front()
+            } else if let Some(tx) = state.low.pop_front() {
+                state.consecutive_high = 0;
+                Some(tx)
+            } else {
+                None
+            };
+            match next {
+                Some(tx) => {
+                    if tx.send(()).is_ok() {
+                        return;
+                    }
+                }
+                None => {
+                    state.available += 1;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(capacity: usize, burst: u32) -> Arc<RequestScheduler> {
+        Arc::new(RequestScheduler::new(&SchedulerConfig {
+            enabled: true,
+            max_concurrent_upstream: capacity,
+            high_priority_burst: burst,
+            max_queue_wait_ms: 1000,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_high_priority_is_served_first_without_starving_low() {
+        let scheduler = scheduler(1, 2);
+        let held = scheduler.acquire(RequestPriority::Low).await.unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for (label, priority) in [("low", RequestPriority::Low), ("high1", RequestPriority::High), ("high2", RequestPriority::High), ("high3", RequestPriority::High)] {
+            let task_scheduler = scheduler.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = task_scheduler.acquire(priority).await.unwrap();
+                order.lock().unwrap().push(label);
+            }));
+            tokio::task::yield_now().await;
+            while scheduler.stats().high_waiting + scheduler.stats().low_waiting < handles.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        drop(held);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec!["high1", "high2", "low", "high3"]);
+        assert_eq!(scheduler.stats().in_flight, 0);
+    }
+
+    #[test]
+    fn test_only_verified_markers_get_high_priority() {
+        let permissions = |list: &[&str]| list.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(RequestPriority::for_permissions(&permissions(&["read", "paid"])), RequestPriority::High);
+        assert_eq!(RequestPriority::for_permissions(&permissions(&["read", "pow_validated"])), RequestPriority::High);
+        assert_eq!(RequestPriority::for_permissions(&permissions(&["read", "write"])), RequestPriority::Low);
+        assert_eq!(RequestPriority::for_permissions(&permissions(&["read", "provisional", "zero_conf"])), RequestPriority::Low);
+        assert_eq!(RequestPriority::for_permissions(&[]), RequestPriority::Low);
+    }
+
+    #[tokio::test]
+    async fn test_queue_wait_timeout_returns_rate_limit() {
+        let scheduler = Arc::new(RequestScheduler::new(&SchedulerConfig {
+            enabled: true,
+            max_concurrent_upstream: 1,
+            high_priority_burst: 4,
+            max_queue_wait_ms: 10,
+        }));
+        let _held = scheduler.acquire(RequestPriority::High).await.unwrap();
+        let result = scheduler.acquire(RequestPriority::Low).await;
+        assert!(matches!(result, Err(AppError::RateLimit)));
+    }
+}
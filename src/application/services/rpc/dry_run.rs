@@ -0,0 +1,263 @@
+//! `testrawtransaction`: validate a raw transaction without broadcasting it
+//!
+//! Answered by the proxy rather than forwarded, so wallets get a preflight
+//! through the same authentication, validation and rate-limit path as
+//! `sendrawtransaction`. The checks mirror `testmempoolaccept`: the
+//! transaction must decode, must not already be known, every transparent
+//! input must be an unspent output (`gettxout` including the mempool), it must
+//! not have expired, and the fee must be non-negative and below `max_fee`.
+
+use crate::{
+    config::app_config::DryRunConfig,
+    domain::rpc::RpcRequest,
+    infrastructure::adapters::ExternalRpcAdapter,
+    shared::error::{AppError, AppResult},
+};
+use futures::future::try_join_all;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Name of the proxy-answered method
+pub const METHOD: &str = "testrawtransaction";
+
+/// State of one transparent input
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct InputCheck {
+    pub txid: String,
+    pub vout: u64,
+    /// `unspent` or `missing` (spent, including by the mempool, or never existed)
+    pub status: &'static str,
+    pub value: Option<f64>,
+}
+
+/// Structured result of a dry run
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DryRunVerdict {
+    pub txid: Option<String>,
+    /// Whether the daemon would be expected to accept the transaction
+    pub allowed: bool,
+    /// Machine-readable reason when `allowed` is false
+    pub reject_reason: Option<String>,
+    /// Transparent fee in native coin, when every input value is known
+    pub fee: Option<f64>,
+    pub size: Option<u64>,
+    pub inputs: Vec<InputCheck>,
+    pub warnings: Vec<String>,
+}
+
+impl DryRunVerdict {
+    fn rejected(txid: Option<String>, reason: &str) -> Self {
+        Self {
+            txid,
+            allowed: false,
+            reject_reason: Some(reason.to_string()),
+            fee: None,
+            size: None,
+            inputs: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// Run the dry run for a `testrawtransaction [hexstring, allowhighfees]` request
+pub async fn test_raw_transaction(
+    rpc: &ExternalRpcAdapter,
+    settings: &DryRunConfig,
+    request: &RpcRequest,
+) -> AppResult<DryRunVerdict> {
+    let params = request.parameters.as_ref().and_then(|p| p.as_array());
+    let hex = params
+        .and_then(|p| p.first())
+        .and_then(|h| h.as_str())
+        .ok_or_else(|| AppError::InvalidParameters { method: METHOD.to_string(), reason: "hexstring is required".to_string() })?;
+    let allow_high_fees = params.and_then(|p| p.get(1)).and_then(|a| a.as_bool()).unwrap_or(false);
+    let decoded = match call(rpc, request, "decoderawtransaction", json!([hex])).await {
+        Ok(decoded) if decoded.is_object() => decoded,
+        Ok(_) | Err(AppError::Rpc(_)) => return Ok(DryRunVerdict::rejected(None, "decode_failed")),
+        Err(e) => return Err(e),
+    };
+    let txid = decoded.get("txid").and_then(|t| t.as_str()).map(|t| t.to_string());
+    let outpoints = transparent_inputs(&decoded);
+    if outpoints.len() > settings.max_inputs {
+        return Ok(DryRunVerdict::rejected(txid, "too_many_inputs"));
+    }
+    if let Some(txid) = &txid {
+        if call(rpc, request, "getrawtransaction", json!([txid, 0])).await.is_ok() {
+            return Ok(DryRunVerdict::rejected(Some(txid.clone()), "already_known"));
+        }
+    }
+
+    let mut outputs = Vec::with_capacity(outpoints.len());
+    for chunk in outpoints.chunks(settings.max_concurrency.max(1)) {
+        let mut lookups = Vec::with_capacity(chunk.len());
+        for (prev, n) in chunk {
+            lookups.push(call(rpc, request, "gettxout", json!([prev, n, true])));
+        }
+        outputs.extend(try_join_all(lookups).await?);
+    }
+    let inputs: Vec<InputCheck> = outpoints
+        .into_iter()
+        .zip(outputs)
+        .map(|((txid, vout), output)| InputCheck {
+            txid,
+            vout,
+            status: if output.is_null() { "missing" } else { "unspent" },
+            value: output.get("value").and_then(|v| v.as_f64()),
+        })
+        .collect();
+    let tip = call(rpc, request, "getblockcount", json!([])).await?.as_u64().unwrap_or(0);
+
+    Ok(verdict(&decoded, inputs, tip, allow_high_fees, settings))
+}
+
+/// Forward one read-only lookup upstream on behalf of the dry-run request
+async fn call(rpc: &ExternalRpcAdapter, request: &RpcRequest, method: &'static str, params: Value) -> AppResult<Value> {
+    let upstream = RpcRequest {
+        method: method.to_string(),
+        parameters: Some(params),
+        id: request.id.clone(),
+        client_info: request.client_info.clone(),
+        upstream: request.upstream.clone(),
+    };
+    rpc.send_request(&upstream).await.map(|r| r.result.unwrap_or(Value::Null))
+}
+
+/// Apply the acceptance rules to a decoded transaction and its input lookups
+fn verdict(decoded: &Value, inputs: Vec<InputCheck>, tip: u64, allow_high_fees: bool, settings: &DryRunConfig) -> DryRunVerdict {
+    let mut result = DryRunVerdict {
+        txid: decoded.get("txid").and_then(|t| t.as_str()).map(|t| t.to_string()),
+        allowed: true,
+        reject_reason: None,
+        fee: None,
+        size: decoded.get("size").and_then(|s| s.as_u64()),
+        inputs,
+        warnings: Vec::new(),
+    };
+    let reject = |result: &mut DryRunVerdict, reason: &str| {
+        if result.reject_reason.is_none() {
+            result.allowed = false;
+            result.reject_reason = Some(reason.to_string());
+        }
+    };
+
+    if result.inputs.iter().any(|i| i.status == "missing") {
+        reject(&mut result, "missing_inputs");
+    }
+    let expiry = decoded.get("expiryheight").and_then(|e| e.as_u64()).unwrap_or(0);
+    if expiry != 0 && expiry <= tip + 1 {
+        reject(&mut result, "tx_expired");
+    }
+
+    // Shielded value entering the transparent pool counts towards the inputs
+    let input_value: Option<f64> = result.inputs.iter().map(|i| i.value).sum();
+    if let Some(input_value) = input_value {
+        let output_value: f64 = decoded
+            .get("vout")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|o| o.get("value").and_then(|v| v.as_f64()))
+            .sum();
+        let shielded = shielded_value_balance(decoded);
+        let fee = round_coin(input_value + shielded - output_value);
+        result.fee = Some(fee);
+        if fee < 0.0 {
+            reject(&mut result, "negative_fee");
+        } else if fee > settings.max_fee && !allow_high_fees {
+            reject(&mut result, "absurdly_high_fee");
+        } else if fee < settings.min_relay_fee {
+            result.warnings.push(format!("fee {} is below the default relay fee {}", fee, settings.min_relay_fee));
+        }
+    }
+    if result.inputs.is_empty() && shielded_value_balance(decoded) == 0.0 {
+        result.warnings.push("transaction has no transparent inputs and no shielded value; fee not checked".to_string());
+    }
+    result
+}
+
+/// Outpoints spent by transparent inputs
+fn transparent_inputs(decoded: &Value) -> Vec<(String, u64)> {
+    decoded
+        .get("vin")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|input| Some((input.get("txid")?.as_str()?.to_string(), input.get("vout")?.as_u64()?)))
+        .collect()
+}
+
+/// Net value moved from the shielded pools into the transparent pool
+fn shielded_value_balance(decoded: &Value) -> f64 {
+    let sapling = decoded.get("valueBalance").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let orchard = decoded.pointer("/orchard/valueBalance").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let sprout: f64 = decoded
+        .get("vjoinsplit")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .map(|js| {
+            js.get("vpub_new").and_then(|v| v.as_f64()).unwrap_or(0.0) - js.get("vpub_old").and_then(|v| v.as_f64()).unwrap_or(0.0)
+        })
+        .sum();
+    sapling + orchard + sprout
+}
+
+fn round_coin(value: f64) -> f64 {
+    (value * 1e8).round() / 1e8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(status: &'static str, value: Option<f64>) -> InputCheck {
+        InputCheck { txid: "prev".into(), vout: 0, status, value }
+    }
+
+    fn tx(outputs: &[f64]) -> Value {
+        json!({
+            "txid": "abc",
+            "size": 250,
+            "expiryheight": 0,
+            "vin": [{ "txid": "prev", "vout": 0 }],
+            "vout": outputs.iter().map(|v| json!({ "value": v })).collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn test_accepts_spendable_transaction() {
+        let result = verdict(&tx(&[0.5, 0.4999]), vec![input("unspent", Some(1.0))], 100, false, &DryRunConfig::default());
+        assert!(result.allowed);
+        assert_eq!(result.fee, Some(0.0001));
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_missing_inputs_and_bad_fees() {
+        let settings = DryRunConfig::default();
+        let result = verdict(&tx(&[0.5]), vec![input("missing", None)], 100, false, &settings);
+        assert_eq!(result.reject_reason.as_deref(), Some("missing_inputs"));
+        assert_eq!(result.fee, None);
+
+        let result = verdict(&tx(&[2.0]), vec![input("unspent", Some(1.0))], 100, false, &settings);
+        assert_eq!(result.reject_reason.as_deref(), Some("negative_fee"));
+
+        let result = verdict(&tx(&[0.5]), vec![input("unspent", Some(1.0))], 100, false, &settings);
+        assert_eq!(result.reject_reason.as_deref(), Some("absurdly_high_fee"));
+        assert!(verdict(&tx(&[0.5]), vec![input("unspent", Some(1.0))], 100, true, &settings).allowed);
+    }
+
+    #[test]
+    fn test_rejects_expired_and_counts_shielded_value() {
+        let mut decoded = tx(&[1.0]);
+        decoded["expiryheight"] = json!(100);
+        let result = verdict(&decoded, vec![input("unspent", Some(1.0))], 100, false, &DryRunConfig::default());
+        assert_eq!(result.reject_reason.as_deref(), Some("tx_expired"));
+
+        let mut decoded = tx(&[1.4999]);
+        decoded["valueBalance"] = json!(0.5);
+        let result = verdict(&decoded, vec![input("unspent", Some(1.0))], 100, false, &DryRunConfig::default());
+        assert!(result.allowed);
+        assert_eq!(result.fee, Some(0.0001));
+    }
+}
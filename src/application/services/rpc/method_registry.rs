@@ -348,7 +348,7 @@ mod tests {
     fn getblock_rules_include_hash_minlen() {
         let m = get_method_info("getblock").expect("method exists");
         let hash_rule = m.parameter_rules.iter().find(|r| r.name == "hash").expect("hash rule");
-        assert!(matches!(hash_rule.constraints.get(0), Some(Constraint::MinLength(64))));
+        assert!(matches!(hash_rule.constraints.first(), Some(Constraint::MinLength(64))));
     }
 }
 
@@ -3,5 +3,5 @@
 pub mod token_extraction;
 pub mod parameter_validation;
 pub mod method_registry;
-
-
+pub mod dry_run;
+pub mod rewrite;
@@ -0,0 +1,198 @@
+//! Operator-defined parameter rewriting
+//!
+//! `[rewrite]` rules fill in defaults, force values or clamp numbers in the
+//! positional parameters of a method, e.g. force the verbosity of `getblock`
+//! or cap the block range of `getexports`. Rules run after validation and
+//! before the request goes upstream; every change is written to the `audit`
+//! log. Named (object) parameters are left alone. Filling a position past the
+//! end of the array pads the gap with `null`, which the daemon treats as an
+//! omitted argument.
+
+use crate::{
+    config::app_config::{RewriteConfig, RewriteRuleConfig},
+    domain::rpc::RpcRequest,
+};
+use serde_json::{Number, Value};
+use tracing::info;
+
+/// One change made by a rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedRewrite {
+    pub position: usize,
+    pub field: Option<String>,
+    /// `default`, `set`, `min` or `max`
+    pub action: &'static str,
+    pub value: Value,
+}
+
+/// Copy of `request` with the rules for its method applied; `None` when nothing changed
+pub fn apply(config: &RewriteConfig, request: &RpcRequest) -> Option<RpcRequest> {
+    if !config.enabled {
+        return None;
+    }
+    let mut params = request.parameters.clone().unwrap_or_else(|| Value::Array(Vec::new()));
+    let applied = rewrite_params(&config.rules, &request.method, &mut params);
+    if applied.is_empty() {
+        return None;
+    }
+    for change in &applied {
+        info!(
+            target: "audit",
+            event = "request_rewritten",
+            method = %request.method,
+            client_ip = %request.client_info.ip_address,
+            position = change.position,
+            field = change.field.as_deref().unwrap_or(""),
+            action = change.action,
+            value = %change.value,
+            "Request parameter rewritten"
+        );
+    }
+    let mut rewritten = request.clone();
+    rewritten.parameters = Some(params);
+    Some(rewritten)
+}
+
+/// Apply the rules for `method` to positional `params` in place
+pub fn rewrite_params(rules: &[RewriteRuleConfig], method: &str, params: &mut Value) -> Vec<AppliedRewrite> {
+    let Value::Array(items) = params else {
+        return Vec::new();
+    };
+    let mut applied = Vec::new();
+    for rule in rules.iter().filter(|rule| rule.method == method) {
+        let mut record = |action: &'static str, value: &Value| {
+            applied.push(AppliedRewrite {
+                position: rule.position,
+                field: rule.field.clone(),
+                action,
+                value: value.clone(),
+            });
+        };
+
+        if let Some(value) = &rule.set {
+            if let Some(slot) = slot(items, rule, true).filter(|current| *current != value) {
+                *slot = value.clone();
+                record("set", value);
+            }
+        } else if let Some(value) = &rule.default {
+            if let Some(slot) = slot(items, rule, true).filter(|current| current.is_null()) {
+                *slot = value.clone();
+                record("default", value);
+            }
+        }
+
+        if let Some(slot) = slot(items, rule, false) {
+            if let Some((action, clamped)) = clamp(slot, rule.min, rule.max) {
+                *slot = clamped;
+                record(action, slot);
+            }
+        }
+    }
+    applied
+}
+
+/// Value addressed by `rule`; with `create`, missing positions and fields are added as `null`
+///
+/// `None` when the position is missing (without `create`) or `field` is set
+/// and the parameter is neither an object nor null.
+fn slot<'a>(items: &'a mut Vec<Value>, rule: &RewriteRuleConfig, create: bool) -> Option<&'a mut Value> {
+    if items.len() <= rule.position {
+        if !create {
+            return None;
+        }
+        items.resize(rule.position + 1, Value::Null);
+    }
+    let item = &mut items[rule.position];
+    let Some(field) = &rule.field else {
+        return Some(item);
+    };
+    if create && item.is_null() {
+        *item = Value::Object(Default::default());
+    }
+    let object = item.as_object_mut()?;
+    if create {
+        Some(object.entry(field.clone()).or_insert(Value::Null))
+    } else {
+        object.get_mut(field)
+    }
+}
+
+/// `value` clamped into `[min, max]`, with the bound that applied; `None` when it is in range or not a number
+fn clamp(value: &Value, min: Option<f64>, max: Option<f64>) -> Option<(&'static str, Value)> {
+    let number = value.as_f64()?;
+    let (action, bound) = match (min, max) {
+        (Some(min), _) if number < min => ("min", min),
+        (_, Some(max)) if number > max => ("max", max),
+        _ => return None,
+    };
+    // Keep integer parameters integral when the bound is a whole number
+    let clamped = if (value.is_i64() || value.is_u64()) && bound.fract() == 0.0 {
+        Value::Number(Number::from(bound as i64))
+    } else {
+        Number::from_f64(bound).map(Value::Number)?
+    };
+    Some((action, clamped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(method: &str, position: usize) -> RewriteRuleConfig {
+        RewriteRuleConfig {
+            method: method.to_string(),
+            position,
+            field: None,
+            default: None,
+            set: None,
+            min: None,
+            max: None,
+        }
+    }
+
+    #[test]
+    fn test_set_default_and_clamp() {
+        let mut verbosity = rule("getblock", 1);
+        verbosity.set = Some(json!(2));
+        let mut count = rule("getexports", 2);
+        count.max = Some(100.0);
+        let mut include_mempool = rule("getaddressbalance", 0);
+        include_mempool.field = Some("friendlynames".to_string());
+        include_mempool.default = Some(json!(true));
+        let rules = vec![verbosity, count, include_mempool];
+
+        let mut params = json!(["00ab"]);
+        let applied = rewrite_params(&rules, "getblock", &mut params);
+        assert_eq!(params, json!(["00ab", 2]));
+        assert_eq!(applied[0].action, "set");
+
+        let mut params = json!(["VRSC", 10, 5000]);
+        let applied = rewrite_params(&rules, "getexports", &mut params);
+        assert_eq!(params, json!(["VRSC", 10, 100]));
+        assert_eq!(applied[0].action, "max");
+
+        let mut params = json!([{"addresses": ["R1"]}]);
+        rewrite_params(&rules, "getaddressbalance", &mut params);
+        assert_eq!(params, json!([{"addresses": ["R1"], "friendlynames": true}]));
+
+        let mut params = json!([{"addresses": ["R1"], "friendlynames": false}]);
+        assert!(rewrite_params(&rules, "getaddressbalance", &mut params).is_empty());
+    }
+
+    #[test]
+    fn test_unchanged_requests_and_named_params() {
+        let mut cap = rule("getexports", 1);
+        cap.min = Some(0.0);
+        cap.max = Some(100.0);
+        let rules = vec![cap];
+
+        let mut in_range = json!(["VRSC", 50]);
+        assert!(rewrite_params(&rules, "getexports", &mut in_range).is_empty());
+        let mut missing = json!(["VRSC"]);
+        assert!(rewrite_params(&rules, "getexports", &mut missing).is_empty());
+        assert_eq!(missing, json!(["VRSC"]));
+        let mut named = json!({"chainname": "VRSC", "count": 5000});
+        assert!(rewrite_params(&rules, "getexports", &mut named).is_empty());
+    }
+}
@@ -22,7 +22,9 @@ mod tests {
                 user_agent: ua.map(|s| s.to_string()),
                 auth_token: None,
                 timestamp: chrono::Utc::now(),
+                request_id: None,
             },
+            upstream: None,
         }
     }
 
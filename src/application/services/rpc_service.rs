@@ -1,21 +1,43 @@
 //! RPC service that orchestrates RPC operations
 
+use super::{
+    request_scheduler::{RequestPriority, RequestScheduler},
+    rpc::{dry_run, rewrite},
+};
 use crate::{
     config::AppConfig,
-    domain::{rpc::*, security::*},
-    infrastructure::adapters::ComprehensiveValidator,
+    domain::{
+        rpc::*,
+        security::*,
+        validation::{MethodRegistry, RuleCheck, TokenScopes, ValidationTrace},
+    },
+    infrastructure::adapters::{partners, ComprehensiveValidator, SensitiveMethodAlerts, UpstreamGuards},
     shared::error::AppResult,
 };
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 use tracing::{info, warn};
 
+fn method_registry() -> &'static MethodRegistry {
+    static REGISTRY: OnceLock<MethodRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(MethodRegistry::new)
+}
+
 /// RPC service that orchestrates RPC operations
 pub struct RpcService {
     _config: Arc<AppConfig>,
     security_validator: Arc<SecurityValidator>,
     external_rpc_adapter: Arc<crate::infrastructure::adapters::ExternalRpcAdapter>,
+    /// Adapters for `[upstreams]`, used by requests pinned to them
+    upstreams: HashMap<String, Arc<crate::infrastructure::adapters::ExternalRpcAdapter>>,
     auth_adapter: Arc<crate::infrastructure::adapters::AuthenticationAdapter>,
     comprehensive_validator: Arc<ComprehensiveValidator>,
+    scheduler: Option<Arc<RequestScheduler>>,
+    sensitive_alerts: SensitiveMethodAlerts,
+    #[cfg(feature = "wasm-plugins")]
+    plugins: Option<Arc<crate::infrastructure::adapters::wasm_plugins::WasmPlugins>>,
+    #[cfg(feature = "scripting")]
+    hooks: Option<Arc<crate::infrastructure::adapters::script_hooks::ScriptHooks>>,
 }
 
 impl RpcService {
@@ -23,13 +45,27 @@ impl RpcService {
     pub fn new(config: Arc<AppConfig>, security_validator: Arc<SecurityValidator>) -> Self {
         let external_rpc_adapter = Arc::new(crate::infrastructure::adapters::ExternalRpcAdapter::new(config.clone()));
         let auth_adapter = Arc::new(crate::infrastructure::adapters::AuthenticationAdapter::new(config.clone()));
-        let comprehensive_validator = Arc::new(ComprehensiveValidator::new());
+        let comprehensive_validator = Arc::new(ComprehensiveValidator::from_config(&config.validation_cache));
+        let scheduler = Self::build_scheduler(&config);
+        let upstreams = Self::build_upstreams(&config, external_rpc_adapter.guards());
+        let sensitive_alerts = SensitiveMethodAlerts::new(&config);
+        #[cfg(feature = "wasm-plugins")]
+        let plugins = crate::infrastructure::adapters::wasm_plugins::WasmPlugins::load(&config.wasm_plugins);
+        #[cfg(feature = "scripting")]
+        let hooks = crate::infrastructure::adapters::script_hooks::ScriptHooks::load(&config.scripting);
         Self {
             _config: config,
             security_validator,
             external_rpc_adapter,
+            upstreams,
             auth_adapter,
             comprehensive_validator,
+            scheduler,
+            sensitive_alerts,
+            #[cfg(feature = "wasm-plugins")]
+            plugins,
+            #[cfg(feature = "scripting")]
+            hooks,
         }
     }
 
@@ -41,28 +77,78 @@ impl RpcService {
         auth_adapter: Arc<crate::infrastructure::adapters::AuthenticationAdapter>,
         comprehensive_validator: Arc<ComprehensiveValidator>,
     ) -> Self {
+        let scheduler = Self::build_scheduler(&config);
+        let upstreams = Self::build_upstreams(&config, external_rpc_adapter.guards());
+        let sensitive_alerts = SensitiveMethodAlerts::new(&config);
+        #[cfg(feature = "wasm-plugins")]
+        let plugins = crate::infrastructure::adapters::wasm_plugins::WasmPlugins::load(&config.wasm_plugins);
+        #[cfg(feature = "scripting")]
+        let hooks = crate::infrastructure::adapters::script_hooks::ScriptHooks::load(&config.scripting);
         Self {
             _config: config,
             security_validator,
             external_rpc_adapter,
+            upstreams,
             auth_adapter,
             comprehensive_validator,
+            scheduler,
+            sensitive_alerts,
+            #[cfg(feature = "wasm-plugins")]
+            plugins,
+            #[cfg(feature = "scripting")]
+            hooks,
         }
     }
 
-    /// Process RPC request with circuit breaker protection
-    pub async fn process_request(&self, request: &RpcRequest) -> AppResult<RpcResponse> {
-        info!(
-            method = %request.method,
-            client_ip = %request.client_info.ip_address,
-            "Processing RPC request with circuit breaker protection"
-        );
+    /// Evaluate the validation rules for a request without sending it upstream
+    ///
+    /// Only available in development mode or to tokens the issuer granted
+    /// `debug`; clients cannot request it for themselves. The checks the request path enforces (security policy, token
+    /// scope, method allowlist) are recorded alongside the registry's
+    /// per-parameter rules, which explain why the allowlist refused a call.
+    pub async fn explain_validation(&self, request: &RpcRequest) -> AppResult<ValidationTrace> {
+        let user_permissions = match &request.client_info.auth_token {
+            Some(token) => self.auth_adapter.validate_token(token).await.unwrap_or_default(),
+            None => vec![],
+        };
+        let permitted = user_permissions.iter().any(|permission| permission == "debug");
+        if !self._config.security.development_mode && !permitted {
+            return Err(crate::shared::error::AppError::Security(
+                "Validation explain mode requires development mode or a debug token".to_string(),
+            ));
+        }
+
+        let security_context = self.security_context(request, user_permissions);
+        let mut trace = ValidationTrace::new(&request.method);
+        trace.record(RuleCheck::request(
+            "security_policy",
+            &self.security_validator.validate_request(&request.method, &security_context),
+        ));
+        trace.record(RuleCheck::request(
+            "token_scope",
+            &TokenScopes::from_permissions(&security_context.user_permissions).ensure_allowed(&request.method, method_registry()),
+        ));
+        method_registry().explain_parameters(&request.method, &request.parameters, &mut trace);
+        trace.record(RuleCheck::request(
+            "method_allowlist",
+            &self.comprehensive_validator.validate_method(&request.method, &request.parameters),
+        ));
+        Ok(trace)
+    }
 
-        // Extract and validate authentication token
+    /// Validate the caller's token, then apply the security policy and token scopes to `request`
+    ///
+    /// The REST facade runs this for the daemon method each endpoint calls,
+    /// so its callers are held to the same rules as `POST /`. Returns the
+    /// permissions of the verified token (none for anonymous callers).
+    pub async fn authorize(&self, request: &RpcRequest) -> AppResult<Vec<String>> {
         let user_permissions = if let Some(auth_token) = &request.client_info.auth_token {
             match self.auth_adapter.validate_token(auth_token).await {
                 Ok(permissions) => {
                     info!("Authentication successful for user");
+                    if let Some(partner_id) = partners::partner_id_from_permissions(&permissions) {
+                        partners::PartnerUsageRegistry::global().record_request(partner_id);
+                    }
                     permissions
                 }
                 Err(e) => {
@@ -75,7 +161,18 @@ impl RpcService {
         };
 
         // Create security context for validation
-        let security_context = crate::domain::security::SecurityContext {
+        let security_context = self.security_context(request, user_permissions);
+
+        // Validate request against security policy
+        self.security_validator.validate_request(&request.method, &security_context)?;
+
+        // Scoped tokens may only call the methods and categories they carry
+        TokenScopes::from_permissions(&security_context.user_permissions).ensure_allowed(&request.method, method_registry())?;
+        Ok(security_context.user_permissions)
+    }
+
+    fn security_context(&self, request: &RpcRequest, user_permissions: Vec<String>) -> SecurityContext {
+        SecurityContext {
             client_ip: request.client_info.ip_address.clone(),
             user_agent: request.client_info.user_agent.clone(),
             auth_token: request.client_info.auth_token.clone(),
@@ -83,24 +180,118 @@ impl RpcService {
             timestamp: request.client_info.timestamp,
             request_id: request.client_info.timestamp.timestamp_millis().to_string(),
             development_mode: self._config.security.development_mode,
-        };
+        }
+    }
 
-        // Validate request against security policy
-        self.security_validator.validate_request(&request.method, &security_context)?;
+    fn build_scheduler(config: &AppConfig) -> Option<Arc<RequestScheduler>> {
+        config
+            .scheduler
+            .enabled
+            .then(|| Arc::new(RequestScheduler::new(&config.scheduler)))
+    }
+
+    fn build_upstreams(
+        config: &Arc<AppConfig>,
+        guards: &UpstreamGuards,
+    ) -> HashMap<String, Arc<crate::infrastructure::adapters::ExternalRpcAdapter>> {
+        config
+            .upstreams
+            .iter()
+            .map(|(name, upstream)| {
+                let adapter = crate::infrastructure::adapters::ExternalRpcAdapter::for_upstream(config.clone(), upstream)
+                    .with_guards(guards.clone());
+                (name.clone(), Arc::new(adapter))
+            })
+            .collect()
+    }
+
+    /// Adapter for the upstream a request is pinned to
+    fn upstream_for(&self, request: &RpcRequest) -> AppResult<&Arc<crate::infrastructure::adapters::ExternalRpcAdapter>> {
+        match &request.upstream {
+            None => Ok(&self.external_rpc_adapter),
+            Some(name) => self
+                .upstreams
+                .get(name)
+                .ok_or_else(|| crate::shared::error::AppError::Config(format!("unknown upstream {}", name))),
+        }
+    }
+
+    /// Process RPC request with circuit breaker protection
+    pub async fn process_request(&self, request: &RpcRequest) -> AppResult<RpcResponse> {
+        let outcome = self.handle_request(request).await;
+        #[cfg(feature = "scripting")]
+        if let (Some(hooks), Err(error)) = (&self.hooks, &outcome) {
+            hooks.on_error(request, error);
+        }
+        outcome
+    }
+
+    async fn handle_request(&self, request: &RpcRequest) -> AppResult<RpcResponse> {
+        info!(
+            method = %request.method,
+            client_ip = %request.client_info.ip_address,
+            "Processing RPC request with circuit breaker protection"
+        );
+
+        // Alert on key export/import, identity revocation and the like, whether or not the call is allowed
+        self.sensitive_alerts.notify(request);
+
+        // Token, security policy and token scopes
+        let permissions = self.authorize(request).await?;
 
         // Validate request parameters
         self.comprehensive_validator.validate_method(&request.method, &request.parameters)?;
 
+        // Operator rewrite rules; the result is validated again so a bad rule cannot send invalid params upstream
+        let rewritten = rewrite::apply(&self._config.rewrite, request);
+        let request = match &rewritten {
+            Some(rewritten) => {
+                self.comprehensive_validator.validate_method(&rewritten.method, &rewritten.parameters)?;
+                rewritten
+            }
+            None => request,
+        };
+
+        // Operator policy script sees the request as it will go upstream
+        #[cfg(feature = "scripting")]
+        if let Some(hooks) = &self.hooks {
+            hooks.on_request(request)?;
+        }
+
+        // Write-class methods are refused while in maintenance mode
+        self.external_rpc_adapter.guards().gate.ensure_writable(request).await?;
+
+        // Tenants pinned to an upstream are served by its daemon only
+        let upstream = self.upstream_for(request)?;
+
         // Check if daemon is available via circuit breaker
-        if !self.external_rpc_adapter.is_available().await {
+        if !upstream.is_available().await {
             warn!("Daemon unavailable (circuit breaker open), providing fallback response");
             return self.provide_fallback_response(request).await;
         }
 
+        // Verified clients (paid, PoW, partner, stake) are served ahead of free and anonymous traffic
+        let priority = RequestPriority::for_permissions(&permissions);
+        let _permit = match &self.scheduler {
+            Some(scheduler) => Some(scheduler.acquire(priority).await?),
+            None => None,
+        };
+
+        // Dry runs are answered here from read-only upstream lookups
+        if request.method == dry_run::METHOD {
+            let verdict = dry_run::test_raw_transaction(upstream, &self._config.dry_run, request).await?;
+            let result = serde_json::to_value(verdict).map_err(|e| crate::shared::error::AppError::Json(e.to_string()))?;
+            return Ok(RpcResponse::success(result, request.id.clone()));
+        }
+
         // Process the request through the external RPC adapter
-        match self.external_rpc_adapter.send_request(request).await {
+        match upstream.send_request(request).await {
             Ok(response) => {
                 info!("RPC request processed successfully");
+                #[cfg(feature = "wasm-plugins")]
+                let response = self.apply_plugins(request, response).await?;
+                #[cfg(feature = "scripting")]
+                let response = self.apply_response_hook(request, response)?;
                 Ok(response)
             }
             Err(error) => {
@@ -117,6 +308,24 @@ impl RpcService {
         }
     }
 
+    /// Run the WASM response plugins registered for the method over a successful result
+    #[cfg(feature = "wasm-plugins")]
+    async fn apply_plugins(&self, request: &RpcRequest, mut response: RpcResponse) -> AppResult<RpcResponse> {
+        if let (Some(plugins), Some(result)) = (&self.plugins, response.result.take()) {
+            response.result = Some(plugins.transform(&request.method, request.parameters.as_ref(), result).await?);
+        }
+        Ok(response)
+    }
+
+    /// Run the policy script's `on_response` hook over a successful result
+    #[cfg(feature = "scripting")]
+    fn apply_response_hook(&self, request: &RpcRequest, mut response: RpcResponse) -> AppResult<RpcResponse> {
+        if let (Some(hooks), Some(result)) = (&self.hooks, response.result.take()) {
+            response.result = Some(hooks.on_response(request, result)?);
+        }
+        Ok(response)
+    }
+
     /// Check if the error is related to connectivity issues
     fn is_connectivity_error(&self, error: &crate::shared::error::AppError) -> bool {
         match error {
@@ -315,6 +524,11 @@ impl RpcService {
         self.security_validator.clone()
     }
 
+    /// Get the upstream request scheduler, if enabled
+    pub fn get_scheduler(&self) -> Option<Arc<RequestScheduler>> {
+        self.scheduler.clone()
+    }
+
     /// Get authentication adapter for external validation
     pub fn get_auth_adapter(&self) -> Arc<crate::infrastructure::adapters::AuthenticationAdapter> {
         self.auth_adapter.clone()
@@ -348,7 +562,9 @@ mod tests {
                 user_agent: Some("test-agent".to_string()),
                 auth_token: None,
                 timestamp: Utc::now(),
+                request_id: None,
             },
+            upstream: None,
         }
     }
 
@@ -362,7 +578,9 @@ mod tests {
                 user_agent: Some("test-agent".to_string()),
                 auth_token: Some(auth_token.to_string()),
                 timestamp: Utc::now(),
+                request_id: None,
             },
+            upstream: None,
         }
     }
 
@@ -377,6 +595,28 @@ mod tests {
         assert!(service.get_external_rpc_adapter().is_available().await);
     }
 
+    #[test]
+    fn test_pinned_requests_use_their_upstream() {
+        let mut config = create_test_config();
+        config.upstreams.insert(
+            "wallet".to_string(),
+            crate::config::app_config::UpstreamConfig {
+                rpc_url: "http://10.0.0.2:27486".to_string(),
+                auth_method: Default::default(),
+                rpc_user: "wallet".to_string(),
+                rpc_password: "wallet-password".to_string(),
+                cookie_file: None,
+            },
+        );
+        let service = RpcService::new(Arc::new(config), Arc::new(SecurityValidator::new(Default::default())));
+
+        let request = create_test_rpc_request("getinfo", json!([]));
+        assert!(Arc::ptr_eq(service.upstream_for(&request).unwrap(), &service.external_rpc_adapter));
+        let pinned = request.clone().with_upstream(Some("wallet".to_string()));
+        assert!(Arc::ptr_eq(service.upstream_for(&pinned).unwrap(), &service.upstreams["wallet"]));
+        assert!(service.upstream_for(&request.with_upstream(Some("missing".to_string()))).is_err());
+    }
+
     #[tokio::test]
     async fn test_rpc_service_new_with_dependencies() {
         let config = Arc::new(create_test_config());
@@ -635,4 +875,25 @@ mod tests {
             }
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_explain_validation_requires_development_or_debug() {
+        let security_validator = Arc::new(SecurityValidator::new(Default::default()));
+        let service = RpcService::new(Arc::new(create_test_config()), security_validator.clone());
+        let request = create_test_rpc_request("getblock", json!([12]));
+        assert!(matches!(
+            service.explain_validation(&request).await,
+            Err(crate::shared::error::AppError::Security(_))
+        ));
+
+        let mut config = create_test_config();
+        config.security.development_mode = true;
+        let service = RpcService::new(Arc::new(config), security_validator);
+        let trace = service.explain_validation(&request).await.unwrap();
+        assert!(!trace.passed);
+        let failure = trace.failure.unwrap();
+        assert_eq!(failure.rule, "type");
+        assert_eq!(failure.parameter_index, Some(0));
+        assert_eq!(trace.checks.last().unwrap().rule, "method_allowlist");
+    }
+}
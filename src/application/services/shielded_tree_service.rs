@@ -0,0 +1,237 @@
+//! Shielded note commitment tree states
+//!
+//! Light shielded wallets sync note commitments through the proxy and need
+//! the Sapling tree state (`getsaplingtree`) at the heights they start from.
+//! A tree state only changes if its block is reorganized away, so states at
+//! least `immutable_confirmations` deep are cached indefinitely, up to
+//! `cache_entries`; shallower ones are always fetched. Range requests page
+//! through consecutive heights with the shared list parameters. Cached states
+//! above a reorg's fork height are dropped when the reorg is detected.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::pagination::{ListQuery, ListSpec, Page, Position};
+use chrono::Utc;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+/// Query parameters accepted by `GET /api/shielded/tree`
+pub const SHIELDED_TREE_LIST: ListSpec = ListSpec {
+    sort_fields: &["height"],
+    descending: false,
+    filters: &["min_height", "max_height"],
+};
+
+/// Concurrent `getsaplingtree` calls per range request
+const FETCH_CONCURRENCY: usize = 8;
+
+/// Tree state at one height
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TreeState {
+    pub height: u64,
+    /// The daemon's `getsaplingtree` result for the height, unchanged
+    pub tree: Value,
+}
+
+/// Immutable tree states, oldest inserted evicted first
+struct TreeCache {
+    entries: BTreeMap<u64, Value>,
+    order: VecDeque<u64>,
+    capacity: usize,
+}
+
+impl TreeCache {
+    fn new(capacity: usize) -> Self {
+        Self { entries: BTreeMap::new(), order: VecDeque::new(), capacity }
+    }
+
+    fn insert(&mut self, height: u64, tree: Value) {
+        if self.capacity == 0 || self.entries.insert(height, tree).is_some() {
+            return;
+        }
+        self.order.push_back(height);
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => { self.entries.remove(&oldest); }
+                None => break,
+            }
+        }
+    }
+
+    /// Drop states at `height` and above; returns how many were removed
+    fn remove_from(&mut self, height: u64) -> usize {
+        let removed = self.entries.split_off(&height).len();
+        self.order.retain(|h| *h < height);
+        removed
+    }
+}
+
+/// Serves Sapling tree states by height
+pub struct ShieldedTreeService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    cache: Mutex<TreeCache>,
+}
+
+impl ShieldedTreeService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        let cache = Mutex::new(TreeCache::new(config.shielded_tree.cache_entries));
+        Self { config, rpc, cache }
+    }
+
+    /// Tree state at `height`; `None` above the chain tip
+    pub async fn tree_state(&self, height: u64) -> AppResult<Option<TreeState>> {
+        let tip = self.tip().await?;
+        if height > tip {
+            return Ok(None);
+        }
+        self.fetch(height, tip).await.map(Some)
+    }
+
+    /// Page of tree states for consecutive heights between `min_height` and `max_height` (default: the tip)
+    pub async fn tree_states(&self, query: &ListQuery) -> AppResult<Page<TreeState>> {
+        let min = query
+            .filter::<u64>("min_height")?
+            .ok_or_else(|| AppError::Validation("min_height is required".into()))?;
+        let tip = self.tip().await?;
+        let max = query.filter::<u64>("max_height")?.unwrap_or(tip).min(tip);
+        let limit = query.limit(20, self.config.shielded_tree.max_page_size);
+        let (heights, total, next_cursor) = page_heights(query, min, max, limit)?;
+
+        let items = stream::iter(heights)
+            .map(|height| async move { self.fetch(height, tip).await })
+            .buffered(FETCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(Page { items, total, next_cursor })
+    }
+
+    /// Drop cached states at `height` and above; returns how many were removed
+    pub async fn invalidate_from(&self, height: u64) -> usize {
+        self.cache.lock().await.remove_from(height)
+    }
+
+    async fn fetch(&self, height: u64, tip: u64) -> AppResult<TreeState> {
+        if let Some(tree) = self.cache.lock().await.entries.get(&height) {
+            return Ok(TreeState { height, tree: tree.clone() });
+        }
+        let result = self.call("getsaplingtree", json!([height])).await?;
+        // The daemon answers with a list of states; take the one for this height
+        let tree = match result {
+            Value::Array(states) => states
+                .iter()
+                .find(|state| state.get("height").and_then(Value::as_u64) == Some(height))
+                .or_else(|| states.first())
+                .cloned()
+                .ok_or_else(|| AppError::Rpc(format!("getsaplingtree returned no state for height {}", height)))?,
+            state => state,
+        };
+        if tip.saturating_sub(height) + 1 >= self.config.shielded_tree.immutable_confirmations {
+            self.cache.lock().await.insert(height, tree.clone());
+        }
+        Ok(TreeState { height, tree })
+    }
+
+    async fn tip(&self) -> AppResult<u64> {
+        self.call("getblockcount", json!([]))
+            .await?
+            .as_u64()
+            .ok_or_else(|| AppError::Rpc("getblockcount returned a non-numeric height".into()))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("shielded_tree_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("shielded-tree".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+/// Heights on the requested page of `min..=max`, the range size and the next cursor
+///
+/// The cursor is the last height returned.
+fn page_heights(query: &ListQuery, min: u64, max: u64, limit: usize) -> AppResult<(Vec<u64>, usize, Option<String>)> {
+    if min > max {
+        return Ok((Vec::new(), 0, None));
+    }
+    let total = usize::try_from(max - min + 1).unwrap_or(usize::MAX);
+    let skip = match &query.position {
+        Position::Start => 0,
+        Position::Offset(offset) => *offset as u64,
+        Position::Cursor(cursor) => {
+            let after = cursor
+                .parse::<u64>()
+                .ok()
+                .filter(|height| (min..=max).contains(height))
+                .ok_or_else(|| AppError::Validation(format!("invalid cursor: {}", cursor)))?;
+            if query.descending { max - after + 1 } else { after - min + 1 }
+        }
+    };
+    let remaining = (max - min + 1).saturating_sub(skip);
+    let count = remaining.min(limit as u64);
+    let heights: Vec<u64> = (skip..skip + count)
+        .map(|i| if query.descending { max - i } else { min + i })
+        .collect();
+    let next_cursor = (count < remaining).then(|| heights.last().map(|h| h.to_string())).flatten();
+    Ok((heights, total, next_cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn query(pairs: &[(&str, &str)]) -> ListQuery {
+        let params: HashMap<String, String> = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        ListQuery::parse(&params, &SHIELDED_TREE_LIST).unwrap()
+    }
+
+    #[test]
+    fn test_page_heights_follow_cursor_in_both_directions() {
+        let (heights, total, cursor) = page_heights(&query(&[]), 100, 104, 2).unwrap();
+        assert_eq!((heights, total, cursor.as_deref()), (vec![100, 101], 5, Some("101")));
+        let (heights, _, cursor) = page_heights(&query(&[("cursor", "103")]), 100, 104, 2).unwrap();
+        assert_eq!((heights, cursor), (vec![104], None));
+
+        let (heights, _, cursor) = page_heights(&query(&[("sort", "-height")]), 100, 104, 3).unwrap();
+        assert_eq!((heights, cursor.as_deref()), (vec![104, 103, 102], Some("102")));
+        let (heights, _, _) = page_heights(&query(&[("sort", "-height"), ("cursor", "102")]), 100, 104, 3).unwrap();
+        assert_eq!(heights, vec![101, 100]);
+
+        assert!(page_heights(&query(&[("cursor", "99")]), 100, 104, 2).is_err());
+        assert_eq!(page_heights(&query(&[]), 105, 104, 2).unwrap().1, 0);
+    }
+
+    #[test]
+    fn test_cache_drops_states_above_fork() {
+        let mut cache = TreeCache::new(3);
+        for height in [10, 20, 30, 40] {
+            cache.insert(height, json!({ "height": height }));
+        }
+        assert!(!cache.entries.contains_key(&10));
+        assert_eq!(cache.remove_from(25), 2);
+        assert_eq!(cache.entries.keys().copied().collect::<Vec<_>>(), vec![20]);
+        cache.insert(50, json!({}));
+        assert_eq!(cache.order, VecDeque::from([20, 50]));
+    }
+}
@@ -0,0 +1,491 @@
+//! Transaction confirmation tracking
+//!
+//! Clients register a txid with a target confirmation count and are notified
+//! once it is reached, or when the transaction drops out of the mempool
+//! (`evicted`) or one of its inputs is spent by another transaction
+//! (`double_spent`). Notifications are published on the [`ChainEventBus`]
+//! (and so reach `/api/events` subscribers) and POSTed to the registration's
+//! webhook when one was given. Registrations are kept in memory only. Each
+//! client IP may hold `max_tracked_per_client` of them at a time.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::application::services::chain_monitor_service::{ChainEvent, ChainEventBus};
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// Where a tracked transaction stands
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackingStatus {
+    Pending,
+    Confirmed,
+    Evicted,
+    DoubleSpent,
+    Expired,
+}
+
+impl TrackingStatus {
+    fn is_final(self) -> bool {
+        self != TrackingStatus::Pending
+    }
+}
+
+/// Body of `POST /track/tx`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrackTxRequest {
+    pub txid: String,
+    /// Confirmations to wait for
+    pub confirmations: u32,
+    /// Receives the final status as a JSON POST (if `allow_client_webhooks`)
+    pub webhook_url: Option<String>,
+}
+
+/// One registration
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackedTx {
+    pub id: String,
+    pub txid: String,
+    pub target_confirmations: u32,
+    pub confirmations: u32,
+    pub status: TrackingStatus,
+    pub block_hash: Option<String>,
+    pub registered_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip)]
+    webhook_url: Option<String>,
+    /// Client IP that registered the txid
+    #[serde(skip)]
+    client: String,
+}
+
+/// Published when a registration reaches a final status
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TxStatusEvent {
+    pub id: String,
+    pub txid: String,
+    pub status: TrackingStatus,
+    pub confirmations: u32,
+    pub target_confirmations: u32,
+    pub block_hash: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// What the daemon currently knows about a transaction
+#[derive(Debug, Clone, PartialEq)]
+enum Lookup {
+    /// In the mempool (0) or mined
+    Found { confirmations: u32, block_hash: Option<String> },
+    /// Neither in the mempool nor on the active chain
+    Missing,
+}
+
+/// Per-txid observations shared by all registrations of the txid
+#[derive(Debug, Default)]
+struct Observation {
+    /// Outpoints spent by the transaction, once it has been seen
+    inputs: Option<Vec<(String, u64)>>,
+    /// When the transaction was first found missing in the current streak
+    missing_since: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    registrations: HashMap<String, TrackedTx>,
+    observations: HashMap<String, Observation>,
+}
+
+/// Confirmation tracker for client-registered txids
+pub struct TxTrackingService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    bus: Arc<ChainEventBus>,
+    state: RwLock<TrackerState>,
+    http: reqwest::Client,
+}
+
+impl TxTrackingService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>, bus: Arc<ChainEventBus>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.tx_tracking.webhook_timeout_seconds))
+            .build()
+            .unwrap_or_default();
+        Self { config, rpc, bus, state: RwLock::new(TrackerState::default()), http }
+    }
+
+    /// Register a txid for `client`; fails with `RateLimit` when the client or the server holds too many
+    pub async fn register(&self, client: &str, request: TrackTxRequest) -> AppResult<TrackedTx> {
+        let settings = &self.config.tx_tracking;
+        let txid = request.txid.trim().to_lowercase();
+        if txid.len() != 64 || !txid.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(AppError::Validation("txid must be 64 hex characters".into()));
+        }
+        if request.confirmations == 0 || request.confirmations > settings.max_target_confirmations {
+            return Err(AppError::Validation(format!(
+                "confirmations must be between 1 and {}",
+                settings.max_target_confirmations
+            )));
+        }
+        if let Some(url) = &request.webhook_url {
+            if !settings.allow_client_webhooks {
+                return Err(AppError::Validation("webhook_url is not accepted by this server".into()));
+            }
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(AppError::Validation("webhook_url must start with http:// or https://".into()));
+            }
+        }
+
+        let mut state = self.state.write().await;
+        let mine = state.registrations.values().filter(|r| r.client == client).count();
+        if mine >= settings.max_tracked_per_client || state.registrations.len() >= settings.max_tracked {
+            return Err(AppError::RateLimit);
+        }
+        let now = Utc::now();
+        let tracked = TrackedTx {
+            id: uuid::Uuid::new_v4().to_string(),
+            txid: txid.clone(),
+            target_confirmations: request.confirmations,
+            confirmations: 0,
+            status: TrackingStatus::Pending,
+            block_hash: None,
+            registered_at: now,
+            updated_at: now,
+            webhook_url: request.webhook_url,
+            client: client.to_string(),
+        };
+        state.registrations.insert(tracked.id.clone(), tracked.clone());
+        state.observations.entry(txid).or_default();
+        Ok(tracked)
+    }
+
+    /// Current state of a registration; final states stay readable until `retention_seconds` pass
+    pub async fn get(&self, id: &str) -> Option<TrackedTx> {
+        self.state.read().await.registrations.get(id).cloned()
+    }
+
+    /// Check every pending txid once and publish the ones that reached a final status
+    pub async fn poll(&self) -> AppResult<Vec<TxStatusEvent>> {
+        let mut events = self.prune().await;
+        let pending: Vec<String> = {
+            let state = self.state.read().await;
+            let txids: HashSet<&String> = state
+                .registrations
+                .values()
+                .filter(|r| r.status == TrackingStatus::Pending)
+                .map(|r| &r.txid)
+                .collect();
+            txids.into_iter().cloned().collect()
+        };
+        if !pending.is_empty() {
+            self.check(pending, &mut events).await?;
+        }
+
+        for event in &events {
+            debug!(txid = %event.txid, status = ?event.status, "Tracked transaction finished");
+            self.bus.publish(ChainEvent::TxStatus(event.clone()));
+        }
+        Ok(events)
+    }
+
+    async fn check(&self, pending: Vec<String>, events: &mut Vec<TxStatusEvent>) -> AppResult<()> {
+        // A txid that is neither here nor fetchable is only treated as missing when this succeeded
+        let mempool: HashSet<String> = self
+            .call("getrawmempool", json!([]))
+            .await?
+            .as_array()
+            .map(|txids| txids.iter().filter_map(|t| t.as_str()).map(|t| t.to_string()).collect())
+            .unwrap_or_default();
+
+        for txid in pending {
+            let (lookup, inputs) = self.lookup(&txid, mempool.contains(&txid)).await;
+            let verdict = match lookup {
+                Lookup::Found { .. } => None,
+                Lookup::Missing => self.missing_verdict(&txid).await,
+            };
+            events.extend(self.apply(&txid, &lookup, inputs, verdict).await);
+        }
+        Ok(())
+    }
+
+    /// Poll in the background and deliver webhooks for finished registrations
+    pub fn start_tracker(self: Arc<Self>) {
+        let interval = std::time::Duration::from_secs(self.config.tx_tracking.poll_interval_seconds.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.poll().await {
+                    Ok(events) if !events.is_empty() => {
+                        let tracker = self.clone();
+                        tokio::spawn(async move { tracker.deliver_webhooks(&events).await });
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("Transaction tracking poll failed: {}", e),
+                }
+            }
+        });
+    }
+
+    async fn deliver_webhooks(&self, events: &[TxStatusEvent]) {
+        for event in events {
+            let url = self.state.read().await.registrations.get(&event.id).and_then(|r| r.webhook_url.clone());
+            let Some(url) = url else { continue };
+            match self.http.post(&url).json(&ChainEvent::TxStatus(event.clone())).send().await {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => warn!(url = %url, status = %response.status(), "Tracking webhook rejected"),
+                Err(e) => warn!(url = %url, "Tracking webhook failed: {}", e),
+            }
+        }
+    }
+
+    /// Fetch the transaction; also returns its inputs the first time it is seen
+    async fn lookup(&self, txid: &str, in_mempool: bool) -> (Lookup, Option<Vec<(String, u64)>>) {
+        let known_inputs = self
+            .state
+            .read()
+            .await
+            .observations
+            .get(txid)
+            .is_some_and(|o| o.inputs.is_some());
+        if in_mempool && known_inputs {
+            return (Lookup::Found { confirmations: 0, block_hash: None }, None);
+        }
+        match self.call("getrawtransaction", json!([txid, 1])).await {
+            Ok(tx) => {
+                let lookup = Lookup::Found {
+                    confirmations: tx.get("confirmations").and_then(|c| c.as_u64()).unwrap_or(0) as u32,
+                    block_hash: tx.get("blockhash").and_then(|h| h.as_str()).map(|h| h.to_string()),
+                };
+                (lookup, Some(transaction_inputs(&tx)))
+            }
+            Err(_) if in_mempool => (Lookup::Found { confirmations: 0, block_hash: None }, None),
+            Err(_) => (Lookup::Missing, None),
+        }
+    }
+
+    /// Final status for a missing transaction, once it has been missing for the grace period
+    async fn missing_verdict(&self, txid: &str) -> Option<TrackingStatus> {
+        let grace = chrono::Duration::seconds(self.config.tx_tracking.eviction_grace_seconds as i64);
+        let inputs = {
+            let mut state = self.state.write().await;
+            let observation = state.observations.entry(txid.to_string()).or_default();
+            let since = *observation.missing_since.get_or_insert_with(Utc::now);
+            if Utc::now() - since < grace {
+                return None;
+            }
+            observation.inputs.clone().unwrap_or_default()
+        };
+        for (prev_txid, vout) in inputs {
+            // gettxout returns null for outputs spent on chain or in the mempool
+            match self.call("gettxout", json!([prev_txid, vout, true])).await {
+                Ok(Value::Null) => return Some(TrackingStatus::DoubleSpent),
+                Ok(_) => {}
+                Err(e) => {
+                    debug!(txid, "gettxout failed while classifying a missing transaction: {}", e);
+                    return None;
+                }
+            }
+        }
+        Some(TrackingStatus::Evicted)
+    }
+
+    /// Record a lookup and return events for registrations that became final
+    async fn apply(
+        &self,
+        txid: &str,
+        lookup: &Lookup,
+        inputs: Option<Vec<(String, u64)>>,
+        verdict: Option<TrackingStatus>,
+    ) -> Vec<TxStatusEvent> {
+        let now = Utc::now();
+        let mut state = self.state.write().await;
+        let observation = state.observations.entry(txid.to_string()).or_default();
+        if let Some(inputs) = inputs {
+            observation.inputs = Some(inputs);
+        }
+        if matches!(lookup, Lookup::Found { .. }) {
+            observation.missing_since = None;
+        }
+
+        let mut events = Vec::new();
+        for tracked in state.registrations.values_mut().filter(|r| r.txid == txid && !r.status.is_final()) {
+            let status = match lookup {
+                Lookup::Found { confirmations, block_hash } => {
+                    tracked.confirmations = *confirmations;
+                    tracked.block_hash = block_hash.clone();
+                    (*confirmations >= tracked.target_confirmations).then_some(TrackingStatus::Confirmed)
+                }
+                Lookup::Missing => {
+                    tracked.confirmations = 0;
+                    tracked.block_hash = None;
+                    verdict
+                }
+            };
+            tracked.updated_at = now;
+            if let Some(status) = status {
+                tracked.status = status;
+                events.push(status_event(tracked, now));
+            }
+        }
+        events
+    }
+
+    /// Expire stale registrations and forget finished ones after the retention period
+    async fn prune(&self) -> Vec<TxStatusEvent> {
+        let settings = &self.config.tx_tracking;
+        let now = Utc::now();
+        let max_age = chrono::Duration::seconds(settings.max_tracking_seconds as i64);
+        let retention = chrono::Duration::seconds(settings.retention_seconds as i64);
+        let mut expired = Vec::new();
+        let mut state = self.state.write().await;
+        for tracked in state.registrations.values_mut() {
+            if tracked.status == TrackingStatus::Pending && now - tracked.registered_at > max_age {
+                tracked.status = TrackingStatus::Expired;
+                tracked.updated_at = now;
+                expired.push(status_event(tracked, now));
+            }
+        }
+        state
+            .registrations
+            .retain(|_, r| !r.status.is_final() || now - r.updated_at <= retention);
+        let tracked: HashSet<String> = state.registrations.values().map(|r| r.txid.clone()).collect();
+        state.observations.retain(|txid, _| tracked.contains(txid));
+        expired
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("tx_tracking_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("tx-tracker".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+fn status_event(tracked: &TrackedTx, at: DateTime<Utc>) -> TxStatusEvent {
+    TxStatusEvent {
+        id: tracked.id.clone(),
+        txid: tracked.txid.clone(),
+        status: tracked.status,
+        confirmations: tracked.confirmations,
+        target_confirmations: tracked.target_confirmations,
+        block_hash: tracked.block_hash.clone(),
+        at,
+    }
+}
+
+/// Outpoints spent by a decoded transaction (none for coinbase)
+fn transaction_inputs(tx: &Value) -> Vec<(String, u64)> {
+    tx.get("vin")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|input| Some((input.get("txid")?.as_str()?.to_string(), input.get("vout")?.as_u64()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXID: &str = "aa00000000000000000000000000000000000000000000000000000000000001";
+    const CLIENT: &str = "10.0.0.1";
+
+    fn service() -> TxTrackingService {
+        let mut config = AppConfig::default();
+        config.tx_tracking.max_tracked = 2;
+        let config = Arc::new(config);
+        TxTrackingService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)), Arc::new(ChainEventBus::new(8)))
+    }
+
+    fn request(confirmations: u32) -> TrackTxRequest {
+        TrackTxRequest { txid: TXID.to_uppercase(), confirmations, webhook_url: None }
+    }
+
+    #[tokio::test]
+    async fn test_register_validates_input() {
+        let service = service();
+        assert!(service.register(CLIENT, TrackTxRequest { txid: "abc".into(), ..request(1) }).await.is_err());
+        assert!(service.register(CLIENT, request(0)).await.is_err());
+        let webhook = TrackTxRequest { webhook_url: Some("https://example.com/hook".into()), ..request(1) };
+        assert!(service.register(CLIENT, webhook).await.is_err());
+
+        let tracked = service.register(CLIENT, request(3)).await.unwrap();
+        assert_eq!(tracked.txid, TXID);
+        assert_eq!(service.get(&tracked.id).await.unwrap().status, TrackingStatus::Pending);
+        service.register(CLIENT, request(1)).await.unwrap();
+        assert!(matches!(service.register(CLIENT, request(1)).await, Err(AppError::RateLimit)));
+    }
+
+    #[tokio::test]
+    async fn test_register_limits_each_client() {
+        let mut config = AppConfig::default();
+        config.tx_tracking.max_tracked_per_client = 1;
+        let config = Arc::new(config);
+        let service =
+            TxTrackingService::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)), Arc::new(ChainEventBus::new(8)));
+
+        service.register(CLIENT, request(1)).await.unwrap();
+        assert!(matches!(service.register(CLIENT, request(2)).await, Err(AppError::RateLimit)));
+        service.register("10.0.0.2", request(2)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_apply_confirms_each_registration_at_its_target() {
+        let service = service();
+        let one = service.register(CLIENT, request(1)).await.unwrap();
+        let three = service.register(CLIENT, request(3)).await.unwrap();
+
+        let mined = |confirmations| Lookup::Found { confirmations, block_hash: Some("b".into()) };
+        let events = service.apply(TXID, &mined(1), Some(vec![("prev".into(), 0)]), None).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, one.id);
+        assert_eq!(events[0].status, TrackingStatus::Confirmed);
+
+        assert!(service.apply(TXID, &mined(2), None, None).await.is_empty());
+        let events = service.apply(TXID, &mined(3), None, None).await;
+        assert_eq!(events[0].id, three.id);
+        // Finished registrations are not reported twice
+        assert!(service.apply(TXID, &mined(4), None, None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_transaction_waits_for_a_verdict() {
+        let service = service();
+        let tracked = service.register(CLIENT, request(1)).await.unwrap();
+        assert!(service.apply(TXID, &Lookup::Missing, None, None).await.is_empty());
+        assert_eq!(service.get(&tracked.id).await.unwrap().status, TrackingStatus::Pending);
+
+        let events = service.apply(TXID, &Lookup::Missing, None, Some(TrackingStatus::DoubleSpent)).await;
+        assert_eq!(events[0].status, TrackingStatus::DoubleSpent);
+        let value = serde_json::to_value(ChainEvent::TxStatus(events[0].clone())).unwrap();
+        assert_eq!(value["type"], "tx_status");
+        assert_eq!(value["status"], "double_spent");
+    }
+
+    #[test]
+    fn test_transaction_inputs_skip_coinbase() {
+        let tx = json!({ "vin": [{ "coinbase": "03" }, { "txid": "prev", "vout": 2 }] });
+        assert_eq!(transaction_inputs(&tx), vec![("prev".to_string(), 2)]);
+    }
+}
@@ -0,0 +1,294 @@
+//! Viewing key management for watch-only payment verification
+//!
+//! With `payments.require_viewing_key`, the server holds no spending keys:
+//! the configured viewing keys are imported into the daemon wallet at
+//! startup, quotes hand out the addresses behind them, and payments are
+//! verified with `z_viewtransaction`. This service does the import, records
+//! for each key the address it covers and whether the wallet holds it
+//! watch-only (`z_validateaddress` reports `ismine: false`), and keeps those
+//! records in a process-wide registry for `GET /admin/viewing-keys`.
+//!
+//! Keys are identified by a fingerprint (the first 8 bytes of SHA-256 over
+//! the key, in hex). Neither the viewing keys nor anything derived from
+//! spending keys is ever stored or returned.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::config::AppConfig;
+use crate::domain::payments::ShieldedAddressType;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+/// Import outcome for one viewing key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewingKeyStatus {
+    /// Imported by this process
+    Imported,
+    /// The daemon already had the key (or its spending key)
+    AlreadyPresent,
+    Failed,
+}
+
+/// What the proxy knows about one configured viewing key
+#[derive(Debug, Clone, Serialize)]
+pub struct ViewingKeyRecord {
+    pub fingerprint: String,
+    pub status: ViewingKeyStatus,
+    pub address: Option<String>,
+    pub address_type: Option<String>,
+    /// The wallet can see but not spend from the address
+    pub watch_only: Option<bool>,
+    pub imported_at: DateTime<Utc>,
+    pub error: Option<String>,
+    /// Payments to the address finalized since startup
+    pub payments_finalized: u64,
+    pub last_payment_at: Option<DateTime<Utc>>,
+}
+
+/// Process-wide viewing key records
+#[derive(Debug, Default)]
+pub struct ViewingKeyRegistry {
+    records: Mutex<HashMap<String, ViewingKeyRecord>>,
+}
+
+impl ViewingKeyRegistry {
+    /// Registry shared by the import, the payments service and the admin API
+    pub fn global() -> &'static ViewingKeyRegistry {
+        static REGISTRY: OnceLock<ViewingKeyRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(ViewingKeyRegistry::default)
+    }
+
+    /// Records ordered by fingerprint
+    pub fn snapshot(&self) -> Vec<ViewingKeyRecord> {
+        let mut records: Vec<ViewingKeyRecord> = self
+            .records
+            .lock()
+            .map(|r| r.values().cloned().collect())
+            .unwrap_or_default();
+        records.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+        records
+    }
+
+    /// Watch-only addresses of a type, ordered by fingerprint
+    pub fn watch_only_addresses(&self, address_type: &ShieldedAddressType) -> Vec<String> {
+        self.snapshot()
+            .into_iter()
+            .filter(|r| r.watch_only == Some(true))
+            .filter(|r| r.address_type.as_deref().is_some_and(|t| t.eq_ignore_ascii_case(address_type.as_str())))
+            .filter_map(|r| r.address)
+            .collect()
+    }
+
+    /// Count a finalized payment to a tracked address
+    pub fn record_payment(&self, address: &str) {
+        if let Ok(mut records) = self.records.lock() {
+            if let Some(record) = records.values_mut().find(|r| r.address.as_deref() == Some(address)) {
+                record.payments_finalized += 1;
+                record.last_payment_at = Some(Utc::now());
+            }
+        }
+    }
+
+    fn is_imported(&self, fingerprint: &str) -> bool {
+        self.records
+            .lock()
+            .map(|r| r.get(fingerprint).is_some_and(|record| record.status != ViewingKeyStatus::Failed))
+            .unwrap_or(false)
+    }
+
+    fn insert(&self, mut record: ViewingKeyRecord) {
+        if let Ok(mut records) = self.records.lock() {
+            // Keep verification counters across re-imports
+            if let Some(previous) = records.get(&record.fingerprint) {
+                record.payments_finalized = previous.payments_finalized;
+                record.last_payment_at = previous.last_payment_at;
+            }
+            records.insert(record.fingerprint.clone(), record);
+        }
+    }
+}
+
+/// Fingerprint identifying a viewing key without revealing it
+pub fn fingerprint(viewing_key: &str) -> String {
+    hex::encode(&Sha256::digest(viewing_key.trim().as_bytes())[..8])
+}
+
+pub struct ViewingKeyService {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+}
+
+impl ViewingKeyService {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        Self { config, rpc }
+    }
+
+    /// Import every configured viewing key not yet imported; returns how many are usable
+    ///
+    /// Safe to run repeatedly: keys already recorded are skipped, and keys the
+    /// daemon already holds are recorded as `already_present`. Failures are
+    /// logged and recorded, never fatal.
+    pub async fn import_all(&self) -> usize {
+        let registry = ViewingKeyRegistry::global();
+        for viewing_key in &self.config.payments.viewing_keys {
+            let fingerprint = fingerprint(viewing_key);
+            if registry.is_imported(&fingerprint) {
+                continue;
+            }
+            let record = self.import(viewing_key, fingerprint).await;
+            match record.status {
+                ViewingKeyStatus::Failed => warn!(
+                    fingerprint = %record.fingerprint,
+                    "Viewing key import failed: {}",
+                    record.error.as_deref().unwrap_or("unknown error")
+                ),
+                _ => info!(
+                    fingerprint = %record.fingerprint,
+                    status = ?record.status,
+                    address_type = record.address_type.as_deref().unwrap_or("unknown"),
+                    watch_only = ?record.watch_only,
+                    "Viewing key ready"
+                ),
+            }
+            if record.watch_only == Some(false) {
+                warn!(fingerprint = %record.fingerprint, "Wallet holds the spending key for a viewing-key address");
+            }
+            registry.insert(record);
+        }
+        registry.snapshot().iter().filter(|r| r.status != ViewingKeyStatus::Failed).count()
+    }
+
+    async fn import(&self, viewing_key: &str, fingerprint: String) -> ViewingKeyRecord {
+        let mut record = ViewingKeyRecord {
+            fingerprint,
+            status: ViewingKeyStatus::Imported,
+            address: None,
+            address_type: None,
+            watch_only: None,
+            imported_at: Utc::now(),
+            error: None,
+            payments_finalized: 0,
+            last_payment_at: None,
+        };
+        let rescan = self.config.payments.viewing_key_rescan.clone();
+        let result = match self.call("z_importviewingkey", json!([viewing_key.trim(), rescan])).await {
+            Ok(result) => result,
+            Err(e) if is_already_present(&e) => {
+                record.status = ViewingKeyStatus::AlreadyPresent;
+                Value::Null
+            }
+            Err(e) => {
+                record.status = ViewingKeyStatus::Failed;
+                record.error = Some(e.to_string());
+                return record;
+            }
+        };
+
+        // Recent daemons answer with the address the key covers
+        record.address = result.get("address").and_then(|a| a.as_str()).map(|a| a.to_string());
+        record.address_type = result
+            .get("address_type")
+            .or_else(|| result.get("type"))
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string());
+        if let Some(address) = record.address.clone() {
+            match self.call("z_validateaddress", json!([address])).await {
+                Ok(validated) => {
+                    record.watch_only = validated.get("ismine").and_then(|m| m.as_bool()).map(|mine| !mine);
+                    if record.address_type.is_none() {
+                        record.address_type = validated.get("type").and_then(|t| t.as_str()).map(|t| t.to_string());
+                    }
+                }
+                Err(e) => record.error = Some(format!("z_validateaddress: {}", e)),
+            }
+        }
+        record
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("viewing_keys_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("viewing-keys".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+/// The daemon refuses keys it already has ("already contains", "already have")
+fn is_already_present(error: &AppError) -> bool {
+    error.to_string().to_lowercase().contains("already")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(fingerprint: &str, address: &str, address_type: &str, watch_only: bool) -> ViewingKeyRecord {
+        ViewingKeyRecord {
+            fingerprint: fingerprint.to_string(),
+            status: ViewingKeyStatus::Imported,
+            address: Some(address.to_string()),
+            address_type: Some(address_type.to_string()),
+            watch_only: Some(watch_only),
+            imported_at: Utc::now(),
+            error: None,
+            payments_finalized: 0,
+            last_payment_at: None,
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_hides_key() {
+        let fp = fingerprint("zxviews1secretkey");
+        assert_eq!(fp.len(), 16);
+        assert_eq!(fp, fingerprint(" zxviews1secretkey\n"));
+        assert!(!fp.contains("secret"));
+    }
+
+    #[test]
+    fn test_watch_only_addresses_by_type() {
+        let registry = ViewingKeyRegistry::default();
+        registry.insert(record("b", "zs1b", "sapling", true));
+        registry.insert(record("a", "u1a", "orchard", true));
+        registry.insert(record("c", "zs1c", "sapling", false));
+
+        assert_eq!(registry.watch_only_addresses(&ShieldedAddressType::Sapling), vec!["zs1b".to_string()]);
+        assert_eq!(registry.watch_only_addresses(&ShieldedAddressType::Orchard), vec!["u1a".to_string()]);
+        assert!(registry.is_imported("a"));
+        assert!(!registry.is_imported("d"));
+    }
+
+    #[test]
+    fn test_payment_counts_survive_reimport() {
+        let registry = ViewingKeyRegistry::default();
+        registry.insert(record("a", "zs1a", "sapling", true));
+        registry.record_payment("zs1a");
+        registry.record_payment("zs1unknown");
+        registry.insert(record("a", "zs1a", "sapling", true));
+
+        let snapshot = registry.snapshot();
+        assert_eq!(snapshot[0].payments_finalized, 1);
+        assert!(snapshot[0].last_payment_at.is_some());
+        assert!(serde_json::to_string(&snapshot).unwrap().contains("\"status\":\"imported\""));
+    }
+}
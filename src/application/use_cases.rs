@@ -2,7 +2,7 @@
 
 use crate::{
     application::services::*,
-    domain::rpc::*,
+    domain::{rpc::*, validation::ValidationTrace},
     shared::error::AppResult,
 };
 use serde_json::Value;
@@ -43,6 +43,11 @@ impl ProcessRpcRequestUseCase {
         result
     }
 
+    /// Trace the validation rules evaluated for a request (explain mode)
+    pub async fn explain_validation(&self, request: &RpcRequest) -> AppResult<ValidationTrace> {
+        self.rpc_service.explain_validation(request).await
+    }
+
     /// Get method information
     pub fn get_method_info(&self, _method_name: &str) -> Option<RpcMethod> {
         // This method is no longer available in the RPC service
@@ -70,9 +75,27 @@ impl GetMetricsUseCase {
 }
 
 /// Use case for health checks
-pub struct HealthCheckUseCase;
+#[derive(Default)]
+pub struct HealthCheckUseCase {
+    /// Server's memory guard; its pressure degrades the status
+    memory_guard: Option<Arc<crate::middleware::memory_guard::MemoryGuard>>,
+    /// Server's upstream gate; reports whether maintenance mode is on
+    upstream_gate: Option<Arc<crate::infrastructure::adapters::UpstreamGate>>,
+}
 
 impl HealthCheckUseCase {
+    /// Report the server's memory guard
+    pub fn with_memory_guard(mut self, memory_guard: Arc<crate::middleware::memory_guard::MemoryGuard>) -> Self {
+        self.memory_guard = Some(memory_guard);
+        self
+    }
+
+    /// Report the server's read-only switch
+    pub fn with_upstream_gate(mut self, upstream_gate: Arc<crate::infrastructure::adapters::UpstreamGate>) -> Self {
+        self.upstream_gate = Some(upstream_gate);
+        self
+    }
+
     /// Execute the use case with enhanced daemon status
     pub async fn execute(&self, rpc_adapter: Option<Arc<crate::infrastructure::adapters::ExternalRpcAdapter>>) -> AppResult<crate::domain::health::HealthResponse> {
         use crate::domain::health::*;
@@ -93,7 +116,8 @@ impl HealthCheckUseCase {
             details["daemon"] = json!({
                 "available": daemon_available,
                 "circuit_breaker": format!("{:?}", circuit_status),
-                "status": if daemon_available { "connected" } else { "disconnected" }
+                "status": if daemon_available { "connected" } else { "disconnected" },
+                "initializing": crate::infrastructure::adapters::DaemonWait::global().is_initializing(),
             });
 
             if !daemon_available {
@@ -113,6 +137,43 @@ impl HealthCheckUseCase {
             status = HealthStatus::Degraded;
         }
 
+        // Redis outages degrade the service: responses come from the memory cache until it returns
+        if let Some(redis) = crate::infrastructure::adapters::RedisLink::installed() {
+            if redis.degraded {
+                status = HealthStatus::Degraded;
+                let warning = json!("Redis cache is unavailable; serving from the memory cache");
+                match details["warnings"].as_array_mut() {
+                    Some(warnings) => warnings.push(warning),
+                    None => details["warnings"] = json!([warning]),
+                }
+            }
+            details["cache"] = json!({ "redis": redis });
+        }
+
+        // Memory pressure degrades the service: caches are shrunk and requests refused
+        if let Some(guard) = self.memory_guard.as_ref().filter(|guard| guard.enabled()) {
+            let pressure = guard.pressure();
+            if pressure != crate::middleware::memory_guard::MemoryPressure::Normal {
+                status = HealthStatus::Degraded;
+                let warning = json!("Memory use is past its soft limit; caches are shrunk and requests may be refused");
+                match details["warnings"].as_array_mut() {
+                    Some(warnings) => warnings.push(warning),
+                    None => details["warnings"] = json!([warning]),
+                }
+            }
+            details["memory"] = json!(guard.metrics());
+        }
+
+        // Maintenance mode does not change the status: reads are still served
+        let read_only = match &self.upstream_gate {
+            Some(gate) => gate.is_read_only().await,
+            None => false,
+        };
+        details["maintenance"] = json!({ "read_only": read_only });
+
+        // Detected once at startup; `null` when detection is off or failed
+        details["capabilities"] = json!(crate::application::services::DaemonCapabilities::current());
+
         // Add system metrics
         details["system"] = json!({
             "memory_usage": self.get_memory_usage(),
@@ -126,6 +187,39 @@ impl HealthCheckUseCase {
         })
     }
 
+    /// Health report with each dependency probed for latency, plus its last error
+    ///
+    /// Serves `GET /health?verbose=true`. The daemon is probed with
+    /// `getblockcount` and Redis with `PING` when `cache` is given. Last errors
+    /// are kept across calls, so they stay visible after a recovery.
+    pub async fn execute_verbose(
+        &self,
+        rpc_adapter: Option<Arc<crate::infrastructure::adapters::ExternalRpcAdapter>>,
+        cache: Option<Arc<crate::middleware::cache::CacheMiddleware>>,
+    ) -> AppResult<crate::domain::health::HealthResponse> {
+        let mut response = self.execute(rpc_adapter.clone()).await?;
+        let mut dependencies = serde_json::Map::new();
+
+        if let Some(adapter) = rpc_adapter {
+            let started = std::time::Instant::now();
+            let error = match tokio::time::timeout(DEPENDENCY_PROBE_TIMEOUT, adapter.send_request(&health_probe_request())).await {
+                Ok(Ok(_)) => None,
+                Ok(Err(e)) => Some(e.to_string()),
+                Err(_) => Some("probe timed out".to_string()),
+            };
+            dependencies.insert("daemon".to_string(), dependency_report("daemon", started.elapsed(), error));
+        }
+        if let Some(cache) = cache {
+            let started = std::time::Instant::now();
+            let up = tokio::time::timeout(DEPENDENCY_PROBE_TIMEOUT, cache.ping()).await.unwrap_or(false);
+            let error = (!up).then(|| "redis did not answer PING".to_string());
+            dependencies.insert("redis".to_string(), dependency_report("redis", started.elapsed(), error));
+        }
+
+        response.details["dependencies"] = Value::Object(dependencies);
+        Ok(response)
+    }
+
     /// Get system uptime
     fn get_uptime(&self) -> String {
         if let Ok(uptime) = std::time::SystemTime::now()
@@ -158,6 +252,48 @@ impl HealthCheckUseCase {
     }
 }
 
+/// Longest a verbose health check waits for one dependency
+const DEPENDENCY_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Last error seen per dependency, with when it happened
+type DependencyErrors = std::collections::HashMap<&'static str, (String, chrono::DateTime<chrono::Utc>)>;
+
+fn last_dependency_errors() -> &'static std::sync::Mutex<DependencyErrors> {
+    static ERRORS: std::sync::OnceLock<std::sync::Mutex<DependencyErrors>> = std::sync::OnceLock::new();
+    ERRORS.get_or_init(Default::default)
+}
+
+/// One dependency's entry in a verbose health report; records `error` as its last error
+fn dependency_report(name: &'static str, latency: std::time::Duration, error: Option<String>) -> Value {
+    let mut errors = last_dependency_errors().lock().unwrap_or_else(|e| e.into_inner());
+    let up = error.is_none();
+    if let Some(error) = error {
+        errors.insert(name, (error, chrono::Utc::now()));
+    }
+    let last_error = errors.get(name);
+    serde_json::json!({
+        "status": if up { "up" } else { "down" },
+        "latency_ms": latency.as_secs_f64() * 1000.0,
+        "last_error": last_error.map(|(error, _)| error.clone()),
+        "last_error_at": last_error.map(|(_, at)| at.to_rfc3339()),
+    })
+}
+
+fn health_probe_request() -> RpcRequest {
+    RpcRequest::new(
+        "getblockcount".to_string(),
+        Some(serde_json::json!([])),
+        Some(serde_json::json!("health_verbose_getblockcount")),
+        ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: Some("health-check".to_string()),
+            auth_token: None,
+            timestamp: chrono::Utc::now(),
+            request_id: None,
+        },
+    )
+}
+
 /// Use case for validating RPC methods
 pub struct ValidateRpcMethodUseCase {
     rpc_service: Arc<RpcService>,
@@ -216,7 +352,9 @@ mod tests {
                 user_agent: Some("test-agent".to_string()),
                 auth_token: None,
                 timestamp: Utc::now(),
+                request_id: None,
             },
+            upstream: None,
         }
     }
 
@@ -230,7 +368,9 @@ mod tests {
                 user_agent: Some("test-agent".to_string()),
                 auth_token: Some(auth_token.to_string()),
                 timestamp: Utc::now(),
+                request_id: None,
             },
+            upstream: None,
         }
     }
 
@@ -331,7 +471,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_health_check_use_case_without_adapter() {
-        let use_case = HealthCheckUseCase;
+        let use_case = HealthCheckUseCase::default();
         
         let result = use_case.execute(None).await;
         
@@ -356,7 +496,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_health_check_use_case_with_adapter() {
-        let use_case = HealthCheckUseCase;
+        let use_case = HealthCheckUseCase::default();
         let config = Arc::new(create_test_config());
         let adapter = Arc::new(ExternalRpcAdapter::new(config));
         
@@ -390,17 +530,17 @@ mod tests {
         // Test valid method (default policy allows all methods)
         let result = use_case.execute("getinfo");
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), true);
+        assert!(result.unwrap());
         
         // Test invalid method (default policy allows all methods)
         let result = use_case.execute("invalid_method");
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), true); // Default policy allows all methods
+        assert!(result.unwrap()); // Default policy allows all methods
     }
 
     #[tokio::test]
     async fn test_health_check_use_case_system_metrics() {
-        let use_case = HealthCheckUseCase;
+        let use_case = HealthCheckUseCase::default();
         
         let result = use_case.execute(None).await;
         assert!(result.is_ok());
@@ -422,7 +562,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_health_check_use_case_version_info() {
-        let use_case = HealthCheckUseCase;
+        let use_case = HealthCheckUseCase::default();
         
         let result = use_case.execute(None).await;
         assert!(result.is_ok());
@@ -440,4 +580,18 @@ mod tests {
         let timestamp = details["timestamp"].as_str().unwrap();
         assert!(!timestamp.is_empty());
     }
+
+    #[test]
+    fn test_dependency_report_keeps_last_error() {
+        let latency = std::time::Duration::from_millis(12);
+        let down = dependency_report("test-dependency", latency, Some("connection refused".to_string()));
+        assert_eq!(down["status"], "down");
+        assert_eq!(down["latency_ms"], 12.0);
+
+        // The error stays visible after the dependency recovers
+        let up = dependency_report("test-dependency", latency, None);
+        assert_eq!(up["status"], "up");
+        assert_eq!(up["last_error"], "connection refused");
+        assert!(up["last_error_at"].is_string());
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,257 @@
+//! Load driver: representative method mixes, RPS and latency percentiles
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+const BLOCK_HASH: &str = "00000000000d8e1f4ba1b2ca4ad4e1b9f3f2e35fb4c1e8b3df0f4e10c84e1a53";
+const TXID: &str = "6f2b1e9c4d7a3f80e5b2c1d09a8f7e6d5c4b3a29180f7e6d5c4b3a2918070605";
+const ADDRESS: &str = "RVBW4u5A4Vy2zDwngJoMZgSDaXdxLEZx4J";
+
+/// One JSON-RPC call in a mix, sent `weight` times per round
+#[derive(Debug, Clone)]
+pub struct MixCall {
+    pub method: &'static str,
+    pub params: Value,
+    pub weight: u32,
+}
+
+/// Named set of calls approximating one kind of client
+#[derive(Debug, Clone)]
+pub struct MethodMix {
+    pub name: &'static str,
+    pub calls: Vec<MixCall>,
+}
+
+impl MethodMix {
+    /// Calls in sending order, each repeated by weight
+    fn schedule(&self) -> Vec<&MixCall> {
+        self.calls
+            .iter()
+            .flat_map(|call| std::iter::repeat_n(call, call.weight as usize))
+            .collect()
+    }
+}
+
+fn call(method: &'static str, params: Value, weight: u32) -> MixCall {
+    MixCall { method, params, weight }
+}
+
+/// Built-in mixes: `explorer`, `wallet` and `mixed`
+pub fn mixes() -> Vec<MethodMix> {
+    vec![
+        MethodMix {
+            name: "explorer",
+            calls: vec![
+                call("getblockcount", json!([]), 4),
+                call("getblockhash", json!([2_999_990]), 2),
+                call("getblock", json!([BLOCK_HASH, true]), 2),
+                call("getrawtransaction", json!([TXID, 1]), 2),
+            ],
+        },
+        MethodMix {
+            name: "wallet",
+            calls: vec![
+                call("getaddressbalance", json!([{"addresses": [ADDRESS]}]), 4),
+                call("getaddressutxos", json!([{"addresses": [ADDRESS]}]), 2),
+                call("getrawtransaction", json!([TXID, 1]), 2),
+                call("getinfo", json!([]), 1),
+            ],
+        },
+        MethodMix {
+            name: "mixed",
+            calls: vec![
+                call("getinfo", json!([]), 3),
+                call("getblockcount", json!([]), 3),
+                call("getblock", json!([BLOCK_HASH, true]), 1),
+                call("getrawtransaction", json!([TXID, 1]), 1),
+                call("getaddressbalance", json!([{"addresses": [ADDRESS]}]), 1),
+                call("getcurrency", json!(["VRSC"]), 1),
+            ],
+        },
+    ]
+}
+
+/// Built-in mix by name
+pub fn mix(name: &str) -> Option<MethodMix> {
+    mixes().into_iter().find(|mix| mix.name == name)
+}
+
+/// How hard and how long to drive the target
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Proxy URL, e.g. `http://127.0.0.1:8080`
+    pub target: String,
+    /// Concurrent in-flight requests
+    pub concurrency: usize,
+    /// Measured run length
+    pub duration: Duration,
+    /// Unmeasured run before, to fill caches and connection pools
+    pub warmup: Duration,
+}
+
+/// Outcome of one run
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadReport {
+    pub mix: String,
+    pub concurrency: usize,
+    pub requests: u64,
+    pub errors: u64,
+    pub elapsed_seconds: f64,
+    pub requests_per_second: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<10} c={:<4} {:>9.1} req/s  p50 {:>7.2} ms  p90 {:>7.2} ms  p99 {:>7.2} ms  max {:>7.2} ms  ({} requests, {} errors)",
+            self.mix,
+            self.concurrency,
+            self.requests_per_second,
+            self.p50_ms,
+            self.p90_ms,
+            self.p99_ms,
+            self.max_ms,
+            self.requests,
+            self.errors
+        )
+    }
+}
+
+/// HTTP client tuned for driving one target
+pub fn client(concurrency: usize) -> reqwest::Client {
+    reqwest::Client::builder()
+        .pool_max_idle_per_host(concurrency.max(1))
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Send one call through the proxy; `Err` for transport errors, non-2xx statuses and JSON-RPC errors
+pub async fn send(client: &reqwest::Client, target: &str, call: &MixCall, id: u64) -> Result<(), String> {
+    let response = client
+        .post(target)
+        // The proxy expects to sit behind a reverse proxy that sets this
+        .header("x-forwarded-for", "127.0.0.1")
+        .json(&json!({"jsonrpc": "2.0", "method": call.method, "params": call.params, "id": id}))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    let body: Value = response.json().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!("{}: HTTP {} {}", call.method, status, body));
+    }
+    match body.get("error") {
+        Some(error) if !error.is_null() => Err(format!("{}: {}", call.method, error)),
+        _ => Ok(()),
+    }
+}
+
+/// Drive `mix` against `options.target` and report throughput and latency
+pub async fn run_load(options: &LoadOptions, mix: &MethodMix) -> LoadReport {
+    let client = client(options.concurrency);
+    if !options.warmup.is_zero() {
+        drive(&client, options, mix, options.warmup).await;
+    }
+    let started = Instant::now();
+    let (mut latencies, errors) = drive(&client, options, mix, options.duration).await;
+    let elapsed = started.elapsed().as_secs_f64();
+    latencies.sort_unstable();
+
+    let requests = latencies.len() as u64 + errors;
+    LoadReport {
+        mix: mix.name.to_string(),
+        concurrency: options.concurrency,
+        requests,
+        errors,
+        elapsed_seconds: elapsed,
+        requests_per_second: if elapsed > 0.0 { requests as f64 / elapsed } else { 0.0 },
+        p50_ms: percentile_ms(&latencies, 0.50),
+        p90_ms: percentile_ms(&latencies, 0.90),
+        p99_ms: percentile_ms(&latencies, 0.99),
+        max_ms: latencies.last().map_or(0.0, |max| max.as_secs_f64() * 1000.0),
+    }
+}
+
+/// Latencies of successful calls and the error count, from `concurrency` worker tasks
+async fn drive(client: &reqwest::Client, options: &LoadOptions, mix: &MethodMix, duration: Duration) -> (Vec<Duration>, u64) {
+    let schedule: Arc<Vec<MixCall>> = Arc::new(mix.schedule().into_iter().cloned().collect());
+    if schedule.is_empty() {
+        return (Vec::new(), 0);
+    }
+    let deadline = Instant::now() + duration;
+    let workers = (0..options.concurrency.max(1)).map(|worker| {
+        let client = client.clone();
+        let target = options.target.clone();
+        let schedule = schedule.clone();
+        tokio::spawn(async move {
+            let mut latencies = Vec::new();
+            let mut errors = 0u64;
+            // Offset workers so the mix is spread evenly at any moment
+            let mut next = worker;
+            while Instant::now() < deadline {
+                let call = &schedule[next % schedule.len()];
+                let sent = Instant::now();
+                match send(&client, &target, call, next as u64).await {
+                    Ok(()) => latencies.push(sent.elapsed()),
+                    Err(e) => {
+                        errors += 1;
+                        if errors == 1 {
+                            tracing::warn!(worker, "Benchmark request failed: {}", e);
+                        }
+                    }
+                }
+                next += 1;
+            }
+            (latencies, errors)
+        })
+    });
+    futures::future::join_all(workers)
+        .await
+        .into_iter()
+        .flatten()
+        .fold((Vec::new(), 0), |(mut all, total), (latencies, errors)| {
+            all.extend(latencies);
+            (all, total + errors)
+        })
+}
+
+/// Nearest-rank percentile of sorted `latencies`, in milliseconds
+fn percentile_ms(latencies: &[Duration], quantile: f64) -> f64 {
+    if latencies.is_empty() {
+        return 0.0;
+    }
+    let rank = ((quantile * latencies.len() as f64).ceil() as usize).clamp(1, latencies.len());
+    latencies[rank - 1].as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let latencies: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile_ms(&latencies, 0.50), 50.0);
+        assert_eq!(percentile_ms(&latencies, 0.99), 99.0);
+        assert_eq!(percentile_ms(&latencies[..1], 0.99), 1.0);
+        assert_eq!(percentile_ms(&[], 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_schedule_follows_weights() {
+        let mix = mix("explorer").unwrap();
+        let schedule = mix.schedule();
+        assert_eq!(schedule.len(), 10);
+        assert_eq!(schedule.iter().filter(|call| call.method == "getblockcount").count(), 4);
+    }
+}
@@ -0,0 +1,121 @@
+//! Benchmark harness
+//!
+//! Runs the real server (listener, warp routes, validation, middleware and the
+//! daemon adapter) on a loopback port against [`MockDaemon`], and drives it
+//! over HTTP with the method mixes in [`load`]. Used by the `verus-rpc-bench`
+//! binary and the criterion suite in `benches/`; built with the `bench`
+//! feature.
+
+pub mod load;
+
+pub use load::{mix, mixes, run_load, LoadOptions, LoadReport, MethodMix, MixCall};
+pub use crate::infrastructure::adapters::MockDaemon;
+
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinHandle;
+
+use crate::config::AppConfig;
+use crate::infrastructure::http::server::HttpServer;
+use crate::shared::error::{AppError, AppResult};
+
+/// How the in-process server is set up
+#[derive(Debug, Clone, Default)]
+pub struct BenchServerOptions {
+    /// Latency added by the mock daemon to every call
+    pub daemon_latency: Duration,
+    /// Enable the response cache against this Redis; the cache stays off without one
+    pub redis_url: Option<String>,
+    /// Keep per-IP rate limiting on (every benchmark request comes from one address)
+    pub rate_limit: bool,
+}
+
+/// Server under test plus the mock daemon behind it
+pub struct BenchServer {
+    url: String,
+    daemon: MockDaemon,
+    task: JoinHandle<AppResult<()>>,
+}
+
+impl BenchServer {
+    /// Start the mock daemon and the server, and wait until the server accepts connections
+    pub async fn start(options: BenchServerOptions) -> AppResult<Self> {
+        let daemon = MockDaemon::start(options.daemon_latency)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to start mock daemon: {}", e)))?;
+        let config = bench_config(&options, &daemon.url())?;
+        let addr = config.server_address();
+        let server = HttpServer::new(config).await?;
+        let task = tokio::spawn(server.run());
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while tokio::net::TcpStream::connect(&addr).await.is_err() {
+            if task.is_finished() || Instant::now() > deadline {
+                return Err(AppError::Internal(format!("Benchmark server did not start on {}", addr)));
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        Ok(Self { url: format!("http://{}/", addr), daemon, task })
+    }
+
+    /// JSON-RPC endpoint of the server
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Calls that reached the mock daemon so far
+    pub fn daemon_calls(&self) -> u64 {
+        self.daemon.calls()
+    }
+}
+
+impl Drop for BenchServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Development-mode configuration pointing at the mock daemon, on a free loopback port
+fn bench_config(options: &BenchServerOptions, daemon_url: &str) -> AppResult<AppConfig> {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map_err(|e| AppError::Internal(format!("No free port for the benchmark server: {}", e)))?
+        .port();
+
+    let mut config = AppConfig::default();
+    config.server.bind_address = [127, 0, 0, 1].into();
+    config.server.port = port;
+    config.security.development_mode = true;
+    config.verus.rpc_url = daemon_url.to_string();
+    config.rate_limit.enabled = options.rate_limit;
+    match &options.redis_url {
+        Some(redis_url) => {
+            config.cache.enabled = true;
+            config.cache.redis_url = redis_url.clone();
+        }
+        None => config.cache.enabled = false,
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mixes_pass_through_the_real_server() {
+        let server = BenchServer::start(BenchServerOptions::default()).await.unwrap();
+        let options = LoadOptions {
+            target: server.url().to_string(),
+            concurrency: 2,
+            duration: Duration::from_millis(300),
+            warmup: Duration::ZERO,
+        };
+        for mix in mixes() {
+            let report = run_load(&options, &mix).await;
+            assert!(report.requests > 0, "{}", report);
+            assert_eq!(report.errors, 0, "{}", report);
+        }
+        assert!(server.daemon_calls() > 0);
+    }
+}
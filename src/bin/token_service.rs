@@ -6,13 +6,17 @@
 use verus_rpc_server::{
     config::AppConfig,
     infrastructure::adapters::{
-        TokenIssuerAdapter, TokenIssuanceRequest,
+        PartnerUsageRegistry, SessionStore, StratumSession, TokenIssuerAdapter, TokenIssuanceRequest,
         TokenValidationRequest
     },
+    infrastructure::http::utils::extract_and_validate_client_ip,
+    middleware::csrf::CsrfMiddleware,
     shared::error::{AppResult, AppError},
 };
 use std::sync::Arc;
-use tracing::{info, error};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use futures::{SinkExt, StreamExt};
+use tracing::{info, error, warn};
 use warp::{Filter, Reply};
 use serde::{Deserialize, Serialize};
 
@@ -51,6 +55,20 @@ impl Default for TokenServiceConfig {
     }
 }
 
+/// Stake challenge request body
+#[derive(Debug, Deserialize)]
+pub struct StakeChallengeRequest {
+    /// Address whose balance will be proven
+    pub address: String,
+}
+
+/// Logout request body
+#[derive(Debug, Deserialize)]
+pub struct LogoutRequest {
+    /// Session-bound token to invalidate
+    pub token: String,
+}
+
 /// Token service
 pub struct TokenService {
     config: TokenServiceConfig,
@@ -71,6 +89,12 @@ impl TokenService {
         }
     }
 
+    /// Enable session-bound tokens
+    pub fn with_session_store(self, store: Arc<SessionStore>) -> Self {
+        let token_issuer = Arc::new(TokenIssuerAdapter::new(self.app_config.clone()).with_session_store(store));
+        Self { token_issuer, ..self }
+    }
+
     /// Run the token service
     pub async fn run(self) -> AppResult<()> {
         let addr = format!("{}:{}", self.config.bind_address, self.config.port)
@@ -92,6 +116,7 @@ impl TokenService {
     fn create_routes(self) -> impl Filter<Extract = impl Reply> + Clone {
         let app_config = self.app_config.clone();
         let token_issuer = self.token_issuer.clone();
+        let csrf = Arc::new(CsrfMiddleware::new(app_config.csrf.clone()));
 
         // Health check endpoint
         let health_route = warp::path("health")
@@ -105,6 +130,17 @@ impl TokenService {
                 warp::reply::json(&response)
             });
 
+        // Batch issuance endpoint (pool shares validated in one pool round trip)
+        let issue_batch_route = warp::path("issue")
+            .and(warp::path("batch"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(256 * 1024))
+            .and(warp::body::json())
+            .and(with_token_issuer(token_issuer.clone()))
+            .and(with_app_config(app_config.clone()))
+            .and_then(handle_issue_token_batch);
+
         // Issue token endpoint
         let issue_token_route = warp::path("issue")
             .and(warp::post())
@@ -121,6 +157,45 @@ impl TokenService {
             .and(with_app_config(app_config.clone()))
             .and_then(handle_pow_challenge);
 
+        // Stake proof challenge endpoint
+        let stake_challenge_route = warp::path("stake")
+            .and(warp::path("challenge"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(4 * 1024))
+            .and(warp::body::json())
+            .and(with_token_issuer(token_issuer.clone()))
+            .and(with_app_config(app_config.clone()))
+            .and_then(handle_stake_challenge);
+
+        // Stratum-lite PoW stream (WebSocket)
+        let stratum_connections = Arc::new(AtomicUsize::new(0));
+        let pow_stream_route = warp::path("pow")
+            .and(warp::path("stream"))
+            .and(warp::path::end())
+            .and(warp::ws())
+            .and(warp::header::optional::<String>("x-forwarded-for"))
+            .and(with_token_issuer(token_issuer.clone()))
+            .and(with_app_config(app_config.clone()))
+            .and(warp::any().map(move || stratum_connections.clone()))
+            .and_then(handle_pow_stream);
+
+        // Session token endpoint (sliding expiration)
+        let session_route = warp::path("session")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_token_issuer(token_issuer.clone()))
+            .and(with_app_config(app_config.clone()))
+            .and_then(handle_issue_session_token);
+
+        // Logout endpoint (ends the token's session)
+        let logout_route = warp::path("logout")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_token_issuer(token_issuer.clone()))
+            .and(with_app_config(app_config.clone()))
+            .and_then(handle_logout);
+
         // Validate token endpoint
         let validate_token_route = warp::path("validate")
             .and(warp::post())
@@ -129,11 +204,19 @@ impl TokenService {
             .and(with_app_config(app_config))
             .and_then(handle_validate_token);
 
-        // Combine routes
-        health_route
+        // Combine routes (CSRF guard first; it passes everything when disabled)
+        csrf.clone()
+            .guard_filter()
+            .or(csrf.token_route())
+            .or(health_route)
+            .or(issue_batch_route)
             .or(issue_token_route)
             .or(pow_challenge_route)
+            .or(pow_stream_route)
+            .or(stake_challenge_route)
             .or(validate_token_route)
+            .or(session_route)
+            .or(logout_route)
     }
 }
 
@@ -168,6 +251,108 @@ async fn handle_issue_token(
     }
 }
 
+/// Maximum number of requests accepted by `POST /issue/batch`
+const MAX_BATCH_ISSUANCE: usize = 100;
+
+/// Handle batch token issuance request
+async fn handle_issue_token_batch(
+    requests: Vec<TokenIssuanceRequest>,
+    token_issuer: Arc<TokenIssuerAdapter>,
+    _app_config: Arc<AppConfig>,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    info!("Processing batch token issuance request ({} tokens)", requests.len());
+
+    if requests.is_empty() || requests.len() > MAX_BATCH_ISSUANCE {
+        let error_response = serde_json::json!({
+            "error": "token_issuance_failed",
+            "message": format!("Batch must contain between 1 and {} requests", MAX_BATCH_ISSUANCE),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&error_response),
+            warp::http::StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    let results: Vec<serde_json::Value> = token_issuer
+        .issue_tokens_batch(requests)
+        .await
+        .into_iter()
+        .map(|result| match result {
+            Ok(response) => serde_json::json!({ "ok": response }),
+            Err(e) => {
+                error!("Token issuance failed: {}", e);
+                serde_json::json!({ "error": "token_issuance_failed", "message": e.to_string() })
+            }
+        })
+        .collect();
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({ "results": results })),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+/// Handle session token issuance request
+async fn handle_issue_session_token(
+    request: TokenIssuanceRequest,
+    token_issuer: Arc<TokenIssuerAdapter>,
+    _app_config: Arc<AppConfig>,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    info!("Processing session token request for user: {}", request.user_id);
+
+    match token_issuer.issue_session_token(request).await {
+        Ok(response) => {
+            Ok(warp::reply::with_status(
+                warp::reply::json(&response),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => {
+            error!("Session token issuance failed: {}", e);
+            let error_response = serde_json::json!({
+                "error": "session_issuance_failed",
+                "message": e.to_string(),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            });
+            
+            Ok(warp::reply::with_status(
+                warp::reply::json(&error_response),
+                warp::http::StatusCode::BAD_REQUEST,
+            ))
+        }
+    }
+}
+
+/// Handle logout request
+async fn handle_logout(
+    request: LogoutRequest,
+    token_issuer: Arc<TokenIssuerAdapter>,
+    _app_config: Arc<AppConfig>,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    match token_issuer.end_session(&request.token).await {
+        Ok(ended) => {
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({ "ended": ended })),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => {
+            error!("Logout failed: {}", e);
+            let error_response = serde_json::json!({
+                "error": "logout_failed",
+                "message": e.to_string(),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            });
+            
+            Ok(warp::reply::with_status(
+                warp::reply::json(&error_response),
+                warp::http::StatusCode::BAD_REQUEST,
+            ))
+        }
+    }
+}
+
 /// Handle PoW challenge generation request
 async fn handle_pow_challenge(
     token_issuer: Arc<TokenIssuerAdapter>,
@@ -202,6 +387,106 @@ async fn handle_pow_challenge(
     }
 }
 
+/// Handle stake challenge request
+async fn handle_stake_challenge(
+    request: StakeChallengeRequest,
+    token_issuer: Arc<TokenIssuerAdapter>,
+    _app_config: Arc<AppConfig>,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    info!("Processing stake challenge request for address: {}", request.address);
+
+    match token_issuer.issue_stake_challenge(&request.address).await {
+        Ok(challenge) => {
+            Ok(warp::reply::with_status(
+                warp::reply::json(&challenge),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => {
+            error!("Stake challenge generation failed: {}", e);
+            let error_response = serde_json::json!({
+                "error": "stake_challenge_generation_failed",
+                "message": e.to_string(),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            });
+            
+            Ok(warp::reply::with_status(
+                warp::reply::json(&error_response),
+                warp::http::StatusCode::BAD_REQUEST,
+            ))
+        }
+    }
+}
+
+/// Handle `GET /pow/stream`: upgrade to a stratum-lite WebSocket session
+async fn handle_pow_stream(
+    ws: warp::ws::Ws,
+    forwarded_for: Option<String>,
+    token_issuer: Arc<TokenIssuerAdapter>,
+    app_config: Arc<AppConfig>,
+    connections: Arc<AtomicUsize>,
+) -> Result<Box<dyn Reply>, warp::reject::Rejection> {
+    let stratum = &app_config.stratum;
+    if !stratum.enabled {
+        return Ok(Box::new(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "error": "pow_stream_disabled" })),
+            warp::http::StatusCode::NOT_FOUND,
+        )));
+    }
+    if connections.fetch_add(1, Ordering::SeqCst) >= stratum.max_connections {
+        connections.fetch_sub(1, Ordering::SeqCst);
+        return Ok(Box::new(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "error": "too_many_connections" })),
+            warp::http::StatusCode::SERVICE_UNAVAILABLE,
+        )));
+    }
+
+    // X-Forwarded-For is only believed from trusted proxies, as for `POST /`
+    let client_ip = extract_and_validate_client_ip(forwarded_for.as_deref().unwrap_or_default(), &app_config);
+    let default_difficulty = app_config
+        .security
+        .pow
+        .as_ref()
+        .map(|pow| pow.default_difficulty.clone())
+        .unwrap_or_else(|| "0000ffff".to_string());
+    let idle_timeout = std::time::Duration::from_secs(stratum.idle_timeout_seconds);
+    let session = StratumSession::new(token_issuer, stratum, &default_difficulty, client_ip);
+
+    Ok(Box::new(ws.on_upgrade(move |socket| async move {
+        run_pow_stream(socket, session, idle_timeout).await;
+        connections.fetch_sub(1, Ordering::SeqCst);
+    })))
+}
+
+/// Drive one stratum-lite connection until the client leaves or goes idle
+async fn run_pow_stream(socket: warp::ws::WebSocket, mut session: StratumSession, idle_timeout: std::time::Duration) {
+    let (mut tx, mut rx) = socket.split();
+    loop {
+        let message = match tokio::time::timeout(idle_timeout, rx.next()).await {
+            Ok(Some(Ok(message))) => message,
+            Ok(Some(Err(e))) => {
+                warn!("PoW stream error: {}", e);
+                break;
+            }
+            Ok(None) => break,
+            Err(_) => {
+                info!("Closing idle PoW stream");
+                break;
+            }
+        };
+        if message.is_close() {
+            break;
+        }
+        let Ok(text) = message.to_str() else { continue };
+        for reply in session.handle(text).await {
+            if tx.send(warp::ws::Message::text(reply)).await.is_err() {
+                return;
+            }
+        }
+    }
+    let _ = tx.close().await;
+}
+
 /// Handle token validation request
 async fn handle_validate_token(
     request: TokenValidationRequest,
@@ -260,8 +545,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app_config = AppConfig::default();
     let token_service_config = TokenServiceConfig::default();
 
+    // Sessions and partner usage live in Redis when the cache is enabled so the RPC server sees them
+    let redis = if app_config.cache.enabled {
+        match redis::Client::open(app_config.cache.redis_url.clone()) {
+            Ok(client) => redis::aio::ConnectionManager::new(client).await.ok().map(Arc::new),
+            Err(_) => None,
+        }
+    } else {
+        None
+    };
+    PartnerUsageRegistry::global().configure(redis.clone());
+    if app_config.session.enabled && redis.is_none() {
+        return Err("session.enabled requires Redis; sessions cannot be shared with the RPC server".into());
+    }
+    let session_store = app_config
+        .session
+        .enabled
+        .then(|| Arc::new(SessionStore::new(redis, &app_config.session)));
+
     // Create and run the service
     let service = TokenService::new(token_service_config, app_config);
+    let service = match session_store {
+        Some(store) => service.with_session_store(store),
+        None => service,
+    };
     
     if let Err(e) = service.run().await {
         error!("Token service failed: {}", e);
@@ -0,0 +1,178 @@
+//! HTTP benchmark for the RPC server
+//!
+//! Starts the real server against a mock daemon (or targets a running
+//! deployment with `--target`), drives it with representative method mixes
+//! and reports requests per second and latency percentiles. `--max-p99-ms`
+//! and `--min-rps` turn it into a regression gate: the exit code is 1 when a
+//! mix misses either threshold.
+//!
+//! ```text
+//! cargo run --release --features bench --bin verus-rpc-bench -- --mix all --concurrency 64 --duration 15
+//! ```
+
+use std::time::Duration;
+
+use tracing::error;
+use verus_rpc_server::bench::{mix, mixes, run_load, BenchServer, BenchServerOptions, LoadOptions, LoadReport};
+
+const USAGE: &str = "Usage: verus-rpc-bench [options]
+
+  --mix NAME              explorer, wallet, mixed or all (default: all)
+  --concurrency N         concurrent requests (default: 32)
+  --duration SECS         measured seconds per mix (default: 10)
+  --warmup SECS           unmeasured seconds before each mix (default: 2)
+  --daemon-latency-ms MS  latency of the mock daemon (default: 0)
+  --redis URL             enable the response cache against this Redis
+  --target URL            benchmark a running server instead of an in-process one
+  --max-p99-ms MS         fail when a mix's p99 latency exceeds MS
+  --min-rps N             fail when a mix serves fewer than N requests per second
+  --json                  print one JSON report per line";
+
+#[derive(Debug)]
+struct Args {
+    mix: String,
+    concurrency: usize,
+    duration: Duration,
+    warmup: Duration,
+    daemon_latency: Duration,
+    redis_url: Option<String>,
+    target: Option<String>,
+    max_p99_ms: Option<f64>,
+    min_rps: Option<f64>,
+    json: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args {
+            mix: "all".to_string(),
+            concurrency: 32,
+            duration: Duration::from_secs(10),
+            warmup: Duration::from_secs(2),
+            daemon_latency: Duration::ZERO,
+            redis_url: None,
+            target: None,
+            max_p99_ms: None,
+            min_rps: None,
+            json: false,
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--mix" => parsed.mix = value()?,
+                "--concurrency" => parsed.concurrency = number(&arg, &value()?)?,
+                "--duration" => parsed.duration = Duration::from_secs_f64(number(&arg, &value()?)?),
+                "--warmup" => parsed.warmup = Duration::from_secs_f64(number(&arg, &value()?)?),
+                "--daemon-latency-ms" => parsed.daemon_latency = Duration::from_millis(number(&arg, &value()?)?),
+                "--redis" => parsed.redis_url = Some(value()?),
+                "--target" => parsed.target = Some(value()?),
+                "--max-p99-ms" => parsed.max_p99_ms = Some(number(&arg, &value()?)?),
+                "--min-rps" => parsed.min_rps = Some(number(&arg, &value()?)?),
+                "--json" => parsed.json = true,
+                "-h" | "--help" => return Err(String::new()),
+                other => return Err(format!("unknown option {}", other)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn number<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{} expects a number, got {:?}", arg, value))
+}
+
+/// Threshold violations for `report`
+fn violations(args: &Args, report: &LoadReport) -> Vec<String> {
+    let mut failed = Vec::new();
+    if report.errors > 0 {
+        failed.push(format!("{}: {} failed requests", report.mix, report.errors));
+    }
+    if let Some(max) = args.max_p99_ms.filter(|max| report.p99_ms > *max) {
+        failed.push(format!("{}: p99 {:.2} ms exceeds {:.2} ms", report.mix, report.p99_ms, max));
+    }
+    if let Some(min) = args.min_rps.filter(|min| report.requests_per_second < *min) {
+        failed.push(format!("{}: {:.1} req/s is below {:.1}", report.mix, report.requests_per_second, min));
+    }
+    failed
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .init();
+
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("{}\n", message);
+            }
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    let selected = if args.mix == "all" {
+        mixes()
+    } else if let Some(selected) = mix(&args.mix) {
+        vec![selected]
+    } else {
+        eprintln!("unknown mix {}\n\n{}", args.mix, USAGE);
+        std::process::exit(2);
+    };
+
+    // Keep the in-process server alive for the whole run
+    let server = match &args.target {
+        Some(_) => None,
+        None => match BenchServer::start(BenchServerOptions {
+            daemon_latency: args.daemon_latency,
+            redis_url: args.redis_url.clone(),
+            rate_limit: false,
+        })
+        .await
+        {
+            Ok(server) => Some(server),
+            Err(e) => {
+                error!("Failed to start the benchmark server: {}", e);
+                std::process::exit(1);
+            }
+        },
+    };
+    let target = args
+        .target
+        .clone()
+        .or_else(|| server.as_ref().map(|server| server.url().to_string()))
+        .unwrap_or_default();
+
+    let options = LoadOptions {
+        target,
+        concurrency: args.concurrency,
+        duration: args.duration,
+        warmup: args.warmup,
+    };
+    let mut failed = Vec::new();
+    for mix in &selected {
+        let report = run_load(&options, mix).await;
+        if args.json {
+            println!("{}", serde_json::to_string(&report).unwrap_or_default());
+        } else {
+            println!("{}", report);
+        }
+        failed.extend(violations(&args, &report));
+    }
+    if let Some(server) = &server {
+        if !args.json {
+            println!("mock daemon calls: {}", server.daemon_calls());
+        }
+    }
+
+    if !failed.is_empty() {
+        for failure in &failed {
+            eprintln!("FAILED {}", failure);
+        }
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,121 @@
+//! Contract tests for a deployment
+//!
+//! Generates request cases from the method registry and checks that a running
+//! deployment accepts and rejects them the way the registry says it should.
+//! Mismatches are printed and make the exit code 1, so the binary can gate
+//! releases and CI. `--emit` prints the cases as JSON Lines without running
+//! them, for use with other tooling.
+//!
+//! ```text
+//! cargo run --bin verus-rpc-contract -- --target http://127.0.0.1:8080/ --forwarded-for 127.0.0.1
+//! ```
+
+use std::collections::HashSet;
+
+use verus_rpc_server::domain::validation::{contract, ContractCase, MethodRegistry};
+use verus_rpc_server::infrastructure::adapters::{ContractRunner, Observed};
+
+const USAGE: &str = "Usage: verus-rpc-contract [options]
+
+  --target URL          JSON-RPC endpoint to test
+  --token TOKEN         bearer token sent with every case
+  --api-key KEY         X-API-Key sent with every case
+  --forwarded-for IP    X-Forwarded-For sent with every case
+  --method NAME         only test this method (repeatable)
+  --emit                print the cases as JSON Lines instead of running them
+  --json                print every result as JSON Lines";
+
+#[derive(Debug, Default)]
+struct Args {
+    target: Option<String>,
+    token: Option<String>,
+    api_key: Option<String>,
+    forwarded_for: Option<String>,
+    methods: HashSet<String>,
+    emit: bool,
+    json: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--target" => parsed.target = Some(value()?),
+                "--token" => parsed.token = Some(value()?),
+                "--api-key" => parsed.api_key = Some(value()?),
+                "--forwarded-for" => parsed.forwarded_for = Some(value()?),
+                "--method" => {
+                    parsed.methods.insert(value()?);
+                }
+                "--emit" => parsed.emit = true,
+                "--json" => parsed.json = true,
+                "-h" | "--help" => return Err(String::new()),
+                other => return Err(format!("unknown option {}", other)),
+            }
+        }
+        if !parsed.emit && parsed.target.is_none() {
+            return Err("--target is required unless --emit is given".to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+fn exit_with_usage(message: &str) -> ! {
+    if !message.is_empty() {
+        eprintln!("{}\n", message);
+    }
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|message| exit_with_usage(&message));
+
+    let registry = MethodRegistry::new();
+    if let Some(unknown) = args.methods.iter().find(|method| registry.get_method(method).is_none()) {
+        exit_with_usage(&format!("unknown method {}", unknown));
+    }
+    let cases: Vec<ContractCase> = contract::generate(&registry)
+        .into_iter()
+        .filter(|case| args.methods.is_empty() || args.methods.contains(&case.method))
+        .collect();
+
+    if args.emit {
+        for case in &cases {
+            println!("{}", serde_json::to_string(case).unwrap_or_default());
+        }
+        return;
+    }
+
+    let runner = ContractRunner::new(args.target.clone().unwrap_or_default())
+        .with_bearer_token(args.token.clone())
+        .with_api_key(args.api_key.clone())
+        .with_forwarded_for(args.forwarded_for.clone());
+    let report = runner.run(&cases).await;
+
+    if args.json {
+        for result in &report.results {
+            println!("{}", serde_json::to_string(result).unwrap_or_default());
+        }
+    } else {
+        for result in report.mismatched() {
+            let observed = match &result.observed {
+                Observed::Accepted => "accepted".to_string(),
+                Observed::Rejected { code, message } => format!("rejected ({} {})", code, message),
+                Observed::Failed { reason } => format!("failed ({})", reason),
+            };
+            println!(
+                "MISMATCH {} / {}: expected {:?}, got {} for params {}",
+                result.case.method, result.case.name, result.case.expect, observed, result.case.params
+            );
+        }
+        println!("{} cases, {} mismatches", report.cases, report.mismatches);
+    }
+
+    if report.mismatches > 0 {
+        std::process::exit(1);
+    }
+}
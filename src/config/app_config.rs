@@ -32,21 +32,103 @@ impl Default for CircuitBreakerConfig {
     }
 }
 
+/// Daemon RPC authentication method
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DaemonAuthMethod {
+    /// Static `rpc_user` / `rpc_password`
+    #[default]
+    Password,
+    /// verusd `.cookie` file, re-read when it rotates
+    Cookie,
+}
+
+/// Preferred IP address family for the upstream daemon
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFamily {
+    /// Keep resolver order
+    #[default]
+    Any,
+    /// Try IPv4 addresses first
+    Ipv4,
+    /// Try IPv6 addresses first
+    Ipv6,
+}
+
+/// Upstream DNS resolution configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpstreamDnsConfig {
+    /// Re-resolve the daemon host name after this many seconds (0 = use system resolver per connection)
+    pub refresh_interval_seconds: u64,
+    
+    /// Address family tried first when the host has both IPv4 and IPv6 addresses
+    pub preferred_family: AddressFamily,
+}
+
+impl Default for UpstreamDnsConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval_seconds: 60,
+            preferred_family: AddressFamily::Any,
+        }
+    }
+}
+
+/// Packing concurrent daemon calls into JSON-RPC batches
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpstreamPipeliningConfig {
+    /// Pack calls into batches when the daemon is busy
+    pub enabled: bool,
+    
+    /// Calls already in flight to the daemon before new ones are packed
+    #[validate(range(min = 1, max = 1000))]
+    pub min_in_flight: usize,
+    
+    /// Most calls in one batch
+    #[validate(range(min = 2, max = 500))]
+    pub max_batch: usize,
+    
+    /// Longest a call waits for others to join its batch (milliseconds)
+    #[validate(range(min = 1, max = 100))]
+    pub max_delay_ms: u64,
+}
+
+impl Default for UpstreamPipeliningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_in_flight: 8,
+            max_batch: 16,
+            max_delay_ms: 2,
+        }
+    }
+}
+
 /// Verus daemon configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_daemon_auth"))]
 pub struct VerusConfig {
     /// RPC URL
     #[validate(url)]
     pub rpc_url: String,
     
-    /// RPC username
-    #[validate(length(min = 1))]
+    /// Authentication method for the daemon
+    #[serde(default)]
+    pub auth_method: DaemonAuthMethod,
+    
+    /// RPC username (password auth)
+    #[serde(default)]
     pub rpc_user: String,
     
-    /// RPC password
-    #[validate(length(min = 1))]
+    /// RPC password (password auth)
+    #[serde(default)]
     pub rpc_password: String,
     
+    /// Path to the daemon cookie file (cookie auth), e.g. `~/.komodo/VRSC/.cookie`
+    #[serde(default)]
+    pub cookie_file: Option<String>,
+    
     /// Connection timeout in seconds
     #[validate(range(min = 1, max = 300))]
     pub timeout_seconds: u64,
@@ -57,6 +139,28 @@ pub struct VerusConfig {
     
     /// Circuit breaker configuration
     pub circuit_breaker: Option<CircuitBreakerConfig>,
+    
+    /// Upstream DNS resolution
+    #[serde(default)]
+    pub dns: UpstreamDnsConfig,
+    
+    /// Batch concurrent calls to the daemon
+    #[serde(default)]
+    #[validate(nested)]
+    pub pipelining: UpstreamPipeliningConfig,
+}
+
+/// Ensure credentials are present for the selected daemon auth method
+fn validate_daemon_auth(verus: &VerusConfig) -> Result<(), validator::ValidationError> {
+    let valid = match verus.auth_method {
+        DaemonAuthMethod::Password => !verus.rpc_user.is_empty() && !verus.rpc_password.is_empty(),
+        DaemonAuthMethod::Cookie => verus.cookie_file.as_deref().is_some_and(|path| !path.is_empty()),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("daemon_auth_credentials_missing"))
+    }
 }
 
 /// Server configuration
@@ -76,6 +180,113 @@ pub struct ServerConfig {
     /// Worker threads (0 for auto-detect)
     #[validate(range(min = 0, max = 64))]
     pub worker_threads: usize,
+    
+    /// Largest request head (request line and headers) in bytes
+    #[serde(default = "default_max_header_bytes")]
+    #[validate(range(min = 8192, max = 1048576))]
+    pub max_header_bytes: usize,
+    
+    /// Most headers accepted in one request
+    #[serde(default = "default_max_headers")]
+    #[validate(range(min = 8, max = 1000))]
+    pub max_headers: usize,
+    
+    /// Time a client has to send the complete request head (seconds)
+    #[serde(default = "default_header_read_timeout_seconds")]
+    #[validate(range(min = 1, max = 300))]
+    pub header_read_timeout_seconds: u64,
+    
+    /// Longest a read may stall while a request is being served (seconds)
+    #[serde(default = "default_read_timeout_seconds")]
+    #[validate(range(min = 1, max = 3600))]
+    pub read_timeout_seconds: u64,
+    
+    /// Longest a write may stall on a client that stopped reading (seconds)
+    #[serde(default = "default_write_timeout_seconds")]
+    #[validate(range(min = 1, max = 3600))]
+    pub write_timeout_seconds: u64,
+    
+    /// Keep-alive connections without a request for this long are closed (seconds)
+    #[serde(default = "default_idle_timeout_seconds")]
+    #[validate(range(min = 1, max = 3600))]
+    pub idle_timeout_seconds: u64,
+    
+    /// Accept HTTP/2 (prior-knowledge h2c) next to HTTP/1.1
+    #[serde(default = "default_http2_enabled")]
+    pub http2_enabled: bool,
+    
+    /// Concurrent streams allowed on one HTTP/2 connection
+    #[serde(default = "default_http2_max_concurrent_streams")]
+    #[validate(range(min = 1, max = 10000))]
+    pub http2_max_concurrent_streams: u32,
+    
+    /// Also serve on this Unix domain socket (unset to listen on TCP only)
+    #[serde(default)]
+    pub unix_socket_path: Option<String>,
+    
+    /// Permission bits of the Unix socket file, e.g. 0o660
+    #[serde(default = "default_unix_socket_mode")]
+    #[validate(range(max = 0o777))]
+    pub unix_socket_mode: u32,
+    
+    /// Most threads the runtime starts for blocking work (file IO, CPU pool jobs)
+    #[serde(default = "default_max_blocking_threads")]
+    #[validate(range(min = 8, max = 4096))]
+    pub max_blocking_threads: usize,
+    
+    /// CPU-heavy jobs run at once off the IO workers (0 for one per core)
+    #[serde(default)]
+    #[validate(range(max = 64))]
+    pub cpu_pool_threads: usize,
+    
+    /// Payloads smaller than this are processed inline instead of on the CPU pool (bytes)
+    #[serde(default = "default_cpu_offload_min_bytes")]
+    #[validate(range(max = 10485760))]
+    pub cpu_offload_min_bytes: usize,
+}
+
+fn default_max_header_bytes() -> usize {
+    16 * 1024
+}
+
+fn default_max_headers() -> usize {
+    64
+}
+
+fn default_header_read_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_read_timeout_seconds() -> u64 {
+    330
+}
+
+fn default_write_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_idle_timeout_seconds() -> u64 {
+    60
+}
+
+fn default_http2_enabled() -> bool {
+    true
+}
+
+fn default_http2_max_concurrent_streams() -> u32 {
+    256
+}
+
+fn default_unix_socket_mode() -> u32 {
+    0o660
+}
+
+fn default_max_blocking_threads() -> usize {
+    512
+}
+
+fn default_cpu_offload_min_bytes() -> usize {
+    16 * 1024
 }
 
 /// PoW configuration
@@ -138,6 +349,33 @@ pub struct MiningPoolConfig {
     
     /// Enable pool integration
     pub enabled: bool,
+    
+    /// How long validation results for identical shares are reused (seconds, 0 disables)
+    #[serde(default = "default_share_cache_ttl_seconds")]
+    #[validate(range(max = 86400))]
+    pub share_cache_ttl_seconds: u64,
+    
+    /// Maximum cached share validation results
+    #[serde(default = "default_share_cache_max_entries")]
+    #[validate(range(max = 1000000))]
+    pub share_cache_max_entries: usize,
+    
+    /// Maximum shares per batch validation request to the pool
+    #[serde(default = "default_pool_max_batch_size")]
+    #[validate(range(min = 1, max = 1000))]
+    pub max_batch_size: usize,
+}
+
+fn default_share_cache_ttl_seconds() -> u64 {
+    300
+}
+
+fn default_share_cache_max_entries() -> usize {
+    10000
+}
+
+fn default_pool_max_batch_size() -> usize {
+    50
 }
 
 impl Default for MiningPoolConfig {
@@ -152,6 +390,9 @@ impl Default for MiningPoolConfig {
             circuit_breaker_timeout: 60,
             requests_per_minute: 100,
             enabled: false,
+            share_cache_ttl_seconds: default_share_cache_ttl_seconds(),
+            share_cache_max_entries: default_share_cache_max_entries(),
+            max_batch_size: default_pool_max_batch_size(),
         }
     }
 }
@@ -224,6 +465,88 @@ pub struct RateLimitConfig {
     
     /// Enable rate limiting
     pub enabled: bool,
+    
+    /// Tokens debited per call, by method
+    #[serde(default)]
+    pub costs: MethodCostConfig,
+    
+    /// Maximum client keys tracked at once; the least recently seen is evicted beyond this
+    #[serde(default = "default_max_tracked_keys")]
+    #[validate(range(min = 1, max = 10000000))]
+    pub max_tracked_keys: usize,
+    
+    /// Clients that bypass rate limiting and client profile budgets
+    #[serde(default)]
+    pub exemptions: RateLimitExemptionConfig,
+}
+
+fn default_max_tracked_keys() -> usize {
+    100_000
+}
+
+/// Rate limit exemptions for internal monitoring and first-party services
+///
+/// A request matching any entry skips the per-client rate limit and its
+/// profile's budget; it is still counted under `rate_limit_exemptions` in
+/// the metrics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct RateLimitExemptionConfig {
+    /// Source networks in CIDR notation (`10.0.0.0/8`, `fd00::/8`); bare addresses match one host
+    #[serde(default)]
+    pub cidrs: Vec<String>,
+    
+    /// Lowercase hex SHA-256 of exempt `X-API-Key` values
+    #[serde(default)]
+    pub api_key_hashes: Vec<String>,
+    
+    /// JWT permissions that exempt their bearer
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// Rate-limit cost weights
+///
+/// Methods listed in `methods` use their own weight, then methods of a
+/// category listed in `categories`; everything else is weighted by its
+/// validation `SecurityLevel`.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct MethodCostConfig {
+    /// Cost of low security-level methods
+    #[validate(range(min = 1, max = 1000))]
+    pub low: u32,
+    
+    /// Cost of medium security-level methods
+    #[validate(range(min = 1, max = 1000))]
+    pub medium: u32,
+    
+    /// Cost of high security-level methods
+    #[validate(range(min = 1, max = 1000))]
+    pub high: u32,
+    
+    /// Per-method overrides
+    pub methods: std::collections::HashMap<String, u32>,
+    
+    /// Per-category overrides, keyed by method category (`address_index`, `wallet_z`, ...)
+    #[serde(default)]
+    pub categories: std::collections::HashMap<String, u32>,
+}
+
+impl Default for MethodCostConfig {
+    fn default() -> Self {
+        let methods = [
+            ("getblocktemplate", 10),
+            ("getaddressdeltas", 10),
+            ("getaddresstxids", 5),
+            ("getaddressutxos", 5),
+            ("getaddressmempool", 3),
+            ("getrawmempool", 3),
+            ("testrawtransaction", 5),
+        ]
+        .into_iter()
+        .map(|(method, cost)| (method.to_string(), cost))
+        .collect();
+        Self { low: 1, medium: 2, high: 5, methods, categories: std::collections::HashMap::new() }
+    }
 }
 
 /// JWT configuration
@@ -244,6 +567,14 @@ pub struct JwtConfig {
     /// JWT audience
     #[validate(length(min = 1))]
     pub audience: String,
+
+    /// JWT signing algorithm; only HS256 is supported
+    #[serde(default = "default_jwt_algorithm")]
+    pub algorithm: String,
+}
+
+fn default_jwt_algorithm() -> String {
+    "HS256".to_string()
 }
 
 /// Logging configuration
@@ -259,6 +590,40 @@ pub struct LoggingConfig {
     
     /// Enable structured logging
     pub structured: bool,
+    
+    /// Sampling of per-request log lines
+    #[serde(default)]
+    #[validate(nested)]
+    pub sampling: RequestLogSamplingConfig,
+}
+
+/// Request log sampling
+///
+/// When enabled, each JSON-RPC request is logged once on completion instead
+/// of on arrival: every error response, every request slower than
+/// `slow_request_ms`, and one in `success_sample_rate` of the rest.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct RequestLogSamplingConfig {
+    /// Log sampled completion lines in place of per-request logging
+    pub enabled: bool,
+    
+    /// Log one in this many successful requests (1 logs all of them)
+    #[validate(range(min = 1, max = 1000000))]
+    pub success_sample_rate: u64,
+    
+    /// Requests slower than this are always logged (milliseconds)
+    #[validate(range(min = 1, max = 600000))]
+    pub slow_request_ms: u64,
+}
+
+impl Default for RequestLogSamplingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            success_sample_rate: 100,
+            slow_request_ms: 1000,
+        }
+    }
 }
 
 /// Cache configuration
@@ -278,135 +643,3088 @@ pub struct CacheConfig {
     /// Maximum cache size in bytes
     #[validate(range(min = 1024, max = 1073741824))] // 1KB to 1GB
     pub max_size: usize,
+    
+    /// Redis command timeout; a slower answer counts as an outage (milliseconds)
+    #[serde(default = "default_redis_timeout_ms")]
+    #[validate(range(min = 10, max = 10000))]
+    pub redis_timeout_ms: u64,
+    
+    /// Longest delay between Redis reconnect attempts while degraded (seconds)
+    #[serde(default = "default_redis_retry_max_seconds")]
+    #[validate(range(min = 1, max = 600))]
+    pub redis_retry_max_seconds: u64,
 }
 
-/// Payment tier configuration
+fn default_redis_timeout_ms() -> u64 {
+    500
+}
+
+fn default_redis_retry_max_seconds() -> u64 {
+    30
+}
+
+/// Error message localization configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
-pub struct PaymentTierConfig {
+pub struct LocalizationConfig {
+    /// Enable localized error messages selected via Accept-Language
+    pub enabled: bool,
+    
+    /// Locale used when no requested locale is available
+    #[validate(length(min = 2))]
+    pub default_locale: String,
+    
+    /// Directory containing `<locale>.json` message catalogs
     #[validate(length(min = 1))]
-    pub id: String,
-    #[validate(range(min = 0.00000001))]
-    pub amount_vrsc: f64,
-    pub description: Option<String>,
-    pub permissions: Vec<String>,
+    pub catalog_dir: String,
 }
 
-/// Payments configuration
+/// Mempool monitoring configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
-pub struct PaymentsAppConfig {
+pub struct MempoolConfig {
+    /// Enable the mempool sampler and `/mempool/stats` endpoint
     pub enabled: bool,
-    /// Allowed shielded address types: "orchard", "sapling"
-    pub address_types: Vec<String>,
-    /// Default address type
-    #[validate(length(min = 1))]
-    pub default_address_type: String,
-    /// Confirmations to issue provisional token
-    #[validate(range(min = 0, max = 10))]
-    pub min_confirmations: u32,
-    /// Quote/session TTL in minutes
-    #[validate(range(min = 1, max = 1440))]
-    pub session_ttl_minutes: u32,
-    /// Require viewing key presence to accept payments (view-only mode)
-    pub require_viewing_key: bool,
-    /// Optional list of viewing keys to import at startup
-    pub viewing_keys: Vec<String>,
-    /// Rescan mode for viewing key import: "yes", "no", or "whenkeyisnew"
-    #[validate(length(min = 2))]
-    pub viewing_key_rescan: String,
-    /// Configured payment tiers
-    pub tiers: Vec<PaymentTierConfig>,
+    
+    /// Interval between `getrawmempool true` samples (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub sample_interval_seconds: u64,
 }
 
-/// Application configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppConfig {
-    /// Verus daemon configuration
-    pub verus: VerusConfig,
+/// Block explorer aggregation configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ExplorerConfig {
+    /// Maximum concurrent `getrawtransaction` calls per block request
+    #[validate(range(min = 1, max = 64))]
+    pub max_concurrency: usize,
     
-    /// Server configuration
-    pub server: ServerConfig,
+    /// Maximum number of decoded transactions kept in memory
+    pub tx_cache_entries: usize,
     
-    /// Security configuration
-    pub security: SecurityConfig,
+    /// Reject blocks with more transactions than this
+    #[validate(range(min = 1))]
+    pub max_block_transactions: usize,
     
-    /// Rate limiting configuration
-    pub rate_limit: RateLimitConfig,
+    /// Maximum addresses per bulk balance request
+    #[serde(default = "default_max_balance_addresses")]
+    #[validate(range(min = 1, max = 100000))]
+    pub max_balance_addresses: usize,
     
-    /// Logging configuration
-    pub logging: LoggingConfig,
+    /// Addresses sent to the daemon per JSON-RPC batch
+    #[serde(default = "default_balance_chunk_size")]
+    #[validate(range(min = 1, max = 1000))]
+    pub balance_chunk_size: usize,
     
-    /// Cache configuration
-    pub cache: CacheConfig,
-    /// Payments configuration
-    pub payments: PaymentsAppConfig,
+    /// How long a per-address balance is served from cache (seconds, 0 disables)
+    #[serde(default = "default_balance_cache_seconds")]
+    pub balance_cache_seconds: u64,
+    
+    /// Maximum number of cached per-address balances
+    #[serde(default = "default_balance_cache_entries")]
+    pub balance_cache_entries: usize,
 }
 
-impl Default for AppConfig {
-    fn default() -> Self {
-        Self {
-            verus: VerusConfig {
-                rpc_url: "http://127.0.0.1:27486".to_string(),
-                rpc_user: "rpcuser".to_string(),
-                rpc_password: "rpcpassword".to_string(),
-                timeout_seconds: 30,
-                max_retries: 3,
-                circuit_breaker: Some(CircuitBreakerConfig::default()),
-            },
-            server: ServerConfig {
-                bind_address: "127.0.0.1".parse().unwrap(),
-                port: 8080,
-                max_request_size: 1024 * 1024, // 1MB
-                worker_threads: 0, // Auto-detect
-            },
-            security: SecurityConfig {
-                cors_origins: vec!["*".to_string()],
-                cors_methods: vec!["GET".to_string(), "POST".to_string(), "OPTIONS".to_string()],
-                cors_headers: vec![
-                    "Content-Type".to_string(),
-                    "Authorization".to_string(),
-                    "Accept".to_string(),
-                ],
-                enable_request_logging: true,
-                enable_security_headers: true,
-                trusted_proxy_headers: vec!["X-Forwarded-For".to_string()],
-                enable_custom_headers: false,
-                custom_security_header: None,
-                method_rate_limits: std::collections::HashMap::new(),
-                jwt: JwtConfig {
-                    secret_key: "your-super-secret-jwt-key-that-should-be-32-chars-min".to_string(),
-                    expiration_seconds: 3600, // 1 hour
-                    issuer: "verus-rpc-server".to_string(),
-                    audience: "verus-clients".to_string(),
-                },
-                pow: None,
-                mining_pool: None,
-                development_mode: false,
-            },
-            rate_limit: RateLimitConfig {
-                requests_per_minute: 1000,
-                burst_size: 100,
-                enabled: true,
-            },
-            logging: LoggingConfig {
-                level: "info".to_string(),
-                format: "json".to_string(),
-                structured: true,
-            },
-            cache: CacheConfig::default(),
-            payments: PaymentsAppConfig::default(),
-        }
-    }
+fn default_max_balance_addresses() -> usize {
+    5000
 }
 
-impl Default for CacheConfig {
-    fn default() -> Self {
-        Self {
-            enabled: false,
-            redis_url: "redis://127.0.0.1:6379".to_string(),
-            default_ttl: 300, // 5 minutes
-            max_size: 100 * 1024 * 1024, // 100MB
-        }
-    }
+fn default_balance_chunk_size() -> usize {
+    100
+}
+
+fn default_balance_cache_seconds() -> u64 {
+    30
+}
+
+fn default_balance_cache_entries() -> usize {
+    50000
+}
+
+/// Slow upstream query log configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SlowQueryConfig {
+    /// Record upstream calls slower than the threshold
+    pub enabled: bool,
+    
+    /// Duration above which a call is logged (milliseconds)
+    #[validate(range(min = 1))]
+    pub threshold_ms: u64,
+    
+    /// Maximum retained slow-query entries
+    #[validate(range(min = 1, max = 100000))]
+    pub max_entries: usize,
+}
+
+/// Upstream request scheduler configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SchedulerConfig {
+    /// Enable priority scheduling of daemon calls
+    pub enabled: bool,
+    
+    /// Maximum concurrent calls to the daemon
+    #[validate(range(min = 1, max = 1024))]
+    pub max_concurrent_upstream: usize,
+    
+    /// Consecutive high priority grants before a waiting low priority request is served
+    #[validate(range(min = 1, max = 1000))]
+    pub high_priority_burst: u32,
+    
+    /// Maximum time a request waits for a slot before being rejected (milliseconds)
+    #[validate(range(min = 1))]
+    pub max_queue_wait_ms: u64,
+}
+
+/// Token revocation storage backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RevocationBackend {
+    /// Redis when the cache is enabled, memory otherwise
+    #[default]
+    Auto,
+    /// Process memory only; revocations are lost on restart
+    Memory,
+    /// Redis, persisted across restarts
+    Redis,
+}
+
+/// Token revocation store configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct RevocationConfig {
+    /// Storage backend
+    #[serde(default)]
+    pub backend: RevocationBackend,
+    
+    /// Redis URL (defaults to `cache.redis_url`)
+    pub redis_url: Option<String>,
+    
+    /// Interval for purging expired in-memory revocations (seconds)
+    #[validate(range(min = 1))]
+    pub cleanup_interval_seconds: u64,
+    
+    /// How long a per-user revocation is enforced; must cover the longest token lifetime (seconds)
+    #[validate(range(min = 1))]
+    pub user_revocation_ttl_seconds: u64,
+    
+    /// Maximum revocations retained for the admin listing
+    #[validate(range(min = 1, max = 100000))]
+    pub max_listed: usize,
+}
+
+/// Session-bound token configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SessionConfig {
+    /// Allow issuing session-bound tokens
+    pub enabled: bool,
+    
+    /// Session expires after this long without activity (seconds)
+    #[validate(range(min = 60))]
+    pub idle_timeout_seconds: u64,
+    
+    /// Absolute session lifetime regardless of activity (seconds)
+    #[validate(range(min = 60))]
+    pub max_lifetime_seconds: u64,
+}
+
+/// Stratum-lite PoW endpoint configuration (token service WebSocket)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct StratumConfig {
+    /// Serve `GET /pow/stream`
+    pub enabled: bool,
+    
+    /// Solve time the per-connection difficulty adjustment aims for (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub target_solve_seconds: u64,
+    
+    /// Close connections idle for this long (seconds)
+    #[validate(range(min = 10, max = 86400))]
+    pub idle_timeout_seconds: u64,
+    
+    /// Maximum concurrent connections
+    #[validate(range(min = 1, max = 100000))]
+    pub max_connections: usize,
+}
+
+/// Adaptive load-shedding configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct LoadSheddingConfig {
+    /// Reject low-priority traffic while the daemon is struggling
+    pub enabled: bool,
+    
+    /// Upstream p95 latency that starts shedding (milliseconds)
+    #[validate(range(min = 1))]
+    pub p95_latency_ms: u64,
+    
+    /// Upstream error rate that starts shedding (0.0 - 1.0)
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub error_rate: f64,
+    
+    /// Shedding stops only once both signals fall below this fraction of their thresholds
+    #[validate(range(min = 0.1, max = 1.0))]
+    pub recovery_ratio: f64,
+    
+    /// Upstream calls needed in an evaluation window before the error rate counts
+    #[validate(range(min = 1))]
+    pub min_samples: u64,
+    
+    /// How often upstream health is re-evaluated (seconds)
+    #[validate(range(min = 1, max = 300))]
+    pub evaluation_interval_seconds: u64,
+    
+    /// Retry-After sent with 503 responses (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub retry_after_seconds: u64,
+    
+    /// Methods treated as high-cost and shed first
+    pub high_cost_methods: Vec<String>,
+}
+
+/// Chain reorganization monitor configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ChainEventsConfig {
+    /// Watch for reorgs and publish them to subscribers
+    pub enabled: bool,
+    
+    /// How often the chain tip is checked (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub poll_interval_seconds: u64,
+    
+    /// Recent blocks whose hashes are tracked; bounds the reorg depth that can be located
+    #[validate(range(min = 1, max = 10000))]
+    pub track_depth: u64,
+    
+    /// URLs that receive each event as a JSON POST
+    pub webhook_urls: Vec<String>,
+    
+    /// Timeout for one webhook delivery (seconds)
+    #[validate(range(min = 1, max = 60))]
+    pub webhook_timeout_seconds: u64,
+    
+    /// Concurrent `/api/events` WebSocket subscribers
+    #[validate(range(min = 1, max = 100000))]
+    pub max_subscribers: usize,
+}
+
+/// Transaction confirmation tracking configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct TxTrackingConfig {
+    /// Serve `/track/tx` and watch registered transactions
+    pub enabled: bool,
+    
+    /// How often tracked transactions are checked (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub poll_interval_seconds: u64,
+    
+    /// Registrations held at once
+    #[validate(range(min = 1, max = 1000000))]
+    pub max_tracked: usize,
+    
+    /// Registrations one client IP may hold at once
+    #[validate(range(min = 1, max = 1000000))]
+    pub max_tracked_per_client: usize,
+    
+    /// Highest confirmation target a client may request
+    #[validate(range(min = 1, max = 10000))]
+    pub max_target_confirmations: u32,
+    
+    /// How long a transaction must be missing before it is reported evicted or double-spent (seconds)
+    #[validate(range(min = 0, max = 86400))]
+    pub eviction_grace_seconds: u64,
+    
+    /// Registrations still pending after this long are expired (seconds)
+    #[validate(range(min = 60))]
+    pub max_tracking_seconds: u64,
+    
+    /// How long finished registrations stay readable (seconds)
+    #[validate(range(max = 604800))]
+    pub retention_seconds: u64,
+    
+    /// Accept a per-registration `webhook_url`
+    pub allow_client_webhooks: bool,
+    
+    /// Timeout for one webhook delivery (seconds)
+    #[validate(range(min = 1, max = 60))]
+    pub webhook_timeout_seconds: u64,
+}
+
+/// Redis coordination between replicas behind a load balancer
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ClusterConfig {
+    /// Share rate-limit windows, revocations, sessions, payment sessions and single-flight locks through Redis
+    pub enabled: bool,
+    
+    /// Redis URL; defaults to `[cache] redis_url`
+    pub redis_url: Option<String>,
+    
+    /// Prefix for every cluster key, so several deployments can share one Redis
+    #[validate(length(min = 1, max = 64))]
+    pub key_prefix: String,
+    
+    /// Name of this replica in logs and lock owners; defaults to a random id per process
+    pub node_id: Option<String>,
+    
+    /// How long a single-flight lock is held before it expires (milliseconds)
+    #[validate(range(min = 100, max = 60000))]
+    pub lock_ttl_ms: u64,
+    
+    /// How long a replica waits for another replica's in-flight result before calling the daemon itself (milliseconds)
+    #[validate(range(max = 60000))]
+    pub coalesce_wait_ms: u64,
+    
+    /// Forward cacheable reads that miss locally to the replica owning the cache key
+    #[serde(default)]
+    pub sticky_routing: bool,
+    
+    /// Base URL other replicas use to reach this one (required for sticky routing)
+    #[serde(default)]
+    pub advertise_url: Option<String>,
+    
+    /// Shared secret marking requests forwarded between replicas (required for sticky routing)
+    #[serde(default)]
+    pub forward_secret: Option<String>,
+    
+    /// Seconds between membership heartbeats; a replica silent for three intervals leaves the ring
+    #[serde(default = "default_heartbeat_interval_seconds")]
+    #[validate(range(min = 1, max = 300))]
+    pub heartbeat_interval_seconds: u64,
+    
+    /// Timeout for a forwarded request before serving it locally (milliseconds)
+    #[serde(default = "default_forward_timeout_ms")]
+    #[validate(range(min = 50, max = 60000))]
+    pub forward_timeout_ms: u64,
+    
+    /// Points per replica on the hash ring
+    #[serde(default = "default_virtual_nodes")]
+    #[validate(range(min = 1, max = 1024))]
+    pub virtual_nodes: u32,
+}
+
+fn default_heartbeat_interval_seconds() -> u64 {
+    5
+}
+
+fn default_forward_timeout_ms() -> u64 {
+    2000
+}
+
+fn default_virtual_nodes() -> u32 {
+    64
+}
+
+/// Read-only maintenance mode
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct MaintenanceConfig {
+    /// Start with write-class methods refused (toggled at runtime through `/admin/read-only`)
+    pub read_only: bool,
+    
+    /// Error message returned for refused methods
+    #[validate(length(min = 1, max = 512))]
+    pub message: String,
+}
+
+/// Per-client policy overrides
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct ClientProfilesConfig {
+    /// Apply client profiles to JSON-RPC requests
+    pub enabled: bool,
+    
+    /// Configured profiles
+    #[serde(default)]
+    #[validate(nested)]
+    pub profiles: Vec<ClientProfileConfig>,
+}
+
+/// Overrides for one consumer; unset fields fall back to the global policy
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ClientProfileConfig {
+    /// Profile name, used in logs and as the rate-limit key
+    #[validate(length(min = 1, max = 64))]
+    pub id: String,
+    
+    /// Token permissions (e.g. `partner_acme`) this profile applies to; never granted on request
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    
+    /// Hex SHA-256 hashes of API keys sent in `X-API-Key`
+    #[serde(default)]
+    pub api_key_hashes: Vec<String>,
+    
+    /// Budget per minute shared by every request of this client
+    #[serde(default)]
+    #[validate(range(min = 1, max = 1000000))]
+    pub requests_per_minute: Option<u32>,
+    
+    /// Only these methods may be called; all enabled methods when unset
+    #[serde(default)]
+    pub allowed_methods: Option<Vec<String>>,
+    
+    /// Largest accepted request body (bytes)
+    #[serde(default)]
+    #[validate(range(min = 1, max = 104857600))]
+    pub max_request_size: Option<usize>,
+    
+    /// Dotted paths removed from every result returned to this client
+    #[serde(default)]
+    pub hidden_fields: Vec<String>,
+}
+
+/// Operator-defined parameter rewriting before upstream dispatch
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct RewriteConfig {
+    /// Apply the rules to validated JSON-RPC requests
+    pub enabled: bool,
+    
+    /// Rules in the order they are applied
+    #[serde(default)]
+    #[validate(nested)]
+    pub rules: Vec<RewriteRuleConfig>,
+}
+
+/// One parameter rewrite; `set` wins over `default`, and `min`/`max` clamp the result
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_rewrite_rule"))]
+pub struct RewriteRuleConfig {
+    /// Method the rule applies to
+    #[validate(length(min = 1))]
+    pub method: String,
+    
+    /// Index of the positional parameter
+    #[validate(range(max = 32))]
+    pub position: usize,
+    
+    /// Key inside an object parameter at `position`
+    #[serde(default)]
+    pub field: Option<String>,
+    
+    /// Value used when the parameter is missing or null
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+    
+    /// Value that replaces whatever the client sent
+    #[serde(default)]
+    pub set: Option<serde_json::Value>,
+    
+    /// Lower bound for a numeric parameter
+    #[serde(default)]
+    pub min: Option<f64>,
+    
+    /// Upper bound for a numeric parameter
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+
+/// A rewrite rule must do something, and its bounds must not cross
+fn validate_rewrite_rule(rule: &RewriteRuleConfig) -> Result<(), validator::ValidationError> {
+    if rule.default.is_none() && rule.set.is_none() && rule.min.is_none() && rule.max.is_none() {
+        return Err(validator::ValidationError::new("rewrite_rule_without_action"));
+    }
+    if let (Some(min), Some(max)) = (rule.min, rule.max) {
+        if min > max {
+            return Err(validator::ValidationError::new("rewrite_rule_min_above_max"));
+        }
+    }
+    Ok(())
+}
+
+/// Issuance path reviewed by the issuance webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssuanceWebhookSource {
+    /// Tokens minted for a confirmed payment
+    Payment,
+    /// Tokens minted for a solved proof-of-work challenge
+    Pow,
+}
+
+/// External review of token issuance, for billing/CRM systems
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct IssuanceWebhookConfig {
+    /// Ask the webhook before minting tokens
+    pub enabled: bool,
+    
+    /// Endpoint receiving the issuance request as JSON
+    #[serde(default)]
+    #[validate(url)]
+    pub url: Option<String>,
+    
+    /// Key for the HMAC-SHA256 signature sent in `X-Verus-Signature`
+    #[serde(default)]
+    #[validate(length(min = 32))]
+    pub secret: Option<String>,
+    
+    /// Issuance paths the webhook reviews
+    #[serde(default = "default_issuance_webhook_sources")]
+    pub sources: Vec<IssuanceWebhookSource>,
+    
+    /// Webhook timeout (milliseconds)
+    #[serde(default = "default_issuance_webhook_timeout_ms")]
+    #[validate(range(min = 100, max = 30000))]
+    pub timeout_ms: u64,
+    
+    /// Mint tokens unreviewed when the webhook fails or times out (refused otherwise)
+    #[serde(default)]
+    pub fail_open: bool,
+}
+
+fn default_issuance_webhook_sources() -> Vec<IssuanceWebhookSource> {
+    vec![IssuanceWebhookSource::Payment, IssuanceWebhookSource::Pow]
+}
+
+fn default_issuance_webhook_timeout_ms() -> u64 {
+    3000
+}
+
+/// systemd supervision
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SystemdConfig {
+    /// Send `WATCHDOG=1` while the internal health check passes (needs `WatchdogSec=` in the unit)
+    pub watchdog_enabled: bool,
+    
+    /// Count an unreachable daemon as a failed health check
+    pub watchdog_require_daemon: bool,
+    
+    /// Timeout for the health check behind each ping (seconds)
+    #[validate(range(min = 1, max = 300))]
+    pub health_check_timeout_seconds: u64,
+}
+
+/// Response post-processing with WASM modules (requires the `wasm-plugins` feature)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct WasmPluginsConfig {
+    /// Run the configured modules on successful responses
+    pub enabled: bool,
+    
+    /// Fuel (roughly, wasm instructions) one module call may use
+    #[validate(range(min = 1000, max = 10000000000))]
+    pub fuel_per_call: u64,
+    
+    /// Linear memory one module instance may grow to (bytes)
+    #[validate(range(min = 65536, max = 1073741824))]
+    pub max_memory_bytes: usize,
+    
+    /// Return the untransformed response when a module fails (refuse otherwise)
+    pub fail_open: bool,
+    
+    /// Modules, applied in order
+    #[serde(default)]
+    #[validate(nested)]
+    pub modules: Vec<WasmPluginModuleConfig>,
+}
+
+/// One WASM response plugin
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct WasmPluginModuleConfig {
+    /// Plugin name used in logs
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    
+    /// Path of the `.wasm` module
+    #[validate(length(min = 1))]
+    pub path: String,
+    
+    /// Methods whose responses the module transforms
+    #[validate(length(min = 1))]
+    pub methods: Vec<String>,
+}
+
+/// Rhai policy hooks (requires the `scripting` feature)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ScriptingConfig {
+    /// Run the hooks defined in `path`
+    pub enabled: bool,
+    
+    /// Script defining `on_request`, `on_response` and/or `on_error`; re-read when it changes
+    #[validate(length(min = 1))]
+    pub path: String,
+    
+    /// Operations one hook call may run before it is aborted
+    #[validate(range(min = 1000, max = 100000000))]
+    pub max_operations: u64,
+    
+    /// Let requests and results through when a hook fails (refuse otherwise)
+    pub fail_open: bool,
+}
+
+/// What the daemon adapter does with upstream calls in `[recording]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingMode {
+    /// Talk to the daemon normally
+    #[default]
+    Off,
+    /// Talk to the daemon and append each call and its outcome to `path`
+    Record,
+    /// Answer from `path` without contacting the daemon
+    Replay,
+}
+
+/// Record-and-replay of daemon interactions
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct RecordingConfig {
+    /// Off, record or replay
+    pub mode: RecordingMode,
+    
+    /// JSON Lines file written in record mode and read in replay mode
+    #[validate(length(min = 1))]
+    pub path: String,
+    
+    /// Object keys (case-insensitive) whose values are replaced with `[scrubbed]` in recorded params and results
+    pub scrub_keys: Vec<String>,
+}
+
+/// How `[upstream_context]` attaches proxy metadata to daemon calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpstreamContextMode {
+    /// Send calls unchanged
+    #[default]
+    Off,
+    /// Add `header` to each upstream HTTP request
+    Header,
+    /// Replace the JSON-RPC `id` with `<id_prefix>:<request id>:<client id>:<original id>`
+    Id,
+}
+
+/// Request context propagation to the daemon, for correlating daemon-side logs with proxy audit logs
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpstreamContextConfig {
+    /// Off, header or id
+    pub mode: UpstreamContextMode,
+    
+    /// Header carrying `request_id=<id>; client=<client id>` in header mode
+    #[validate(length(min = 1, max = 64))]
+    pub header: String,
+    
+    /// First segment of the JSON-RPC id in id mode
+    #[validate(length(min = 1, max = 32))]
+    pub id_prefix: String,
+    
+    /// Secret mixed into the anonymized client id (SHA-256 of salt and client IP); set it so ids can't be reversed by guessing IPs
+    pub client_id_salt: String,
+}
+
+/// Daemon version and index detection at startup
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CapabilitiesConfig {
+    /// Query the daemon's version and probe its optional indexes at startup
+    pub enabled: bool,
+    
+    /// Refuse methods whose index the daemon was found to lack
+    pub disable_unsupported: bool,
+    
+    /// Warn when the daemon's `VRSCversion` is older than this (e.g. "1.2.0")
+    #[serde(default)]
+    pub min_daemon_version: Option<String>,
+    
+    /// Recent blocks searched for a spent output to probe the spent index with
+    #[validate(range(min = 1, max = 100))]
+    pub probe_blocks: u64,
+    
+    /// Give up on detection after this long (seconds)
+    #[validate(range(min = 1, max = 300))]
+    pub timeout_seconds: u64,
+}
+
+/// Startup diff of the daemon's `help` output against the method registry
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct MethodDiscoveryConfig {
+    /// Call `help` at startup and log methods missing on either side
+    pub enabled: bool,
+    
+    /// Serve unknown read-only methods with conservative defaults until the registry catches up
+    pub auto_register: bool,
+    
+    /// Parameters accepted by an auto-registered method (any JSON type)
+    #[validate(range(max = 16))]
+    pub max_params: usize,
+    
+    /// Methods never auto-registered
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Background warming of hot cache entries
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CacheWarmerConfig {
+    /// Re-fetch hot queries into the response cache on every new block
+    pub enabled: bool,
+    
+    /// Interval between chain tip checks (seconds)
+    #[validate(range(min = 1, max = 300))]
+    pub poll_interval_seconds: u64,
+    
+    /// Methods kept warm, called with `[]` params
+    pub methods: Vec<String>,
+    
+    /// Keep `getblock <tip hash>` warm
+    pub tip_block: bool,
+    
+    /// Currencies kept warm with `getcurrency <name>`
+    #[serde(default)]
+    pub currencies: Vec<String>,
+    
+    /// Maximum concurrent daemon calls per warming pass
+    #[validate(range(min = 1, max = 32))]
+    pub max_concurrency: usize,
+}
+
+/// On-disk cache tier for immutable responses (requires the `disk-cache` feature)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct DiskCacheConfig {
+    /// Keep blocks, headers and transactions looked up by hash on disk, with no TTL
+    pub enabled: bool,
+    
+    /// Database directory
+    #[validate(length(min = 1))]
+    pub path: String,
+    
+    /// Depth at which verbose blocks and transactions are stored
+    #[validate(range(min = 1, max = 10000))]
+    pub min_confirmations: u64,
+    
+    /// Stop storing new entries once the database reaches this size (megabytes)
+    #[validate(range(min = 1))]
+    pub max_size_mb: u64,
+    
+    /// Interval between chain height checks used to refresh `confirmations` (seconds)
+    #[validate(range(min = 1, max = 300))]
+    pub tip_poll_seconds: u64,
+}
+
+/// Bloom-filter cache of block and transaction lookups that found nothing
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct NegativeCacheConfig {
+    /// Answer repeated lookups for unknown txids and block hashes without a daemon call
+    pub enabled: bool,
+    
+    /// Identifiers held before the filter is cleared
+    #[validate(range(min = 1000, max = 10000000))]
+    pub expected_items: usize,
+    
+    /// Chance that a known identifier is reported missing until the next block
+    #[validate(range(min = 0.000001, max = 0.1))]
+    pub false_positive_rate: f64,
+    
+    /// Interval between chain height checks that clear the filter (seconds)
+    #[validate(range(min = 1, max = 300))]
+    pub tip_poll_seconds: u64,
+}
+
+/// Request complexity budgets
+///
+/// A request's score is its method weight times the work its params ask for:
+/// addresses times block-range units for address-index lookups, `count` for
+/// `getexports` and `getblockhashes`, and outputs for `sendcurrency` and
+/// `z_sendmany`. Other calls score their weight alone.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ComplexityConfig {
+    /// Score requests and enforce the budgets below
+    pub enabled: bool,
+    
+    /// Requests scoring above this are rejected
+    #[validate(range(min = 1, max = 1000000000))]
+    pub max_score: u64,
+    
+    /// Requests scoring above this are shed like high-cost methods under load
+    #[validate(range(min = 1, max = 1000000000))]
+    pub deprioritize_score: u64,
+    
+    /// Blocks of an address-index `start`/`end` range that count as one unit
+    #[validate(range(min = 1, max = 10000000))]
+    pub blocks_per_unit: u64,
+    
+    /// Per-method weights; unlisted methods weigh 1
+    pub method_weights: std::collections::HashMap<String, u64>,
+}
+
+/// Waiting for the daemon at startup
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct DaemonWaitConfig {
+    /// Start serving without the daemon and keep retrying it, answering 503 until it is up
+    pub enabled: bool,
+    
+    /// Give up and stop the server once the daemon has not answered for this long (seconds)
+    #[validate(range(min = 1, max = 86400))]
+    pub max_wait_seconds: u64,
+    
+    /// Longest delay between attempts; delays double from half a second up to this (seconds)
+    #[validate(range(min = 1, max = 300))]
+    pub retry_max_seconds: u64,
+}
+
+/// Alerts on calls to sensitive methods
+///
+/// Every call to a listed method is logged at WARN with the caller's
+/// identity and source IP, and POSTed to `webhook_url` when one is set.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SensitiveMethodAlertsConfig {
+    /// Raise an alert for every call to a listed method
+    pub enabled: bool,
+    
+    /// Methods that raise an alert
+    #[serde(default = "default_sensitive_methods")]
+    pub methods: Vec<String>,
+    
+    /// Endpoint receiving each alert as JSON; alerts are only logged when unset
+    #[serde(default)]
+    #[validate(url)]
+    pub webhook_url: Option<String>,
+    
+    /// Key for the HMAC-SHA256 signature sent in `X-Verus-Signature`; alerts are unsigned when unset
+    #[serde(default)]
+    #[validate(length(min = 32))]
+    pub webhook_secret: Option<String>,
+    
+    /// Webhook timeout (milliseconds)
+    #[serde(default = "default_sensitive_alert_timeout_ms")]
+    #[validate(range(min = 100, max = 30000))]
+    pub webhook_timeout_ms: u64,
+}
+
+fn default_sensitive_methods() -> Vec<String> {
+    [
+        "dumpprivkey",
+        "importprivkey",
+        "z_exportkey",
+        "z_importkey",
+        "z_exportviewingkey",
+        "z_importviewingkey",
+        "dumpwallet",
+        "importwallet",
+        "z_exportwallet",
+        "z_importwallet",
+        "revokeidentity",
+        "recoveridentity",
+        "setidentitytimelock",
+        "sendcurrency",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_sensitive_alert_timeout_ms() -> u64 {
+    3000
+}
+
+/// Temporary bans after repeated offenses
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AutoBanConfig {
+    /// Ban clients that keep breaching the rate limit or sending invalid requests
+    pub enabled: bool,
+    
+    /// Rate limit breaches within the window that earn a ban
+    #[validate(range(min = 1, max = 100000))]
+    pub rate_limit_threshold: u32,
+    
+    /// Requests refused as invalid within the window that earn a ban
+    #[validate(range(min = 1, max = 100000))]
+    pub validation_failure_threshold: u32,
+    
+    /// Window over which offenses are counted (seconds)
+    #[validate(range(min = 1, max = 86400))]
+    pub window_seconds: u64,
+    
+    /// Ban duration (seconds)
+    #[validate(range(min = 1, max = 2592000))]
+    pub ban_seconds: u64,
+    
+    /// Clients with recent offenses tracked at once; further offenders are not counted
+    #[validate(range(min = 1, max = 10000000))]
+    pub max_tracked_clients: usize,
+}
+
+/// Ed25519-signed JSON-RPC responses
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_response_signing"))]
+pub struct ResponseSigningConfig {
+    /// Sign successful responses to `methods`, and to requests asking with `X-Verus-Sign: true`
+    pub enabled: bool,
+    
+    /// Hex Ed25519 seed (32 bytes); required when enabled
+    pub signing_key: Option<String>,
+    
+    /// Methods whose responses are always signed
+    #[serde(default)]
+    pub methods: Vec<String>,
+    
+    /// Sign responses to any method for requests sending `X-Verus-Sign: true`
+    pub allow_header: bool,
+}
+
+/// Enabled response signing needs a 32-byte hex seed
+fn validate_response_signing(signing: &ResponseSigningConfig) -> Result<(), validator::ValidationError> {
+    let valid_key = signing
+        .signing_key
+        .as_deref()
+        .and_then(|key| hex::decode(key.trim()).ok())
+        .is_some_and(|seed| seed.len() == 32);
+    if signing.enabled && !valid_key {
+        Err(validator::ValidationError::new("response_signing_key_invalid"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Canonical JSON bodies for selected routes
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_canonical_json"))]
+pub struct CanonicalJsonConfig {
+    /// Re-encode JSON response bodies on `routes` canonically
+    pub enabled: bool,
+    
+    /// Request paths, matched exactly or, when ending in `*`, by prefix (e.g. `/` or `/api/*`)
+    #[serde(default)]
+    pub routes: Vec<String>,
+}
+
+/// Canonical JSON routes are absolute paths
+fn validate_canonical_json(canonical: &CanonicalJsonConfig) -> Result<(), validator::ValidationError> {
+    if canonical.routes.iter().all(|route| route.starts_with('/')) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("canonical_json_route_invalid"))
+    }
+}
+
+/// Currency and identity name resolution
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct NameResolverConfig {
+    /// Serve `GET /resolve/{name}`
+    pub enabled: bool,
+    
+    /// Maximum number of cached resolutions
+    #[validate(range(max = 10000000))]
+    pub cache_entries: usize,
+    
+    /// How long a resolution is served from cache (seconds, 0 disables)
+    #[validate(range(max = 604800))]
+    pub cache_seconds: u64,
+}
+
+/// Batch conversion estimates
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ConversionEstimatesConfig {
+    /// Serve `POST /convert/estimate-batch`
+    pub enabled: bool,
+    
+    /// Maximum conversions per request
+    #[validate(range(min = 1, max = 1000))]
+    pub max_conversions: usize,
+    
+    /// Batches sent before giving up when the chain tip keeps moving
+    #[validate(range(min = 1, max = 10))]
+    pub max_attempts: u32,
+}
+
+/// Marketplace offer aggregation
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct MarketplaceConfig {
+    /// Serve `GET /marketplace/offers/{name}`
+    pub enabled: bool,
+    
+    /// How long a currency's or identity's offers are served from cache (seconds, 0 disables)
+    #[validate(range(max = 3600))]
+    pub cache_seconds: u64,
+    
+    /// Maximum number of currencies and identities whose offers are cached
+    pub cache_entries: usize,
+    
+    /// Maximum offers per page
+    #[validate(range(min = 1, max = 1000))]
+    pub max_page_size: usize,
+}
+
+/// Shielded note commitment tree states
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ShieldedTreeConfig {
+    /// Serve `GET /api/shielded/tree/{height}` and the range endpoint `GET /api/shielded/tree`
+    pub enabled: bool,
+    
+    /// Confirmations after which a height's tree state is treated as immutable and cached
+    #[validate(range(min = 1, max = 10000))]
+    pub immutable_confirmations: u64,
+    
+    /// Maximum number of cached tree states
+    pub cache_entries: usize,
+    
+    /// Maximum tree states per range request
+    #[validate(range(min = 1, max = 1000))]
+    pub max_page_size: usize,
+}
+
+/// Watch-only portfolio balances
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PortfolioConfig {
+    /// Serve `POST /api/portfolio`
+    pub enabled: bool,
+    
+    /// Maximum addresses and identities per request
+    #[validate(range(min = 1, max = 1000))]
+    pub max_addresses: usize,
+    
+    /// How long a portfolio's balances are served from cache (seconds, 0 disables)
+    #[validate(range(max = 3600))]
+    pub cache_seconds: u64,
+    
+    /// Maximum number of cached portfolios
+    pub cache_entries: usize,
+}
+
+/// Asynchronous jobs for long-running aggregate queries
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct JobsConfig {
+    /// Serve `POST /jobs` and `GET /jobs/{id}`
+    pub enabled: bool,
+    
+    /// Maximum running jobs per client IP
+    #[validate(range(min = 1, max = 100))]
+    pub max_jobs_per_client: usize,
+    
+    /// Maximum running jobs across all clients
+    #[validate(range(min = 1, max = 10000))]
+    pub max_jobs: usize,
+    
+    /// How long a job may run before it fails (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub timeout_seconds: u64,
+    
+    /// How long a finished job's status and result are kept (seconds)
+    #[validate(range(min = 1, max = 86400))]
+    pub result_ttl_seconds: u64,
+}
+
+/// Scheduled reports
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ReportsConfig {
+    /// Run the configured reports on their schedules
+    pub enabled: bool,
+    
+    /// Directory `disk` outputs are written to
+    #[validate(length(min = 1))]
+    pub output_dir: String,
+    
+    /// Timeout for `webhook` deliveries (seconds)
+    #[validate(range(min = 1, max = 60))]
+    pub webhook_timeout_seconds: u64,
+    
+    /// How long a `cache` output is served by `GET /reports/{name}` (seconds)
+    #[validate(range(min = 60, max = 2592000))]
+    pub cache_ttl_seconds: u64,
+    
+    /// Configured reports
+    #[serde(default)]
+    #[validate(nested)]
+    pub reports: Vec<ReportConfig>,
+}
+
+/// Query a report runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportTemplate {
+    /// Supply and reserves of `currencies` from `getcurrency`
+    SupplySnapshot,
+    /// `getmempoolinfo`
+    MempoolStats,
+    /// A read-only `method` called with `params`
+    Rpc,
+}
+
+/// Where a report's results go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportOutput {
+    /// A JSON file per run under `output_dir`
+    Disk,
+    /// A JSON POST to `webhook_url`
+    Webhook,
+    /// The response cache, served by `GET /reports/{name}`
+    Cache,
+}
+
+/// One scheduled report
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_report"))]
+pub struct ReportConfig {
+    /// Report name, used in file names and `GET /reports/{name}`
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    
+    /// Cron expression (minute hour day-of-month month day-of-week), in UTC
+    pub schedule: String,
+    
+    /// Query the report runs
+    pub template: ReportTemplate,
+    
+    /// Currencies for `supply_snapshot`
+    #[serde(default)]
+    pub currencies: Vec<String>,
+    
+    /// Method for `rpc`
+    #[serde(default)]
+    pub method: Option<String>,
+    
+    /// Parameters for `rpc`
+    #[serde(default)]
+    pub params: Vec<serde_json::Value>,
+    
+    /// Where results go
+    #[serde(default = "default_report_outputs")]
+    pub outputs: Vec<ReportOutput>,
+    
+    /// Receives results when `outputs` includes `webhook`
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+fn default_report_outputs() -> Vec<ReportOutput> {
+    vec![ReportOutput::Cache]
+}
+
+/// A report needs a valid schedule, a file-safe name and what its template and outputs require
+fn validate_report(report: &ReportConfig) -> Result<(), validator::ValidationError> {
+    if report.schedule.parse::<crate::shared::cron::CronSchedule>().is_err() {
+        return Err(validator::ValidationError::new("report_invalid_schedule"));
+    }
+    if !report.name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')) {
+        return Err(validator::ValidationError::new("report_invalid_name"));
+    }
+    match report.template {
+        ReportTemplate::SupplySnapshot if report.currencies.is_empty() => {
+            return Err(validator::ValidationError::new("report_supply_snapshot_without_currencies"));
+        }
+        ReportTemplate::Rpc if report.method.is_none() => {
+            return Err(validator::ValidationError::new("report_rpc_without_method"));
+        }
+        _ => {}
+    }
+    let webhook = report.webhook_url.as_deref();
+    if report.outputs.contains(&ReportOutput::Webhook)
+        && !webhook.is_some_and(|url| url.starts_with("http://") || url.starts_with("https://"))
+    {
+        return Err(validator::ValidationError::new("report_webhook_without_url"));
+    }
+    Ok(())
+}
+
+/// One tenant sharing the deployment (`[tenants.<name>]`)
+///
+/// Requests are matched to a tenant by API key or by the audience of their
+/// JWT; a tenant's policy applies on top of the global one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_tenant"))]
+pub struct TenantConfig {
+    /// Hex SHA-256 hashes of API keys sent in `X-API-Key`
+    #[serde(default)]
+    pub api_key_hashes: Vec<String>,
+    
+    /// JWT audience (`aud`) of this tenant's tokens, signed with the global secret and issuer
+    #[serde(default)]
+    #[validate(length(min = 1, max = 128))]
+    pub jwt_audience: Option<String>,
+    
+    /// Only these methods may be called; all enabled methods when unset
+    #[serde(default)]
+    pub allowed_methods: Option<Vec<String>>,
+    
+    /// Budget per minute shared by every request of the tenant
+    #[serde(default)]
+    #[validate(range(min = 1, max = 1000000))]
+    pub requests_per_minute: Option<u32>,
+    
+    /// Requests allowed per UTC day; unlimited when unset
+    #[serde(default)]
+    #[validate(range(min = 1))]
+    pub daily_request_quota: Option<u64>,
+    
+    /// Value of the `tenant` label in metrics; the tenant name when unset
+    #[serde(default)]
+    pub metrics_label: Option<String>,
+    
+    /// `[upstreams]` entry serving this tenant; `[verus]` when unset
+    #[serde(default)]
+    pub upstream: Option<String>,
+}
+
+/// Tenant names and metrics labels end up in rate-limit keys and Prometheus labels
+pub fn is_valid_tenant_name(name: &str) -> bool {
+    (1..=64).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+}
+
+fn validate_tenant(tenant: &TenantConfig) -> Result<(), validator::ValidationError> {
+    if tenant.metrics_label.as_deref().is_some_and(|label| !is_valid_tenant_name(label)) {
+        return Err(validator::ValidationError::new("tenant_invalid_metrics_label"));
+    }
+    Ok(())
+}
+
+/// An additional daemon tenants can be pinned to (`[upstreams.<name>]`)
+///
+/// Timeouts, retries, the circuit breaker policy and DNS settings are taken
+/// from `[verus]`; each upstream gets its own breaker and connection pool.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_upstream_auth"))]
+pub struct UpstreamConfig {
+    /// RPC URL
+    #[validate(url)]
+    pub rpc_url: String,
+    
+    /// Authentication method for the daemon
+    #[serde(default)]
+    pub auth_method: DaemonAuthMethod,
+    
+    /// RPC username (password auth)
+    #[serde(default)]
+    pub rpc_user: String,
+    
+    /// RPC password (password auth)
+    #[serde(default)]
+    pub rpc_password: String,
+    
+    /// Path to the daemon cookie file (cookie auth)
+    #[serde(default)]
+    pub cookie_file: Option<String>,
+}
+
+impl UpstreamConfig {
+    /// `[verus]` settings pointed at this upstream
+    pub fn daemon_config(&self, verus: &VerusConfig) -> VerusConfig {
+        VerusConfig {
+            rpc_url: self.rpc_url.clone(),
+            auth_method: self.auth_method,
+            rpc_user: self.rpc_user.clone(),
+            rpc_password: self.rpc_password.clone(),
+            cookie_file: self.cookie_file.clone(),
+            ..verus.clone()
+        }
+    }
+}
+
+fn validate_upstream_auth(upstream: &UpstreamConfig) -> Result<(), validator::ValidationError> {
+    let valid = match upstream.auth_method {
+        DaemonAuthMethod::Password => !upstream.rpc_user.is_empty() && !upstream.rpc_password.is_empty(),
+        DaemonAuthMethod::Cookie => upstream.cookie_file.as_deref().is_some_and(|path| !path.is_empty()),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("daemon_auth_credentials_missing"))
+    }
+}
+
+/// Zero-downtime binary upgrades by listener handover
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpgradeConfig {
+    /// Bind TCP with `SO_REUSEPORT` and accept upgrades from `POST /admin/upgrade` or `SIGUSR2`
+    pub enabled: bool,
+    
+    /// Binary started as the new process; the running executable when unset
+    #[serde(default)]
+    pub binary_path: Option<String>,
+    
+    /// How long the new process may take to bind its listeners (seconds)
+    #[validate(range(min = 1, max = 600))]
+    pub ready_timeout_seconds: u64,
+    
+    /// How long the old process waits for open connections to finish (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub drain_timeout_seconds: u64,
+}
+
+/// Memory guardrails
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_memory_guard"))]
+pub struct MemoryGuardConfig {
+    /// Watch memory use and react to pressure
+    pub enabled: bool,
+    
+    /// Memory use that starts pressure handling (bytes)
+    #[validate(range(min = 1048576))]
+    pub soft_limit_bytes: u64,
+    
+    /// Memory use at which new RPC requests are refused (bytes)
+    pub hard_limit_bytes: u64,
+    
+    /// A level is left only once memory use falls below this fraction of its limit
+    #[validate(range(min = 0.1, max = 1.0))]
+    pub recovery_ratio: f64,
+    
+    /// How often memory use is re-measured (seconds)
+    #[validate(range(min = 1, max = 300))]
+    pub evaluation_interval_seconds: u64,
+    
+    /// Share of the in-memory response cache kept when the soft limit is crossed (percent)
+    #[validate(range(max = 100))]
+    pub cache_keep_percent: u8,
+    
+    /// Request bodies admitted at once above the soft limit (bytes)
+    #[validate(range(min = 1024))]
+    pub max_in_flight_bytes: usize,
+    
+    /// Retry-After sent with 503 responses (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub retry_after_seconds: u64,
+}
+
+fn validate_memory_guard(guard: &MemoryGuardConfig) -> Result<(), validator::ValidationError> {
+    if guard.hard_limit_bytes > guard.soft_limit_bytes {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("memory_guard_hard_limit_not_above_soft_limit"))
+    }
+}
+
+/// Heap profiling (requires the `heap-profiling` feature)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct HeapProfilingConfig {
+    /// Sample allocations from startup and serve `GET /debug/heap` to admins
+    pub enabled: bool,
+}
+
+/// Fixtures for `--mock-daemon` and the `capture-fixtures` command
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct MockDaemonConfig {
+    /// Directory of captured responses, one `<method>.json` per method
+    #[validate(length(min = 1))]
+    pub fixtures_dir: String,
+    
+    /// Calls `capture-fixtures` makes against the live daemon
+    #[validate(nested)]
+    pub capture: Vec<FixtureCallConfig>,
+}
+
+/// One daemon call whose response is saved as a fixture
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct FixtureCallConfig {
+    #[validate(length(min = 1))]
+    pub method: String,
+    
+    /// Positional parameters
+    #[serde(default)]
+    pub params: Vec<serde_json::Value>,
+}
+
+/// Operator access to the `/admin` and `/debug` endpoints
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct AdminConfig {
+    /// Operators allowed to call admin endpoints; with none, every admin request is refused
+    #[validate(nested)]
+    pub operators: Vec<AdminOperatorConfig>,
+}
+
+/// One operator and the keys it authenticates with
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AdminOperatorConfig {
+    /// Recorded as the actor of admin actions such as refund decisions
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    
+    /// Hex SHA-256 hashes of the operator's keys, sent as `Authorization: Bearer <key>`
+    pub key_hashes: Vec<String>,
+}
+
+/// Remembered parameter validation outcomes
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ValidationCacheConfig {
+    /// Reuse the outcome of an earlier request with the same method and parameter shape
+    pub enabled: bool,
+    
+    /// Outcomes kept before the cache starts over
+    #[validate(range(min = 16, max = 1000000))]
+    pub max_entries: usize,
+}
+
+/// HTML status page (requires the `status-page` feature)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct StatusPageConfig {
+    /// Serve `GET /status`
+    pub enabled: bool,
+    
+    /// Page heading and title
+    #[validate(length(min = 1, max = 100))]
+    pub title: String,
+    
+    /// Browser auto-refresh interval (seconds, 0 disables)
+    #[validate(range(max = 3600))]
+    pub refresh_seconds: u64,
+}
+
+/// Rolling health-check history behind `/health/history`
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct HealthHistoryConfig {
+    /// Probe the daemon, Redis and the mining pool in the background and serve `/health/history`
+    pub enabled: bool,
+    
+    /// Seconds between probes
+    #[validate(range(min = 1, max = 3600))]
+    pub check_interval_seconds: u64,
+    
+    /// Timeout for one probe (seconds)
+    #[validate(range(min = 1, max = 60))]
+    pub probe_timeout_seconds: u64,
+    
+    /// State transitions kept; uptime is computed from these, so older windows lose coverage once they roll off
+    #[validate(range(min = 10, max = 100000))]
+    pub max_transitions: usize,
+}
+
+/// Wire format for pushed metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsPushFormat {
+    /// `name:value|g` datagrams over UDP
+    #[default]
+    Statsd,
+    /// `name value timestamp` lines over TCP (Carbon plaintext)
+    Graphite,
+}
+
+/// Metrics persistence across restarts and optional push to statsd/graphite
+///
+/// Prometheus pull (`/metrics/prometheus`) is always served; both options here
+/// are off by default.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct MetricsPersistenceConfig {
+    /// Periodically write request counters to `snapshot_path` and restore them on start
+    pub snapshot_enabled: bool,
+    
+    /// Snapshot file (JSON)
+    #[validate(length(min = 1))]
+    pub snapshot_path: String,
+    
+    /// Seconds between snapshots
+    #[validate(range(min = 1, max = 86400))]
+    pub snapshot_interval_seconds: u64,
+    
+    /// Push counters to a statsd or graphite endpoint
+    pub push_enabled: bool,
+    
+    /// Push wire format
+    pub push_format: MetricsPushFormat,
+    
+    /// `host:port` of the statsd (UDP) or graphite (TCP) endpoint
+    #[validate(length(min = 1))]
+    pub push_address: String,
+    
+    /// Prefix for pushed metric names
+    pub push_prefix: String,
+    
+    /// Seconds between pushes
+    #[validate(range(min = 1, max = 3600))]
+    pub push_interval_seconds: u64,
+}
+
+/// Strict parsing of JSON-RPC request bodies
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct StrictJsonConfig {
+    /// Reject ambiguous bodies (unknown fields, duplicate keys, odd `id` types, oversized params)
+    pub enabled: bool,
+    
+    /// Maximum nesting depth of arrays and objects in the body
+    #[validate(range(min = 2, max = 128))]
+    pub max_depth: usize,
+    
+    /// Maximum elements in any array
+    #[validate(range(min = 1, max = 1000000))]
+    pub max_array_length: usize,
+    
+    /// Maximum keys in any object
+    #[validate(range(min = 4, max = 100000))]
+    pub max_object_keys: usize,
+}
+
+/// Values for the security headers added to API responses
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SecurityHeadersConfig {
+    /// `Content-Security-Policy` value (empty to omit)
+    pub content_security_policy: String,
+    
+    /// Send the policy as `Content-Security-Policy-Report-Only`
+    pub csp_report_only: bool,
+    
+    /// Appended to the policy as `report-uri`
+    #[validate(url)]
+    pub csp_report_uri: Option<String>,
+    
+    /// Send `Strict-Transport-Security` (only meaningful when clients reach the server over HTTPS)
+    pub hsts_enabled: bool,
+    
+    /// HSTS `max-age` (seconds)
+    #[validate(range(max = 63072000))]
+    pub hsts_max_age_seconds: u64,
+    
+    /// HSTS `includeSubDomains`
+    pub hsts_include_subdomains: bool,
+    
+    /// HSTS `preload` (requires `includeSubDomains` and a max-age of at least one year)
+    pub hsts_preload: bool,
+    
+    /// `X-Frame-Options`: `DENY`, `SAMEORIGIN` or empty to omit
+    pub x_frame_options: String,
+    
+    /// `Referrer-Policy` value (empty to omit)
+    pub referrer_policy: String,
+    
+    /// `Permissions-Policy` value (empty to omit)
+    pub permissions_policy: String,
+}
+
+/// How browser requests prove they are not cross-site forgeries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CsrfMode {
+    /// A non-empty custom header is required; cross-site pages cannot set it without a CORS preflight
+    #[default]
+    Header,
+    /// The header must echo the `SameSite=Strict` cookie handed out by `GET /csrf`
+    DoubleSubmit,
+}
+
+/// CSRF protection for browser-facing state-changing routes
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CsrfConfig {
+    /// Check unsafe requests (POST, PUT, PATCH, DELETE) under `protected_prefixes`
+    pub enabled: bool,
+    
+    /// Custom header or double-submit cookie
+    pub mode: CsrfMode,
+    
+    /// Header carrying the CSRF token
+    #[validate(length(min = 1))]
+    pub header_name: String,
+    
+    /// Cookie carrying the CSRF token (double-submit mode)
+    #[validate(length(min = 1))]
+    pub cookie_name: String,
+    
+    /// Mark the CSRF cookie `Secure` (disable only for plain-HTTP development)
+    pub cookie_secure: bool,
+    
+    /// Let requests without `Origin` or `Cookie` headers through (non-browser clients)
+    pub allow_without_origin: bool,
+    
+    /// When non-empty, a present `Origin` must match one of these (CORS wildcard syntax)
+    pub trusted_origins: Vec<String>,
+    
+    /// Path prefixes the check applies to
+    pub protected_prefixes: Vec<String>,
+}
+
+/// In-process CORS enforcement (for standalone deployments without a reverse proxy)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CorsPolicyConfig {
+    /// Answer preflights and add CORS headers here instead of at the reverse proxy
+    pub enabled: bool,
+    
+    /// Send `Access-Control-Allow-Credentials: true` (requires explicit origins)
+    pub allow_credentials: bool,
+    
+    /// Preflight cache lifetime (seconds)
+    #[validate(range(max = 86400))]
+    pub max_age_seconds: u64,
+    
+    /// Response headers readable by browser scripts
+    pub expose_headers: Vec<String>,
+    
+    /// Per-route overrides, matched by longest path prefix
+    pub routes: Vec<CorsRouteConfig>,
+}
+
+/// CORS override for requests under a path prefix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsRouteConfig {
+    /// Path prefix, e.g. `/api/` or `/track/tx`
+    pub path_prefix: String,
+    
+    /// Origins for this prefix (defaults to `security.cors_origins`)
+    #[serde(default)]
+    pub origins: Option<Vec<String>>,
+    
+    /// Methods for this prefix (defaults to `security.cors_methods`)
+    #[serde(default)]
+    pub methods: Option<Vec<String>>,
+    
+    /// Request headers for this prefix (defaults to `security.cors_headers`)
+    #[serde(default)]
+    pub headers: Option<Vec<String>>,
+}
+
+/// `testrawtransaction` dry-run configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct DryRunConfig {
+    /// Fees above this (native coin) are rejected unless `allowhighfees` is set
+    #[validate(range(min = 0.0))]
+    pub max_fee: f64,
+    
+    /// Fees below this (native coin) produce a warning
+    #[validate(range(min = 0.0))]
+    pub min_relay_fee: f64,
+    
+    /// Transparent inputs checked per transaction
+    #[validate(range(min = 1, max = 10000))]
+    pub max_inputs: usize,
+    
+    /// Concurrent `gettxout` lookups per dry run
+    #[validate(range(min = 1, max = 64))]
+    pub max_concurrency: usize,
+}
+
+/// Embedded address index configuration (requires the `indexer` feature)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct IndexerConfig {
+    /// Tail blocks and serve `/api/address/{addr}/txs` from the local index
+    pub enabled: bool,
+    
+    /// First height to index (defaults to the tip at first start)
+    pub start_height: Option<u64>,
+    
+    /// How often the chain tip is checked (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub poll_interval_seconds: u64,
+    
+    /// Blocks left unindexed below the tip so short reorgs never reach the index
+    #[validate(range(max = 100))]
+    pub confirmations: u64,
+    
+    /// Blocks indexed per poll while catching up
+    #[validate(range(min = 1, max = 10000))]
+    pub max_blocks_per_poll: u64,
+    
+    /// Directory for the index log
+    pub storage_path: String,
+    
+    /// Upper bound for `limit` on `/api/address/{addr}/txs`
+    #[validate(range(min = 1, max = 10000))]
+    pub max_page_size: usize,
+}
+
+/// Currency state history sampler configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CurrencyHistoryConfig {
+    /// Record currency state snapshots and serve `/api/currency/{id}/history`
+    pub enabled: bool,
+    
+    /// Currencies to sample (names or i-addresses)
+    pub currencies: Vec<String>,
+    
+    /// How often the chain tip is checked (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub poll_interval_seconds: u64,
+    
+    /// Record a snapshot every N blocks
+    #[validate(range(min = 1))]
+    pub block_interval: u64,
+    
+    /// Snapshots kept per currency; the oldest are dropped first
+    #[validate(range(min = 1, max = 10000000))]
+    pub max_points_per_currency: usize,
+    
+    /// Directory for snapshot files (memory only when unset)
+    pub storage_path: Option<String>,
+    
+    /// Maximum points returned per history request
+    #[validate(range(min = 1, max = 100000))]
+    pub max_points_per_request: usize,
+}
+
+/// Staking proof token issuance configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct StakeProofConfig {
+    /// Accept `StakeProof` token requests
+    pub enabled: bool,
+    
+    /// Minimum address balance required (VRSC)
+    #[validate(range(min = 0.0))]
+    pub min_balance: f64,
+    
+    /// Lifetime of an issued stake challenge (seconds)
+    #[validate(range(min = 30, max = 3600))]
+    pub challenge_ttl_seconds: u64,
+    
+    /// Lifetime of stake-validated tokens (seconds)
+    #[validate(range(min = 60, max = 86400))]
+    pub token_duration_seconds: u64,
+    
+    /// Rate limit multiplier for stake-validated tokens
+    #[validate(range(min = 1.0, max = 10.0))]
+    pub rate_limit_multiplier: f64,
+}
+
+/// Partner token issuance configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PartnersConfig {
+    /// Accept signed partner token requests
+    pub enabled: bool,
+    
+    /// Maximum age (or future skew) of a signed partner request (seconds)
+    #[validate(range(min = 1, max = 3600))]
+    pub max_clock_skew_seconds: u64,
+    
+    /// Configured partners
+    #[serde(default)]
+    pub partners: Vec<PartnerConfig>,
+}
+
+/// A single trusted partner
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PartnerConfig {
+    /// Partner identifier used in signed requests
+    #[validate(length(min = 1, max = 64))]
+    pub id: String,
+    
+    /// Display name
+    pub name: Option<String>,
+    
+    /// Hex-encoded ed25519 public key
+    #[validate(length(equal = 64))]
+    pub public_key: String,
+    
+    /// Permissions the partner may grant to its users
+    #[serde(default)]
+    pub allowed_permissions: Vec<String>,
+    
+    /// Rate limit multiplier applied to partner-issued tokens
+    #[serde(default = "default_partner_rate_multiplier")]
+    pub rate_multiplier: f64,
+    
+    /// Lifetime of partner-issued tokens (seconds)
+    #[serde(default = "default_partner_token_ttl_seconds")]
+    pub token_ttl_seconds: u64,
+}
+
+fn default_partner_rate_multiplier() -> f64 {
+    1.0
+}
+
+fn default_partner_token_ttl_seconds() -> u64 {
+    86400
+}
+
+/// Payment tier configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PaymentTierConfig {
+    #[validate(length(min = 1))]
+    pub id: String,
+    #[validate(range(min = 0.00000001))]
+    pub amount_vrsc: f64,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+    /// PBaaS currencies accepted in addition to the native currency
+    #[serde(default)]
+    pub accepted_currencies: Vec<String>,
+}
+
+/// Payments configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PaymentsAppConfig {
+    pub enabled: bool,
+    /// Allowed shielded address types: "orchard", "sapling"
+    pub address_types: Vec<String>,
+    /// Default address type
+    #[validate(length(min = 1))]
+    pub default_address_type: String,
+    /// Confirmations to issue provisional token
+    #[validate(range(min = 0, max = 10))]
+    pub min_confirmations: u32,
+    /// Quote/session TTL in minutes
+    #[validate(range(min = 1, max = 1440))]
+    pub session_ttl_minutes: u32,
+    /// Require viewing key presence to accept payments (view-only mode)
+    pub require_viewing_key: bool,
+    /// Optional list of viewing keys to import at startup
+    pub viewing_keys: Vec<String>,
+    /// Rescan mode for viewing key import: "yes", "no", or "whenkeyisnew"
+    #[validate(length(min = 2))]
+    pub viewing_key_rescan: String,
+    /// Configured payment tiers
+    pub tiers: Vec<PaymentTierConfig>,
+    /// Underpayment policy: "reject", "hold_open" or "reduced_tier"
+    #[serde(default = "default_underpayment_policy")]
+    pub underpayment_policy: String,
+    /// Shortfall in VRSC still accepted as full payment (absorbs fee rounding)
+    #[serde(default)]
+    #[validate(range(min = 0.0))]
+    pub underpayment_tolerance_vrsc: f64,
+    /// Overpayment policy: "ignore", "credit" or "refund"
+    #[serde(default = "default_overpayment_policy")]
+    pub overpayment_policy: String,
+    /// Native chain currency tiers are priced in ("VRSC", or "VRSCTEST" on testnet)
+    #[serde(default = "default_native_currency")]
+    #[validate(length(min = 1))]
+    pub native_currency: String,
+    /// Minutes a PBaaS currency quote keeps its exchange rate
+    #[serde(default = "default_exchange_lock_minutes")]
+    #[validate(range(min = 1, max = 1440))]
+    pub exchange_lock_minutes: u32,
+    /// Hex ed25519 seed for signing receipts (derived from the JWT secret when unset)
+    #[serde(default)]
+    pub receipt_signing_key: Option<String>,
+    /// Days payment sessions are kept for history and receipts (Redis-backed stores)
+    #[serde(default = "default_history_retention_days")]
+    #[validate(range(min = 2, max = 3650))]
+    pub history_retention_days: u32,
+    /// Upstream policy for payment-critical daemon calls
+    #[serde(default)]
+    #[validate(nested)]
+    pub rpc: PaymentsRpcConfig,
+    /// Pre-generated shielded addresses for new quotes
+    #[serde(default)]
+    #[validate(nested)]
+    pub address_pool: AddressPoolConfig,
+    /// Refunds for late, rejected and (with the `refund` policy) excess payments
+    #[serde(default)]
+    #[validate(nested)]
+    pub refunds: RefundsConfig,
+    /// Provisional tokens for unconfirmed payments on small tiers
+    #[serde(default)]
+    #[validate(nested)]
+    pub zero_conf: ZeroConfConfig,
+}
+
+/// Low-permission tokens issued before `min_confirmations`
+///
+/// The token is upgraded once the payment reaches `min_confirmations` (the
+/// regular provisional token is pushed to the session webhook and returned by
+/// `POST /payments/token/refresh`) and revoked if the transaction is rolled back.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ZeroConfConfig {
+    /// Issue tokens for payments still in the mempool
+    pub enabled: bool,
+    /// Only tiers priced at or below this amount qualify
+    #[validate(range(min = 0.0))]
+    pub max_amount_vrsc: f64,
+    /// Permissions kept from the tier (the token gets their intersection)
+    pub permissions: Vec<String>,
+    /// Lifetime of a zero-conf token in seconds
+    #[validate(range(min = 60, max = 86400))]
+    pub token_ttl_seconds: u64,
+    /// Accept a per-session `webhook_url` that receives upgraded tokens
+    pub allow_webhooks: bool,
+    /// Timeout for webhook deliveries in seconds
+    #[validate(range(min = 1, max = 60))]
+    pub webhook_timeout_seconds: u64,
+}
+
+impl Default for ZeroConfConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_amount_vrsc: 1.0,
+            permissions: vec!["read".to_string()],
+            token_ttl_seconds: 600,
+            allow_webhooks: false,
+            webhook_timeout_seconds: 5,
+        }
+    }
+}
+
+/// Who approves refunds before they are sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefundApproval {
+    /// An operator approves each refund through `POST /admin/refunds/{payment_id}`
+    #[default]
+    Manual,
+    /// Refunds are sent as soon as they are opened
+    Auto,
+}
+
+/// Refund subsystem
+///
+/// Refunds spend from the session address, so they need a hot wallet holding
+/// the spending keys; they are never sent in `require_viewing_key` mode.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct RefundsConfig {
+    /// Open refunds for sessions with a refund address
+    pub enabled: bool,
+    /// "manual" (operator approval) or "auto"
+    pub approval: RefundApproval,
+    /// Network fee deducted from each refund
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub fee_vrsc: f64,
+    /// Refunds worth less than this after the fee are not opened
+    #[validate(range(min = 0.0))]
+    pub min_refund_vrsc: f64,
+}
+
+impl Default for RefundsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            approval: RefundApproval::Manual,
+            fee_vrsc: 0.0001,
+            min_refund_vrsc: 0.001,
+        }
+    }
+}
+
+/// Pool of unused shielded addresses, refilled in the background
+///
+/// Quotes take an address from the pool instead of calling `z_getnewaddress`
+/// inline, and fall back to the inline call when the pool is empty.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AddressPoolConfig {
+    /// Enable the pool
+    pub enabled: bool,
+    /// Addresses kept ready per allowed address type
+    #[validate(range(min = 1, max = 10000))]
+    pub size_per_type: u32,
+    /// Seconds between refills
+    #[validate(range(min = 1, max = 3600))]
+    pub refill_interval_seconds: u64,
+}
+
+impl Default for AddressPoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            size_per_type: 20,
+            refill_interval_seconds: 30,
+        }
+    }
+}
+
+/// Timeout, retries and circuit breaker for payment-critical daemon calls
+///
+/// These calls go through their own adapter, isolated from general traffic.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PaymentsRpcConfig {
+    /// Methods routed through the payments adapter when `PaymentsService` calls them
+    pub methods: Vec<String>,
+    /// Per-call timeout in seconds
+    #[validate(range(min = 1, max = 300))]
+    pub timeout_seconds: u64,
+    /// Retry attempts after the first
+    #[validate(range(min = 0, max = 10))]
+    pub max_retries: u32,
+    /// Circuit breaker, independent of `[verus.circuit_breaker]`
+    #[validate(nested)]
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+impl Default for PaymentsRpcConfig {
+    fn default() -> Self {
+        Self {
+            methods: ["z_getnewaddress", "sendrawtransaction", "z_listreceivedbyaddress", "z_viewtransaction", "getrawtransaction"]
+                .iter()
+                .map(|method| method.to_string())
+                .collect(),
+            timeout_seconds: 10,
+            max_retries: 2,
+            circuit_breaker: CircuitBreakerConfig {
+                failure_threshold: 3,
+                recovery_timeout_seconds: 15,
+                half_open_max_requests: 1,
+            },
+        }
+    }
+}
+
+fn default_underpayment_policy() -> String {
+    "reject".to_string()
+}
+
+fn default_overpayment_policy() -> String {
+    "ignore".to_string()
+}
+
+fn default_native_currency() -> String {
+    "VRSC".to_string()
+}
+
+fn default_exchange_lock_minutes() -> u32 {
+    10
+}
+
+fn default_history_retention_days() -> u32 {
+    90
+}
+
+/// Application configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppConfig {
+    /// Verus daemon configuration
+    pub verus: VerusConfig,
+    
+    /// Server configuration
+    pub server: ServerConfig,
+    
+    /// Security configuration
+    pub security: SecurityConfig,
+    
+    /// Rate limiting configuration
+    pub rate_limit: RateLimitConfig,
+    
+    /// Logging configuration
+    pub logging: LoggingConfig,
+    
+    /// Cache configuration
+    pub cache: CacheConfig,
+    /// Payments configuration
+    pub payments: PaymentsAppConfig,
+    
+    /// Error localization configuration
+    #[serde(default)]
+    pub localization: LocalizationConfig,
+    
+    /// Mempool monitoring configuration
+    #[serde(default)]
+    pub mempool: MempoolConfig,
+    
+    /// Block explorer aggregation configuration
+    #[serde(default)]
+    pub explorer: ExplorerConfig,
+    
+    /// Slow upstream query log configuration
+    #[serde(default)]
+    pub slow_query_log: SlowQueryConfig,
+    
+    /// Upstream request scheduler configuration
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    
+    /// Token revocation store configuration
+    #[serde(default)]
+    pub revocation: RevocationConfig,
+    
+    /// Session-bound token configuration
+    #[serde(default)]
+    pub session: SessionConfig,
+    
+    /// Partner token issuance configuration
+    #[serde(default)]
+    pub partners: PartnersConfig,
+    
+    /// Stratum-lite PoW endpoint configuration
+    #[serde(default)]
+    pub stratum: StratumConfig,
+    
+    /// Staking proof token issuance configuration
+    #[serde(default)]
+    pub stake_proof: StakeProofConfig,
+    
+    /// Adaptive load-shedding configuration
+    #[serde(default)]
+    pub load_shedding: LoadSheddingConfig,
+    
+    /// Currency state history sampler configuration
+    #[serde(default)]
+    pub currency_history: CurrencyHistoryConfig,
+    
+    /// Embedded address index
+    #[serde(default)]
+    pub indexer: IndexerConfig,
+    
+    /// Reorg detection and chain event delivery
+    #[serde(default)]
+    pub chain_events: ChainEventsConfig,
+    
+    /// Transaction confirmation tracking
+    #[serde(default)]
+    pub tx_tracking: TxTrackingConfig,
+    
+    /// `testrawtransaction` dry-run settings
+    #[serde(default)]
+    pub dry_run: DryRunConfig,
+    
+    /// In-process CORS enforcement
+    #[serde(default)]
+    pub cors: CorsPolicyConfig,
+    
+    /// CSRF protection for browser-facing routes
+    #[serde(default)]
+    pub csrf: CsrfConfig,
+    
+    /// Security header values
+    #[serde(default)]
+    pub security_headers: SecurityHeadersConfig,
+    
+    /// Strict JSON-RPC body parsing
+    #[serde(default)]
+    pub strict_json: StrictJsonConfig,
+    
+    /// Metrics snapshots and statsd/graphite push
+    #[serde(default)]
+    pub metrics_persistence: MetricsPersistenceConfig,
+    
+    /// Rolling health-check history
+    #[serde(default)]
+    pub health_history: HealthHistoryConfig,
+    
+    /// HTML status page
+    #[serde(default)]
+    pub status_page: StatusPageConfig,
+    
+    /// Shared state between replicas
+    #[serde(default)]
+    pub cluster: ClusterConfig,
+    
+    /// Read-only maintenance mode
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    
+    /// Per-client policy overrides
+    #[serde(default)]
+    pub client_profiles: ClientProfilesConfig,
+    
+    /// External review of token issuance
+    #[serde(default)]
+    #[validate(nested)]
+    pub issuance_webhook: IssuanceWebhookConfig,
+    
+    /// systemd notify and watchdog integration
+    #[serde(default)]
+    pub systemd: SystemdConfig,
+    
+    /// Per-method parameter rewriting
+    #[serde(default)]
+    pub rewrite: RewriteConfig,
+    
+    /// WASM response plugins
+    #[serde(default)]
+    pub wasm_plugins: WasmPluginsConfig,
+    
+    /// Rhai policy hooks
+    #[serde(default)]
+    pub scripting: ScriptingConfig,
+    
+    /// Daemon record-and-replay
+    #[serde(default)]
+    pub recording: RecordingConfig,
+    
+    /// Proxy metadata attached to daemon calls
+    #[serde(default)]
+    pub upstream_context: UpstreamContextConfig,
+    
+    /// Daemon capability detection at startup
+    #[serde(default)]
+    pub capabilities: CapabilitiesConfig,
+    
+    /// Daemon method discovery at startup
+    #[serde(default)]
+    pub method_discovery: MethodDiscoveryConfig,
+    
+    /// Background cache warming
+    #[serde(default)]
+    pub cache_warmer: CacheWarmerConfig,
+    
+    /// On-disk cache tier for immutable responses
+    #[serde(default)]
+    pub disk_cache: DiskCacheConfig,
+    
+    /// Negative cache for unknown txids and block hashes
+    #[serde(default)]
+    pub negative_cache: NegativeCacheConfig,
+    
+    /// Request complexity budgets
+    #[serde(default)]
+    pub complexity: ComplexityConfig,
+    
+    /// Waiting for the daemon at startup
+    #[serde(default)]
+    pub daemon_wait: DaemonWaitConfig,
+    
+    /// Alerts on calls to sensitive methods
+    #[serde(default)]
+    pub sensitive_method_alerts: SensitiveMethodAlertsConfig,
+    
+    /// Temporary bans after repeated offenses
+    #[serde(default)]
+    pub auto_ban: AutoBanConfig,
+    
+    /// Ed25519-signed JSON-RPC responses
+    #[serde(default)]
+    pub response_signing: ResponseSigningConfig,
+    
+    /// Canonical JSON serialization per route
+    #[serde(default)]
+    pub canonical_json: CanonicalJsonConfig,
+    
+    /// Currency and identity name resolution
+    #[serde(default)]
+    pub name_resolver: NameResolverConfig,
+    
+    /// Batch conversion estimates
+    #[serde(default)]
+    pub conversion_estimates: ConversionEstimatesConfig,
+    
+    /// Marketplace offer aggregation
+    #[serde(default)]
+    pub marketplace: MarketplaceConfig,
+    
+    /// Shielded note commitment tree states
+    #[serde(default)]
+    pub shielded_tree: ShieldedTreeConfig,
+    
+    /// Watch-only portfolio balances
+    #[serde(default)]
+    pub portfolio: PortfolioConfig,
+    
+    /// Asynchronous jobs for long-running aggregate queries
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    
+    /// Scheduled reports
+    #[serde(default)]
+    pub reports: ReportsConfig,
+    
+    /// Tenants served by this deployment, keyed by name
+    #[serde(default)]
+    pub tenants: std::collections::BTreeMap<String, TenantConfig>,
+    
+    /// Additional daemons tenants can be routed to, keyed by name
+    #[serde(default)]
+    pub upstreams: std::collections::BTreeMap<String, UpstreamConfig>,
+    
+    /// Zero-downtime binary upgrades
+    #[serde(default)]
+    pub upgrade: UpgradeConfig,
+    
+    /// Memory guardrails
+    #[serde(default)]
+    pub memory_guard: MemoryGuardConfig,
+    
+    /// Heap profiling
+    #[serde(default)]
+    pub heap_profiling: HeapProfilingConfig,
+    
+    /// Mock daemon fixtures
+    #[serde(default)]
+    pub mock_daemon: MockDaemonConfig,
+    
+    /// Parameter validation outcome cache
+    #[serde(default)]
+    pub validation_cache: ValidationCacheConfig,
+    
+    /// Admin endpoint operators
+    #[serde(default)]
+    pub admin: AdminConfig,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            verus: VerusConfig {
+                rpc_url: "http://127.0.0.1:27486".to_string(),
+                auth_method: DaemonAuthMethod::Password,
+                rpc_user: "rpcuser".to_string(),
+                rpc_password: "rpcpassword".to_string(),
+                cookie_file: None,
+                timeout_seconds: 30,
+                max_retries: 3,
+                circuit_breaker: Some(CircuitBreakerConfig::default()),
+                dns: UpstreamDnsConfig::default(),
+                pipelining: UpstreamPipeliningConfig::default(),
+            },
+            server: ServerConfig {
+                bind_address: "127.0.0.1".parse().unwrap(),
+                port: 8080,
+                max_request_size: 1024 * 1024, // 1MB
+                worker_threads: 0, // Auto-detect
+                max_header_bytes: default_max_header_bytes(),
+                max_headers: default_max_headers(),
+                header_read_timeout_seconds: default_header_read_timeout_seconds(),
+                read_timeout_seconds: default_read_timeout_seconds(),
+                write_timeout_seconds: default_write_timeout_seconds(),
+                idle_timeout_seconds: default_idle_timeout_seconds(),
+                http2_enabled: default_http2_enabled(),
+                http2_max_concurrent_streams: default_http2_max_concurrent_streams(),
+                unix_socket_path: None,
+                unix_socket_mode: default_unix_socket_mode(),
+                max_blocking_threads: default_max_blocking_threads(),
+                cpu_pool_threads: 0, // One per core
+                cpu_offload_min_bytes: default_cpu_offload_min_bytes(),
+            },
+            security: SecurityConfig {
+                cors_origins: vec!["*".to_string()],
+                cors_methods: vec!["GET".to_string(), "POST".to_string(), "OPTIONS".to_string()],
+                cors_headers: vec![
+                    "Content-Type".to_string(),
+                    "Authorization".to_string(),
+                    "Accept".to_string(),
+                ],
+                enable_request_logging: true,
+                enable_security_headers: true,
+                trusted_proxy_headers: vec!["X-Forwarded-For".to_string()],
+                enable_custom_headers: false,
+                custom_security_header: None,
+                method_rate_limits: std::collections::HashMap::new(),
+                jwt: JwtConfig {
+                    secret_key: "your-super-secret-jwt-key-that-should-be-32-chars-min".to_string(),
+                    expiration_seconds: 3600, // 1 hour
+                    issuer: "verus-rpc-server".to_string(),
+                    audience: "verus-clients".to_string(),
+                    algorithm: default_jwt_algorithm(),
+                },
+                pow: None,
+                mining_pool: None,
+                development_mode: false,
+            },
+            rate_limit: RateLimitConfig {
+                requests_per_minute: 1000,
+                burst_size: 100,
+                enabled: true,
+                costs: MethodCostConfig::default(),
+                max_tracked_keys: default_max_tracked_keys(),
+                exemptions: RateLimitExemptionConfig::default(),
+            },
+            logging: LoggingConfig {
+                level: "info".to_string(),
+                format: "json".to_string(),
+                structured: true,
+                sampling: RequestLogSamplingConfig::default(),
+            },
+            cache: CacheConfig::default(),
+            payments: PaymentsAppConfig::default(),
+            localization: LocalizationConfig::default(),
+            mempool: MempoolConfig::default(),
+            explorer: ExplorerConfig::default(),
+            slow_query_log: SlowQueryConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            revocation: RevocationConfig::default(),
+            session: SessionConfig::default(),
+            partners: PartnersConfig::default(),
+            stratum: StratumConfig::default(),
+            stake_proof: StakeProofConfig::default(),
+            load_shedding: LoadSheddingConfig::default(),
+            currency_history: CurrencyHistoryConfig::default(),
+            indexer: IndexerConfig::default(),
+            chain_events: ChainEventsConfig::default(),
+            tx_tracking: TxTrackingConfig::default(),
+            dry_run: DryRunConfig::default(),
+            cors: CorsPolicyConfig::default(),
+            csrf: CsrfConfig::default(),
+            security_headers: SecurityHeadersConfig::default(),
+            strict_json: StrictJsonConfig::default(),
+            metrics_persistence: MetricsPersistenceConfig::default(),
+            health_history: HealthHistoryConfig::default(),
+            status_page: StatusPageConfig::default(),
+            cluster: ClusterConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            client_profiles: ClientProfilesConfig::default(),
+            issuance_webhook: IssuanceWebhookConfig::default(),
+            systemd: SystemdConfig::default(),
+            rewrite: RewriteConfig::default(),
+            wasm_plugins: WasmPluginsConfig::default(),
+            scripting: ScriptingConfig::default(),
+            recording: RecordingConfig::default(),
+            upstream_context: UpstreamContextConfig::default(),
+            capabilities: CapabilitiesConfig::default(),
+            method_discovery: MethodDiscoveryConfig::default(),
+            cache_warmer: CacheWarmerConfig::default(),
+            disk_cache: DiskCacheConfig::default(),
+            negative_cache: NegativeCacheConfig::default(),
+            complexity: ComplexityConfig::default(),
+            daemon_wait: DaemonWaitConfig::default(),
+            sensitive_method_alerts: SensitiveMethodAlertsConfig::default(),
+            auto_ban: AutoBanConfig::default(),
+            response_signing: ResponseSigningConfig::default(),
+            canonical_json: CanonicalJsonConfig::default(),
+            name_resolver: NameResolverConfig::default(),
+            conversion_estimates: ConversionEstimatesConfig::default(),
+            marketplace: MarketplaceConfig::default(),
+            shielded_tree: ShieldedTreeConfig::default(),
+            portfolio: PortfolioConfig::default(),
+            jobs: JobsConfig::default(),
+            reports: ReportsConfig::default(),
+            tenants: std::collections::BTreeMap::new(),
+            upstreams: std::collections::BTreeMap::new(),
+            upgrade: UpgradeConfig::default(),
+            memory_guard: MemoryGuardConfig::default(),
+            heap_profiling: HeapProfilingConfig::default(),
+            mock_daemon: MockDaemonConfig::default(),
+            validation_cache: ValidationCacheConfig::default(),
+            admin: AdminConfig::default(),
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            default_ttl: 300, // 5 minutes
+            max_size: 100 * 1024 * 1024, // 100MB
+            redis_timeout_ms: default_redis_timeout_ms(),
+            redis_retry_max_seconds: default_redis_retry_max_seconds(),
+        }
+    }
+}
+
+impl Default for LocalizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_locale: "en".to_string(),
+            catalog_dir: "locales".to_string(),
+        }
+    }
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_interval_seconds: 15,
+        }
+    }
+}
+
+impl Default for ExplorerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 8,
+            tx_cache_entries: 10000,
+            max_block_transactions: 1000,
+            max_balance_addresses: default_max_balance_addresses(),
+            balance_chunk_size: default_balance_chunk_size(),
+            balance_cache_seconds: default_balance_cache_seconds(),
+            balance_cache_entries: default_balance_cache_entries(),
+        }
+    }
+}
+
+impl Default for SlowQueryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_ms: 1000,
+            max_entries: 200,
+        }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_concurrent_upstream: 32,
+            high_priority_burst: 4,
+            max_queue_wait_ms: 10000,
+        }
+    }
+}
+
+impl Default for RevocationConfig {
+    fn default() -> Self {
+        Self {
+            backend: RevocationBackend::Auto,
+            redis_url: None,
+            cleanup_interval_seconds: 300,
+            user_revocation_ttl_seconds: 7 * 24 * 3600,
+            max_listed: 1000,
+        }
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_timeout_seconds: 1800,
+            max_lifetime_seconds: 86400,
+        }
+    }
+}
+
+impl Default for StratumConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_solve_seconds: 10,
+            idle_timeout_seconds: 300,
+            max_connections: 1000,
+        }
+    }
+}
+
+impl Default for LoadSheddingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            p95_latency_ms: 2000,
+            error_rate: 0.2,
+            recovery_ratio: 0.5,
+            min_samples: 20,
+            evaluation_interval_seconds: 5,
+            retry_after_seconds: 10,
+            high_cost_methods: vec![
+                "getblocktemplate".to_string(),
+                "getaddressdeltas".to_string(),
+                "getaddresstxids".to_string(),
+                "getaddressutxos".to_string(),
+                "getaddressmempool".to_string(),
+                "getrawmempool".to_string(),
+                "listcurrencies".to_string(),
+                "getcurrencyconverters".to_string(),
+            ],
+        }
+    }
+}
+
+impl Default for CurrencyHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            currencies: Vec::new(),
+            poll_interval_seconds: 30,
+            block_interval: 1,
+            max_points_per_currency: 100000,
+            storage_path: Some("data/currency_history".to_string()),
+            max_points_per_request: 5000,
+        }
+    }
+}
+
+impl Default for ChainEventsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_seconds: 5,
+            track_depth: 100,
+            webhook_urls: Vec::new(),
+            webhook_timeout_seconds: 5,
+            max_subscribers: 100,
+        }
+    }
+}
+
+impl Default for TxTrackingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_seconds: 10,
+            max_tracked: 10000,
+            max_tracked_per_client: 100,
+            max_target_confirmations: 100,
+            eviction_grace_seconds: 120,
+            max_tracking_seconds: 86400,
+            retention_seconds: 3600,
+            allow_client_webhooks: false,
+            webhook_timeout_seconds: 5,
+        }
+    }
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            redis_url: None,
+            key_prefix: "verus_rpc".to_string(),
+            node_id: None,
+            lock_ttl_ms: 5000,
+            coalesce_wait_ms: 2000,
+            sticky_routing: false,
+            advertise_url: None,
+            forward_secret: None,
+            heartbeat_interval_seconds: default_heartbeat_interval_seconds(),
+            forward_timeout_ms: default_forward_timeout_ms(),
+            virtual_nodes: default_virtual_nodes(),
+        }
+    }
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            read_only: false,
+            message: "Write methods are disabled during maintenance".to_string(),
+        }
+    }
+}
+
+impl Default for IssuanceWebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: None,
+            secret: None,
+            sources: default_issuance_webhook_sources(),
+            timeout_ms: default_issuance_webhook_timeout_ms(),
+            fail_open: false,
+        }
+    }
+}
+
+impl Default for SystemdConfig {
+    fn default() -> Self {
+        Self {
+            watchdog_enabled: false,
+            watchdog_require_daemon: false,
+            health_check_timeout_seconds: 5,
+        }
+    }
+}
+
+impl Default for WasmPluginsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fuel_per_call: 50_000_000,
+            max_memory_bytes: 16 * 1024 * 1024,
+            fail_open: false,
+            modules: Vec::new(),
+        }
+    }
+}
+
+impl Default for ScriptingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "scripts/policy.rhai".to_string(),
+            max_operations: 100_000,
+            fail_open: false,
+        }
+    }
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            mode: RecordingMode::Off,
+            path: "recordings/daemon.jsonl".to_string(),
+            scrub_keys: [
+                "privkey", "privatekey", "wif", "seed", "spendingkey", "viewingkey",
+                "extendedspendingkey", "extendedviewingkey", "passphrase", "password",
+            ]
+            .iter()
+            .map(|key| key.to_string())
+            .collect(),
+        }
+    }
+}
+
+impl Default for UpstreamContextConfig {
+    fn default() -> Self {
+        Self {
+            mode: UpstreamContextMode::Off,
+            header: "X-Verus-Proxy-Context".to_string(),
+            id_prefix: "vrpc".to_string(),
+            client_id_salt: String::new(),
+        }
+    }
+}
+
+impl Default for CapabilitiesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            disable_unsupported: true,
+            min_daemon_version: None,
+            probe_blocks: 10,
+            timeout_seconds: 15,
+        }
+    }
+}
+
+impl Default for MethodDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            auto_register: false,
+            max_params: 4,
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl Default for CacheWarmerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_seconds: 5,
+            methods: vec![
+                "getinfo".to_string(),
+                "getblockcount".to_string(),
+                "getdifficulty".to_string(),
+                "getmempoolinfo".to_string(),
+            ],
+            tip_block: true,
+            currencies: Vec::new(),
+            max_concurrency: 4,
+        }
+    }
+}
+
+impl Default for DiskCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "cache/immutable".to_string(),
+            min_confirmations: 100,
+            max_size_mb: 10240,
+            tip_poll_seconds: 10,
+        }
+    }
+}
+
+impl Default for NegativeCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            expected_items: 100000,
+            false_positive_rate: 0.001,
+            tip_poll_seconds: 5,
+        }
+    }
+}
+
+impl Default for ComplexityConfig {
+    fn default() -> Self {
+        let method_weights = [
+            ("getblocktemplate", 50),
+            ("getaddressdeltas", 2),
+            ("getaddresstxids", 2),
+            ("getaddressutxos", 2),
+        ]
+        .into_iter()
+        .map(|(method, weight)| (method.to_string(), weight))
+        .collect();
+        Self {
+            enabled: false,
+            max_score: 1000,
+            deprioritize_score: 100,
+            blocks_per_unit: 1000,
+            method_weights,
+        }
+    }
+}
+
+impl Default for DaemonWaitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_wait_seconds: 300,
+            retry_max_seconds: 10,
+        }
+    }
+}
+
+impl Default for SensitiveMethodAlertsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            methods: default_sensitive_methods(),
+            webhook_url: None,
+            webhook_secret: None,
+            webhook_timeout_ms: default_sensitive_alert_timeout_ms(),
+        }
+    }
+}
+
+impl Default for AutoBanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rate_limit_threshold: 20,
+            validation_failure_threshold: 50,
+            window_seconds: 300,
+            ban_seconds: 900,
+            max_tracked_clients: 100_000,
+        }
+    }
+}
+
+impl Default for ResponseSigningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            signing_key: None,
+            methods: Vec::new(),
+            allow_header: true,
+        }
+    }
+}
+
+impl Default for CanonicalJsonConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            routes: Vec::new(),
+        }
+    }
+}
+
+impl Default for NameResolverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cache_entries: 10000,
+            cache_seconds: 3600,
+        }
+    }
+}
+
+impl Default for ConversionEstimatesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_conversions: 50,
+            max_attempts: 3,
+        }
+    }
+}
+
+impl Default for MarketplaceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cache_seconds: 15,
+            cache_entries: 1000,
+            max_page_size: 100,
+        }
+    }
+}
+
+impl Default for ShieldedTreeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            immutable_confirmations: 100,
+            cache_entries: 100000,
+            max_page_size: 100,
+        }
+    }
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_addresses: 100,
+            cache_seconds: 30,
+            cache_entries: 1000,
+        }
+    }
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_jobs_per_client: 2,
+            max_jobs: 100,
+            timeout_seconds: 300,
+            result_ttl_seconds: 600,
+        }
+    }
+}
+
+impl Default for ReportsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            output_dir: "reports".to_string(),
+            webhook_timeout_seconds: 10,
+            cache_ttl_seconds: 86400,
+            reports: Vec::new(),
+        }
+    }
+}
+
+impl Default for UpgradeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            binary_path: None,
+            ready_timeout_seconds: 30,
+            drain_timeout_seconds: 30,
+        }
+    }
+}
+
+impl Default for MemoryGuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            soft_limit_bytes: 512 * 1024 * 1024,
+            hard_limit_bytes: 768 * 1024 * 1024,
+            recovery_ratio: 0.9,
+            evaluation_interval_seconds: 5,
+            cache_keep_percent: 50,
+            max_in_flight_bytes: 16 * 1024 * 1024,
+            retry_after_seconds: 5,
+        }
+    }
+}
+
+impl Default for MockDaemonConfig {
+    fn default() -> Self {
+        let methods = [
+            "getinfo",
+            "getblockchaininfo",
+            "getnetworkinfo",
+            "getmininginfo",
+            "getmempoolinfo",
+            "getblockcount",
+            "getbestblockhash",
+            "getdifficulty",
+            "getchaintips",
+            "getrawmempool",
+        ];
+        Self {
+            fixtures_dir: "fixtures/mock-daemon".to_string(),
+            capture: methods
+                .into_iter()
+                .map(|method| FixtureCallConfig { method: method.to_string(), params: Vec::new() })
+                .collect(),
+        }
+    }
+}
+
+impl Default for ValidationCacheConfig {
+    fn default() -> Self {
+        Self { enabled: true, max_entries: 4096 }
+    }
+}
+
+impl Default for StatusPageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            title: "Verus RPC Server".to_string(),
+            refresh_seconds: 30,
+        }
+    }
+}
+
+impl Default for HealthHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_seconds: 30,
+            probe_timeout_seconds: 5,
+            max_transitions: 1000,
+        }
+    }
+}
+
+impl Default for MetricsPersistenceConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_enabled: false,
+            snapshot_path: "data/metrics_snapshot.json".to_string(),
+            snapshot_interval_seconds: 60,
+            push_enabled: false,
+            push_format: MetricsPushFormat::Statsd,
+            push_address: "127.0.0.1:8125".to_string(),
+            push_prefix: "verus_rpc".to_string(),
+            push_interval_seconds: 10,
+        }
+    }
+}
+
+impl Default for StrictJsonConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_depth: 32,
+            max_array_length: 10000,
+            max_object_keys: 1000,
+        }
+    }
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            content_security_policy: "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data: https:; font-src 'self' https:; connect-src 'self'; frame-ancestors 'none';".to_string(),
+            csp_report_only: false,
+            csp_report_uri: None,
+            hsts_enabled: false,
+            hsts_max_age_seconds: 31536000,
+            hsts_include_subdomains: true,
+            hsts_preload: false,
+            x_frame_options: "DENY".to_string(),
+            referrer_policy: "strict-origin-when-cross-origin".to_string(),
+            permissions_policy: "geolocation=(), microphone=(), camera=(), payment=(), usb=(), magnetometer=(), gyroscope=(), accelerometer=()".to_string(),
+        }
+    }
+}
+
+impl Default for CsrfConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: CsrfMode::Header,
+            header_name: "X-CSRF-Token".to_string(),
+            cookie_name: "csrf_token".to_string(),
+            cookie_secure: true,
+            allow_without_origin: true,
+            trusted_origins: Vec::new(),
+            protected_prefixes: [
+                "/payments/",
+                "/track/",
+                "/issue",
+                "/session",
+                "/logout",
+                "/pow/challenge",
+                "/stake/challenge",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        }
+    }
+}
+
+impl Default for CorsPolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow_credentials: false,
+            max_age_seconds: 3600,
+            expose_headers: Vec::new(),
+            routes: Vec::new(),
+        }
+    }
+}
+
+impl Default for DryRunConfig {
+    fn default() -> Self {
+        Self {
+            max_fee: 0.1,
+            min_relay_fee: 0.0001,
+            max_inputs: 500,
+            max_concurrency: 8,
+        }
+    }
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start_height: None,
+            poll_interval_seconds: 5,
+            confirmations: 3,
+            max_blocks_per_poll: 200,
+            storage_path: "data/indexer".to_string(),
+            max_page_size: 500,
+        }
+    }
+}
+
+impl Default for StakeProofConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_balance: 1000.0,
+            challenge_ttl_seconds: 300,
+            token_duration_seconds: 86400,
+            rate_limit_multiplier: 2.0,
+        }
+    }
+}
+
+impl Default for PartnersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_clock_skew_seconds: 300,
+            partners: Vec::new(),
+        }
+    }
 }
 
 impl Default for PaymentsAppConfig {
@@ -426,14 +3744,27 @@ impl Default for PaymentsAppConfig {
                     amount_vrsc: 1.0,
                     description: Some("Basic access".to_string()),
                     permissions: vec!["read".to_string()],
+                    accepted_currencies: vec![],
                 },
                 PaymentTierConfig {
                     id: "pro".to_string(),
                     amount_vrsc: 5.0,
                     description: Some("Pro access".to_string()),
                     permissions: vec!["read".to_string(), "write".to_string()],
+                    accepted_currencies: vec![],
                 },
             ],
+            underpayment_policy: default_underpayment_policy(),
+            underpayment_tolerance_vrsc: 0.0,
+            overpayment_policy: default_overpayment_policy(),
+            native_currency: default_native_currency(),
+            exchange_lock_minutes: default_exchange_lock_minutes(),
+            receipt_signing_key: None,
+            history_retention_days: default_history_retention_days(),
+            rpc: PaymentsRpcConfig::default(),
+            address_pool: AddressPoolConfig::default(),
+            refunds: RefundsConfig::default(),
+            zero_conf: ZeroConfConfig::default(),
         }
     }
 }
@@ -464,13 +3795,105 @@ impl AppConfig {
         self.server.validate()?;
         self.security.validate()?;
         self.rate_limit.validate()?;
+        self.rate_limit.costs.validate()?;
         self.logging.validate()?;
         self.cache.validate()?;
-        // payments uses only simple validations; nothing extra
+        self.localization.validate()?;
+        self.mempool.validate()?;
+        self.explorer.validate()?;
+        self.slow_query_log.validate()?;
+        self.scheduler.validate()?;
+        self.revocation.validate()?;
+        self.session.validate()?;
+        self.partners.validate()?;
+        self.stratum.validate()?;
+        self.stake_proof.validate()?;
+        self.load_shedding.validate()?;
+        self.currency_history.validate()?;
+        self.indexer.validate()?;
+        self.chain_events.validate()?;
+        self.tx_tracking.validate()?;
+        self.dry_run.validate()?;
+        self.cors.validate()?;
+        self.csrf.validate()?;
+        self.security_headers.validate()?;
+        self.strict_json.validate()?;
+        self.metrics_persistence.validate()?;
+        self.health_history.validate()?;
+        self.status_page.validate()?;
+        self.cluster.validate()?;
+        self.maintenance.validate()?;
+        self.client_profiles.validate()?;
+        self.issuance_webhook.validate()?;
+        self.systemd.validate()?;
+        self.rewrite.validate()?;
+        self.wasm_plugins.validate()?;
+        self.scripting.validate()?;
+        self.recording.validate()?;
+        self.upstream_context.validate()?;
+        self.capabilities.validate()?;
+        self.method_discovery.validate()?;
+        self.cache_warmer.validate()?;
+        self.disk_cache.validate()?;
+        self.negative_cache.validate()?;
+        self.complexity.validate()?;
+        self.daemon_wait.validate()?;
+        self.sensitive_method_alerts.validate()?;
+        self.auto_ban.validate()?;
+        self.response_signing.validate()?;
+        self.canonical_json.validate()?;
+        self.name_resolver.validate()?;
+        self.conversion_estimates.validate()?;
+        self.marketplace.validate()?;
+        self.shielded_tree.validate()?;
+        self.portfolio.validate()?;
+        self.jobs.validate()?;
+        self.reports.validate()?;
+        for (name, tenant) in &self.tenants {
+            if !is_valid_tenant_name(name) {
+                let mut errors = validator::ValidationErrors::new();
+                errors.add("tenants", validator::ValidationError::new("tenant_invalid_name"));
+                return Err(errors);
+            }
+            tenant.validate()?;
+            if tenant.upstream.as_ref().is_some_and(|upstream| !self.upstreams.contains_key(upstream)) {
+                let mut errors = validator::ValidationErrors::new();
+                errors.add("tenants", validator::ValidationError::new("tenant_unknown_upstream"));
+                return Err(errors);
+            }
+        }
+        for upstream in self.upstreams.values() {
+            upstream.validate()?;
+        }
+        self.upgrade.validate()?;
+        self.memory_guard.validate()?;
+        self.heap_profiling.validate()?;
+        self.mock_daemon.validate()?;
+        self.validation_cache.validate()?;
+        self.admin.validate()?;
+        // payments uses only simple validations; only its upstream policy, address pool and refunds are range-checked
+        self.payments.rpc.validate()?;
+        self.payments.address_pool.validate()?;
+        self.payments.refunds.validate()?;
+        self.payments.zero_conf.validate()?;
         
         Ok(())
     }
     
+    /// Body limit for JSON-RPC routes: the largest of `[server] max_request_size` and any client profile's
+    pub fn rpc_body_limit(&self) -> u64 {
+        let profiles = self.client_profiles.profiles.iter().filter(|_| self.client_profiles.enabled);
+        profiles
+            .filter_map(|profile| profile.max_request_size)
+            .fold(self.server.max_request_size, usize::max) as u64
+    }
+    
+    /// JWT audiences accepted for bearer tokens: the global one and every tenant's
+    pub fn jwt_audiences(&self) -> Vec<&str> {
+        let tenants = self.tenants.values().filter_map(|tenant| tenant.jwt_audience.as_deref());
+        std::iter::once(self.security.jwt.audience.as_str()).chain(tenants).collect()
+    }
+    
     /// Get server address as string
     pub fn server_address(&self) -> String {
         format!("{}:{}", self.server.bind_address, self.server.port)
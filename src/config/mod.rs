@@ -7,4 +7,4 @@ pub mod app_config;
 pub mod validation;
 
 pub use app_config::AppConfig;
-pub use validation::ConfigValidator; 
\ No newline at end of file
+pub use validation::{ConfigIssue, ConfigValidator}; 
\ No newline at end of file
@@ -3,9 +3,37 @@
 //! This module provides additional validation logic for configuration
 //! beyond the basic validator crate validation.
 
+use crate::config::app_config::RevocationBackend;
 use crate::config::AppConfig;
 use crate::shared::error::AppError;
 
+/// Configuration problem located by its TOML key, with the change that resolves it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Dotted TOML path, e.g. `rate_limit.requests_per_minute`
+    pub path: String,
+    pub problem: String,
+    pub fix: String,
+}
+
+impl ConfigIssue {
+    fn new(path: &str, problem: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { path: path.to_string(), problem: problem.into(), fix: fix.into() }
+    }
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} (fix: {})", self.path, self.problem, self.fix)
+    }
+}
+
+impl From<ConfigIssue> for AppError {
+    fn from(issue: ConfigIssue) -> Self {
+        AppError::Validation(issue.to_string())
+    }
+}
+
 /// Configuration validator for additional validation logic
 pub struct ConfigValidator;
 
@@ -21,9 +49,119 @@ impl ConfigValidator {
         // Validate rate limiting settings
         Self::validate_rate_limit_config(&config.rate_limit)?;
         
+        // Validate complexity budgets
+        Self::validate_complexity_config(&config.complexity)?;
+        
+        // Validate chain event webhooks
+        Self::validate_webhook_urls(&config.chain_events.webhook_urls)?;
+        
+        // Validate security header values
+        Self::validate_security_headers(&config.security_headers)?;
+        
+        // Validate settings that depend on each other, reporting every problem at once
+        let issues = Self::cross_validate(config);
+        if !issues.is_empty() {
+            let report: Vec<String> = issues.iter().map(ToString::to_string).collect();
+            return Err(AppError::Validation(report.join("; ")));
+        }
+        
         Ok(())
     }
     
+    /// Settings that are valid alone but contradict others
+    pub fn cross_validate(config: &AppConfig) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        
+        let payments = &config.payments;
+        if payments.enabled && payments.tiers.is_empty() {
+            issues.push(ConfigIssue::new(
+                "payments.tiers",
+                "payments are enabled but no tiers are configured",
+                "add a [[payments.tiers]] entry with id, amount_vrsc and permissions, or set payments.enabled = false",
+            ));
+        }
+        if payments.enabled && !payments.address_types.contains(&payments.default_address_type) {
+            issues.push(ConfigIssue::new(
+                "payments.default_address_type",
+                format!("\"{}\" is not one of payments.address_types", payments.default_address_type),
+                format!("use one of {:?} or add it to payments.address_types", payments.address_types),
+            ));
+        }
+        if payments.zero_conf.enabled && !payments.enabled {
+            issues.push(ConfigIssue::new(
+                "payments.zero_conf.enabled",
+                "zero-confirmation tokens are enabled but payments are disabled",
+                "set payments.enabled = true, or payments.zero_conf.enabled = false",
+            ));
+        }
+        
+        if let Some(pow) = config.security.pow.as_ref().filter(|pow| pow.enabled) {
+            let difficulty = pow.default_difficulty.trim();
+            if difficulty.is_empty() {
+                issues.push(ConfigIssue::new(
+                    "security.pow.default_difficulty",
+                    "PoW is enabled without a difficulty",
+                    "set a hex target such as default_difficulty = \"0000ffff\", or security.pow.enabled = false",
+                ));
+            } else if difficulty.len() > 16 || u64::from_str_radix(difficulty, 16).is_err() {
+                issues.push(ConfigIssue::new(
+                    "security.pow.default_difficulty",
+                    format!("\"{}\" is not a hex target of at most 16 digits", difficulty),
+                    "use a hex target such as \"0000ffff\"; fewer leading f digits make challenges harder",
+                ));
+            }
+        }
+        
+        let webhook = &config.issuance_webhook;
+        if webhook.enabled && webhook.url.is_none() {
+            issues.push(ConfigIssue::new(
+                "issuance_webhook.url",
+                "the issuance webhook is enabled without an endpoint",
+                "set url to the endpoint reviewing issuance, or issuance_webhook.enabled = false",
+            ));
+        }
+        if webhook.enabled && webhook.secret.is_none() {
+            issues.push(ConfigIssue::new(
+                "issuance_webhook.secret",
+                "the issuance webhook is enabled without a signing secret",
+                "generate one with `openssl rand -hex 32`, or set issuance_webhook.enabled = false",
+            ));
+        }
+        
+        // The token service creates sessions and the server checks them, so both must reach the same Redis
+        let server_uses_redis = config.cluster.enabled || config.revocation.backend != RevocationBackend::Memory;
+        if config.session.enabled && !(config.cache.enabled && server_uses_redis) {
+            issues.push(ConfigIssue::new(
+                "session.enabled",
+                "session-bound tokens are enabled without Redis, so sessions would stay in the process that created them",
+                "set cache.enabled = true and revocation.backend = \"auto\" or \"redis\", or session.enabled = false",
+            ));
+        }
+        
+        if config.rate_limit.enabled && config.rate_limit.requests_per_minute == 0 {
+            issues.push(Self::zero_rate_limit_issue());
+        }
+        
+        let secret_len = config.security.jwt.secret_key.len();
+        if secret_len < 32 && !config.security.development_mode {
+            issues.push(ConfigIssue::new(
+                "security.jwt.secret_key",
+                format!("JWT secret is {} bytes, at least 32 are required outside development mode", secret_len),
+                "generate one with `openssl rand -hex 32`, or set security.development_mode = true for local testing",
+            ));
+        }
+        
+        issues
+    }
+    
+    fn zero_rate_limit_issue() -> ConfigIssue {
+        ConfigIssue::new(
+            "rate_limit.requests_per_minute",
+            "Rate limiting enabled but requests_per_minute is 0",
+            "set requests_per_minute to the allowance per client (e.g. 100), or rate_limit.enabled = false",
+        )
+    }
+    
     /// Validate Verus RPC URL
     fn validate_verus_url(url: &str) -> crate::Result<()> {
         if !url.starts_with("http://") && !url.starts_with("https://") {
@@ -46,6 +184,52 @@ impl ConfigValidator {
         }
     }
     
+    /// Validate complexity budgets
+    fn validate_complexity_config(complexity: &crate::config::app_config::ComplexityConfig) -> crate::Result<()> {
+        if complexity.enabled && complexity.deprioritize_score > complexity.max_score {
+            return Err(AppError::Validation(
+                "complexity.deprioritize_score cannot be greater than complexity.max_score".to_string()
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Validate webhook URLs
+    fn validate_webhook_urls(urls: &[String]) -> crate::Result<()> {
+        for url in urls {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(AppError::Validation(
+                    format!("Webhook URL must start with http:// or https://: {}", url)
+                ));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Validate security header values
+    fn validate_security_headers(headers: &crate::config::app_config::SecurityHeadersConfig) -> crate::Result<()> {
+        if !["", "DENY", "SAMEORIGIN"].contains(&headers.x_frame_options.to_ascii_uppercase().as_str()) {
+            return Err(AppError::Validation(
+                format!("X-Frame-Options must be DENY or SAMEORIGIN: {}", headers.x_frame_options)
+            ));
+        }
+        
+        if headers.hsts_enabled && headers.hsts_preload
+            && (!headers.hsts_include_subdomains || headers.hsts_max_age_seconds < 31536000)
+        {
+            return Err(AppError::Validation(
+                "HSTS preload requires includeSubDomains and a max-age of at least 31536000".to_string()
+            ));
+        }
+        
+        let values = [&headers.content_security_policy, &headers.referrer_policy, &headers.permissions_policy];
+        if values.iter().any(|v| v.contains(['\r', '\n'])) {
+            return Err(AppError::Validation("Security header values must be single-line".to_string()));
+        }
+        
+        Ok(())
+    }
+    
     /// Validate security configuration
     fn validate_security_config(security: &crate::config::app_config::SecurityConfig) -> crate::Result<()> {
         // Check for overly permissive CORS settings
@@ -67,11 +251,17 @@ impl ConfigValidator {
     
     /// Validate rate limiting configuration
     fn validate_rate_limit_config(rate_limit: &crate::config::app_config::RateLimitConfig) -> crate::Result<()> {
+        if let Some(cidr) = rate_limit.exemptions.cidrs.iter()
+            .find(|c| c.parse::<crate::shared::network::IpCidr>().is_err())
+        {
+            return Err(AppError::Validation(format!(
+                "Invalid CIDR in rate_limit.exemptions.cidrs: {}", cidr
+            )));
+        }
+        
         if rate_limit.enabled {
             if rate_limit.requests_per_minute == 0 {
-                return Err(AppError::Validation(
-                    "Rate limiting enabled but requests_per_minute is 0".to_string()
-                ));
+                return Err(Self::zero_rate_limit_issue().into());
             }
             
             if rate_limit.burst_size > rate_limit.requests_per_minute {
@@ -79,6 +269,25 @@ impl ConfigValidator {
                     "Burst size cannot be greater than requests per minute".to_string()
                 ));
             }
+            
+            let costs = &rate_limit.costs;
+            if let Some(category) = costs.categories.keys()
+                .find(|c| c.parse::<crate::domain::validation::MethodCategory>().is_err())
+            {
+                return Err(AppError::Validation(format!(
+                    "Unknown method category in rate_limit.costs.categories: {}", category
+                )));
+            }
+            let max_cost = costs.methods.values().copied()
+                .chain(costs.categories.values().copied())
+                .chain([costs.low, costs.medium, costs.high])
+                .max()
+                .unwrap_or(1);
+            if max_cost > rate_limit.requests_per_minute {
+                return Err(AppError::Validation(
+                    "Method cost cannot be greater than requests per minute".to_string()
+                ));
+            }
         }
         
         Ok(())
@@ -110,6 +319,27 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("must start with http:// or https://"));
     }
 
+    #[test]
+    fn test_validate_webhook_urls() {
+        assert!(ConfigValidator::validate_webhook_urls(&["https://hooks.example.com/reorg".to_string()]).is_ok());
+        assert!(ConfigValidator::validate_webhook_urls(&["hooks.example.com".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_validate_security_headers() {
+        let mut headers = crate::config::app_config::SecurityHeadersConfig::default();
+        assert!(ConfigValidator::validate_security_headers(&headers).is_ok());
+        
+        headers.x_frame_options = "ALLOW-FROM https://example.com".to_string();
+        assert!(ConfigValidator::validate_security_headers(&headers).is_err());
+        
+        headers.x_frame_options = "SAMEORIGIN".to_string();
+        headers.hsts_enabled = true;
+        headers.hsts_preload = true;
+        headers.hsts_max_age_seconds = 86400;
+        assert!(ConfigValidator::validate_security_headers(&headers).is_err());
+    }
+
     #[test]
     fn test_validate_verus_url_production_requires_https() {
         let result = ConfigValidator::validate_verus_url("http://api.verus.io");
@@ -134,6 +364,7 @@ mod tests {
                 expiration_seconds: 3600,
                 issuer: "verus-rpc-server".to_string(),
                 audience: "verus-clients".to_string(),
+                algorithm: "HS256".to_string(),
             },
             pow: None,
             mining_pool: None,
@@ -161,6 +392,7 @@ mod tests {
                 expiration_seconds: 3600,
                 issuer: "test".to_string(),
                 audience: "test".to_string(),
+                algorithm: "HS256".to_string(),
             },
             pow: None,
             mining_pool: None,
@@ -178,6 +410,9 @@ mod tests {
             requests_per_minute: 100,
             burst_size: 50,
             enabled: true,
+            costs: Default::default(),
+            max_tracked_keys: 100_000,
+            exemptions: Default::default(),
         };
         
         let result = ConfigValidator::validate_rate_limit_config(&rate_limit);
@@ -190,6 +425,9 @@ mod tests {
             requests_per_minute: 0,
             burst_size: 50,
             enabled: true,
+            costs: Default::default(),
+            max_tracked_keys: 100_000,
+            exemptions: Default::default(),
         };
         
         let result = ConfigValidator::validate_rate_limit_config(&rate_limit);
@@ -203,6 +441,9 @@ mod tests {
             requests_per_minute: 100,
             burst_size: 150,
             enabled: true,
+            costs: Default::default(),
+            max_tracked_keys: 100_000,
+            exemptions: Default::default(),
         };
         
         let result = ConfigValidator::validate_rate_limit_config(&rate_limit);
@@ -210,21 +451,120 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Burst size cannot be greater"));
     }
 
+    #[test]
+    fn test_validate_rate_limit_config_cost_too_large() {
+        let mut rate_limit = RateLimitConfig {
+            requests_per_minute: 100,
+            burst_size: 50,
+            enabled: true,
+            costs: Default::default(),
+            max_tracked_keys: 100_000,
+            exemptions: Default::default(),
+        };
+        rate_limit.costs.methods.insert("getblocktemplate".to_string(), 101);
+        
+        let result = ConfigValidator::validate_rate_limit_config(&rate_limit);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Method cost cannot be greater"));
+    }
+
     #[test]
     fn test_validate_rate_limit_config_disabled() {
         let rate_limit = RateLimitConfig {
             requests_per_minute: 100,
             burst_size: 50,
             enabled: false,
+            costs: Default::default(),
+            max_tracked_keys: 100_000,
+            exemptions: Default::default(),
         };
         let result = ConfigValidator::validate_rate_limit_config(&rate_limit);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_validate_rate_limit_exemption_cidrs() {
+        let mut rate_limit = RateLimitConfig {
+            requests_per_minute: 100,
+            burst_size: 50,
+            enabled: false,
+            costs: Default::default(),
+            max_tracked_keys: 100_000,
+            exemptions: Default::default(),
+        };
+        rate_limit.exemptions.cidrs = vec!["10.0.0.0/8".to_string(), "::1".to_string()];
+        assert!(ConfigValidator::validate_rate_limit_config(&rate_limit).is_ok());
+        
+        rate_limit.exemptions.cidrs.push("10.0.0.0/40".to_string());
+        let result = ConfigValidator::validate_rate_limit_config(&rate_limit);
+        assert!(result.unwrap_err().to_string().contains("rate_limit.exemptions.cidrs"));
+    }
+
     #[test]
     fn test_validate_config_complete() {
         let config = AppConfig::default();
         let result = ConfigValidator::validate_config(&config);
         assert!(result.is_ok());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_cross_validation_reports_paths_and_fixes() {
+        let mut config = AppConfig::default();
+        assert!(ConfigValidator::cross_validate(&config).is_empty());
+        
+        config.payments.tiers.clear();
+        config.security.pow = Some(crate::config::app_config::PowConfig {
+            default_difficulty: "zz".to_string(),
+            challenge_expiration_minutes: 10,
+            token_duration_seconds: 3600,
+            rate_limit_multiplier: 1.0,
+            enabled: true,
+        });
+        config.issuance_webhook.enabled = true;
+        config.issuance_webhook.url = Some("https://billing.example.com/issuance".to_string());
+        config.security.jwt.secret_key = "short".to_string();
+        let paths: Vec<String> = ConfigValidator::cross_validate(&config).into_iter().map(|issue| issue.path).collect();
+        assert_eq!(
+            paths,
+            ["payments.tiers", "security.pow.default_difficulty", "issuance_webhook.secret", "security.jwt.secret_key"]
+        );
+        
+        let error = ConfigValidator::validate_config(&config).unwrap_err().to_string();
+        assert!(error.contains("payments.tiers: payments are enabled but no tiers are configured (fix: add a [[payments.tiers]]"));
+        
+        config.security.development_mode = true;
+        config.payments.enabled = false;
+        config.security.pow = None;
+        config.issuance_webhook.enabled = false;
+        assert!(ConfigValidator::cross_validate(&config).is_empty());
+    }
+
+    #[test]
+    fn test_sessions_require_shared_redis() {
+        let mut config = AppConfig::default();
+        config.session.enabled = true;
+        let paths: Vec<String> = ConfigValidator::cross_validate(&config).into_iter().map(|issue| issue.path).collect();
+        assert_eq!(paths, ["session.enabled"]);
+        
+        config.cache.enabled = true;
+        assert!(ConfigValidator::cross_validate(&config).is_empty());
+        
+        config.revocation.backend = RevocationBackend::Memory;
+        assert_eq!(ConfigValidator::cross_validate(&config).len(), 1);
+        
+        config.cluster.enabled = true;
+        assert!(ConfigValidator::cross_validate(&config).is_empty());
+    }
+    
+    #[test]
+    fn test_daemon_auth_requires_credentials_for_method() {
+        let mut config = AppConfig::default();
+        config.verus.auth_method = crate::config::app_config::DaemonAuthMethod::Cookie;
+        assert!(config.validate_config().is_err());
+
+        config.verus.cookie_file = Some("~/.komodo/VRSC/.cookie".to_string());
+        config.verus.rpc_user.clear();
+        config.verus.rpc_password.clear();
+        assert!(config.validate_config().is_ok());
+    }
+}
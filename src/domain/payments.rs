@@ -38,6 +38,22 @@ pub struct PaymentTier {
     pub amount_vrsc: f64,
     pub description: Option<String>,
     pub permissions: Vec<String>,
+    /// PBaaS currencies accepted in addition to the native currency
+    #[serde(default)]
+    pub accepted_currencies: Vec<String>,
+}
+
+/// Non-native currency a quote is payable in, priced at quote time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CurrencyQuote {
+    /// Currency name as requested
+    pub currency: String,
+    /// Currency i-address reported by the daemon
+    pub currency_id: String,
+    /// Amount payable in `currency`
+    pub amount: f64,
+    /// The quoted amount is only honoured for submissions before this time
+    pub rate_locked_until: chrono::DateTime<chrono::Utc>,
 }
 
 /// Payment session status
@@ -49,10 +65,132 @@ pub enum PaymentStatus {
     Verified,
     Confirmed1,
     Finalized,
+    /// Paid less than quoted; held open for a top-up transaction
+    Underpaid,
     Failed,
     Expired,
 }
 
+/// What to do when a payment falls short of the quote
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnderpaymentPolicy {
+    /// Fail the session
+    Reject,
+    /// Keep the session open until further payments cover the quote
+    HoldOpen,
+    /// Accept the payment for the best tier it covers
+    ReducedTier,
+}
+
+impl std::str::FromStr for UnderpaymentPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "reject" => Ok(UnderpaymentPolicy::Reject),
+            "hold_open" => Ok(UnderpaymentPolicy::HoldOpen),
+            "reduced_tier" => Ok(UnderpaymentPolicy::ReducedTier),
+            _ => Err(format!("unsupported underpayment policy: {}", s)),
+        }
+    }
+}
+
+/// What to do with the excess when a payment exceeds the quote
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverpaymentPolicy {
+    /// Accept the payment and keep the excess
+    Ignore,
+    /// Scale the issued token's rate limit by the amount paid
+    Credit,
+    /// Record the excess against the submitter's refund address
+    Refund,
+}
+
+impl std::str::FromStr for OverpaymentPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ignore" => Ok(OverpaymentPolicy::Ignore),
+            "credit" => Ok(OverpaymentPolicy::Credit),
+            "refund" => Ok(OverpaymentPolicy::Refund),
+            _ => Err(format!("unsupported overpayment policy: {}", s)),
+        }
+    }
+}
+
+/// How a verified payment amount was settled against the quote
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "decision")]
+pub enum PaymentResolution {
+    /// Paid the quoted amount (within tolerance)
+    Exact,
+    /// Paid more than quoted
+    Overpaid { excess_vrsc: f64, policy: OverpaymentPolicy },
+    /// Paid less than quoted; waiting for a top-up
+    AwaitingTopUp { shortfall_vrsc: f64 },
+    /// Paid less than quoted; accepted for a cheaper tier
+    ReducedTier { tier_id: String },
+    /// Paid less than quoted; session failed
+    Rejected { shortfall_vrsc: f64 },
+}
+
+/// Why a refund was opened
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RefundReason {
+    /// Funds reached the session address after the session expired
+    LatePayment,
+    /// Paid less than quoted under the `reject` underpayment policy
+    RejectedUnderpayment,
+    /// Excess over the quote under the `refund` overpayment policy
+    Overpayment,
+}
+
+/// Refund lifecycle
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RefundStatus {
+    /// Waiting for an operator to approve or decline
+    PendingApproval,
+    /// Sent to the wallet (`z_sendmany`); waiting for the operation to finish
+    Broadcast,
+    /// Refund transaction created
+    Completed,
+    /// Declined by an operator
+    Declined,
+    /// The wallet operation failed; can be approved again
+    Failed,
+}
+
+/// Refund owed on a payment session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PaymentRefund {
+    pub reason: RefundReason,
+    pub status: RefundStatus,
+    pub address: String,
+    /// Amount owed before the network fee
+    pub amount_vrsc: f64,
+    pub fee_vrsc: f64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// "auto" or "admin"
+    #[serde(default)]
+    pub decided_by: Option<String>,
+    #[serde(default)]
+    pub decided_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Wallet async operation id returned by `z_sendmany`
+    #[serde(default)]
+    pub operation_id: Option<String>,
+    #[serde(default)]
+    pub txid: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
 /// Payment session persisted in the store
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentSession {
@@ -70,12 +208,146 @@ pub struct PaymentSession {
     pub confirmations: u32,
     pub provisional_token: Option<String>,
     pub final_token: Option<String>,
+    /// Every txid submitted for this session (replay tracking)
+    #[serde(default)]
+    pub submitted_txids: Vec<String>,
+    /// Total verified amount received across submitted transactions
+    #[serde(default)]
+    pub paid_amount_vrsc: f64,
+    /// Latest settlement decision for the received amount
+    #[serde(default)]
+    pub resolution: Option<PaymentResolution>,
+    /// Address the payer asked to be refunded to
+    #[serde(default)]
+    pub refund_address: Option<String>,
+    /// Set when the quote is payable in a PBaaS currency instead of the native one
+    #[serde(default)]
+    pub currency_quote: Option<CurrencyQuote>,
+    /// VerusID named in the quote; filed for payment history once `identity_verified`
+    #[serde(default)]
+    pub identity: Option<String>,
+    /// The identity signed this payment's id, proving the payment is theirs
+    #[serde(default)]
+    pub identity_verified: bool,
+    /// Refund opened for this session, if any
+    #[serde(default)]
+    pub refund: Option<PaymentRefund>,
+    /// Low-permission token issued before `min_confirmations` (`[payments.zero_conf]`)
+    #[serde(default)]
+    pub zero_conf_token: Option<String>,
+    /// Receives upgraded tokens as the payment confirms
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Height of the block holding the newest paying transaction
+    #[serde(default)]
+    pub block_height: Option<u64>,
+}
+
+/// On-chain payment a token was bought with, embedded in it as the `payment` claim
+///
+/// Lets a relying party audit the token against the chain without asking the proxy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentClaim {
+    pub payment_id: String,
+    pub tier_id: String,
+    /// Amount received, in the native currency
+    pub amount_vrsc: f64,
+    /// i-address of the PBaaS currency paid in, when the quote was not in the native currency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Address the payment was made to
+    pub address: String,
+    pub txids: Vec<String>,
+    /// Height of the block holding the newest payment transaction; `None` while unconfirmed
+    pub block_height: Option<u64>,
+}
+
+/// Receipt contents covered by the signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptBody {
+    pub payment_id: String,
+    pub identity: Option<String>,
+    pub tier_id: String,
+    pub amount_vrsc: f64,
+    pub paid_amount_vrsc: f64,
+    pub currency_quote: Option<CurrencyQuote>,
+    pub txids: Vec<String>,
+    pub confirmations: u32,
+    /// `jti` of the final access token
+    pub token_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Signed receipt for a finalized payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentReceipt {
+    pub receipt: ReceiptBody,
+    /// Hex ed25519 public key of the receipt signer
+    pub public_key: String,
+    /// Hex ed25519 signature over the compact JSON encoding of `receipt`
+    pub signature: String,
+}
+
+/// One entry in an identity's payment history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentHistoryEntry {
+    pub payment_id: String,
+    pub tier_id: String,
+    pub amount_vrsc: f64,
+    pub paid_amount_vrsc: f64,
+    pub status: PaymentStatus,
+    pub txid: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Whether `GET /payments/receipt/{payment_id}` can produce a receipt
+    pub receipt_available: bool,
 }
 
 impl PaymentSession {
     pub fn is_expired(&self) -> bool {
         chrono::Utc::now() > self.expires_at
     }
+
+    /// Convert an amount in the quote's currency to its native-currency equivalent
+    /// at the locked quote rate
+    pub fn to_native_amount(&self, amount: f64) -> f64 {
+        match &self.currency_quote {
+            Some(quote) if quote.amount > 0.0 => amount * self.amount_vrsc / quote.amount,
+            _ => amount,
+        }
+    }
+
+    /// Transactions submitted for this session
+    pub fn txids(&self) -> Vec<String> {
+        if self.submitted_txids.is_empty() {
+            self.txid.iter().cloned().collect()
+        } else {
+            self.submitted_txids.clone()
+        }
+    }
+
+    /// Identity this payment is filed under, once the identity has proven it
+    pub fn verified_identity(&self) -> Option<&str> {
+        self.identity.as_deref().filter(|_| self.identity_verified)
+    }
+
+    /// Message the quote's identity signs (`signmessage`) to claim the payment
+    pub fn identity_message(&self) -> String {
+        format!("verus-rpc payment {}", self.payment_id)
+    }
+
+    /// Claim embedded in tokens issued for this session
+    pub fn claim(&self) -> PaymentClaim {
+        PaymentClaim {
+            payment_id: self.payment_id.clone(),
+            tier_id: self.tier_id.clone(),
+            amount_vrsc: self.paid_amount_vrsc,
+            currency: self.currency_quote.as_ref().map(|quote| quote.currency_id.clone()),
+            address: self.address.clone(),
+            txids: self.txids(),
+            block_height: self.block_height,
+        }
+    }
 }
 
 
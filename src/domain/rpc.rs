@@ -78,6 +78,9 @@ pub struct RpcRequest {
     
     /// Client information
     pub client_info: ClientInfo,
+    
+    /// Named upstream the request is pinned to; the default daemon when `None`
+    pub upstream: Option<String>,
 }
 
 /// Client information for request tracking
@@ -94,6 +97,9 @@ pub struct ClientInfo {
     
     /// Request timestamp
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    
+    /// Proxy request id, for correlating upstream calls with proxy logs
+    pub request_id: Option<String>,
 }
 
 /// RPC response with domain logic
@@ -149,8 +155,15 @@ impl RpcRequest {
             parameters,
             id,
             client_info,
+            upstream: None,
         }
     }
+
+    /// Pin the request to a named upstream
+    pub fn with_upstream(mut self, upstream: Option<String>) -> Self {
+        self.upstream = upstream;
+        self
+    }
     
     /// Validate the request against business rules
     pub fn validate(&self) -> AppResult<()> {
@@ -229,8 +229,7 @@ impl SecurityValidator {
             return true;
         }
         
-        if range.ends_with("*") {
-            let prefix = &range[..range.len() - 1];
+        if let Some(prefix) = range.strip_suffix("*") {
             return ip.starts_with(prefix);
         }
         
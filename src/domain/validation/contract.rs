@@ -0,0 +1,240 @@
+//! Contract cases derived from the method registry
+//!
+//! For every registered method this produces example requests the proxy
+//! should accept (required parameters only, then every parameter) and
+//! boundary cases it should reject: a missing required parameter, one
+//! parameter too many, a wrong type and values just outside each
+//! `ValidationConstraint`. Every case is checked against the registry before
+//! it is emitted, so a case the registry itself disagrees with is dropped
+//! rather than reported as a deployment mismatch later.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, value::RawValue, Value};
+
+use super::registry::MethodRegistry;
+use super::types::{ParameterType, ParameterValidationRule, RpcMethodDefinition, ValidationConstraint};
+
+/// Sample strings tried, in order, for string parameters without an enum
+const STRING_SAMPLES: &[&str] = &[
+    "0000000000000000000000000000000000000000000000000000000000000000",
+    "VRSC",
+    "RVBW4u5A4Vy2zDwngJoMZgSDaXdxLEZx4J",
+    "i5w5MuNik5NtLcYmNzcvaoixooEebB6MGV",
+    "verus@",
+    "00",
+    "a",
+];
+
+/// How the proxy should answer a case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expectation {
+    /// Passed on to the daemon (whatever the daemon then answers)
+    Accepted,
+    /// Refused by validation as an unknown method or invalid parameters
+    Rejected,
+}
+
+/// One generated request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractCase {
+    pub method: String,
+    /// What the case exercises, e.g. `hash shorter than 64`
+    pub name: String,
+    pub params: Value,
+    pub expect: Expectation,
+}
+
+/// Cases for every registered method, ordered by method name
+pub fn generate(registry: &MethodRegistry) -> Vec<ContractCase> {
+    let mut methods: Vec<_> = registry.list_methods().collect();
+    methods.sort_by(|a, b| a.name.cmp(&b.name));
+    methods.into_iter().flat_map(|method| cases_for(registry, method)).collect()
+}
+
+/// Cases for one method; empty when no valid example can be built for its parameters
+pub fn cases_for(registry: &MethodRegistry, method: &RpcMethodDefinition) -> Vec<ContractCase> {
+    let mut cases = Vec::new();
+    if !method.enabled {
+        push_checked(&mut cases, registry, method, "disabled method", Vec::new(), Expectation::Rejected);
+        return cases;
+    }
+    let Some(examples) = method
+        .parameter_rules
+        .iter()
+        .map(|rule| example(registry, rule))
+        .collect::<Option<Vec<Value>>>()
+    else {
+        return cases;
+    };
+    let required = method.parameter_rules.iter().take_while(|rule| rule.required).count();
+
+    push_checked(&mut cases, registry, method, "required parameters", examples[..required].to_vec(), Expectation::Accepted);
+    if examples.len() > required {
+        push_checked(&mut cases, registry, method, "all parameters", examples.clone(), Expectation::Accepted);
+    }
+    if let Some(last) = required.checked_sub(1) {
+        let name = format!("missing {}", method.parameter_rules[last].name);
+        push_checked(&mut cases, registry, method, &name, examples[..last].to_vec(), Expectation::Rejected);
+    }
+    let mut too_many = examples.clone();
+    too_many.push(Value::Null);
+    push_checked(&mut cases, registry, method, "too many parameters", too_many, Expectation::Rejected);
+
+    for (position, rule) in method.parameter_rules.iter().enumerate() {
+        for (label, value) in invalid_values(rule) {
+            let mut params = examples[..(position + 1).max(required)].to_vec();
+            params[position] = value;
+            let name = format!("{} {}", rule.name, label);
+            push_checked(&mut cases, registry, method, &name, params, Expectation::Rejected);
+        }
+    }
+    cases
+}
+
+/// Add the case if the registry agrees with `expect`
+fn push_checked(
+    cases: &mut Vec<ContractCase>,
+    registry: &MethodRegistry,
+    method: &RpcMethodDefinition,
+    name: &str,
+    params: Vec<Value>,
+    expect: Expectation,
+) {
+    let accepted = method.enabled
+        && params
+            .iter()
+            .map(raw)
+            .collect::<Option<Vec<_>>>()
+            .is_some_and(|raw| registry.validate_method_parameters(&method.name, &raw).is_ok());
+    if accepted == (expect == Expectation::Accepted) {
+        cases.push(ContractCase { method: method.name.clone(), name: name.to_string(), params: Value::Array(params), expect });
+    }
+}
+
+fn raw(value: &Value) -> Option<Box<RawValue>> {
+    serde_json::value::to_raw_value(value).ok()
+}
+
+/// First candidate value the rule accepts
+fn example(registry: &MethodRegistry, rule: &ParameterValidationRule) -> Option<Value> {
+    candidates(rule)
+        .into_iter()
+        .find(|value| raw(value).is_some_and(|raw| registry.validate_parameter(&raw, rule).is_ok()))
+}
+
+fn candidates(rule: &ParameterValidationRule) -> Vec<Value> {
+    let mut values: Vec<Value> = rule.default_value.iter().cloned().collect();
+    match rule.param_type {
+        ParameterType::String | ParameterType::Any => {
+            for constraint in &rule.constraints {
+                match constraint {
+                    ValidationConstraint::Enum(allowed) => values.extend(allowed.iter().map(|value| json!(value))),
+                    ValidationConstraint::MinLength(length) | ValidationConstraint::MaxLength(length) => {
+                        values.push(json!("0".repeat(*length)))
+                    }
+                    _ => {}
+                }
+            }
+            values.extend(STRING_SAMPLES.iter().map(|sample| json!(sample)));
+        }
+        ParameterType::Number => {
+            for constraint in &rule.constraints {
+                if let ValidationConstraint::MinValue(bound) | ValidationConstraint::MaxValue(bound) = constraint {
+                    values.push(number(*bound));
+                }
+            }
+            values.extend([json!(1), json!(0)]);
+        }
+        ParameterType::Boolean => values.push(json!(true)),
+        ParameterType::Object => values.push(json!({})),
+        ParameterType::Array => values.push(json!([])),
+    }
+    values
+}
+
+/// Values just outside the rule, labelled for the case name
+fn invalid_values(rule: &ParameterValidationRule) -> Vec<(String, Value)> {
+    let mut values = Vec::new();
+    let wrong_type = match rule.param_type {
+        ParameterType::String => Some(json!(12345)),
+        ParameterType::Number => Some(json!("1")),
+        ParameterType::Boolean => Some(json!("true")),
+        ParameterType::Object => Some(json!([])),
+        ParameterType::Array => Some(json!({})),
+        ParameterType::Any => None,
+    };
+    if let Some(value) = wrong_type {
+        values.push(("wrong type".to_string(), value));
+    }
+    for constraint in &rule.constraints {
+        match constraint {
+            ValidationConstraint::MinLength(length) if *length > 0 => {
+                values.push((format!("shorter than {}", length), json!("0".repeat(length - 1))));
+            }
+            ValidationConstraint::MaxLength(length) => {
+                values.push((format!("longer than {}", length), json!("0".repeat(length + 1))));
+            }
+            ValidationConstraint::MinValue(bound) => values.push((format!("below {}", bound), number(bound - 1.0))),
+            ValidationConstraint::MaxValue(bound) => values.push((format!("above {}", bound), number(bound + 1.0))),
+            ValidationConstraint::Enum(_) => values.push(("outside enum".to_string(), json!("not-an-allowed-value"))),
+            ValidationConstraint::Pattern(_) => values.push(("not matching pattern".to_string(), json!("!"))),
+            ValidationConstraint::Custom(name) => values.push((format!("invalid {}", name), json!("zz!"))),
+            _ => {}
+        }
+    }
+    values
+}
+
+/// Integral bounds stay integers so integer-only daemons parse them
+fn number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        json!(value as i64)
+    } else {
+        json!(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cases(method: &str) -> Vec<ContractCase> {
+        let registry = MethodRegistry::new();
+        cases_for(&registry, registry.get_method(method).unwrap())
+    }
+
+    #[test]
+    fn test_getblock_cases_cover_boundaries() {
+        let cases = cases("getblock");
+        let find = |name: &str| cases.iter().find(|case| case.name == name).unwrap();
+
+        assert_eq!(find("required parameters").expect, Expectation::Accepted);
+        assert_eq!(find("all parameters").params.as_array().unwrap().len(), 2);
+        assert_eq!(find("missing hash").params, json!([]));
+        assert_eq!(find("hash shorter than 64").expect, Expectation::Rejected);
+        assert_eq!(find("hash longer than 64").expect, Expectation::Rejected);
+        assert_eq!(find("verbose wrong type").expect, Expectation::Rejected);
+        assert_eq!(find("too many parameters").expect, Expectation::Rejected);
+    }
+
+    #[test]
+    fn test_every_case_agrees_with_the_registry() {
+        let registry = MethodRegistry::new();
+        let cases = generate(&registry);
+        assert!(!cases.is_empty());
+        for case in &cases {
+            let raw: Vec<_> = case.params.as_array().unwrap().iter().filter_map(raw).collect();
+            let accepted = registry.is_method_allowed(&case.method)
+                && registry.validate_method_parameters(&case.method, &raw).is_ok();
+            assert_eq!(accepted, case.expect == Expectation::Accepted, "{} / {}", case.method, case.name);
+        }
+        // Almost every enabled method gets an accepted example
+        let enabled = registry.list_methods().filter(|method| method.enabled).count();
+        let with_example = cases
+            .iter()
+            .filter(|case| case.name == "required parameters")
+            .count();
+        assert!(with_example * 10 >= enabled * 9, "{} of {} methods have examples", with_example, enabled);
+    }
+}
@@ -0,0 +1,271 @@
+//! Validation traces for explain mode
+//!
+//! A trace lists every rule evaluated for a request, in order, with what the
+//! rule expected and what the request carried. It is attached to error
+//! responses when a client sends `X-Debug-Validate: true` (development mode,
+//! or tokens carrying `admin` or `debug`).
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::registry::MethodRegistry;
+use super::types::{ParameterType, ValidationConstraint};
+use crate::shared::error::AppResult;
+
+/// One evaluated rule
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RuleCheck {
+    /// Rule name: a request-level check (`security_policy`, `token_scope`,
+    /// `method_allowlist`), a registry check (`registered`, `params_array`,
+    /// `param_count`, `required`, `type`) or a constraint (`min_length`,
+    /// `max_length`, `min_value`, `max_value`, `pattern`, `enum`, `custom:<name>`)
+    pub rule: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub got: Option<String>,
+    pub passed: bool,
+}
+
+impl RuleCheck {
+    /// Request-level check; a failure carries the error message in `got`
+    pub fn request(rule: &str, outcome: &AppResult<()>) -> Self {
+        Self {
+            rule: rule.to_string(),
+            parameter_index: None,
+            parameter: None,
+            expected: None,
+            got: outcome.as_ref().err().map(|e| e.to_string()),
+            passed: outcome.is_ok(),
+        }
+    }
+
+    fn method(rule: &str, expected: impl Into<String>, got: impl Into<String>, passed: bool) -> Self {
+        Self {
+            rule: rule.to_string(),
+            parameter_index: None,
+            parameter: None,
+            expected: Some(expected.into()),
+            got: Some(got.into()),
+            passed,
+        }
+    }
+
+    fn parameter(rule: &str, index: usize, name: &str, expected: String, got: String, passed: bool) -> Self {
+        Self {
+            rule: rule.to_string(),
+            parameter_index: Some(index),
+            parameter: Some(name.to_string()),
+            expected: Some(expected),
+            got: Some(got),
+            passed,
+        }
+    }
+}
+
+/// Every rule evaluated for one request
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationTrace {
+    pub method: String,
+    /// Whether every check passed
+    pub passed: bool,
+    pub checks: Vec<RuleCheck>,
+    /// First failed check
+    pub failure: Option<RuleCheck>,
+}
+
+impl ValidationTrace {
+    pub fn new(method: &str) -> Self {
+        Self { method: method.to_string(), passed: true, checks: Vec::new(), failure: None }
+    }
+
+    pub fn record(&mut self, check: RuleCheck) {
+        if !check.passed && self.failure.is_none() {
+            self.failure = Some(check.clone());
+        }
+        self.passed &= check.passed;
+        self.checks.push(check);
+    }
+}
+
+impl MethodRegistry {
+    /// Evaluate every parameter rule of `method` against `params`, recording each check
+    ///
+    /// Unlike `validate_method_parameters`, evaluation continues past the first
+    /// failure; a parameter of the wrong type skips its constraints.
+    pub fn explain_parameters(&self, method: &str, params: &Option<Value>, trace: &mut ValidationTrace) {
+        let Some(definition) = self.get_method(method) else {
+            trace.record(RuleCheck::method("registered", "a registered method", method, false));
+            return;
+        };
+        trace.record(RuleCheck::method("registered", "a registered method", method, true));
+
+        let values: &[Value] = match params {
+            None => &[],
+            Some(Value::Array(values)) => values,
+            Some(other) => {
+                trace.record(RuleCheck::method("params_array", "array", type_name(other), false));
+                return;
+            }
+        };
+        let limit = definition.parameter_rules.len();
+        trace.record(RuleCheck::method(
+            "param_count",
+            format!("at most {}", limit),
+            values.len().to_string(),
+            values.len() <= limit,
+        ));
+
+        for rule in &definition.parameter_rules {
+            let Some(value) = values.get(rule.index) else {
+                if rule.required {
+                    trace.record(RuleCheck::parameter("required", rule.index, &rule.name, "a value".into(), "missing".into(), false));
+                }
+                continue;
+            };
+            let type_ok = type_matches(value, &rule.param_type);
+            trace.record(RuleCheck::parameter(
+                "type",
+                rule.index,
+                &rule.name,
+                format!("{:?}", rule.param_type).to_lowercase(),
+                type_name(value).to_string(),
+                type_ok,
+            ));
+            if !type_ok {
+                continue;
+            }
+            for constraint in &rule.constraints {
+                if let Some((name, expected, got, passed)) = check_constraint(value, constraint) {
+                    trace.record(RuleCheck::parameter(&name, rule.index, &rule.name, expected, got, passed));
+                }
+            }
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(value: &Value, param_type: &ParameterType) -> bool {
+    match param_type {
+        ParameterType::String => value.is_string(),
+        ParameterType::Number => value.is_number(),
+        ParameterType::Boolean => value.is_boolean(),
+        ParameterType::Object => value.is_object(),
+        ParameterType::Array => value.is_array(),
+        ParameterType::Any => true,
+    }
+}
+
+/// `(rule, expected, got, passed)` for a constraint, or `None` when it does not apply to the value's type
+///
+/// Mirrors the registry's own constraint checks.
+fn check_constraint(value: &Value, constraint: &ValidationConstraint) -> Option<(String, String, String, bool)> {
+    let string = value.as_str();
+    let number = value.as_f64();
+    let outcome = match constraint {
+        ValidationConstraint::MinLength(min) => {
+            let len = string?.len();
+            ("min_length".to_string(), format!("length >= {}", min), len.to_string(), len >= *min)
+        }
+        ValidationConstraint::MaxLength(max) => {
+            let len = string?.len();
+            ("max_length".to_string(), format!("length <= {}", max), len.to_string(), len <= *max)
+        }
+        ValidationConstraint::MinValue(min) => {
+            let n = number?;
+            ("min_value".to_string(), format!(">= {}", min), n.to_string(), n >= *min)
+        }
+        ValidationConstraint::MaxValue(max) => {
+            let n = number?;
+            ("max_value".to_string(), format!("<= {}", max), n.to_string(), n <= *max)
+        }
+        ValidationConstraint::Pattern(pattern) => {
+            let s = string?;
+            let passed = Regex::new(pattern).is_ok_and(|regex| regex.is_match(s));
+            ("pattern".to_string(), pattern.clone(), s.to_string(), passed)
+        }
+        ValidationConstraint::Enum(allowed) => {
+            let s = string?;
+            ("enum".to_string(), format!("one of {}", allowed.join(", ")), s.to_string(), allowed.iter().any(|a| a == s))
+        }
+        ValidationConstraint::Custom(name) => {
+            let rule = format!("custom:{}", name);
+            match name.as_str() {
+                "hex_string" => {
+                    let s = string?;
+                    (rule, "hex digits".to_string(), s.to_string(), s.chars().all(|c| c.is_ascii_hexdigit()))
+                }
+                "base58_string" => {
+                    let s = string?;
+                    let passed = s.chars().all(|c| c.is_alphanumeric() && !"0OIl".contains(c));
+                    (rule, "base58 characters".to_string(), s.to_string(), passed)
+                }
+                "block_hash" => {
+                    let s = string?;
+                    let passed = s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit());
+                    (rule, "64 hex digits".to_string(), s.to_string(), passed)
+                }
+                _ => (rule, "a known custom validation".to_string(), name.clone(), false),
+            }
+        }
+    };
+    Some(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_explain_reports_each_rule() {
+        let registry = MethodRegistry::new();
+        let mut trace = ValidationTrace::new("getblock");
+        registry.explain_parameters("getblock", &Some(json!([12, 1])), &mut trace);
+        assert!(!trace.passed);
+        let failure = trace.failure.unwrap();
+        assert_eq!(failure.rule, "type");
+        assert_eq!(failure.parameter_index, Some(0));
+        assert_eq!(failure.expected.as_deref(), Some("string"));
+        assert_eq!(failure.got.as_deref(), Some("number"));
+
+        let mut trace = ValidationTrace::new("getblockcount");
+        registry.explain_parameters("getblockcount", &Some(json!([1])), &mut trace);
+        let failure = trace.failure.unwrap();
+        assert_eq!(failure.rule, "param_count");
+        assert_eq!(failure.expected.as_deref(), Some("at most 0"));
+    }
+
+    #[test]
+    fn test_explain_unknown_method() {
+        let mut trace = ValidationTrace::new("nosuchmethod");
+        MethodRegistry::new().explain_parameters("nosuchmethod", &None, &mut trace);
+        assert_eq!(trace.checks.len(), 1);
+        assert_eq!(trace.failure.unwrap().rule, "registered");
+    }
+
+    #[test]
+    fn test_constraint_checks() {
+        let check = check_constraint(&json!("abc"), &ValidationConstraint::MinLength(5)).unwrap();
+        assert_eq!(check, ("min_length".to_string(), "length >= 5".to_string(), "3".to_string(), false));
+        assert!(check_constraint(&json!(3), &ValidationConstraint::MinLength(5)).is_none());
+        let check = check_constraint(&json!("zz"), &ValidationConstraint::Custom("hex_string".into())).unwrap();
+        assert_eq!(check.0, "custom:hex_string");
+        assert!(!check.3);
+    }
+}
@@ -1,5 +1,5 @@
 use serde_json::Value;
-use crate::domain::validation::types::{RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
+use crate::domain::validation::types::{MethodCategory, RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
 use crate::domain::validation::registry::MethodRegistry;
 
 pub fn register_blocks(registry: &mut MethodRegistry) {
@@ -7,6 +7,7 @@ pub fn register_blocks(registry: &mut MethodRegistry) {
         name: "getblock".to_string(),
         description: "Get block information".to_string(),
         read_only: true,
+        category: MethodCategory::Blockchain,
         required_permissions: vec![],
         parameter_rules: vec![
             ParameterValidationRule {
@@ -37,6 +38,7 @@ pub fn register_blocks(registry: &mut MethodRegistry) {
         name: "getblockhash".to_string(),
         description: "Get block hash by height".to_string(),
         read_only: true,
+        category: MethodCategory::Blockchain,
         required_permissions: vec![],
         parameter_rules: vec![
             ParameterValidationRule {
@@ -58,6 +60,7 @@ pub fn register_blocks(registry: &mut MethodRegistry) {
         name: "getblockheader".to_string(),
         description: "Get block header".to_string(),
         read_only: true,
+        category: MethodCategory::Blockchain,
         required_permissions: vec![],
         parameter_rules: vec![
             ParameterValidationRule {
@@ -1,4 +1,4 @@
-use crate::domain::validation::types::{RpcMethodDefinition, SecurityLevel};
+use crate::domain::validation::types::{MethodCategory, RpcMethodDefinition, SecurityLevel};
 use crate::domain::validation::registry::MethodRegistry;
 
 pub fn register_core(registry: &mut MethodRegistry) {
@@ -6,6 +6,7 @@ pub fn register_core(registry: &mut MethodRegistry) {
         name: "getinfo".to_string(),
         description: "Get general information about the node".to_string(),
         read_only: true,
+        category: MethodCategory::Blockchain,
         required_permissions: vec![],
         parameter_rules: vec![],
         security_level: SecurityLevel::Low,
@@ -16,6 +17,7 @@ pub fn register_core(registry: &mut MethodRegistry) {
         name: "getblockchaininfo".to_string(),
         description: "Get blockchain information".to_string(),
         read_only: true,
+        category: MethodCategory::Blockchain,
         required_permissions: vec![],
         parameter_rules: vec![],
         security_level: SecurityLevel::Low,
@@ -26,6 +28,7 @@ pub fn register_core(registry: &mut MethodRegistry) {
         name: "getblockcount".to_string(),
         description: "Get current block count".to_string(),
         read_only: true,
+        category: MethodCategory::Blockchain,
         required_permissions: vec![],
         parameter_rules: vec![],
         security_level: SecurityLevel::Low,
@@ -36,6 +39,7 @@ pub fn register_core(registry: &mut MethodRegistry) {
         name: "getdifficulty".to_string(),
         description: "Get current difficulty".to_string(),
         read_only: true,
+        category: MethodCategory::Blockchain,
         required_permissions: vec![],
         parameter_rules: vec![],
         security_level: SecurityLevel::Low,
@@ -46,6 +50,7 @@ pub fn register_core(registry: &mut MethodRegistry) {
         name: "getmempoolinfo".to_string(),
         description: "Get mempool information".to_string(),
         read_only: true,
+        category: MethodCategory::Blockchain,
         required_permissions: vec![],
         parameter_rules: vec![],
         security_level: SecurityLevel::Low,
@@ -56,6 +61,7 @@ pub fn register_core(registry: &mut MethodRegistry) {
         name: "getmininginfo".to_string(),
         description: "Get mining information".to_string(),
         read_only: true,
+        category: MethodCategory::Mining,
         required_permissions: vec![],
         parameter_rules: vec![],
         security_level: SecurityLevel::Low,
@@ -66,6 +72,7 @@ pub fn register_core(registry: &mut MethodRegistry) {
         name: "getnetworkinfo".to_string(),
         description: "Get network information".to_string(),
         read_only: true,
+        category: MethodCategory::Utility,
         required_permissions: vec![],
         parameter_rules: vec![],
         security_level: SecurityLevel::Low,
@@ -1,4 +1,4 @@
-use crate::domain::validation::types::{RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
+use crate::domain::validation::types::{MethodCategory, RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
 use crate::domain::validation::registry::MethodRegistry;
 
 pub fn register_currency(registry: &mut MethodRegistry) {
@@ -6,6 +6,7 @@ pub fn register_currency(registry: &mut MethodRegistry) {
         name: "getcurrency".to_string(),
         description: "Get currency information".to_string(),
         read_only: true,
+        category: MethodCategory::Currency,
         required_permissions: vec![],
         parameter_rules: vec![
             ParameterValidationRule {
@@ -1,5 +1,5 @@
 use serde_json::Value;
-use crate::domain::validation::types::{RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
+use crate::domain::validation::types::{MethodCategory, RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
 use crate::domain::validation::registry::MethodRegistry;
 
 pub fn register_identity(registry: &mut MethodRegistry) {
@@ -7,6 +7,7 @@ pub fn register_identity(registry: &mut MethodRegistry) {
         name: "getidentity".to_string(),
         description: "Get identity information".to_string(),
         read_only: true,
+        category: MethodCategory::Identity,
         required_permissions: vec![],
         parameter_rules: vec![
             ParameterValidationRule {
@@ -1,5 +1,5 @@
 use serde_json::Value;
-use crate::domain::validation::types::{RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
+use crate::domain::validation::types::{MethodCategory, RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
 use crate::domain::validation::registry::MethodRegistry;
 
 pub fn register_transactions(registry: &mut MethodRegistry) {
@@ -7,6 +7,7 @@ pub fn register_transactions(registry: &mut MethodRegistry) {
         name: "getrawtransaction".to_string(),
         description: "Get raw transaction".to_string(),
         read_only: true,
+        category: MethodCategory::Blockchain,
         required_permissions: vec![],
         parameter_rules: vec![
             ParameterValidationRule {
@@ -35,6 +36,36 @@ pub fn register_transactions(registry: &mut MethodRegistry) {
         security_level: SecurityLevel::Low,
         enabled: true,
     });
-}
-
 
+    // Answered by the proxy: validates a raw transaction without broadcasting it
+    registry.register_method(RpcMethodDefinition {
+        name: "testrawtransaction".to_string(),
+        description: "Validate a raw transaction without broadcasting it".to_string(),
+        read_only: true,
+        category: MethodCategory::Blockchain,
+        required_permissions: vec![],
+        parameter_rules: vec![
+            ParameterValidationRule {
+                index: 0,
+                name: "hexstring".to_string(),
+                param_type: ParameterType::String,
+                required: true,
+                constraints: vec![
+                    ValidationConstraint::MinLength(1),
+                    ValidationConstraint::MaxLength(100000),
+                ],
+                default_value: None,
+            },
+            ParameterValidationRule {
+                index: 1,
+                name: "allowhighfees".to_string(),
+                param_type: ParameterType::Boolean,
+                required: false,
+                constraints: vec![],
+                default_value: Some(Value::Bool(false)),
+            },
+        ],
+        security_level: SecurityLevel::Medium,
+        enabled: true,
+    });
+}
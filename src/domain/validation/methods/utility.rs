@@ -1,4 +1,4 @@
-use crate::domain::validation::types::{RpcMethodDefinition, SecurityLevel};
+use crate::domain::validation::types::{MethodCategory, RpcMethodDefinition, SecurityLevel};
 use crate::domain::validation::registry::MethodRegistry;
 
 pub fn register_utility(registry: &mut MethodRegistry) {
@@ -6,6 +6,7 @@ pub fn register_utility(registry: &mut MethodRegistry) {
         name: "help".to_string(),
         description: "Get help information".to_string(),
         read_only: true,
+        category: MethodCategory::Utility,
         required_permissions: vec![],
         parameter_rules: vec![],
         security_level: SecurityLevel::Low,
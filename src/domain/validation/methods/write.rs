@@ -1,4 +1,4 @@
-use crate::domain::validation::types::{RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
+use crate::domain::validation::types::{MethodCategory, RpcMethodDefinition, SecurityLevel, ParameterValidationRule, ParameterType, ValidationConstraint};
 use crate::domain::validation::registry::MethodRegistry;
 
 pub fn register_write(registry: &mut MethodRegistry) {
@@ -6,6 +6,7 @@ pub fn register_write(registry: &mut MethodRegistry) {
         name: "sendrawtransaction".to_string(),
         description: "Send a raw transaction".to_string(),
         read_only: false,
+        category: MethodCategory::Blockchain,
         required_permissions: vec!["send_transaction".to_string()],
         parameter_rules: vec![
             ParameterValidationRule {
@@ -6,6 +6,9 @@ pub mod types;
 pub mod registry;
 pub mod domain_validator;
 pub mod methods;
+pub mod contract;
+pub mod scopes;
+pub mod explain;
 
 pub use types::{
     RpcMethodDefinition,
@@ -13,8 +16,13 @@ pub use types::{
     ParameterType,
     ValidationConstraint,
     SecurityLevel,
+    MethodCategory,
+    DaemonIndex,
 };
 pub use registry::MethodRegistry;
 pub use domain_validator::DomainValidator;
+pub use contract::{ContractCase, Expectation};
+pub use scopes::{TokenScope, TokenScopes};
+pub use explain::{RuleCheck, ValidationTrace};
 
 
@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
 use serde_json::{Value, value::RawValue};
 use crate::shared::error::AppResult;
 use super::types::{
@@ -18,6 +20,15 @@ use super::methods::{
     additional::register_additional_methods,
 };
 
+/// Methods auto-registered from the daemon's `help` output, visible to every registry
+static DISCOVERED: OnceLock<HashMap<String, RpcMethodDefinition>> = OnceLock::new();
+
+/// Methods disabled at startup because the daemon cannot serve them, with the reason
+static UNAVAILABLE: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Bumped whenever discovered or unavailable methods are installed
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
 /// Method registry for RPC validation
 pub struct MethodRegistry {
     pub(crate) methods: HashMap<String, RpcMethodDefinition>,
@@ -41,21 +52,70 @@ impl MethodRegistry {
         self.methods.insert(method.name.clone(), method);
     }
 
-    /// Get a method definition
+    /// Install methods discovered on the daemon at startup
+    ///
+    /// Only the first call takes effect; returns whether this one did.
+    pub fn install_discovered(methods: Vec<RpcMethodDefinition>) -> bool {
+        let installed = DISCOVERED
+            .set(methods.into_iter().map(|method| (method.name.clone(), method)).collect())
+            .is_ok();
+        if installed {
+            GENERATION.fetch_add(1, Ordering::Relaxed);
+        }
+        installed
+    }
+
+    /// Definition of a method auto-registered from daemon discovery
+    pub fn discovered_method(name: &str) -> Option<&'static RpcMethodDefinition> {
+        DISCOVERED.get()?.get(name)
+    }
+
+    /// Disable methods the daemon cannot serve (missing index); keyed by method name
+    ///
+    /// Only the first call takes effect; returns whether this one did.
+    pub fn install_unavailable(methods: HashMap<String, String>) -> bool {
+        let installed = UNAVAILABLE.set(methods).is_ok();
+        if installed {
+            GENERATION.fetch_add(1, Ordering::Relaxed);
+        }
+        installed
+    }
+
+    /// Changes each time the set of served methods changes; outcomes cached under an older value are stale
+    pub fn generation() -> u64 {
+        GENERATION.load(Ordering::Relaxed)
+    }
+
+    /// Why a method was disabled at startup, if it was
+    pub fn unavailable_reason(name: &str) -> Option<&'static str> {
+        UNAVAILABLE.get()?.get(name).map(String::as_str)
+    }
+
+    /// Get a method definition, including auto-registered methods
     pub fn get_method(&self, name: &str) -> Option<&RpcMethodDefinition> {
-        self.methods.get(name)
+        self.methods.get(name).or_else(|| Self::discovered_method(name))
+    }
+
+    /// Iterate over all registered method definitions, including auto-registered methods
+    pub fn list_methods(&self) -> impl Iterator<Item = &RpcMethodDefinition> {
+        let discovered = DISCOVERED
+            .get()
+            .into_iter()
+            .flat_map(|methods| methods.values())
+            .filter(move |method| !self.methods.contains_key(&method.name));
+        self.methods.values().chain(discovered)
     }
 
     /// Check if a method is allowed
     pub fn is_method_allowed(&self, name: &str) -> bool {
-        self.methods.get(name)
-            .map(|method| method.enabled)
+        self.get_method(name)
+            .map(|method| method.enabled && Self::unavailable_reason(name).is_none())
             .unwrap_or(false)
     }
 
     /// Validate method parameters
     pub fn validate_method_parameters(&self, method_name: &str, params: &[Box<RawValue>]) -> AppResult<()> {
-        let method = self.methods.get(method_name)
+        let method = self.get_method(method_name)
             .ok_or_else(|| crate::shared::error::AppError::MethodNotAllowed {
                 method: method_name.to_string(),
             })?;
@@ -84,7 +144,7 @@ impl MethodRegistry {
     }
 
     /// Validate a single parameter
-    fn validate_parameter(&self, param: &RawValue, rule: &ParameterValidationRule) -> AppResult<()> {
+    pub(super) fn validate_parameter(&self, param: &RawValue, rule: &ParameterValidationRule) -> AppResult<()> {
         let value: Value = serde_json::from_str(&param.to_string())
             .map_err(|e| crate::shared::error::AppError::InvalidParameters {
                 method: "unknown".to_string(),
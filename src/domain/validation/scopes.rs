@@ -0,0 +1,159 @@
+//! Structured token scopes
+//!
+//! Token permissions are free-form strings. Some of them narrow which RPC
+//! methods the token may call:
+//!
+//! - `method:<name>` allows one method by name;
+//! - `read:<category>` allows the read-only methods of a category;
+//! - `write:<category>` allows every method of a category, read or write.
+//!
+//! Categories are the `MethodCategory` names (`blockchain`, `address_index`,
+//! `identity`, `currency`, `wallet_z`, `mining`, `utility`, or the short forms
+//! `tx`, `address`, `wallet`); `*` matches every category (`read:*`). A scope
+//! naming an unknown category allows nothing. A token without any scope keeps
+//! the unscoped behaviour and is only subject to the method's
+//! `required_permissions`. A token with at least one scope may only call
+//! methods one of its scopes allows.
+
+use super::registry::MethodRegistry;
+use super::types::{MethodCategory, RpcMethodDefinition};
+use crate::shared::error::{AppError, AppResult};
+
+/// Access level of a category scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeAccess {
+    Read,
+    Write,
+}
+
+/// One structured scope parsed from a token permission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenScope {
+    /// `method:<name>`
+    Method(String),
+    /// `read:<category>` or `write:<category>`; `None` for `*`
+    Category { access: ScopeAccess, category: Option<MethodCategory> },
+    /// A category scope naming no known category
+    Unknown(String),
+}
+
+impl TokenScope {
+    /// Parse a permission; `None` for permissions that are not scopes
+    pub fn parse(permission: &str) -> Option<Self> {
+        let (prefix, value) = permission.split_once(':')?;
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        let access = match prefix {
+            "method" => return Some(TokenScope::Method(value.to_string())),
+            "read" => ScopeAccess::Read,
+            "write" => ScopeAccess::Write,
+            _ => return None,
+        };
+        if value == "*" {
+            return Some(TokenScope::Category { access, category: None });
+        }
+        Some(match value.parse() {
+            Ok(category) => TokenScope::Category { access, category: Some(category) },
+            Err(_) => TokenScope::Unknown(permission.to_string()),
+        })
+    }
+
+    fn allows(&self, method: &RpcMethodDefinition) -> bool {
+        match self {
+            TokenScope::Method(name) => *name == method.name,
+            TokenScope::Category { access, category } => {
+                let in_category = category.is_none_or(|c| c == method.category);
+                in_category && (*access == ScopeAccess::Write || method.read_only)
+            }
+            TokenScope::Unknown(_) => false,
+        }
+    }
+}
+
+/// Scopes carried by a token
+#[derive(Debug, Clone, Default)]
+pub struct TokenScopes {
+    scopes: Vec<TokenScope>,
+}
+
+impl TokenScopes {
+    pub fn from_permissions(permissions: &[String]) -> Self {
+        Self { scopes: permissions.iter().filter_map(|p| TokenScope::parse(p)).collect() }
+    }
+
+    /// Whether the token carries any scope
+    pub fn is_scoped(&self) -> bool {
+        !self.scopes.is_empty()
+    }
+
+    /// Refuse methods outside the token's scopes
+    ///
+    /// Methods the registry does not know are left to the method allow-list.
+    pub fn ensure_allowed(&self, method_name: &str, registry: &MethodRegistry) -> AppResult<()> {
+        if !self.is_scoped() {
+            return Ok(());
+        }
+        let Some(method) = registry.get_method(method_name) else {
+            return Ok(());
+        };
+        if self.scopes.iter().any(|scope| scope.allows(method)) {
+            Ok(())
+        } else {
+            Err(AppError::Security(format!("Token scope does not allow method {}", method_name)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permissions(list: &[&str]) -> TokenScopes {
+        TokenScopes::from_permissions(&list.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_parse_scopes() {
+        assert_eq!(TokenScope::parse("method:getinfo"), Some(TokenScope::Method("getinfo".into())));
+        assert_eq!(
+            TokenScope::parse("write:TX"),
+            Some(TokenScope::Category { access: ScopeAccess::Write, category: Some(MethodCategory::Blockchain) })
+        );
+        assert_eq!(TokenScope::parse("read:*"), Some(TokenScope::Category { access: ScopeAccess::Read, category: None }));
+        assert_eq!(TokenScope::parse("read:bogus"), Some(TokenScope::Unknown("read:bogus".into())));
+        assert_eq!(TokenScope::parse("read"), None);
+        assert_eq!(TokenScope::parse("read:"), None);
+        assert_eq!(TokenScope::parse("rate_multiplier_1.50"), None);
+    }
+
+    #[test]
+    fn test_scopes_restrict_methods() {
+        let registry = MethodRegistry::new();
+
+        // Unscoped tokens are not restricted here
+        assert!(permissions(&["read", "paid"]).ensure_allowed("sendrawtransaction", &registry).is_ok());
+
+        let reader = permissions(&["read", "read:blockchain", "method:getidentity"]);
+        assert!(reader.ensure_allowed("getblockcount", &registry).is_ok());
+        assert!(reader.ensure_allowed("getrawtransaction", &registry).is_ok());
+        assert!(reader.ensure_allowed("getidentity", &registry).is_ok());
+        assert!(reader.ensure_allowed("getidentitytrust", &registry).is_err());
+        assert!(reader.ensure_allowed("getaddressbalance", &registry).is_err());
+        assert!(reader.ensure_allowed("sendrawtransaction", &registry).is_err());
+
+        // Write implies read within the category
+        let sender = permissions(&["write:tx"]);
+        assert!(sender.ensure_allowed("sendrawtransaction", &registry).is_ok());
+        assert!(sender.ensure_allowed("getrawtransaction", &registry).is_ok());
+        assert!(sender.ensure_allowed("z_sendmany", &registry).is_err());
+
+        // Unknown categories restrict without allowing anything
+        assert!(permissions(&["read:bogus"]).ensure_allowed("getblockcount", &registry).is_err());
+
+        let all_reads = permissions(&["read:*"]);
+        assert!(all_reads.ensure_allowed("getcurrency", &registry).is_ok());
+        assert!(all_reads.ensure_allowed("sendrawtransaction", &registry).is_err());
+    }
+}
@@ -7,12 +7,110 @@ pub struct RpcMethodDefinition {
     pub name: String,
     pub description: String,
     pub read_only: bool,
+    pub category: MethodCategory,
     pub required_permissions: Vec<String>,
     pub parameter_rules: Vec<ParameterValidationRule>,
     pub security_level: SecurityLevel,
     pub enabled: bool,
 }
 
+/// Functional area a method belongs to
+///
+/// Used for token scopes (`read:<category>`), rate-limit costs and metrics labels.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MethodCategory {
+    /// Blocks, transactions, mempool and chain state
+    Blockchain,
+    /// Methods served by the address, spent and timestamp indexes
+    AddressIndex,
+    /// VerusID lookups
+    Identity,
+    /// PBaaS currencies, conversions and cross-chain transfers
+    Currency,
+    /// Shielded wallet (`z_*`)
+    WalletZ,
+    /// Block templates and mining state
+    Mining,
+    /// Stateless helpers: encoding, signatures, fee estimates
+    Utility,
+}
+
+impl MethodCategory {
+    pub const ALL: [MethodCategory; 7] = [
+        MethodCategory::Blockchain,
+        MethodCategory::AddressIndex,
+        MethodCategory::Identity,
+        MethodCategory::Currency,
+        MethodCategory::WalletZ,
+        MethodCategory::Mining,
+        MethodCategory::Utility,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MethodCategory::Blockchain => "blockchain",
+            MethodCategory::AddressIndex => "address_index",
+            MethodCategory::Identity => "identity",
+            MethodCategory::Currency => "currency",
+            MethodCategory::WalletZ => "wallet_z",
+            MethodCategory::Mining => "mining",
+            MethodCategory::Utility => "utility",
+        }
+    }
+}
+
+impl std::str::FromStr for MethodCategory {
+    type Err = String;
+
+    /// Accepts the category names and the short forms `tx`, `address` and `wallet`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "blockchain" | "tx" => Ok(MethodCategory::Blockchain),
+            "address_index" | "address" => Ok(MethodCategory::AddressIndex),
+            "identity" => Ok(MethodCategory::Identity),
+            "currency" => Ok(MethodCategory::Currency),
+            "wallet_z" | "wallet" => Ok(MethodCategory::WalletZ),
+            "mining" => Ok(MethodCategory::Mining),
+            "utility" => Ok(MethodCategory::Utility),
+            _ => Err(format!("unknown method category: {}", s)),
+        }
+    }
+}
+
+/// Optional daemon index a method depends on (`-addressindex`, `-spentindex`, `-timestampindex`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum DaemonIndex {
+    AddressIndex,
+    SpentIndex,
+    TimestampIndex,
+}
+
+impl DaemonIndex {
+    pub const ALL: [DaemonIndex; 3] = [DaemonIndex::AddressIndex, DaemonIndex::SpentIndex, DaemonIndex::TimestampIndex];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DaemonIndex::AddressIndex => "addressindex",
+            DaemonIndex::SpentIndex => "spentindex",
+            DaemonIndex::TimestampIndex => "timestampindex",
+        }
+    }
+}
+
+impl RpcMethodDefinition {
+    /// Daemon index the method cannot be served without
+    pub fn required_index(&self) -> Option<DaemonIndex> {
+        match self.name.as_str() {
+            "getspentinfo" => Some(DaemonIndex::SpentIndex),
+            "getblockhashes" => Some(DaemonIndex::TimestampIndex),
+            _ if self.category == MethodCategory::AddressIndex => Some(DaemonIndex::AddressIndex),
+            _ => None,
+        }
+    }
+}
+
 /// Parameter validation rule
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParameterValidationRule {
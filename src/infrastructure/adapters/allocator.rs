@@ -0,0 +1,115 @@
+//! Global allocator selection and heap profiling
+//!
+//! The `jemalloc` and `mimalloc` Cargo features replace the system allocator
+//! for every binary of the crate; long-running proxies usually fragment less
+//! with either. jemalloc builds also export allocator statistics to
+//! `/metrics/prometheus`. The `heap-profiling` feature builds jemalloc with
+//! sampling support: with `[heap_profiling]` enabled, sampling starts at
+//! startup and `GET /debug/heap` returns the live heap as a gzipped pprof
+//! profile, readable with `go tool pprof` or `pprof`.
+
+#[cfg(all(feature = "jemalloc", feature = "mimalloc"))]
+compile_error!("the `jemalloc` and `mimalloc` features are mutually exclusive");
+
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[cfg(feature = "mimalloc")]
+#[global_allocator]
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+/// Profiling compiled in but inactive until `[heap_profiling]` turns it on; one sample per 512 KiB allocated
+#[cfg(feature = "heap-profiling")]
+#[allow(non_upper_case_globals)]
+#[cfg_attr(target_os = "macos", export_name = "_rjem_malloc_conf")]
+#[cfg_attr(not(target_os = "macos"), export_name = "malloc_conf")]
+pub static malloc_conf: &[u8] = b"prof:true,prof_active:false,lg_prof_sample:19\0";
+
+#[cfg(feature = "heap-profiling")]
+use crate::shared::error::{AppError, AppResult};
+
+/// Name of the allocator this binary was built with
+pub fn name() -> &'static str {
+    if cfg!(feature = "jemalloc") {
+        "jemalloc"
+    } else if cfg!(feature = "mimalloc") {
+        "mimalloc"
+    } else {
+        "system"
+    }
+}
+
+/// Render the allocator in use and, for jemalloc, its statistics in Prometheus text format
+pub fn prometheus_text() -> String {
+    let mut out = String::new();
+    out.push_str("# HELP verus_allocator_info Global allocator the server was built with\n");
+    out.push_str("# TYPE verus_allocator_info gauge\n");
+    out.push_str(&format!("verus_allocator_info{{allocator=\"{}\"}} 1\n", name()));
+    #[cfg(feature = "jemalloc")]
+    out.push_str(&jemalloc_stats_text());
+    out
+}
+
+/// Bytes allocated, in active pages, resident and retained, as of a fresh stats epoch
+#[cfg(feature = "jemalloc")]
+fn jemalloc_stats_text() -> String {
+    use tikv_jemalloc_ctl::{epoch, stats};
+
+    let mut out = String::new();
+    if epoch::advance().is_err() {
+        return out;
+    }
+    let gauges = [
+        ("verus_allocator_allocated_bytes", "Bytes allocated by the application", stats::allocated::read()),
+        ("verus_allocator_active_bytes", "Bytes in pages the allocator has in use", stats::active::read()),
+        ("verus_allocator_resident_bytes", "Bytes the allocator holds in physically resident pages", stats::resident::read()),
+        ("verus_allocator_retained_bytes", "Bytes the allocator keeps mapped but returned to the OS", stats::retained::read()),
+    ];
+    for (name, help, value) in gauges {
+        if let Ok(value) = value {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n{} {}\n", name, help, name, name, value));
+        }
+    }
+    out
+}
+
+/// Start sampling allocations for heap profiles
+#[cfg(feature = "heap-profiling")]
+pub async fn activate_heap_profiling() -> AppResult<()> {
+    let ctl = jemalloc_pprof::PROF_CTL
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("jemalloc profiling is not available in this process".into()))?;
+    ctl.lock()
+        .await
+        .activate()
+        .map_err(|e| AppError::Internal(format!("Failed to activate heap profiling: {}", e)))
+}
+
+/// Live heap as a gzipped pprof profile
+#[cfg(feature = "heap-profiling")]
+pub async fn heap_profile() -> AppResult<Vec<u8>> {
+    let ctl = jemalloc_pprof::PROF_CTL
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("jemalloc profiling is not available in this process".into()))?;
+    let mut ctl = ctl.lock().await;
+    if !ctl.activated() {
+        return Err(AppError::Validation("heap profiling is not active, set [heap_profiling] enabled = true".into()));
+    }
+    ctl.dump_pprof().map_err(|e| AppError::Internal(format!("Failed to dump heap profile: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prometheus_text_names_the_allocator() {
+        let text = prometheus_text();
+        assert!(text.contains(&format!("verus_allocator_info{{allocator=\"{}\"}} 1", name())));
+        #[cfg(not(any(feature = "jemalloc", feature = "mimalloc")))]
+        assert_eq!(name(), "system");
+        #[cfg(feature = "jemalloc")]
+        assert!(text.contains("verus_allocator_allocated_bytes"));
+    }
+}
@@ -1,8 +1,12 @@
 //! Authentication adapter for token validation
 //! 
 //! This adapter handles JWT token validation and user permission management.
+//! Admin endpoints are authenticated separately, with the operator keys of
+//! `[admin]`: a JWT never grants admin access, whatever permissions it carries.
 
 use crate::shared::error::AppResult;
+use crate::shared::security::{find_by_api_key, normalize_api_key_hashes};
+use crate::config::app_config::AdminOperatorConfig;
 use crate::config::AppConfig;
 use std::sync::Arc;
 use tracing::{info, warn, error};
@@ -25,6 +29,10 @@ pub struct JwtClaims {
     /// Issued at
     pub iat: usize,
     
+    /// Issued at, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat_ms: Option<i64>,
+    
     /// Expiration time
     pub exp: usize,
     
@@ -42,18 +50,35 @@ pub struct JwtClaims {
     
     /// User agent (for additional security)
     pub user_agent: Option<String>,
+    
+    /// Session ID for session-bound tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+}
+
+impl JwtClaims {
+    /// Issue time in milliseconds; tokens without `iat_ms` count from the start of their `iat` second
+    pub fn issued_at_ms(&self) -> i64 {
+        self.iat_ms.unwrap_or(self.iat as i64 * 1000)
+    }
 }
 
 /// Adapter for authentication services
 pub struct AuthenticationAdapter {
     config: Arc<AppConfig>,
+    admin_operators: Vec<AdminOperatorConfig>,
     revocations: Option<Arc<crate::infrastructure::adapters::RevocationStore>>,
+    sessions: Option<Arc<crate::infrastructure::adapters::SessionStore>>,
 }
 
 impl AuthenticationAdapter {
     /// Create a new authentication adapter
     pub fn new(config: Arc<AppConfig>) -> Self {
-        Self { config, revocations: None }
+        let mut admin_operators = config.admin.operators.clone();
+        for operator in &mut admin_operators {
+            normalize_api_key_hashes(&mut operator.key_hashes, &format!("Admin operator {}", operator.name));
+        }
+        Self { config, admin_operators, revocations: None, sessions: None }
     }
 
     /// Inject revocation store
@@ -62,6 +87,12 @@ impl AuthenticationAdapter {
         self
     }
 
+    /// Inject session store (required to accept session-bound tokens)
+    pub fn with_session_store(mut self, store: Arc<crate::infrastructure::adapters::SessionStore>) -> Self {
+        self.sessions = Some(store);
+        self
+    }
+
     /// Validate authentication token
     pub async fn validate_token(&self, token: &str) -> AppResult<Vec<String>> {
         info!("Validating authentication token");
@@ -86,21 +117,57 @@ impl AuthenticationAdapter {
         self.validate_jwt_token(token_value).await
     }
 
+    /// Claims of an `Authorization: Bearer` token that is signed, current and not revoked
+    ///
+    /// Client profiles, tenants and rate limit exemptions are matched on these
+    /// claims. Session-bound tokens are checked against their session only by
+    /// `validate_token`, which also slides the session's idle deadline.
+    pub async fn verified_claims(&self, authorization: &str) -> AppResult<JwtClaims> {
+        let token = authorization
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| crate::shared::error::AppError::Authentication("Invalid token format".to_string()))?;
+        self.verify_jwt(token).await
+    }
+
     /// Validate JWT token
     async fn validate_jwt_token(&self, token: &str) -> AppResult<Vec<String>> {
+        let claims = self.verify_jwt(token).await.inspect_err(|e| error!("JWT validation failed: {}", e))?;
+
+        // Session-bound tokens need a live session; each request slides its idle deadline
+        if let Some(sid) = &claims.sid {
+            match &self.sessions {
+                Some(sessions) => { sessions.touch(sid).await?; }
+                None => {
+                    return Err(crate::shared::error::AppError::Authentication("Session tokens are not accepted".to_string()));
+                }
+            }
+        }
+
+        // Extract permissions from token
+        let permissions = claims.permissions;
+        
+        if permissions.is_empty() {
+            warn!("Token has no permissions for user: {}", claims.sub);
+            return Ok(vec!["read".to_string()]); // Default to read-only
+        }
+        
+        info!("JWT token validated successfully for user: {} with permissions: {:?}", claims.sub, permissions);
+        
+        Ok(permissions)
+    }
+
+    /// Check a token's signature, issuer, audience, validity window and revocation
+    async fn verify_jwt(&self, token: &str) -> AppResult<JwtClaims> {
         // Decode and validate JWT token
         let mut validation = Validation::new(Algorithm::HS256);
-        validation.set_audience(&[&self.config.security.jwt.audience]);
+        validation.set_audience(&self.config.jwt_audiences());
         validation.set_issuer(&[&self.config.security.jwt.issuer]);
         
         let token_data = decode::<JwtClaims>(
             token,
             &DecodingKey::from_secret(self.config.security.jwt.secret_key.as_ref()),
             &validation
-        ).map_err(|e| {
-            error!("JWT validation failed: {}", e);
-            crate::shared::error::AppError::Authentication(format!("JWT validation failed: {}", e))
-        })?;
+        ).map_err(|e| crate::shared::error::AppError::Authentication(format!("JWT validation failed: {}", e)))?;
 
         let claims = token_data.claims;
         
@@ -117,30 +184,31 @@ impl AuthenticationAdapter {
 
         // Check revocation list
         if let Some(store) = &self.revocations {
-            if store.is_revoked(&claims.jti).await.unwrap_or(false) {
+            if store.is_token_revoked(&claims.jti, &claims.sub, claims.issued_at_ms()).await.unwrap_or(false) {
                 return Err(crate::shared::error::AppError::Authentication("Token revoked".to_string()));
             }
         }
 
-        // Extract permissions from token
-        let permissions = claims.permissions;
-        
-        if permissions.is_empty() {
-            warn!("Token has no permissions for user: {}", claims.sub);
-            return Ok(vec!["read".to_string()]); // Default to read-only
-        }
-        
-        info!("JWT token validated successfully for user: {} with permissions: {:?}", claims.sub, permissions);
-        
-        Ok(permissions)
+        Ok(claims)
+    }
+
+    /// Name of the `[admin]` operator whose key is sent as `Authorization: Bearer <key>`
+    pub fn authenticate_admin(&self, auth_header: &str) -> AppResult<String> {
+        let key = auth_header
+            .strip_prefix("Bearer ")
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| crate::shared::error::AppError::Authentication("Invalid token format".to_string()))?;
+        find_by_api_key(&self.admin_operators, key, |operator| operator.key_hashes.as_slice())
+            .map(|operator| operator.name.clone())
+            .ok_or_else(|| crate::shared::error::AppError::Authentication("admin permission required".to_string()))
     }
 
     /// Extract token from request headers
     pub fn extract_token(&self, headers: &str) -> Option<String> {
         // Parse headers string and look for Authorization header
         for line in headers.lines() {
-            if line.starts_with("Authorization:") {
-                let token = line[14..].trim(); // Remove "Authorization: " prefix and trim whitespace
+            if let Some(value) = line.strip_prefix("Authorization:") {
+                let token = value.trim();
                 if !token.is_empty() {
                     return Some(token.to_string());
                 }
@@ -188,6 +256,59 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_verified_claims_checks_signature_and_revocation() {
+        use jsonwebtoken::{encode, EncodingKey, Header};
+
+        let config = AppConfig::default();
+        let now = Utc::now().timestamp() as usize;
+        let claims = JwtClaims {
+            sub: "alice".to_string(),
+            iss: config.security.jwt.issuer.clone(),
+            aud: config.security.jwt.audience.clone(),
+            iat: now,
+            iat_ms: None,
+            exp: now + 600,
+            nbf: now,
+            jti: "claims-test".to_string(),
+            permissions: vec!["internal".to_string()],
+            client_ip: None,
+            user_agent: None,
+            sid: None,
+        };
+        let bearer = |secret: &str| {
+            let token = encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(secret.as_bytes()));
+            format!("Bearer {}", token.unwrap())
+        };
+        let revocations = Arc::new(crate::infrastructure::adapters::RevocationStore::new(None));
+        let auth = AuthenticationAdapter::new(Arc::new(config.clone())).with_revocation_store(revocations.clone());
+
+        let valid = bearer(&config.security.jwt.secret_key);
+        assert_eq!(auth.verified_claims(&valid).await.unwrap().permissions, vec!["internal".to_string()]);
+        assert!(auth.verified_claims(&bearer("a-different-secret-that-is-long-enough")).await.is_err());
+        assert!(auth.verified_claims(valid.trim_start_matches("Bearer ")).await.is_err());
+
+        revocations.revoke("claims-test", 600).await.unwrap();
+        assert!(auth.verified_claims(&valid).await.is_err());
+    }
+
+    #[test]
+    fn test_admin_access_needs_an_operator_key() {
+        let mut config = AppConfig::default();
+        config.admin.operators = vec![AdminOperatorConfig {
+            name: "alice".to_string(),
+            key_hashes: vec![crate::shared::security::api_key_hash("alice-key").to_uppercase()],
+        }];
+        let auth = AuthenticationAdapter::new(Arc::new(config));
+        assert_eq!(auth.authenticate_admin("Bearer alice-key").unwrap(), "alice");
+        assert!(auth.authenticate_admin("Bearer someone-else").is_err());
+        assert!(auth.authenticate_admin("alice-key").is_err());
+
+        // Without operators nobody is an admin
+        let auth = AuthenticationAdapter::default();
+        assert!(auth.authenticate_admin("Bearer alice-key").is_err());
+    }
+
     #[tokio::test]
     async fn test_token_extraction() {
         let config = Arc::new(AppConfig::default());
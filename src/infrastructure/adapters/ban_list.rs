@@ -0,0 +1,398 @@
+//! Temporary bans for abusive clients
+//!
+//! With `[auto_ban] enabled`, every rate limit breach and every request
+//! refused as invalid counts as a strike against the client address. A client
+//! reaching `rate_limit_threshold` or `validation_failure_threshold` strikes
+//! within `window_seconds` is banned for `ban_seconds`: its JSON-RPC requests
+//! are refused with HTTP 403 and a structured error until the ban expires or
+//! is lifted through `DELETE /admin/bans/{client}`. Strikes are counted per
+//! replica; bans are written to Redis when a connection is available, so a
+//! ban issued by one replica applies to all of them. Rate-limit-exempt
+//! clients are never banned.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::app_config::AutoBanConfig;
+use crate::shared::error::{AppError, AppResult};
+
+/// Sorted set of banned clients, scored by ban expiry
+const BANNED_KEY: &str = "ban:clients";
+
+/// What earned a client a strike
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offense {
+    /// Request over the rate limit
+    RateLimit,
+    /// Request refused as invalid
+    ValidationFailure,
+}
+
+impl Offense {
+    /// Offense committed by a request that failed with `error`, if any
+    pub fn for_error(error: &AppError) -> Option<Offense> {
+        match error {
+            AppError::RateLimit => Some(Offense::RateLimit),
+            AppError::Validation(_)
+            | AppError::Security(_)
+            | AppError::MethodNotAllowed { .. }
+            | AppError::InvalidParameters { .. }
+            | AppError::Json(_) => Some(Offense::ValidationFailure),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Offense::RateLimit => "rate_limit",
+            Offense::ValidationFailure => "validation_failure",
+        }
+    }
+}
+
+/// An active ban
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BanEntry {
+    /// Banned client address
+    pub client: String,
+    pub reason: String,
+    /// Unix timestamp of the ban
+    pub banned_at: i64,
+    /// Unix timestamp at which the ban ends
+    pub expires_at: i64,
+}
+
+impl BanEntry {
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// Ban counters
+#[derive(Debug, Clone, Serialize)]
+pub struct BanMetrics {
+    pub enabled: bool,
+    /// Bans issued by this replica
+    pub bans_issued: u64,
+    /// Bans lifted through the admin API on this replica
+    pub bans_lifted: u64,
+    /// Requests refused because their client was banned
+    pub refused: u64,
+    /// Unexpired bans known to this replica
+    pub active: usize,
+}
+
+/// Strike timestamps of one client
+#[derive(Debug, Default)]
+struct Strikes {
+    rate_limit: VecDeque<i64>,
+    validation_failure: VecDeque<i64>,
+}
+
+impl Strikes {
+    fn of(&mut self, offense: Offense) -> &mut VecDeque<i64> {
+        match offense {
+            Offense::RateLimit => &mut self.rate_limit,
+            Offense::ValidationFailure => &mut self.validation_failure,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rate_limit.is_empty() && self.validation_failure.is_empty()
+    }
+}
+
+/// Strike counting and the ban list
+#[derive(Default)]
+pub struct BanList {
+    /// `None` unless `[auto_ban]` is enabled
+    config: Option<AutoBanConfig>,
+    redis: Option<Arc<ConnectionManager>>,
+    strikes: Mutex<HashMap<String, Strikes>>,
+    bans: Mutex<HashMap<String, BanEntry>>,
+    bans_issued: AtomicU64,
+    bans_lifted: AtomicU64,
+    refused: AtomicU64,
+}
+
+impl BanList {
+    /// Ban list for `[auto_ban]`; bans are shared through `redis` when given
+    pub fn new(config: &AutoBanConfig, redis: Option<Arc<ConnectionManager>>) -> Self {
+        Self { config: config.enabled.then(|| config.clone()), redis, ..Default::default() }
+    }
+
+    /// Whether bans are shared with other replicas
+    pub fn is_persistent(&self) -> bool {
+        self.redis.is_some()
+    }
+
+    fn config(&self) -> Option<&AutoBanConfig> {
+        self.config.as_ref()
+    }
+
+    fn redis(&self) -> Option<Arc<ConnectionManager>> {
+        self.redis.clone()
+    }
+
+    fn key(client: &str) -> String {
+        format!("ban:client:{}", client)
+    }
+
+    /// The ban in force against `client`, if any; counts the refused request
+    pub async fn check(&self, client: &str) -> Option<BanEntry> {
+        self.config()?;
+        let now = Utc::now().timestamp();
+        let local = self
+            .bans
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(client)
+            .filter(|ban| !ban.is_expired(now))
+            .cloned();
+        let ban = match local {
+            Some(ban) => Some(ban),
+            None => self.remote_ban(client).await,
+        };
+        if ban.is_some() {
+            self.refused.fetch_add(1, Ordering::Relaxed);
+        }
+        ban
+    }
+
+    /// Ban issued by another replica; Redis failures count as no ban
+    async fn remote_ban(&self, client: &str) -> Option<BanEntry> {
+        let mut conn = (*self.redis()?).clone();
+        match conn.get::<_, Option<String>>(Self::key(client)).await {
+            Ok(stored) => stored.and_then(|stored| serde_json::from_str(&stored).ok()),
+            Err(e) => {
+                warn!(client = %client, "Ban lookup in Redis failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Count a strike against `client`; bans it once a threshold is reached
+    pub async fn record(&self, client: &str, offense: Offense) -> Option<BanEntry> {
+        let config = self.config()?;
+        let now = Utc::now().timestamp();
+        let threshold = match offense {
+            Offense::RateLimit => config.rate_limit_threshold,
+            Offense::ValidationFailure => config.validation_failure_threshold,
+        } as usize;
+        let strikes = {
+            let mut all = self.strikes.lock().unwrap_or_else(|e| e.into_inner());
+            if !all.contains_key(client) && all.len() >= config.max_tracked_clients {
+                all.retain(|_, strikes| {
+                    for timestamps in [&mut strikes.rate_limit, &mut strikes.validation_failure] {
+                        timestamps.retain(|at| now - at < config.window_seconds as i64);
+                    }
+                    !strikes.is_empty()
+                });
+                if all.len() >= config.max_tracked_clients {
+                    return None;
+                }
+            }
+            let timestamps = all.entry(client.to_string()).or_default().of(offense);
+            timestamps.push_back(now);
+            while timestamps.front().is_some_and(|at| now - at >= config.window_seconds as i64) {
+                timestamps.pop_front();
+            }
+            let count = timestamps.len();
+            if count >= threshold {
+                all.remove(client);
+            }
+            count
+        };
+        if strikes < threshold {
+            return None;
+        }
+        let reason = format!("{} {} strikes within {} seconds", strikes, offense.as_str(), config.window_seconds);
+        let ban = self.ban(client, config.ban_seconds, reason).await;
+        warn!(
+            target: "audit",
+            event = "client_banned",
+            client = %ban.client,
+            offense = offense.as_str(),
+            expires_at = ban.expires_at,
+            "Client banned after repeated offenses"
+        );
+        Some(ban)
+    }
+
+    /// Ban `client` for `seconds`; kept in memory even if Redis fails
+    async fn ban(&self, client: &str, seconds: u64, reason: String) -> BanEntry {
+        let now = Utc::now().timestamp();
+        let ban = BanEntry {
+            client: client.to_string(),
+            reason,
+            banned_at: now,
+            expires_at: now.saturating_add(seconds as i64),
+        };
+        self.bans.lock().unwrap_or_else(|e| e.into_inner()).insert(client.to_string(), ban.clone());
+        self.bans_issued.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = self.persist(&ban, seconds).await {
+            warn!(client = %client, "Ban not shared through Redis: {}", e);
+        }
+        ban
+    }
+
+    async fn persist(&self, ban: &BanEntry, seconds: u64) -> AppResult<()> {
+        let Some(redis) = self.redis() else { return Ok(()) };
+        let value = serde_json::to_string(ban).map_err(|e| AppError::Internal(format!("ban encode: {}", e)))?;
+        let mut conn = (*redis).clone();
+        let _: () = conn
+            .set_ex(Self::key(&ban.client), &value, seconds.max(1))
+            .await
+            .map_err(|e| AppError::Internal(format!("redis set: {}", e)))?;
+        let _: () = conn
+            .zadd(BANNED_KEY, &ban.client, ban.expires_at)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis zadd: {}", e)))?;
+        Ok(())
+    }
+
+    /// Unexpired bans, newest first
+    pub async fn list(&self) -> AppResult<Vec<BanEntry>> {
+        let now = Utc::now().timestamp();
+        let mut bans: Vec<BanEntry> = match self.redis() {
+            Some(redis) => {
+                let mut conn = (*redis).clone();
+                let _: () = conn
+                    .zrembyscore(BANNED_KEY, "-inf", now)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("redis zrembyscore: {}", e)))?;
+                let clients: Vec<String> = conn
+                    .zrange(BANNED_KEY, 0, -1)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("redis zrange: {}", e)))?;
+                let mut bans = Vec::with_capacity(clients.len());
+                for client in clients {
+                    let stored: Option<String> = conn
+                        .get(Self::key(&client))
+                        .await
+                        .map_err(|e| AppError::Internal(format!("redis get: {}", e)))?;
+                    bans.extend(stored.and_then(|stored| serde_json::from_str(&stored).ok()));
+                }
+                bans
+            }
+            None => {
+                let mut local = self.bans.lock().unwrap_or_else(|e| e.into_inner());
+                local.retain(|_, ban| !ban.is_expired(now));
+                local.values().cloned().collect()
+            }
+        };
+        bans.sort_by_key(|ban| std::cmp::Reverse(ban.banned_at));
+        Ok(bans)
+    }
+
+    /// Lift the ban on `client`; returns whether there was one
+    pub async fn lift(&self, client: &str) -> AppResult<bool> {
+        let now = Utc::now().timestamp();
+        let mut lifted = self
+            .bans
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(client)
+            .is_some_and(|ban| !ban.is_expired(now));
+        self.strikes.lock().unwrap_or_else(|e| e.into_inner()).remove(client);
+        if let Some(redis) = self.redis() {
+            let mut conn = (*redis).clone();
+            let removed: u64 = conn
+                .del(Self::key(client))
+                .await
+                .map_err(|e| AppError::Internal(format!("redis del: {}", e)))?;
+            let _: () = conn
+                .zrem(BANNED_KEY, client)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis zrem: {}", e)))?;
+            lifted |= removed > 0;
+        }
+        if lifted {
+            self.bans_lifted.fetch_add(1, Ordering::Relaxed);
+            warn!(target: "audit", event = "client_ban_lifted", client = %client, "Client ban lifted");
+        }
+        Ok(lifted)
+    }
+
+    /// Current counters
+    pub fn metrics(&self) -> BanMetrics {
+        let now = Utc::now().timestamp();
+        BanMetrics {
+            enabled: self.config().is_some(),
+            bans_issued: self.bans_issued.load(Ordering::Relaxed),
+            bans_lifted: self.bans_lifted.load(Ordering::Relaxed),
+            refused: self.refused.load(Ordering::Relaxed),
+            active: self
+                .bans
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .values()
+                .filter(|ban| !ban.is_expired(now))
+                .count(),
+        }
+    }
+
+    /// Render ban counters in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let m = self.metrics();
+        let mut out = String::new();
+        out.push_str("# HELP verus_auto_ban_bans_total Clients banned after repeated offenses\n");
+        out.push_str("# TYPE verus_auto_ban_bans_total counter\n");
+        out.push_str(&format!("verus_auto_ban_bans_total {}\n", m.bans_issued));
+        out.push_str("# HELP verus_auto_ban_refused_total Requests refused from banned clients\n");
+        out.push_str("# TYPE verus_auto_ban_refused_total counter\n");
+        out.push_str(&format!("verus_auto_ban_refused_total {}\n", m.refused));
+        out.push_str("# HELP verus_auto_ban_active Unexpired bans known to this replica\n");
+        out.push_str("# TYPE verus_auto_ban_active gauge\n");
+        out.push_str(&format!("verus_auto_ban_active {}\n", m.active));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ban_list(rate_limit_threshold: u32) -> BanList {
+        BanList::new(&AutoBanConfig { enabled: true, rate_limit_threshold, ..Default::default() }, None)
+    }
+
+    #[tokio::test]
+    async fn test_repeated_offenses_ban_until_lifted() {
+        let bans = ban_list(3);
+        assert!(bans.record("198.51.100.1", Offense::RateLimit).await.is_none());
+        assert!(bans.record("198.51.100.1", Offense::RateLimit).await.is_none());
+        // Strikes of another kind or another client do not add up
+        assert!(bans.record("198.51.100.1", Offense::ValidationFailure).await.is_none());
+        assert!(bans.record("198.51.100.2", Offense::RateLimit).await.is_none());
+
+        let ban = bans.record("198.51.100.1", Offense::RateLimit).await.unwrap();
+        assert!(ban.reason.contains("rate_limit"));
+        assert_eq!(bans.check("198.51.100.1").await, Some(ban));
+        assert!(bans.check("198.51.100.2").await.is_none());
+        assert_eq!(bans.list().await.unwrap().len(), 1);
+
+        assert!(bans.lift("198.51.100.1").await.unwrap());
+        assert!(bans.check("198.51.100.1").await.is_none());
+        assert!(!bans.lift("198.51.100.1").await.unwrap());
+        let metrics = bans.metrics();
+        assert_eq!((metrics.bans_issued, metrics.bans_lifted, metrics.refused), (1, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_never_bans() {
+        let bans = BanList::default();
+        for _ in 0..100 {
+            assert!(bans.record("198.51.100.1", Offense::RateLimit).await.is_none());
+        }
+        assert!(bans.check("198.51.100.1").await.is_none());
+        assert_eq!(Offense::for_error(&AppError::RateLimit), Some(Offense::RateLimit));
+        assert_eq!(Offense::for_error(&AppError::Internal("boom".to_string())), None);
+    }
+}
@@ -3,12 +3,14 @@
 //! This adapter provides HTTP response caching using Redis to improve
 //! performance and reduce load on the Verus daemon.
 
+use crate::infrastructure::adapters::memory_usage::{MemoryUsage, ENTRY_OVERHEAD_BYTES};
 use crate::shared::error::{AppError, AppResult};
 use redis::{Client, aio::ConnectionManager, RedisResult};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 use validator::Validate;
@@ -39,47 +41,236 @@ pub struct CacheConfig {
     pub enabled: bool,
     /// Maximum cache size in bytes
     pub max_size: usize,
+    /// Redis command timeout in milliseconds
+    pub redis_timeout_ms: u64,
+    /// Longest delay between Redis reconnect attempts in seconds
+    pub redis_retry_max_seconds: u64,
+}
+
+/// Redis connection and its health, shared with the reconnect task
+///
+/// A Redis failure on the request path marks the link degraded: requests stop
+/// touching Redis and are served from the memory cache, while a background
+/// task retries with exponential backoff and clears the flag once Redis
+/// answers again. Invalidations skipped during the outage are replayed on
+/// recovery so Redis never serves entries dropped meanwhile.
+pub struct RedisLink {
+    redis_url: String,
+    manager: RwLock<Option<ConnectionManager>>,
+    degraded: AtomicBool,
+    /// Unix seconds when the current outage began; 0 while connected
+    degraded_since: AtomicU64,
+    outages: AtomicU64,
+    /// Method prefixes to invalidate once Redis returns
+    pending_invalidations: std::sync::Mutex<HashSet<String>>,
+    timeout: Duration,
+    max_backoff: Duration,
+}
+
+/// Redis health as reported by `/health`
+#[derive(Debug, Clone, Serialize)]
+pub struct RedisHealth {
+    pub degraded: bool,
+    /// Unix seconds when the current outage began
+    pub degraded_since: Option<u64>,
+    /// Outages since startup
+    pub outages: u64,
+}
+
+static REDIS_LINK: OnceLock<Arc<RedisLink>> = OnceLock::new();
+
+/// Delay before the first reconnect attempt; doubled after each failure up to `redis_retry_max_seconds`
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Interval between `PING`s while connected, so an idle outage is noticed too
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+impl RedisLink {
+    fn new(config: &CacheConfig) -> Self {
+        Self {
+            redis_url: config.redis_url.clone(),
+            manager: RwLock::new(None),
+            degraded: AtomicBool::new(false),
+            degraded_since: AtomicU64::new(0),
+            outages: AtomicU64::new(0),
+            pending_invalidations: std::sync::Mutex::new(HashSet::new()),
+            timeout: Duration::from_millis(config.redis_timeout_ms.max(1)),
+            max_backoff: Duration::from_secs(config.redis_retry_max_seconds.max(1)),
+        }
+    }
+
+    /// Link reported by `/health`, set once at server startup
+    pub fn installed() -> Option<RedisHealth> {
+        REDIS_LINK.get().map(|link| link.health())
+    }
+
+    pub fn health(&self) -> RedisHealth {
+        let since = self.degraded_since.load(Ordering::Relaxed);
+        RedisHealth {
+            degraded: self.is_degraded(),
+            degraded_since: (since > 0).then_some(since),
+            outages: self.outages.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// Connection for the request path; `None` while degraded
+    async fn connection(&self) -> Option<ConnectionManager> {
+        if self.is_degraded() {
+            return None;
+        }
+        self.manager.read().await.clone()
+    }
+
+    /// Run one Redis command within the configured timeout
+    async fn run<T>(&self, command: impl std::future::Future<Output = RedisResult<T>>) -> AppResult<T> {
+        match tokio::time::timeout(self.timeout, command).await {
+            Ok(result) => result.map_err(|e| AppError::Internal(format!("Redis error: {}", e))),
+            Err(_) => Err(AppError::Internal(format!("Redis did not answer within {:?}", self.timeout))),
+        }
+    }
+
+    fn mark_down(&self, reason: &str) {
+        if !self.degraded.swap(true, Ordering::Relaxed) {
+            self.degraded_since.store(unix_now(), Ordering::Relaxed);
+            self.outages.fetch_add(1, Ordering::Relaxed);
+            warn!("Redis cache unavailable ({}); serving from the memory cache until it returns", reason);
+        }
+    }
+
+    fn mark_up(&self) {
+        if self.degraded.swap(false, Ordering::Relaxed) {
+            let since = self.degraded_since.swap(0, Ordering::Relaxed);
+            info!(outage_seconds = unix_now().saturating_sub(since), "Redis cache connection restored");
+        }
+    }
+
+    /// Connect if needed and `PING`; true once Redis answers
+    async fn probe(&self) -> bool {
+        if self.manager.read().await.is_none() {
+            match tokio::time::timeout(self.timeout, CacheAdapter::create_redis_manager(&self.redis_url)).await {
+                Ok(Ok(manager)) => *self.manager.write().await = Some(manager),
+                Ok(Err(e)) => {
+                    debug!("Redis reconnect failed: {}", e);
+                    return false;
+                }
+                Err(_) => return false,
+            }
+        }
+        let Some(mut conn) = self.manager.read().await.clone() else { return false };
+        let reply = self.run(redis::cmd("PING").query_async::<String>(&mut conn)).await;
+        reply.is_ok()
+    }
+
+    /// Watch the connection: `PING` while connected, reconnect with backoff while degraded
+    async fn supervise(self: Arc<Self>) {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            if !self.is_degraded() {
+                tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+                if !self.is_degraded() && !self.probe().await {
+                    self.mark_down("health check failed");
+                }
+                backoff = INITIAL_BACKOFF;
+                continue;
+            }
+            tokio::time::sleep(backoff).await;
+            if self.probe().await && self.replay_invalidations().await {
+                self.mark_up();
+                backoff = INITIAL_BACKOFF;
+            } else {
+                backoff = (backoff * 2).min(self.max_backoff);
+                debug!(retry_in = ?backoff, "Redis cache still unavailable");
+            }
+        }
+    }
+
+    /// Apply invalidations skipped during the outage; false if Redis failed again
+    async fn replay_invalidations(&self) -> bool {
+        let prefixes: Vec<String> = self.pending_invalidations.lock().unwrap_or_else(|e| e.into_inner()).drain().collect();
+        if prefixes.is_empty() {
+            return true;
+        }
+        let Some(mut conn) = self.manager.read().await.clone() else { return false };
+        match delete_prefixes(&mut conn, &prefixes).await {
+            Ok(removed) => {
+                info!(removed, "Replayed cache invalidations missed during the Redis outage");
+                true
+            }
+            Err(e) => {
+                debug!("Replaying cache invalidations failed: {}", e);
+                self.defer_invalidations(&prefixes);
+                false
+            }
+        }
+    }
+
+    fn defer_invalidations(&self, prefixes: &[String]) {
+        self.pending_invalidations.lock().unwrap_or_else(|e| e.into_inner()).extend(prefixes.iter().cloned());
+    }
 }
 
 /// Cache adapter for HTTP response caching
 pub struct CacheAdapter {
-    /// Redis connection manager
-    redis_manager: Option<ConnectionManager>,
+    /// Redis connection, reconnected in the background after failures
+    redis: Arc<RedisLink>,
     /// In-memory cache fallback
     memory_cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
+    /// Approximate bytes held by `memory_cache`
+    memory_bytes: Arc<AtomicUsize>,
     /// Cache configuration
     config: CacheConfig,
+    /// Lookups answered from the cache
+    hits: AtomicU64,
+    /// Lookups that found nothing (or only expired entries)
+    misses: AtomicU64,
 }
 
 impl CacheAdapter {
     /// Create a new cache adapter
+    ///
+    /// When Redis is unreachable the adapter starts degraded, serving from
+    /// memory, and keeps retrying in the background.
     pub async fn new(config: CacheConfig) -> AppResult<Self> {
-        let redis_manager = if config.enabled {
-            match Self::create_redis_manager(&config.redis_url).await {
-                Ok(manager) => {
+        let redis = Arc::new(RedisLink::new(&config));
+        if config.enabled {
+            let connected = tokio::time::timeout(redis.timeout, Self::create_redis_manager(&config.redis_url)).await;
+            match connected {
+                Ok(Ok(manager)) => {
                     info!("Redis cache connection established successfully");
-                    Some(manager)
-                }
-                Err(e) => {
-                    warn!("Failed to connect to Redis cache: {}. Using in-memory fallback only.", e);
-                    info!("To use Redis caching, ensure Redis is running and accessible at: {}", config.redis_url);
-                    info!("You can start Redis with: redis-server");
-                    info!("Or disable caching by setting cache.enabled = false in configuration");
-                    None
+                    *redis.manager.write().await = Some(manager);
                 }
+                Ok(Err(e)) => redis.mark_down(&e.to_string()),
+                Err(_) => redis.mark_down("connection timed out"),
             }
+            tokio::spawn(redis.clone().supervise());
         } else {
             info!("Redis caching is disabled in configuration");
-            None
-        };
+        }
 
+        let memory_cache = Arc::new(RwLock::new(HashMap::new()));
+        let memory_bytes = Arc::new(AtomicUsize::new(0));
+        track_memory_cache(&memory_cache, &memory_bytes);
         Ok(Self {
-            redis_manager,
-            memory_cache: Arc::new(RwLock::new(HashMap::new())),
+            redis,
+            memory_cache,
+            memory_bytes,
             config,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         })
     }
 
+    /// Report this adapter's Redis health on `/health`
+    pub fn install_health(&self) {
+        if REDIS_LINK.set(self.redis.clone()).is_err() {
+            warn!("Redis health was already installed; keeping the first");
+        }
+    }
+
     /// Create Redis connection manager
     async fn create_redis_manager(redis_url: &str) -> AppResult<ConnectionManager> {
         let client = Client::open(redis_url)
@@ -99,23 +290,25 @@ impl CacheAdapter {
         }
 
         // Try Redis first
-        if let Some(ref manager) = self.redis_manager {
-            match self.get_from_redis(manager, key).await {
+        if let Some(conn) = self.redis.connection().await {
+            match self.get_from_redis(conn, key).await {
                 Ok(Some(entry)) => {
                     debug!("Cache hit for key: {}", key);
+                    self.hits.fetch_add(1, Ordering::Relaxed);
                     return Ok(Some(entry));
                 }
                 Ok(None) => {
                     debug!("Cache miss for key: {}", key);
                 }
-                Err(e) => {
-                    warn!("Redis cache error: {}. Falling back to memory cache.", e);
-                }
+                Err(e) => self.redis.mark_down(&e.to_string()),
             }
         }
 
         // Fall back to in-memory cache
-        self.get_from_memory(key).await
+        let entry = self.get_from_memory(key).await?;
+        let counter = if entry.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        Ok(entry)
     }
 
     /// Set a cached response
@@ -125,15 +318,13 @@ impl CacheAdapter {
         }
 
         // Try Redis first
-        if let Some(ref manager) = self.redis_manager {
-            match self.set_in_redis(manager, &entry).await {
+        if let Some(conn) = self.redis.connection().await {
+            match self.set_in_redis(conn, &entry).await {
                 Ok(()) => {
                     debug!("Cached response in Redis for key: {}", entry.key);
                     return Ok(());
                 }
-                Err(e) => {
-                    warn!("Redis cache error: {}. Falling back to memory cache.", e);
-                }
+                Err(e) => self.redis.mark_down(&e.to_string()),
             }
         }
 
@@ -142,57 +333,31 @@ impl CacheAdapter {
     }
 
     /// Get from Redis cache
-    async fn get_from_redis(&self, manager: &ConnectionManager, key: &str) -> AppResult<Option<CacheEntry>> {
-        let mut conn = manager.clone();
+    async fn get_from_redis(&self, mut conn: ConnectionManager, key: &str) -> AppResult<Option<CacheEntry>> {
+        let data: Option<Vec<u8>> = self.redis.run(redis::cmd("GET").arg(key).query_async(&mut conn)).await?;
+        let Some(data) = data else { return Ok(None) };
+        let entry: CacheEntry = serde_json::from_slice(&data)
+            .map_err(|e| AppError::Internal(format!("Failed to deserialize cache entry: {}", e)))?;
         
-        let data: RedisResult<Option<Vec<u8>>> = redis::cmd("GET")
-            .arg(key)
-            .query_async(&mut conn)
-            .await;
-
-        match data {
-            Ok(Some(data)) => {
-                let entry: CacheEntry = serde_json::from_slice(&data)
-                    .map_err(|e| AppError::Internal(format!("Failed to deserialize cache entry: {}", e)))?;
-                
-                // Check if entry is expired
-                let now = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
-                
-                if now - entry.timestamp > entry.ttl {
-                    // Entry is expired, remove it
-                    let _: () = redis::cmd("DEL")
-                        .arg(key)
-                        .query_async(&mut conn)
-                        .await
-                        .unwrap_or_default();
-                    Ok(None)
-                } else {
-                    Ok(Some(entry))
-                }
-            }
-            Ok(None) => Ok(None),
-            Err(e) => Err(AppError::Internal(format!("Redis get error: {}", e))),
+        // Check if entry is expired
+        let now = unix_now();
+        if now.saturating_sub(entry.timestamp) > entry.ttl {
+            // Entry is expired, remove it
+            let _: AppResult<()> = self.redis.run(redis::cmd("DEL").arg(key).query_async(&mut conn)).await;
+            Ok(None)
+        } else {
+            Ok(Some(entry))
         }
     }
 
     /// Set in Redis cache
-    async fn set_in_redis(&self, manager: &ConnectionManager, entry: &CacheEntry) -> AppResult<()> {
-        let mut conn = manager.clone();
-        
+    async fn set_in_redis(&self, mut conn: ConnectionManager, entry: &CacheEntry) -> AppResult<()> {
         let data = serde_json::to_vec(entry)
             .map_err(|e| AppError::Internal(format!("Failed to serialize cache entry: {}", e)))?;
         
-        let _: () = redis::cmd("SETEX")
-            .arg(&entry.key)
-            .arg(entry.ttl)
-            .arg(data)
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| AppError::Internal(format!("Redis set error: {}", e)))?;
-        
+        let _: () = self.redis
+            .run(redis::cmd("SETEX").arg(&entry.key).arg(entry.ttl).arg(data).query_async(&mut conn))
+            .await?;
         Ok(())
     }
 
@@ -231,6 +396,7 @@ impl CacheAdapter {
         }
         
         cache.insert(entry.key.clone(), entry);
+        self.memory_bytes.store(memory_cache_bytes(&cache), Ordering::Relaxed);
         debug!("Cached response in memory");
         
         Ok(())
@@ -261,9 +427,52 @@ impl CacheAdapter {
         method.hash(&mut hasher);
         params.to_string().hash(&mut hasher);
         
-        format!("verus_rpc:{:x}", hasher.finish())
+        format!("verus_rpc:{}:{:x}", method, hasher.finish())
     }
 
+    /// Remove cached responses of the given methods
+    ///
+    /// Returns the number of entries removed.
+    pub async fn invalidate_methods(&self, methods: &[&str]) -> AppResult<usize> {
+        let prefixes: Vec<String> = methods.iter().map(|m| format!("verus_rpc:{}:", m)).collect();
+        let mut removed = {
+            let mut cache = self.memory_cache.write().await;
+            let before = cache.len();
+            cache.retain(|key, _| !prefixes.iter().any(|p| key.starts_with(p.as_str())));
+            self.memory_bytes.store(memory_cache_bytes(&cache), Ordering::Relaxed);
+            before - cache.len()
+        };
+
+        match self.redis.connection().await {
+            Some(mut conn) => match delete_prefixes(&mut conn, &prefixes).await {
+                Ok(deleted) => removed += deleted,
+                Err(e) => {
+                    self.redis.mark_down(&e.to_string());
+                    self.redis.defer_invalidations(&prefixes);
+                }
+            },
+            // Redis may still hold these entries; drop them once it is back
+            None if self.config.enabled => self.redis.defer_invalidations(&prefixes),
+            None => {}
+        }
+
+        debug!("Invalidated {} cache entries", removed);
+        Ok(removed)
+    }
+
+    /// Cacheable methods whose results change when the active chain changes
+    pub const CHAIN_STATE_METHODS: &'static [&'static str] = &[
+        "getinfo",
+        "getblock",
+        "getblockcount",
+        "getdifficulty",
+        "getrawtransaction",
+        "getblockhash",
+        "getblockheader",
+        "getmempoolinfo",
+        "getcurrency",
+    ];
+
     /// Check if a method should be cached
     pub fn should_cache_method(&self, method: &str) -> bool {
         // Cache read-only methods
@@ -278,19 +487,33 @@ impl CacheAdapter {
             "getmempoolinfo",
             "getnetworkinfo",
             "getpeerinfo",
+            "getcurrency",
         ];
         
         cacheable_methods.contains(&method)
     }
 
+    /// Round-trip a `PING` to Redis; false when Redis is unreachable or not connected
+    pub async fn ping(&self) -> bool {
+        let Some(mut conn) = self.redis.manager.read().await.clone() else {
+            return false;
+        };
+        let reply = self.redis.run(redis::cmd("PING").query_async::<String>(&mut conn)).await;
+        reply.is_ok()
+    }
+
     /// Get cache statistics
     pub async fn get_stats(&self) -> CacheStats {
         let memory_size = self.memory_cache.read().await.len();
         
         CacheStats {
             memory_entries: memory_size,
-            redis_available: self.redis_manager.is_some(),
+            redis_available: self.redis.connection().await.is_some(),
+            redis_degraded: self.redis.is_degraded(),
+            redis_outages: self.redis.outages.load(Ordering::Relaxed),
             cache_enabled: self.config.enabled,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
         }
     }
 
@@ -298,12 +521,12 @@ impl CacheAdapter {
     pub async fn clear(&self) -> AppResult<()> {
         // Clear memory cache
         self.memory_cache.write().await.clear();
+        self.memory_bytes.store(0, Ordering::Relaxed);
         
         // Clear Redis cache if available
-        if let Some(ref manager) = self.redis_manager {
-            let mut conn = manager.clone();
-            let _: () = redis::cmd("FLUSHDB")
-                .query_async(&mut conn)
+        if let Some(mut conn) = self.redis.connection().await {
+            self.redis
+                .run(redis::cmd("FLUSHDB").query_async::<()>(&mut conn))
                 .await
                 .map_err(|e| AppError::Internal(format!("Failed to clear Redis cache: {}", e)))?;
         }
@@ -313,15 +536,107 @@ impl CacheAdapter {
     }
 }
 
+/// Delete every key under `prefixes`; returns the number deleted
+async fn delete_prefixes(conn: &mut ConnectionManager, prefixes: &[String]) -> AppResult<usize> {
+    let mut removed = 0;
+    for prefix in prefixes {
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(format!("{}*", prefix))
+                .arg("COUNT")
+                .arg(500)
+                .query_async(conn)
+                .await
+                .map_err(|e| AppError::Internal(format!("Redis scan error: {}", e)))?;
+            if !keys.is_empty() {
+                let deleted: usize = redis::cmd("DEL")
+                    .arg(&keys)
+                    .query_async(conn)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("Redis delete error: {}", e)))?;
+                removed += deleted;
+            }
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+    }
+    Ok(removed)
+}
+
+/// Report the memory cache's size and let the memory guard shrink it
+fn track_memory_cache(cache: &Arc<RwLock<HashMap<String, CacheEntry>>>, bytes: &Arc<AtomicUsize>) {
+    let usage = MemoryUsage::global();
+    usage.register("response_cache", bytes);
+    let (cache, bytes) = (Arc::downgrade(cache), Arc::downgrade(bytes));
+    usage.register_shrinker(move |keep_percent| {
+        let (Some(cache), Some(bytes)) = (cache.upgrade(), bytes.upgrade()) else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        runtime.spawn(async move {
+            let mut cache = cache.write().await;
+            let removed = keep_newest(&mut cache, keep_percent);
+            bytes.store(memory_cache_bytes(&cache), Ordering::Relaxed);
+            info!("Dropped {} in-memory cache entries under memory pressure", removed);
+        });
+    });
+}
+
+/// Keep the newest `keep_percent` of the entries; returns the number removed
+fn keep_newest(cache: &mut HashMap<String, CacheEntry>, keep_percent: u8) -> usize {
+    let keep = cache.len() * usize::from(keep_percent.min(100)) / 100;
+    let mut entries: Vec<(u64, String)> = cache.iter().map(|(key, entry)| (entry.timestamp, key.clone())).collect();
+    entries.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+    let removed = entries.len() - keep;
+    for (_, key) in entries.into_iter().skip(keep) {
+        cache.remove(&key);
+    }
+    removed
+}
+
+fn memory_cache_bytes(cache: &HashMap<String, CacheEntry>) -> usize {
+    cache
+        .iter()
+        .map(|(key, entry)| key.len() + entry.key.len() + entry.content_type.len() + entry.data.len() + ENTRY_OVERHEAD_BYTES)
+        .sum()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
 /// Cache statistics
 #[derive(Debug, Clone, Serialize)]
 pub struct CacheStats {
     /// Number of entries in memory cache
     pub memory_entries: usize,
-    /// Whether Redis is available
+    /// Whether Redis is connected and in use
     pub redis_available: bool,
+    /// Whether Redis failed and the cache is serving from memory while it reconnects
+    pub redis_degraded: bool,
+    /// Redis outages since startup
+    pub redis_outages: u64,
     /// Whether caching is enabled
     pub cache_enabled: bool,
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that missed
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Share of lookups answered from the cache, once there has been any
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
 }
 
 impl Default for CacheConfig {
@@ -331,6 +646,8 @@ impl Default for CacheConfig {
             default_ttl: 300, // 5 minutes
             enabled: true,
             max_size: 100 * 1024 * 1024, // 100MB
+            redis_timeout_ms: 500,
+            redis_retry_max_seconds: 30,
         }
     }
 }
@@ -355,6 +672,49 @@ mod tests {
         assert!(key1.starts_with("verus_rpc:"));
     }
 
+    #[tokio::test]
+    async fn test_invalidate_methods_keeps_other_entries() {
+        let config = CacheConfig {
+            enabled: false, // Disable cache to avoid Redis connection
+            ..Default::default()
+        };
+        let adapter = CacheAdapter::new(config).await.unwrap();
+        {
+            let mut cache = adapter.memory_cache.write().await;
+            for method in ["getblock", "getblockcount", "getpeerinfo"] {
+                let key = adapter.generate_cache_key(method, &serde_json::json!([]));
+                cache.insert(key.clone(), CacheEntry { data: Vec::new(), content_type: "application/json".into(), timestamp: 0, ttl: 60, key });
+            }
+        }
+
+        let removed = adapter.invalidate_methods(CacheAdapter::CHAIN_STATE_METHODS).await.unwrap();
+        assert_eq!(removed, 2);
+        let remaining: Vec<String> = adapter.memory_cache.read().await.keys().cloned().collect();
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0].starts_with("verus_rpc:getpeerinfo:"));
+    }
+
+    #[tokio::test]
+    async fn test_memory_bytes_follow_entries_and_shrink_keeps_newest() {
+        let config = CacheConfig { enabled: false, ..Default::default() };
+        let adapter = CacheAdapter::new(config).await.unwrap();
+        for (i, method) in ["getblock", "getblockcount", "getinfo", "getmininginfo"].into_iter().enumerate() {
+            let key = adapter.generate_cache_key(method, &serde_json::json!([]));
+            let entry = CacheEntry { data: vec![0; 1000], content_type: "application/json".into(), timestamp: unix_now() + i as u64, ttl: 60, key };
+            adapter.set_in_memory(entry).await.unwrap();
+        }
+        let bytes = adapter.memory_bytes.load(Ordering::Relaxed);
+        assert!(bytes >= 4000);
+
+        let removed = keep_newest(&mut *adapter.memory_cache.write().await, 50);
+        assert_eq!(removed, 2);
+        let remaining: HashSet<String> = adapter.memory_cache.read().await.keys().cloned().collect();
+        assert!(remaining.iter().all(|key| key.contains("getinfo") || key.contains("getmininginfo")));
+
+        adapter.clear().await.unwrap();
+        assert_eq!(adapter.memory_bytes.load(Ordering::Relaxed), 0);
+    }
+
     #[tokio::test]
     async fn test_should_cache_method() {
         let config = CacheConfig {
@@ -368,6 +728,32 @@ mod tests {
         assert!(!adapter.should_cache_method("sendrawtransaction"));
     }
 
+    #[tokio::test]
+    async fn test_degraded_mode_serves_from_memory() {
+        let config = CacheConfig {
+            enabled: true,
+            redis_url: "redis://127.0.0.1:1".to_string(), // Nothing listens here
+            redis_timeout_ms: 200,
+            ..Default::default()
+        };
+        let adapter = CacheAdapter::new(config).await.unwrap();
+        assert!(adapter.redis.is_degraded());
+
+        let key = adapter.generate_cache_key("getblockcount", &serde_json::json!([]));
+        let entry = CacheEntry { data: b"1".to_vec(), content_type: "application/json".into(), timestamp: unix_now(), ttl: 60, key: key.clone() };
+        adapter.set(entry).await.unwrap();
+        assert!(adapter.get(&key).await.unwrap().is_some());
+
+        let stats = adapter.get_stats().await;
+        assert!(stats.redis_degraded);
+        assert!(!stats.redis_available);
+        assert_eq!(stats.redis_outages, 1);
+
+        // Redis may still hold the entry, so the invalidation waits for it to return
+        assert_eq!(adapter.invalidate_methods(&["getblockcount"]).await.unwrap(), 1);
+        assert!(adapter.redis.pending_invalidations.lock().unwrap().contains("verus_rpc:getblockcount:"));
+    }
+
     #[tokio::test]
     #[ignore] // Skip this test as it hangs due to Redis connection attempts
     async fn test_memory_cache() {
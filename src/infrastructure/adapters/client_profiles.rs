@@ -0,0 +1,167 @@
+//! Per-client policy profiles
+//!
+//! A profile is matched by the SHA-256 of an `X-API-Key` header or by a
+//! permission carried in a JWT signed with the configured secret, and
+//! overrides the global rate limit, the callable methods, the body size limit
+//! and the fields returned to that client. Clients choose their own token
+//! subject, so it never selects a profile; profile permissions are granted by
+//! the token service alone. Tokens are matched on the claims the
+//! authentication adapter verified for the request (signature, expiry and
+//! revocation); method permissions are still enforced by the RPC service.
+
+use serde_json::Value;
+
+use crate::config::app_config::ClientProfileConfig;
+use crate::config::AppConfig;
+use crate::infrastructure::adapters::authentication::JwtClaims;
+use crate::shared::security::{find_by_api_key, normalize_api_key_hashes};
+
+/// Configured client profiles
+pub struct ClientProfiles {
+    enabled: bool,
+    profiles: Vec<ClientProfileConfig>,
+}
+
+impl ClientProfiles {
+    /// Build the profile set from `[client_profiles]`
+    pub fn new(config: &AppConfig) -> Self {
+        let mut profiles = config.client_profiles.profiles.clone();
+        for profile in &mut profiles {
+            normalize_api_key_hashes(&mut profile.api_key_hashes, &format!("Client profile {}", profile.id));
+        }
+        Self {
+            enabled: config.client_profiles.enabled,
+            profiles,
+        }
+    }
+
+    /// Profile for a request; an API key match takes precedence over the permissions of verified `claims`
+    pub fn resolve(&self, api_key: Option<&str>, claims: Option<&JwtClaims>) -> Option<&ClientProfileConfig> {
+        if !self.enabled || self.profiles.is_empty() {
+            return None;
+        }
+        if let Some(key) = api_key.filter(|key| !key.is_empty()) {
+            let profile = find_by_api_key(&self.profiles, key, |profile| profile.api_key_hashes.as_slice());
+            if profile.is_some() {
+                return profile;
+            }
+        }
+        let permissions = &claims?.permissions;
+        self.profiles
+            .iter()
+            .find(|profile| profile.permissions.iter().any(|permission| permissions.contains(permission)))
+    }
+}
+
+/// Whether `profile` lets its client call `method`
+pub fn allows_method(profile: &ClientProfileConfig, method: &str) -> bool {
+    profile
+        .allowed_methods
+        .as_ref()
+        .is_none_or(|methods| methods.iter().any(|m| m == method))
+}
+
+/// Remove dotted `paths` (e.g. `tx.vout`) from `value`; arrays apply the rest of the path to each element
+pub fn remove_fields(value: &mut Value, paths: &[String]) {
+    for path in paths {
+        remove_path(value, &path.split('.').collect::<Vec<_>>());
+    }
+}
+
+fn remove_path(value: &mut Value, segments: &[&str]) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|item| remove_path(item, segments)),
+        Value::Object(fields) => match segments {
+            [] => {}
+            [last] => {
+                fields.remove(*last);
+            }
+            [first, rest @ ..] => {
+                if let Some(child) = fields.get_mut(*first) {
+                    remove_path(child, rest);
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::app_config::ClientProfilesConfig;
+    use serde_json::json;
+
+    fn profile(id: &str) -> ClientProfileConfig {
+        ClientProfileConfig {
+            id: id.to_string(),
+            permissions: vec![],
+            api_key_hashes: vec![],
+            requests_per_minute: None,
+            allowed_methods: None,
+            max_request_size: None,
+            hidden_fields: vec![],
+        }
+    }
+
+    fn profiles(list: Vec<ClientProfileConfig>) -> ClientProfiles {
+        let mut config = AppConfig::default();
+        config.client_profiles = ClientProfilesConfig { enabled: true, profiles: list };
+        ClientProfiles::new(&config)
+    }
+
+    fn claims(permission: &str) -> JwtClaims {
+        let now = chrono::Utc::now().timestamp() as usize;
+        JwtClaims {
+            sub: "alice".to_string(),
+            iss: "verus-rpc-server".to_string(),
+            aud: "verus-rpc-clients".to_string(),
+            iat: now,
+            iat_ms: None,
+            exp: now + 600,
+            nbf: now,
+            jti: "jti".to_string(),
+            permissions: vec!["read".to_string(), permission.to_string()],
+            client_ip: None,
+            user_agent: None,
+            sid: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_by_api_key_hash() {
+        let mut enterprise = profile("enterprise");
+        enterprise.api_key_hashes = vec![crate::shared::security::api_key_hash("key-1").to_uppercase()];
+        let profiles = profiles(vec![enterprise]);
+
+        assert_eq!(profiles.resolve(Some("key-1"), None).map(|p| p.id.as_str()), Some("enterprise"));
+        assert!(profiles.resolve(Some("key-2"), None).is_none());
+        assert!(profiles.resolve(None, None).is_none());
+    }
+
+    #[test]
+    fn test_resolve_by_permission_only() {
+        let mut partner = profile("partner");
+        partner.permissions = vec!["partner_acme".to_string()];
+        let profiles = profiles(vec![partner]);
+
+        assert_eq!(profiles.resolve(None, Some(&claims("partner_acme"))).map(|p| p.id.as_str()), Some("partner"));
+        // The subject is the client's own choice and selects nothing
+        let mut anonymous = claims("read");
+        anonymous.sub = "partner".to_string();
+        assert!(profiles.resolve(None, Some(&anonymous)).is_none());
+    }
+
+    #[test]
+    fn test_allowed_methods_and_field_removal() {
+        let mut restricted = profile("restricted");
+        assert!(allows_method(&restricted, "getinfo"));
+        restricted.allowed_methods = Some(vec!["getblock".to_string()]);
+        assert!(allows_method(&restricted, "getblock"));
+        assert!(!allows_method(&restricted, "getinfo"));
+
+        let mut result = json!({"hash": "00", "tx": [{"txid": "a", "hex": "ff"}, {"txid": "b", "hex": "ee"}], "size": 1});
+        remove_fields(&mut result, &["tx.hex".to_string(), "size".to_string(), "missing.path".to_string()]);
+        assert_eq!(result, json!({"hash": "00", "tx": [{"txid": "a"}, {"txid": "b"}]}));
+    }
+}
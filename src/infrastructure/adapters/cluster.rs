@@ -0,0 +1,449 @@
+//! Redis coordination between proxy replicas
+//!
+//! Without `[cluster]`, every replica keeps its own rate-limit buckets and
+//! falls back to process memory for revocations, sessions and payment sessions
+//! whenever Redis is unavailable, so scaling out is best-effort: a client gets
+//! `N` times its limit and a token revoked on one replica stays valid on the
+//! others. With `[cluster] enabled = true` the server refuses to start without
+//! Redis, and every replica uses the one connection opened here for:
+//!
+//! - rate-limit windows (`INCRBY` on a per-key, per-minute counter)
+//! - the revocation, session and payments stores
+//! - single-flight locks (`SET NX PX`), so a cold cache key is fetched from the
+//!   daemon by one replica while the others wait for its cache entry
+//!
+//! With `sticky_routing` on top, replicas heartbeat into a membership set and
+//! place each other on a consistent-hash ring; a cacheable read that misses
+//! locally is forwarded to the replica owning its cache key, so repeated reads
+//! land on the one warm in-memory cache instead of missing on every replica.
+
+use crate::{
+    config::AppConfig,
+    shared::error::{AppError, AppResult},
+};
+use redis::{aio::ConnectionManager, Client};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Header carrying `[cluster] forward_secret` on requests forwarded between replicas
+pub const FORWARD_HEADER: &str = "x-verus-cluster-forward";
+
+/// Deletes a lock only while it still holds our token, so an expired lock re-taken by another replica survives
+const RELEASE_SCRIPT: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then return redis.call('DEL', KEYS[1]) else return 0 end";
+
+/// Shared Redis connection and key layout for cluster mode
+pub struct ClusterCoordinator {
+    redis: Arc<ConnectionManager>,
+    key_prefix: String,
+    node_id: String,
+    lock_ttl: Duration,
+    advertise_url: Option<String>,
+    forward_secret: Option<String>,
+    heartbeat_interval: Duration,
+    virtual_nodes: u32,
+    ring: RwLock<HashRing>,
+    http: reqwest::Client,
+    counters: ForwardCounters,
+}
+
+/// Consistent-hash ring over live replicas
+#[derive(Debug, Default, Clone)]
+pub struct HashRing {
+    /// Ring position -> index into `members`
+    points: BTreeMap<u64, usize>,
+    /// `(node_id, advertise_url)`, sorted by node id
+    members: Vec<(String, String)>,
+}
+
+/// Where a key is served from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyOwner {
+    /// This replica owns the key (or the ring is empty)
+    Local,
+    /// Another replica, reachable at the given URL
+    Remote { node_id: String, url: String },
+}
+
+#[derive(Default)]
+struct ForwardCounters {
+    forwarded: AtomicU64,
+    failed: AtomicU64,
+    received: AtomicU64,
+    owned: AtomicU64,
+}
+
+/// Sticky routing counters
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterMetrics {
+    pub node_id: String,
+    /// Replicas currently on the hash ring, this one included
+    pub members: usize,
+    /// Requests answered by the owning replica
+    pub forwarded_requests: u64,
+    /// Forwards that failed and were served locally
+    pub forward_failures: u64,
+    /// Requests received from other replicas
+    pub received_forwards: u64,
+    /// Cacheable misses this replica owned and served itself
+    pub owned_requests: u64,
+}
+
+/// A held single-flight lock; released explicitly or by its TTL
+pub struct ClusterLock {
+    coordinator: Arc<ClusterCoordinator>,
+    key: String,
+    token: String,
+}
+
+impl ClusterCoordinator {
+    /// Connect to the cluster Redis; `Ok(None)` unless `[cluster]` is enabled
+    pub async fn connect(config: &AppConfig) -> AppResult<Option<Arc<Self>>> {
+        let settings = &config.cluster;
+        if !settings.enabled {
+            return Ok(None);
+        }
+        if settings.sticky_routing && (settings.advertise_url.is_none() || settings.forward_secret.is_none()) {
+            return Err(AppError::Config(
+                "cluster.sticky_routing requires cluster.advertise_url and cluster.forward_secret".to_string(),
+            ));
+        }
+        let url = settings.redis_url.clone().unwrap_or_else(|| config.cache.redis_url.clone());
+        let client = Client::open(url).map_err(|e| AppError::Config(format!("cluster redis url: {}", e)))?;
+        let manager = tokio::time::timeout(Duration::from_secs(10), ConnectionManager::new(client))
+            .await
+            .map_err(|_| AppError::Config("cluster redis: connection timed out".to_string()))?
+            .map_err(|e| AppError::Config(format!("cluster redis unavailable: {}", e)))?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(settings.forward_timeout_ms))
+            .build()
+            .map_err(|e| AppError::Config(format!("cluster forwarding client: {}", e)))?;
+        let coordinator = Self {
+            redis: Arc::new(manager),
+            key_prefix: settings.key_prefix.clone(),
+            node_id: settings.node_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string()),
+            lock_ttl: Duration::from_millis(settings.lock_ttl_ms),
+            advertise_url: settings.sticky_routing.then(|| settings.advertise_url.clone()).flatten(),
+            forward_secret: settings.forward_secret.clone(),
+            heartbeat_interval: Duration::from_secs(settings.heartbeat_interval_seconds),
+            virtual_nodes: settings.virtual_nodes,
+            ring: RwLock::new(HashRing::default()),
+            http,
+            counters: ForwardCounters::default(),
+        };
+        info!(node_id = %coordinator.node_id, "Cluster mode enabled");
+        Ok(Some(Arc::new(coordinator)))
+    }
+
+    /// Connection shared with the revocation, session and payments stores
+    pub fn redis(&self) -> Arc<ConnectionManager> {
+        self.redis.clone()
+    }
+
+    /// Name of this replica
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Namespaced key for `kind` and `name`
+    pub fn key(&self, kind: &str, name: &str) -> String {
+        format!("{}:cluster:{}:{}", self.key_prefix, kind, name)
+    }
+
+    /// Add `cost` to the shared counter of `key` for the window starting at
+    /// `window_start`; returns the new total
+    pub async fn add_to_window(&self, key: &str, window_start: u64, window_seconds: u64, cost: u32) -> AppResult<u64> {
+        let key = self.key("ratelimit", &format!("{}:{}", window_start, key));
+        let mut conn = (*self.redis).clone();
+        let (total,): (u64,) = redis::pipe()
+            .atomic()
+            .cmd("INCRBY").arg(&key).arg(cost)
+            .cmd("EXPIRE").arg(&key).arg(window_seconds * 2).ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis incrby: {}", e)))?;
+        Ok(total)
+    }
+
+    /// Take back `cost` from a window counter (for a refused request)
+    pub async fn refund_window(&self, key: &str, window_start: u64, cost: u32) -> AppResult<()> {
+        let key = self.key("ratelimit", &format!("{}:{}", window_start, key));
+        let mut conn = (*self.redis).clone();
+        redis::cmd("DECRBY")
+            .arg(&key)
+            .arg(cost)
+            .query_async::<i64>(&mut conn)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis decrby: {}", e)))?;
+        Ok(())
+    }
+
+    /// Try to take the single-flight lock for `name`; `None` while another replica holds it
+    pub async fn try_lock(self: &Arc<Self>, name: &str) -> AppResult<Option<ClusterLock>> {
+        let key = self.key("flight", name);
+        let token = format!("{}:{}", self.node_id, uuid::Uuid::new_v4().simple());
+        let mut conn = (*self.redis).clone();
+        let set: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg(&token)
+            .arg("NX")
+            .arg("PX")
+            .arg(self.lock_ttl.as_millis() as u64)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis set: {}", e)))?;
+        Ok(set.map(|_| ClusterLock { coordinator: self.clone(), key, token }))
+    }
+
+    /// Read a cluster-wide switch
+    pub async fn flag(&self, name: &str) -> AppResult<bool> {
+        let mut conn = (*self.redis).clone();
+        redis::cmd("EXISTS")
+            .arg(self.key("flag", name))
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis exists: {}", e)))
+    }
+
+    /// Turn a cluster-wide switch on or off for every replica
+    pub async fn set_flag(&self, name: &str, on: bool) -> AppResult<()> {
+        let key = self.key("flag", name);
+        let mut conn = (*self.redis).clone();
+        let command = if on {
+            redis::cmd("SET").arg(&key).arg(&self.node_id).to_owned()
+        } else {
+            redis::cmd("DEL").arg(&key).to_owned()
+        };
+        command
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis flag: {}", e)))
+    }
+
+    /// Whether sticky routing is on for this replica
+    pub fn sticky_routing(&self) -> bool {
+        self.advertise_url.is_some()
+    }
+
+    /// Whether `header` proves a request was forwarded by another replica
+    pub fn is_forwarded(&self, header: Option<&str>) -> bool {
+        match (&self.forward_secret, header) {
+            (Some(secret), Some(header)) => crate::shared::security::constant_time_str_eq(secret, header),
+            _ => false,
+        }
+    }
+
+    /// Replica owning `key` on the current ring
+    pub fn owner(&self, key: &str) -> KeyOwner {
+        let ring = self.ring.read().unwrap_or_else(|e| e.into_inner());
+        match ring.owner(key) {
+            Some((node_id, url)) if node_id != &self.node_id => KeyOwner::Remote { node_id: node_id.clone(), url: url.clone() },
+            _ => KeyOwner::Local,
+        }
+    }
+
+    /// Announce this replica and rebuild the ring from every live member
+    pub async fn heartbeat(&self) -> AppResult<()> {
+        let Some(url) = &self.advertise_url else { return Ok(()) };
+        let key = self.key("members", "ring");
+        let now = chrono::Utc::now().timestamp();
+        let stale_before = now - 3 * self.heartbeat_interval.as_secs() as i64;
+        let mut conn = (*self.redis).clone();
+        let (entries,): (Vec<String>,) = redis::pipe()
+            .atomic()
+            .cmd("ZADD").arg(&key).arg(now).arg(format!("{} {}", self.node_id, url)).ignore()
+            .cmd("ZREMRANGEBYSCORE").arg(&key).arg("-inf").arg(stale_before).ignore()
+            .cmd("ZRANGE").arg(&key).arg(0).arg(-1)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis membership: {}", e)))?;
+        let members = entries
+            .iter()
+            .filter_map(|entry| entry.split_once(' '))
+            .map(|(node_id, url)| (node_id.to_string(), url.to_string()))
+            .collect();
+        let ring = HashRing::new(members, self.virtual_nodes);
+        *self.ring.write().unwrap_or_else(|e| e.into_inner()) = ring;
+        Ok(())
+    }
+
+    /// Spawn the membership heartbeat (no-op without sticky routing)
+    pub fn start_membership(self: Arc<Self>) {
+        if !self.sticky_routing() {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.heartbeat_interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.heartbeat().await {
+                    warn!("Cluster heartbeat failed: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Send a JSON-RPC `body` to the replica at `url`; the response body on HTTP 200
+    pub async fn forward(
+        &self,
+        url: &str,
+        body: &serde_json::Value,
+        client_ip: &str,
+        authorization: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> AppResult<serde_json::Value> {
+        let mut request = self
+            .http
+            .post(url)
+            .header(FORWARD_HEADER, self.forward_secret.as_deref().unwrap_or_default())
+            .header("x-forwarded-for", client_ip)
+            .json(body);
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        if let Some(user_agent) = user_agent {
+            request = request.header("user-agent", user_agent);
+        }
+        let result = async {
+            let response = request.send().await.map_err(|e| AppError::Http(e.to_string()))?;
+            if !response.status().is_success() {
+                return Err(AppError::Http(format!("replica answered {}", response.status())));
+            }
+            response.json::<serde_json::Value>().await.map_err(|e| AppError::Json(e.to_string()))
+        }
+        .await;
+        let counter = if result.is_ok() { &self.counters.forwarded } else { &self.counters.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    /// Count a request forwarded to this replica
+    pub fn record_received(&self) {
+        self.counters.received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a cacheable miss this replica owned
+    pub fn record_owned(&self) {
+        self.counters.owned.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current sticky routing counters
+    pub fn metrics(&self) -> ClusterMetrics {
+        ClusterMetrics {
+            node_id: self.node_id.clone(),
+            members: self.ring.read().unwrap_or_else(|e| e.into_inner()).members.len(),
+            forwarded_requests: self.counters.forwarded.load(Ordering::Relaxed),
+            forward_failures: self.counters.failed.load(Ordering::Relaxed),
+            received_forwards: self.counters.received.load(Ordering::Relaxed),
+            owned_requests: self.counters.owned.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Render cluster metrics in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let m = self.metrics();
+        let mut out = String::new();
+        out.push_str("# HELP verus_cluster_members Replicas on the sticky routing hash ring\n");
+        out.push_str("# TYPE verus_cluster_members gauge\n");
+        out.push_str(&format!("verus_cluster_members {}\n", m.members));
+        out.push_str("# HELP verus_cluster_sticky_requests_total Cacheable misses by sticky routing outcome\n");
+        out.push_str("# TYPE verus_cluster_sticky_requests_total counter\n");
+        out.push_str(&format!("verus_cluster_sticky_requests_total{{outcome=\"forwarded\"}} {}\n", m.forwarded_requests));
+        out.push_str(&format!("verus_cluster_sticky_requests_total{{outcome=\"forward_failed\"}} {}\n", m.forward_failures));
+        out.push_str(&format!("verus_cluster_sticky_requests_total{{outcome=\"received\"}} {}\n", m.received_forwards));
+        out.push_str(&format!("verus_cluster_sticky_requests_total{{outcome=\"owned\"}} {}\n", m.owned_requests));
+        out
+    }
+}
+
+impl HashRing {
+    /// Ring with `virtual_nodes` points per member
+    pub fn new(mut members: Vec<(String, String)>, virtual_nodes: u32) -> Self {
+        members.sort();
+        members.dedup_by(|a, b| a.0 == b.0);
+        let mut points = BTreeMap::new();
+        for (index, (node_id, _)) in members.iter().enumerate() {
+            for replica in 0..virtual_nodes.max(1) {
+                points.insert(ring_hash(&format!("{}#{}", node_id, replica)), index);
+            }
+        }
+        Self { points, members }
+    }
+
+    /// `(node_id, url)` owning `key`: the first point at or after its hash, wrapping around
+    pub fn owner(&self, key: &str) -> Option<&(String, String)> {
+        let hash = ring_hash(key);
+        let (_, index) = self.points.range(hash..).next().or_else(|| self.points.iter().next())?;
+        self.members.get(*index)
+    }
+}
+
+/// Position on the ring; identical on every replica regardless of build
+fn ring_hash(value: &str) -> u64 {
+    let digest = blake3::hash(value.as_bytes());
+    u64::from_be_bytes(digest.as_bytes()[..8].try_into().unwrap_or_default())
+}
+
+impl ClusterLock {
+    /// Release the lock if it is still ours
+    pub async fn release(self) {
+        let mut conn = (*self.coordinator.redis).clone();
+        let released: Result<i64, _> = redis::Script::new(RELEASE_SCRIPT)
+            .key(&self.key)
+            .arg(&self.token)
+            .invoke_async(&mut conn)
+            .await;
+        match released {
+            Ok(0) => debug!(key = %self.key, "single-flight lock expired before release"),
+            Ok(_) => {}
+            Err(e) => warn!(key = %self.key, "single-flight lock release failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(ids: &[&str]) -> Vec<(String, String)> {
+        ids.iter().map(|id| (id.to_string(), format!("http://{}:8080", id))).collect()
+    }
+
+    #[test]
+    fn test_hash_ring_moves_few_keys_when_a_member_joins() {
+        let keys: Vec<String> = (0..2000).map(|i| format!("verus:getblock:{}", i)).collect();
+        let before = HashRing::new(members(&["a", "b", "c"]), 64);
+        let after = HashRing::new(members(&["c", "a", "b", "d"]), 64);
+        assert!(HashRing::default().owner("key").is_none());
+
+        let moved = keys.iter().filter(|k| before.owner(k) != after.owner(k)).count();
+        // Ideally a quarter of the keys move, all of them to the new member
+        assert!(moved > 300 && moved < 700, "moved {}", moved);
+        assert!(keys
+            .iter()
+            .filter(|k| before.owner(k) != after.owner(k))
+            .all(|k| after.owner(k).unwrap().0 == "d"));
+        assert_eq!(before.owner("same-key"), HashRing::new(members(&["c", "b", "a"]), 64).owner("same-key"));
+    }
+
+    #[tokio::test]
+    async fn test_connect_is_a_no_op_unless_enabled() {
+        assert!(ClusterCoordinator::connect(&AppConfig::default()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_connect_fails_without_redis() {
+        let mut config = AppConfig::default();
+        config.cluster.enabled = true;
+        config.cluster.redis_url = Some("redis://127.0.0.1:1".to_string());
+        let result = ClusterCoordinator::connect(&config).await;
+        assert!(matches!(result, Err(AppError::Config(_))));
+
+        config.cluster.sticky_routing = true;
+        let result = ClusterCoordinator::connect(&config).await;
+        assert!(matches!(result, Err(AppError::Config(message)) if message.contains("advertise_url")));
+    }
+}
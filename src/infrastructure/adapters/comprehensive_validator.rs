@@ -1,9 +1,12 @@
-/// Comprehensive method validator for RPC requests
-/// 
-/// This validator provides detailed parameter validation for all Verus RPC methods,
-/// ensuring type safety and parameter constraints are enforced before requests
-/// are forwarded to the daemon.
-
+//! Comprehensive method validator for RPC requests
+//! 
+//! This validator provides detailed parameter validation for all Verus RPC methods,
+//! ensuring type safety and parameter constraints are enforced before requests
+//! are forwarded to the daemon.
+
+use crate::config::app_config::ValidationCacheConfig;
+use crate::domain::validation::MethodRegistry;
+use crate::infrastructure::adapters::validation_cache::ValidationCache;
 use crate::shared::error::{AppError, AppResult};
 use serde_json::{Value, value::RawValue};
 use std::collections::HashMap;
@@ -11,15 +14,20 @@ use std::collections::HashMap;
 pub struct ComprehensiveValidator {
     /// Cache for compiled validation rules
     validation_cache: HashMap<String, ValidationRule>,
+    /// Outcomes of earlier requests by parameter shape
+    outcomes: ValidationCache,
 }
 
+/// Custom parameter check for a method
+type CustomValidator = fn(&[Box<RawValue>]) -> bool;
+
 /// Validation rule for a method
 #[derive(Debug, Clone)]
 struct ValidationRule {
     /// Expected parameter types
     expected_types: Vec<ParameterType>,
     /// Custom validation function
-    custom_validator: Option<fn(&[Box<RawValue>]) -> bool>,
+    custom_validator: Option<CustomValidator>,
 }
 
 /// Parameter types for validation
@@ -37,8 +45,14 @@ enum ParameterType {
 impl ComprehensiveValidator {
     /// Create a new validator
     pub fn new() -> Self {
+        Self::from_config(&ValidationCacheConfig::default())
+    }
+
+    /// Create a validator remembering outcomes as `[validation_cache]` says
+    pub fn from_config(config: &ValidationCacheConfig) -> Self {
         let mut validator = Self {
             validation_cache: HashMap::new(),
+            outcomes: ValidationCache::new(config),
         };
         
         // Initialize validation rules for all supported methods
@@ -49,28 +63,41 @@ impl ComprehensiveValidator {
 
     /// Validate a method and its parameters
     pub fn validate_method(&self, method: &str, params: &Option<Value>) -> AppResult<()> {
-        // Convert params to the format expected by the validation logic
-        let raw_params = if let Some(params) = params {
-            if let Some(array) = params.as_array() {
+        // Disabled at startup when the daemon lacks a required index
+        if MethodRegistry::unavailable_reason(method).is_some() {
+            return Err(AppError::MethodNotAllowed {
+                method: method.to_string(),
+            });
+        }
+
+        let array: &[Value] = match params {
+            Some(Value::Array(array)) => array,
+            Some(_) => {
+                return Err(AppError::InvalidParameters {
+                    method: method.to_string(),
+                    reason: "Parameters must be an array".to_string(),
+                });
+            }
+            None => &[],
+        };
+
+        // Same method and parameter shape as an earlier request: same outcome
+        let allowed = match self.outcomes.outcome(method, array) {
+            Some(allowed) => allowed,
+            None => {
+                // Convert params to the format expected by the validation logic
                 let raw_params: Vec<Box<RawValue>> = array
                     .iter()
                     .map(|v| RawValue::from_string(v.to_string())
                         .map_err(|e| AppError::Internal(format!("Failed to create raw value: {}", e))))
                     .collect::<AppResult<Vec<Box<RawValue>>>>()?;
-                Some(raw_params)
-            } else {
-                return Err(AppError::InvalidParameters {
-                    method: method.to_string(),
-                    reason: "Parameters must be an array".to_string(),
-                });
+                let allowed = self.is_method_allowed(method, &raw_params);
+                self.outcomes.remember(method, array, allowed);
+                allowed
             }
-        } else {
-            None
         };
 
-        let params_slice = raw_params.as_deref().unwrap_or(&[]);
-        
-        if !self.is_method_allowed(method, params_slice) {
+        if !allowed {
             return Err(AppError::MethodNotAllowed {
                 method: method.to_string(),
             });
@@ -164,6 +191,7 @@ impl ComprehensiveValidator {
             "help" => self.check_params(params, &[]),
             "listcurrencies" => self.check_params(params, &[ParameterType::Object, ParameterType::Integer, ParameterType::Integer]),
             "sendrawtransaction" => self.check_params(params, &[ParameterType::String]),
+            "testrawtransaction" => self.check_params(params, &[ParameterType::String, ParameterType::Boolean]),
             "submitacceptednotarization" => self.check_params(params, &[ParameterType::Object, ParameterType::Object]),
             "submitimports" => self.check_params(params, &[ParameterType::Object]),
             "verifymessage" => self.check_params(params, &[ParameterType::String, ParameterType::String, ParameterType::String, ParameterType::Boolean]),
@@ -181,7 +209,9 @@ impl ComprehensiveValidator {
             "z_importkey" => self.check_params(params, &[ParameterType::String, ParameterType::String]),
             "z_exportviewingkey" => self.check_params(params, &[ParameterType::String]),
             "z_importviewingkey" => self.check_params(params, &[ParameterType::String, ParameterType::String]),
-            _ => false,
+            // Read-only methods auto-registered from the daemon's help output
+            _ => MethodRegistry::discovered_method(method)
+                .is_some_and(|definition| params.len() <= definition.parameter_rules.len()),
         }
     }
 
@@ -247,12 +277,8 @@ impl ComprehensiveValidator {
                     return false;
                 }
                 
-                if let Ok(value) = serde_json::from_str::<Value>(&params[0].to_string()) {
-                    if let Value::Object(obj) = value {
-                        !obj.contains_key("address")
-                    } else {
-                        false
-                    }
+                if let Ok(Value::Object(obj)) = serde_json::from_str::<Value>(&params[0].to_string()) {
+                    !obj.contains_key("address")
                 } else {
                     false
                 }
@@ -285,12 +311,8 @@ impl ComprehensiveValidator {
             return false;
         }
         
-        if let Ok(value) = serde_json::from_str::<Value>(&params[0].to_string()) {
-            if let Value::Object(obj) = value {
-                !obj.contains_key("address")
-            } else {
-                false
-            }
+        if let Ok(Value::Object(obj)) = serde_json::from_str::<Value>(&params[0].to_string()) {
+            !obj.contains_key("address")
         } else {
             false
         }
@@ -300,7 +322,7 @@ impl ComprehensiveValidator {
     fn validate_recoveridentity(&self, params: &[Box<RawValue>]) -> bool {
         params.get(1)
             .and_then(|p| serde_json::from_str::<Value>(&p.to_string()).ok())
-            .map_or(false, |v| v.as_bool().unwrap_or(false)) 
+            .is_some_and(|v| v.as_bool().unwrap_or(false)) 
             && self.check_params(params, &[ParameterType::Object, ParameterType::Boolean, ParameterType::Boolean, ParameterType::Float, ParameterType::String])
     }
 
@@ -308,7 +330,7 @@ impl ComprehensiveValidator {
     fn validate_registeridentity(&self, params: &[Box<RawValue>]) -> bool {
         params.get(1)
             .and_then(|p| serde_json::from_str::<Value>(&p.to_string()).ok())
-            .map_or(false, |v| v.as_bool().unwrap_or(false)) 
+            .is_some_and(|v| v.as_bool().unwrap_or(false)) 
             && self.check_params(params, &[ParameterType::Object, ParameterType::Boolean, ParameterType::Float, ParameterType::String])
     }
 
@@ -316,7 +338,7 @@ impl ComprehensiveValidator {
     fn validate_revokeidentity(&self, params: &[Box<RawValue>]) -> bool {
         params.get(1)
             .and_then(|p| serde_json::from_str::<Value>(&p.to_string()).ok())
-            .map_or(false, |v| v.as_bool().unwrap_or(false)) 
+            .is_some_and(|v| v.as_bool().unwrap_or(false)) 
             && self.check_params(params, &[ParameterType::String, ParameterType::Boolean, ParameterType::Boolean, ParameterType::Float, ParameterType::String])
     }
 
@@ -324,7 +346,7 @@ impl ComprehensiveValidator {
     fn validate_updateidentity(&self, params: &[Box<RawValue>]) -> bool {
         params.get(1)
             .and_then(|p| serde_json::from_str::<Value>(&p.to_string()).ok())
-            .map_or(false, |v| v.as_bool().unwrap_or(false)) 
+            .is_some_and(|v| v.as_bool().unwrap_or(false)) 
             && self.check_params(params, &[ParameterType::Object, ParameterType::Boolean, ParameterType::Boolean, ParameterType::Float, ParameterType::String])
     }
 
@@ -332,7 +354,7 @@ impl ComprehensiveValidator {
     fn validate_setidentitytimelock(&self, params: &[Box<RawValue>]) -> bool {
         params.get(2)
             .and_then(|p| serde_json::from_str::<Value>(&p.to_string()).ok())
-            .map_or(false, |v| v.as_bool().unwrap_or(false)) 
+            .is_some_and(|v| v.as_bool().unwrap_or(false)) 
             && self.check_params(params, &[ParameterType::String, ParameterType::Object, ParameterType::Boolean, ParameterType::Float, ParameterType::String])
     }
 
@@ -340,7 +362,7 @@ impl ComprehensiveValidator {
     fn validate_sendcurrency(&self, params: &[Box<RawValue>]) -> bool {
         params.get(4)
             .and_then(|p| serde_json::from_str::<Value>(&p.to_string()).ok())
-            .map_or(false, |v| v.as_bool().unwrap_or(false)) 
+            .is_some_and(|v| v.as_bool().unwrap_or(false)) 
             && self.check_params(params, &[ParameterType::String, ParameterType::Array, ParameterType::Integer, ParameterType::Float, ParameterType::Boolean])
     }
 
@@ -372,10 +394,8 @@ impl ComprehensiveValidator {
         }
         
         // Check optional expiry parameter if present
-        if params.len() > 6 {
-            if !self.check_params(&params[6..7], &[ParameterType::Number]) {
-                return false;
-            }
+        if params.len() > 6 && !self.check_params(&params[6..7], &[ParameterType::Number]) {
+            return false;
         }
         
         true
@@ -399,16 +419,12 @@ impl ComprehensiveValidator {
         }
         
         // Check optional minconf and fee parameters if present
-        if params.len() > 2 {
-            if !self.check_params(&params[2..3], &[ParameterType::Integer]) {
-                return false;
-            }
+        if params.len() > 2 && !self.check_params(&params[2..3], &[ParameterType::Integer]) {
+            return false;
         }
         
-        if params.len() > 3 {
-            if !self.check_params(&params[3..4], &[ParameterType::Float]) {
-                return false;
-            }
+        if params.len() > 3 && !self.check_params(&params[3..4], &[ParameterType::Float]) {
+            return false;
         }
         
         true
@@ -448,8 +464,11 @@ impl ComprehensiveValidator {
     }
 
     /// Clear validation cache (useful for testing or cache invalidation)
+    ///
+    /// Remembered outcomes go too, since they were decided by the cleared rules.
     pub fn clear_cache(&mut self) {
         self.validation_cache.clear();
+        self.outcomes.clear();
     }
 }
 
@@ -496,6 +515,15 @@ mod tests {
         assert!(validator.validate_method("getblock", &params).is_err());
     }
 
+    #[test]
+    fn test_testrawtransaction_params() {
+        let validator = ComprehensiveValidator::new();
+        let params = Some(serde_json::json!(["0400008085202f89", true]));
+        assert!(validator.validate_method("testrawtransaction", &params).is_ok());
+        let params = Some(serde_json::json!(["0400008085202f89", "yes"]));
+        assert!(validator.validate_method("testrawtransaction", &params).is_err());
+    }
+
     #[test]
     fn test_fundrawtransaction_validation() {
         let validator = ComprehensiveValidator::new();
@@ -533,6 +561,21 @@ mod tests {
         assert_eq!(new_cache_size, 0);
     }
 
+    #[test]
+    fn test_repeated_shapes_reuse_the_outcome() {
+        let validator = ComprehensiveValidator::new();
+        let first = Some(serde_json::json!(["0400008085202f89", true]));
+        let second = Some(serde_json::json!(["0500008085202f89", false]));
+        assert!(validator.validate_method("testrawtransaction", &first).is_ok());
+        assert!(validator.validate_method("testrawtransaction", &second).is_ok());
+        assert_eq!(validator.outcomes.len(), 2);
+
+        // An outcome is only reused for the shape it was decided on
+        assert!(validator.validate_method("testrawtransaction", &Some(serde_json::json!(["00", "yes"]))).is_err());
+        assert!(validator.validate_method("testrawtransaction", &first).is_ok());
+        assert_eq!(validator.outcomes.len(), 3);
+    }
+
     #[test]
     fn test_number_variant_usage() {
         let validator = ComprehensiveValidator::new();
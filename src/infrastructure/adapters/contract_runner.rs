@@ -0,0 +1,181 @@
+//! Runs contract cases against a deployment
+//!
+//! Each case is sent as a JSON-RPC request and the answer classified as
+//! accepted (a result, or an error from the daemon) or rejected (unknown
+//! method or invalid parameters, i.e. JSON-RPC -32600/-32601/-32602 or an
+//! HTTP 400/404/405/413 without a JSON-RPC error). Answers that are neither,
+//! such as authentication failures, rate limiting or transport errors, are
+//! reported as failures rather than guessed at.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::domain::validation::{ContractCase, Expectation};
+
+/// JSON-RPC codes meaning the proxy refused the call itself
+const REJECTION_CODES: &[i64] = &[-32600, -32601, -32602];
+
+/// How the deployment answered a case
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Observed {
+    Accepted,
+    Rejected { code: i64, message: String },
+    /// Neither accepted nor rejected, e.g. 401, 429 or a connection error
+    Failed { reason: String },
+}
+
+impl Observed {
+    fn matches(&self, expect: Expectation) -> bool {
+        matches!(
+            (self, expect),
+            (Observed::Accepted, Expectation::Accepted) | (Observed::Rejected { .. }, Expectation::Rejected)
+        )
+    }
+}
+
+/// Outcome of one case
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    #[serde(flatten)]
+    pub case: ContractCase,
+    pub observed: Observed,
+    pub matches: bool,
+}
+
+/// Outcome of a run
+#[derive(Debug, Clone, Serialize)]
+pub struct ContractReport {
+    pub cases: usize,
+    pub mismatches: usize,
+    pub results: Vec<CaseResult>,
+}
+
+impl ContractReport {
+    /// Results that differ from the expectation
+    pub fn mismatched(&self) -> impl Iterator<Item = &CaseResult> {
+        self.results.iter().filter(|result| !result.matches)
+    }
+}
+
+/// Sends contract cases to one deployment
+pub struct ContractRunner {
+    client: reqwest::Client,
+    target: String,
+    bearer_token: Option<String>,
+    api_key: Option<String>,
+    forwarded_for: Option<String>,
+}
+
+impl ContractRunner {
+    /// Runner for the JSON-RPC endpoint at `target`
+    pub fn new(target: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+            target: target.into(),
+            bearer_token: None,
+            api_key: None,
+            forwarded_for: None,
+        }
+    }
+
+    /// Send `Authorization: Bearer <token>` with every case
+    pub fn with_bearer_token(mut self, token: Option<String>) -> Self {
+        self.bearer_token = token;
+        self
+    }
+
+    /// Send `X-API-Key` with every case
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Send `X-Forwarded-For`, needed when talking to the proxy without its reverse proxy
+    pub fn with_forwarded_for(mut self, address: Option<String>) -> Self {
+        self.forwarded_for = address;
+        self
+    }
+
+    /// Run `cases` in order
+    pub async fn run(&self, cases: &[ContractCase]) -> ContractReport {
+        let mut results = Vec::with_capacity(cases.len());
+        for (id, case) in cases.iter().enumerate() {
+            let observed = self.send(case, id).await;
+            let matches = observed.matches(case.expect);
+            results.push(CaseResult { case: case.clone(), observed, matches });
+        }
+        ContractReport {
+            cases: results.len(),
+            mismatches: results.iter().filter(|result| !result.matches).count(),
+            results,
+        }
+    }
+
+    async fn send(&self, case: &ContractCase, id: usize) -> Observed {
+        let mut request = self
+            .client
+            .post(&self.target)
+            .json(&json!({"jsonrpc": "2.0", "method": case.method, "params": case.params, "id": id}));
+        if let Some(token) = &self.bearer_token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-api-key", api_key);
+        }
+        if let Some(address) = &self.forwarded_for {
+            request = request.header("x-forwarded-for", address);
+        }
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => return Observed::Failed { reason: e.to_string() },
+        };
+        let status = response.status().as_u16();
+        let body = response.json::<Value>().await.unwrap_or(Value::Null);
+        classify(status, &body)
+    }
+}
+
+/// Classify an HTTP status and JSON-RPC body
+pub fn classify(status: u16, body: &Value) -> Observed {
+    if let Some(error) = body.get("error").filter(|error| !error.is_null()) {
+        let code = error.get("code").and_then(Value::as_i64).unwrap_or_default();
+        let message = error.get("message").and_then(Value::as_str).unwrap_or_default().to_string();
+        if REJECTION_CODES.contains(&code) {
+            return Observed::Rejected { code, message };
+        }
+        return match status {
+            401 | 403 | 429 | 503 => Observed::Failed { reason: format!("HTTP {}: {}", status, message) },
+            // Passed validation; the daemon (or the call itself) failed
+            _ => Observed::Accepted,
+        };
+    }
+    match status {
+        200..=299 if body.get("result").is_some() => Observed::Accepted,
+        400 | 404 | 405 | 413 => Observed::Rejected { code: 0, message: format!("HTTP {}", status) },
+        _ => Observed::Failed { reason: format!("HTTP {} without a JSON-RPC result", status) },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(200, &json!({"result": 5, "error": null, "id": 1})), Observed::Accepted);
+        assert!(matches!(
+            classify(400, &json!({"error": {"code": -32602, "message": "Invalid parameters"}})),
+            Observed::Rejected { code: -32602, .. }
+        ));
+        // Daemon errors mean validation let the call through
+        assert_eq!(classify(500, &json!({"error": {"code": -32603, "message": "Block not found"}})), Observed::Accepted);
+        assert!(matches!(classify(429, &json!({"error": {"code": -32000, "message": "Rate limited"}})), Observed::Failed { .. }));
+        assert!(matches!(classify(502, &Value::Null), Observed::Failed { .. }));
+    }
+}
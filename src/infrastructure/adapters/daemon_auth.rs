@@ -0,0 +1,140 @@
+//! Daemon RPC credential resolution
+//!
+//! Resolves the basic-auth credentials used for the Verus daemon, either from
+//! static configuration or from the daemon's `.cookie` file. The cookie is
+//! re-read whenever the file changes (verusd rewrites it on every restart) or
+//! after the daemon rejects the current credentials.
+
+use crate::{
+    config::app_config::{DaemonAuthMethod, VerusConfig},
+    shared::error::{AppError, AppResult},
+};
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tokio::sync::RwLock;
+use tracing::info;
+
+/// Credentials read from a cookie file
+#[derive(Debug, Clone)]
+struct CookieCredentials {
+    user: String,
+    password: String,
+    modified: Option<SystemTime>,
+}
+
+/// Resolves daemon credentials for the configured auth method
+#[derive(Debug)]
+pub struct DaemonAuth {
+    method: DaemonAuthMethod,
+    user: String,
+    password: String,
+    cookie_path: Option<PathBuf>,
+    cookie: RwLock<Option<CookieCredentials>>,
+}
+
+impl DaemonAuth {
+    /// Create a credential resolver from the daemon configuration
+    pub fn new(config: &VerusConfig) -> Self {
+        Self {
+            method: config.auth_method,
+            user: config.rpc_user.clone(),
+            password: config.rpc_password.clone(),
+            cookie_path: config.cookie_file.as_deref().map(expand_home),
+            cookie: RwLock::new(None),
+        }
+    }
+
+    /// Current username and password for the daemon
+    pub async fn credentials(&self) -> AppResult<(String, String)> {
+        match self.method {
+            DaemonAuthMethod::Password => Ok((self.user.clone(), self.password.clone())),
+            DaemonAuthMethod::Cookie => self.cookie_credentials().await,
+        }
+    }
+
+    /// Drop cached cookie credentials so the next request re-reads the file
+    pub async fn invalidate(&self) {
+        if self.method == DaemonAuthMethod::Cookie {
+            *self.cookie.write().await = None;
+        }
+    }
+
+    async fn cookie_credentials(&self) -> AppResult<(String, String)> {
+        let path = self
+            .cookie_path
+            .as_ref()
+            .ok_or_else(|| AppError::Config("cookie auth selected but no cookie_file configured".into()))?;
+        let modified = tokio::fs::metadata(path).await.ok().and_then(|m| m.modified().ok());
+
+        if let Some(cached) = self.cookie.read().await.as_ref() {
+            if cached.modified == modified {
+                return Ok((cached.user.clone(), cached.password.clone()));
+            }
+        }
+
+        let contents = tokio::fs::read_to_string(path).await.map_err(|e| {
+            AppError::Config(format!("Failed to read daemon cookie file {}: {}", path.display(), e))
+        })?;
+        let (user, password) = parse_cookie(&contents)
+            .ok_or_else(|| AppError::Config(format!("Malformed daemon cookie file {}", path.display())))?;
+
+        info!(path = %path.display(), "Loaded daemon cookie credentials");
+        *self.cookie.write().await = Some(CookieCredentials {
+            user: user.clone(),
+            password: password.clone(),
+            modified,
+        });
+        Ok((user, password))
+    }
+}
+
+/// Parse `user:password` cookie contents
+fn parse_cookie(contents: &str) -> Option<(String, String)> {
+    let (user, password) = contents.trim().split_once(':')?;
+    if user.is_empty() || password.is_empty() {
+        return None;
+    }
+    Some((user.to_string(), password.to_string()))
+}
+
+/// Expand a leading `~/` using `$HOME`
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    #[test]
+    fn test_parse_cookie() {
+        assert_eq!(
+            parse_cookie("__cookie__:abc123\n"),
+            Some(("__cookie__".to_string(), "abc123".to_string()))
+        );
+        assert_eq!(parse_cookie("no-separator"), None);
+    }
+
+    #[tokio::test]
+    async fn test_cookie_rotation_is_picked_up() {
+        let path = std::env::temp_dir().join(format!("verus-rpc-cookie-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "__cookie__:first").unwrap();
+
+        let mut config = AppConfig::default();
+        config.verus.auth_method = DaemonAuthMethod::Cookie;
+        config.verus.cookie_file = Some(path.to_string_lossy().to_string());
+        let auth = DaemonAuth::new(&config.verus);
+
+        assert_eq!(auth.credentials().await.unwrap().1, "first");
+
+        std::fs::write(&path, "__cookie__:second").unwrap();
+        auth.invalidate().await;
+        assert_eq!(auth.credentials().await.unwrap().1, "second");
+
+        std::fs::remove_file(&path).ok();
+    }
+}
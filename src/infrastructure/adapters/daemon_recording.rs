@@ -0,0 +1,260 @@
+//! Record-and-replay of daemon interactions
+//!
+//! In `record` mode every upstream call and its outcome (result or daemon
+//! error) is appended to a JSON Lines file; transport failures are not
+//! recorded. In `replay` mode the daemon adapter answers from that file
+//! instead of contacting the daemon, which allows client development
+//! without a node and deterministic integration tests of the proxy.
+//!
+//! Calls are matched on method and parameters. A call recorded several times
+//! replays its outcomes in order and then keeps returning the last one. The
+//! values of `scrub_keys` are replaced in params and results before anything
+//! is written, and lookups are scrubbed the same way so they still match.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+use crate::config::app_config::{RecordingConfig, RecordingMode};
+use crate::domain::rpc::RpcRequest;
+use crate::shared::error::{AppError, AppResult};
+
+const SCRUBBED: &str = "[scrubbed]";
+
+/// One line of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub method: String,
+    pub params: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl RecordedCall {
+    fn outcome(&self) -> AppResult<Value> {
+        match &self.error {
+            Some(error) => Err(AppError::Rpc(error.clone())),
+            None => Ok(self.result.clone().unwrap_or(Value::Null)),
+        }
+    }
+}
+
+/// Recorded outcomes of one call, replayed in order
+#[derive(Debug, Default)]
+struct Tape {
+    calls: Vec<RecordedCall>,
+    next: usize,
+}
+
+/// Recorder or player shared by a server's daemon adapters
+pub struct DaemonRecording {
+    mode: RecordingMode,
+    scrub_keys: Vec<String>,
+    writer: tokio::sync::Mutex<Option<tokio::fs::File>>,
+    tapes: Mutex<HashMap<String, Tape>>,
+}
+
+impl DaemonRecording {
+    /// Open the recording for `[recording]`; `None` when the mode is `off`
+    pub fn from_config(config: &RecordingConfig) -> AppResult<Option<Self>> {
+        if config.mode == RecordingMode::Off {
+            return Ok(None);
+        }
+        Self::open(config)
+            .map(Some)
+            .map_err(|e| AppError::Config(format!("Cannot open recording {}: {}", config.path, e)))
+    }
+
+    fn open(config: &RecordingConfig) -> io::Result<Self> {
+        let path = Path::new(&config.path);
+        let mut recording = Self {
+            mode: config.mode,
+            scrub_keys: config.scrub_keys.iter().map(|key| key.to_lowercase()).collect(),
+            writer: tokio::sync::Mutex::new(None),
+            tapes: Mutex::new(HashMap::new()),
+        };
+        match config.mode {
+            RecordingMode::Off => {}
+            RecordingMode::Record => {
+                if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+                recording.writer = tokio::sync::Mutex::new(Some(tokio::fs::File::from_std(file)));
+                info!(path = %config.path, "Recording daemon calls");
+            }
+            RecordingMode::Replay => {
+                let contents = std::fs::read_to_string(path)?;
+                let mut tapes: HashMap<String, Tape> = HashMap::new();
+                let mut count = 0;
+                for (line_number, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                    let call: RecordedCall = serde_json::from_str(line).map_err(|e| {
+                        io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line_number + 1, e))
+                    })?;
+                    tapes.entry(key(&call.method, &call.params)).or_default().calls.push(call);
+                    count += 1;
+                }
+                recording.tapes = Mutex::new(tapes);
+                info!(path = %config.path, calls = count, "Replaying recorded daemon responses; the daemon is not contacted");
+            }
+        }
+        Ok(recording)
+    }
+
+    /// Whether calls are answered from the recording
+    pub fn is_replay(&self) -> bool {
+        self.mode == RecordingMode::Replay
+    }
+
+    /// Recorded outcome for `request`
+    pub fn replay(&self, request: &RpcRequest) -> AppResult<Value> {
+        let params = self.scrubbed_params(request);
+        let mut tapes = self.tapes.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(tape) = tapes.get_mut(&key(&request.method, &params)) else {
+            warn!(method = %request.method, params = %params, "No recorded response for call");
+            return Err(AppError::Rpc(format!("No recorded response for {} with these parameters", request.method)));
+        };
+        let index = tape.next.min(tape.calls.len() - 1);
+        tape.next = (tape.next + 1).min(tape.calls.len());
+        tape.calls[index].outcome()
+    }
+
+    /// Append `request` and its outcome; only results and errors returned by the daemon are kept
+    pub async fn record(&self, request: &RpcRequest, outcome: Result<&Value, &AppError>) {
+        if self.mode != RecordingMode::Record {
+            return;
+        }
+        let (result, error) = match outcome {
+            Ok(result) => {
+                let mut result = result.clone();
+                scrub(&mut result, &self.scrub_keys);
+                (Some(result), None)
+            }
+            Err(AppError::Rpc(message)) if message.starts_with("RPC error") => (None, Some(message.clone())),
+            Err(_) => return,
+        };
+        let call = RecordedCall {
+            method: request.method.clone(),
+            params: self.scrubbed_params(request),
+            result,
+            error,
+            recorded_at: chrono::Utc::now(),
+        };
+        let Ok(mut line) = serde_json::to_vec(&call) else {
+            return;
+        };
+        line.push(b'\n');
+        let mut writer = self.writer.lock().await;
+        if let Some(file) = writer.as_mut() {
+            if let Err(e) = async { file.write_all(&line).await?; file.flush().await }.await {
+                warn!(method = %request.method, "Failed to record daemon call: {}", e);
+            }
+        }
+    }
+
+    fn scrubbed_params(&self, request: &RpcRequest) -> Value {
+        let mut params = request.parameters.clone().unwrap_or_else(|| Value::Array(Vec::new()));
+        scrub(&mut params, &self.scrub_keys);
+        params
+    }
+}
+
+/// Lookup key: method plus canonical (key-sorted) params
+fn key(method: &str, params: &Value) -> String {
+    format!("{}\n{}", method, params)
+}
+
+/// Replace the values of `keys` (lowercase) anywhere in `value`
+pub(crate) fn scrub(value: &mut Value, keys: &[String]) {
+    match value {
+        Value::Object(object) => {
+            for (name, field) in object.iter_mut() {
+                if keys.iter().any(|key| *key == name.to_lowercase()) {
+                    *field = Value::String(SCRUBBED.to_string());
+                } else {
+                    scrub(field, keys);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| scrub(item, keys)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::rpc::ClientInfo;
+    use serde_json::json;
+
+    fn request(method: &str, params: Value) -> RpcRequest {
+        RpcRequest {
+            method: method.to_string(),
+            parameters: Some(params),
+            id: Some(json!(1)),
+            client_info: ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: None,
+                auth_token: None,
+                timestamp: chrono::Utc::now(),
+                request_id: None,
+            },
+            upstream: None,
+        }
+    }
+
+    fn config(mode: RecordingMode, path: &Path) -> RecordingConfig {
+        RecordingConfig { mode, path: path.to_string_lossy().into_owned(), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay_in_order() {
+        let path = std::env::temp_dir().join(format!("verus-rpc-recording-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = DaemonRecording::open(&config(RecordingMode::Record, &path)).unwrap();
+        let count = request("getblockcount", json!([]));
+        recorder.record(&count, Ok(&json!(100))).await;
+        recorder.record(&count, Ok(&json!(101))).await;
+        let missing = request("getrawtransaction", json!(["00", 1]));
+        recorder.record(&missing, Err(&AppError::Rpc("RPC error: {\"code\":-5}".to_string()))).await;
+        recorder.record(&missing, Err(&AppError::Rpc("Request failed: connection refused".to_string()))).await;
+        drop(recorder);
+
+        let player = DaemonRecording::open(&config(RecordingMode::Replay, &path)).unwrap();
+        assert_eq!(player.replay(&count).unwrap(), json!(100));
+        assert_eq!(player.replay(&count).unwrap(), json!(101));
+        assert_eq!(player.replay(&count).unwrap(), json!(101));
+        assert!(matches!(player.replay(&missing), Err(AppError::Rpc(message)) if message.contains("-5")));
+        assert!(matches!(player.replay(&missing), Err(AppError::Rpc(message)) if message.contains("-5")));
+        assert!(player.replay(&request("getinfo", json!([]))).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_secrets_are_scrubbed_and_still_match() {
+        let path = std::env::temp_dir().join(format!("verus-rpc-recording-scrub-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = DaemonRecording::open(&config(RecordingMode::Record, &path)).unwrap();
+        let import = request("z_importviewingkey", json!([{"viewingKey": "zxviews1secret"}]));
+        recorder.record(&import, Ok(&json!({"address": "zs1abc", "privkey": "secret"}))).await;
+        drop(recorder);
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("secret"));
+        let player = DaemonRecording::open(&config(RecordingMode::Replay, &path)).unwrap();
+        let replayed = player.replay(&import).unwrap();
+        assert_eq!(replayed, json!({"address": "zs1abc", "privkey": SCRUBBED}));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
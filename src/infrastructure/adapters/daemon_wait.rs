@@ -0,0 +1,115 @@
+//! Waiting for the daemon at cold start
+//!
+//! Under docker-compose and similar orchestrators the proxy often starts
+//! before the daemon accepts RPC calls. With `[daemon_wait]` enabled the
+//! server starts serving anyway and retries the daemon with exponential
+//! backoff; until it answers, every upstream call fails fast with
+//! `AppError::UpstreamInitializing` (HTTP 503). If the daemon has not answered
+//! within `max_wait_seconds` the server stops with an error.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config::app_config::DaemonWaitConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+
+/// Delay before the second attempt; doubled after each failure
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Startup readiness of the daemon
+#[derive(Debug, Default)]
+pub struct DaemonWait {
+    initializing: AtomicBool,
+}
+
+impl DaemonWait {
+    /// Readiness shared by all RPC adapters
+    pub fn global() -> &'static DaemonWait {
+        static WAIT: OnceLock<DaemonWait> = OnceLock::new();
+        WAIT.get_or_init(DaemonWait::default)
+    }
+
+    /// Whether the server is still waiting for the daemon's first answer
+    pub fn is_initializing(&self) -> bool {
+        self.initializing.load(Ordering::Relaxed)
+    }
+
+    /// Refuse upstream calls while the daemon is initializing
+    pub fn check(&self) -> AppResult<()> {
+        if self.is_initializing() {
+            return Err(AppError::UpstreamInitializing);
+        }
+        Ok(())
+    }
+
+    /// Whether the daemon answers right now; marks it initializing if not
+    pub async fn probe(&self, rpc: &ExternalRpcAdapter) -> bool {
+        let ready = rpc.probe(&probe_request()).await.is_ok();
+        self.initializing.store(!ready, Ordering::Relaxed);
+        ready
+    }
+
+    /// Retry the daemon with backoff until it answers or `max_wait_seconds` pass
+    pub async fn wait(&self, rpc: &ExternalRpcAdapter, config: &DaemonWaitConfig) -> AppResult<()> {
+        let started = Instant::now();
+        let deadline = Duration::from_secs(config.max_wait_seconds);
+        let max_backoff = Duration::from_secs(config.retry_max_seconds);
+        let mut backoff = INITIAL_BACKOFF.min(max_backoff);
+        let mut attempts = 1u32;
+        warn!(max_wait_seconds = config.max_wait_seconds, "Daemon not reachable yet; serving 503 until it answers");
+        loop {
+            let remaining = deadline.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                return Err(AppError::Rpc(format!(
+                    "daemon did not answer within {} seconds ({} attempts)",
+                    config.max_wait_seconds, attempts
+                )));
+            }
+            tokio::time::sleep(backoff.min(remaining)).await;
+            attempts += 1;
+            if self.probe(rpc).await {
+                info!(attempts, waited_ms = started.elapsed().as_millis() as u64, "Daemon is up");
+                return Ok(());
+            }
+            backoff = (backoff * 2).min(max_backoff);
+        }
+    }
+}
+
+fn probe_request() -> RpcRequest {
+    RpcRequest::new(
+        "getblockcount".to_string(),
+        Some(json!([])),
+        Some(json!("daemon_wait_getblockcount")),
+        ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: Some("daemon-wait".to_string()),
+            auth_token: None,
+            timestamp: Utc::now(),
+            request_id: None,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initializing_refuses_upstream_calls() {
+        let wait = DaemonWait::default();
+        assert!(wait.check().is_ok());
+
+        wait.initializing.store(true, Ordering::Relaxed);
+        let error = wait.check().unwrap_err();
+        assert!(matches!(error, AppError::UpstreamInitializing));
+        assert_eq!(error.http_status_code(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,200 @@
+//! On-disk cache tier for immutable responses (requires the `disk-cache` feature)
+//!
+//! Blocks, block headers and transactions looked up by hash never change, so
+//! their responses are kept in a sled database with no TTL and consulted after
+//! a memory/Redis miss. Raw (hex) forms are stored as soon as they are seen.
+//! Verbose forms are stored once `min_confirmations` deep, out of reach of
+//! reorgs; their `confirmations` field is recomputed from the current tip on
+//! every read.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+
+use crate::config::app_config::DiskCacheConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+
+/// Shape of a stored response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Form {
+    /// Hex string, byte-for-byte immutable
+    Raw,
+    /// JSON object whose `confirmations` depends on the tip
+    Verbose,
+}
+
+pub struct DiskCache {
+    db: sled::Db,
+    min_confirmations: u64,
+    max_bytes: u64,
+    /// Latest known chain height; 0 until the first poll
+    tip: AtomicU64,
+}
+
+impl DiskCache {
+    /// Open the database at `[disk_cache] path`
+    pub fn open(config: &DiskCacheConfig) -> AppResult<Self> {
+        let db = sled::open(&config.path)
+            .map_err(|e| AppError::Internal(format!("failed to open disk cache at {}: {}", config.path, e)))?;
+        info!(path = %config.path, entries = db.len(), "Disk cache tier opened");
+        Ok(DiskCache {
+            db,
+            min_confirmations: config.min_confirmations,
+            max_bytes: config.max_size_mb.saturating_mul(1024 * 1024),
+            tip: AtomicU64::new(0),
+        })
+    }
+
+    /// Track the chain tip for verbose reads
+    pub fn start_tip_poller(self: Arc<Self>, rpc: Arc<ExternalRpcAdapter>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match tip_height(&rpc).await {
+                    Ok(height) => self.tip.store(height, Ordering::Relaxed),
+                    Err(e) => debug!("Disk cache tip poll failed: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Stored response for `method(params)`, with `confirmations` brought up to date
+    pub fn get(&self, method: &str, params: &Value) -> Option<Vec<u8>> {
+        let form = classify(method, params)?;
+        let data = self.db.get(key(method, params)).ok()??;
+        match form {
+            Form::Raw => Some(data.to_vec()),
+            Form::Verbose => refresh_confirmations(&data, self.tip.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Store a serialized JSON-RPC response if it is immutable
+    pub fn put(&self, method: &str, params: &Value, data: &[u8]) {
+        let Some(form) = classify(method, params) else { return };
+        let Ok(response) = serde_json::from_slice::<Value>(data) else { return };
+        if !admissible(&response, form, self.min_confirmations) {
+            return;
+        }
+        if self.db.size_on_disk().is_ok_and(|size| size >= self.max_bytes) {
+            debug!("Disk cache is full; not storing {}", method);
+            return;
+        }
+        if let Err(e) = self.db.insert(key(method, params), data) {
+            warn!("Disk cache write failed: {}", e);
+        }
+    }
+}
+
+fn key(method: &str, params: &Value) -> Vec<u8> {
+    format!("{}:{}", method, params).into_bytes()
+}
+
+/// Form of an immutable lookup, or `None` when `method(params)` may change over time
+fn classify(method: &str, params: &Value) -> Option<Form> {
+    let params = params.as_array().filter(|params| !params.is_empty() && params.len() <= 2)?;
+    let hash = params[0].as_str()?;
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    // getblock and getblockheader default to verbose output, getrawtransaction to hex
+    let verbose_by_default = match method {
+        "getblock" | "getblockheader" => true,
+        "getrawtransaction" => false,
+        _ => return None,
+    };
+    let verbose = match params.get(1) {
+        None => verbose_by_default,
+        Some(Value::Bool(verbose)) => *verbose,
+        Some(Value::Number(level)) => level.as_u64()? > 0,
+        Some(_) => return None,
+    };
+    Some(if verbose { Form::Verbose } else { Form::Raw })
+}
+
+/// Successful responses only; verbose ones must be `min_confirmations` deep and carry a height
+fn admissible(response: &Value, form: Form, min_confirmations: u64) -> bool {
+    if !response["error"].is_null() {
+        return false;
+    }
+    let result = &response["result"];
+    match form {
+        Form::Raw => result.is_string(),
+        Form::Verbose => {
+            result["height"].is_u64() && result["confirmations"].as_u64().is_some_and(|c| c >= min_confirmations)
+        }
+    }
+}
+
+/// Verbose response with `confirmations` computed from `tip`; `None` while the tip is unknown
+fn refresh_confirmations(data: &[u8], tip: u64) -> Option<Vec<u8>> {
+    if tip == 0 {
+        return None;
+    }
+    let mut response: Value = serde_json::from_slice(data).ok()?;
+    let height = response["result"]["height"].as_u64()?;
+    response["result"]["confirmations"] = json!(tip.saturating_sub(height) + 1);
+    serde_json::to_vec(&response).ok()
+}
+
+async fn tip_height(rpc: &ExternalRpcAdapter) -> AppResult<u64> {
+    let request = RpcRequest::new(
+        "getblockcount".to_string(),
+        Some(json!([])),
+        Some(json!("disk_cache_getblockcount")),
+        ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: Some("disk-cache".to_string()),
+            auth_token: None,
+            timestamp: Utc::now(),
+            request_id: None,
+        },
+    );
+    rpc.send_request(&request)
+        .await?
+        .result
+        .and_then(|height| height.as_u64())
+        .ok_or_else(|| AppError::Rpc("getblockcount returned a non-numeric height".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "00000000000000000000000000000000000000000000000000000000000000ab";
+
+    #[test]
+    fn test_classify_immutable_lookups() {
+        assert_eq!(classify("getblock", &json!([HASH])), Some(Form::Verbose));
+        assert_eq!(classify("getblock", &json!([HASH, 0])), Some(Form::Raw));
+        assert_eq!(classify("getblockheader", &json!([HASH, false])), Some(Form::Raw));
+        assert_eq!(classify("getrawtransaction", &json!([HASH])), Some(Form::Raw));
+        assert_eq!(classify("getrawtransaction", &json!([HASH, 1])), Some(Form::Verbose));
+        // Heights and other methods can change meaning after a reorg
+        assert_eq!(classify("getblock", &json!(["1000"])), None);
+        assert_eq!(classify("getblockhash", &json!([1000])), None);
+        assert_eq!(classify("getinfo", &json!([])), None);
+    }
+
+    #[test]
+    fn test_verbose_entries_need_depth_and_fresh_confirmations() {
+        let shallow = json!({ "result": { "height": 90, "confirmations": 10 }, "error": null, "id": 1 });
+        let deep = json!({ "result": { "height": 10, "confirmations": 100 }, "error": null, "id": 1 });
+        let failed = json!({ "result": null, "error": { "code": -5 }, "id": 1 });
+        assert!(!admissible(&shallow, Form::Verbose, 100));
+        assert!(admissible(&deep, Form::Verbose, 100));
+        assert!(!admissible(&failed, Form::Raw, 100));
+        assert!(admissible(&json!({ "result": "0400", "id": 1 }), Form::Raw, 100));
+
+        let data = serde_json::to_vec(&deep).unwrap();
+        assert_eq!(refresh_confirmations(&data, 0), None);
+        let refreshed: Value = serde_json::from_slice(&refresh_confirmations(&data, 150).unwrap()).unwrap();
+        assert_eq!(refreshed["result"]["confirmations"], 141);
+    }
+}
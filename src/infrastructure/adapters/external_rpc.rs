@@ -7,6 +7,17 @@ use crate::{
     domain::rpc::*,
     shared::error::AppResult,
     config::AppConfig,
+    infrastructure::adapters::{
+        daemon_auth::DaemonAuth,
+        daemon_wait::DaemonWait,
+        daemon_recording::DaemonRecording,
+        negative_cache::NegativeCache,
+        upstream_context::UpstreamContext,
+        upstream_gate::UpstreamGate,
+        upstream_metrics::{upstream_label, UpstreamMetrics},
+        upstream_pipeline::{queued_response, Admission, PendingBatch, UpstreamPipeline},
+        upstream_resolver::UpstreamResolver,
+    },
 };
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -145,34 +156,240 @@ impl CircuitBreaker {
     }
 }
 
+/// Checks every daemon call passes through, shared by a server's adapters
+///
+/// The default has an open gate, no negative cache and no recording.
+#[derive(Clone, Default)]
+pub struct UpstreamGuards {
+    pub gate: Arc<UpstreamGate>,
+    pub negative_cache: Arc<NegativeCache>,
+    /// Present unless `[recording] mode = "off"`
+    pub recording: Option<Arc<DaemonRecording>>,
+}
+
 /// Adapter for external RPC services with circuit breaker
+///
+/// Clones share the connection pool, breaker and batch queue.
+#[derive(Clone)]
 pub struct ExternalRpcAdapter {
+    state: Arc<AdapterState>,
+    guards: UpstreamGuards,
+}
+
+/// Shared state behind an [`ExternalRpcAdapter`]
+pub struct AdapterState {
     _config: Arc<AppConfig>,
     circuit_breaker: Arc<CircuitBreaker>,
     daemon_available: AtomicBool,
+    auth: DaemonAuth,
+    resolver: UpstreamResolver,
+    client: RwLock<Option<reqwest::Client>>,
+    upstream_label: String,
+    context: UpstreamContext,
+    timeout: Duration,
+    max_retries: u32,
+    pipeline: Option<UpstreamPipeline>,
+}
+
+impl std::ops::Deref for ExternalRpcAdapter {
+    type Target = AdapterState;
+
+    fn deref(&self) -> &AdapterState {
+        &self.state
+    }
 }
 
 impl ExternalRpcAdapter {
     /// Create a new external RPC adapter
     pub fn new(config: Arc<AppConfig>) -> Self {
-        let circuit_config = config.verus.circuit_breaker
-            .as_ref()
+        let timeout = Duration::from_secs(config.verus.timeout_seconds);
+        let max_retries = config.verus.max_retries;
+        let circuit_breaker = config.verus.circuit_breaker.clone();
+        let upstream_label = upstream_label(&config.verus.rpc_url);
+        let pipeline = UpstreamPipeline::new(&config.verus.pipelining);
+        Self::with_policy(config, timeout, max_retries, circuit_breaker.as_ref(), upstream_label, pipeline)
+    }
+
+    /// Adapter for payment-critical calls (`[payments.rpc]`)
+    ///
+    /// It has its own timeout, retries, circuit breaker and connection pool,
+    /// so a burst of failing or slow general traffic neither opens the
+    /// breaker for payments nor queues payment calls behind it.
+    pub fn for_payments(config: Arc<AppConfig>) -> Self {
+        let policy = config.payments.rpc.clone();
+        let upstream_label = format!("{}/payments", upstream_label(&config.verus.rpc_url));
+        // Payment calls never wait for a batch to fill
+        Self::with_policy(
+            config,
+            Duration::from_secs(policy.timeout_seconds),
+            policy.max_retries,
+            Some(&policy.circuit_breaker),
+            upstream_label,
+            None,
+        )
+    }
+
+    /// Adapter for a named `[upstreams]` daemon
+    ///
+    /// It shares the `[verus]` timeout, retries and breaker policy but has its
+    /// own breaker state and connection pool, so one tenant's daemon failing
+    /// does not open the breaker for tenants served by another.
+    pub fn for_upstream(config: Arc<AppConfig>, upstream: &crate::config::app_config::UpstreamConfig) -> Self {
+        let mut upstream_config = (*config).clone();
+        upstream_config.verus = upstream.daemon_config(&config.verus);
+        Self::new(Arc::new(upstream_config))
+    }
+
+    /// Run calls through `guards` instead of the defaults
+    pub fn with_guards(mut self, guards: UpstreamGuards) -> Self {
+        self.guards = guards;
+        self
+    }
+
+    /// Gate, negative cache and recording this adapter's calls pass through
+    pub fn guards(&self) -> &UpstreamGuards {
+        &self.guards
+    }
+
+    fn with_policy(
+        config: Arc<AppConfig>,
+        timeout: Duration,
+        max_retries: u32,
+        circuit_breaker: Option<&crate::config::app_config::CircuitBreakerConfig>,
+        upstream_label: String,
+        pipeline: Option<UpstreamPipeline>,
+    ) -> Self {
+        let circuit_config = circuit_breaker
             .map(|cb_config| CircuitBreakerConfig {
                 failure_threshold: cb_config.failure_threshold,
                 recovery_timeout: Duration::from_secs(cb_config.recovery_timeout_seconds),
                 half_open_max_requests: cb_config.half_open_max_requests,
             })
-            .unwrap_or_else(CircuitBreakerConfig::default);
+            .unwrap_or_default();
         
-        Self {
+        let auth = DaemonAuth::new(&config.verus);
+        let resolver = UpstreamResolver::new(&config.verus);
+        let context = UpstreamContext::new(&config.upstream_context);
+        // Header mode tags a whole request, and a packed batch would mix clients
+        let pipeline = pipeline.filter(|_| !context.is_header_mode());
+
+        let state = AdapterState {
             _config: config,
             circuit_breaker: Arc::new(CircuitBreaker::new(circuit_config)),
             daemon_available: AtomicBool::new(true),
+            auth,
+            resolver,
+            client: RwLock::new(None),
+            upstream_label,
+            context,
+            timeout,
+            max_retries,
+            pipeline,
+        };
+        Self { state: Arc::new(state), guards: UpstreamGuards::default() }
+    }
+
+    /// Get the HTTP client, rebuilding it when the upstream addresses change
+    async fn http_client(&self, force_resolve: bool) -> AppResult<reqwest::Client> {
+        let resolution = match self.resolver.resolve_if_stale(force_resolve).await {
+            Ok(resolution) => resolution,
+            Err(e) => {
+                // Keep the previous addresses (or system DNS) if re-resolution fails
+                warn!("Upstream DNS refresh failed: {}", e);
+                None
+            }
+        };
+
+        if resolution.as_ref().is_none_or(|r| !r.changed) {
+            if let Some(client) = self.client.read().await.clone() {
+                return Ok(client);
+            }
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .timeout(self.timeout);
+        if let Some(resolution) = &resolution {
+            builder = builder.resolve_to_addrs(&resolution.host, &resolution.addrs);
         }
+        let client = builder
+            .build()
+            .map_err(|e| crate::shared::error::AppError::Config(format!("Failed to create HTTP client: {}", e)))?;
+        *self.client.write().await = Some(client.clone());
+        Ok(client)
     }
 
     /// Send request to external RPC service with circuit breaker protection
     pub async fn send_request(&self, request: &RpcRequest) -> AppResult<RpcResponse> {
+        self.guards.gate.check(request).await?;
+        if let Some(error) = self.guards.negative_cache.check(request) {
+            return Err(error);
+        }
+        let recording = self.guards.recording.as_deref();
+        if let Some(recording) = recording.filter(|recording| recording.is_replay()) {
+            return recording.replay(request).map(|result| RpcResponse::success(result, request.id.clone()));
+        }
+        DaemonWait::global().check()?;
+        let started = Instant::now();
+        let result = self.dispatch(request).await;
+        self.guards.negative_cache.observe(request, &result);
+        if let Some(recording) = recording {
+            let outcome = result.as_ref().map(|response| response.result.as_ref().unwrap_or(&serde_json::Value::Null));
+            recording.record(request, outcome).await;
+        }
+        UpstreamMetrics::global().record(
+            &self.upstream_label,
+            &request.method,
+            request.parameters.as_ref(),
+            started.elapsed().as_millis() as u64,
+            result.is_ok(),
+            &self._config.slow_query_log,
+        );
+        result
+    }
+
+    /// Send a call on its own, or as part of a batch while the daemon is busy
+    async fn dispatch(&self, request: &RpcRequest) -> AppResult<RpcResponse> {
+        let Some(pipeline) = &self.pipeline else {
+            return self.send_request_with_retries(request).await;
+        };
+        let (_in_flight, admission) = pipeline.admit(request);
+        let (mut reply, full) = match admission {
+            Admission::Direct => return self.send_request_with_retries(request).await,
+            Admission::Queued { reply, full } => (reply, full),
+        };
+        let batch = match full {
+            Some(batch) => batch,
+            None => tokio::select! {
+                delivered = &mut reply => return queued_response(delivered, request),
+                // Whoever waits out the delay first sends everything queued so far
+                _ = tokio::time::sleep(pipeline.max_delay()) => pipeline.take(),
+            },
+        };
+        // Sent on its own task so the other callers are answered even if this one is dropped
+        let adapter = self.clone();
+        tokio::spawn(async move { adapter.send_pending(batch).await });
+        queued_response(reply.await, request)
+    }
+
+    /// Send queued calls, as a batch when there is more than one
+    async fn send_pending(&self, batch: PendingBatch) {
+        let results = match batch.len() {
+            0 => return,
+            1 => self
+                .send_request_with_retries(&batch.requests[0])
+                .await
+                .map(|response| vec![Ok(response.result.unwrap_or(serde_json::Value::Null))]),
+            _ => self.send_batch_with_retries(&batch.requests).await,
+        };
+        batch.resolve(results);
+    }
+
+    /// Call the daemon directly, past the policy gate and startup wait; for readiness probes
+    pub(crate) async fn probe(&self, request: &RpcRequest) -> AppResult<RpcResponse> {
+        self.send_request_with_retries(request).await
+    }
+
+    async fn send_request_with_retries(&self, request: &RpcRequest) -> AppResult<RpcResponse> {
         // Check circuit breaker first
         if !self.circuit_breaker.should_allow_request().await {
             return Err(crate::shared::error::AppError::Rpc(
@@ -183,41 +400,38 @@ impl ExternalRpcAdapter {
         // Increment half-open request counter if needed
         self.circuit_breaker.increment_half_open_requests().await;
 
-        use reqwest::Client;
         use serde_json::json;
-        use std::time::Duration;
         
         info!(
             method = %request.method,
             client_ip = %request.client_info.ip_address,
+            request_id = request.client_info.request_id.as_deref().unwrap_or("-"),
             "Sending request to external RPC service"
         );
 
-        // Create HTTP client with timeout
-        let client = Client::builder()
-            .timeout(Duration::from_secs(self._config.verus.timeout_seconds))
-            .build()
-            .map_err(|e| crate::shared::error::AppError::Config(format!("Failed to create HTTP client: {}", e)))?;
-
         // Create JSON-RPC request payload
         let payload = json!({
             "jsonrpc": "2.0",
             "method": request.method,
             "params": request.parameters,
-            "id": request.id
+            "id": self.context.call_id(&request.client_info, &request.id)
         });
+        let context_header = self.context.header(&request.client_info);
 
         // Send request with retries
         let mut last_error = None;
-        for attempt in 0..=self._config.verus.max_retries {
-            match client
+        let mut force_resolve = false;
+        for attempt in 0..=self.max_retries {
+            let client = self.http_client(force_resolve).await?;
+            let (rpc_user, rpc_password) = self.auth.credentials().await?;
+            let mut upstream_request = client
                 .post(&self._config.verus.rpc_url)
                 .header("Content-Type", "application/json")
-                .basic_auth(&self._config.verus.rpc_user, Some(&self._config.verus.rpc_password))
-                .json(&payload)
-                .send()
-                .await
-            {
+                .basic_auth(&rpc_user, Some(&rpc_password));
+            if let Some((name, value)) = &context_header {
+                upstream_request = upstream_request.header(*name, value);
+            }
+            match upstream_request.json(&payload).send().await {
                 Ok(response) => {
                     if response.status().is_success() {
                         match response.json::<serde_json::Value>().await {
@@ -243,25 +457,149 @@ impl ExternalRpcAdapter {
                             }
                         }
                     } else {
+                        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                            // Cookie may have rotated; re-read it before the next attempt
+                            self.auth.invalidate().await;
+                        }
                         last_error = Some(format!("HTTP error: {}", response.status()));
                         self.circuit_breaker.record_failure().await;
                     }
                 }
                 Err(e) => {
+                    // The daemon may have moved; re-resolve before retrying
+                    force_resolve = e.is_connect();
                     last_error = Some(format!("Request failed: {}", e));
                     self.circuit_breaker.record_failure().await;
                 }
             }
             
-            if attempt < self._config.verus.max_retries {
-                info!("RPC request failed, retrying... (attempt {}/{})", attempt + 1, self._config.verus.max_retries + 1);
+            if attempt < self.max_retries {
+                info!("RPC request failed, retrying... (attempt {}/{})", attempt + 1, self.max_retries + 1);
                 tokio::time::sleep(Duration::from_millis(100 * (attempt + 1) as u64)).await;
             }
         }
 
         // Mark daemon as unavailable after all retries failed
         self.daemon_available.store(false, Ordering::Relaxed);
-        Err(crate::shared::error::AppError::Rpc(format!("RPC request failed after {} attempts: {:?}", self._config.verus.max_retries + 1, last_error)))
+        Err(crate::shared::error::AppError::Rpc(format!("RPC request failed after {} attempts: {:?}", self.max_retries + 1, last_error)))
+    }
+
+    /// Send several calls as one JSON-RPC batch; results are returned in request order
+    pub async fn send_batch(&self, requests: &[RpcRequest]) -> AppResult<Vec<AppResult<serde_json::Value>>> {
+        for request in requests {
+            self.guards.gate.check(request).await?;
+        }
+        let recording = self.guards.recording.as_deref();
+        if let Some(recording) = recording.filter(|recording| recording.is_replay()) {
+            return Ok(requests.iter().map(|request| recording.replay(request)).collect());
+        }
+        DaemonWait::global().check()?;
+        let started = Instant::now();
+        let result = self.send_batch_with_retries(requests).await;
+        if let (Some(recording), Ok(results)) = (recording, &result) {
+            for (request, outcome) in requests.iter().zip(results) {
+                recording.record(request, outcome.as_ref()).await;
+            }
+        }
+        let method = requests.first().map(|r| format!("batch:{}", r.method)).unwrap_or_else(|| "batch".to_string());
+        UpstreamMetrics::global().record(
+            &self.upstream_label,
+            &method,
+            None,
+            started.elapsed().as_millis() as u64,
+            result.is_ok(),
+            &self._config.slow_query_log,
+        );
+        result
+    }
+
+    async fn send_batch_with_retries(&self, requests: &[RpcRequest]) -> AppResult<Vec<AppResult<serde_json::Value>>> {
+        if requests.is_empty() {
+            return Ok(Vec::new());
+        }
+        if !self.circuit_breaker.should_allow_request().await {
+            return Err(crate::shared::error::AppError::Rpc(
+                "Service temporarily unavailable (circuit breaker open)".to_string()
+            ));
+        }
+        self.circuit_breaker.increment_half_open_requests().await;
+
+        // Ids end in positions so responses can be matched regardless of order
+        let payload: Vec<serde_json::Value> = requests
+            .iter()
+            .enumerate()
+            .map(|(idx, request)| serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": request.method,
+                "params": request.parameters,
+                "id": self.context.batch_id(&request.client_info, idx)
+            }))
+            .collect();
+        let context_header = self.context.header(&requests[0].client_info);
+
+        let mut last_error = None;
+        let mut force_resolve = false;
+        for attempt in 0..=self.max_retries {
+            let client = self.http_client(force_resolve).await?;
+            let (rpc_user, rpc_password) = self.auth.credentials().await?;
+            let mut upstream_request = client
+                .post(&self._config.verus.rpc_url)
+                .header("Content-Type", "application/json")
+                .basic_auth(&rpc_user, Some(&rpc_password));
+            if let Some((name, value)) = &context_header {
+                upstream_request = upstream_request.header(*name, value);
+            }
+            match upstream_request.json(&payload).send().await {
+                Ok(response) if response.status().is_success() => {
+                    match response.json::<Vec<serde_json::Value>>().await {
+                        Ok(entries) => {
+                            self.circuit_breaker.record_success().await;
+                            self.daemon_available.store(true, Ordering::Relaxed);
+                            let mut results: Vec<AppResult<serde_json::Value>> = (0..requests.len())
+                                .map(|_| Err(crate::shared::error::AppError::Rpc("missing batch response".to_string())))
+                                .collect();
+                            for entry in entries {
+                                let Some(idx) = entry.get("id").and_then(UpstreamContext::batch_index) else { continue };
+                                if idx >= results.len() {
+                                    continue;
+                                }
+                                results[idx] = match (entry.get("result"), entry.get("error")) {
+                                    (_, Some(error)) if !error.is_null() => {
+                                        Err(crate::shared::error::AppError::Rpc(format!("RPC error: {}", error)))
+                                    }
+                                    (Some(result), _) => Ok(result.clone()),
+                                    _ => Err(crate::shared::error::AppError::Rpc("Invalid RPC response".to_string())),
+                                };
+                            }
+                            return Ok(results);
+                        }
+                        Err(e) => {
+                            last_error = Some(format!("Failed to parse batch response: {}", e));
+                            self.circuit_breaker.record_failure().await;
+                        }
+                    }
+                }
+                Ok(response) => {
+                    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                        self.auth.invalidate().await;
+                    }
+                    last_error = Some(format!("HTTP error: {}", response.status()));
+                    self.circuit_breaker.record_failure().await;
+                }
+                Err(e) => {
+                    force_resolve = e.is_connect();
+                    last_error = Some(format!("Request failed: {}", e));
+                    self.circuit_breaker.record_failure().await;
+                }
+            }
+
+            if attempt < self.max_retries {
+                tokio::time::sleep(Duration::from_millis(100 * (attempt + 1) as u64)).await;
+            }
+        }
+
+        self.daemon_available.store(false, Ordering::Relaxed);
+        Err(crate::shared::error::AppError::Rpc(format!("RPC batch failed after {} attempts: {:?}", self.max_retries + 1, last_error)))
     }
 
     /// Check if external service is available
@@ -291,6 +629,8 @@ mod tests {
     use super::*;
     use crate::domain::rpc::{RpcRequest, ClientInfo};
     use crate::config::AppConfig;
+    use crate::config::app_config::{DaemonAuthMethod, UpstreamContextMode, UpstreamPipeliningConfig};
+    use crate::infrastructure::adapters::MockDaemon;
 
     fn create_test_config() -> AppConfig {
         AppConfig::default()
@@ -306,7 +646,9 @@ mod tests {
                 user_agent: Some("test-agent".to_string()),
                 auth_token: None,
                 timestamp: chrono::Utc::now(),
+                request_id: None,
             },
+            upstream: None,
         }
     }
 
@@ -408,4 +750,61 @@ mod tests {
         
         assert!(!adapter.is_available().await);
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_payments_adapter_has_independent_breaker() {
+        let mut config = create_test_config();
+        config.verus.rpc_url = "http://127.0.0.1:1".to_string();
+        config.payments.rpc.max_retries = 0;
+        let config = Arc::new(config);
+        let general = ExternalRpcAdapter::new(config.clone());
+        let payments = ExternalRpcAdapter::for_payments(config);
+        let request = create_test_request();
+
+        for _ in 0..3 {
+            let _ = payments.send_request(&request).await;
+        }
+
+        assert_eq!(payments.get_circuit_status().await, CircuitState::Open);
+        assert_eq!(general.get_circuit_status().await, CircuitState::Closed);
+    }
+
+    fn pipelined_config(rpc_url: String) -> AppConfig {
+        let mut config = create_test_config();
+        config.verus.rpc_url = rpc_url;
+        config.verus.auth_method = DaemonAuthMethod::Password;
+        config.verus.rpc_user = "mock".to_string();
+        config.verus.rpc_password = "mock".to_string();
+        config.verus.pipelining = UpstreamPipeliningConfig { enabled: true, min_in_flight: 1, max_batch: 2, max_delay_ms: 100 };
+        config
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_sent_when_the_caller_that_filled_it_is_dropped() {
+        let daemon = MockDaemon::start(Duration::from_millis(50)).await.unwrap();
+        let adapter = ExternalRpcAdapter::new(Arc::new(pipelined_config(daemon.url())));
+        let call = |adapter: &ExternalRpcAdapter| {
+            let adapter = adapter.clone();
+            tokio::spawn(async move { adapter.send_request(&create_test_request()).await })
+        };
+
+        let busy = call(&adapter);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let waiting = call(&adapter);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let filler = call(&adapter);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        filler.abort();
+
+        assert!(waiting.await.unwrap().is_ok());
+        assert!(busy.await.unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_header_context_disables_pipelining() {
+        let mut config = pipelined_config("http://127.0.0.1:1".to_string());
+        assert!(ExternalRpcAdapter::new(Arc::new(config.clone())).pipeline.is_some());
+        config.upstream_context.mode = UpstreamContextMode::Header;
+        assert!(ExternalRpcAdapter::new(Arc::new(config)).pipeline.is_none());
+    }
+}
@@ -0,0 +1,238 @@
+//! Zero-downtime binary upgrades by listener handover
+//!
+//! With `[upgrade]` enabled the TCP listener is bound with `SO_REUSEPORT`.
+//! An upgrade, started by `POST /admin/upgrade` or `SIGUSR2`, runs the new
+//! binary with this process's arguments and environment. The new process
+//! binds the same port next to this one and, once its listeners are up,
+//! creates the ready file named in `VERUS_RPC_UPGRADE_READY`. This process
+//! then stops accepting, lets open connections finish their requests for up
+//! to `drain_timeout_seconds` and exits. If the new process exits or is not
+//! ready within `ready_timeout_seconds` it is killed and this process keeps
+//! serving. Under systemd the new process is announced with `MAINPID=`, which
+//! needs `NotifyAccess=all` in the unit; socket-activated units restart
+//! through systemd instead.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::process::Child;
+use tracing::{info, warn};
+
+use crate::config::app_config::UpgradeConfig;
+use crate::config::AppConfig;
+use crate::infrastructure::adapters::systemd;
+use crate::infrastructure::http::listener::Drain;
+use crate::shared::error::{AppError, AppResult};
+
+/// File the new process creates once it is serving
+pub const READY_ENV: &str = "VERUS_RPC_UPGRADE_READY";
+/// Pid of the process being replaced, as seen by the new one
+pub const PREDECESSOR_ENV: &str = "VERUS_RPC_UPGRADE_FROM";
+
+/// Where an upgrade is
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradePhase {
+    Idle,
+    /// New process started, waiting for it to listen
+    Starting,
+    /// New process is serving; this one finishes open connections and exits
+    Draining,
+    /// Last upgrade was abandoned; this process keeps serving
+    Failed,
+}
+
+/// Upgrade state as reported by `GET /admin/upgrade`
+#[derive(Debug, Clone, Serialize)]
+pub struct UpgradeStatus {
+    pub enabled: bool,
+    pub phase: UpgradePhase,
+    pub pid: u32,
+    /// Process this one took over from
+    pub predecessor_pid: Option<u32>,
+    /// Process started by the current or last upgrade
+    pub successor_pid: Option<u32>,
+    pub open_connections: usize,
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct State {
+    phase: UpgradePhase,
+    successor_pid: Option<u32>,
+    error: Option<String>,
+    updated_at: DateTime<Utc>,
+}
+
+/// Coordinates handing the listeners over to a new binary
+pub struct Handover {
+    config: UpgradeConfig,
+    state: Mutex<State>,
+}
+
+impl Handover {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            config: config.upgrade.clone(),
+            state: Mutex::new(State { phase: UpgradePhase::Idle, successor_pid: None, error: None, updated_at: Utc::now() }),
+        }
+    }
+
+    pub fn status(&self) -> UpgradeStatus {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        UpgradeStatus {
+            enabled: self.config.enabled,
+            phase: state.phase,
+            pid: std::process::id(),
+            predecessor_pid: std::env::var(PREDECESSOR_ENV).ok().and_then(|pid| pid.parse().ok()),
+            successor_pid: state.successor_pid,
+            open_connections: Drain::global().open_connections(),
+            error: state.error.clone(),
+            updated_at: state.updated_at,
+        }
+    }
+
+    /// Start the new binary; the handover continues in the background
+    pub fn start(self: &Arc<Self>) -> AppResult<UpgradeStatus> {
+        if !self.config.enabled {
+            return Err(AppError::Validation("upgrades are disabled, set [upgrade] enabled = true".into()));
+        }
+        if std::env::var_os("LISTEN_FDS").is_some() {
+            return Err(AppError::Validation("sockets are managed by systemd, restart the unit instead".into()));
+        }
+        let binary = self.binary()?;
+        let ready = std::env::temp_dir().join(format!("verus-rpc-upgrade-{}.ready", uuid::Uuid::new_v4().simple()));
+        let child = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if matches!(state.phase, UpgradePhase::Starting | UpgradePhase::Draining) {
+                return Err(AppError::Validation("an upgrade is already in progress".into()));
+            }
+            let child = tokio::process::Command::new(&binary)
+                .args(std::env::args_os().skip(1))
+                .env(READY_ENV, &ready)
+                .env(PREDECESSOR_ENV, std::process::id().to_string())
+                .stdin(Stdio::null())
+                .spawn()
+                .map_err(|e| AppError::Internal(format!("Failed to start {}: {}", binary.display(), e)))?;
+            *state = State { phase: UpgradePhase::Starting, successor_pid: child.id(), error: None, updated_at: Utc::now() };
+            child
+        };
+        info!(binary = %binary.display(), pid = ?child.id(), "Started new process for upgrade");
+        let handover = self.clone();
+        tokio::spawn(async move { handover.supervise(child, ready).await });
+        Ok(self.status())
+    }
+
+    /// Start an upgrade on every `SIGUSR2`
+    #[cfg(unix)]
+    pub fn listen_for_signal(self: &Arc<Self>) {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut signals = match signal(SignalKind::user_defined2()) {
+            Ok(signals) => signals,
+            Err(e) => {
+                warn!("Cannot listen for SIGUSR2, upgrades need the admin API: {}", e);
+                return;
+            }
+        };
+        let handover = self.clone();
+        tokio::spawn(async move {
+            while signals.recv().await.is_some() {
+                info!("Received SIGUSR2, starting upgrade");
+                if let Err(e) = handover.start() {
+                    warn!("Upgrade not started: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Tell the process that started this one that the listeners are up
+    pub fn announce_ready() {
+        let Some(path) = std::env::var_os(READY_ENV) else {
+            return;
+        };
+        match std::fs::write(&path, std::process::id().to_string()) {
+            Ok(()) => info!("Listeners are up, taking over from the previous process"),
+            Err(e) => warn!("Failed to signal readiness to the previous process: {}", e),
+        }
+    }
+
+    /// Wait for the new process to listen, then drain this one; kill it if it never does
+    async fn supervise(&self, mut child: Child, ready: PathBuf) {
+        let timeout = Duration::from_secs(self.config.ready_timeout_seconds);
+        let deadline = tokio::time::Instant::now() + timeout;
+        let failure = loop {
+            if tokio::fs::try_exists(&ready).await.unwrap_or(false) {
+                break None;
+            }
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(format!("new process exited with {} before listening", status)),
+                Err(e) => break Some(format!("cannot watch new process: {}", e)),
+                Ok(None) => {}
+            }
+            if tokio::time::Instant::now() >= deadline {
+                let _ = child.kill().await;
+                break Some(format!("new process did not listen within {}s", timeout.as_secs()));
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        };
+        let _ = tokio::fs::remove_file(&ready).await;
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.updated_at = Utc::now();
+        if let Some(error) = failure {
+            warn!("Upgrade abandoned, still serving: {}", error);
+            state.phase = UpgradePhase::Failed;
+            state.error = Some(error);
+            return;
+        }
+        if let Some(pid) = child.id() {
+            systemd::notify(&format!("MAINPID={}", pid));
+        }
+        info!(successor = ?child.id(), "New process is serving, draining connections");
+        state.phase = UpgradePhase::Draining;
+        Drain::global().start();
+    }
+
+    /// Binary to start: `binary_path`, or the one this process runs from
+    fn binary(&self) -> AppResult<PathBuf> {
+        if let Some(path) = &self.config.binary_path {
+            return Ok(PathBuf::from(path));
+        }
+        let exe = std::env::current_exe()
+            .map_err(|e| AppError::Config(format!("Cannot locate the running binary, set binary_path: {}", e)))?;
+        Ok(installed_path(&exe))
+    }
+}
+
+/// Path a replaced binary was installed at; Linux appends ` (deleted)` once the file is replaced
+fn installed_path(exe: &Path) -> PathBuf {
+    let path = exe.to_string_lossy();
+    PathBuf::from(path.strip_suffix(" (deleted)").unwrap_or(&path).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_installed_path_strips_deleted_marker() {
+        assert_eq!(installed_path(Path::new("/usr/bin/verus-rpc-server (deleted)")), PathBuf::from("/usr/bin/verus-rpc-server"));
+        assert_eq!(installed_path(Path::new("/usr/bin/verus-rpc-server")), PathBuf::from("/usr/bin/verus-rpc-server"));
+    }
+
+    #[test]
+    fn test_start_refused_when_disabled() {
+        let handover = Arc::new(Handover::new(&AppConfig::default()));
+        assert!(matches!(handover.start(), Err(AppError::Validation(_))));
+        let status = handover.status();
+        assert!(!status.enabled);
+        assert_eq!(status.phase, UpgradePhase::Idle);
+        assert_eq!(status.pid, std::process::id());
+    }
+}
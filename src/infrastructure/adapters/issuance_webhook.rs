@@ -0,0 +1,244 @@
+//! Issuance webhook: external review of token minting
+//!
+//! Before a token is minted for a payment or a solved PoW challenge, the
+//! configured endpoint receives the pending issuance as JSON. The body is
+//! signed with HMAC-SHA256 over `"{timestamp}.{body}"`, sent as
+//! `X-Verus-Signature` next to `X-Verus-Timestamp`, so billing and CRM systems
+//! can reject forged or replayed calls. The endpoint answers
+//! `{"allow": bool, "reason": "...", "annotations": {...}}`; a refusal vetoes
+//! the issuance and annotations (e.g. a customer id) are embedded in the JWT.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::{info, warn};
+
+use crate::config::app_config::{IssuanceWebhookConfig, IssuanceWebhookSource};
+use crate::domain::payments::PaymentClaim;
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::security::hmac_sha256_hex;
+
+/// Header carrying the hex HMAC-SHA256 signature
+pub const SIGNATURE_HEADER: &str = "x-verus-signature";
+
+/// Header carrying the Unix timestamp covered by the signature
+pub const TIMESTAMP_HEADER: &str = "x-verus-timestamp";
+
+/// What a pending token is issued for
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum IssuanceSource {
+    /// A confirmed payment session
+    Payment {
+        #[serde(flatten)]
+        payment: PaymentClaim,
+        provisional: bool,
+    },
+    /// A solved proof-of-work challenge
+    Pow { challenge_id: String },
+}
+
+impl IssuanceSource {
+    fn kind(&self) -> IssuanceWebhookSource {
+        match self {
+            IssuanceSource::Payment { .. } => IssuanceWebhookSource::Payment,
+            IssuanceSource::Pow { .. } => IssuanceWebhookSource::Pow,
+        }
+    }
+
+    /// Payment embedded in the token as its `payment` claim
+    pub fn payment_claim(&self) -> Option<&PaymentClaim> {
+        match self {
+            IssuanceSource::Payment { payment, .. } => Some(payment),
+            IssuanceSource::Pow { .. } => None,
+        }
+    }
+}
+
+/// Pending issuance sent to the webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuanceReview<'a> {
+    /// Unique id of this call, for idempotency on the receiving side
+    pub event_id: String,
+    pub user_id: &'a str,
+    pub permissions: &'a [String],
+    pub client_ip: Option<&'a str>,
+    pub expires_in: u64,
+    #[serde(flatten)]
+    pub source: &'a IssuanceSource,
+}
+
+/// Webhook answer
+#[derive(Debug, Clone, Deserialize)]
+pub struct IssuanceDecision {
+    pub allow: bool,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Claims attached to the token under `annotations`
+    #[serde(default)]
+    pub annotations: Map<String, Value>,
+}
+
+/// Client for the configured issuance webhook
+pub struct IssuanceWebhook {
+    config: IssuanceWebhookConfig,
+    http: reqwest::Client,
+}
+
+impl IssuanceWebhook {
+    /// Webhook from `[issuance_webhook]`; `None` when disabled
+    ///
+    /// Startup validation refuses an enabled webhook without its URL or secret.
+    pub fn new(config: &IssuanceWebhookConfig) -> Option<Self> {
+        if !config.enabled || config.url.is_none() || config.secret.is_none() {
+            return None;
+        }
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .unwrap_or_default();
+        Some(Self { config: config.clone(), http })
+    }
+
+    /// Whether issuance from `source` is reviewed
+    pub fn reviews(&self, source: &IssuanceSource) -> bool {
+        self.config.sources.contains(&source.kind())
+    }
+
+    /// Ask the webhook about a pending issuance; returns the annotations to embed
+    ///
+    /// A refusal fails with an authentication error. When the webhook cannot
+    /// be reached or answers garbage, issuance is refused unless `fail_open`.
+    pub async fn review(&self, review: &IssuanceReview<'_>) -> AppResult<Map<String, Value>> {
+        match self.call(review).await {
+            Ok(decision) if decision.allow => {
+                info!(event_id = %review.event_id, user_id = %review.user_id, "Issuance approved by webhook");
+                Ok(decision.annotations)
+            }
+            Ok(decision) => {
+                let reason = decision.reason.unwrap_or_else(|| "refused by issuance webhook".to_string());
+                warn!(event_id = %review.event_id, user_id = %review.user_id, reason = %reason, "Issuance vetoed by webhook");
+                Err(AppError::Authentication(format!("Token issuance declined: {}", reason)))
+            }
+            Err(e) if self.config.fail_open => {
+                warn!(event_id = %review.event_id, error = %e, "Issuance webhook failed; issuing unreviewed token");
+                Ok(Map::new())
+            }
+            Err(e) => {
+                warn!(event_id = %review.event_id, error = %e, "Issuance webhook failed; refusing issuance");
+                Err(AppError::Internal("Token issuance review unavailable".to_string()))
+            }
+        }
+    }
+
+    async fn call(&self, review: &IssuanceReview<'_>) -> AppResult<IssuanceDecision> {
+        let (Some(url), Some(secret)) = (&self.config.url, &self.config.secret) else {
+            return Err(AppError::Config("issuance webhook url or secret missing".to_string()));
+        };
+        let body = serde_json::to_vec(review).map_err(|e| AppError::Json(e.to_string()))?;
+        let timestamp = Utc::now().timestamp().to_string();
+        let response = self
+            .http
+            .post(url)
+            .header("content-type", "application/json")
+            .header(TIMESTAMP_HEADER, &timestamp)
+            .header(SIGNATURE_HEADER, sign(secret, &timestamp, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::Http(format!("issuance webhook request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Http(format!("issuance webhook answered {}", response.status())));
+        }
+        response
+            .json::<IssuanceDecision>()
+            .await
+            .map_err(|e| AppError::Json(format!("invalid issuance webhook answer: {}", e)))
+    }
+}
+
+/// Signature of a webhook body sent at `timestamp`
+pub fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut message = Vec::with_capacity(timestamp.len() + 1 + body.len());
+    message.extend_from_slice(timestamp.as_bytes());
+    message.push(b'.');
+    message.extend_from_slice(body);
+    hmac_sha256_hex(secret.as_bytes(), &message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(sources: Vec<IssuanceWebhookSource>) -> IssuanceWebhookConfig {
+        IssuanceWebhookConfig {
+            enabled: true,
+            // Nothing listens on the discard port, so calls fail fast
+            url: Some("http://127.0.0.1:9/issuance".to_string()),
+            secret: Some("0123456789abcdef0123456789abcdef".to_string()),
+            sources,
+            timeout_ms: 500,
+            fail_open: false,
+        }
+    }
+
+    fn review(source: &IssuanceSource) -> IssuanceReview<'_> {
+        IssuanceReview {
+            event_id: "evt".to_string(),
+            user_id: "pay_1",
+            permissions: &[],
+            client_ip: None,
+            expires_in: 3600,
+            source,
+        }
+    }
+
+    #[test]
+    fn test_review_body_and_signature() {
+        let source = IssuanceSource::Pow { challenge_id: "c1".to_string() };
+        let body = serde_json::to_value(review(&source)).unwrap();
+        assert_eq!(body["source"], "pow");
+        assert_eq!(body["challenge_id"], "c1");
+        assert_eq!(body["user_id"], "pay_1");
+
+        let signature = sign("secret", "1700000000", b"{}");
+        assert_eq!(signature, hmac_sha256_hex(b"secret", b"1700000000.{}"));
+        assert_ne!(signature, sign("secret", "1700000001", b"{}"));
+    }
+
+    #[test]
+    fn test_sources_and_incomplete_config() {
+        let webhook = IssuanceWebhook::new(&config(vec![IssuanceWebhookSource::Payment])).unwrap();
+        assert!(!webhook.reviews(&IssuanceSource::Pow { challenge_id: "c1".to_string() }));
+        assert!(webhook.reviews(&IssuanceSource::Payment {
+            payment: PaymentClaim {
+                payment_id: "p".to_string(),
+                tier_id: "basic".to_string(),
+                amount_vrsc: 1.0,
+                currency: None,
+                address: "zs1quote".to_string(),
+                txids: vec!["tx1".to_string()],
+                block_height: Some(100),
+            },
+            provisional: false,
+        }));
+
+        let mut missing_secret = config(vec![]);
+        missing_secret.secret = None;
+        assert!(IssuanceWebhook::new(&missing_secret).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_webhook_fails_closed_unless_fail_open() {
+        let source = IssuanceSource::Pow { challenge_id: "c1".to_string() };
+        let closed = IssuanceWebhook::new(&config(vec![IssuanceWebhookSource::Pow])).unwrap();
+        assert!(matches!(closed.review(&review(&source)).await, Err(AppError::Internal(_))));
+
+        let mut open = config(vec![IssuanceWebhookSource::Pow]);
+        open.fail_open = true;
+        let open = IssuanceWebhook::new(&open).unwrap();
+        assert!(open.review(&review(&source)).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+//! Approximate memory held by in-process tables
+//!
+//! Components report the bytes they hold: the in-memory response cache, the
+//! rate-limit buckets and the bodies of RPC requests being served. Figures are
+//! estimates (payload and key sizes plus a fixed per-entry overhead), meant to
+//! show which table grows rather than to match the allocator. Tables that can
+//! give memory back register a shrinker, which the memory guard calls under
+//! pressure.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+/// Estimated bookkeeping cost of one table entry beyond its keys and payload
+pub const ENTRY_OVERHEAD_BYTES: usize = 64;
+
+/// Called with the percentage of a table to keep
+type Shrinker = Box<dyn Fn(u8) + Send + Sync>;
+
+/// Process-wide registry of tracked tables
+pub struct MemoryUsage {
+    components: Mutex<Vec<(&'static str, Weak<AtomicUsize>)>>,
+    shrinkers: Mutex<Vec<Shrinker>>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl MemoryUsage {
+    pub fn new() -> Self {
+        let usage = Self {
+            components: Mutex::new(Vec::new()),
+            shrinkers: Mutex::new(Vec::new()),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        };
+        usage.register("in_flight_requests", &usage.in_flight);
+        usage
+    }
+
+    /// Registry shared by every table in the process
+    pub fn global() -> &'static MemoryUsage {
+        static USAGE: OnceLock<MemoryUsage> = OnceLock::new();
+        USAGE.get_or_init(MemoryUsage::new)
+    }
+
+    /// Report `bytes`, kept up to date by its owner, under `component`; forgotten once the owner drops it
+    pub fn register(&self, component: &'static str, bytes: &Arc<AtomicUsize>) {
+        if let Ok(mut components) = self.components.lock() {
+            components.push((component, Arc::downgrade(bytes)));
+        }
+    }
+
+    /// Let the memory guard shrink a table
+    pub fn register_shrinker(&self, shrinker: impl Fn(u8) + Send + Sync + 'static) {
+        if let Ok(mut shrinkers) = self.shrinkers.lock() {
+            shrinkers.push(Box::new(shrinker));
+        }
+    }
+
+    /// Ask every shrinkable table to keep `keep_percent` of its entries
+    pub fn shrink(&self, keep_percent: u8) {
+        if let Ok(shrinkers) = self.shrinkers.lock() {
+            for shrinker in shrinkers.iter() {
+                shrinker(keep_percent);
+            }
+        }
+    }
+
+    /// Count a request body as in flight until the returned guard is dropped
+    pub fn track_request(&self, bytes: usize) -> InFlightRequest {
+        self.in_flight.fetch_add(bytes, Ordering::Relaxed);
+        InFlightRequest { bytes, counter: self.in_flight.clone() }
+    }
+
+    /// Request body bytes currently in flight
+    pub fn in_flight_bytes(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Bytes per component, summed over live tables
+    pub fn by_component(&self) -> BTreeMap<&'static str, usize> {
+        let mut totals = BTreeMap::new();
+        if let Ok(mut components) = self.components.lock() {
+            components.retain(|(_, bytes)| bytes.strong_count() > 0);
+            for (component, bytes) in components.iter() {
+                if let Some(bytes) = bytes.upgrade() {
+                    *totals.entry(*component).or_insert(0) += bytes.load(Ordering::Relaxed);
+                }
+            }
+        }
+        totals
+    }
+
+    /// Bytes held by all tracked tables
+    pub fn total(&self) -> usize {
+        self.by_component().values().sum()
+    }
+
+    /// Render tracked bytes in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP verus_memory_tracked_bytes Approximate bytes held by in-process tables\n");
+        out.push_str("# TYPE verus_memory_tracked_bytes gauge\n");
+        for (component, bytes) in self.by_component() {
+            out.push_str(&format!("verus_memory_tracked_bytes{{component=\"{}\"}} {}\n", component, bytes));
+        }
+        out
+    }
+}
+
+impl Default for MemoryUsage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Request body counted as in flight; released on drop
+#[derive(Debug)]
+pub struct InFlightRequest {
+    bytes: usize,
+    counter: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components_are_summed_and_forgotten_when_dropped() {
+        let usage = MemoryUsage::new();
+        let first = Arc::new(AtomicUsize::new(100));
+        let second = Arc::new(AtomicUsize::new(50));
+        usage.register("response_cache", &first);
+        usage.register("response_cache", &second);
+        assert_eq!(usage.by_component().get("response_cache"), Some(&150));
+
+        drop(second);
+        assert_eq!(usage.by_component().get("response_cache"), Some(&100));
+        assert_eq!(usage.total(), 100);
+        assert!(usage.prometheus_text().contains("verus_memory_tracked_bytes{component=\"response_cache\"} 100"));
+    }
+
+    #[test]
+    fn test_in_flight_requests_release_on_drop() {
+        let usage = MemoryUsage::new();
+        let request = usage.track_request(4096);
+        let other = usage.track_request(1024);
+        assert_eq!(usage.in_flight_bytes(), 5120);
+        drop(request);
+        assert_eq!(usage.in_flight_bytes(), 1024);
+        drop(other);
+        assert_eq!(usage.by_component().get("in_flight_requests"), Some(&0));
+    }
+}
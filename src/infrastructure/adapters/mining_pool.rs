@@ -11,9 +11,11 @@ use tracing::{info, warn, error, debug};
 use serde::{Deserialize, Serialize};
 use chrono::{Utc, DateTime};
 use reqwest::Client;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use tokio::sync::Mutex;
-use ed25519_dalek::{Verifier, VerifyingKey, Signature};
+use ed25519_dalek::VerifyingKey;
+use crate::shared::security::{verify_ed25519_hex, SignatureError};
+use sha2::{Sha256, Digest};
 
 /// Pool share structure for mining pool validation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +65,10 @@ pub struct PoolValidationResponse {
     
     /// Error message if validation failed
     pub error: Option<String>,
+    
+    /// Whether this result was served from the share cache (identical share seen before)
+    #[serde(default)]
+    pub cached: bool,
 }
 
 /// Pool share submission request
@@ -87,6 +93,45 @@ pub struct PoolShareRequest {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Batch share submission request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoolBatchRequest {
+    /// Shares to validate, answered in the same order
+    pub shares: Vec<PoolShareRequest>,
+}
+
+/// Batch share validation response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoolBatchResponse {
+    /// One result per submitted share, in submission order
+    pub results: Vec<PoolValidationResponse>,
+}
+
+/// Circuit breaker metrics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerMetrics {
+    /// Current state
+    pub state: CircuitBreakerState,
+    
+    /// Failures counted towards the threshold since the circuit last closed
+    pub failure_count: u32,
+    
+    /// Failures required to open the circuit
+    pub failure_threshold: u32,
+    
+    /// Seconds the circuit stays open before a trial request
+    pub open_timeout_seconds: u64,
+    
+    /// Number of times the circuit has opened
+    pub times_opened: u64,
+    
+    /// Calls rejected without reaching the pool while open
+    pub rejected_calls: u64,
+    
+    /// When the circuit last opened
+    pub last_opened_at: Option<DateTime<Utc>>,
+}
+
 /// Enhanced pool metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PoolMetrics {
@@ -113,6 +158,21 @@ pub struct PoolMetrics {
     
     /// Error rate percentage
     pub error_rate_percent: f64,
+    
+    /// Share validations answered from the cache
+    pub cache_hits: u64,
+    
+    /// Share validations that went to the pool
+    pub cache_misses: u64,
+    
+    /// Entries currently held in the share cache
+    pub cached_entries: usize,
+    
+    /// Batch requests sent to the pool
+    pub batch_requests: u64,
+    
+    /// Circuit breaker details
+    pub circuit_breaker: CircuitBreakerMetrics,
 }
 
 /// Circuit breaker state
@@ -155,6 +215,9 @@ pub struct CircuitBreaker {
     last_failure_time: Mutex<Option<Instant>>,
     threshold: u32,
     timeout: Duration,
+    times_opened: AtomicU64,
+    rejected_calls: AtomicU64,
+    last_opened_at: Mutex<Option<DateTime<Utc>>>,
 }
 
 impl CircuitBreaker {
@@ -165,6 +228,9 @@ impl CircuitBreaker {
             last_failure_time: Mutex::new(None),
             threshold,
             timeout: Duration::from_secs(timeout_seconds),
+            times_opened: AtomicU64::new(0),
+            rejected_calls: AtomicU64::new(0),
+            last_opened_at: Mutex::new(None),
         }
     }
 
@@ -197,21 +263,20 @@ impl CircuitBreaker {
                                 }
                                 Err(e) => {
                                     // Still failing, open the circuit
-                                    *self.state.lock().await = CircuitBreakerState::Open;
-                                    *self.last_failure_time.lock().await = Some(Instant::now());
+                                    self.open().await;
                                     Err(e)
                                 }
                             }
                         } else {
                             // Another thread already changed the state
-                            Err(self.create_circuit_open_error().into())
+                            Err(self.reject().into())
                         }
                     } else {
                         // Circuit is still open
-                        Err(self.create_circuit_open_error().into())
+                        Err(self.reject().into())
                     }
                 } else {
-                    Err(self.create_circuit_open_error().into())
+                    Err(self.reject().into())
                 }
             }
             CircuitBreakerState::HalfOpen => {
@@ -225,8 +290,7 @@ impl CircuitBreaker {
                     }
                     Err(e) => {
                         // Still failing, open the circuit
-                        *self.state.lock().await = CircuitBreakerState::Open;
-                        *self.last_failure_time.lock().await = Some(Instant::now());
+                        self.open().await;
                         Err(e)
                     }
                 }
@@ -240,8 +304,7 @@ impl CircuitBreaker {
                         let failures = self.failure_count.fetch_add(1, Ordering::SeqCst) + 1;
                         if failures >= self.threshold {
                             // Open the circuit
-                            *self.state.lock().await = CircuitBreakerState::Open;
-                            *self.last_failure_time.lock().await = Some(Instant::now());
+                            self.open().await;
                         }
                         Err(e)
                     }
@@ -250,6 +313,20 @@ impl CircuitBreaker {
         }
     }
 
+    /// Transition to open and record when it happened
+    async fn open(&self) {
+        *self.state.lock().await = CircuitBreakerState::Open;
+        *self.last_failure_time.lock().await = Some(Instant::now());
+        *self.last_opened_at.lock().await = Some(Utc::now());
+        self.times_opened.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Fail fast while open
+    fn reject(&self) -> crate::shared::error::AppError {
+        self.rejected_calls.fetch_add(1, Ordering::Relaxed);
+        self.create_circuit_open_error()
+    }
+
     fn create_circuit_open_error(&self) -> crate::shared::error::AppError {
         crate::shared::error::AppError::Internal(
             "Mining pool service is temporarily unavailable".to_string()
@@ -257,7 +334,20 @@ impl CircuitBreaker {
     }
 
     pub async fn get_state(&self) -> CircuitBreakerState {
-        self.state.lock().await.clone()
+        *self.state.lock().await
+    }
+
+    /// Snapshot of circuit breaker counters
+    pub async fn metrics(&self) -> CircuitBreakerMetrics {
+        CircuitBreakerMetrics {
+            state: self.get_state().await,
+            failure_count: self.failure_count.load(Ordering::SeqCst),
+            failure_threshold: self.threshold,
+            open_timeout_seconds: self.timeout.as_secs(),
+            times_opened: self.times_opened.load(Ordering::Relaxed),
+            rejected_calls: self.rejected_calls.load(Ordering::Relaxed),
+            last_opened_at: *self.last_opened_at.lock().await,
+        }
     }
 }
 
@@ -318,6 +408,7 @@ pub struct MiningPoolClient {
     retry_mechanism: RetryMechanism,
     metrics: Mutex<PoolMetrics>,
     pool_public_key: Option<VerifyingKey>,
+    share_cache: Mutex<HashMap<String, (PoolValidationResponse, Instant)>>,
 }
 
 impl MiningPoolClient {
@@ -366,6 +457,19 @@ impl MiningPoolClient {
             last_success: None,
             last_error: None,
             error_rate_percent: 0.0,
+            cache_hits: 0,
+            cache_misses: 0,
+            cached_entries: 0,
+            batch_requests: 0,
+            circuit_breaker: CircuitBreakerMetrics {
+                state: CircuitBreakerState::Closed,
+                failure_count: 0,
+                failure_threshold: pool_config.circuit_breaker_threshold,
+                open_timeout_seconds: pool_config.circuit_breaker_timeout,
+                times_opened: 0,
+                rejected_calls: 0,
+                last_opened_at: None,
+            },
         });
         
         Self {
@@ -376,7 +480,19 @@ impl MiningPoolClient {
             retry_mechanism,
             metrics,
             pool_public_key,
+            share_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Client for the HTTP routes
+    ///
+    /// Without a `[security.mining_pool]` section the client is created disabled.
+    pub fn from_app_config(config: &AppConfig) -> Self {
+        let mut config = config.clone();
+        if config.security.mining_pool.is_none() {
+            config.security.mining_pool = Some(crate::config::app_config::MiningPoolConfig::default());
         }
+        Self::new(Arc::new(config))
     }
 
     /// Validate a pool share with the external mining pool
@@ -392,11 +508,17 @@ impl MiningPoolClient {
             ));
         }
 
+        // Identical shares are answered from the cache without contacting the pool
+        let cache_key = Self::share_cache_key(share);
+        if let Some(cached) = self.cached_result(&cache_key).await {
+            return Ok(cached);
+        }
+
         // Check rate limiting
         self.check_rate_limit(&share.miner_address).await?;
 
         // Update metrics
-        self.update_metrics_start().await;
+        self.update_metrics_start(1).await;
 
         let start_time = Instant::now();
 
@@ -408,7 +530,11 @@ impl MiningPoolClient {
         }).await;
 
         let response_time = start_time.elapsed();
-        self.update_metrics_end(result.is_ok(), response_time).await;
+        self.update_metrics_end(result.is_ok(), 1, response_time).await;
+
+        if let Ok(response) = &result {
+            self.cache_result(cache_key, response.clone()).await;
+        }
 
         result.map_err(|e| match e {
             crate::shared::error::AppError::Internal(msg) => {
@@ -418,18 +544,222 @@ impl MiningPoolClient {
         })
     }
 
-    /// Submit share to the mining pool for validation
-    async fn submit_share_to_pool(&self, share: &PoolShare) -> AppResult<PoolValidationResponse> {
-        let pool_config = self.config.security.mining_pool.as_ref().unwrap();
+    /// Validate several shares, sending uncached shares to the pool in batches
+    ///
+    /// Results are returned in input order. Shares repeated within the batch
+    /// or seen recently are answered from the cache and marked `cached`.
+    pub async fn validate_shares(&self, shares: &[PoolShare]) -> AppResult<Vec<PoolValidationResponse>> {
+        let pool_config = self.config.security.mining_pool.as_ref()
+            .ok_or_else(|| crate::shared::error::AppError::Internal(
+                "Mining pool configuration not found".to_string()
+            ))?;
         
-        let request = PoolShareRequest {
+        if !pool_config.enabled {
+            return Err(crate::shared::error::AppError::Internal(
+                "Mining pool integration is disabled".to_string()
+            ));
+        }
+
+        let mut results: Vec<Option<PoolValidationResponse>> = vec![None; shares.len()];
+        let mut pending: Vec<(String, usize)> = Vec::new();
+        let mut duplicates: Vec<(usize, usize)> = Vec::new(); // (index, index of first occurrence)
+        let mut first_seen: HashMap<String, usize> = HashMap::new();
+
+        for (index, share) in shares.iter().enumerate() {
+            let key = Self::share_cache_key(share);
+            if let Some(&first) = first_seen.get(&key) {
+                duplicates.push((index, first));
+                continue;
+            }
+            first_seen.insert(key.clone(), index);
+            if let Some(cached) = self.cached_result(&key).await {
+                results[index] = Some(cached);
+            } else if self.check_rate_limit(&share.miner_address).await.is_err() {
+                results[index] = Some(Self::rejected_response("Rate limit exceeded for miner"));
+            } else {
+                pending.push((key, index));
+            }
+        }
+
+        for chunk in pending.chunks(pool_config.max_batch_size.max(1)) {
+            let batch: Vec<&PoolShare> = chunk.iter().map(|(_, index)| &shares[*index]).collect();
+            self.update_metrics_start(batch.len() as u64).await;
+            let start_time = Instant::now();
+
+            let result = self.circuit_breaker.call(|| async {
+                self.retry_mechanism.execute(|| async {
+                    self.submit_batch_to_pool(&batch).await
+                }).await
+            }).await;
+
+            self.update_metrics_end(result.is_ok(), batch.len() as u64, start_time.elapsed()).await;
+            let responses = result?;
+
+            for ((key, index), response) in chunk.iter().zip(responses) {
+                self.cache_result(key.clone(), response.clone()).await;
+                results[*index] = Some(response);
+            }
+        }
+
+        for (index, first) in duplicates {
+            let mut response = results[first].clone()
+                .unwrap_or_else(|| Self::rejected_response("Share validation unavailable"));
+            response.cached = true;
+            results[index] = Some(response);
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Self::rejected_response("Share validation unavailable")))
+            .collect())
+    }
+
+    /// Submit a batch of shares to the mining pool for validation
+    async fn submit_batch_to_pool(&self, shares: &[&PoolShare]) -> AppResult<Vec<PoolValidationResponse>> {
+        let pool_config = self.config.security.mining_pool.as_ref().unwrap();
+
+        let request = PoolBatchRequest {
+            shares: shares.iter().map(|share| Self::share_request(share)).collect(),
+        };
+
+        let url = format!("{}/api/v1/share/validate/batch", pool_config.pool_url);
+
+        debug!("Submitting {} shares to pool: {}", shares.len(), url);
+
+        let response = self.http_client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", pool_config.api_key))
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "VerusRpcServer/1.0")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| {
+                error!("Failed to submit share batch to pool: {}", e);
+                crate::shared::error::AppError::Internal(
+                    format!("Pool communication failed: {}", e)
+                )
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            error!("Pool returned error status {} for batch: {}", status, error_text);
+            return Err(crate::shared::error::AppError::Internal(
+                format!("Pool batch validation failed: {}", error_text)
+            ));
+        }
+
+        let batch_response: PoolBatchResponse = response.json().await
+            .map_err(|e| {
+                error!("Failed to parse pool batch response: {}", e);
+                crate::shared::error::AppError::Internal(
+                    format!("Invalid pool response: {}", e)
+                )
+            })?;
+
+        if batch_response.results.len() != shares.len() {
+            return Err(crate::shared::error::AppError::Internal(format!(
+                "Pool returned {} results for {} shares",
+                batch_response.results.len(),
+                shares.len()
+            )));
+        }
+
+        for (share, result) in shares.iter().zip(&batch_response.results) {
+            if let (Some(signature), Some(public_key)) = (&result.pool_signature, &self.pool_public_key) {
+                self.verify_pool_signature(share, signature, public_key).await?;
+            }
+        }
+
+        self.metrics.lock().await.batch_requests += 1;
+        Ok(batch_response.results)
+    }
+
+    /// Cache key identifying a share by its contents
+    fn share_cache_key(share: &PoolShare) -> String {
+        hex::encode(Sha256::digest(Self::share_message(share).as_bytes()))
+    }
+
+    /// Canonical share representation, also the message the pool signs
+    fn share_message(share: &PoolShare) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            share.challenge_id,
+            share.miner_address,
+            share.nonce,
+            share.solution,
+            share.difficulty,
+            share.timestamp.timestamp()
+        )
+    }
+
+    fn share_request(share: &PoolShare) -> PoolShareRequest {
+        PoolShareRequest {
             challenge_id: share.challenge_id.clone(),
             miner_address: share.miner_address.clone(),
             nonce: share.nonce.clone(),
             solution: share.solution.clone(),
             difficulty: share.difficulty,
             timestamp: share.timestamp,
+        }
+    }
+
+    fn rejected_response(error: &str) -> PoolValidationResponse {
+        PoolValidationResponse {
+            valid: false,
+            share_id: None,
+            pool_signature: None,
+            difficulty_achieved: None,
+            miner_reputation: None,
+            timestamp: Utc::now(),
+            error: Some(error.to_string()),
+            cached: false,
+        }
+    }
+
+    /// Look up a recent validation result for an identical share
+    async fn cached_result(&self, key: &str) -> Option<PoolValidationResponse> {
+        let ttl = Duration::from_secs(self.config.security.mining_pool.as_ref()?.share_cache_ttl_seconds);
+        let hit = {
+            let cache = self.share_cache.lock().await;
+            cache.get(key)
+                .filter(|(_, stored_at)| stored_at.elapsed() < ttl)
+                .map(|(response, _)| PoolValidationResponse { cached: true, ..response.clone() })
         };
+        let mut metrics = self.metrics.lock().await;
+        if hit.is_some() {
+            metrics.cache_hits += 1;
+        } else {
+            metrics.cache_misses += 1;
+        }
+        hit
+    }
+
+    /// Remember a pool answer, evicting expired and then oldest entries when full
+    async fn cache_result(&self, key: String, response: PoolValidationResponse) {
+        let Some(pool_config) = self.config.security.mining_pool.as_ref() else { return };
+        if pool_config.share_cache_ttl_seconds == 0 || pool_config.share_cache_max_entries == 0 {
+            return;
+        }
+        let ttl = Duration::from_secs(pool_config.share_cache_ttl_seconds);
+        let mut cache = self.share_cache.lock().await;
+        if cache.len() >= pool_config.share_cache_max_entries {
+            cache.retain(|_, (_, stored_at)| stored_at.elapsed() < ttl);
+        }
+        if cache.len() >= pool_config.share_cache_max_entries {
+            if let Some(oldest) = cache.iter().min_by_key(|(_, (_, stored_at))| *stored_at).map(|(k, _)| k.clone()) {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(key, (response, Instant::now()));
+    }
+
+    /// Submit share to the mining pool for validation
+    async fn submit_share_to_pool(&self, share: &PoolShare) -> AppResult<PoolValidationResponse> {
+        let pool_config = self.config.security.mining_pool.as_ref().unwrap();
+        
+        let request = Self::share_request(share);
 
         let url = format!("{}/api/v1/share/validate", pool_config.pool_url);
         
@@ -490,36 +820,16 @@ impl MiningPoolClient {
         public_key: &VerifyingKey
     ) -> AppResult<()> {
         // Create the message to verify
-        let message = format!(
-            "{}:{}:{}:{}:{}:{}",
-            share.challenge_id,
-            share.miner_address,
-            share.nonce,
-            share.solution,
-            share.difficulty,
-            share.timestamp.timestamp()
-        );
+        let message = Self::share_message(share);
         
-        let signature_bytes = hex::decode(signature)
-            .map_err(|e| crate::shared::error::AppError::Validation(
-                format!("Invalid signature format: {}", e)
-            ))?;
-        
-        if signature_bytes.len() != 64 {
-            return Err(crate::shared::error::AppError::Validation(
-                "Invalid signature length".to_string()
-            ));
-        }
-        
-        let mut sig_array = [0u8; 64];
-        sig_array.copy_from_slice(&signature_bytes);
-        
-        let signature = Signature::from_bytes(&sig_array);
-        
-        public_key.verify(message.as_bytes(), &signature)
-            .map_err(|e| crate::shared::error::AppError::Validation(
-                format!("Signature verification failed: {}", e)
-            ))?;
+        verify_ed25519_hex(public_key, message.as_bytes(), signature).map_err(|e| match e {
+            SignatureError::Malformed => crate::shared::error::AppError::Validation(
+                "Invalid signature format".to_string()
+            ),
+            SignatureError::Invalid => crate::shared::error::AppError::Validation(
+                "Signature verification failed".to_string()
+            ),
+        })?;
         
         debug!("Pool signature verified successfully");
         Ok(())
@@ -554,29 +864,29 @@ impl MiningPoolClient {
     }
 
     /// Update metrics at start of validation
-    async fn update_metrics_start(&self) {
+    async fn update_metrics_start(&self, shares: u64) {
         let mut metrics = self.metrics.lock().await;
-        metrics.total_shares += 1;
+        metrics.total_shares += shares;
         metrics.circuit_breaker_state = self.circuit_breaker.get_state().await;
     }
 
     /// Update metrics at end of validation
-    async fn update_metrics_end(&self, success: bool, response_time: Duration) {
+    async fn update_metrics_end(&self, success: bool, shares: u64, response_time: Duration) {
         let mut metrics = self.metrics.lock().await;
         
         if success {
-            metrics.valid_shares += 1;
+            metrics.valid_shares += shares;
             metrics.last_success = Some(Utc::now());
         } else {
-            metrics.invalid_shares += 1;
+            metrics.invalid_shares += shares;
             metrics.last_error = Some(Utc::now());
         }
         
-        // Update average response time
+        // Update average response time (a batch counts once per share)
         let response_time_ms = response_time.as_millis() as f64;
         let total_requests = metrics.valid_shares + metrics.invalid_shares;
         metrics.avg_response_time_ms = 
-            (metrics.avg_response_time_ms * (total_requests - 1) as f64 + response_time_ms) / total_requests as f64;
+            (metrics.avg_response_time_ms * (total_requests - shares) as f64 + response_time_ms * shares as f64) / total_requests as f64;
         
         // Update error rate
         metrics.error_rate_percent = 
@@ -590,8 +900,12 @@ impl MiningPoolClient {
 
     /// Get pool metrics
     pub async fn get_metrics(&self) -> PoolMetrics {
+        let cached_entries = self.share_cache.lock().await.len();
+        let circuit_breaker = self.circuit_breaker.metrics().await;
         let mut metrics = self.metrics.lock().await;
-        metrics.circuit_breaker_state = self.circuit_breaker.get_state().await;
+        metrics.circuit_breaker_state = circuit_breaker.state;
+        metrics.circuit_breaker = circuit_breaker;
+        metrics.cached_entries = cached_entries;
         metrics.clone()
     }
 
@@ -737,6 +1051,9 @@ mod tests {
             circuit_breaker_timeout: 60,
             requests_per_minute: 100,
             enabled: true,
+            share_cache_ttl_seconds: 300,
+            share_cache_max_entries: 10000,
+            max_batch_size: 50,
         });
         
         let config = Arc::new(config);
@@ -748,4 +1065,74 @@ mod tests {
         assert_eq!(metrics.invalid_shares, 0);
         assert_eq!(metrics.circuit_breaker_state, CircuitBreakerState::Closed);
     }
+
+    fn test_client() -> MiningPoolClient {
+        let mut config = AppConfig::default();
+        config.security.mining_pool = Some(crate::config::app_config::MiningPoolConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        MiningPoolClient::new(Arc::new(config))
+    }
+
+    fn test_share(nonce: &str) -> PoolShare {
+        PoolShare {
+            challenge_id: "challenge".to_string(),
+            miner_address: "miner".to_string(),
+            nonce: nonce.to_string(),
+            solution: "abcdef".to_string(),
+            difficulty: 1.0,
+            timestamp: Utc::now(),
+            pool_signature: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identical_shares_served_from_cache() {
+        let client = test_client();
+        let share = test_share("1");
+        let answer = PoolValidationResponse {
+            valid: true,
+            share_id: Some("share-1".to_string()),
+            pool_signature: None,
+            difficulty_achieved: Some(1.0),
+            miner_reputation: None,
+            timestamp: Utc::now(),
+            error: None,
+            cached: false,
+        };
+        client.cache_result(MiningPoolClient::share_cache_key(&share), answer).await;
+
+        // Neither call reaches the (unreachable) pool
+        let single = client.validate_share(&share).await.unwrap();
+        assert!(single.valid && single.cached);
+
+        let batch = client.validate_shares(&[share.clone(), share]).await.unwrap();
+        assert_eq!(batch.len(), 2);
+        assert!(batch.iter().all(|r| r.valid && r.cached));
+
+        let metrics = client.get_metrics().await;
+        assert_eq!(metrics.cache_hits, 2);
+        assert_eq!(metrics.cached_entries, 1);
+        assert_eq!(metrics.total_shares, 0);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_metrics() {
+        let circuit_breaker = CircuitBreaker::new(1, 60);
+        let _ = circuit_breaker.call(|| async {
+            Err::<i32, crate::shared::error::AppError>(
+                crate::shared::error::AppError::Internal("error".to_string())
+            )
+        }).await;
+        let _ = circuit_breaker.call(|| async {
+            Ok::<i32, crate::shared::error::AppError>(1)
+        }).await;
+
+        let metrics = circuit_breaker.metrics().await;
+        assert_eq!(metrics.state, CircuitBreakerState::Open);
+        assert_eq!(metrics.times_opened, 1);
+        assert_eq!(metrics.rejected_calls, 1);
+        assert!(metrics.last_opened_at.is_some());
+    }
 }
@@ -0,0 +1,321 @@
+//! Stand-in Verus daemon
+//!
+//! Answers JSON-RPC over HTTP on a loopback port with canned, realistically
+//! shaped results, after an optional fixed latency. The benchmark harness
+//! measures the proxy's own overhead against it, and `--mock-daemon` points
+//! `[verus] rpc_url` at it so frontends can be developed against the proxy
+//! without a node. Every method in the registry is answered: methods with a
+//! response captured from a real daemon (see [`load_fixtures`]) with that
+//! response, common ones with a built-in fixture, `help` with the registry's
+//! method list, other write methods with a transaction id and other read
+//! methods with an empty object. Batches are answered call by call; methods
+//! outside the registry get the daemon's "Method not found" error.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+use warp::Filter;
+
+use crate::config::AppConfig;
+use crate::domain::validation::MethodRegistry;
+use crate::infrastructure::adapters::daemon_recording::RecordedCall;
+use crate::infrastructure::http::listener::{serve_all, BoundListener, ConnectionLimits};
+
+const TIP_HEIGHT: u64 = 3_000_000;
+const BLOCK_HASH: &str = "00000000000d8e1f4ba1b2ca4ad4e1b9f3f2e35fb4c1e8b3df0f4e10c84e1a53";
+const TXID: &str = "6f2b1e9c4d7a3f80e5b2c1d09a8f7e6d5c4b3a29180f7e6d5c4b3a2918070605";
+const ADDRESS: &str = "RVBW4u5A4Vy2zDwngJoMZgSDaXdxLEZx4J";
+
+/// Mock daemon listening on `127.0.0.1`
+pub struct MockDaemon {
+    addr: SocketAddr,
+    calls: Arc<AtomicU64>,
+    task: JoinHandle<()>,
+}
+
+impl MockDaemon {
+    /// Start answering on a free port; every call waits `latency` first
+    pub async fn start(latency: Duration) -> io::Result<Self> {
+        Self::start_with_fixtures(latency, HashMap::new()).await
+    }
+
+    /// Start answering, preferring captured results (by method) over the built-in ones
+    pub async fn start_with_fixtures(latency: Duration, fixtures: HashMap<String, Value>) -> io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let calls = Arc::new(AtomicU64::new(0));
+        let answers = Arc::new(Answers { registry: MethodRegistry::new(), fixtures });
+
+        let counter = calls.clone();
+        let route = warp::post().and(warp::body::json()).and_then(move |body: Value| {
+            let (counter, answers) = (counter.clone(), answers.clone());
+            async move {
+                counter.fetch_add(1, Ordering::Relaxed);
+                if !latency.is_zero() {
+                    tokio::time::sleep(latency).await;
+                }
+                let response = match &body {
+                    Value::Array(batch) => Value::Array(batch.iter().map(|call| answers.reply(call)).collect()),
+                    call => answers.reply(call),
+                };
+                Ok::<_, warp::Rejection>(warp::reply::json(&response))
+            }
+        });
+        let limits = ConnectionLimits::from_config(&AppConfig::default().server);
+        let task = tokio::spawn(serve_all(vec![BoundListener::Tcp(listener)], warp::service(route), limits));
+
+        Ok(Self { addr, calls, task })
+    }
+
+    /// URL for `[verus] rpc_url`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Calls answered so far
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for MockDaemon {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Captured results by method from `dir`, one `<method>.json` call per file as written by `capture-fixtures`
+///
+/// A missing directory holds no fixtures.
+pub fn load_fixtures(dir: &Path) -> io::Result<HashMap<String, Value>> {
+    let mut fixtures = HashMap::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(fixtures),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        let call: RecordedCall = serde_json::from_slice(&std::fs::read(&path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+        if let Some(result) = call.result {
+            fixtures.insert(call.method, result);
+        }
+    }
+    Ok(fixtures)
+}
+
+/// What the mock answers with
+struct Answers {
+    registry: MethodRegistry,
+    fixtures: HashMap<String, Value>,
+}
+
+impl Answers {
+    /// JSON-RPC envelope for `request`
+    fn reply(&self, request: &Value) -> Value {
+        let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let result = self.fixtures.get(method).cloned().or_else(|| result(method)).or_else(|| match method {
+            // Lists the registry, so method discovery finds the mock in agreement
+            "help" => {
+                let mut names: Vec<&str> = self.registry.list_methods().map(|definition| definition.name.as_str()).collect();
+                names.sort_unstable();
+                Some(json!(names.join("\n")))
+            }
+            _ => self
+                .registry
+                .get_method(method)
+                .map(|definition| if definition.read_only { json!({}) } else { json!(TXID) }),
+        });
+        match result {
+            Some(result) => json!({"result": result, "error": null, "id": id}),
+            None => json!({"result": null, "error": {"code": -32601, "message": "Method not found"}, "id": id}),
+        }
+    }
+}
+
+/// Canned result for `method`
+fn result(method: &str) -> Option<Value> {
+    let result = match method {
+        "getinfo" => json!({
+            "version": 2000753,
+            "protocolversion": 170010,
+            "VRSCversion": "1.2.11",
+            "blocks": TIP_HEIGHT,
+            "longestchain": TIP_HEIGHT,
+            "connections": 16,
+            "difficulty": 185_346_719_423.5_f64,
+            "testnet": false,
+            "name": "VRSC",
+            "chainid": "i5w5MuNik5NtLcYmNzcvaoixooEebB6MGV",
+        }),
+        "getblockchaininfo" => json!({
+            "chain": "main",
+            "name": "VRSC",
+            "blocks": TIP_HEIGHT,
+            "headers": TIP_HEIGHT,
+            "bestblockhash": BLOCK_HASH,
+            "difficulty": 185_346_719_423.5_f64,
+            "verificationprogress": 1.0,
+            "chainwork": "0000000000000000000000000000000000000000000000000000a3f2c1d4e5b6",
+            "pruned": false,
+        }),
+        "getnetworkinfo" => json!({
+            "version": 2000753,
+            "subversion": "/MagicBean:2.0.7-3/",
+            "protocolversion": 170010,
+            "connections": 16,
+            "relayfee": 0.000001,
+            "localaddresses": [],
+        }),
+        "getmempoolinfo" => json!({"size": 50, "bytes": 112_640, "usage": 287_744}),
+        "getdifficulty" => json!(185_346_719_423.5_f64),
+        "getblockcount" => json!(TIP_HEIGHT),
+        "getblockheader" => json!({
+            "hash": BLOCK_HASH,
+            "confirmations": 1,
+            "height": TIP_HEIGHT,
+            "version": 65540,
+            "merkleroot": TXID,
+            "time": 1_735_689_600,
+            "bits": "1b03ba9b",
+            "difficulty": 185_346_719_423.5_f64,
+            "previousblockhash": BLOCK_HASH,
+        }),
+        "getchaintips" => json!([{"height": TIP_HEIGHT, "hash": BLOCK_HASH, "branchlen": 0, "status": "active"}]),
+        "getbestblockhash" | "getblockhash" => json!(BLOCK_HASH),
+        "getblock" => json!({
+            "hash": BLOCK_HASH,
+            "confirmations": 1,
+            "size": 4_812,
+            "height": TIP_HEIGHT,
+            "version": 65540,
+            "merkleroot": TXID,
+            "tx": (0..12).map(|i| format!("{:064x}", i)).collect::<Vec<_>>(),
+            "time": 1_735_689_600,
+            "nonce": "0000000000000000000000000000000000000000000000000000000000000000",
+            "bits": "1b03ba9b",
+            "difficulty": 185_346_719_423.5_f64,
+            "previousblockhash": BLOCK_HASH,
+        }),
+        "getrawtransaction" => json!({
+            "txid": TXID,
+            "version": 4,
+            "locktime": 0,
+            "vin": [{"txid": TXID, "vout": 0, "sequence": 4294967295u32}],
+            "vout": [
+                {"value": 12.5, "n": 0, "scriptPubKey": {"type": "pubkeyhash", "addresses": [ADDRESS]}},
+                {"value": 0.9999, "n": 1, "scriptPubKey": {"type": "pubkeyhash", "addresses": [ADDRESS]}},
+            ],
+            "blockhash": BLOCK_HASH,
+            "height": TIP_HEIGHT,
+            "confirmations": 1,
+        }),
+        "getidentity" => json!({
+            "identity": {
+                "version": 3,
+                "flags": 0,
+                "primaryaddresses": [ADDRESS],
+                "minimumsignatures": 1,
+                "name": "mock",
+                "identityaddress": "iMockIdentityAddress1111111111111",
+                "parent": "i5w5MuNik5NtLcYmNzcvaoixooEebB6MGV",
+                "contentmap": {},
+                "revocationauthority": "iMockIdentityAddress1111111111111",
+                "recoveryauthority": "iMockIdentityAddress1111111111111",
+            },
+            "status": "active",
+            "canspendfor": false,
+            "cansignfor": false,
+            "blockheight": TIP_HEIGHT,
+            "txid": TXID,
+        }),
+        "getaddresstxids" => json!([TXID]),
+        "getaddressmempool" => json!([]),
+        "gettxout" => json!({
+            "bestblock": BLOCK_HASH,
+            "confirmations": 1,
+            "value": 12.5,
+            "scriptPubKey": {"type": "pubkeyhash", "addresses": [ADDRESS]},
+            "coinbase": false,
+        }),
+        "getaddressbalance" => json!({"balance": 1_250_000_000u64, "received": 9_870_000_000u64}),
+        "getaddressutxos" => json!((0..5)
+            .map(|i| json!({"address": ADDRESS, "txid": TXID, "outputIndex": i, "satoshis": 100_000_000, "height": TIP_HEIGHT - i}))
+            .collect::<Vec<_>>()),
+        "getcurrency" => json!({
+            "version": 1,
+            "options": 40,
+            "name": "VRSC",
+            "currencyid": "i5w5MuNik5NtLcYmNzcvaoixooEebB6MGV",
+            "systemid": "i5w5MuNik5NtLcYmNzcvaoixooEebB6MGV",
+            "notarizationprotocol": 1,
+            "proofprotocol": 1,
+        }),
+        "getrawmempool" => json!((0..50).map(|i| format!("{:064x}", i)).collect::<Vec<_>>()),
+        "getmininginfo" => json!({"blocks": TIP_HEIGHT, "difficulty": 185_346_719_423.5_f64, "networkhashps": 98_765_432_109u64}),
+        _ => return None,
+    };
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(fixtures: HashMap<String, Value>) -> Answers {
+        Answers { registry: MethodRegistry::new(), fixtures }
+    }
+
+    #[test]
+    fn test_reply_echoes_id_and_rejects_unknown_methods() {
+        let answers = answers(HashMap::new());
+        let ok = answers.reply(&json!({"jsonrpc": "2.0", "method": "getblockcount", "params": [], "id": 7}));
+        assert_eq!(ok["result"], json!(TIP_HEIGHT));
+        assert_eq!(ok["id"], json!(7));
+
+        let unknown = answers.reply(&json!({"method": "stop", "id": 8}));
+        assert!(unknown["result"].is_null());
+        assert_eq!(unknown["error"]["code"], json!(-32601));
+    }
+
+    #[test]
+    fn test_every_registered_method_is_answered() {
+        let answers = answers(HashMap::new());
+        for method in answers.registry.list_methods() {
+            let answer = answers.reply(&json!({"method": method.name, "id": 1}));
+            assert!(answer["error"].is_null(), "{} has no mock answer", method.name);
+        }
+        let sent = answers.reply(&json!({"method": "sendrawtransaction", "params": ["00"], "id": 2}));
+        assert_eq!(sent["result"], json!(TXID));
+    }
+
+    #[test]
+    fn test_captured_fixtures_override_built_in_results() {
+        let dir = std::env::temp_dir().join(format!("verus-rpc-fixtures-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let call = json!({"method": "getblockcount", "params": [], "result": 3_456_789, "recorded_at": "2026-01-01T00:00:00Z"});
+        std::fs::write(dir.join("getblockcount.json"), call.to_string()).unwrap();
+        std::fs::write(dir.join("README.md"), "not a fixture").unwrap();
+
+        let fixtures = load_fixtures(&dir).unwrap();
+        assert_eq!(fixtures.len(), 1);
+        let answers = answers(fixtures);
+        assert_eq!(answers.reply(&json!({"method": "getblockcount", "id": 1}))["result"], json!(3_456_789));
+        assert_eq!(answers.reply(&json!({"method": "getinfo", "id": 2}))["result"]["blocks"], json!(TIP_HEIGHT));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(load_fixtures(&dir).unwrap().is_empty());
+    }
+}
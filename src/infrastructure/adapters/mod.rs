@@ -1,19 +1,65 @@
 //! This module contains adapters for external services and infrastructure concerns.
 
+pub mod allocator;
 pub mod authentication;
+pub mod ban_list;
 pub mod cache;
+pub mod client_profiles;
+pub mod cluster;
 pub mod comprehensive_validator;
+pub mod contract_runner;
+pub mod daemon_auth;
+pub mod daemon_recording;
+pub mod daemon_wait;
+#[cfg(feature = "disk-cache")]
+pub mod disk_cache;
 pub mod external_rpc;
+pub mod handover;
+pub mod issuance_webhook;
 pub mod monitoring;
 pub mod token_issuer;
+pub mod memory_usage;
 pub mod mining_pool;
+pub mod mock_daemon;
+pub mod negative_cache;
+pub mod partners;
 pub mod payments_store;
+pub mod process_metrics;
+pub mod rate_limit_exemptions;
 pub mod revocation_store;
+pub mod runtime;
+#[cfg(feature = "scripting")]
+pub mod script_hooks;
+pub mod sensitive_method_alerts;
+pub mod session_store;
+pub mod sli_metrics;
+pub mod stake_proof;
+pub mod stratum;
+pub mod systemd;
+pub mod tenants;
+pub mod upstream_context;
+pub mod upstream_gate;
+pub mod upstream_metrics;
+pub mod upstream_pipeline;
+pub mod upstream_resolver;
+pub mod validation_cache;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm_plugins;
 
 pub use authentication::AuthenticationAdapter;
-pub use cache::{CacheAdapter, CacheConfig, CacheEntry, CacheStats};
+pub use cache::{CacheAdapter, CacheConfig, CacheEntry, CacheStats, RedisHealth, RedisLink};
+pub use client_profiles::ClientProfiles;
+pub use cluster::{ClusterCoordinator, ClusterLock, ClusterMetrics, HashRing, KeyOwner};
 pub use comprehensive_validator::ComprehensiveValidator;
-pub use external_rpc::ExternalRpcAdapter;
+pub use contract_runner::{ContractReport, ContractRunner, Observed};
+pub use daemon_auth::DaemonAuth;
+pub use daemon_recording::DaemonRecording;
+pub use daemon_wait::DaemonWait;
+pub use external_rpc::{ExternalRpcAdapter, UpstreamGuards};
+pub use handover::{Handover, UpgradePhase, UpgradeStatus};
+pub use issuance_webhook::{IssuanceSource, IssuanceWebhook};
+pub use memory_usage::{InFlightRequest, MemoryUsage};
+pub use mock_daemon::MockDaemon;
 pub use monitoring::{MonitoringAdapter, MetricsEvent, MetricsSummary};
 pub use token_issuer::{
     TokenIssuerAdapter, TokenIssuanceRequest, TokenIssuanceResponse,
@@ -22,7 +68,24 @@ pub use token_issuer::{
 };
 pub use mining_pool::{
     MiningPoolClient, PoolShare, PoolValidationResponse, PoolShareRequest,
-    CircuitBreaker, CircuitBreakerState
+    PoolBatchRequest, PoolBatchResponse, CircuitBreaker, CircuitBreakerState, CircuitBreakerMetrics
 }; 
+pub use ban_list::{BanEntry, BanList, BanMetrics, Offense};
+pub use negative_cache::{NegativeCache, NegativeCacheMetrics};
+pub use partners::{PartnerRegistry, PartnerTokenRequest, PartnerUsage, PartnerUsageRegistry};
 pub use payments_store::PaymentsStore;
-pub use revocation_store::RevocationStore;
\ No newline at end of file
+pub use process_metrics::ProcessSnapshot;
+pub use rate_limit_exemptions::{ExemptionMetrics, ExemptionReason, RateLimitExemptions};
+pub use revocation_store::{RevocationEntry, RevocationScope, RevocationStore};
+pub use runtime::CpuPool;
+pub use sensitive_method_alerts::{SensitiveMethodAlert, SensitiveMethodAlerts};
+pub use session_store::{Session, SessionStore};
+pub use sli_metrics::{RequestOutcome, SliMetrics, SliSummary};
+pub use stake_proof::{StakeChallenge, StakeProof, StakeVerifier};
+pub use stratum::StratumSession;
+pub use tenants::{Tenant, TenantRejection, Tenants};
+pub use upstream_context::UpstreamContext;
+pub use upstream_gate::{UpstreamGate, UpstreamGateMetrics};
+pub use upstream_metrics::{UpstreamMetrics, UpstreamStats, SlowQueryEntry};
+pub use upstream_pipeline::UpstreamPipeline;
+pub use upstream_resolver::UpstreamResolver;
\ No newline at end of file
@@ -0,0 +1,329 @@
+//! Negative cache for block and transaction lookups that found nothing
+//!
+//! Explorers and scrapers repeat lookups for txids and block hashes the
+//! daemon does not know. With `[negative_cache] enabled`, every `-5`
+//! ("not found") answer to `getrawtransaction`, `getblock` or `getblockheader`
+//! by hash adds the hash to a Bloom filter, and later lookups for it are
+//! answered with the same error in `ExternalRpcAdapter::send_request` without
+//! a daemon call. A new block may confirm any of those identifiers, so the
+//! filter is cleared whenever the chain height changes, after a
+//! `sendrawtransaction` goes through, and once it holds `expected_items`
+//! entries. A false positive answers "not found" for an identifier that
+//! exists, at most until the next block, at `false_positive_rate`.
+
+use crate::{
+    config::app_config::NegativeCacheConfig,
+    domain::rpc::{ClientInfo, RpcRequest},
+    infrastructure::adapters::ExternalRpcAdapter,
+    shared::error::{AppError, AppResult},
+};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+/// Probabilistic set of identifiers
+#[derive(Debug)]
+struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+    items: usize,
+    capacity: usize,
+}
+
+impl BloomFilter {
+    /// Sized for `capacity` items at `false_positive_rate`
+    fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-(capacity.max(1) as f64) * false_positive_rate.ln() / (ln2 * ln2)).ceil().max(64.0) as usize;
+        let hashes = ((bits as f64 / capacity.max(1) as f64) * ln2).round().clamp(1.0, 32.0) as u32;
+        Self { bits: vec![0; bits.div_ceil(64)], hashes, items: 0, capacity }
+    }
+
+    /// Bit positions by double hashing
+    fn positions(&self, key: &str) -> impl Iterator<Item = usize> {
+        let seeded = |seed: u8| {
+            let mut hasher = DefaultHasher::new();
+            seed.hash(&mut hasher);
+            key.hash(&mut hasher);
+            hasher.finish()
+        };
+        let (h1, h2) = (seeded(0), seeded(1) | 1);
+        let len = (self.bits.len() * 64) as u64;
+        (0..self.hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.positions(key).all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    fn insert(&mut self, key: &str) {
+        if self.items >= self.capacity {
+            self.clear();
+        }
+        let positions: Vec<usize> = self.positions(key).collect();
+        for bit in positions {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        self.items += 1;
+    }
+
+    fn clear(&mut self) {
+        self.bits.fill(0);
+        self.items = 0;
+    }
+}
+
+/// Negative cache counters
+#[derive(Debug, Clone, Serialize)]
+pub struct NegativeCacheMetrics {
+    pub enabled: bool,
+    /// Lookups checked against the filter
+    pub lookups: u64,
+    /// Lookups answered from the filter
+    pub hits: u64,
+    /// `hits / lookups`
+    pub hit_rate: f64,
+    /// Identifiers added since startup
+    pub inserts: u64,
+    /// Filter resets (new blocks, broadcasts, capacity)
+    pub resets: u64,
+    /// Identifiers currently in the filter
+    pub items: usize,
+}
+
+/// Bloom filter of identifiers the daemon recently reported missing
+#[derive(Debug, Default)]
+pub struct NegativeCache {
+    /// `None` unless `[negative_cache]` is enabled
+    filter: Mutex<Option<BloomFilter>>,
+    lookups: AtomicU64,
+    hits: AtomicU64,
+    inserts: AtomicU64,
+    resets: AtomicU64,
+}
+
+impl NegativeCache {
+    /// Negative cache for `[negative_cache]`
+    pub fn new(config: &NegativeCacheConfig) -> Self {
+        let filter = config
+            .enabled
+            .then(|| BloomFilter::new(config.expected_items, config.false_positive_rate));
+        Self { filter: Mutex::new(filter), ..Default::default() }
+    }
+
+    /// The daemon's "not found" error if `request` looks up an identifier in the filter
+    pub fn check(&self, request: &RpcRequest) -> Option<AppError> {
+        let (kind, key) = lookup_key(request)?;
+        let guard = self.lock();
+        let filter = guard.as_ref()?;
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        if !filter.contains(&key) {
+            return None;
+        }
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(not_found(kind))
+    }
+
+    /// Learn from a daemon answer: remember "not found" lookups, forget everything after a broadcast
+    pub fn observe<T>(&self, request: &RpcRequest, result: &AppResult<T>) {
+        if request.method == "sendrawtransaction" && result.is_ok() {
+            self.reset();
+            return;
+        }
+        let Err(AppError::Rpc(message)) = result else { return };
+        if !is_not_found(message) {
+            return;
+        }
+        let Some((_, key)) = lookup_key(request) else { return };
+        if let Some(filter) = self.lock().as_mut() {
+            if filter.items >= filter.capacity {
+                self.resets.fetch_add(1, Ordering::Relaxed);
+            }
+            filter.insert(&key);
+            self.inserts.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Empty the filter
+    pub fn reset(&self) {
+        if let Some(filter) = self.lock().as_mut() {
+            filter.clear();
+            self.resets.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Reset the filter whenever the chain height changes
+    pub fn start_reset_poller(self: Arc<Self>, rpc: Arc<ExternalRpcAdapter>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            let mut last_height: Option<u64> = None;
+            loop {
+                ticker.tick().await;
+                let height = match rpc.send_request(&height_request()).await {
+                    Ok(response) => response.result.and_then(|height| height.as_u64()),
+                    Err(e) => {
+                        debug!("Negative cache height check failed: {}", e);
+                        continue;
+                    }
+                };
+                if height.is_some() && last_height.is_some() && height != last_height {
+                    self.reset();
+                }
+                last_height = height.or(last_height);
+            }
+        });
+    }
+
+    /// Current counters
+    pub fn metrics(&self) -> NegativeCacheMetrics {
+        let guard = self.lock();
+        let lookups = self.lookups.load(Ordering::Relaxed);
+        let hits = self.hits.load(Ordering::Relaxed);
+        NegativeCacheMetrics {
+            enabled: guard.is_some(),
+            lookups,
+            hits,
+            hit_rate: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+            inserts: self.inserts.load(Ordering::Relaxed),
+            resets: self.resets.load(Ordering::Relaxed),
+            items: guard.as_ref().map_or(0, |filter| filter.items),
+        }
+    }
+
+    /// Render negative cache metrics in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let m = self.metrics();
+        let mut out = String::new();
+        out.push_str("# HELP verus_negative_cache_lookups_total Lookups checked against the negative cache\n");
+        out.push_str("# TYPE verus_negative_cache_lookups_total counter\n");
+        out.push_str(&format!("verus_negative_cache_lookups_total {}\n", m.lookups));
+        out.push_str("# HELP verus_negative_cache_hits_total Lookups answered \"not found\" without a daemon call\n");
+        out.push_str("# TYPE verus_negative_cache_hits_total counter\n");
+        out.push_str(&format!("verus_negative_cache_hits_total {}\n", m.hits));
+        out.push_str("# HELP verus_negative_cache_resets_total Negative cache filter resets\n");
+        out.push_str("# TYPE verus_negative_cache_resets_total counter\n");
+        out.push_str(&format!("verus_negative_cache_resets_total {}\n", m.resets));
+        out.push_str("# HELP verus_negative_cache_items Identifiers in the negative cache filter\n");
+        out.push_str("# TYPE verus_negative_cache_items gauge\n");
+        out.push_str(&format!("verus_negative_cache_items {}\n", m.items));
+        out
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<BloomFilter>> {
+        self.filter.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Kind (`tx` or `block`) and filter key of a lookup by hash
+fn lookup_key(request: &RpcRequest) -> Option<(&'static str, String)> {
+    let kind = match request.method.as_str() {
+        "getrawtransaction" => "tx",
+        "getblock" | "getblockheader" => "block",
+        _ => return None,
+    };
+    let hash = request.parameters.as_ref()?.get(0)?.as_str()?;
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some((kind, format!("{}:{}", kind, hash.to_ascii_lowercase())))
+}
+
+/// Daemon errors meaning the identifier is unknown (`RPC_INVALID_ADDRESS_OR_KEY`)
+fn is_not_found(message: &str) -> bool {
+    message.replace(' ', "").contains("\"code\":-5")
+}
+
+fn not_found(kind: &str) -> AppError {
+    let message = match kind {
+        "tx" => "No information available about transaction",
+        _ => "Block not found",
+    };
+    AppError::Rpc(format!("RPC error: {}", json!({ "code": -5, "message": message })))
+}
+
+fn height_request() -> RpcRequest {
+    RpcRequest::new(
+        "getblockcount".to_string(),
+        Some(json!([])),
+        Some(json!("negative_cache_getblockcount")),
+        ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: Some("negative-cache".to_string()),
+            auth_token: None,
+            timestamp: Utc::now(),
+            request_id: None,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXID: &str = "00000000000000000000000000000000000000000000000000000000000000ab";
+
+    fn request(method: &str, params: serde_json::Value) -> RpcRequest {
+        let mut request = height_request();
+        request.method = method.to_string();
+        request.parameters = Some(params);
+        request
+    }
+
+    fn enabled() -> NegativeCache {
+        NegativeCache::new(&NegativeCacheConfig { enabled: true, ..NegativeCacheConfig::default() })
+    }
+
+    #[test]
+    fn test_not_found_lookups_short_circuit() {
+        let cache = enabled();
+        let lookup = request("getrawtransaction", json!([TXID, 1]));
+        assert!(cache.check(&lookup).is_none());
+
+        let missing: AppResult<()> = Err(not_found("tx"));
+        cache.observe(&lookup, &missing);
+        let upper = request("getrawtransaction", json!([TXID.to_uppercase()]));
+        assert!(matches!(cache.check(&upper), Some(AppError::Rpc(message)) if is_not_found(&message)));
+        // Blocks and transactions live in separate namespaces
+        assert!(cache.check(&request("getblock", json!([TXID]))).is_none());
+
+        let metrics = cache.metrics();
+        assert_eq!((metrics.lookups, metrics.hits, metrics.inserts), (3, 1, 1));
+
+        cache.observe(&request("sendrawtransaction", json!(["00"])), &Ok(()));
+        assert!(cache.check(&lookup).is_none());
+        assert_eq!(cache.metrics().resets, 1);
+    }
+
+    #[test]
+    fn test_other_errors_are_not_cached() {
+        let cache = enabled();
+        let lookup = request("getblock", json!([TXID]));
+        cache.observe(&lookup, &Err::<(), _>(AppError::Rpc("RPC request failed after 3 attempts".to_string())));
+        assert!(cache.check(&lookup).is_none());
+
+        let disabled = NegativeCache::default();
+        disabled.observe(&lookup, &Err::<(), _>(not_found("block")));
+        assert!(disabled.check(&lookup).is_none());
+        assert_eq!(disabled.metrics().lookups, 0);
+    }
+
+    #[test]
+    fn test_bloom_filter_false_positive_rate() {
+        let mut filter = BloomFilter::new(1000, 0.01);
+        for i in 0..1000 {
+            filter.insert(&format!("tx:{}", i));
+        }
+        assert!((0..1000).all(|i| filter.contains(&format!("tx:{}", i))));
+        let false_positives = (1000..11000).filter(|i| filter.contains(&format!("tx:{}", i))).count();
+        assert!(false_positives < 300, "{} false positives", false_positives);
+
+        filter.insert("tx:overflow");
+        assert_eq!(filter.items, 1);
+    }
+}
@@ -0,0 +1,371 @@
+//! Partner registry and signed partner token requests
+//!
+//! Partners (trusted DEXs, wallets, explorers) are configured with an ed25519
+//! public key, the permissions they may grant and a rate multiplier. A partner
+//! token request must be signed with the partner's key over a canonical
+//! message that binds the partner, a timestamp, a single-use nonce, the user
+//! and the requested permissions, so a captured request cannot be replayed or
+//! altered.
+//!
+//! Partner tokens are issued by the token service and used against the RPC
+//! server, so usage counters are kept in Redis when a connection is
+//! configured; `GET /admin/partners` then reports both processes' counts.
+//! Without Redis each process only sees its own counters.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use chrono::Utc;
+use ed25519_dalek::VerifyingKey;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::app_config::{PartnerConfig, PartnersConfig};
+use crate::shared::error::{AppError, AppResult};
+use crate::shared::security::{verify_ed25519_hex, SignatureError};
+
+/// Set of partner ids with usage recorded in Redis
+const USAGE_IDS_KEY: &str = "partner:usage:ids";
+
+/// Signed partner assertion carried by `TokenIssuanceMode::Partner`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartnerTokenRequest {
+    /// Configured partner id
+    pub partner_id: String,
+    /// Unix timestamp the request was signed at
+    pub timestamp: i64,
+    /// Single-use random value
+    pub nonce: String,
+    /// Hex-encoded ed25519 signature over `signing_message`
+    pub signature: String,
+}
+
+/// Canonical message a partner signs for a token request
+pub fn signing_message(partner_id: &str, timestamp: i64, nonce: &str, user_id: &str, permissions: &[String]) -> String {
+    let mut permissions = permissions.to_vec();
+    permissions.sort();
+    format!("verus-rpc-partner-token\n{}\n{}\n{}\n{}\n{}", partner_id, timestamp, nonce, user_id, permissions.join(","))
+}
+
+struct RegisteredPartner {
+    config: PartnerConfig,
+    key: VerifyingKey,
+}
+
+/// Configured partners and their verification keys
+pub struct PartnerRegistry {
+    partners: HashMap<String, RegisteredPartner>,
+    max_clock_skew_seconds: i64,
+    /// Nonces seen within the clock-skew window, keyed by `partner:nonce`
+    seen_nonces: Mutex<HashMap<String, i64>>,
+}
+
+impl PartnerRegistry {
+    /// Build the registry, rejecting malformed public keys
+    pub fn new(config: &PartnersConfig) -> AppResult<Self> {
+        let mut partners = HashMap::new();
+        for partner in &config.partners {
+            let key = parse_public_key(&partner.public_key)
+                .ok_or_else(|| AppError::Config(format!("partner {} has an invalid ed25519 public key", partner.id)))?;
+            if partners
+                .insert(partner.id.clone(), RegisteredPartner { config: partner.clone(), key })
+                .is_some()
+            {
+                return Err(AppError::Config(format!("duplicate partner id {}", partner.id)));
+            }
+        }
+        Ok(Self {
+            partners,
+            max_clock_skew_seconds: config.max_clock_skew_seconds as i64,
+            seen_nonces: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Configured partners
+    pub fn partners(&self) -> Vec<PartnerConfig> {
+        let mut partners: Vec<PartnerConfig> = self.partners.values().map(|p| p.config.clone()).collect();
+        partners.sort_by(|a, b| a.id.cmp(&b.id));
+        partners
+    }
+
+    /// Verify a signed partner request for the given user and permissions
+    pub fn verify(&self, request: &PartnerTokenRequest, user_id: &str, permissions: &[String]) -> AppResult<PartnerConfig> {
+        let partner = self
+            .partners
+            .get(&request.partner_id)
+            .ok_or_else(|| AppError::Authentication(format!("unknown partner {}", request.partner_id)))?;
+
+        let result = self.check(partner, request, user_id, permissions);
+        if result.is_err() {
+            PartnerUsageRegistry::global().record_rejected(&request.partner_id);
+        }
+        result.map(|_| partner.config.clone())
+    }
+
+    fn check(&self, partner: &RegisteredPartner, request: &PartnerTokenRequest, user_id: &str, permissions: &[String]) -> AppResult<()> {
+        let now = Utc::now().timestamp();
+        if (now - request.timestamp).abs() > self.max_clock_skew_seconds {
+            return Err(AppError::Authentication("partner request timestamp outside allowed window".to_string()));
+        }
+
+        if let Some(denied) = permissions.iter().find(|p| !partner.config.allowed_permissions.contains(p)) {
+            return Err(AppError::Authentication(format!("partner {} may not grant permission {}", partner.config.id, denied)));
+        }
+
+        let message = signing_message(&request.partner_id, request.timestamp, &request.nonce, user_id, permissions);
+        verify_ed25519_hex(&partner.key, message.as_bytes(), &request.signature).map_err(|e| match e {
+            SignatureError::Malformed => AppError::Authentication("malformed partner signature".to_string()),
+            SignatureError::Invalid => AppError::Authentication("invalid partner signature".to_string()),
+        })?;
+
+        // Only record the nonce once the signature is known to be genuine
+        let mut seen = self
+            .seen_nonces
+            .lock()
+            .map_err(|_| AppError::Internal("partner nonce lock poisoned".to_string()))?;
+        seen.retain(|_, ts| (now - *ts).abs() <= self.max_clock_skew_seconds);
+        let nonce_key = format!("{}:{}", request.partner_id, request.nonce);
+        if seen.insert(nonce_key, request.timestamp).is_some() {
+            return Err(AppError::Authentication("partner request nonce already used".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn parse_public_key(hex_key: &str) -> Option<VerifyingKey> {
+    let bytes = hex::decode(hex_key).ok()?;
+    let bytes: [u8; 32] = bytes.as_slice().try_into().ok()?;
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+/// Usage counters for one partner
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PartnerUsage {
+    pub partner_id: String,
+    pub tokens_issued: u64,
+    pub rejected_requests: u64,
+    pub rpc_requests: u64,
+    pub last_seen: Option<chrono::DateTime<Utc>>,
+}
+
+/// Partner usage counters, shared through Redis when configured
+#[derive(Default)]
+pub struct PartnerUsageRegistry {
+    usage: Mutex<HashMap<String, PartnerUsage>>,
+    redis: RwLock<Option<Arc<ConnectionManager>>>,
+}
+
+impl PartnerUsageRegistry {
+    /// Shared registry used by token issuance and request processing
+    pub fn global() -> &'static PartnerUsageRegistry {
+        static USAGE: OnceLock<PartnerUsageRegistry> = OnceLock::new();
+        USAGE.get_or_init(PartnerUsageRegistry::default)
+    }
+
+    /// Share counters with other processes through `redis` when given
+    pub fn configure(&self, redis: Option<Arc<ConnectionManager>>) {
+        *self.redis.write().unwrap_or_else(|e| e.into_inner()) = redis;
+    }
+
+    pub fn record_issued(&self, partner_id: &str) {
+        self.update(partner_id, "tokens_issued", |u| u.tokens_issued += 1);
+    }
+
+    pub fn record_rejected(&self, partner_id: &str) {
+        self.update(partner_id, "rejected_requests", |u| u.rejected_requests += 1);
+    }
+
+    /// Record an RPC request made with a partner token
+    pub fn record_request(&self, partner_id: &str) {
+        self.update(partner_id, "rpc_requests", |u| u.rpc_requests += 1);
+    }
+
+    /// Usage for every partner seen so far, by any process sharing the Redis connection
+    pub async fn snapshot(&self) -> Vec<PartnerUsage> {
+        if let Some(redis) = self.redis() {
+            match Self::load(&redis).await {
+                Ok(usage) => return usage,
+                Err(e) => warn!("Partner usage lookup in Redis failed, reporting this process only: {}", e),
+            }
+        }
+        let mut usage: Vec<PartnerUsage> = self
+            .usage
+            .lock()
+            .map(|u| u.values().cloned().collect())
+            .unwrap_or_default();
+        usage.sort_by(|a, b| a.partner_id.cmp(&b.partner_id));
+        usage
+    }
+
+    fn redis(&self) -> Option<Arc<ConnectionManager>> {
+        self.redis.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn key(partner_id: &str) -> String {
+        format!("partner:usage:{}", partner_id)
+    }
+
+    fn update(&self, partner_id: &str, counter: &'static str, f: impl FnOnce(&mut PartnerUsage)) {
+        let now = Utc::now();
+        if let Ok(mut usage) = self.usage.lock() {
+            let entry = usage.entry(partner_id.to_string()).or_insert_with(|| PartnerUsage {
+                partner_id: partner_id.to_string(),
+                ..Default::default()
+            });
+            f(entry);
+            entry.last_seen = Some(now);
+        }
+        if let Some(redis) = self.redis() {
+            let partner_id = partner_id.to_string();
+            tokio::spawn(async move {
+                if let Err(e) = Self::persist(&redis, &partner_id, counter, now.timestamp()).await {
+                    warn!(partner = %partner_id, "Partner usage not shared through Redis: {}", e);
+                }
+            });
+        }
+    }
+
+    async fn persist(redis: &ConnectionManager, partner_id: &str, counter: &str, now: i64) -> AppResult<()> {
+        let mut conn = redis.clone();
+        let key = Self::key(partner_id);
+        let _: () = conn
+            .hincr(&key, counter, 1)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis hincrby: {}", e)))?;
+        let _: () = conn
+            .hset(&key, "last_seen", now)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis hset: {}", e)))?;
+        let _: () = conn
+            .sadd(USAGE_IDS_KEY, partner_id)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis sadd: {}", e)))?;
+        Ok(())
+    }
+
+    async fn load(redis: &ConnectionManager) -> AppResult<Vec<PartnerUsage>> {
+        let mut conn = redis.clone();
+        let mut ids: Vec<String> = conn
+            .smembers(USAGE_IDS_KEY)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis smembers: {}", e)))?;
+        ids.sort();
+        let mut usage = Vec::with_capacity(ids.len());
+        for partner_id in ids {
+            let fields: HashMap<String, i64> = conn
+                .hgetall(Self::key(&partner_id))
+                .await
+                .map_err(|e| AppError::Internal(format!("redis hgetall: {}", e)))?;
+            let count = |field: &str| fields.get(field).copied().unwrap_or_default().max(0) as u64;
+            usage.push(PartnerUsage {
+                tokens_issued: count("tokens_issued"),
+                rejected_requests: count("rejected_requests"),
+                rpc_requests: count("rpc_requests"),
+                last_seen: fields.get("last_seen").and_then(|ts| chrono::DateTime::from_timestamp(*ts, 0)),
+                partner_id,
+            });
+        }
+        Ok(usage)
+    }
+}
+
+/// Partner id carried by a token's permissions (`partner_<id>`), if any
+pub fn partner_id_from_permissions(permissions: &[String]) -> Option<&str> {
+    if !permissions.iter().any(|p| p == "partner_validated") {
+        return None;
+    }
+    permissions.iter().find_map(|p| p.strip_prefix("partner_").filter(|id| *id != "validated"))
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// Deterministic partner key and config for tests
+    pub fn partner(id: &str, seed: u8) -> (SigningKey, PartnerConfig) {
+        let signing_key = SigningKey::from_bytes(&[seed; 32]);
+        let config = PartnerConfig {
+            id: id.to_string(),
+            name: None,
+            public_key: hex::encode(signing_key.verifying_key().to_bytes()),
+            allowed_permissions: vec!["read".to_string(), "write".to_string()],
+            rate_multiplier: 3.0,
+            token_ttl_seconds: 3600 * 24,
+        };
+        (signing_key, config)
+    }
+
+    /// Sign a partner token request
+    pub fn sign(key: &SigningKey, partner_id: &str, nonce: &str, user_id: &str, permissions: &[String]) -> PartnerTokenRequest {
+        let timestamp = Utc::now().timestamp();
+        let message = signing_message(partner_id, timestamp, nonce, user_id, permissions);
+        PartnerTokenRequest {
+            partner_id: partner_id.to_string(),
+            timestamp,
+            nonce: nonce.to_string(),
+            signature: hex::encode(key.sign(message.as_bytes()).to_bytes()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::{partner, sign};
+    use super::*;
+
+    fn registry() -> (ed25519_dalek::SigningKey, PartnerRegistry) {
+        let (key, config) = partner("dex", 7);
+        let registry = PartnerRegistry::new(&PartnersConfig {
+            enabled: true,
+            max_clock_skew_seconds: 300,
+            partners: vec![config],
+        })
+        .unwrap();
+        (key, registry)
+    }
+
+    #[test]
+    fn test_valid_signature_accepted_once() {
+        let (key, registry) = registry();
+        let permissions = vec!["read".to_string()];
+        let request = sign(&key, "dex", "n1", "user", &permissions);
+
+        assert_eq!(registry.verify(&request, "user", &permissions).unwrap().id, "dex");
+        assert!(registry.verify(&request, "user", &permissions).is_err());
+    }
+
+    #[test]
+    fn test_tampered_or_disallowed_requests_rejected() {
+        let (key, registry) = registry();
+        let permissions = vec!["read".to_string()];
+        let request = sign(&key, "dex", "n2", "user", &permissions);
+        assert!(registry.verify(&request, "someone_else", &permissions).is_err());
+
+        let admin = vec!["admin".to_string()];
+        let request = sign(&key, "dex", "n3", "user", &admin);
+        assert!(registry.verify(&request, "user", &admin).is_err());
+    }
+
+    #[test]
+    fn test_partner_id_from_permissions() {
+        let permissions = vec!["read".to_string(), "partner_validated".to_string(), "partner_dex".to_string()];
+        assert_eq!(partner_id_from_permissions(&permissions), Some("dex"));
+        assert_eq!(partner_id_from_permissions(&["partner_dex".to_string()]), None);
+    }
+
+    #[tokio::test]
+    async fn test_usage_without_redis_reports_this_process() {
+        let usage = PartnerUsageRegistry::default();
+        usage.record_issued("dex");
+        usage.record_request("dex");
+        usage.record_request("dex");
+        usage.record_rejected("amm");
+
+        let snapshot = usage.snapshot().await;
+        assert_eq!(snapshot.iter().map(|u| u.partner_id.as_str()).collect::<Vec<_>>(), ["amm", "dex"]);
+        assert_eq!((snapshot[1].tokens_issued, snapshot[1].rpc_requests), (1, 2));
+        assert_eq!(snapshot[0].rejected_requests, 1);
+    }
+}
@@ -1,15 +1,34 @@
 //! Redis-backed payments store
 
 use crate::shared::error::{AppError, AppResult};
-use crate::domain::payments::PaymentSession;
+use crate::domain::payments::{PaymentSession, PaymentStatus};
 use redis::{aio::ConnectionManager, AsyncCommands};
+use std::collections::VecDeque;
 use std::sync::Arc;
 
+/// Redis list of payment ids with a refund
+const REFUNDS_KEY: &str = "payments:refunds";
+
+/// Redis set of payment ids with a transaction that may still change state
+const ACTIVE_KEY: &str = "payments:active";
+
 /// Abstraction for persisting payment sessions
 #[derive(Clone)]
 pub struct PaymentsStore {
     redis: Option<Arc<ConnectionManager>>, // optional; can operate in-memory only if None
     memory: Arc<tokio::sync::RwLock<std::collections::HashMap<String, PaymentSession>>>,
+    /// txid -> payment_id that submitted it
+    txids: Arc<tokio::sync::RwLock<std::collections::HashMap<String, String>>>,
+    /// identity -> payment ids, oldest first
+    identities: Arc<tokio::sync::RwLock<std::collections::HashMap<String, Vec<String>>>>,
+    /// payment ids with a refund, oldest first
+    refunds: Arc<tokio::sync::RwLock<Vec<String>>>,
+    /// payment ids whose refund was handed to the wallet
+    refund_sends: Arc<tokio::sync::RwLock<std::collections::HashSet<String>>>,
+    /// address type -> pre-generated unused addresses, oldest first
+    address_pool: Arc<tokio::sync::RwLock<std::collections::HashMap<String, VecDeque<String>>>>,
+    /// Redis retention for sessions and identity indexes
+    retention_seconds: u64,
 }
 
 impl PaymentsStore {
@@ -17,13 +36,273 @@ impl PaymentsStore {
         Self {
             redis,
             memory: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            txids: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            identities: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            refunds: Arc::new(tokio::sync::RwLock::new(Vec::new())),
+            refund_sends: Arc::new(tokio::sync::RwLock::new(std::collections::HashSet::new())),
+            address_pool: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            retention_seconds: 48 * 3600,
+        }
+    }
+
+    /// Keep sessions in Redis for `seconds` (default 48h)
+    pub fn with_retention(mut self, seconds: u64) -> Self {
+        self.retention_seconds = seconds.max(48 * 3600);
+        self
+    }
+
+    fn identity_key(identity: &str) -> String {
+        format!("payments:identity:{}", identity)
+    }
+
+    /// Index a payment under the identity that requested it
+    pub async fn index_identity(&self, identity: &str, payment_id: &str) -> AppResult<()> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let key = Self::identity_key(identity);
+            let _: () = conn
+                .rpush(&key, payment_id)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis rpush: {}", e)))?;
+            let _: () = conn
+                .expire(&key, self.retention_seconds as i64)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis expire: {}", e)))?;
         }
+        self.identities
+            .write()
+            .await
+            .entry(identity.to_string())
+            .or_default()
+            .push(payment_id.to_string());
+        Ok(())
+    }
+
+    /// Sessions requested by an identity that are still retained, oldest first
+    pub async fn list_by_identity(&self, identity: &str) -> AppResult<Vec<PaymentSession>> {
+        let payment_ids: Vec<String> = match &self.redis {
+            Some(redis) => {
+                let mut conn = (**redis).clone();
+                conn.lrange(Self::identity_key(identity), 0, -1)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("redis lrange: {}", e)))?
+            }
+            None => self.identities.read().await.get(identity).cloned().unwrap_or_default(),
+        };
+        let mut sessions = Vec::with_capacity(payment_ids.len());
+        for payment_id in payment_ids {
+            if let Some(session) = self.get(&payment_id).await? {
+                sessions.push(session);
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Index a payment that has a refund (call once, when the refund is opened)
+    pub async fn index_refund(&self, payment_id: &str) -> AppResult<()> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let _: () = conn
+                .rpush(REFUNDS_KEY, payment_id)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis rpush: {}", e)))?;
+        }
+        let mut refunds = self.refunds.write().await;
+        if !refunds.iter().any(|id| id == payment_id) {
+            refunds.push(payment_id.to_string());
+        }
+        Ok(())
+    }
+
+    /// Sessions with a refund that are still retained, oldest first
+    pub async fn list_refunds(&self) -> AppResult<Vec<PaymentSession>> {
+        let payment_ids: Vec<String> = match &self.redis {
+            Some(redis) => {
+                let mut conn = (**redis).clone();
+                conn.lrange(REFUNDS_KEY, 0, -1)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("redis lrange: {}", e)))?
+            }
+            None => self.refunds.read().await.clone(),
+        };
+        let mut seen = std::collections::HashSet::new();
+        let mut sessions = Vec::with_capacity(payment_ids.len());
+        for payment_id in payment_ids {
+            if !seen.insert(payment_id.clone()) {
+                continue;
+            }
+            if let Some(session) = self.get(&payment_id).await?.filter(|s| s.refund.is_some()) {
+                sessions.push(session);
+            }
+        }
+        Ok(sessions)
+    }
+
+    fn refund_send_key(payment_id: &str) -> String {
+        format!("payments:refund-send:{}", payment_id)
+    }
+
+    /// Claim the right to send a payment's refund; false when it was already claimed
+    ///
+    /// Guards against concurrent status checks or approvals sending a refund twice.
+    pub async fn claim_refund_send(&self, payment_id: &str) -> AppResult<bool> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let set: Option<String> = redis::cmd("SET")
+                .arg(Self::refund_send_key(payment_id))
+                .arg(1)
+                .arg("NX")
+                .arg("EX")
+                .arg(self.retention_seconds)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis set: {}", e)))?;
+            return Ok(set.is_some());
+        }
+        Ok(self.refund_sends.write().await.insert(payment_id.to_string()))
+    }
+
+    /// Release a refund claim after the wallet refused the send
+    pub async fn release_refund_send(&self, payment_id: &str) -> AppResult<()> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let _: () = conn
+                .del(Self::refund_send_key(payment_id))
+                .await
+                .map_err(|e| AppError::Internal(format!("redis del: {}", e)))?;
+            return Ok(());
+        }
+        self.refund_sends.write().await.remove(payment_id);
+        Ok(())
     }
 
     fn key(payment_id: &str) -> String {
         format!("payments:{}", payment_id)
     }
 
+    fn tx_key(txid: &str) -> String {
+        format!("payments:tx:{}", txid)
+    }
+
+    fn pool_key(address_type: &str) -> String {
+        format!("payments:pool:{}", address_type)
+    }
+
+    /// Add an unused address to the pool for its type
+    ///
+    /// Pool entries do not expire: the addresses belong to the daemon wallet
+    /// and stay valid, and with Redis they are shared by all replicas.
+    pub async fn pool_push(&self, address_type: &str, address: &str) -> AppResult<()> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let _: () = conn
+                .rpush(Self::pool_key(address_type), address)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis rpush: {}", e)))?;
+            return Ok(());
+        }
+        self.address_pool
+            .write()
+            .await
+            .entry(address_type.to_string())
+            .or_default()
+            .push_back(address.to_string());
+        Ok(())
+    }
+
+    /// Take the oldest pooled address of a type; each address is handed out once
+    pub async fn pool_pop(&self, address_type: &str) -> AppResult<Option<String>> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            return conn
+                .lpop(Self::pool_key(address_type), None)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis lpop: {}", e)));
+        }
+        Ok(self
+            .address_pool
+            .write()
+            .await
+            .get_mut(address_type)
+            .and_then(|pool| pool.pop_front()))
+    }
+
+    /// Number of pooled addresses of a type
+    pub async fn pool_len(&self, address_type: &str) -> AppResult<usize> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            return conn
+                .llen(Self::pool_key(address_type))
+                .await
+                .map_err(|e| AppError::Internal(format!("redis llen: {}", e)));
+        }
+        Ok(self.address_pool.read().await.get(address_type).map_or(0, VecDeque::len))
+    }
+
+    /// Record that a payment submitted a transaction
+    ///
+    /// Returns the owning payment id when the txid was already claimed by a
+    /// different payment; claiming again for the same payment is a no-op.
+    pub async fn claim_txid(&self, txid: &str, payment_id: &str) -> AppResult<Option<String>> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let key = Self::tx_key(txid);
+            let set: Option<String> = redis::cmd("SET")
+                .arg(&key)
+                .arg(payment_id)
+                .arg("NX")
+                .arg("EX")
+                .arg(self.retention_seconds)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis set: {}", e)))?;
+            if set.is_none() {
+                let owner: Option<String> = conn
+                    .get(&key)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("redis get: {}", e)))?;
+                if let Some(owner) = owner.filter(|o| o != payment_id) {
+                    return Ok(Some(owner));
+                }
+            }
+            return Ok(None);
+        }
+
+        let mut txids = self.txids.write().await;
+        match txids.get(txid) {
+            Some(owner) if owner != payment_id => Ok(Some(owner.clone())),
+            Some(_) => Ok(None),
+            None => {
+                txids.insert(txid.to_string(), payment_id.to_string());
+                Ok(None)
+            }
+        }
+    }
+
+    /// Drop a claim (e.g. when the broadcast failed)
+    pub async fn release_txid(&self, txid: &str, payment_id: &str) -> AppResult<()> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let key = Self::tx_key(txid);
+            let owner: Option<String> = conn
+                .get(&key)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis get: {}", e)))?;
+            if owner.as_deref() == Some(payment_id) {
+                let _: () = conn
+                    .del(&key)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("redis del: {}", e)))?;
+            }
+            return Ok(());
+        }
+        let mut txids = self.txids.write().await;
+        if txids.get(txid).map(String::as_str) == Some(payment_id) {
+            txids.remove(txid);
+        }
+        Ok(())
+    }
+
     pub async fn put(&self, session: &PaymentSession) -> AppResult<()> {
         let serialized = serde_json::to_vec(session)
             .map_err(|e| AppError::Internal(format!("serialize payment: {}", e)))?;
@@ -31,11 +310,17 @@ impl PaymentsStore {
         if let Some(redis) = &self.redis {
             let mut conn = (**redis).clone();
             let key = Self::key(&session.payment_id);
-            // TTL: keep for 48h
             let _: () = conn
-                .set_ex(key, serialized, 48 * 3600)
+                .set_ex(key, serialized, self.retention_seconds)
                 .await
                 .map_err(|e| AppError::Internal(format!("redis set: {}", e)))?;
+            // Every instance re-verifies from the shared set after a reorg
+            let _: () = if Self::is_active(session) {
+                conn.sadd(ACTIVE_KEY, &session.payment_id).await
+            } else {
+                conn.srem(ACTIVE_KEY, &session.payment_id).await
+            }
+            .map_err(|e| AppError::Internal(format!("redis active set: {}", e)))?;
         }
 
         // Always mirror to memory
@@ -43,6 +328,53 @@ impl PaymentsStore {
         Ok(())
     }
 
+    /// Sessions that have a transaction and may still change state
+    ///
+    /// With Redis these are the sessions of every instance; ids whose session
+    /// expired or settled are dropped from the set as they are found. Without
+    /// Redis, or while it is unreachable, only this instance's sessions are seen.
+    pub async fn active_sessions(&self) -> Vec<PaymentSession> {
+        if let Some(redis) = &self.redis {
+            match self.redis_active_sessions(redis).await {
+                Ok(sessions) => return sessions,
+                Err(e) => tracing::warn!("Listing active payments from Redis failed, using this instance's: {}", e),
+            }
+        }
+        self.memory
+            .read()
+            .await
+            .values()
+            .filter(|s| Self::is_active(s))
+            .cloned()
+            .collect()
+    }
+
+    async fn redis_active_sessions(&self, redis: &Arc<ConnectionManager>) -> AppResult<Vec<PaymentSession>> {
+        let mut conn = (**redis).clone();
+        let payment_ids: Vec<String> = conn
+            .smembers(ACTIVE_KEY)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis smembers: {}", e)))?;
+        let mut sessions = Vec::with_capacity(payment_ids.len());
+        for payment_id in payment_ids {
+            match self.get(&payment_id).await? {
+                Some(session) if Self::is_active(&session) => sessions.push(session),
+                _ => {
+                    let _: () = conn
+                        .srem(ACTIVE_KEY, &payment_id)
+                        .await
+                        .map_err(|e| AppError::Internal(format!("redis srem: {}", e)))?;
+                }
+            }
+        }
+        Ok(sessions)
+    }
+
+    fn is_active(session: &PaymentSession) -> bool {
+        (session.txid.is_some() || !session.submitted_txids.is_empty())
+            && !matches!(session.status, PaymentStatus::Failed | PaymentStatus::Expired)
+    }
+
     pub async fn get(&self, payment_id: &str) -> AppResult<Option<PaymentSession>> {
         if let Some(redis) = &self.redis {
             let mut conn = (**redis).clone();
@@ -63,4 +395,82 @@ impl PaymentsStore {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_txid_claimed_by_one_payment() {
+        let store = PaymentsStore::new(None);
+        assert_eq!(store.claim_txid("tx1", "pay-a").await.unwrap(), None);
+        assert_eq!(store.claim_txid("tx1", "pay-a").await.unwrap(), None);
+        assert_eq!(store.claim_txid("tx1", "pay-b").await.unwrap(), Some("pay-a".to_string()));
+
+        store.release_txid("tx1", "pay-b").await.unwrap();
+        assert_eq!(store.claim_txid("tx1", "pay-b").await.unwrap(), Some("pay-a".to_string()));
+        store.release_txid("tx1", "pay-a").await.unwrap();
+        assert_eq!(store.claim_txid("tx1", "pay-b").await.unwrap(), None);
+    }
+    #[tokio::test]
+    async fn test_address_pool_hands_out_each_address_once() {
+        let store = PaymentsStore::new(None);
+        store.pool_push("orchard", "u1a").await.unwrap();
+        store.pool_push("orchard", "u1b").await.unwrap();
+        assert_eq!(store.pool_len("orchard").await.unwrap(), 2);
+        assert_eq!(store.pool_len("sapling").await.unwrap(), 0);
+
+        assert_eq!(store.pool_pop("orchard").await.unwrap(), Some("u1a".to_string()));
+        assert_eq!(store.pool_pop("orchard").await.unwrap(), Some("u1b".to_string()));
+        assert_eq!(store.pool_pop("orchard").await.unwrap(), None);
+        assert_eq!(store.pool_pop("sapling").await.unwrap(), None);
+    }
+    #[tokio::test]
+    #[ignore] // Needs a Redis server at REDIS_URL (default redis://127.0.0.1:6379)
+    async fn test_active_sessions_are_shared_through_redis() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let redis = Arc::new(ConnectionManager::new(redis::Client::open(url).unwrap()).await.unwrap());
+        let instance_a = PaymentsStore::new(Some(redis.clone()));
+        let instance_b = PaymentsStore::new(Some(redis));
 
+        let mut session: PaymentSession = serde_json::from_value(serde_json::json!({
+            "payment_id": format!("active-test-{}", uuid::Uuid::new_v4()),
+            "tier_id": "basic",
+            "address": "zs1quote",
+            "address_type": "sapling",
+            "amount_vrsc": 1.0,
+            "created_at": chrono::Utc::now(),
+            "expires_at": chrono::Utc::now() + chrono::Duration::minutes(30),
+            "client_ip": null,
+            "user_agent": null,
+            "status": "pending",
+            "txid": null,
+            "confirmations": 0,
+            "provisional_token": null,
+            "final_token": null
+        }))
+        .unwrap();
+        let listed = |sessions: Vec<PaymentSession>, id: &str| sessions.iter().any(|s| s.payment_id == id);
+
+        instance_a.put(&session).await.unwrap();
+        assert!(!listed(instance_b.active_sessions().await, &session.payment_id));
+
+        // A session submitted on one instance is re-verified by the others after a reorg
+        session.txid = Some("tx-active".to_string());
+        session.status = PaymentStatus::Confirmed1;
+        instance_a.put(&session).await.unwrap();
+        assert!(listed(instance_b.active_sessions().await, &session.payment_id));
+
+        session.status = PaymentStatus::Failed;
+        instance_a.put(&session).await.unwrap();
+        assert!(!listed(instance_b.active_sessions().await, &session.payment_id));
+    }
+
+    #[tokio::test]
+    async fn test_refund_send_claimed_once() {
+        let store = PaymentsStore::new(None);
+        assert!(store.claim_refund_send("pay-a").await.unwrap());
+        assert!(!store.claim_refund_send("pay-a").await.unwrap());
+        store.release_refund_send("pay-a").await.unwrap();
+        assert!(store.claim_refund_send("pay-a").await.unwrap());
+    }
+}
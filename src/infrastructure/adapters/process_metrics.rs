@@ -0,0 +1,148 @@
+//! Process-level metrics for the Prometheus exporter
+//!
+//! Memory and file descriptor figures come from `/proc/self` and are omitted on
+//! platforms without procfs. Tokio figures come from the current runtime's
+//! stable metrics.
+
+use serde::{Deserialize, Serialize};
+
+use crate::infrastructure::adapters::CacheStats;
+
+/// Point-in-time process and runtime figures
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessSnapshot {
+    pub resident_memory_bytes: Option<u64>,
+    pub virtual_memory_bytes: Option<u64>,
+    pub threads: Option<u64>,
+    pub open_fds: Option<u64>,
+    pub max_fds: Option<u64>,
+    pub tokio_workers: Option<u64>,
+    pub tokio_alive_tasks: Option<u64>,
+    pub tokio_global_queue_depth: Option<u64>,
+}
+
+impl ProcessSnapshot {
+    /// Collect a snapshot of the current process
+    pub fn collect() -> Self {
+        let mut snapshot = ProcessSnapshot::default();
+
+        if let Ok(status) = std::fs::read_to_string("/proc/self/status") {
+            snapshot.resident_memory_bytes = status_value(&status, "VmRSS:").map(|kb| kb * 1024);
+            snapshot.virtual_memory_bytes = status_value(&status, "VmSize:").map(|kb| kb * 1024);
+            snapshot.threads = status_value(&status, "Threads:");
+        }
+        snapshot.open_fds = std::fs::read_dir("/proc/self/fd").ok().map(|dir| dir.count() as u64);
+        snapshot.max_fds = std::fs::read_to_string("/proc/self/limits")
+            .ok()
+            .and_then(|limits| max_open_files(&limits));
+
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let metrics = handle.metrics();
+            snapshot.tokio_workers = Some(metrics.num_workers() as u64);
+            snapshot.tokio_alive_tasks = Some(metrics.num_alive_tasks() as u64);
+            snapshot.tokio_global_queue_depth = Some(metrics.global_queue_depth() as u64);
+        }
+        snapshot
+    }
+
+    /// Resident memory of this process alone, for callers polling it often
+    pub fn current_resident_memory() -> Option<u64> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        status_value(&status, "VmRSS:").map(|kb| kb * 1024)
+    }
+
+    /// Render process, runtime and cache metrics in Prometheus text format
+    pub fn prometheus_text(&self, cache: Option<&CacheStats>) -> String {
+        let mut out = String::new();
+        let gauges = [
+            ("process_resident_memory_bytes", "Resident memory size in bytes", self.resident_memory_bytes),
+            ("process_virtual_memory_bytes", "Virtual memory size in bytes", self.virtual_memory_bytes),
+            ("process_threads", "Number of OS threads", self.threads),
+            ("process_open_fds", "Number of open file descriptors", self.open_fds),
+            ("process_max_fds", "Maximum number of open file descriptors", self.max_fds),
+            ("tokio_workers", "Number of tokio runtime worker threads", self.tokio_workers),
+            ("tokio_alive_tasks", "Number of alive tokio tasks", self.tokio_alive_tasks),
+            ("tokio_global_queue_depth", "Tasks waiting in the tokio runtime's global queue", self.tokio_global_queue_depth),
+        ];
+        for (name, help, value) in gauges {
+            if let Some(value) = value {
+                push_gauge(&mut out, name, help, value);
+            }
+        }
+
+        if let Some(cache) = cache {
+            push_gauge(&mut out, "verus_cache_enabled", "Whether response caching is enabled", cache.cache_enabled as u64);
+            push_gauge(&mut out, "verus_cache_memory_entries", "Entries in the in-memory response cache", cache.memory_entries as u64);
+            push_gauge(&mut out, "verus_cache_redis_up", "Whether the Redis cache connection is available", cache.redis_available as u64);
+            push_gauge(&mut out, "verus_cache_redis_degraded", "Whether the cache is serving from memory while Redis reconnects", cache.redis_degraded as u64);
+            out.push_str("# HELP verus_cache_redis_outages_total Redis cache outages since startup
+# TYPE verus_cache_redis_outages_total counter
+");
+            out.push_str(&format!("verus_cache_redis_outages_total {}
+", cache.redis_outages));
+            out.push_str("# HELP verus_cache_lookups_total Response cache lookups by result\n# TYPE verus_cache_lookups_total counter\n");
+            out.push_str(&format!("verus_cache_lookups_total{{result=\"hit\"}} {}\n", cache.hits));
+            out.push_str(&format!("verus_cache_lookups_total{{result=\"miss\"}} {}\n", cache.misses));
+        }
+        out
+    }
+}
+
+fn push_gauge(out: &mut String, name: &str, help: &str, value: u64) {
+    out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n{} {}\n", name, help, name, name, value));
+}
+
+/// Numeric value of a `/proc/self/status` field (`VmRSS:   1234 kB`)
+fn status_value(status: &str, field: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(field))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|value| value.parse().ok())
+}
+
+/// Soft limit from the `Max open files` row of `/proc/self/limits`
+fn max_open_files(limits: &str) -> Option<u64> {
+    limits
+        .lines()
+        .find_map(|line| line.strip_prefix("Max open files"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|value| value.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proc_parsing() {
+        let status = "Name:\tverus-rpc\nVmSize:\t  204800 kB\nVmRSS:\t   10240 kB\nThreads:\t9\n";
+        assert_eq!(status_value(status, "VmRSS:"), Some(10240));
+        assert_eq!(status_value(status, "Threads:"), Some(9));
+        assert_eq!(status_value(status, "VmSwap:"), None);
+
+        let limits = "Limit                     Soft Limit           Hard Limit           Units\nMax open files            1024                 524288               files\n";
+        assert_eq!(max_open_files(limits), Some(1024));
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_text_includes_runtime_and_cache() {
+        let snapshot = ProcessSnapshot::collect();
+        let cache = CacheStats {
+            memory_entries: 3,
+            redis_available: false,
+            redis_degraded: true,
+            redis_outages: 2,
+            cache_enabled: true,
+            hits: 4,
+            misses: 1,
+        };
+        let text = snapshot.prometheus_text(Some(&cache));
+        assert!(text.contains("tokio_alive_tasks"));
+        assert!(text.contains("verus_cache_memory_entries 3"));
+        assert!(text.contains("verus_cache_lookups_total{result=\"hit\"} 4"));
+        assert!(text.contains("verus_cache_redis_up 0"));
+        assert!(text.contains("verus_cache_redis_degraded 1"));
+        assert!(text.contains("verus_cache_redis_outages_total 2"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Rate limit exemptions for internal and first-party clients
+//!
+//! Monitoring probes and first-party services can be exempted from the
+//! per-client rate limit and client profile budgets by source network, by
+//! the SHA-256 of an `X-API-Key` header, or by a permission carried in a JWT
+//! signed with the configured secret. As with client profiles, a token counts
+//! only once the authentication adapter has checked its signature, expiry and
+//! revocation. Exempted requests are counted per reason so the traffic stays
+//! visible in metrics.
+
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::config::AppConfig;
+use crate::infrastructure::adapters::authentication::JwtClaims;
+use crate::shared::network::IpCidr;
+use crate::shared::security::{api_key_hash, api_key_hash_matches, normalize_api_key_hashes};
+
+/// Why a request skipped rate limiting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExemptionReason {
+    Cidr,
+    ApiKey,
+    Permission,
+}
+
+impl ExemptionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExemptionReason::Cidr => "cidr",
+            ExemptionReason::ApiKey => "api_key",
+            ExemptionReason::Permission => "permission",
+        }
+    }
+}
+
+/// Exempted request counters
+#[derive(Debug, Clone, Serialize)]
+pub struct ExemptionMetrics {
+    pub cidr: u64,
+    pub api_key: u64,
+    pub permission: u64,
+}
+
+/// Configured exemptions
+pub struct RateLimitExemptions {
+    cidrs: Vec<IpCidr>,
+    api_key_hashes: Vec<String>,
+    permissions: Vec<String>,
+    cidr_hits: AtomicU64,
+    api_key_hits: AtomicU64,
+    permission_hits: AtomicU64,
+}
+
+impl RateLimitExemptions {
+    /// Build the exemptions from `[rate_limit.exemptions]`
+    pub fn new(config: &AppConfig) -> Self {
+        let exemptions = &config.rate_limit.exemptions;
+        let cidrs = exemptions
+            .cidrs
+            .iter()
+            .filter_map(|cidr| match cidr.parse() {
+                Ok(cidr) => Some(cidr),
+                Err(e) => {
+                    warn!("Ignoring rate limit exemption: {}", e);
+                    None
+                }
+            })
+            .collect();
+        let mut api_key_hashes = exemptions.api_key_hashes.clone();
+        normalize_api_key_hashes(&mut api_key_hashes, "A rate limit exemption");
+        Self {
+            cidrs,
+            api_key_hashes,
+            permissions: exemptions.permissions.clone(),
+            cidr_hits: AtomicU64::new(0),
+            api_key_hits: AtomicU64::new(0),
+            permission_hits: AtomicU64::new(0),
+        }
+    }
+
+    /// Exemption matching a request, checked by network, then API key, then a permission in verified `claims`
+    pub fn resolve(&self, client_ip: &str, api_key: Option<&str>, claims: Option<&JwtClaims>) -> Option<ExemptionReason> {
+        if let Ok(ip) = client_ip.parse::<IpAddr>() {
+            if self.cidrs.iter().any(|cidr| cidr.contains(ip)) {
+                return Some(ExemptionReason::Cidr);
+            }
+        }
+        if let Some(key) = api_key.filter(|key| !key.is_empty() && !self.api_key_hashes.is_empty()) {
+            if api_key_hash_matches(&self.api_key_hashes, &api_key_hash(key)) {
+                return Some(ExemptionReason::ApiKey);
+            }
+        }
+        if self.permissions.is_empty() {
+            return None;
+        }
+        claims?
+            .permissions
+            .iter()
+            .any(|permission| self.permissions.contains(permission))
+            .then_some(ExemptionReason::Permission)
+    }
+
+    /// Count a request let through by `reason`
+    pub fn record(&self, reason: ExemptionReason) {
+        let counter = match reason {
+            ExemptionReason::Cidr => &self.cidr_hits,
+            ExemptionReason::ApiKey => &self.api_key_hits,
+            ExemptionReason::Permission => &self.permission_hits,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Exempted requests by reason
+    pub fn metrics(&self) -> ExemptionMetrics {
+        ExemptionMetrics {
+            cidr: self.cidr_hits.load(Ordering::Relaxed),
+            api_key: self.api_key_hits.load(Ordering::Relaxed),
+            permission: self.permission_hits.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Render exemption counters in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let m = self.metrics();
+        let mut out = String::new();
+        out.push_str("# HELP verus_rate_limit_exempted_total Requests that skipped rate limiting, by exemption\n");
+        out.push_str("# TYPE verus_rate_limit_exempted_total counter\n");
+        for (reason, count) in [("cidr", m.cidr), ("api_key", m.api_key), ("permission", m.permission)] {
+            out.push_str(&format!("verus_rate_limit_exempted_total{{reason=\"{}\"}} {}\n", reason, count));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exemptions(configure: impl FnOnce(&mut AppConfig)) -> RateLimitExemptions {
+        let mut config = AppConfig::default();
+        configure(&mut config);
+        RateLimitExemptions::new(&config)
+    }
+
+    fn claims(permissions: &[&str]) -> JwtClaims {
+        let now = chrono::Utc::now().timestamp() as usize;
+        JwtClaims {
+            sub: "monitor".to_string(),
+            iss: "verus-rpc-server".to_string(),
+            aud: "verus-rpc-clients".to_string(),
+            iat: now,
+            iat_ms: None,
+            exp: now + 3600,
+            nbf: now,
+            jti: "exempt-test".to_string(),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            client_ip: None,
+            user_agent: None,
+            sid: None,
+        }
+    }
+
+    #[test]
+    fn test_exemption_by_network_and_api_key() {
+        let exemptions = exemptions(|config| {
+            config.rate_limit.exemptions.cidrs = vec!["10.0.0.0/8".to_string(), "bogus".to_string()];
+            config.rate_limit.exemptions.api_key_hashes = vec![api_key_hash("first-party")];
+        });
+        assert_eq!(exemptions.resolve("10.1.2.3", None, None), Some(ExemptionReason::Cidr));
+        assert_eq!(exemptions.resolve("203.0.113.5", Some("first-party"), None), Some(ExemptionReason::ApiKey));
+        assert_eq!(exemptions.resolve("203.0.113.5", Some("guess"), None), None);
+
+        exemptions.record(ExemptionReason::Cidr);
+        assert!(exemptions.prometheus_text().contains("verus_rate_limit_exempted_total{reason=\"cidr\"} 1"));
+    }
+
+    #[test]
+    fn test_exemption_by_jwt_permission() {
+        let exemptions = exemptions(|config| {
+            config.rate_limit.exemptions.permissions = vec!["internal".to_string()];
+        });
+        let internal = claims(&["read", "internal"]);
+        assert_eq!(exemptions.resolve("203.0.113.5", None, Some(&internal)), Some(ExemptionReason::Permission));
+        assert_eq!(exemptions.resolve("203.0.113.5", None, Some(&claims(&["read"]))), None);
+        assert_eq!(exemptions.resolve("203.0.113.5", None, None), None);
+    }
+}
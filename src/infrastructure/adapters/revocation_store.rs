@@ -1,40 +1,131 @@
 //! JWT revocation store (Redis-backed with memory fallback)
+//!
+//! Revocations are kept only until the revoked token would have expired
+//! anyway: Redis entries carry a TTL, and memory entries are purged by
+//! `purge_expired` (run periodically via `start_cleanup`). Revoking a user
+//! records a watermark that invalidates every token issued to that user up to
+//! that moment, without having to know their token ids. The watermark is kept
+//! in milliseconds so a token issued in the same second as the revocation is
+//! still ordered against it.
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
+use chrono::Utc;
 use redis::{aio::ConnectionManager, AsyncCommands};
+use serde::{Deserialize, Serialize};
 
 use crate::shared::error::{AppError, AppResult};
 
+/// Sorted set of recent revocations (scored by expiry) used for listing
+const RECENT_KEY: &str = "jwt:revocations:recent";
+
+/// Default cap on revocations retained for listing
+const DEFAULT_MAX_LISTED: usize = 1000;
+
+/// What a revocation applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevocationScope {
+    /// A single token, by `jti`
+    Token,
+    /// Every token issued to a user before `revoked_at`
+    User,
+}
+
+/// A recorded revocation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevocationEntry {
+    pub scope: RevocationScope,
+    /// Token id for token revocations, user id for user revocations
+    pub subject: String,
+    pub reason: Option<String>,
+    /// Unix timestamp of the revocation
+    pub revoked_at: i64,
+    /// Unix timestamp of the revocation in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at_ms: Option<i64>,
+    /// Unix timestamp after which the entry can be dropped
+    pub expires_at: i64,
+}
+
+impl RevocationEntry {
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Whether a token issued at `issued_at_ms` predates this revocation
+    ///
+    /// Entries stored without milliseconds cover their whole second.
+    fn covers(&self, issued_at_ms: i64) -> bool {
+        let revoked_at_ms = self.revoked_at_ms.unwrap_or_else(|| self.revoked_at.saturating_add(1).saturating_mul(1000));
+        issued_at_ms < revoked_at_ms
+    }
+}
+
+#[derive(Debug, Default)]
+struct MemoryRevocations {
+    tokens: HashMap<String, RevocationEntry>,
+    users: HashMap<String, RevocationEntry>,
+}
+
 #[derive(Clone)]
 pub struct RevocationStore {
     redis: Option<Arc<ConnectionManager>>, // optional
-    memory: Arc<tokio::sync::RwLock<std::collections::HashSet<String>>>,
+    memory: Arc<tokio::sync::RwLock<MemoryRevocations>>,
+    max_listed: usize,
 }
 
 impl RevocationStore {
     pub fn new(redis: Option<Arc<ConnectionManager>>) -> Self {
         Self {
             redis,
-            memory: Arc::new(tokio::sync::RwLock::new(std::collections::HashSet::new())),
+            memory: Arc::new(tokio::sync::RwLock::new(MemoryRevocations::default())),
+            max_listed: DEFAULT_MAX_LISTED,
         }
     }
 
+    /// Limit the number of revocations retained for listing
+    pub fn with_max_listed(mut self, max_listed: usize) -> Self {
+        self.max_listed = max_listed.max(1);
+        self
+    }
+
+    /// Whether revocations survive a restart
+    pub fn is_persistent(&self) -> bool {
+        self.redis.is_some()
+    }
+
     fn key(jti: &str) -> String { format!("jwt:revoked:{}", jti) }
 
+    fn user_key(user_id: &str) -> String { format!("jwt:revoked:user:{}", user_id) }
+
+    /// Revoke a single token for `ttl_seconds` (its remaining lifetime)
     pub async fn revoke(&self, jti: &str, ttl_seconds: u64) -> AppResult<()> {
-        if let Some(redis) = &self.redis {
-            let mut conn = (**redis).clone();
-            let _: () = conn
-                .set_ex(Self::key(jti), 1u8, ttl_seconds)
-                .await
-                .map_err(|e| AppError::Internal(format!("redis set: {}", e)))?;
-        }
-        self.memory.write().await.insert(jti.to_string());
-        Ok(())
+        self.revoke_with_reason(jti, ttl_seconds, None).await.map(|_| ())
+    }
+
+    /// Revoke a single token, recording why
+    pub async fn revoke_with_reason(&self, jti: &str, ttl_seconds: u64, reason: Option<String>) -> AppResult<RevocationEntry> {
+        let entry = Self::entry(RevocationScope::Token, jti, ttl_seconds, reason);
+        self.persist(&Self::key(jti), &entry, ttl_seconds).await?;
+        self.memory.write().await.tokens.insert(jti.to_string(), entry.clone());
+        Ok(entry)
+    }
+
+    /// Revoke every token issued to `user_id` up to now
+    ///
+    /// `ttl_seconds` must cover the longest lifetime of a token issued to the user.
+    pub async fn revoke_user(&self, user_id: &str, ttl_seconds: u64, reason: Option<String>) -> AppResult<RevocationEntry> {
+        let entry = Self::entry(RevocationScope::User, user_id, ttl_seconds, reason);
+        self.persist(&Self::user_key(user_id), &entry, ttl_seconds).await?;
+        self.memory.write().await.users.insert(user_id.to_string(), entry.clone());
+        Ok(entry)
     }
 
     pub async fn is_revoked(&self, jti: &str) -> AppResult<bool> {
+        let now = Utc::now().timestamp();
         if let Some(redis) = &self.redis {
             let mut conn = (**redis).clone();
             let exists: bool = conn
@@ -43,8 +134,171 @@ impl RevocationStore {
                 .map_err(|e| AppError::Internal(format!("redis exists: {}", e)))?;
             if exists { return Ok(true); }
         }
-        Ok(self.memory.read().await.contains(jti))
+        Ok(self.memory.read().await.tokens.get(jti).is_some_and(|e| !e.is_expired(now)))
+    }
+
+    /// Whether a token is revoked individually or through its user
+    ///
+    /// `issued_at_ms` is the token's issue time in milliseconds.
+    pub async fn is_token_revoked(&self, jti: &str, user_id: &str, issued_at_ms: i64) -> AppResult<bool> {
+        if self.is_revoked(jti).await? {
+            return Ok(true);
+        }
+        let now = Utc::now().timestamp();
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let stored: Option<String> = conn
+                .get(Self::user_key(user_id))
+                .await
+                .map_err(|e| AppError::Internal(format!("redis get: {}", e)))?;
+            if let Some(entry) = stored.and_then(|s| serde_json::from_str::<RevocationEntry>(&s).ok()) {
+                if entry.covers(issued_at_ms) { return Ok(true); }
+            }
+        }
+        Ok(self
+            .memory
+            .read()
+            .await
+            .users
+            .get(user_id)
+            .is_some_and(|e| !e.is_expired(now) && e.covers(issued_at_ms)))
+    }
+
+    /// Most recent unexpired revocations, newest first
+    pub async fn list_recent(&self, limit: usize) -> AppResult<Vec<RevocationEntry>> {
+        let now = Utc::now().timestamp();
+        let mut entries: Vec<RevocationEntry> = if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let _: () = conn
+                .zrembyscore(RECENT_KEY, "-inf", now)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis zrembyscore: {}", e)))?;
+            let raw: Vec<String> = conn
+                .zrange(RECENT_KEY, 0, -1)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis zrange: {}", e)))?;
+            raw.iter().filter_map(|s| serde_json::from_str(s).ok()).collect()
+        } else {
+            let memory = self.memory.read().await;
+            memory
+                .tokens
+                .values()
+                .chain(memory.users.values())
+                .filter(|e| !e.is_expired(now))
+                .cloned()
+                .collect()
+        };
+        entries.sort_by_key(|e| std::cmp::Reverse(e.revoked_at));
+        entries.truncate(limit.min(self.max_listed));
+        Ok(entries)
+    }
+
+    /// Drop expired in-memory entries, returning how many were removed
+    pub async fn purge_expired(&self) -> usize {
+        let now = Utc::now().timestamp();
+        let mut memory = self.memory.write().await;
+        let before = memory.tokens.len() + memory.users.len();
+        memory.tokens.retain(|_, e| !e.is_expired(now));
+        memory.users.retain(|_, e| !e.is_expired(now));
+        before - (memory.tokens.len() + memory.users.len())
+    }
+
+    /// Periodically purge expired in-memory entries
+    pub fn start_cleanup(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let removed = self.purge_expired().await;
+                if removed > 0 {
+                    tracing::debug!(removed, "Purged expired token revocations");
+                }
+            }
+        })
+    }
+
+    fn entry(scope: RevocationScope, subject: &str, ttl_seconds: u64, reason: Option<String>) -> RevocationEntry {
+        let now_ms = Utc::now().timestamp_millis();
+        let now = now_ms.div_euclid(1000);
+        RevocationEntry {
+            scope,
+            subject: subject.to_string(),
+            reason,
+            revoked_at: now,
+            revoked_at_ms: Some(now_ms),
+            expires_at: now.saturating_add(ttl_seconds as i64),
+        }
+    }
+
+    async fn persist(&self, key: &str, entry: &RevocationEntry, ttl_seconds: u64) -> AppResult<()> {
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return Ok(()),
+        };
+        let value = serde_json::to_string(entry).map_err(|e| AppError::Internal(format!("revocation encode: {}", e)))?;
+        let mut conn = (**redis).clone();
+        let _: () = conn
+            .set_ex(key, &value, ttl_seconds.max(1))
+            .await
+            .map_err(|e| AppError::Internal(format!("redis set: {}", e)))?;
+        let _: () = conn
+            .zadd(RECENT_KEY, &value, entry.expires_at)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis zadd: {}", e)))?;
+        // Keep the listing bounded; entries expiring soonest are dropped first
+        let _: () = conn
+            .zremrangebyrank(RECENT_KEY, 0, -(self.max_listed as isize) - 1)
+            .await
+            .map_err(|e| AppError::Internal(format!("redis zremrangebyrank: {}", e)))?;
+        Ok(())
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_revoke_user_invalidates_earlier_tokens_only() {
+        let store = RevocationStore::new(None);
+        let issued = Utc::now().timestamp_millis() - 10_000;
+        store.revoke_user("alice", 3600, Some("compromised".to_string())).await.unwrap();
+
+        assert!(store.is_token_revoked("jti-1", "alice", issued).await.unwrap());
+        assert!(!store.is_token_revoked("jti-2", "alice", issued + 3_600_000).await.unwrap());
+        assert!(!store.is_token_revoked("jti-1", "bob", issued).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_user_revocation_orders_tokens_within_the_same_second() {
+        let store = RevocationStore::new(None);
+        let entry = store.revoke_user("alice", 3600, None).await.unwrap();
+        let revoked_at_ms = entry.revoked_at_ms.unwrap();
+        assert_eq!(revoked_at_ms.div_euclid(1000), entry.revoked_at);
 
+        // A token minted right after the revocation survives it, one minted just before does not
+        assert!(store.is_token_revoked("before", "alice", revoked_at_ms - 1).await.unwrap());
+        assert!(!store.is_token_revoked("after", "alice", revoked_at_ms).await.unwrap());
+
+        // Entries written before milliseconds were recorded still cover their whole second
+        let legacy = RevocationEntry { revoked_at_ms: None, ..entry };
+        assert!(legacy.covers(legacy.revoked_at * 1000 + 999));
+        assert!(!legacy.covers((legacy.revoked_at + 1) * 1000));
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_ignored_and_purged() {
+        let store = RevocationStore::new(None);
+        store.revoke("short", 0).await.unwrap();
+        store.revoke_with_reason("long", 3600, Some("logout".to_string())).await.unwrap();
+
+        assert!(!store.is_revoked("short").await.unwrap());
+        assert!(store.is_revoked("long").await.unwrap());
+        assert_eq!(store.purge_expired().await, 1);
+
+        let listed = store.list_recent(10).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].subject, "long");
+        assert_eq!(listed[0].scope, RevocationScope::Token);
+    }
+}
@@ -0,0 +1,175 @@
+//! Tokio runtime tuning and the CPU pool
+//!
+//! The server runtime is built from `[server]`: `worker_threads` IO workers
+//! (one per core when 0) and at most `max_blocking_threads` blocking threads.
+//! CPU-heavy work on the request path (canonical JSON re-encoding, PoW
+//! verification) runs on the blocking threads instead of the IO workers, so a
+//! burst of large responses cannot stall the reactor for every other
+//! connection. At most `cpu_pool_threads` such jobs run at once; more wait
+//! their turn without holding a worker. Payloads under
+//! `cpu_offload_min_bytes` are cheaper to process inline than to hand over.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::sync::Semaphore;
+
+use crate::config::app_config::ServerConfig;
+use crate::shared::error::{AppError, AppResult};
+
+/// Multi-threaded runtime sized from `[server]`
+pub fn build_runtime(config: &ServerConfig) -> std::io::Result<Runtime> {
+    let mut builder = Builder::new_multi_thread();
+    builder.enable_all().thread_name("verus-rpc-worker").max_blocking_threads(config.max_blocking_threads);
+    if config.worker_threads > 0 {
+        builder.worker_threads(config.worker_threads);
+    }
+    builder.build()
+}
+
+/// Bounded set of CPU-heavy jobs running on the blocking threads
+pub struct CpuPool {
+    permits: Arc<Semaphore>,
+    threads: usize,
+    offload_min_bytes: usize,
+    waiting: AtomicUsize,
+    completed: AtomicU64,
+}
+
+impl CpuPool {
+    pub fn new(config: &ServerConfig) -> Self {
+        let threads = match config.cpu_pool_threads {
+            0 => std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+            n => n,
+        };
+        Self {
+            permits: Arc::new(Semaphore::new(threads)),
+            threads,
+            offload_min_bytes: config.cpu_offload_min_bytes,
+            waiting: AtomicUsize::new(0),
+            completed: AtomicU64::new(0),
+        }
+    }
+
+    /// CPU-heavy jobs allowed to run at once
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Whether a payload of `bytes` is worth moving off the IO workers
+    pub fn offloads(&self, bytes: usize) -> bool {
+        bytes >= self.offload_min_bytes
+    }
+
+    /// Run `work` on a blocking thread once a pool slot is free
+    pub async fn run<F, T>(&self, work: F) -> AppResult<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let permit = self.permits.clone().acquire_owned().await;
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+        let permit = permit.map_err(|_| AppError::Internal("CPU pool is closed".into()))?;
+        let result = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            work()
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("CPU pool job failed: {}", e)));
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    /// Run `work` on the pool when `bytes` is large enough, inline otherwise
+    pub async fn run_sized<F, T>(&self, bytes: usize, work: F) -> AppResult<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        if self.offloads(bytes) {
+            self.run(work).await
+        } else {
+            Ok(work())
+        }
+    }
+
+    /// Render pool and runtime state in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let busy = self.threads.saturating_sub(self.permits.available_permits());
+        let mut out = String::new();
+        out.push_str("# HELP verus_cpu_pool_threads CPU-heavy jobs allowed to run at once\n");
+        out.push_str("# TYPE verus_cpu_pool_threads gauge\n");
+        out.push_str(&format!("verus_cpu_pool_threads {}\n", self.threads));
+        out.push_str("# HELP verus_cpu_pool_busy CPU-heavy jobs running\n");
+        out.push_str("# TYPE verus_cpu_pool_busy gauge\n");
+        out.push_str(&format!("verus_cpu_pool_busy {}\n", busy));
+        out.push_str("# HELP verus_cpu_pool_waiting CPU-heavy jobs waiting for a free slot\n");
+        out.push_str("# TYPE verus_cpu_pool_waiting gauge\n");
+        out.push_str(&format!("verus_cpu_pool_waiting {}\n", self.waiting.load(Ordering::Relaxed)));
+        out.push_str("# HELP verus_cpu_pool_jobs_total CPU-heavy jobs completed\n");
+        out.push_str("# TYPE verus_cpu_pool_jobs_total counter\n");
+        out.push_str(&format!("verus_cpu_pool_jobs_total {}\n", self.completed.load(Ordering::Relaxed)));
+        if let Ok(handle) = Handle::try_current() {
+            let metrics = handle.metrics();
+            out.push_str("# HELP verus_runtime_workers Tokio IO worker threads\n");
+            out.push_str("# TYPE verus_runtime_workers gauge\n");
+            out.push_str(&format!("verus_runtime_workers {}\n", metrics.num_workers()));
+            out.push_str("# HELP verus_runtime_alive_tasks Tasks alive on the runtime\n");
+            out.push_str("# TYPE verus_runtime_alive_tasks gauge\n");
+            out.push_str(&format!("verus_runtime_alive_tasks {}\n", metrics.num_alive_tasks()));
+            out.push_str("# HELP verus_runtime_global_queue_depth Tasks waiting in the runtime's global queue\n");
+            out.push_str("# TYPE verus_runtime_global_queue_depth gauge\n");
+            out.push_str(&format!("verus_runtime_global_queue_depth {}\n", metrics.global_queue_depth()));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(cpu_pool_threads: usize) -> ServerConfig {
+        ServerConfig { cpu_pool_threads, cpu_offload_min_bytes: 1024, ..crate::config::AppConfig::default().server }
+    }
+
+    #[test]
+    fn test_build_runtime_uses_configured_workers() {
+        let runtime = build_runtime(&ServerConfig { worker_threads: 2, ..config(1) }).unwrap();
+        assert_eq!(runtime.metrics().num_workers(), 2);
+        let worker = runtime.block_on(tokio::spawn(async { std::thread::current().name().map(str::to_string) }));
+        assert_eq!(worker.unwrap().as_deref(), Some("verus-rpc-worker"));
+    }
+
+    #[tokio::test]
+    async fn test_jobs_leave_the_worker_and_respect_the_limit() {
+        let pool = Arc::new(CpuPool::new(&config(1)));
+        assert!(!pool.offloads(100));
+        assert!(pool.offloads(4096));
+
+        let caller = std::thread::current().id();
+        let inline = pool.run_sized(100, move || std::thread::current().id() == caller).await.unwrap();
+        assert!(inline);
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let jobs = (0..4).map(|_| {
+            let (pool, running) = (pool.clone(), running.clone());
+            tokio::spawn(async move {
+                pool.run(move || {
+                    let concurrent = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    concurrent
+                })
+                .await
+                .unwrap()
+            })
+        });
+        for job in jobs.collect::<Vec<_>>() {
+            assert_eq!(job.await.unwrap(), 1);
+        }
+        assert!(pool.prometheus_text().contains("verus_cpu_pool_jobs_total 4"));
+    }
+}
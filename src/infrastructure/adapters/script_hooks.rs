@@ -0,0 +1,272 @@
+//! Rhai scripting hooks (lighter alternative to WASM plugins)
+//!
+//! The script configured in `[scripting]` may define any of
+//!
+//! - `fn on_request(ctx)`: return `false` or a message string to reject the
+//!   request; anything else lets it through
+//! - `fn on_response(ctx, result)`: return `()` to keep the result, any other
+//!   value replaces it
+//! - `fn on_error(ctx, error)`: observe failures (`error.code`, `error.message`)
+//!
+//! `ctx` carries `method`, `params`, `client_ip`, `user_agent` and
+//! `authenticated`; it is a copy, so scripts cannot alter the request that
+//! goes upstream. The file is re-read when it changes. A script that fails to
+//! compile keeps the previous version running. Operation, call depth and
+//! collection size limits bound what one hook call can cost.
+
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use rhai::{Dynamic, Engine, FuncArgs, Scope, AST};
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
+
+use crate::config::app_config::ScriptingConfig;
+use crate::domain::rpc::RpcRequest;
+use crate::shared::error::{AppError, AppResult};
+
+#[derive(Default)]
+struct LoadedScript {
+    ast: Option<Arc<AST>>,
+    /// Whether the file has been looked at yet
+    checked: bool,
+    /// Modification time of the file last compiled (successfully or not)
+    modified: Option<SystemTime>,
+}
+
+/// Hooks from the configured script
+pub struct ScriptHooks {
+    engine: Engine,
+    path: PathBuf,
+    fail_open: bool,
+    script: RwLock<LoadedScript>,
+}
+
+impl ScriptHooks {
+    /// Hooks from `[scripting]`; `None` when disabled
+    pub fn load(config: &ScriptingConfig) -> Option<Arc<Self>> {
+        if !config.enabled {
+            return None;
+        }
+        let mut engine = Engine::new();
+        engine.set_max_operations(config.max_operations);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(1024 * 1024);
+        engine.set_max_array_size(100_000);
+        engine.set_max_map_size(100_000);
+        engine.on_print(|text| info!(target: "script", "{}", text));
+        engine.on_debug(|text, _, position| info!(target: "script", position = %position, "{}", text));
+        let hooks = Arc::new(Self {
+            engine,
+            path: PathBuf::from(&config.path),
+            fail_open: config.fail_open,
+            script: RwLock::new(LoadedScript::default()),
+        });
+        hooks.current_script();
+        Some(hooks)
+    }
+
+    /// Run `on_request`; a rejection fails with a security error
+    pub fn on_request(&self, request: &RpcRequest) -> AppResult<()> {
+        match self.call("on_request", (context(request),)) {
+            Ok(Some(verdict)) => {
+                if verdict.as_bool() == Ok(false) {
+                    return Err(reject(request, "rejected by policy script".to_string()));
+                }
+                if verdict.is_string() {
+                    return Err(reject(request, verdict.into_string().unwrap_or_default()));
+                }
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(e) if self.fail_open => {
+                warn!(method = %request.method, "on_request hook failed; allowing request: {}", e);
+                Ok(())
+            }
+            Err(e) => {
+                warn!(method = %request.method, "on_request hook failed; refusing request: {}", e);
+                Err(AppError::Internal("Request policy script failed".to_string()))
+            }
+        }
+    }
+
+    /// Run `on_response` over a successful result
+    pub fn on_response(&self, request: &RpcRequest, result: Value) -> AppResult<Value> {
+        let argument = to_dynamic(&result);
+        let outcome = argument.and_then(|argument| self.call("on_response", (context(request), argument)));
+        match outcome {
+            Ok(Some(replacement)) if !replacement.is_unit() => {
+                rhai::serde::from_dynamic::<Value>(&replacement).or_else(|e| self.response_failed(request, result, e.to_string()))
+            }
+            Ok(_) => Ok(result),
+            Err(e) => self.response_failed(request, result, e),
+        }
+    }
+
+    /// Run `on_error`; failures of the hook itself are only logged
+    pub fn on_error(&self, request: &RpcRequest, failure: &AppError) {
+        let details = to_dynamic(&json!({
+            "code": failure.http_status_code().as_u16(),
+            "message": failure.to_string(),
+        }));
+        if let Err(e) = details.and_then(|details| self.call("on_error", (context(request), details))) {
+            warn!(method = %request.method, "on_error hook failed: {}", e);
+        }
+    }
+
+    fn response_failed(&self, request: &RpcRequest, result: Value, e: String) -> AppResult<Value> {
+        if self.fail_open {
+            warn!(method = %request.method, "on_response hook failed; returning the result unchanged: {}", e);
+            Ok(result)
+        } else {
+            warn!(method = %request.method, "on_response hook failed: {}", e);
+            Err(AppError::Internal("Response policy script failed".to_string()))
+        }
+    }
+
+    /// Call `hook` if the script defines it
+    fn call(&self, hook: &str, args: impl FuncArgs) -> Result<Option<Dynamic>, String> {
+        let ast = self.current_script().ok_or("no script loaded")?;
+        if !ast.iter_functions().any(|function| function.name == hook) {
+            return Ok(None);
+        }
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &ast, hook, args)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Compiled script, recompiled when the file changed since the last look
+    fn current_script(&self) -> Option<Arc<AST>> {
+        let modified = std::fs::metadata(&self.path).and_then(|meta| meta.modified()).ok();
+        {
+            let loaded = self.script.read().unwrap_or_else(|e| e.into_inner());
+            if loaded.checked && loaded.modified == modified {
+                return loaded.ast.clone();
+            }
+        }
+        let mut loaded = self.script.write().unwrap_or_else(|e| e.into_inner());
+        loaded.checked = true;
+        loaded.modified = modified;
+        match std::fs::read_to_string(&self.path).map_err(|e| e.to_string()).and_then(|source| {
+            self.engine.compile(source).map_err(|e| e.to_string())
+        }) {
+            Ok(ast) => {
+                info!(path = %self.path.display(), "Loaded policy script");
+                loaded.ast = Some(Arc::new(ast));
+            }
+            Err(e) if loaded.ast.is_some() => {
+                error!(path = %self.path.display(), "Failed to reload policy script; keeping the previous version: {}", e);
+            }
+            Err(e) => error!(path = %self.path.display(), "Failed to load policy script: {}", e),
+        }
+        loaded.ast.clone()
+    }
+}
+
+/// Read-only view of the request handed to hooks
+fn context(request: &RpcRequest) -> Dynamic {
+    to_dynamic(&json!({
+        "method": request.method,
+        "params": request.parameters,
+        "client_ip": request.client_info.ip_address,
+        "user_agent": request.client_info.user_agent,
+        "authenticated": request.client_info.auth_token.is_some(),
+    }))
+    .unwrap_or_default()
+}
+
+fn to_dynamic(value: &Value) -> Result<Dynamic, String> {
+    rhai::serde::to_dynamic(value).map_err(|e| e.to_string())
+}
+
+fn reject(request: &RpcRequest, reason: String) -> AppError {
+    warn!(
+        target: "audit",
+        event = "request_rejected_by_script",
+        method = %request.method,
+        client_ip = %request.client_info.ip_address,
+        reason = %reason,
+        "Request rejected by policy script"
+    );
+    AppError::Security(format!("Request rejected: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::rpc::ClientInfo;
+
+    fn hooks(name: &str, source: &str, fail_open: bool) -> (Arc<ScriptHooks>, PathBuf) {
+        let path = std::env::temp_dir().join(format!("verus-rpc-script-{}-{}.rhai", name, std::process::id()));
+        std::fs::write(&path, source).unwrap();
+        let config = ScriptingConfig {
+            enabled: true,
+            path: path.to_string_lossy().into_owned(),
+            max_operations: 10_000,
+            fail_open,
+        };
+        (ScriptHooks::load(&config).unwrap(), path)
+    }
+
+    fn request(method: &str, params: Value) -> RpcRequest {
+        RpcRequest {
+            method: method.to_string(),
+            parameters: Some(params),
+            id: Some(json!(1)),
+            client_info: ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: None,
+                auth_token: None,
+                timestamp: chrono::Utc::now(),
+                request_id: None,
+            },
+            upstream: None,
+        }
+    }
+
+    #[test]
+    fn test_on_request_rejects_parameter_combination() {
+        let (hooks, path) = hooks(
+            "reject",
+            r#"fn on_request(ctx) {
+                if ctx.method == "getaddressutxos" && !ctx.authenticated && ctx.params.len() > 1 {
+                    return "chainInfo requires a token";
+                }
+            }"#,
+            false,
+        );
+        assert!(hooks.on_request(&request("getaddressutxos", json!([{}]))).is_ok());
+        let rejected = hooks.on_request(&request("getaddressutxos", json!([{}, true])));
+        assert!(matches!(rejected, Err(AppError::Security(message)) if message.contains("chainInfo")));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_on_response_replaces_result_and_missing_hooks_pass() {
+        let (hooks, path) = hooks(
+            "response",
+            r#"fn on_response(ctx, result) { if ctx.method == "getinfo" { #{ blocks: result.blocks } } }"#,
+            false,
+        );
+        let result = hooks.on_response(&request("getinfo", json!([])), json!({"blocks": 5, "connections": 8})).unwrap();
+        assert_eq!(result, json!({"blocks": 5}));
+        let untouched = hooks.on_response(&request("getblockcount", json!([])), json!(5)).unwrap();
+        assert_eq!(untouched, json!(5));
+        assert!(hooks.on_request(&request("getinfo", json!([]))).is_ok());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_runaway_script_fails_closed_unless_fail_open() {
+        let source = "fn on_request(ctx) { loop { } }";
+        let (closed, closed_path) = hooks("closed", source, false);
+        assert!(matches!(closed.on_request(&request("getinfo", json!([]))), Err(AppError::Internal(_))));
+        let (open, open_path) = hooks("open", source, true);
+        assert!(open.on_request(&request("getinfo", json!([]))).is_ok());
+        std::fs::remove_file(closed_path).unwrap();
+        std::fs::remove_file(open_path).unwrap();
+    }
+}
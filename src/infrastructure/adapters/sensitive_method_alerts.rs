@@ -0,0 +1,207 @@
+//! Alerts on sensitive method calls
+//!
+//! Key export and import, identity revocation and `sendcurrency` are the
+//! calls an attacker holding a stolen token would make. Each call to a method
+//! listed in `[sensitive_method_alerts]` is logged at WARN with the caller's
+//! identity and source IP, before authentication decides on it, so refused
+//! attempts are reported too. With `webhook_url` set the alert is also POSTed
+//! as JSON, signed like the issuance webhook when a secret is configured.
+//! Params are never included: some of these methods carry private keys.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::config::app_config::{JwtConfig, SensitiveMethodAlertsConfig};
+use crate::config::AppConfig;
+use crate::domain::rpc::RpcRequest;
+use crate::infrastructure::adapters::authentication::JwtClaims;
+use crate::infrastructure::adapters::issuance_webhook::{sign, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+
+/// Alert raised for one sensitive call
+#[derive(Debug, Clone, Serialize)]
+pub struct SensitiveMethodAlert {
+    /// Unique id of this alert, for deduplication on the receiving side
+    pub event_id: String,
+    pub method: String,
+    pub client_ip: String,
+    pub user_agent: Option<String>,
+    /// Whether the call carried a token with a valid signature
+    pub authenticated: bool,
+    /// Subject of that token
+    pub subject: Option<String>,
+    pub permissions: Vec<String>,
+    pub timestamp: String,
+}
+
+/// Raises alerts for the configured methods
+pub struct SensitiveMethodAlerts {
+    config: SensitiveMethodAlertsConfig,
+    jwt: JwtConfig,
+    http: reqwest::Client,
+}
+
+impl SensitiveMethodAlerts {
+    pub fn new(config: &AppConfig) -> Self {
+        let alerts = &config.sensitive_method_alerts;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(alerts.webhook_timeout_ms))
+            .build()
+            .unwrap_or_default();
+        Self {
+            config: alerts.clone(),
+            jwt: config.security.jwt.clone(),
+            http,
+        }
+    }
+
+    /// Whether calls to `method` raise an alert
+    pub fn watches(&self, method: &str) -> bool {
+        self.config.enabled && self.config.methods.iter().any(|watched| watched == method)
+    }
+
+    /// Alert on `request` if its method is watched; the webhook is called in the background
+    pub fn notify(&self, request: &RpcRequest) {
+        if !self.watches(&request.method) {
+            return;
+        }
+        let alert = self.alert(request);
+        warn!(
+            event_id = %alert.event_id,
+            method = %alert.method,
+            client_ip = %alert.client_ip,
+            user_agent = alert.user_agent.as_deref().unwrap_or(""),
+            authenticated = alert.authenticated,
+            subject = alert.subject.as_deref().unwrap_or(""),
+            permissions = %alert.permissions.join(","),
+            "Sensitive method invoked"
+        );
+        if let Some(url) = self.config.webhook_url.clone() {
+            let http = self.http.clone();
+            let secret = self.config.webhook_secret.clone();
+            tokio::spawn(async move { deliver(&http, &url, secret.as_deref(), &alert).await });
+        }
+    }
+
+    fn alert(&self, request: &RpcRequest) -> SensitiveMethodAlert {
+        let claims = request.client_info.auth_token.as_deref().and_then(|token| self.verified_claims(token));
+        SensitiveMethodAlert {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            method: request.method.clone(),
+            client_ip: request.client_info.ip_address.clone(),
+            user_agent: request.client_info.user_agent.clone(),
+            authenticated: claims.is_some(),
+            subject: claims.as_ref().map(|claims| claims.sub.clone()),
+            permissions: claims.map(|claims| claims.permissions).unwrap_or_default(),
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Claims of a token carrying a valid signature
+    fn verified_claims(&self, token: &str) -> Option<JwtClaims> {
+        let token = token.strip_prefix("Bearer ").unwrap_or(token);
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&[&self.jwt.audience]);
+        validation.set_issuer(&[&self.jwt.issuer]);
+        decode::<JwtClaims>(token, &DecodingKey::from_secret(self.jwt.secret_key.as_ref()), &validation)
+            .ok()
+            .map(|data| data.claims)
+    }
+}
+
+/// POST an alert; failures are logged, not retried
+async fn deliver(http: &reqwest::Client, url: &str, secret: Option<&str>, alert: &SensitiveMethodAlert) {
+    let body = match serde_json::to_vec(alert) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(event_id = %alert.event_id, "Could not encode sensitive method alert: {}", e);
+            return;
+        }
+    };
+    let mut post = http.post(url).header("content-type", "application/json");
+    if let Some(secret) = secret {
+        let timestamp = Utc::now().timestamp().to_string();
+        post = post
+            .header(SIGNATURE_HEADER, sign(secret, &timestamp, &body))
+            .header(TIMESTAMP_HEADER, timestamp);
+    }
+    match post.body(body).send().await {
+        Ok(response) if response.status().is_success() => debug!(event_id = %alert.event_id, "Delivered sensitive method alert"),
+        Ok(response) => warn!(event_id = %alert.event_id, status = %response.status(), "Sensitive method alert webhook rejected"),
+        Err(e) => warn!(event_id = %alert.event_id, "Sensitive method alert webhook failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::rpc::ClientInfo;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    fn request(method: &str, auth_token: Option<String>) -> RpcRequest {
+        RpcRequest::new(
+            method.to_string(),
+            Some(json!(["secret-key-material"])),
+            Some(json!(1)),
+            ClientInfo {
+                ip_address: "203.0.113.7".to_string(),
+                user_agent: Some("test".to_string()),
+                auth_token,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        )
+    }
+
+    fn token(config: &AppConfig) -> String {
+        let now = Utc::now().timestamp() as usize;
+        let claims = JwtClaims {
+            sub: "ops-bot".to_string(),
+            iss: config.security.jwt.issuer.clone(),
+            aud: config.security.jwt.audience.clone(),
+            iat: now,
+            iat_ms: None,
+            exp: now + 3600,
+            nbf: now,
+            jti: "alert-test".to_string(),
+            permissions: vec!["admin".to_string()],
+            client_ip: None,
+            user_agent: None,
+            sid: None,
+        };
+        let key = EncodingKey::from_secret(config.security.jwt.secret_key.as_ref());
+        encode(&Header::default(), &claims, &key).unwrap()
+    }
+
+    #[test]
+    fn test_watched_methods() {
+        let mut config = AppConfig::default();
+        let alerts = SensitiveMethodAlerts::new(&config);
+        assert!(alerts.watches("z_exportkey"));
+        assert!(alerts.watches("sendcurrency"));
+        assert!(!alerts.watches("getinfo"));
+
+        config.sensitive_method_alerts.enabled = false;
+        assert!(!SensitiveMethodAlerts::new(&config).watches("z_exportkey"));
+    }
+
+    #[test]
+    fn test_alert_identifies_caller_without_params() {
+        let config = AppConfig::default();
+        let alerts = SensitiveMethodAlerts::new(&config);
+
+        let alert = alerts.alert(&request("dumpprivkey", Some(format!("Bearer {}", token(&config)))));
+        assert!(alert.authenticated);
+        assert_eq!(alert.subject.as_deref(), Some("ops-bot"));
+        assert_eq!(alert.client_ip, "203.0.113.7");
+        assert!(!serde_json::to_string(&alert).unwrap().contains("secret-key-material"));
+
+        let forged = alerts.alert(&request("dumpprivkey", Some("Bearer forged.token.value".to_string())));
+        assert!(!forged.authenticated);
+        assert!(forged.subject.is_none());
+    }
+}
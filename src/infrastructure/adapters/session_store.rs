@@ -0,0 +1,212 @@
+//! Server-side sessions for session-bound tokens
+//!
+//! A session token's JWT `exp` is the session's absolute maximum lifetime; the
+//! session itself expires after `idle_timeout_seconds` without activity. Each
+//! successful validation slides the idle deadline forward, and ending the
+//! session (logout) invalidates its tokens immediately.
+//!
+//! Sessions are created by the token service and checked by the RPC server,
+//! so deployments keep them in Redis. The in-memory store only serves a single
+//! process, such as tests.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::app_config::SessionConfig;
+use crate::shared::error::{AppError, AppResult};
+
+/// A server-side session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub user_id: String,
+    /// Unix timestamp the session was created
+    pub created_at: i64,
+    /// Unix timestamp of the last validated request
+    pub last_activity: i64,
+    /// Absolute expiry; activity never extends the session past this
+    pub expires_at: i64,
+}
+
+impl Session {
+    /// When the session expires if no further activity happens
+    pub fn idle_deadline(&self, idle_timeout_seconds: u64) -> i64 {
+        self.last_activity
+            .saturating_add(idle_timeout_seconds as i64)
+            .min(self.expires_at)
+    }
+}
+
+#[derive(Clone)]
+pub struct SessionStore {
+    redis: Option<Arc<ConnectionManager>>, // optional
+    memory: Arc<tokio::sync::RwLock<HashMap<String, Session>>>,
+    idle_timeout_seconds: u64,
+    max_lifetime_seconds: u64,
+}
+
+impl SessionStore {
+    pub fn new(redis: Option<Arc<ConnectionManager>>, config: &SessionConfig) -> Self {
+        Self {
+            redis,
+            memory: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            idle_timeout_seconds: config.idle_timeout_seconds.min(config.max_lifetime_seconds),
+            max_lifetime_seconds: config.max_lifetime_seconds,
+        }
+    }
+
+    /// Absolute lifetime of new sessions (seconds)
+    pub fn max_lifetime_seconds(&self) -> u64 {
+        self.max_lifetime_seconds
+    }
+
+    fn key(session_id: &str) -> String { format!("session:{}", session_id) }
+
+    /// Start a session for a user
+    pub async fn create(&self, user_id: &str) -> AppResult<Session> {
+        let now = Utc::now().timestamp();
+        let session = Session {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            created_at: now,
+            last_activity: now,
+            expires_at: now.saturating_add(self.max_lifetime_seconds as i64),
+        };
+        self.save(&session, now).await?;
+        Ok(session)
+    }
+
+    /// Validate a session and slide its idle deadline forward
+    pub async fn touch(&self, session_id: &str) -> AppResult<Session> {
+        let now = Utc::now().timestamp();
+        let mut session = self
+            .load(session_id)
+            .await?
+            .ok_or_else(|| AppError::Authentication("Session not found or ended".to_string()))?;
+        if session.idle_deadline(self.idle_timeout_seconds) <= now {
+            self.end(session_id).await?;
+            return Err(AppError::Authentication("Session expired".to_string()));
+        }
+        session.last_activity = now;
+        self.save(&session, now).await?;
+        Ok(session)
+    }
+
+    /// End a session (logout); returns whether it existed
+    pub async fn end(&self, session_id: &str) -> AppResult<bool> {
+        let mut existed = self.memory.write().await.remove(session_id).is_some();
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let removed: i64 = conn
+                .del(Self::key(session_id))
+                .await
+                .map_err(|e| AppError::Internal(format!("redis del: {}", e)))?;
+            existed |= removed > 0;
+        }
+        Ok(existed)
+    }
+
+    /// Drop idle or expired in-memory sessions, returning how many were removed
+    pub async fn purge_expired(&self) -> usize {
+        let now = Utc::now().timestamp();
+        let mut memory = self.memory.write().await;
+        let before = memory.len();
+        memory.retain(|_, s| s.idle_deadline(self.idle_timeout_seconds) > now);
+        before - memory.len()
+    }
+
+    /// Periodically purge idle in-memory sessions
+    pub fn start_cleanup(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let removed = self.purge_expired().await;
+                if removed > 0 {
+                    tracing::debug!(removed, "Purged expired sessions");
+                }
+            }
+        })
+    }
+
+    async fn load(&self, session_id: &str) -> AppResult<Option<Session>> {
+        if let Some(redis) = &self.redis {
+            let mut conn = (**redis).clone();
+            let stored: Option<String> = conn
+                .get(Self::key(session_id))
+                .await
+                .map_err(|e| AppError::Internal(format!("redis get: {}", e)))?;
+            if let Some(session) = stored.and_then(|s| serde_json::from_str::<Session>(&s).ok()) {
+                return Ok(Some(session));
+            }
+        }
+        Ok(self.memory.read().await.get(session_id).cloned())
+    }
+
+    async fn save(&self, session: &Session, now: i64) -> AppResult<()> {
+        if let Some(redis) = &self.redis {
+            // Redis expiry tracks the idle deadline so abandoned sessions clean themselves up
+            let ttl = (session.idle_deadline(self.idle_timeout_seconds) - now).max(1) as u64;
+            let value = serde_json::to_string(session).map_err(|e| AppError::Internal(format!("session encode: {}", e)))?;
+            let mut conn = (**redis).clone();
+            let _: () = conn
+                .set_ex(Self::key(&session.id), value, ttl)
+                .await
+                .map_err(|e| AppError::Internal(format!("redis set: {}", e)))?;
+        } else {
+            self.memory.write().await.insert(session.id.clone(), session.clone());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(idle: u64, max: u64) -> SessionStore {
+        SessionStore::new(None, &SessionConfig { enabled: true, idle_timeout_seconds: idle, max_lifetime_seconds: max })
+    }
+
+    #[tokio::test]
+    async fn test_touch_and_end_session() {
+        let store = store(1800, 86400);
+        let session = store.create("alice").await.unwrap();
+        assert_eq!(session.expires_at - session.created_at, 86400);
+
+        let touched = store.touch(&session.id).await.unwrap();
+        assert_eq!(touched.user_id, "alice");
+
+        assert!(store.end(&session.id).await.unwrap());
+        assert!(matches!(store.touch(&session.id).await, Err(AppError::Authentication(_))));
+    }
+
+    #[tokio::test]
+    async fn test_idle_session_expires() {
+        let store = store(60, 3600);
+        let mut session = store.create("bob").await.unwrap();
+        session.last_activity -= 61;
+        store.memory.write().await.insert(session.id.clone(), session.clone());
+
+        assert!(matches!(store.touch(&session.id).await, Err(AppError::Authentication(_))));
+        assert_eq!(store.purge_expired().await, 0);
+    }
+
+    #[test]
+    fn test_idle_deadline_capped_by_absolute_expiry() {
+        let session = Session {
+            id: "s".to_string(),
+            user_id: "u".to_string(),
+            created_at: 0,
+            last_activity: 3500,
+            expires_at: 3600,
+        };
+        assert_eq!(session.idle_deadline(1800), 3600);
+        assert_eq!(session.idle_deadline(60), 3560);
+    }
+}
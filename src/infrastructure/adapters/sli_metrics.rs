@@ -0,0 +1,354 @@
+//! Request-level SLIs: cache hit ratio, coalescing and upstream savings
+//!
+//! Every JSON-RPC request that gets past validation and rate limiting is
+//! counted once, by how it was answered. Counts are kept cumulatively for
+//! Prometheus and in 10-second buckets covering the last hour for
+//! `/metrics/summary`, which reports 1m/5m/1h windows for quick operational
+//! checks and autoscaling signals.
+
+use crate::domain::validation::MethodRegistry;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// Width of one bucket (seconds)
+const BUCKET_SECONDS: u64 = 10;
+
+/// Buckets kept: one hour
+const MAX_BUCKETS: usize = 360;
+
+/// Windows reported by the summary
+const WINDOWS: &[(&str, u64)] = &[("1m", 60), ("5m", 300), ("1h", 3600)];
+
+/// How a request was answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+    /// From the local or shared cache
+    CacheHit,
+    /// From the cache after waiting for another replica's upstream call
+    Coalesced,
+    /// By the replica owning the cache key
+    Forwarded,
+    /// Cacheable, but sent upstream
+    CacheMiss,
+    /// Not cacheable; sent upstream
+    Uncached,
+}
+
+impl RequestOutcome {
+    fn label(self) -> &'static str {
+        match self {
+            RequestOutcome::CacheHit => "cache_hit",
+            RequestOutcome::Coalesced => "coalesced",
+            RequestOutcome::Forwarded => "forwarded",
+            RequestOutcome::CacheMiss => "cache_miss",
+            RequestOutcome::Uncached => "uncached",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Counts {
+    cache_hits: u64,
+    coalesced: u64,
+    forwarded: u64,
+    cache_misses: u64,
+    uncached: u64,
+}
+
+impl Counts {
+    fn add(&mut self, outcome: RequestOutcome) {
+        match outcome {
+            RequestOutcome::CacheHit => self.cache_hits += 1,
+            RequestOutcome::Coalesced => self.coalesced += 1,
+            RequestOutcome::Forwarded => self.forwarded += 1,
+            RequestOutcome::CacheMiss => self.cache_misses += 1,
+            RequestOutcome::Uncached => self.uncached += 1,
+        }
+    }
+
+    fn merge(&mut self, other: &Counts) {
+        self.cache_hits += other.cache_hits;
+        self.coalesced += other.coalesced;
+        self.forwarded += other.forwarded;
+        self.cache_misses += other.cache_misses;
+        self.uncached += other.uncached;
+    }
+
+    fn get(&self, outcome: RequestOutcome) -> u64 {
+        match outcome {
+            RequestOutcome::CacheHit => self.cache_hits,
+            RequestOutcome::Coalesced => self.coalesced,
+            RequestOutcome::Forwarded => self.forwarded,
+            RequestOutcome::CacheMiss => self.cache_misses,
+            RequestOutcome::Uncached => self.uncached,
+        }
+    }
+
+    fn requests(&self) -> u64 {
+        self.cacheable() + self.uncached
+    }
+
+    fn cacheable(&self) -> u64 {
+        self.cache_hits + self.coalesced + self.forwarded + self.cache_misses
+    }
+
+    fn upstream_calls(&self) -> u64 {
+        self.cache_misses + self.uncached
+    }
+}
+
+/// `numerator / denominator`, `None` without traffic
+fn ratio(numerator: u64, denominator: u64) -> Option<f64> {
+    (denominator > 0).then(|| numerator as f64 / denominator as f64)
+}
+
+/// SLIs for one method over a window
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodSli {
+    pub requests: u64,
+    pub cache_hits: u64,
+    /// Hits among cacheable requests
+    pub cache_hit_ratio: Option<f64>,
+}
+
+/// SLIs over one window
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowSli {
+    pub requests: u64,
+    pub cache_hits: u64,
+    pub coalesced: u64,
+    pub forwarded: u64,
+    pub upstream_calls: u64,
+    /// Hits among cacheable requests
+    pub cache_hit_ratio: Option<f64>,
+    /// Requests answered by another replica's upstream call, among cacheable requests
+    pub coalesced_ratio: Option<f64>,
+    /// Requests answered without an upstream call from this replica
+    pub upstream_savings_ratio: Option<f64>,
+    pub methods: BTreeMap<String, MethodSli>,
+}
+
+impl WindowSli {
+    fn from_counts(methods: &HashMap<String, Counts>) -> Self {
+        let mut total = Counts::default();
+        methods.values().for_each(|counts| total.merge(counts));
+        Self {
+            requests: total.requests(),
+            cache_hits: total.cache_hits,
+            coalesced: total.coalesced,
+            forwarded: total.forwarded,
+            upstream_calls: total.upstream_calls(),
+            cache_hit_ratio: ratio(total.cache_hits, total.cacheable()),
+            coalesced_ratio: ratio(total.coalesced, total.cacheable()),
+            upstream_savings_ratio: ratio(total.requests() - total.upstream_calls(), total.requests()),
+            methods: methods
+                .iter()
+                .map(|(method, counts)| {
+                    (
+                        method.clone(),
+                        MethodSli {
+                            requests: counts.requests(),
+                            cache_hits: counts.cache_hits,
+                            cache_hit_ratio: ratio(counts.cache_hits, counts.cacheable()),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Body of `GET /metrics/summary`
+#[derive(Debug, Clone, Serialize)]
+pub struct SliSummary {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Keyed by window (`1m`, `5m`, `1h`)
+    pub windows: BTreeMap<&'static str, WindowSli>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    index: u64,
+    methods: HashMap<String, Counts>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    buckets: VecDeque<Bucket>,
+    totals: HashMap<String, Counts>,
+}
+
+/// Registry category label of a method; `unknown` for unregistered methods
+fn method_category(method: &str) -> &'static str {
+    static REGISTRY: OnceLock<MethodRegistry> = OnceLock::new();
+    REGISTRY
+        .get_or_init(MethodRegistry::new)
+        .get_method(method)
+        .map_or("unknown", |definition| definition.category.as_str())
+}
+
+/// Process-wide SLI registry
+#[derive(Debug, Default)]
+pub struct SliMetrics {
+    state: Mutex<State>,
+}
+
+impl SliMetrics {
+    /// Registry shared by all request handlers
+    pub fn global() -> &'static SliMetrics {
+        static METRICS: OnceLock<SliMetrics> = OnceLock::new();
+        METRICS.get_or_init(SliMetrics::default)
+    }
+
+    /// Count a request answered with `outcome`
+    pub fn record(&self, method: &str, outcome: RequestOutcome) {
+        self.record_at(method, outcome, now_seconds());
+    }
+
+    fn record_at(&self, method: &str, outcome: RequestOutcome, now: u64) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let index = now / BUCKET_SECONDS;
+        if state.buckets.back().is_none_or(|bucket| bucket.index != index) {
+            state.buckets.push_back(Bucket { index, methods: HashMap::new() });
+            while state.buckets.len() > MAX_BUCKETS {
+                state.buckets.pop_front();
+            }
+        }
+        if let Some(bucket) = state.buckets.back_mut() {
+            bucket.methods.entry(method.to_string()).or_default().add(outcome);
+        }
+        state.totals.entry(method.to_string()).or_default().add(outcome);
+    }
+
+    /// SLIs over the last minute, five minutes and hour
+    pub fn summary(&self) -> SliSummary {
+        self.summary_at(now_seconds())
+    }
+
+    fn summary_at(&self, now: u64) -> SliSummary {
+        let mut windows = BTreeMap::new();
+        let Ok(state) = self.state.lock() else {
+            return SliSummary { timestamp: chrono::Utc::now(), windows };
+        };
+        let current = now / BUCKET_SECONDS;
+        for (name, seconds) in WINDOWS {
+            let first = (current + 1).saturating_sub(seconds / BUCKET_SECONDS);
+            let mut methods: HashMap<String, Counts> = HashMap::new();
+            for bucket in state.buckets.iter().filter(|bucket| bucket.index >= first && bucket.index <= current) {
+                for (method, counts) in &bucket.methods {
+                    methods.entry(method.clone()).or_default().merge(counts);
+                }
+            }
+            windows.insert(*name, WindowSli::from_counts(&methods));
+        }
+        SliSummary { timestamp: chrono::Utc::now(), windows }
+    }
+
+    /// Cumulative outcome counters and windowed ratios in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP verus_sli_requests_total Requests by method, method category and how they were answered\n");
+        out.push_str("# TYPE verus_sli_requests_total counter\n");
+        if let Ok(state) = self.state.lock() {
+            let mut methods: Vec<_> = state.totals.iter().collect();
+            methods.sort_by(|a, b| a.0.cmp(b.0));
+            for (method, counts) in methods {
+                for outcome in [
+                    RequestOutcome::CacheHit,
+                    RequestOutcome::Coalesced,
+                    RequestOutcome::Forwarded,
+                    RequestOutcome::CacheMiss,
+                    RequestOutcome::Uncached,
+                ] {
+                    out.push_str(&format!(
+                        "verus_sli_requests_total{{method=\"{}\",category=\"{}\",outcome=\"{}\"}} {}\n",
+                        method,
+                        method_category(method),
+                        outcome.label(),
+                        counts.get(outcome)
+                    ));
+                }
+            }
+        }
+        let summary = self.summary();
+        push_ratio_gauge(&mut out, &summary, "verus_sli_cache_hit_ratio", "Cache hits among cacheable requests", |w| {
+            w.cache_hit_ratio
+        });
+        push_ratio_gauge(
+            &mut out,
+            &summary,
+            "verus_sli_coalesced_ratio",
+            "Cacheable requests answered by another replica's upstream call",
+            |w| w.coalesced_ratio,
+        );
+        push_ratio_gauge(
+            &mut out,
+            &summary,
+            "verus_sli_upstream_savings_ratio",
+            "Requests answered without an upstream call",
+            |w| w.upstream_savings_ratio,
+        );
+        out
+    }
+}
+
+/// One gauge per window; windows without traffic are left out
+fn push_ratio_gauge(out: &mut String, summary: &SliSummary, metric: &str, help: &str, value: fn(&WindowSli) -> Option<f64>) {
+    out.push_str(&format!("# HELP {} {}\n", metric, help));
+    out.push_str(&format!("# TYPE {} gauge\n", metric));
+    for (window, sli) in &summary.windows {
+        if let Some(value) = value(sli) {
+            out.push_str(&format!("{}{{window=\"{}\"}} {}\n", metric, window, value));
+        }
+    }
+}
+
+fn now_seconds() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_and_ratios() {
+        let metrics = SliMetrics::default();
+        let now = 1_700_000_000;
+        // Ten minutes ago: only in the 1h window
+        metrics.record_at("getinfo", RequestOutcome::CacheMiss, now - 600);
+        metrics.record_at("getinfo", RequestOutcome::CacheHit, now - 5);
+        metrics.record_at("getinfo", RequestOutcome::CacheHit, now);
+        metrics.record_at("getinfo", RequestOutcome::Coalesced, now);
+        metrics.record_at("sendrawtransaction", RequestOutcome::Uncached, now);
+
+        let summary = metrics.summary_at(now);
+        let minute = &summary.windows["1m"];
+        assert_eq!(minute.requests, 4);
+        assert_eq!(minute.upstream_calls, 1);
+        assert_eq!(minute.cache_hit_ratio, Some(2.0 / 3.0));
+        assert_eq!(minute.coalesced_ratio, Some(1.0 / 3.0));
+        assert_eq!(minute.upstream_savings_ratio, Some(0.75));
+        assert_eq!(minute.methods["sendrawtransaction"].cache_hit_ratio, None);
+
+        let hour = &summary.windows["1h"];
+        assert_eq!(hour.requests, 5);
+        assert_eq!(hour.methods["getinfo"].cache_hit_ratio, Some(0.5));
+    }
+
+    #[test]
+    fn test_buckets_older_than_an_hour_are_dropped() {
+        let metrics = SliMetrics::default();
+        let now = 1_700_000_000;
+        metrics.record_at("getinfo", RequestOutcome::CacheHit, now - 7200);
+        metrics.record_at("getinfo", RequestOutcome::CacheHit, now);
+        assert_eq!(metrics.summary_at(now).windows["1h"].requests, 1);
+        assert!(metrics.prometheus_text().contains("verus_sli_requests_total{method=\"getinfo\",category=\"blockchain\",outcome=\"cache_hit\"} 2"));
+    }
+}
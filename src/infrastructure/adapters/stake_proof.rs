@@ -0,0 +1,192 @@
+//! Challenge-response staking proofs
+//!
+//! A holder asks for a server nonce bound to their address, signs the
+//! challenge message with the address key (`signmessage`), and submits the
+//! signature. The daemon checks the signature (`verifymessage`) and the
+//! address balance (`getaddressbalance`); holders above the configured
+//! minimum get enhanced-permission tokens without solving PoW.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::config::app_config::StakeProofConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::shared::error::{AppError, AppResult};
+
+/// Satoshis per VRSC
+const SATOSHIS_PER_COIN: f64 = 100_000_000.0;
+
+/// Maximum outstanding challenges kept in memory
+const MAX_PENDING_CHALLENGES: usize = 10_000;
+
+/// Server nonce a holder must sign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeChallenge {
+    /// Address the challenge is bound to
+    pub address: String,
+    /// Single-use server nonce
+    pub nonce: String,
+    /// Exact message to sign with `signmessage`
+    pub message: String,
+    /// When the challenge expires
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Signed challenge carried by `TokenIssuanceMode::StakeProof`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeProof {
+    /// Address holding the stake
+    pub address: String,
+    /// Nonce from the issued challenge
+    pub nonce: String,
+    /// Base64 signature from `signmessage`
+    pub signature: String,
+}
+
+/// Issues stake challenges and verifies proofs against the daemon
+pub struct StakeVerifier {
+    config: StakeProofConfig,
+    rpc: Arc<ExternalRpcAdapter>,
+    /// nonce -> challenge
+    pending: Mutex<HashMap<String, StakeChallenge>>,
+}
+
+impl StakeVerifier {
+    pub fn new(config: StakeProofConfig, rpc: Arc<ExternalRpcAdapter>) -> Self {
+        Self { config, rpc, pending: Mutex::new(HashMap::new()) }
+    }
+
+    /// Message a holder signs for a challenge
+    pub fn challenge_message(address: &str, nonce: &str) -> String {
+        format!("verus-rpc-stake-proof:{}:{}", address, nonce)
+    }
+
+    /// Issue a single-use challenge for an address
+    pub async fn issue_challenge(&self, address: &str) -> AppResult<StakeChallenge> {
+        let address = address.trim();
+        if address.is_empty() || address.len() > 128 {
+            return Err(AppError::Validation("A valid address is required".to_string()));
+        }
+        let nonce = Uuid::new_v4().to_string();
+        let challenge = StakeChallenge {
+            address: address.to_string(),
+            message: Self::challenge_message(address, &nonce),
+            nonce: nonce.clone(),
+            expires_at: Utc::now() + Duration::seconds(self.config.challenge_ttl_seconds as i64),
+        };
+
+        let mut pending = self.pending.lock().await;
+        let now = Utc::now();
+        pending.retain(|_, c| c.expires_at > now);
+        if pending.len() >= MAX_PENDING_CHALLENGES {
+            return Err(AppError::RateLimit);
+        }
+        pending.insert(nonce, challenge.clone());
+        Ok(challenge)
+    }
+
+    /// Verify a proof, consuming its challenge; returns the balance in satoshis
+    pub async fn verify(&self, proof: &StakeProof) -> AppResult<u64> {
+        let challenge = self
+            .pending
+            .lock()
+            .await
+            .remove(&proof.nonce)
+            .ok_or_else(|| AppError::Validation("Unknown or already used stake challenge".to_string()))?;
+        if challenge.address != proof.address {
+            return Err(AppError::Validation("Stake proof address does not match challenge".to_string()));
+        }
+        if challenge.expires_at < Utc::now() {
+            return Err(AppError::Validation("Stake challenge expired".to_string()));
+        }
+
+        let verified = self
+            .call("verifymessage", json!([proof.address, proof.signature, challenge.message]))
+            .await?;
+        if verified != Value::Bool(true) {
+            return Err(AppError::Validation("Invalid stake proof signature".to_string()));
+        }
+
+        let balance = self
+            .call("getaddressbalance", json!([{ "addresses": [proof.address] }]))
+            .await?;
+        let satoshis = balance
+            .get("balance")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| AppError::Rpc("getaddressbalance returned no balance".to_string()))? as u64;
+        let required = self.min_balance_satoshis();
+        if satoshis < required {
+            return Err(AppError::Validation(format!(
+                "Address balance {} is below the required {} satoshis",
+                satoshis, required
+            )));
+        }
+        Ok(satoshis)
+    }
+
+    /// Minimum balance in satoshis
+    pub fn min_balance_satoshis(&self) -> u64 {
+        (self.config.min_balance * SATOSHIS_PER_COIN).round() as u64
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let request = RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("stake_proof_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("stake-proof".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        );
+        self.rpc
+            .send_request(&request)
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    fn verifier() -> StakeVerifier {
+        let config = Arc::new(AppConfig::default());
+        StakeVerifier::new(StakeProofConfig::default(), Arc::new(ExternalRpcAdapter::new(config)))
+    }
+
+    #[tokio::test]
+    async fn test_challenge_is_single_use_and_address_bound() {
+        let verifier = verifier();
+        let challenge = verifier.issue_challenge("RAddress1").await.unwrap();
+        assert_eq!(challenge.message, StakeVerifier::challenge_message("RAddress1", &challenge.nonce));
+
+        // Wrong address consumes the challenge without reaching the daemon
+        let proof = StakeProof {
+            address: "RAddress2".to_string(),
+            nonce: challenge.nonce.clone(),
+            signature: "sig".to_string(),
+        };
+        assert!(matches!(verifier.verify(&proof).await, Err(AppError::Validation(_))));
+        assert!(matches!(verifier.verify(&proof).await, Err(AppError::Validation(msg)) if msg.contains("already used")));
+    }
+
+    #[tokio::test]
+    async fn test_min_balance_in_satoshis() {
+        let verifier = verifier();
+        assert_eq!(verifier.min_balance_satoshis(), (StakeProofConfig::default().min_balance * 1e8) as u64);
+        assert!(verifier.issue_challenge("").await.is_err());
+    }
+}
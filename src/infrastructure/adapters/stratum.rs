@@ -0,0 +1,250 @@
+//! Stratum-lite PoW session
+//!
+//! Transport-independent state machine behind the token service's
+//! `GET /pow/stream` WebSocket. Mirrors how mining clients talk to a pool:
+//! the client subscribes once, the server pushes challenges (`mining.notify`)
+//! and difficulty changes (`mining.set_difficulty`), and the client submits
+//! nonces (`mining.submit`). Challenges are held server-side, so a client can
+//! only solve the challenge it was given.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::app_config::StratumConfig;
+use crate::infrastructure::adapters::token_issuer::{
+    PowAlgorithm, PowChallenge, PowProof, TokenIssuanceMode, TokenIssuanceRequest, TokenIssuerAdapter,
+};
+use crate::shared::error::{AppError, AppResult};
+
+/// A client message: `{"id": 1, "method": "mining.submit", "params": {...}}`
+#[derive(Debug, Deserialize)]
+struct StratumMessage {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubscribeParams {
+    #[serde(default)]
+    user_id: String,
+    #[serde(default)]
+    permissions: Vec<String>,
+    user_agent: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmitParams {
+    challenge_id: String,
+    nonce: String,
+}
+
+struct Subscription {
+    user_id: String,
+    permissions: Vec<String>,
+    user_agent: Option<String>,
+}
+
+/// Per-connection stratum state
+pub struct StratumSession {
+    issuer: Arc<TokenIssuerAdapter>,
+    client_ip: String,
+    target_solve_seconds: u64,
+    /// Easiest allowed target (the configured default difficulty)
+    max_target: u64,
+    target: u64,
+    target_width: usize,
+    subscription: Option<Subscription>,
+    challenge: Option<(PowChallenge, Instant)>,
+}
+
+impl StratumSession {
+    pub fn new(issuer: Arc<TokenIssuerAdapter>, config: &StratumConfig, default_difficulty: &str, client_ip: String) -> Self {
+        let max_target = u64::from_str_radix(default_difficulty, 16).unwrap_or(0x0000ffff).max(1);
+        Self {
+            issuer,
+            client_ip,
+            target_solve_seconds: config.target_solve_seconds,
+            max_target,
+            target: max_target,
+            target_width: default_difficulty.len().max(8),
+            subscription: None,
+            challenge: None,
+        }
+    }
+
+    /// Current target as a hex difficulty string
+    pub fn difficulty(&self) -> String {
+        format!("{:0width$x}", self.target, width = self.target_width)
+    }
+
+    /// Handle one client message, returning the messages to send back
+    pub async fn handle(&mut self, text: &str) -> Vec<String> {
+        let message: StratumMessage = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => return vec![Self::error(Value::Null, format!("Invalid message: {}", e))],
+        };
+        let id = message.id.clone();
+        let result = match message.method.as_str() {
+            "mining.subscribe" => self.subscribe(id.clone(), message.params).await,
+            "mining.submit" => self.submit(id.clone(), message.params).await,
+            "mining.ping" => Ok(vec![Self::result(id.clone(), json!("pong"))]),
+            other => Err(AppError::Validation(format!("Unknown method: {}", other))),
+        };
+        result.unwrap_or_else(|e| vec![Self::error(id, e.to_string())])
+    }
+
+    async fn subscribe(&mut self, id: Value, params: Value) -> AppResult<Vec<String>> {
+        let params: SubscribeParams = if params.is_null() {
+            SubscribeParams { user_id: String::new(), permissions: Vec::new(), user_agent: None }
+        } else {
+            serde_json::from_value(params).map_err(|e| AppError::Validation(format!("Invalid subscribe params: {}", e)))?
+        };
+        let permissions = if params.permissions.is_empty() { vec!["read".to_string()] } else { params.permissions };
+        self.subscription = Some(Subscription { user_id: params.user_id, permissions, user_agent: params.user_agent });
+
+        let mut out = vec![Self::result(id, json!({ "subscribed": true, "difficulty": self.difficulty() }))];
+        out.extend(self.notify_new_challenge().await?);
+        Ok(out)
+    }
+
+    async fn submit(&mut self, id: Value, params: Value) -> AppResult<Vec<String>> {
+        let params: SubmitParams =
+            serde_json::from_value(params).map_err(|e| AppError::Validation(format!("Invalid submit params: {}", e)))?;
+        let subscription = self
+            .subscription
+            .as_ref()
+            .ok_or_else(|| AppError::Validation("Subscribe before submitting".to_string()))?;
+        let (challenge, issued_at) = self
+            .challenge
+            .clone()
+            .ok_or_else(|| AppError::Validation("No active challenge".to_string()))?;
+
+        if challenge.expires_at < Utc::now() {
+            let mut out = vec![Self::error(id, "Challenge expired".to_string())];
+            out.extend(self.notify_new_challenge().await?);
+            return Ok(out);
+        }
+        if params.challenge_id != challenge.id {
+            return Err(AppError::Validation("Stale or unknown challenge".to_string()));
+        }
+
+        let input = format!("{}{}", challenge.challenge, params.nonce);
+        let solution = match challenge.algorithm {
+            PowAlgorithm::Sha256 => self.issuer.pow_manager.hash_sha256(&input),
+            PowAlgorithm::Blake3 => hex::encode(blake3::hash(input.as_bytes()).as_bytes()),
+        };
+        let request = TokenIssuanceRequest {
+            user_id: subscription.user_id.clone(),
+            permissions: subscription.permissions.clone(),
+            client_ip: Some(self.client_ip.clone()),
+            user_agent: subscription.user_agent.clone(),
+            custom_expiration: None,
+            mode: TokenIssuanceMode::ProofOfWork(PowProof {
+                challenge_id: challenge.id.clone(),
+                nonce: params.nonce,
+                solution,
+                difficulty: challenge.target_difficulty.clone(),
+                submitted_at: Utc::now(),
+                client_ip: self.client_ip.clone(),
+            }),
+            pow_challenge: Some(challenge),
+        };
+
+        // Rejected solutions keep the current challenge so the client can keep hashing
+        let token = self.issuer.issue_token(request).await?;
+
+        let mut out = vec![Self::result(id, serde_json::to_value(&token).unwrap_or(Value::Null))];
+        if self.retarget(issued_at.elapsed().as_secs()) {
+            out.push(Self::notification("mining.set_difficulty", json!({ "difficulty": self.difficulty() })));
+        }
+        out.extend(self.notify_new_challenge().await?);
+        Ok(out)
+    }
+
+    /// Adjust difficulty toward the target solve time; returns whether it changed
+    ///
+    /// Fast solvers get harder challenges; slow solvers ease back, but never
+    /// below the configured default difficulty.
+    fn retarget(&mut self, solve_seconds: u64) -> bool {
+        let previous = self.target;
+        if solve_seconds * 2 < self.target_solve_seconds {
+            self.target = (self.target / 2).max(1);
+        } else if solve_seconds > self.target_solve_seconds * 2 {
+            // Shift-and-fill keeps all-ones targets (e.g. 0000ffff) exact inverses of halving
+            self.target = self.target.saturating_mul(2).saturating_add(1).min(self.max_target);
+        }
+        self.target != previous
+    }
+
+    async fn notify_new_challenge(&mut self) -> AppResult<Vec<String>> {
+        let mut challenge = self.issuer.generate_pow_challenge(&self.client_ip).await?;
+        challenge.target_difficulty = self.difficulty();
+        let notification = Self::notification("mining.notify", serde_json::to_value(&challenge).unwrap_or(Value::Null));
+        self.challenge = Some((challenge, Instant::now()));
+        Ok(vec![notification])
+    }
+
+    fn result(id: Value, result: Value) -> String {
+        json!({ "id": id, "result": result, "error": Value::Null }).to_string()
+    }
+
+    fn error(id: Value, message: String) -> String {
+        json!({ "id": id, "result": Value::Null, "error": message }).to_string()
+    }
+
+    fn notification(method: &str, params: Value) -> String {
+        json!({ "id": Value::Null, "method": method, "params": params }).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    fn session() -> StratumSession {
+        let issuer = Arc::new(TokenIssuerAdapter::new(Arc::new(AppConfig::default())));
+        StratumSession::new(issuer, &StratumConfig::default(), "ffffffff", "127.0.0.1".to_string())
+    }
+
+    fn parse(messages: &[String]) -> Vec<Value> {
+        messages.iter().map(|m| serde_json::from_str(m).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_pushes_challenge_and_submit_issues_token() {
+        let mut session = session();
+        let out = parse(&session.handle(r#"{"id":1,"method":"mining.subscribe","params":{"permissions":["read"]}}"#).await);
+        assert_eq!(out[0]["result"]["subscribed"], true);
+        assert_eq!(out[1]["method"], "mining.notify");
+        let challenge_id = out[1]["params"]["id"].as_str().unwrap().to_string();
+
+        // The easiest difficulty accepts any nonce
+        let submit = json!({ "id": 2, "method": "mining.submit", "params": { "challenge_id": challenge_id, "nonce": "1" } });
+        let out = parse(&session.handle(&submit.to_string()).await);
+        assert!(out[0]["result"]["token"].is_string());
+        assert_eq!(out.last().unwrap()["method"], "mining.notify");
+        assert_ne!(out.last().unwrap()["params"]["id"], challenge_id.as_str());
+
+        // The solved challenge cannot be reused
+        let out = parse(&session.handle(&submit.to_string()).await);
+        assert!(out[0]["error"].is_string());
+    }
+
+    #[test]
+    fn test_retarget_bounds() {
+        let mut session = session();
+        assert!(session.retarget(0));
+        assert_eq!(session.difficulty(), "7fffffff");
+        assert!(session.retarget(3600));
+        assert_eq!(session.difficulty(), "ffffffff");
+        assert!(!session.retarget(3600));
+    }
+}
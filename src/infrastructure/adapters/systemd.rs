@@ -0,0 +1,196 @@
+//! systemd integration: socket activation and sd_notify
+//!
+//! With socket activation systemd binds the sockets of the `.socket` unit and
+//! passes them as file descriptors from 3 on (`LISTEN_FDS`, `LISTEN_PID`); the
+//! server then serves those instead of binding its own. `READY=1` is sent on
+//! `NOTIFY_SOCKET` once the listeners are up, for `Type=notify` units, and the
+//! watchdog sends `WATCHDOG=1` at half of `WATCHDOG_USEC` for as long as the
+//! health check passes, so systemd restarts a wedged process. Outside systemd
+//! all of this is a no-op.
+
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+use crate::infrastructure::http::listener::BoundListener;
+
+/// First file descriptor passed by systemd
+#[cfg(unix)]
+const LISTEN_FDS_START: i32 = 3;
+
+/// Listening sockets passed by systemd socket activation; empty when not socket-activated
+pub fn listen_fds() -> io::Result<Vec<BoundListener>> {
+    let count = activated_fd_count(
+        std::env::var("LISTEN_PID").ok().as_deref(),
+        std::env::var("LISTEN_FDS").ok().as_deref(),
+        std::process::id(),
+    );
+    #[cfg(unix)]
+    {
+        (LISTEN_FDS_START..LISTEN_FDS_START + count as i32).map(adopt_listener).collect()
+    }
+    #[cfg(not(unix))]
+    {
+        if count > 0 {
+            warn!("LISTEN_FDS is set but socket activation is not supported on this platform");
+        }
+        Ok(Vec::new())
+    }
+}
+
+/// Number of sockets meant for this process; `LISTEN_PID` must name it
+fn activated_fd_count(listen_pid: Option<&str>, listen_fds: Option<&str>, own_pid: u32) -> usize {
+    if listen_pid.and_then(|pid| pid.trim().parse::<u32>().ok()) != Some(own_pid) {
+        return 0;
+    }
+    listen_fds.and_then(|fds| fds.trim().parse::<usize>().ok()).unwrap_or(0)
+}
+
+/// Take ownership of an inherited listening socket, TCP or Unix
+#[cfg(unix)]
+fn adopt_listener(fd: i32) -> io::Result<BoundListener> {
+    use std::os::fd::{FromRawFd, IntoRawFd};
+
+    // SAFETY: systemd hands fds LISTEN_FDS_START.. to this process (LISTEN_PID
+    // was checked) and nothing else in the process owns them.
+    let tcp = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+    if tcp.local_addr().is_ok() {
+        tcp.set_nonblocking(true)?;
+        return Ok(BoundListener::Tcp(tokio::net::TcpListener::from_std(tcp)?));
+    }
+    // SAFETY: ownership moves from the TcpListener that just gave it up
+    let unix = unsafe { std::os::unix::net::UnixListener::from_raw_fd(tcp.into_raw_fd()) };
+    unix.local_addr()
+        .map_err(|e| io::Error::new(e.kind(), format!("fd {} is neither a TCP nor a Unix socket: {}", fd, e)))?;
+    unix.set_nonblocking(true)?;
+    Ok(BoundListener::Unix(tokio::net::UnixListener::from_std(unix)?))
+}
+
+/// Tell systemd the service is up
+pub fn notify_ready() {
+    if notify("READY=1\nSTATUS=Serving JSON-RPC") {
+        debug!("Sent READY=1 to systemd");
+    }
+}
+
+/// Send `state` to the systemd notify socket; false when there is none or sending failed
+pub fn notify(state: &str) -> bool {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return false;
+    };
+    match send_notify(&path, state) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(error = %e, "Failed to notify systemd");
+            false
+        }
+    }
+}
+
+#[cfg(unix)]
+fn send_notify(path: &std::ffi::OsStr, state: &str) -> io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let socket = UnixDatagram::unbound()?;
+    if let Some(name) = path.as_bytes().strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            socket.send_to_addr(state.as_bytes(), &addr)?;
+            return Ok(());
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = name;
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "abstract notify socket"));
+        }
+    }
+    socket.send_to(state.as_bytes(), path)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_notify(_path: &std::ffi::OsStr, _state: &str) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "sd_notify needs Unix sockets"))
+}
+
+/// Interval between watchdog pings: half of `WATCHDOG_USEC`, when it is meant for this process
+fn watchdog_interval(usec: Option<&str>, watchdog_pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    if let Some(pid) = watchdog_pid {
+        if pid.trim().parse::<u32>().ok()? != own_pid {
+            return None;
+        }
+    }
+    let usec = usec?.trim().parse::<u64>().ok().filter(|usec| *usec > 0)?;
+    Some(Duration::from_micros(usec / 2))
+}
+
+/// Ping the systemd watchdog for as long as `check` reports the service healthy
+///
+/// A failed check skips the ping; once `WatchdogSec=` passes without one,
+/// systemd restarts the service.
+pub fn start_watchdog<F, Fut>(check: F)
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = bool> + Send,
+{
+    let Some(interval) = watchdog_interval(
+        std::env::var("WATCHDOG_USEC").ok().as_deref(),
+        std::env::var("WATCHDOG_PID").ok().as_deref(),
+        std::process::id(),
+    ) else {
+        warn!("systemd.watchdog_enabled is set but systemd did not enable a watchdog (WatchdogSec=); not pinging");
+        return;
+    };
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if check().await {
+                notify("WATCHDOG=1");
+            } else {
+                warn!("Health check failed; withholding systemd watchdog ping");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activated_fd_count_requires_own_pid() {
+        assert_eq!(activated_fd_count(Some("42"), Some("2"), 42), 2);
+        assert_eq!(activated_fd_count(Some("41"), Some("2"), 42), 0);
+        assert_eq!(activated_fd_count(None, Some("2"), 42), 0);
+        assert_eq!(activated_fd_count(Some("42"), Some("x"), 42), 0);
+    }
+
+    #[test]
+    fn test_watchdog_interval_is_half_the_timeout() {
+        assert_eq!(watchdog_interval(Some("30000000"), None, 7), Some(Duration::from_secs(15)));
+        assert_eq!(watchdog_interval(Some("30000000"), Some("7"), 7), Some(Duration::from_secs(15)));
+        assert_eq!(watchdog_interval(Some("30000000"), Some("8"), 7), None);
+        assert_eq!(watchdog_interval(Some("0"), None, 7), None);
+        assert_eq!(watchdog_interval(None, None, 7), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_send_notify_to_datagram_socket() {
+        let path = std::env::temp_dir().join(format!("verus-rpc-notify-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let receiver = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+
+        send_notify(path.as_os_str(), "READY=1").unwrap();
+        let mut buf = [0u8; 32];
+        let n = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,243 @@
+//! Tenants: isolated namespaces sharing one deployment
+//!
+//! Each `[tenants.<name>]` entry is matched by the SHA-256 of an `X-API-Key`
+//! header or by the audience of a JWT signed with the configured secret and
+//! issuer, so several applications can use one proxy with their own keys and
+//! tokens. A tenant restricts the callable methods, gets its own rate-limit
+//! budget and a daily request quota, and its traffic is counted under its
+//! `tenant` label in metrics. As with client profiles, a token only selects a
+//! tenant once the authentication adapter has checked its signature, expiry
+//! and revocation.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use chrono::{NaiveDate, Utc};
+
+use crate::config::app_config::TenantConfig;
+use crate::config::AppConfig;
+use crate::infrastructure::adapters::authentication::JwtClaims;
+use crate::shared::security::{find_by_api_key, normalize_api_key_hashes};
+
+/// A tenant matched to a request
+#[derive(Debug, Clone)]
+pub struct Tenant {
+    pub name: String,
+    pub config: TenantConfig,
+}
+
+impl Tenant {
+    /// Value of the `tenant` metrics label
+    pub fn metrics_label(&self) -> &str {
+        self.config.metrics_label.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Whether the tenant may call `method`
+    pub fn allows_method(&self, method: &str) -> bool {
+        self.config
+            .allowed_methods
+            .as_ref()
+            .is_none_or(|methods| methods.iter().any(|m| m == method))
+    }
+}
+
+/// Why a tenant's request was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TenantRejection {
+    Method,
+    Quota,
+    RateLimit,
+}
+
+impl TenantRejection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TenantRejection::Method => "method",
+            TenantRejection::Quota => "quota",
+            TenantRejection::RateLimit => "rate_limit",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Usage {
+    day: Option<NaiveDate>,
+    /// Requests counted against the quota on `day`
+    today: u64,
+    requests: u64,
+    rejected_method: u64,
+    rejected_quota: u64,
+    rejected_rate_limit: u64,
+}
+
+/// Configured tenants and their usage
+pub struct Tenants {
+    tenants: Vec<Tenant>,
+    /// Keyed by metrics label
+    usage: Mutex<HashMap<String, Usage>>,
+}
+
+impl Tenants {
+    /// Build the tenant set from `[tenants]`
+    pub fn new(config: &AppConfig) -> Self {
+        let mut tenants: Vec<Tenant> = config
+            .tenants
+            .iter()
+            .map(|(name, tenant)| Tenant { name: name.clone(), config: tenant.clone() })
+            .collect();
+        for tenant in &mut tenants {
+            normalize_api_key_hashes(&mut tenant.config.api_key_hashes, &format!("Tenant {}", tenant.name));
+        }
+        Self { tenants, usage: Mutex::new(HashMap::new()) }
+    }
+
+    /// Tenant for a request; an API key match takes precedence over the audience of verified `claims`
+    pub fn resolve(&self, api_key: Option<&str>, claims: Option<&JwtClaims>) -> Option<&Tenant> {
+        if self.tenants.is_empty() {
+            return None;
+        }
+        if let Some(key) = api_key.filter(|key| !key.is_empty()) {
+            let tenant = find_by_api_key(&self.tenants, key, |tenant| tenant.config.api_key_hashes.as_slice());
+            if tenant.is_some() {
+                return tenant;
+            }
+        }
+        let audience = claims?.aud.as_str();
+        self.tenants.iter().find(|tenant| tenant.config.jwt_audience.as_deref() == Some(audience))
+    }
+
+    /// Count a request against the tenant's daily quota; `false` once the quota is used up
+    pub fn admit(&self, tenant: &Tenant) -> bool {
+        let today = Utc::now().date_naive();
+        let Ok(mut usage) = self.usage.lock() else {
+            return true;
+        };
+        let usage = usage.entry(tenant.metrics_label().to_string()).or_default();
+        if usage.day != Some(today) {
+            usage.day = Some(today);
+            usage.today = 0;
+        }
+        if tenant.config.daily_request_quota.is_some_and(|quota| usage.today >= quota) {
+            usage.rejected_quota += 1;
+            return false;
+        }
+        usage.today += 1;
+        usage.requests += 1;
+        true
+    }
+
+    /// Count a request refused for `reason` other than the quota
+    pub fn record_rejected(&self, tenant: &Tenant, reason: TenantRejection) {
+        if let Ok(mut usage) = self.usage.lock() {
+            let usage = usage.entry(tenant.metrics_label().to_string()).or_default();
+            match reason {
+                TenantRejection::Method => usage.rejected_method += 1,
+                TenantRejection::Quota => usage.rejected_quota += 1,
+                TenantRejection::RateLimit => usage.rejected_rate_limit += 1,
+            }
+        }
+    }
+
+    /// Render per-tenant counters in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let mut out = String::new();
+        if self.tenants.is_empty() {
+            return out;
+        }
+        let usage: BTreeMap<String, Usage> = self
+            .usage
+            .lock()
+            .map(|usage| usage.iter().map(|(label, u)| (label.clone(), *u)).collect())
+            .unwrap_or_default();
+        out.push_str("# HELP verus_tenant_requests_total Requests admitted per tenant\n");
+        out.push_str("# TYPE verus_tenant_requests_total counter\n");
+        for (label, u) in &usage {
+            out.push_str(&format!("verus_tenant_requests_total{{tenant=\"{}\"}} {}\n", label, u.requests));
+        }
+        out.push_str("# HELP verus_tenant_rejected_total Requests refused by tenant policy\n");
+        out.push_str("# TYPE verus_tenant_rejected_total counter\n");
+        for (label, u) in &usage {
+            for (reason, count) in [
+                (TenantRejection::Method, u.rejected_method),
+                (TenantRejection::Quota, u.rejected_quota),
+                (TenantRejection::RateLimit, u.rejected_rate_limit),
+            ] {
+                out.push_str(&format!(
+                    "verus_tenant_rejected_total{{tenant=\"{}\",reason=\"{}\"}} {}\n",
+                    label,
+                    reason.as_str(),
+                    count
+                ));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tenants(list: Vec<(&str, TenantConfig)>) -> (AppConfig, Tenants) {
+        let mut config = AppConfig::default();
+        config.tenants = list.into_iter().map(|(name, tenant)| (name.to_string(), tenant)).collect();
+        let tenants = Tenants::new(&config);
+        (config, tenants)
+    }
+
+    fn claims(aud: &str) -> JwtClaims {
+        let now = Utc::now().timestamp() as usize;
+        JwtClaims {
+            sub: "user".to_string(),
+            iss: "verus-rpc-server".to_string(),
+            aud: aud.to_string(),
+            iat: now,
+            iat_ms: None,
+            exp: now + 600,
+            nbf: now,
+            jti: "jti".to_string(),
+            permissions: vec!["read".to_string()],
+            client_ip: None,
+            user_agent: None,
+            sid: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_by_api_key_or_audience() {
+        let wallet = TenantConfig {
+            api_key_hashes: vec![crate::shared::security::api_key_hash("wallet-key")],
+            ..Default::default()
+        };
+        let explorer = TenantConfig { jwt_audience: Some("explorer-app".to_string()), ..Default::default() };
+        let (config, tenants) = tenants(vec![("wallet", wallet), ("explorer", explorer)]);
+
+        assert_eq!(tenants.resolve(Some("wallet-key"), None).map(|t| t.name.as_str()), Some("wallet"));
+        assert!(tenants.resolve(Some("other-key"), None).is_none());
+
+        assert_eq!(tenants.resolve(None, Some(&claims("explorer-app"))).map(|t| t.name.as_str()), Some("explorer"));
+        assert!(tenants.resolve(None, Some(&claims(&config.security.jwt.audience))).is_none());
+    }
+
+    #[test]
+    fn test_daily_quota_and_metrics_label() {
+        let limited = TenantConfig {
+            daily_request_quota: Some(2),
+            metrics_label: Some("acme".to_string()),
+            allowed_methods: Some(vec!["getinfo".to_string()]),
+            ..Default::default()
+        };
+        let (_, tenants) = tenants(vec![("acme-prod", limited)]);
+        let tenant = tenants.tenants[0].clone();
+
+        assert!(tenant.allows_method("getinfo"));
+        assert!(!tenant.allows_method("sendrawtransaction"));
+        assert!(tenants.admit(&tenant));
+        assert!(tenants.admit(&tenant));
+        assert!(!tenants.admit(&tenant));
+
+        let text = tenants.prometheus_text();
+        assert!(text.contains("verus_tenant_requests_total{tenant=\"acme\"} 2"));
+        assert!(text.contains("verus_tenant_rejected_total{tenant=\"acme\",reason=\"quota\"} 1"));
+    }
+}
@@ -3,7 +3,9 @@
 //! This adapter handles secure JWT token issuance for external authentication services.
 
 use crate::shared::error::AppResult;
+use crate::shared::security::constant_time_str_eq;
 use crate::config::AppConfig;
+use crate::domain::payments::PaymentClaim;
 use std::sync::Arc;
 use tracing::{info, warn, error};
 use jsonwebtoken::{encode, decode, Header, EncodingKey, DecodingKey, Validation, Algorithm};
@@ -12,7 +14,32 @@ use chrono::{Utc, Duration};
 use uuid::Uuid;
 use sha2::{Sha256, Digest};
 use blake3::Hasher;
-use crate::infrastructure::adapters::mining_pool::{PoolShare, MiningPoolClient};
+use crate::infrastructure::adapters::mining_pool::{PoolShare, PoolValidationResponse, MiningPoolClient};
+use crate::infrastructure::adapters::SessionStore;
+use crate::infrastructure::adapters::partners::{PartnerRegistry, PartnerTokenRequest, PartnerUsageRegistry};
+use crate::config::app_config::PartnerConfig;
+use crate::infrastructure::adapters::stake_proof::{StakeChallenge, StakeProof, StakeVerifier};
+use crate::infrastructure::adapters::{CpuPool, ExternalRpcAdapter};
+use crate::infrastructure::adapters::issuance_webhook::{IssuanceReview, IssuanceSource, IssuanceWebhook};
+
+/// Permissions only the issuer grants, once a payment, proof or partner signature checks out
+const RESERVED_PERMISSIONS: &[&str] = &[
+    "admin",
+    "debug",
+    "paid",
+    "provisional",
+    "zero_conf",
+    "pow_validated",
+    "pool_validated",
+    "partner_validated",
+    "stake_validated",
+];
+
+/// Prefixes of reserved permissions: token scopes and issuer-granted markers
+const RESERVED_PREFIXES: &[&str] = &["method:", "read:", "write:", "rate_multiplier_", "partner_", "staker_", "miner_"];
+
+/// Prefixes of user IDs the issuer assigns itself (payment tokens, generated anonymous IDs)
+const RESERVED_USER_ID_PREFIXES: &[&str] = &["pay_", "anon_user_"];
 
 /// JWT claims structure
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,6 +56,10 @@ pub struct JwtClaims {
     /// Issued at
     pub iat: usize,
     
+    /// Issued at, in milliseconds; orders the token against user revocations made in the same second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat_ms: Option<i64>,
+    
     /// Expiration time
     pub exp: usize,
     
@@ -46,6 +77,24 @@ pub struct JwtClaims {
     
     /// User agent (for additional security)
     pub user_agent: Option<String>,
+    
+    /// Session ID for session-bound tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    
+    /// Claims attached by the issuance webhook (e.g. a billing customer id)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<serde_json::Map<String, serde_json::Value>>,    
+    /// On-chain payment behind a token bought through the payments flow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payment: Option<PaymentClaim>,
+}
+
+impl JwtClaims {
+    /// Issue time in milliseconds; tokens without `iat_ms` count from the start of their `iat` second
+    pub fn issued_at_ms(&self) -> i64 {
+        self.iat_ms.unwrap_or(self.iat as i64 * 1000)
+    }
 }
 
 /// Token issuance mode
@@ -57,8 +106,10 @@ pub enum TokenIssuanceMode {
     ProofOfWork(PowProof),
     /// Pool-validated token with enhanced permissions
     PoolValidated(PoolShare),
-    /// Partner-issued token (for trusted DEXs)
-    Partner(String),
+    /// Partner-issued token (for trusted DEXs), signed with the partner's key
+    Partner(PartnerTokenRequest),
+    /// Stake-validated token: proof of control of an address holding a minimum balance
+    StakeProof(StakeProof),
 }
 
 /// PoW algorithm types
@@ -157,6 +208,10 @@ pub struct TokenIssuanceResponse {
     
     /// User ID (generated for anonymous users)
     pub user_id: Option<String>,
+    
+    /// Session ID (session-bound tokens only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
 }
 
 /// Token validation request
@@ -190,6 +245,10 @@ pub struct TokenIssuerAdapter {
     config: Arc<AppConfig>,
     pub pow_manager: PowManager,
     pub mining_pool_client: Option<MiningPoolClient>,
+    sessions: Option<Arc<SessionStore>>,
+    partners: Option<PartnerRegistry>,
+    stake_verifier: Option<StakeVerifier>,
+    webhook: Option<IssuanceWebhook>,
 }
 
 impl TokenIssuerAdapter {
@@ -200,19 +259,102 @@ impl TokenIssuerAdapter {
         } else {
             None
         };
+
+        let partners = if config.partners.enabled {
+            match PartnerRegistry::new(&config.partners) {
+                Ok(registry) => Some(registry),
+                Err(e) => {
+                    error!("Partner token issuance disabled: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let stake_verifier = if config.stake_proof.enabled {
+            Some(StakeVerifier::new(
+                config.stake_proof.clone(),
+                Arc::new(ExternalRpcAdapter::new(config.clone())),
+            ))
+        } else {
+            None
+        };
+        
+        let webhook = IssuanceWebhook::new(&config.issuance_webhook);
         
         Self {
             config: config.clone(),
             pow_manager: PowManager::new(config),
             mining_pool_client,
+            sessions: None,
+            partners,
+            stake_verifier,
+            webhook,
         }
     }
 
+    /// Hash PoW proofs on the server's CPU pool
+    pub fn with_cpu_pool(mut self, cpu_pool: Arc<CpuPool>) -> Self {
+        self.pow_manager.cpu_pool = cpu_pool;
+        self
+    }
+
+    /// Enable session-bound tokens backed by the given store
+    pub fn with_session_store(mut self, store: Arc<SessionStore>) -> Self {
+        self.sessions = Some(store);
+        self
+    }
+
+    /// Issue a session-bound token with sliding expiration
+    ///
+    /// The token stays valid while the session is active: each validation
+    /// extends the idle deadline, up to the session's absolute lifetime.
+    pub async fn issue_session_token(&self, request: TokenIssuanceRequest) -> AppResult<TokenIssuanceResponse> {
+        let sessions = self.sessions.as_ref().ok_or_else(|| {
+            crate::shared::error::AppError::Validation("Session tokens are not enabled".to_string())
+        })?;
+        if !matches!(request.mode, TokenIssuanceMode::Anonymous) {
+            return Err(crate::shared::error::AppError::Validation(
+                "Session tokens only support anonymous issuance mode".to_string(),
+            ));
+        }
+        let request = self.without_reserved_permissions(request);
+        self.validate_issuance_request(&request).await?;
+
+        let user_id = Self::resolve_user_id(&request);
+        let session = sessions.create(&user_id).await?;
+        info!("Session {} started for user: {}", session.id, user_id);
+        self.encode_token(&request, user_id, sessions.max_lifetime_seconds(), Some(session.id), None, None)
+    }
+
+    /// End the session a token belongs to (logout)
+    pub async fn end_session(&self, token: &str) -> AppResult<bool> {
+        let sessions = self.sessions.as_ref().ok_or_else(|| {
+            crate::shared::error::AppError::Validation("Session tokens are not enabled".to_string())
+        })?;
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&[&self.config.security.jwt.audience]);
+        validation.set_issuer(&[&self.config.security.jwt.issuer]);
+        let claims = decode::<JwtClaims>(
+            token,
+            &DecodingKey::from_secret(self.config.security.jwt.secret_key.as_ref()),
+            &validation,
+        )
+        .map_err(|e| crate::shared::error::AppError::Authentication(format!("JWT validation failed: {}", e)))?
+        .claims;
+        let sid = claims.sid.ok_or_else(|| {
+            crate::shared::error::AppError::Validation("Token is not session-bound".to_string())
+        })?;
+        sessions.end(&sid).await
+    }
+
     /// Issue a JWT token
     pub async fn issue_token(&self, request: TokenIssuanceRequest) -> AppResult<TokenIssuanceResponse> {
         info!("Processing token issuance request");
         
         // Validate request
+        let request = self.without_reserved_permissions(request);
         self.validate_issuance_request(&request).await?;
         
         // Handle different issuance modes
@@ -226,25 +368,91 @@ impl TokenIssuerAdapter {
             TokenIssuanceMode::PoolValidated(share) => {
                 self.issue_pool_token(&request, share).await
             }
-            TokenIssuanceMode::Partner(partner_id) => {
-                self.issue_partner_token(&request, partner_id).await
+            TokenIssuanceMode::Partner(partner_request) => {
+                self.issue_partner_token(&request, partner_request).await
+            }
+            TokenIssuanceMode::StakeProof(proof) => {
+                self.issue_stake_token(&request, proof).await
             }
         }
     }
     
     /// Issue anonymous token (current implementation)
     async fn issue_anonymous_token(&self, request: TokenIssuanceRequest) -> AppResult<TokenIssuanceResponse> {
-        // Generate token ID
-        let token_id = Uuid::new_v4().to_string();
-        
-        // Handle anonymous users (no user_id provided)
-        let user_id = if request.user_id.is_empty() {
+        let user_id = Self::resolve_user_id(&request);
+        let expiration_seconds = request.custom_expiration.unwrap_or(self.config.security.jwt.expiration_seconds);
+        self.encode_token(&request, user_id, expiration_seconds, None, None, None)
+    }
+
+    /// Issue a token after the issuance webhook, when it covers `source`, approves it
+    ///
+    /// Used for tokens bought with a payment or earned with proof of work; the
+    /// webhook's annotations, and the payment for bought tokens, are embedded
+    /// in the token.
+    pub async fn issue_reviewed_token(&self, request: TokenIssuanceRequest, source: IssuanceSource) -> AppResult<TokenIssuanceResponse> {
+        let user_id = Self::resolve_user_id(&request);
+        let expiration_seconds = request.custom_expiration.unwrap_or(self.config.security.jwt.expiration_seconds);
+        let annotations = match &self.webhook {
+            Some(webhook) if webhook.reviews(&source) => {
+                let review = IssuanceReview {
+                    event_id: Uuid::new_v4().to_string(),
+                    user_id: &user_id,
+                    permissions: &request.permissions,
+                    client_ip: request.client_ip.as_deref(),
+                    expires_in: expiration_seconds,
+                    source: &source,
+                };
+                Some(webhook.review(&review).await?).filter(|annotations| !annotations.is_empty())
+            }
+            _ => None,
+        };
+        let payment = source.payment_claim().cloned();
+        self.encode_token(&request, user_id, expiration_seconds, None, annotations, payment)
+    }
+
+    /// Drop requested permissions that only the issuer may grant
+    ///
+    /// Clients choose the permissions of their own tokens, so anything that
+    /// widens access, marks a verified proof, exempts the bearer from rate
+    /// limits (`[rate_limit.exemptions] permissions`) or selects a client
+    /// profile (`[[client_profiles.profiles]] permissions`) is added by the issuer alone.
+    fn without_reserved_permissions(&self, mut request: TokenIssuanceRequest) -> TokenIssuanceRequest {
+        let exempting = &self.config.rate_limit.exemptions.permissions;
+        let profiles = &self.config.client_profiles.profiles;
+        request.permissions.retain(|permission| {
+            let reserved = RESERVED_PERMISSIONS.contains(&permission.as_str())
+                || RESERVED_PREFIXES.iter().any(|prefix| permission.starts_with(prefix))
+                || exempting.contains(permission)
+                || profiles.iter().any(|profile| profile.permissions.contains(permission));
+            if reserved {
+                warn!("Dropping reserved permission {} from issuance request", permission);
+            }
+            !reserved
+        });
+        request
+    }
+
+    /// Use the requested user ID, or generate one for anonymous users
+    fn resolve_user_id(request: &TokenIssuanceRequest) -> String {
+        if request.user_id.is_empty() {
             format!("anon_user_{}", Uuid::new_v4().to_string().split('-').next().unwrap())
         } else {
             request.user_id.clone()
-        };
-        
-        let expiration_seconds = request.custom_expiration.unwrap_or(self.config.security.jwt.expiration_seconds);
+        }
+    }
+
+    /// Build and sign the JWT for an issuance request
+    fn encode_token(
+        &self,
+        request: &TokenIssuanceRequest,
+        user_id: String,
+        expiration_seconds: u64,
+        session_id: Option<String>,
+        annotations: Option<serde_json::Map<String, serde_json::Value>>,
+        payment: Option<PaymentClaim>,
+    ) -> AppResult<TokenIssuanceResponse> {
+        // Generate token ID
+        let token_id = Uuid::new_v4().to_string();
         let now = Utc::now();
         let expiration = now + Duration::seconds(expiration_seconds as i64);
 
@@ -257,12 +465,16 @@ impl TokenIssuerAdapter {
             iss: self.config.security.jwt.issuer.clone(),
             aud: self.config.security.jwt.audience.clone(),
             iat: now.timestamp() as usize,
+            iat_ms: Some(now.timestamp_millis()),
             exp: expiration.timestamp() as usize,
             nbf: now.timestamp() as usize,
             jti: token_id.clone(),
             permissions,
             client_ip,
             user_agent,
+            sid: session_id.clone(),
+            annotations,
+            payment,
         };
         
         // Encode JWT token
@@ -283,6 +495,7 @@ impl TokenIssuerAdapter {
             expires_in: expiration_seconds,
             token_id,
             user_id: Some(user_id),
+            session_id,
         })
     }
     
@@ -316,8 +529,9 @@ impl TokenIssuerAdapter {
             pow_challenge: None,
         };
         
-        // Issue token with enhanced privileges
-        self.issue_anonymous_token(enhanced_request).await
+        // Issue token with enhanced privileges, subject to the issuance webhook
+        let source = IssuanceSource::Pow { challenge_id: challenge.id.clone() };
+        self.issue_reviewed_token(enhanced_request, source).await
     }
     
     /// Issue Pool-validated token
@@ -335,7 +549,16 @@ impl TokenIssuerAdapter {
             ))?;
         
         let validation_response = pool_client.validate_share(share).await?;
-        
+        self.issue_validated_pool_token(request, share, validation_response).await
+    }
+
+    /// Issue a token for a share the pool has already answered for
+    async fn issue_validated_pool_token(
+        &self,
+        request: &TokenIssuanceRequest,
+        share: &PoolShare,
+        validation_response: PoolValidationResponse,
+    ) -> AppResult<TokenIssuanceResponse> {
         if !validation_response.valid {
             return Err(crate::shared::error::AppError::Validation(
                 validation_response.error.unwrap_or_else(|| 
@@ -343,6 +566,13 @@ impl TokenIssuerAdapter {
                 )
             ));
         }
+
+        // A cached answer means this exact share was already submitted; it can only earn one token
+        if validation_response.cached {
+            return Err(crate::shared::error::AppError::Validation(
+                "Pool share has already been used".to_string()
+            ));
+        }
         
         info!("Pool share validated successfully: share_id={:?}, reputation={:?}",
               validation_response.share_id, validation_response.miner_reputation);
@@ -360,30 +590,129 @@ impl TokenIssuerAdapter {
         
         self.issue_anonymous_token(enhanced_request).await
     }
+
+    /// Issue several tokens, validating all pool shares in a single batch
+    ///
+    /// Results are returned in request order; one failing request does not
+    /// affect the others.
+    pub async fn issue_tokens_batch(&self, requests: Vec<TokenIssuanceRequest>) -> Vec<AppResult<TokenIssuanceResponse>> {
+        let shares: Vec<PoolShare> = requests
+            .iter()
+            .filter_map(|request| match &request.mode {
+                TokenIssuanceMode::PoolValidated(share) => Some(share.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut pool_results = match (&self.mining_pool_client, shares.is_empty()) {
+            (_, true) => Ok(Vec::new()),
+            (Some(pool_client), false) => pool_client.validate_shares(&shares).await,
+            (None, false) => Err(crate::shared::error::AppError::Internal(
+                "Mining pool client not available".to_string()
+            )),
+        }
+        .map(|results| results.into_iter());
+
+        let mut responses = Vec::with_capacity(requests.len());
+        for request in requests {
+            let request = self.without_reserved_permissions(request);
+            let response = match &request.mode {
+                TokenIssuanceMode::PoolValidated(share) => match &mut pool_results {
+                    Ok(results) => match self.validate_issuance_request(&request).await {
+                        Ok(()) => match results.next() {
+                            Some(validation) => self.issue_validated_pool_token(&request, share, validation).await,
+                            None => Err(crate::shared::error::AppError::Internal("Missing pool validation result".to_string())),
+                        },
+                        Err(e) => {
+                            results.next();
+                            Err(e)
+                        }
+                    },
+                    Err(e) => Err(e.clone()),
+                },
+                _ => self.issue_token(request.clone()).await,
+            };
+            responses.push(response);
+        }
+        responses
+    }
     
-    /// Issue partner token (placeholder for future implementation)
+    /// Issue partner token after verifying the partner's signature
     async fn issue_partner_token(
         &self, 
         request: &TokenIssuanceRequest, 
-        partner_id: &str
+        partner_request: &PartnerTokenRequest
     ) -> AppResult<TokenIssuanceResponse> {
-        // For now, treat partner tokens as anonymous with enhanced permissions
-        // In production, this would validate partner credentials
-        info!("Processing partner token issuance for partner: {}", partner_id);
+        info!("Processing partner token issuance for partner: {}", partner_request.partner_id);
+
+        let registry = self.partners.as_ref().ok_or_else(|| {
+            crate::shared::error::AppError::Validation("Partner token issuance is not enabled".to_string())
+        })?;
+
+        // The partner signs the exact user ID, so anonymous IDs cannot be generated here
+        if request.user_id.is_empty() {
+            return Err(crate::shared::error::AppError::Validation(
+                "User ID is required for partner tokens".to_string(),
+            ));
+        }
+
+        let partner = registry.verify(partner_request, &request.user_id, &request.permissions)?;
         
         let enhanced_request = TokenIssuanceRequest {
             user_id: request.user_id.clone(),
-            permissions: self.enhance_partner_permissions(&request.permissions, partner_id),
+            permissions: self.enhance_partner_permissions(&request.permissions, &partner),
             client_ip: request.client_ip.clone(),
             user_agent: request.user_agent.clone(),
-            custom_expiration: Some(3600 * 24), // 24 hours for partners
+            custom_expiration: None,
             mode: TokenIssuanceMode::Anonymous,
             pow_challenge: None,
         };
         
-        self.issue_anonymous_token(enhanced_request).await
+        let response = self.encode_token(&enhanced_request, enhanced_request.user_id.clone(), partner.token_ttl_seconds, None, None, None)?;
+        PartnerUsageRegistry::global().record_issued(&partner.id);
+        Ok(response)
     }
     
+    /// Issue a stake challenge for an address (first step of `StakeProof` issuance)
+    pub async fn issue_stake_challenge(&self, address: &str) -> AppResult<StakeChallenge> {
+        self.stake_verifier()?.issue_challenge(address).await
+    }
+
+    fn stake_verifier(&self) -> AppResult<&StakeVerifier> {
+        self.stake_verifier.as_ref().ok_or_else(|| {
+            crate::shared::error::AppError::Validation("Stake proof token issuance is not enabled".to_string())
+        })
+    }
+
+    /// Issue stake-validated token after verifying signature and balance with the daemon
+    async fn issue_stake_token(
+        &self,
+        request: &TokenIssuanceRequest,
+        proof: &StakeProof,
+    ) -> AppResult<TokenIssuanceResponse> {
+        info!("Processing stake proof token issuance for address: {}", proof.address);
+
+        let balance = self.stake_verifier()?.verify(proof).await?;
+        info!("Stake proof verified: address={}, balance={} satoshis", proof.address, balance);
+
+        let mut permissions = request.permissions.clone();
+        permissions.push("stake_validated".to_string());
+        permissions.push(format!("staker_{}", proof.address));
+        permissions.push(format!("rate_multiplier_{}", self.config.stake_proof.rate_limit_multiplier));
+
+        let enhanced_request = TokenIssuanceRequest {
+            user_id: if request.user_id.is_empty() { format!("stake_{}", proof.address) } else { request.user_id.clone() },
+            permissions,
+            client_ip: request.client_ip.clone(),
+            user_agent: request.user_agent.clone(),
+            custom_expiration: Some(self.config.stake_proof.token_duration_seconds),
+            mode: TokenIssuanceMode::Anonymous,
+            pow_challenge: None,
+        };
+
+        self.issue_anonymous_token(enhanced_request).await
+    }
+
     /// Enhance permissions based on PoW validation
     fn enhance_permissions(&self, base_permissions: &[String], challenge: &PowChallenge) -> Vec<String> {
         let mut enhanced = base_permissions.to_vec();
@@ -398,13 +727,13 @@ impl TokenIssuerAdapter {
     }
     
     /// Enhance permissions for partner tokens
-    fn enhance_partner_permissions(&self, base_permissions: &[String], partner_id: &str) -> Vec<String> {
+    fn enhance_partner_permissions(&self, base_permissions: &[String], partner: &PartnerConfig) -> Vec<String> {
         let mut enhanced = base_permissions.to_vec();
         
         // Add partner-specific permissions
         enhanced.push("partner_validated".to_string());
-        enhanced.push(format!("partner_{}", partner_id));
-        enhanced.push("rate_multiplier_3.0".to_string()); // 3x rate limit for partners
+        enhanced.push(format!("partner_{}", partner.id));
+        enhanced.push(format!("rate_multiplier_{}", partner.rate_multiplier));
         
         enhanced
     }
@@ -459,6 +788,22 @@ impl TokenIssuerAdapter {
                     });
                 }
                 
+                // Session-bound tokens require a live session; validation counts as activity
+                if let Some(sid) = &claims.sid {
+                    let session = match &self.sessions {
+                        Some(sessions) => sessions.touch(sid).await,
+                        None => Err(crate::shared::error::AppError::Authentication("Session tokens are not enabled".to_string())),
+                    };
+                    if let Err(e) = session {
+                        return Ok(TokenValidationResponse {
+                            valid: false,
+                            user_id: None,
+                            permissions: None,
+                            error: Some(e.to_string()),
+                        });
+                    }
+                }
+                
                 // Optional: Validate client IP if provided
                 if let (Some(token_ip), Some(request_ip)) = (&claims.client_ip, &request.client_ip) {
                     if token_ip != request_ip {
@@ -499,6 +844,9 @@ impl TokenIssuerAdapter {
         // if request.user_id.is_empty() {
         //     return Err(crate::shared::error::AppError::Validation("User ID cannot be empty".to_string()));
         // }
+        if RESERVED_USER_ID_PREFIXES.iter().any(|prefix| request.user_id.starts_with(prefix)) {
+            return Err(crate::shared::error::AppError::Validation(format!("User ID {} is reserved", request.user_id)));
+        }
         
         // Validate permissions
         if request.permissions.is_empty() {
@@ -507,7 +855,7 @@ impl TokenIssuerAdapter {
         
         // Validate custom expiration
         if let Some(exp) = request.custom_expiration {
-            if exp < 60 || exp > 86400 {
+            if !(60..=86400).contains(&exp) {
                 return Err(crate::shared::error::AppError::Validation("Custom expiration must be between 60 and 86400 seconds".to_string()));
             }
         }
@@ -517,11 +865,7 @@ impl TokenIssuerAdapter {
 
     /// Extract token from authorization header
     pub fn extract_token_from_header(&self, auth_header: &str) -> Option<String> {
-        if auth_header.starts_with("Bearer ") {
-            Some(auth_header[7..].to_string())
-        } else {
-            None
-        }
+        auth_header.strip_prefix("Bearer ").map(|token| token.to_string())
     }
 }
 
@@ -534,12 +878,15 @@ impl Default for TokenIssuerAdapter {
 /// PoW Manager for challenge generation and validation
 pub struct PowManager {
     config: Arc<AppConfig>,
+    /// Proofs are hashed here, off the IO workers
+    cpu_pool: Arc<CpuPool>,
 }
 
 impl PowManager {
-    /// Create a new PoW manager
+    /// Create a new PoW manager with a CPU pool of its own
     pub fn new(config: Arc<AppConfig>) -> Self {
-        Self { config }
+        let cpu_pool = Arc::new(CpuPool::new(&config.server));
+        Self { config, cpu_pool }
     }
 
     /// Generate new PoW challenge
@@ -599,15 +946,19 @@ impl PowManager {
             return Ok(false);
         }
         
-        // Hash the challenge + nonce
+        // Hash the challenge + nonce on the CPU pool; anyone may submit proofs, so hashing stays bounded
         let input = format!("{}{}", challenge.challenge, proof.nonce);
-        let hash = match challenge.algorithm {
-            PowAlgorithm::Sha256 => self.hash_sha256(&input),
-            PowAlgorithm::Blake3 => self.hash_blake3(&input),
-        };
+        let algorithm = challenge.algorithm.clone();
+        let hash = self
+            .cpu_pool
+            .run(move || match algorithm {
+                PowAlgorithm::Sha256 => sha256_hex(&input),
+                PowAlgorithm::Blake3 => blake3_hex(&input),
+            })
+            .await?;
         
         // Verify the solution hash matches
-        if hash != proof.solution {
+        if !constant_time_str_eq(&hash, &proof.solution) {
             warn!("PoW solution hash mismatch for challenge: {}", challenge.id);
             return Ok(false);
         }
@@ -646,18 +997,27 @@ impl PowManager {
     
     /// Hash input using SHA256
     pub fn hash_sha256(&self, input: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(input.as_bytes());
-        format!("{:x}", hasher.finalize())
+        sha256_hex(input)
     }
     
     /// Hash input using Blake3
+    #[cfg(test)]
     fn hash_blake3(&self, input: &str) -> String {
-        let hash = Hasher::new().update(input.as_bytes()).finalize();
-        hex::encode(hash.as_bytes())
+        blake3_hex(input)
     }
 }
 
+fn sha256_hex(input: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(input.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn blake3_hex(input: &str) -> String {
+    let hash = Hasher::new().update(input.as_bytes()).finalize();
+    hex::encode(hash.as_bytes())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -685,6 +1045,72 @@ mod tests {
         assert!(response.user_id.is_some());
     }
 
+    #[tokio::test]
+    async fn test_reserved_permissions_are_not_issued_on_request() {
+        let issuer = TokenIssuerAdapter::new(Arc::new(AppConfig::default()));
+        let request = TokenIssuanceRequest {
+            user_id: String::new(),
+            permissions: ["read", "admin", "rate_multiplier_100", "stake_validated", "paid", "provisional", "zero_conf"]
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            client_ip: None,
+            user_agent: None,
+            custom_expiration: None,
+            mode: TokenIssuanceMode::Anonymous,
+            pow_challenge: None,
+        };
+        let response = issuer.issue_token(request.clone()).await.unwrap();
+        let validation = issuer
+            .validate_token(TokenValidationRequest { token: response.token, client_ip: None })
+            .await
+            .unwrap();
+        assert_eq!(validation.permissions, Some(vec!["read".to_string()]));
+
+        let only_reserved = TokenIssuanceRequest { permissions: vec!["admin".to_string()], ..request.clone() };
+        assert!(issuer.issue_token(only_reserved).await.is_err());
+
+        // Subjects the issuer assigns itself cannot be requested
+        for user_id in ["pay_b2c8e1d9", "anon_user_1234"] {
+            let reserved_subject = TokenIssuanceRequest { user_id: user_id.to_string(), ..request.clone() };
+            assert!(issuer.issue_token(reserved_subject).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requested_scopes_and_exemptions_are_dropped() {
+        let mut config = AppConfig::default();
+        config.rate_limit.exemptions.permissions = vec!["monitoring".to_string()];
+        config.client_profiles.profiles = vec![crate::config::app_config::ClientProfileConfig {
+            id: "enterprise".to_string(),
+            permissions: vec!["enterprise".to_string()],
+            api_key_hashes: vec![],
+            requests_per_minute: None,
+            allowed_methods: None,
+            max_request_size: None,
+            hidden_fields: vec![],
+        }];
+        let issuer = TokenIssuerAdapter::new(Arc::new(config));
+        let request = TokenIssuanceRequest {
+            user_id: String::new(),
+            permissions: ["read", "admin", "write:*", "method:sendrawtransaction", "debug", "monitoring", "enterprise"]
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            client_ip: None,
+            user_agent: None,
+            custom_expiration: None,
+            mode: TokenIssuanceMode::Anonymous,
+            pow_challenge: None,
+        };
+        let response = issuer.issue_token(request).await.unwrap();
+        let validation = issuer
+            .validate_token(TokenValidationRequest { token: response.token, client_ip: None })
+            .await
+            .unwrap();
+        assert_eq!(validation.permissions, Some(vec!["read".to_string()]));
+    }
+
     #[tokio::test]
     async fn test_token_validation() {
         let config = Arc::new(AppConfig::default());
@@ -767,6 +1193,35 @@ mod tests {
         assert_eq!(validation_response.permissions, Some(vec!["read".to_string()]));
     }
 
+    #[tokio::test]
+    async fn test_session_token_sliding_and_logout() {
+        let mut app_config = AppConfig::default();
+        app_config.session.enabled = true;
+        let config = Arc::new(app_config);
+        let sessions = Arc::new(SessionStore::new(None, &config.session));
+        let issuer = TokenIssuerAdapter::new(config.clone()).with_session_store(sessions);
+
+        let issuance_request = TokenIssuanceRequest {
+            user_id: "dashboard".to_string(),
+            permissions: vec!["read".to_string()],
+            client_ip: None,
+            user_agent: None,
+            custom_expiration: None,
+            mode: TokenIssuanceMode::Anonymous,
+            pow_challenge: None,
+        };
+        let response = issuer.issue_session_token(issuance_request).await.unwrap();
+        assert!(response.session_id.is_some());
+        assert_eq!(response.expires_in, config.session.max_lifetime_seconds);
+
+        let validate = |token: String| TokenValidationRequest { token, client_ip: None };
+        assert!(issuer.validate_token(validate(response.token.clone())).await.unwrap().valid);
+
+        assert!(issuer.end_session(&response.token).await.unwrap());
+        let after_logout = issuer.validate_token(validate(response.token)).await.unwrap();
+        assert!(!after_logout.valid);
+    }
+
     #[tokio::test]
     async fn test_pow_challenge_generation() {
         let config = Arc::new(AppConfig::default());
@@ -852,17 +1307,24 @@ mod tests {
     
     #[tokio::test]
     async fn test_partner_token_issuance() {
-        let config = Arc::new(AppConfig::default());
-        let issuer = TokenIssuerAdapter::new(config);
-        
-        // Create partner token request
+        let (key, partner) = crate::infrastructure::adapters::partners::test_support::partner("test_partner", 11);
+        let mut config = AppConfig::default();
+        config.partners.enabled = true;
+        config.partners.partners = vec![partner];
+        let issuer = TokenIssuerAdapter::new(Arc::new(config));
+        
+        // Create partner token request signed with the partner key
+        let permissions = vec!["read".to_string()];
+        let signed = crate::infrastructure::adapters::partners::test_support::sign(
+            &key, "test_partner", "nonce-1", "partner_user", &permissions,
+        );
         let issuance_request = TokenIssuanceRequest {
             user_id: "partner_user".to_string(),
-            permissions: vec!["read".to_string()],
+            permissions,
             client_ip: Some("127.0.0.1".to_string()),
             user_agent: Some("PartnerApp/1.0".to_string()),
             custom_expiration: None,
-            mode: TokenIssuanceMode::Partner("test_partner".to_string()),
+            mode: TokenIssuanceMode::Partner(signed),
             pow_challenge: None,
         };
         
@@ -871,7 +1333,67 @@ mod tests {
         // Verify partner token has enhanced permissions
         assert!(issuance_response.user_id.is_some());
         assert_eq!(issuance_response.token_type, "Bearer");
-        assert_eq!(issuance_response.expires_in, 3600 * 24); // 24 hours for partners
+        assert_eq!(issuance_response.expires_in, 3600 * 24); // configured partner TTL
+
+        let validation = issuer.validate_token(TokenValidationRequest {
+            token: issuance_response.token,
+            client_ip: None,
+        }).await.unwrap();
+        let granted = validation.permissions.unwrap();
+        assert!(granted.contains(&"partner_test_partner".to_string()));
+        assert!(granted.contains(&"rate_multiplier_3".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_stake_proof_requires_issued_challenge() {
+        let mut config = AppConfig::default();
+        config.stake_proof.enabled = true;
+        let issuer = TokenIssuerAdapter::new(Arc::new(config));
+
+        let challenge = issuer.issue_stake_challenge("RStakeAddress").await.unwrap();
+        assert!(challenge.message.contains(&challenge.nonce));
+
+        let request = TokenIssuanceRequest {
+            user_id: String::new(),
+            permissions: vec!["read".to_string()],
+            client_ip: None,
+            user_agent: None,
+            custom_expiration: None,
+            mode: TokenIssuanceMode::StakeProof(StakeProof {
+                address: "RStakeAddress".to_string(),
+                nonce: "not-issued".to_string(),
+                signature: "c2ln".to_string(),
+            }),
+            pow_challenge: None,
+        };
+        assert!(issuer.issue_token(request).await.is_err());
+        assert!(TokenIssuerAdapter::default().issue_stake_challenge("RStakeAddress").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_partner_token_rejects_unsigned_request() {
+        let (_, partner) = crate::infrastructure::adapters::partners::test_support::partner("test_partner", 12);
+        let mut config = AppConfig::default();
+        config.partners.enabled = true;
+        config.partners.partners = vec![partner];
+        let issuer = TokenIssuerAdapter::new(Arc::new(config));
+
+        let issuance_request = TokenIssuanceRequest {
+            user_id: "partner_user".to_string(),
+            permissions: vec!["read".to_string()],
+            client_ip: None,
+            user_agent: None,
+            custom_expiration: None,
+            mode: TokenIssuanceMode::Partner(PartnerTokenRequest {
+                partner_id: "test_partner".to_string(),
+                timestamp: Utc::now().timestamp(),
+                nonce: "nonce-1".to_string(),
+                signature: "00".repeat(64),
+            }),
+            pow_challenge: None,
+        };
+
+        assert!(issuer.issue_token(issuance_request).await.is_err());
     }
 
     #[tokio::test]
@@ -1035,7 +1557,7 @@ mod tests {
         let hash = issuer.pow_manager.hash_blake3(input);
         
         // Verify it's a valid hex string
-        assert!(hash.len() > 0);
+        assert!(!hash.is_empty());
         assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
         
         // Verify it's different from SHA256
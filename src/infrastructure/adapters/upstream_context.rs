@@ -0,0 +1,140 @@
+//! Proxy metadata attached to daemon calls
+//!
+//! With `[upstream_context]` enabled, every upstream call carries the proxy
+//! request id and an anonymized client id, either in a header or folded
+//! into the JSON-RPC `id`, so daemon-side logs (or a logging reverse proxy in
+//! front of the daemon) can be matched with the proxy's audit logs. The
+//! client id is the first 16 hex digits of SHA-256 over the configured salt
+//! and the client IP; responders holding the salt can compute it for a
+//! suspect IP, while the daemon logs never contain the IP itself.
+//!
+//! Responses to single calls are returned with the client's own id, so
+//! rewriting the upstream id is invisible to clients. Batch entries keep
+//! their position as the last id segment for matching.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::config::app_config::{UpstreamContextConfig, UpstreamContextMode};
+use crate::domain::rpc::ClientInfo;
+
+/// Stand-in when a call has no proxy request id (internal calls)
+const NO_REQUEST_ID: &str = "-";
+
+/// Builds the metadata for upstream calls
+#[derive(Debug, Clone)]
+pub struct UpstreamContext {
+    config: UpstreamContextConfig,
+}
+
+impl UpstreamContext {
+    pub fn new(config: &UpstreamContextConfig) -> Self {
+        Self { config: config.clone() }
+    }
+
+    /// Whether calls carry the context in a header
+    pub fn is_header_mode(&self) -> bool {
+        self.config.mode == UpstreamContextMode::Header
+    }
+
+    /// Header name and value to add, in header mode
+    pub fn header(&self, client: &ClientInfo) -> Option<(&str, String)> {
+        (self.config.mode == UpstreamContextMode::Header).then(|| {
+            let value = format!("request_id={}; client={}", request_id(client), self.client_id(client));
+            (self.config.header.as_str(), value)
+        })
+    }
+
+    /// JSON-RPC id for a single call
+    pub fn call_id(&self, client: &ClientInfo, id: &Option<Value>) -> Option<Value> {
+        if self.config.mode != UpstreamContextMode::Id {
+            return id.clone();
+        }
+        let original = match id {
+            Some(Value::String(id)) => id.clone(),
+            Some(Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        };
+        Some(Value::String(self.namespaced(client, &original)))
+    }
+
+    /// JSON-RPC id for the batch entry at `index`
+    pub fn batch_id(&self, client: &ClientInfo, index: usize) -> Value {
+        match self.config.mode {
+            UpstreamContextMode::Id => Value::String(self.namespaced(client, &index.to_string())),
+            _ => Value::from(index),
+        }
+    }
+
+    /// Position of a batch response entry, from an id made by `batch_id`
+    pub fn batch_index(id: &Value) -> Option<usize> {
+        match id {
+            Value::Number(number) => number.as_u64().map(|index| index as usize),
+            Value::String(id) => id.rsplit(':').next()?.parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn namespaced(&self, client: &ClientInfo, suffix: &str) -> String {
+        format!("{}:{}:{}:{}", self.config.id_prefix, request_id(client), self.client_id(client), suffix)
+    }
+
+    /// Anonymized, stable id for the client's IP
+    pub fn client_id(&self, client: &ClientInfo) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.config.client_id_salt.as_bytes());
+        hasher.update(client.ip_address.as_bytes());
+        hex::encode(&hasher.finalize()[..8])
+    }
+}
+
+fn request_id(client: &ClientInfo) -> &str {
+    client.request_id.as_deref().unwrap_or(NO_REQUEST_ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn client() -> ClientInfo {
+        ClientInfo {
+            ip_address: "203.0.113.7".to_string(),
+            user_agent: None,
+            auth_token: None,
+            timestamp: chrono::Utc::now(),
+            request_id: Some("req-1".to_string()),
+        }
+    }
+
+    fn context(mode: UpstreamContextMode) -> UpstreamContext {
+        UpstreamContext::new(&UpstreamContextConfig { mode, client_id_salt: "salt".to_string(), ..Default::default() })
+    }
+
+    #[test]
+    fn test_modes() {
+        let off = context(UpstreamContextMode::Off);
+        assert!(off.header(&client()).is_none());
+        assert_eq!(off.call_id(&client(), &Some(json!(5))), Some(json!(5)));
+        assert_eq!(off.batch_id(&client(), 2), json!(2));
+
+        let header = context(UpstreamContextMode::Header);
+        let (name, value) = header.header(&client()).unwrap();
+        assert_eq!(name, "X-Verus-Proxy-Context");
+        assert_eq!(value, format!("request_id=req-1; client={}", header.client_id(&client())));
+        assert!(!value.contains("203.0.113.7"));
+
+        let id = context(UpstreamContextMode::Id);
+        let client_id = id.client_id(&client());
+        assert_eq!(client_id.len(), 16);
+        assert_eq!(id.call_id(&client(), &Some(json!(5))), Some(json!(format!("vrpc:req-1:{}:5", client_id))));
+        assert_eq!(UpstreamContext::batch_index(&id.batch_id(&client(), 3)), Some(3));
+        assert_eq!(UpstreamContext::batch_index(&json!(4)), Some(4));
+    }
+
+    #[test]
+    fn test_client_id_depends_on_salt() {
+        let other = UpstreamContext::new(&UpstreamContextConfig { client_id_salt: "other".to_string(), ..Default::default() });
+        assert_ne!(context(UpstreamContextMode::Id).client_id(&client()), other.client_id(&client()));
+    }
+}
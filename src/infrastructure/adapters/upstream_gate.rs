@@ -0,0 +1,261 @@
+//! Last policy check before a call leaves for the daemon
+//!
+//! Requests are validated long before they reach the adapter (domain,
+//! comprehensive and security validators), but every upstream call, from
+//! clients and from the proxy's own services alike, ends in
+//! `ExternalRpcAdapter::send_request`. The gate there re-checks the method
+//! against the method registry, so a bug that lets a request skip validation
+//! still cannot reach an unknown or disabled method. It also writes an `audit`
+//! record for every write-class call and enforces the emergency read-only
+//! switch toggled through `POST /admin/read-only` (or started on by
+//! `[maintenance] read_only`); in cluster mode that switch lives in Redis and
+//! applies to every replica. `RpcService` consults the same switch before any
+//! other work so client writes are refused with the maintenance error up front.
+
+use crate::{
+    config::app_config::MaintenanceConfig,
+    domain::{rpc::RpcRequest, validation::MethodRegistry},
+    infrastructure::adapters::{upstream_metrics::hash_params, ClusterCoordinator},
+    shared::error::{AppError, AppResult},
+};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use tracing::{error, info, warn};
+
+/// Cluster flag holding the read-only switch
+pub const READ_ONLY_FLAG: &str = "read_only";
+
+/// Methods the proxy calls itself that are not exposed to clients (payment addresses and refunds)
+const INTERNAL_METHODS: &[&str] = &["getnewaddress", "z_listreceivedbyaddress", "z_getoperationstatus"];
+
+/// Policy gate in front of the daemon
+#[derive(Default)]
+pub struct UpstreamGate {
+    /// Holds the read-only switch for every replica when `[cluster]` is enabled
+    cluster: Option<Arc<ClusterCoordinator>>,
+    read_only: AtomicBool,
+    audited_writes: AtomicU64,
+    blocked_policy: AtomicU64,
+    blocked_read_only: AtomicU64,
+    /// Maintenance error text; empty until `configure` runs
+    maintenance_message: RwLock<String>,
+}
+
+/// Gate counters
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamGateMetrics {
+    pub read_only: bool,
+    /// Write-class calls let through (each has an audit record)
+    pub audited_writes: u64,
+    /// Calls to methods outside the registry policy
+    pub blocked_policy: u64,
+    /// Write-class calls refused in read-only mode
+    pub blocked_read_only: u64,
+}
+
+impl UpstreamGate {
+    /// Keep the read-only switch in `cluster`, so it applies to every replica
+    pub fn with_cluster(mut self, cluster: Option<Arc<ClusterCoordinator>>) -> Self {
+        self.cluster = cluster;
+        self
+    }
+
+    /// Apply `[maintenance]` at startup
+    pub async fn configure(&self, config: &MaintenanceConfig) -> AppResult<()> {
+        *self.maintenance_message.write().unwrap_or_else(|e| e.into_inner()) = config.message.clone();
+        if config.read_only {
+            self.set_read_only(true, Some("[maintenance] read_only")).await?;
+        }
+        Ok(())
+    }
+
+    /// Allow `request` to go upstream, or refuse it
+    pub async fn check(&self, request: &RpcRequest) -> AppResult<()> {
+        let definition = registry().get_method(&request.method);
+        let allowed = match definition {
+            Some(definition) => definition.enabled,
+            None => INTERNAL_METHODS.contains(&request.method.as_str()),
+        };
+        if !allowed {
+            self.blocked_policy.fetch_add(1, Ordering::Relaxed);
+            error!(
+                target: "audit",
+                event = "upstream_blocked",
+                method = %request.method,
+                client_ip = %request.client_info.ip_address,
+                "Upstream call outside the method policy blocked"
+            );
+            return Err(AppError::MethodNotAllowed { method: request.method.clone() });
+        }
+        if definition.is_some_and(|definition| definition.read_only) {
+            return Ok(());
+        }
+        self.refuse_in_maintenance(request).await?;
+        self.audited_writes.fetch_add(1, Ordering::Relaxed);
+        info!(
+            target: "audit",
+            event = "upstream_write",
+            method = %request.method,
+            client_ip = %request.client_info.ip_address,
+            user_agent = request.client_info.user_agent.as_deref().unwrap_or(""),
+            authenticated = request.client_info.auth_token.is_some(),
+            request_id = %request.id.as_ref().map(|id| id.to_string()).unwrap_or_default(),
+            params_hash = %hash_params(request.parameters.as_ref()),
+            "Write-class upstream call"
+        );
+        Ok(())
+    }
+
+    /// Refuse a client request for a write-class method while in maintenance mode
+    pub async fn ensure_writable(&self, request: &RpcRequest) -> AppResult<()> {
+        if registry().get_method(&request.method).is_some_and(|definition| definition.read_only) {
+            return Ok(());
+        }
+        self.refuse_in_maintenance(request).await
+    }
+
+    async fn refuse_in_maintenance(&self, request: &RpcRequest) -> AppResult<()> {
+        if !self.is_read_only().await {
+            return Ok(());
+        }
+        self.blocked_read_only.fetch_add(1, Ordering::Relaxed);
+        warn!(
+            target: "audit",
+            event = "upstream_write_refused",
+            method = %request.method,
+            client_ip = %request.client_info.ip_address,
+            "Write-class call refused in read-only mode"
+        );
+        let message = self.maintenance_message.read().unwrap_or_else(|e| e.into_inner()).clone();
+        Err(AppError::Maintenance(if message.is_empty() {
+            MaintenanceConfig::default().message
+        } else {
+            message
+        }))
+    }
+
+    /// Whether write-class calls are currently refused
+    pub async fn is_read_only(&self) -> bool {
+        if let Some(cluster) = &self.cluster {
+            match cluster.flag(READ_ONLY_FLAG).await {
+                Ok(on) => {
+                    self.read_only.store(on, Ordering::Relaxed);
+                    return on;
+                }
+                Err(e) => warn!("Cluster read-only flag unavailable, using last known value: {}", e),
+            }
+        }
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    /// Turn read-only mode on or off (for every replica in cluster mode)
+    pub async fn set_read_only(&self, on: bool, reason: Option<&str>) -> AppResult<()> {
+        if let Some(cluster) = &self.cluster {
+            cluster.set_flag(READ_ONLY_FLAG, on).await?;
+        }
+        self.read_only.store(on, Ordering::Relaxed);
+        warn!(target: "audit", event = "read_only_mode", enabled = on, reason = reason.unwrap_or(""), "Upstream read-only mode changed");
+        Ok(())
+    }
+
+    /// Current counters (read-only state as last seen)
+    pub fn metrics(&self) -> UpstreamGateMetrics {
+        UpstreamGateMetrics {
+            read_only: self.read_only.load(Ordering::Relaxed),
+            audited_writes: self.audited_writes.load(Ordering::Relaxed),
+            blocked_policy: self.blocked_policy.load(Ordering::Relaxed),
+            blocked_read_only: self.blocked_read_only.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Render gate metrics in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let m = self.metrics();
+        let mut out = String::new();
+        out.push_str("# HELP verus_upstream_read_only Whether write-class upstream calls are refused\n");
+        out.push_str("# TYPE verus_upstream_read_only gauge\n");
+        out.push_str(&format!("verus_upstream_read_only {}\n", u8::from(m.read_only)));
+        out.push_str("# HELP verus_upstream_audited_writes_total Write-class upstream calls let through\n");
+        out.push_str("# TYPE verus_upstream_audited_writes_total counter\n");
+        out.push_str(&format!("verus_upstream_audited_writes_total {}\n", m.audited_writes));
+        out.push_str("# HELP verus_upstream_blocked_total Upstream calls refused by the gate\n");
+        out.push_str("# TYPE verus_upstream_blocked_total counter\n");
+        out.push_str(&format!("verus_upstream_blocked_total{{reason=\"policy\"}} {}\n", m.blocked_policy));
+        out.push_str(&format!("verus_upstream_blocked_total{{reason=\"read_only\"}} {}\n", m.blocked_read_only));
+        out
+    }
+}
+
+fn registry() -> &'static MethodRegistry {
+    static REGISTRY: OnceLock<MethodRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(MethodRegistry::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::rpc::ClientInfo;
+
+    fn request(method: &str) -> RpcRequest {
+        RpcRequest {
+            method: method.to_string(),
+            parameters: Some(serde_json::json!([])),
+            id: Some(serde_json::json!(1)),
+            client_info: ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: None,
+                auth_token: None,
+                timestamp: chrono::Utc::now(),
+                request_id: None,
+            },
+            upstream: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gate_enforces_method_policy() {
+        let gate = UpstreamGate::default();
+        assert!(matches!(gate.check(&request("stop")).await, Err(AppError::MethodNotAllowed { .. })));
+        assert!(gate.check(&request("dumpprivkey")).await.is_err());
+        // Every method the proxy's own services send must pass
+        for method in [
+            "getinfo", "getblockcount", "getblockhash", "getblock", "getrawtransaction", "gettxout",
+            "getrawmempool", "getaddressbalance", "getcurrencystate", "decoderawtransaction",
+            "sendrawtransaction", "getnewaddress", "z_getnewaddress", "z_listaddresses",
+            "z_validateaddress", "z_viewtransaction", "z_listreceivedbyaddress", "z_sendmany", "z_getoperationstatus",
+        ] {
+            assert!(gate.check(&request(method)).await.is_ok(), "{} blocked", method);
+        }
+        let metrics = gate.metrics();
+        assert_eq!(metrics.blocked_policy, 2);
+        assert!(metrics.audited_writes >= 2);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_mode_refuses_writes_only() {
+        let gate = UpstreamGate::default();
+        gate.set_read_only(true, Some("wallet maintenance")).await.unwrap();
+        assert!(matches!(gate.check(&request("sendrawtransaction")).await, Err(AppError::Maintenance(_))));
+        assert!(gate.check(&request("getnewaddress")).await.is_err());
+        gate.check(&request("getblockcount")).await.unwrap();
+        assert!(gate.prometheus_text().contains("verus_upstream_blocked_total{reason=\"read_only\"} 2"));
+
+        gate.set_read_only(false, None).await.unwrap();
+        gate.check(&request("sendrawtransaction")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_config_refuses_client_writes() {
+        let gate = UpstreamGate::default();
+        let config = MaintenanceConfig { read_only: true, message: "Wallet upgrade in progress".to_string() };
+        gate.configure(&config).await.unwrap();
+        assert!(gate.is_read_only().await);
+        match gate.ensure_writable(&request("sendrawtransaction")).await {
+            Err(AppError::Maintenance(message)) => assert_eq!(message, "Wallet upgrade in progress"),
+            other => panic!("expected maintenance error, got {:?}", other),
+        }
+        gate.ensure_writable(&request("getblockcount")).await.unwrap();
+        assert_eq!(gate.metrics().blocked_read_only, 1);
+    }
+}
@@ -0,0 +1,215 @@
+//! Packing concurrent daemon calls into JSON-RPC batches
+//!
+//! With `[verus.pipelining]` enabled, a call made while `min_in_flight`
+//! others are already waiting on the daemon joins a queue instead of opening
+//! its own request. The queue is sent as one JSON-RPC batch over a pooled
+//! keep-alive connection once it holds `max_batch` calls or its first call
+//! has waited `max_delay_ms`, whichever comes first. Each caller receives its
+//! own result or error; a batch the daemon rejects as a whole fails every
+//! call in it. Below the threshold calls go out individually as before, so
+//! quiet periods pay no added latency.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::config::app_config::UpstreamPipeliningConfig;
+use crate::domain::rpc::{RpcRequest, RpcResponse};
+use crate::shared::error::{AppError, AppResult};
+
+static BATCHES: AtomicU64 = AtomicU64::new(0);
+static PACKED_CALLS: AtomicU64 = AtomicU64::new(0);
+
+/// How a call reaches the daemon
+pub enum Admission {
+    /// Sent on its own
+    Direct,
+    /// Waits for its batch; `full` holds the batch to send now when this call filled it
+    Queued {
+        reply: oneshot::Receiver<AppResult<Value>>,
+        full: Option<PendingBatch>,
+    },
+}
+
+/// Queued calls and where their results go
+#[derive(Default)]
+pub struct PendingBatch {
+    pub requests: Vec<RpcRequest>,
+    replies: Vec<oneshot::Sender<AppResult<Value>>>,
+}
+
+impl PendingBatch {
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Hand each caller its result, in request order; a failed batch fails every call
+    pub fn resolve(self, results: AppResult<Vec<AppResult<Value>>>) {
+        if self.len() > 1 {
+            BATCHES.fetch_add(1, Ordering::Relaxed);
+            PACKED_CALLS.fetch_add(self.len() as u64, Ordering::Relaxed);
+        }
+        match results {
+            Ok(results) => {
+                for (reply, result) in self.replies.into_iter().zip(results) {
+                    let _ = reply.send(result);
+                }
+            }
+            Err(e) => {
+                for reply in self.replies {
+                    let _ = reply.send(Err(e.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Call counted as in flight to the daemon until dropped
+pub struct InFlightCall {
+    counter: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightCall {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Per-daemon queue of calls waiting to be batched
+pub struct UpstreamPipeline {
+    config: UpstreamPipeliningConfig,
+    in_flight: Arc<AtomicUsize>,
+    queue: Mutex<PendingBatch>,
+}
+
+impl UpstreamPipeline {
+    /// Pipeline for `[verus.pipelining]`; `None` when disabled
+    pub fn new(config: &UpstreamPipeliningConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            config: config.clone(),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            queue: Mutex::new(PendingBatch::default()),
+        })
+    }
+
+    /// Longest a queued call waits for its batch to fill
+    pub fn max_delay(&self) -> Duration {
+        Duration::from_millis(self.config.max_delay_ms)
+    }
+
+    /// Decide whether `request` goes out alone or joins the queue
+    pub fn admit(&self, request: &RpcRequest) -> (InFlightCall, Admission) {
+        let busy = self.in_flight.fetch_add(1, Ordering::Relaxed) >= self.config.min_in_flight;
+        let in_flight = InFlightCall { counter: self.in_flight.clone() };
+        if !busy {
+            return (in_flight, Admission::Direct);
+        }
+        let (sender, reply) = oneshot::channel();
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        queue.requests.push(request.clone());
+        queue.replies.push(sender);
+        let full = (queue.len() >= self.config.max_batch).then(|| std::mem::take(&mut *queue));
+        (in_flight, Admission::Queued { reply, full })
+    }
+
+    /// Calls queued so far, leaving the queue empty
+    pub fn take(&self) -> PendingBatch {
+        std::mem::take(&mut *self.queue.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Response for a queued call from what its batch delivered
+pub fn queued_response(
+    delivered: Result<AppResult<Value>, oneshot::error::RecvError>,
+    request: &RpcRequest,
+) -> AppResult<RpcResponse> {
+    match delivered {
+        Ok(result) => result.map(|value| RpcResponse::success(value, request.id.clone())),
+        Err(_) => Err(AppError::Rpc("Batched daemon call was abandoned".to_string())),
+    }
+}
+
+/// Render batching counters in Prometheus text format
+pub fn prometheus_text() -> String {
+    let mut out = String::new();
+    out.push_str("# HELP verus_upstream_pipelined_batches_total Batches the daemon received in place of concurrent calls\n");
+    out.push_str("# TYPE verus_upstream_pipelined_batches_total counter\n");
+    out.push_str(&format!("verus_upstream_pipelined_batches_total {}\n", BATCHES.load(Ordering::Relaxed)));
+    out.push_str("# HELP verus_upstream_pipelined_calls_total Calls sent to the daemon inside those batches\n");
+    out.push_str("# TYPE verus_upstream_pipelined_calls_total counter\n");
+    out.push_str(&format!("verus_upstream_pipelined_calls_total {}\n", PACKED_CALLS.load(Ordering::Relaxed)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::rpc::ClientInfo;
+
+    fn request(method: &str) -> RpcRequest {
+        let client_info = ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: None,
+            auth_token: None,
+            timestamp: chrono::Utc::now(),
+            request_id: None,
+        };
+        RpcRequest::new(method.to_string(), None, Some(Value::from(1)), client_info)
+    }
+
+    fn pipeline() -> UpstreamPipeline {
+        UpstreamPipeline::new(&UpstreamPipeliningConfig { enabled: true, min_in_flight: 1, max_batch: 3, max_delay_ms: 2 })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_calls_are_queued_only_while_the_daemon_is_busy() {
+        assert!(UpstreamPipeline::new(&UpstreamPipeliningConfig::default()).is_none());
+        let pipeline = pipeline();
+
+        let (first, admission) = pipeline.admit(&request("getinfo"));
+        assert!(matches!(admission, Admission::Direct));
+        let (_second, admission) = pipeline.admit(&request("getblockcount"));
+        assert!(matches!(admission, Admission::Queued { full: None, .. }));
+        let (_third, _) = pipeline.admit(&request("getbestblockhash"));
+        let (_fourth, admission) = pipeline.admit(&request("getdifficulty"));
+        match admission {
+            Admission::Queued { full: Some(batch), .. } => assert_eq!(batch.len(), 3),
+            _ => panic!("a full batch is handed to the call that filled it"),
+        }
+        assert!(pipeline.take().is_empty());
+
+        drop(first);
+        assert_eq!(pipeline.in_flight.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_batch_results_reach_their_callers() {
+        let pipeline = pipeline();
+        let _busy = pipeline.admit(&request("getinfo"));
+        let requests = [request("getblockcount"), request("getrawtransaction")];
+        let replies: Vec<_> = requests
+            .iter()
+            .map(|request| match pipeline.admit(request) {
+                (in_flight, Admission::Queued { reply, .. }) => (in_flight, reply),
+                _ => panic!("daemon is busy"),
+            })
+            .collect();
+
+        pipeline.take().resolve(Ok(vec![Ok(Value::from(42)), Err(AppError::Rpc("RPC error: not found".into()))]));
+        let mut results = Vec::new();
+        for ((_in_flight, reply), request) in replies.into_iter().zip(&requests) {
+            results.push(queued_response(reply.await, request));
+        }
+        assert_eq!(results[0].as_ref().unwrap().result, Some(Value::from(42)));
+        assert!(matches!(results[1], Err(AppError::Rpc(_))));
+        assert!(prometheus_text().contains("verus_upstream_pipelined_batches_total"));
+    }
+}
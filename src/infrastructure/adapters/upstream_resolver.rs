@@ -0,0 +1,162 @@
+//! Upstream daemon address resolution
+//!
+//! Resolves the daemon host name (IPv4 and IPv6) and re-resolves it at a
+//! configurable interval, so a daemon behind a dynamic hostname is followed
+//! when its address changes. Addresses are ordered by the preferred family;
+//! the HTTP client races the remaining addresses (happy eyeballs) on connect.
+
+use crate::{
+    config::app_config::{AddressFamily, VerusConfig},
+    shared::error::{AppError, AppResult},
+};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::info;
+
+/// Resolved addresses for the upstream host
+#[derive(Debug, Clone)]
+struct ResolvedUpstream {
+    addrs: Vec<SocketAddr>,
+    resolved_at: Instant,
+}
+
+/// Result of a resolution attempt
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    /// Host name the addresses belong to
+    pub host: String,
+    /// Ordered socket addresses
+    pub addrs: Vec<SocketAddr>,
+    /// Whether the addresses differ from the previous resolution
+    pub changed: bool,
+}
+
+/// Periodically refreshed resolver for the daemon host
+#[derive(Debug)]
+pub struct UpstreamResolver {
+    host: Option<String>,
+    port: u16,
+    preferred_family: AddressFamily,
+    refresh_interval: Duration,
+    state: RwLock<Option<ResolvedUpstream>>,
+}
+
+impl UpstreamResolver {
+    /// Create a resolver for the configured daemon URL
+    pub fn new(config: &VerusConfig) -> Self {
+        let url = reqwest::Url::parse(&config.rpc_url).ok();
+        let host = url
+            .as_ref()
+            .and_then(|u| u.host_str())
+            .map(|h| h.trim_start_matches('[').trim_end_matches(']').to_string())
+            // IP literals need no resolution
+            .filter(|h| h.parse::<IpAddr>().is_err());
+        let port = url.as_ref().and_then(|u| u.port_or_known_default()).unwrap_or(80);
+
+        Self {
+            host,
+            port,
+            preferred_family: config.dns.preferred_family,
+            refresh_interval: Duration::from_secs(config.dns.refresh_interval_seconds),
+            state: RwLock::new(None),
+        }
+    }
+
+    /// Whether the upstream uses a host name that needs resolution
+    pub fn is_enabled(&self) -> bool {
+        self.host.is_some() && !self.refresh_interval.is_zero()
+    }
+
+    /// Resolve the host if the cached addresses are stale (or `force` is set)
+    ///
+    /// Returns `None` when resolution is disabled or the cache is still fresh.
+    pub async fn resolve_if_stale(&self, force: bool) -> AppResult<Option<Resolution>> {
+        let host = match (&self.host, self.is_enabled()) {
+            (Some(host), true) => host.clone(),
+            _ => return Ok(None),
+        };
+
+        if !force {
+            if let Some(state) = self.state.read().await.as_ref() {
+                if state.resolved_at.elapsed() < self.refresh_interval {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), self.port))
+            .await
+            .map_err(|e| AppError::Rpc(format!("Failed to resolve upstream host {}: {}", host, e)))?
+            .collect();
+        let addrs = order_by_family(addrs, self.preferred_family);
+        if addrs.is_empty() {
+            return Err(AppError::Rpc(format!("Upstream host {} resolved to no addresses", host)));
+        }
+
+        let mut state = self.state.write().await;
+        let changed = state.as_ref().is_none_or(|s| s.addrs != addrs);
+        if changed {
+            info!(host = %host, addrs = ?addrs, "Upstream address resolution changed");
+        }
+        *state = Some(ResolvedUpstream { addrs: addrs.clone(), resolved_at: Instant::now() });
+
+        Ok(Some(Resolution { host, addrs, changed }))
+    }
+}
+
+/// Order addresses so the preferred family comes first (stable within family)
+fn order_by_family(mut addrs: Vec<SocketAddr>, preferred: AddressFamily) -> Vec<SocketAddr> {
+    addrs.dedup();
+    match preferred {
+        AddressFamily::Any => addrs,
+        AddressFamily::Ipv4 => {
+            addrs.sort_by_key(|a| !a.is_ipv4());
+            addrs
+        }
+        AddressFamily::Ipv6 => {
+            addrs.sort_by_key(|a| !a.is_ipv6());
+            addrs
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    #[test]
+    fn test_ip_literals_skip_resolution() {
+        let mut config = AppConfig::default();
+        config.verus.rpc_url = "http://[::1]:27486".to_string();
+        assert!(!UpstreamResolver::new(&config.verus).is_enabled());
+
+        config.verus.rpc_url = "http://verusd.internal:27486".to_string();
+        let resolver = UpstreamResolver::new(&config.verus);
+        assert!(resolver.is_enabled());
+        assert_eq!(resolver.port, 27486);
+    }
+
+    #[test]
+    fn test_order_by_family() {
+        let v4: SocketAddr = "10.0.0.1:27486".parse().unwrap();
+        let v6: SocketAddr = "[fd00::1]:27486".parse().unwrap();
+        assert_eq!(order_by_family(vec![v4, v6], AddressFamily::Ipv6), vec![v6, v4]);
+        assert_eq!(order_by_family(vec![v6, v4], AddressFamily::Ipv4), vec![v4, v6]);
+        assert_eq!(order_by_family(vec![v6, v4], AddressFamily::Any), vec![v6, v4]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_localhost_is_cached() {
+        let mut config = AppConfig::default();
+        config.verus.rpc_url = "http://localhost:27486".to_string();
+        let resolver = UpstreamResolver::new(&config.verus);
+
+        let first = resolver.resolve_if_stale(false).await.unwrap().unwrap();
+        assert!(first.changed);
+        assert!(resolver.resolve_if_stale(false).await.unwrap().is_none());
+        let forced = resolver.resolve_if_stale(true).await.unwrap().unwrap();
+        assert!(!forced.changed);
+    }
+}
@@ -0,0 +1,237 @@
+//! Parameter validation outcomes remembered by request shape
+//!
+//! Polling clients send the same calls over and over, and each one used to
+//! be re-encoded parameter by parameter before the allowlist checks ran.
+//! With `[validation_cache]` enabled, the outcome is remembered under an
+//! encoding of the method and the parameters' shape, and identical shapes
+//! skip the checks. The shape covers exactly what the checks read: each parameter's
+//! JSON type, whether a number is a signed, unsigned or floating-point value,
+//! the value of booleans and the key names of objects. String contents and
+//! array items never affect the outcome, so `getblock` for any hash is one
+//! entry. Outcomes are filed under a hash of the encoding keyed per process,
+//! and a lookup compares the full encoding, so a hash collision is a miss
+//! rather than another shape's outcome. Outcomes are dropped whenever the served methods change
+//! (discovered or unavailable methods installed at startup), and the cache
+//! starts over once it holds `max_entries` outcomes.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use crate::config::app_config::ValidationCacheConfig;
+use crate::domain::validation::MethodRegistry;
+
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+static INVALIDATIONS: AtomicU64 = AtomicU64::new(0);
+
+struct Outcome {
+    /// Encoded method and parameter shape the outcome was decided for
+    shape: Vec<u8>,
+    allowed: bool,
+}
+
+struct CacheState {
+    /// Registry generation the outcomes were decided under
+    generation: u64,
+    outcomes: HashMap<u64, Outcome>,
+}
+
+/// Validation outcomes by method and parameter shape
+pub struct ValidationCache {
+    config: ValidationCacheConfig,
+    hasher: RandomState,
+    state: Mutex<CacheState>,
+}
+
+impl ValidationCache {
+    pub fn new(config: &ValidationCacheConfig) -> Self {
+        Self {
+            config: config.clone(),
+            hasher: RandomState::new(),
+            state: Mutex::new(CacheState { generation: MethodRegistry::generation(), outcomes: HashMap::new() }),
+        }
+    }
+
+    /// Remembered outcome for `method` called with parameters shaped like `params`
+    pub fn outcome(&self, method: &str, params: &[Value]) -> Option<bool> {
+        if !self.config.enabled {
+            return None;
+        }
+        let shape = encode_shape(method, params);
+        let key = self.key(&shape);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        Self::refresh(&mut state);
+        let allowed = state
+            .outcomes
+            .get(&key)
+            .filter(|outcome| outcome.shape == shape)
+            .map(|outcome| outcome.allowed);
+        match allowed {
+            Some(_) => HITS.fetch_add(1, Ordering::Relaxed),
+            None => MISSES.fetch_add(1, Ordering::Relaxed),
+        };
+        allowed
+    }
+
+    /// Remember the outcome of validating `method` with `params`
+    pub fn remember(&self, method: &str, params: &[Value], allowed: bool) {
+        if !self.config.enabled {
+            return;
+        }
+        let shape = encode_shape(method, params);
+        let key = self.key(&shape);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        Self::refresh(&mut state);
+        if state.outcomes.len() >= self.config.max_entries {
+            state.outcomes.clear();
+        }
+        state.outcomes.insert(key, Outcome { shape, allowed });
+    }
+
+    /// Forget every outcome
+    pub fn clear(&self) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).outcomes.clear();
+    }
+
+    /// Outcomes currently remembered
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).outcomes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop outcomes decided before the served methods last changed
+    fn refresh(state: &mut CacheState) {
+        let generation = MethodRegistry::generation();
+        if state.generation != generation {
+            state.generation = generation;
+            if !state.outcomes.is_empty() {
+                state.outcomes.clear();
+                INVALIDATIONS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn key(&self, shape: &[u8]) -> u64 {
+        let mut hasher = self.hasher.build_hasher();
+        hasher.write(shape);
+        hasher.finish()
+    }
+}
+
+/// Encode `method` and the parts of `params` the validation checks read
+fn encode_shape(method: &str, params: &[Value]) -> Vec<u8> {
+    let mut shape = Vec::with_capacity(method.len() + 16 + params.len() * 2);
+    encode_str(method, &mut shape);
+    encode_len(params.len(), &mut shape);
+    for param in params {
+        encode_value(param, &mut shape);
+    }
+    shape
+}
+
+fn encode_value(value: &Value, shape: &mut Vec<u8>) {
+    match value {
+        Value::Null => shape.push(0),
+        Value::Bool(flag) => shape.extend([1, u8::from(*flag)]),
+        Value::Number(number) => shape.extend([2, if number.is_i64() { 0 } else if number.is_u64() { 1 } else { 2 }]),
+        Value::String(_) => shape.push(3),
+        Value::Array(_) => shape.push(4),
+        Value::Object(object) => {
+            shape.push(5);
+            encode_len(object.len(), shape);
+            object.keys().for_each(|key| encode_str(key, shape));
+        }
+    }
+}
+
+/// Length-prefixed so adjacent strings cannot run together
+fn encode_str(text: &str, shape: &mut Vec<u8>) {
+    encode_len(text.len(), shape);
+    shape.extend_from_slice(text.as_bytes());
+}
+
+fn encode_len(len: usize, shape: &mut Vec<u8>) {
+    shape.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+/// Render cache counters in Prometheus text format
+pub fn prometheus_text() -> String {
+    let mut out = String::new();
+    out.push_str("# HELP verus_validation_cache_hits_total Requests whose validation outcome was reused\n");
+    out.push_str("# TYPE verus_validation_cache_hits_total counter\n");
+    out.push_str(&format!("verus_validation_cache_hits_total {}\n", HITS.load(Ordering::Relaxed)));
+    out.push_str("# HELP verus_validation_cache_misses_total Requests validated in full\n");
+    out.push_str("# TYPE verus_validation_cache_misses_total counter\n");
+    out.push_str(&format!("verus_validation_cache_misses_total {}\n", MISSES.load(Ordering::Relaxed)));
+    out.push_str("# HELP verus_validation_cache_invalidations_total Times remembered outcomes were dropped after the served methods changed\n");
+    out.push_str("# TYPE verus_validation_cache_invalidations_total counter\n");
+    out.push_str(&format!("verus_validation_cache_invalidations_total {}\n", INVALIDATIONS.load(Ordering::Relaxed)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cache(max_entries: usize) -> ValidationCache {
+        ValidationCache::new(&ValidationCacheConfig { enabled: true, max_entries })
+    }
+
+    fn params(value: Value) -> Vec<Value> {
+        value.as_array().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_shapes_that_validate_alike_share_an_outcome() {
+        let cache = cache(16);
+        cache.remember("getblock", &params(json!(["00ab", true])), true);
+        assert_eq!(cache.outcome("getblock", &params(json!(["ffff", true]))), Some(true));
+        assert_eq!(cache.outcome("getblockheader", &params(json!(["ffff", true]))), None);
+
+        // Anything the checks read is part of the shape
+        assert_eq!(cache.outcome("getblock", &params(json!(["ffff", false]))), None);
+        assert_eq!(cache.outcome("getblock", &params(json!(["ffff"]))), None);
+        cache.remember("estimatefee", &params(json!([6])), true);
+        assert_eq!(cache.outcome("estimatefee", &params(json!([6.5]))), None);
+        cache.remember("signdata", &params(json!([{"data": "x"}])), true);
+        assert_eq!(cache.outcome("signdata", &params(json!([{"address": "x"}]))), None);
+        assert!(prometheus_text().contains("verus_validation_cache_hits_total"));
+    }
+
+    #[test]
+    fn test_colliding_keys_do_not_share_an_outcome() {
+        let cache = cache(16);
+        let shape = encode_shape("getblock", &params(json!(["00ab", true])));
+        let other = encode_shape("getblock", &params(json!(["00ab", false])));
+        // File a different shape's outcome under this shape's key, as a hash collision would
+        cache.state.lock().unwrap().outcomes.insert(cache.key(&shape), Outcome { shape: other, allowed: true });
+        assert_eq!(cache.outcome("getblock", &params(json!(["00ab", true]))), None);
+        assert_ne!(encode_shape("ab", &[]), encode_shape("a", &params(json!(["b"]))));
+    }
+
+    #[test]
+    fn test_cache_is_bounded_and_can_be_disabled() {
+        let cache = cache(16);
+        for n in 0..16 {
+            cache.remember("getinfo", &vec![json!("x"); n], false);
+        }
+        assert_eq!(cache.len(), 16);
+        cache.remember("getinfo", &[json!(1)], false);
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+
+        let disabled = ValidationCache::new(&ValidationCacheConfig { enabled: false, max_entries: 16 });
+        disabled.remember("getinfo", &[], true);
+        assert_eq!(disabled.outcome("getinfo", &[]), None);
+    }
+}
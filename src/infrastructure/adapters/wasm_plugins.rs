@@ -0,0 +1,198 @@
+//! Response post-processing with WASM modules (experimental)
+//!
+//! Operators can redact, enrich or reshape the result of chosen methods
+//! without forking the proxy. Each call runs in a fresh instance with no
+//! imports, so a module cannot reach the network, the filesystem or the
+//! clock; fuel bounds its CPU time and a store limiter its memory.
+//!
+//! A module exports `memory`, `alloc(len: i32) -> i32` and
+//! `transform(ptr: i32, len: i32) -> i64`. The proxy writes
+//! `{"method": ..., "params": ..., "result": ...}` as JSON into the buffer
+//! returned by `alloc` and calls `transform`, which returns the location of
+//! the new result JSON packed as `(ptr << 32) | len`, or `0` to leave the
+//! result unchanged.
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
+use wasmtime::{Engine, Instance, Module, ResourceLimiter, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::config::app_config::WasmPluginsConfig;
+use crate::shared::error::{AppError, AppResult};
+
+struct Plugin {
+    name: String,
+    methods: Vec<String>,
+    /// Compile error kept so calls fail (or pass through with `fail_open`) instead of silently skipping the plugin
+    module: Result<Module, String>,
+}
+
+impl Plugin {
+    fn applies_to(&self, method: &str) -> bool {
+        self.methods.iter().any(|m| m == method)
+    }
+}
+
+/// Loaded response plugins
+pub struct WasmPlugins {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+    fuel_per_call: u64,
+    max_memory_bytes: usize,
+    fail_open: bool,
+}
+
+impl WasmPlugins {
+    /// Compile the modules from `[wasm_plugins]`; `None` when disabled or empty
+    pub fn load(config: &WasmPluginsConfig) -> Option<Arc<Self>> {
+        if !config.enabled || config.modules.is_empty() {
+            return None;
+        }
+        let mut engine_config = wasmtime::Config::new();
+        engine_config.consume_fuel(true);
+        let engine = match Engine::new(&engine_config) {
+            Ok(engine) => engine,
+            Err(e) => {
+                error!("Failed to start the WASM engine; response plugins disabled: {}", e);
+                return None;
+            }
+        };
+        let plugins = config
+            .modules
+            .iter()
+            .map(|module| {
+                let compiled = Module::from_file(&engine, &module.path).map_err(|e| e.to_string());
+                match &compiled {
+                    Ok(_) => info!(plugin = %module.name, methods = ?module.methods, "Loaded WASM response plugin"),
+                    Err(e) => error!(plugin = %module.name, path = %module.path, "Failed to load WASM response plugin: {}", e),
+                }
+                Plugin { name: module.name.clone(), methods: module.methods.clone(), module: compiled }
+            })
+            .collect();
+        Some(Arc::new(Self {
+            engine,
+            plugins,
+            fuel_per_call: config.fuel_per_call,
+            max_memory_bytes: config.max_memory_bytes,
+            fail_open: config.fail_open,
+        }))
+    }
+
+    /// Run the plugins registered for `method` over `result`, in configuration order
+    pub async fn transform(self: &Arc<Self>, method: &str, params: Option<&Value>, result: Value) -> AppResult<Value> {
+        if !self.plugins.iter().any(|plugin| plugin.applies_to(method)) {
+            return Ok(result);
+        }
+        let plugins = self.clone();
+        let method = method.to_string();
+        let params = params.cloned();
+        // Module code is synchronous; keep it off the async workers
+        tokio::task::spawn_blocking(move || plugins.run_chain(&method, params.as_ref(), result))
+            .await
+            .map_err(|e| AppError::Internal(format!("Response plugin task failed: {}", e)))?
+    }
+
+    fn run_chain(&self, method: &str, params: Option<&Value>, mut result: Value) -> AppResult<Value> {
+        for plugin in self.plugins.iter().filter(|plugin| plugin.applies_to(method)) {
+            match self.run(plugin, method, params, &result) {
+                Ok(Some(transformed)) => result = transformed,
+                Ok(None) => {}
+                Err(e) if self.fail_open => {
+                    warn!(plugin = %plugin.name, method = %method, "Response plugin failed; returning the response unchanged: {}", e);
+                }
+                Err(e) => {
+                    warn!(plugin = %plugin.name, method = %method, "Response plugin failed: {}", e);
+                    return Err(AppError::Internal(format!("Response plugin {} failed", plugin.name)));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    fn run(&self, plugin: &Plugin, method: &str, params: Option<&Value>, result: &Value) -> Result<Option<Value>, String> {
+        let module = plugin.module.as_ref().map_err(Clone::clone)?;
+        let limits = StoreLimitsBuilder::new().memory_size(self.max_memory_bytes).instances(1).build();
+        let mut store: Store<StoreLimits> = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits as &mut dyn ResourceLimiter);
+        store.set_fuel(self.fuel_per_call).map_err(|e| e.to_string())?;
+
+        // No imports: the module only sees the memory it owns
+        let instance = Instance::new(&mut store, module, &[]).map_err(|e| e.to_string())?;
+        let memory = instance.get_memory(&mut store, "memory").ok_or("module does not export `memory`")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc").map_err(|e| e.to_string())?;
+        let transform = instance.get_typed_func::<(i32, i32), i64>(&mut store, "transform").map_err(|e| e.to_string())?;
+
+        let input = serde_json::to_vec(&json!({"method": method, "params": params, "result": result})).map_err(|e| e.to_string())?;
+        let len = i32::try_from(input.len()).map_err(|_| "response too large for a plugin")?;
+        let ptr = alloc.call(&mut store, len).map_err(|e| e.to_string())?;
+        memory.write(&mut store, ptr as u32 as usize, &input).map_err(|e| e.to_string())?;
+
+        let packed = transform.call(&mut store, (ptr, len)).map_err(|e| e.to_string())?;
+        if packed == 0 {
+            return Ok(None);
+        }
+        let (out_ptr, out_len) = ((packed as u64 >> 32) as usize, (packed as u64 & 0xffff_ffff) as usize);
+        let mut output = vec![0u8; out_len.min(memory.data_size(&store))];
+        memory.read(&store, out_ptr, &mut output).map_err(|e| e.to_string())?;
+        serde_json::from_slice(&output).map(Some).map_err(|e| format!("invalid result JSON: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::app_config::WasmPluginModuleConfig;
+
+    /// Replaces every result with `{"redacted":true}` (17 bytes at offset 16)
+    const REDACT: &str = r#"(module
+        (memory (export "memory") 1)
+        (data (i32.const 16) "{\"redacted\":true}")
+        (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "transform") (param i32 i32) (result i64)
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 17))))"#;
+
+    const SPIN: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "transform") (param i32 i32) (result i64)
+            (loop $forever (br $forever))
+            (i64.const 0)))"#;
+
+    fn plugins(sources: &[(&str, &str)], fail_open: bool) -> Arc<WasmPlugins> {
+        let dir = std::env::temp_dir().join(format!("verus-rpc-wasm-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let modules = sources
+            .iter()
+            .map(|(name, wat)| {
+                let path = dir.join(format!("{}.wat", name));
+                std::fs::write(&path, wat).unwrap();
+                WasmPluginModuleConfig {
+                    name: name.to_string(),
+                    path: path.to_string_lossy().into_owned(),
+                    methods: vec![name.to_string()],
+                }
+            })
+            .collect();
+        let config = WasmPluginsConfig { enabled: true, fuel_per_call: 100_000, fail_open, modules, ..Default::default() };
+        WasmPlugins::load(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_transform_only_registered_methods() {
+        let plugins = plugins(&[("redact", REDACT)], false);
+        let result = plugins.transform("redact", None, json!({"secret": 1})).await.unwrap();
+        assert_eq!(result, json!({"redacted": true}));
+        let untouched = plugins.transform("getinfo", None, json!({"secret": 1})).await.unwrap();
+        assert_eq!(untouched, json!({"secret": 1}));
+    }
+
+    #[tokio::test]
+    async fn test_fuel_exhaustion_fails_closed_unless_fail_open() {
+        let closed = plugins(&[("spin", SPIN)], false);
+        assert!(matches!(closed.transform("spin", None, json!(1)).await, Err(AppError::Internal(_))));
+
+        let open = plugins(&[("spin", SPIN)], true);
+        assert_eq!(open.transform("spin", None, json!(1)).await.unwrap(), json!(1));
+    }
+}
@@ -21,14 +21,17 @@ impl ModelConverter {
             user_agent: context.user_agent.clone(),
             auth_token: context.auth_token.clone(),
             timestamp: context.timestamp,
+            request_id: Some(context.request_id.clone()),
         };
 
+        let upstream = context.tenant.as_ref().and_then(|tenant| tenant.config.upstream.clone());
         Ok(RpcRequest::new(
             infra_request.method.clone(),
             infra_request.params.clone(),
             infra_request.id.clone(),
             client_info,
-        ))
+        )
+        .with_upstream(upstream))
     }
 
     /// Convert domain response to infrastructure response
@@ -109,6 +112,12 @@ mod tests {
             method: "getinfo".to_string(),
             params: Some(serde_json::json!([])),
             auth_token: None,
+            locale: None,
+            message_catalog: None,
+            client_profile: None,
+            tenant: None,
+            rate_limit_exemption: None,
+            response_signer: None,
         };
 
         let result = ModelConverter::to_domain_request(&infra_request, &context);
@@ -120,6 +129,21 @@ mod tests {
         assert_eq!(domain_request.client_info.user_agent, Some("test-agent".to_string()));
     }
 
+    #[test]
+    fn test_to_domain_request_pins_tenant_upstream() {
+        let infra_request = JsonRpcRequest::new("getwalletinfo".to_string(), None, Some(serde_json::json!(1)));
+        let tenant = crate::infrastructure::adapters::Tenant {
+            name: "wallet".to_string(),
+            config: crate::config::app_config::TenantConfig { upstream: Some("wallet-node".to_string()), ..Default::default() },
+        };
+        let context = RequestContext::new("127.0.0.1".to_string(), "getwalletinfo".to_string(), None);
+
+        let unpinned = ModelConverter::to_domain_request(&infra_request, &context).unwrap();
+        assert_eq!(unpinned.upstream, None);
+        let pinned = ModelConverter::to_domain_request(&infra_request, &context.with_tenant(tenant)).unwrap();
+        assert_eq!(pinned.upstream.as_deref(), Some("wallet-node"));
+    }
+
     #[test]
     fn test_to_infrastructure_response_success() {
         let domain_response = RpcResponse {
@@ -213,6 +237,12 @@ mod tests {
             method: "getinfo".to_string(),
             params: Some(serde_json::json!([])),
             auth_token: None,
+            locale: None,
+            message_catalog: None,
+            client_profile: None,
+            tenant: None,
+            rate_limit_exemption: None,
+            response_signer: None,
         };
 
         let auth_token = Some("jwt-token".to_string());
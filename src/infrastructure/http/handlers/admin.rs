@@ -0,0 +1,421 @@
+//! Admin HTTP handlers
+//!
+//! Operational endpoints that require the key of an `[admin]` operator.
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use warp::Reply;
+
+use crate::application::services::{payments_service::PaymentsService, ViewingKeyRegistry};
+use crate::config::AppConfig;
+use crate::infrastructure::adapters::{AuthenticationAdapter, Handover, PartnerUsageRegistry, RevocationStore, UpstreamMetrics};
+use crate::infrastructure::http::processors::RequestGuards;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+use crate::shared::error::{AppError, AppResult};
+
+/// Default number of revocations returned by `GET /admin/revocations`
+const DEFAULT_REVOCATION_LIST_LIMIT: usize = 100;
+
+/// Query parameters for `GET /admin/revocations`
+#[derive(Debug, Deserialize)]
+pub struct RevocationListQuery {
+    pub limit: Option<usize>,
+}
+
+/// Body of `POST /admin/revocations/user`
+#[derive(Debug, Deserialize)]
+pub struct RevokeUserRequest {
+    pub user_id: String,
+    pub reason: Option<String>,
+}
+
+/// Body of `POST /admin/refunds/{payment_id}`
+#[derive(Debug, Deserialize)]
+pub struct RefundDecisionRequest {
+    pub approve: bool,
+    pub note: Option<String>,
+}
+
+/// Body of `POST /admin/read-only`
+#[derive(Debug, Deserialize)]
+pub struct ReadOnlyRequest {
+    pub enabled: bool,
+    pub reason: Option<String>,
+}
+
+/// Ensure the Authorization header carries an operator key; returns the operator's name
+pub async fn require_admin(auth: &AuthenticationAdapter, auth_header: Option<String>) -> AppResult<String> {
+    let header = auth_header.ok_or_else(|| AppError::Authentication("Missing Authorization header".to_string()))?;
+    auth.authenticate_admin(&header)
+}
+
+/// Build a JSON error reply for admin endpoints
+pub fn admin_error_reply(error: &AppError, config: &AppConfig) -> warp::reply::WithStatus<Box<dyn Reply>> {
+    let status = match error {
+        AppError::Authentication(msg) if msg == "admin permission required" => warp::http::StatusCode::FORBIDDEN,
+        AppError::Validation(_) => warp::http::StatusCode::BAD_REQUEST,
+        _ => error.http_status_code(),
+    };
+    warp::reply::with_status(
+        create_json_response_with_security_headers(
+            &serde_json::json!({ "error": error.to_string() }),
+            &SecurityHeadersMiddleware::new(config.clone()),
+        ),
+        status,
+    )
+}
+
+/// Handle `GET /admin/upstreams`
+pub async fn handle_admin_upstreams(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    let body = serde_json::json!({ "upstreams": UpstreamMetrics::global().snapshot() });
+    Ok(warp::reply::with_status(
+        create_json_response_with_security_headers(&body, &SecurityHeadersMiddleware::new(config.clone())),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+/// Handle `GET /admin/slow-queries`
+pub async fn handle_admin_slow_queries(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    let body = serde_json::json!({
+        "threshold_ms": config.slow_query_log.threshold_ms,
+        "entries": UpstreamMetrics::global().slow_queries(),
+    });
+    Ok(warp::reply::with_status(
+        create_json_response_with_security_headers(&body, &SecurityHeadersMiddleware::new(config.clone())),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+/// Handle `GET /admin/revocations`
+pub async fn handle_admin_revocations(
+    query: RevocationListQuery,
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    revocations: Arc<RevocationStore>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_REVOCATION_LIST_LIMIT);
+    match revocations.list_recent(limit).await {
+        Ok(entries) => {
+            let body = serde_json::json!({
+                "persistent": revocations.is_persistent(),
+                "revocations": entries,
+            });
+            Ok(warp::reply::with_status(
+                create_json_response_with_security_headers(&body, &SecurityHeadersMiddleware::new(config.clone())),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => Ok(admin_error_reply(&e, &config)),
+    }
+}
+
+/// Handle `POST /admin/revocations/user`: revoke every token issued to a user so far
+pub async fn handle_admin_revoke_user(
+    body: RevokeUserRequest,
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    revocations: Arc<RevocationStore>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    if body.user_id.trim().is_empty() {
+        return Ok(admin_error_reply(&AppError::Validation("user_id is required".to_string()), &config));
+    }
+    let ttl = config.revocation.user_revocation_ttl_seconds;
+    match revocations.revoke_user(&body.user_id, ttl, body.reason).await {
+        Ok(entry) => {
+            tracing::warn!(user_id = %entry.subject, "Revoked all tokens for user");
+            Ok(warp::reply::with_status(
+                create_json_response_with_security_headers(&entry, &SecurityHeadersMiddleware::new(config.clone())),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => Ok(admin_error_reply(&e, &config)),
+    }
+}
+
+/// Handle `GET /admin/read-only`
+pub async fn handle_admin_read_only(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    request_guards: Arc<RequestGuards>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    let gate = &request_guards.upstream.gate;
+    let body = serde_json::json!({ "read_only": gate.is_read_only().await, "gate": gate.metrics() });
+    Ok(warp::reply::with_status(
+        create_json_response_with_security_headers(&body, &SecurityHeadersMiddleware::new(config.clone())),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+/// Handle `POST /admin/read-only`: refuse (or allow again) write-class upstream calls
+pub async fn handle_admin_set_read_only(
+    body: ReadOnlyRequest,
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    request_guards: Arc<RequestGuards>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    match request_guards.upstream.gate.set_read_only(body.enabled, body.reason.as_deref()).await {
+        Ok(()) => Ok(warp::reply::with_status(
+            create_json_response_with_security_headers(
+                &serde_json::json!({ "read_only": body.enabled }),
+                &SecurityHeadersMiddleware::new(config.clone()),
+            ),
+            warp::http::StatusCode::OK,
+        )),
+        Err(e) => Ok(admin_error_reply(&e, &config)),
+    }
+}
+
+/// Handle `GET /admin/upgrade`: state of the current or last binary upgrade
+pub async fn handle_admin_upgrade(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    handover: Arc<Handover>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    let status = handover.status();
+    Ok(warp::reply::with_status(
+        create_json_response_with_security_headers(&status, &SecurityHeadersMiddleware::new(config.clone())),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+/// Handle `POST /admin/upgrade`: start the new binary and hand the listeners over to it
+pub async fn handle_admin_start_upgrade(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    handover: Arc<Handover>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    match handover.start() {
+        Ok(status) => {
+            tracing::warn!(target: "audit", event = "upgrade_started", successor_pid = ?status.successor_pid, "Binary upgrade started by admin");
+            Ok(warp::reply::with_status(
+                create_json_response_with_security_headers(&status, &SecurityHeadersMiddleware::new(config.clone())),
+                warp::http::StatusCode::ACCEPTED,
+            ))
+        }
+        Err(e) => Ok(admin_error_reply(&e, &config)),
+    }
+}
+
+/// Handle `GET /debug/heap`: live heap as a gzipped pprof profile (404 unless `[heap_profiling]` is enabled)
+#[cfg(feature = "heap-profiling")]
+pub async fn handle_heap_profile(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if !config.heap_profiling.enabled {
+        return Err(warp::reject::not_found());
+    }
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    match crate::infrastructure::adapters::allocator::heap_profile().await {
+        Ok(profile) => {
+            tracing::info!(target: "audit", event = "heap_profile_dumped", bytes = profile.len(), "Heap profile downloaded by admin");
+            let reply = warp::reply::with_header(profile, "content-type", "application/octet-stream");
+            let reply = warp::reply::with_header(reply, "content-disposition", "attachment; filename=\"heap.pb.gz\"");
+            Ok(warp::reply::with_status(
+                crate::middleware::security_headers::add_security_headers_to_response(
+                    reply,
+                    &SecurityHeadersMiddleware::new(config.clone()),
+                ),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => Ok(admin_error_reply(&e, &config)),
+    }
+}
+
+/// Handle `GET /admin/bans`: clients currently banned by `[auto_ban]`
+pub async fn handle_admin_bans(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    request_guards: Arc<RequestGuards>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    let bans = &request_guards.ban_list;
+    match bans.list().await {
+        Ok(list) => {
+            let body = serde_json::json!({ "persistent": bans.is_persistent(), "bans": list });
+            Ok(warp::reply::with_status(
+                create_json_response_with_security_headers(&body, &SecurityHeadersMiddleware::new(config.clone())),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => Ok(admin_error_reply(&e, &config)),
+    }
+}
+
+/// Handle `DELETE /admin/bans/{client}`: lift a ban before it expires
+pub async fn handle_admin_lift_ban(
+    client: String,
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    request_guards: Arc<RequestGuards>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    match request_guards.ban_list.lift(&client).await {
+        Ok(lifted) => {
+            tracing::warn!(target: "audit", event = "client_unbanned", client = %client, lifted, "Ban lifted by admin");
+            Ok(warp::reply::with_status(
+                create_json_response_with_security_headers(
+                    &serde_json::json!({ "client": client, "lifted": lifted }),
+                    &SecurityHeadersMiddleware::new(config.clone()),
+                ),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => Ok(admin_error_reply(&e, &config)),
+    }
+}
+
+/// Handle `GET /admin/viewing-keys`: imported viewing keys by fingerprint, never the keys themselves
+pub async fn handle_admin_viewing_keys(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    let body = serde_json::json!({
+        "require_viewing_key": config.payments.require_viewing_key,
+        "configured": config.payments.viewing_keys.len(),
+        "keys": ViewingKeyRegistry::global().snapshot(),
+    });
+    Ok(warp::reply::with_status(
+        create_json_response_with_security_headers(&body, &SecurityHeadersMiddleware::new(config.clone())),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+/// Handle `GET /admin/refunds`
+pub async fn handle_admin_refunds(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    payments: Arc<PaymentsService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    match payments.refunds().await {
+        Ok(refunds) => {
+            let body = serde_json::json!({
+                "enabled": config.payments.refunds.enabled,
+                "approval": config.payments.refunds.approval,
+                "refunds": refunds,
+            });
+            Ok(warp::reply::with_status(
+                create_json_response_with_security_headers(&body, &SecurityHeadersMiddleware::new(config.clone())),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => Ok(admin_error_reply(&e, &config)),
+    }
+}
+
+/// Handle `POST /admin/refunds/{payment_id}`: approve (send) or decline a refund
+pub async fn handle_admin_decide_refund(
+    payment_id: String,
+    body: RefundDecisionRequest,
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    payments: Arc<PaymentsService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let operator = match require_admin(&auth, auth_header).await {
+        Ok(operator) => operator,
+        Err(e) => return Ok(admin_error_reply(&e, &config)),
+    };
+    match payments.decide_refund(&payment_id, body.approve, &operator, body.note).await {
+        Ok(refund) => Ok(warp::reply::with_status(
+            create_json_response_with_security_headers(
+                &serde_json::json!({ "payment_id": payment_id, "refund": refund }),
+                &SecurityHeadersMiddleware::new(config.clone()),
+            ),
+            warp::http::StatusCode::OK,
+        )),
+        Err(e) => Ok(admin_error_reply(&e, &config)),
+    }
+}
+
+/// Handle `GET /admin/partners`: configured partners and their usage
+pub async fn handle_admin_partners(
+    auth_header: Option<String>,
+    auth: Arc<AuthenticationAdapter>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if let Err(e) = require_admin(&auth, auth_header).await {
+        return Ok(admin_error_reply(&e, &config));
+    }
+    let usage = PartnerUsageRegistry::global().snapshot().await;
+    let partners: Vec<serde_json::Value> = config
+        .partners
+        .partners
+        .iter()
+        .map(|partner| {
+            serde_json::json!({
+                "id": partner.id,
+                "name": partner.name,
+                "allowed_permissions": partner.allowed_permissions,
+                "rate_multiplier": partner.rate_multiplier,
+                "token_ttl_seconds": partner.token_ttl_seconds,
+                "usage": usage.iter().find(|u| u.partner_id == partner.id),
+            })
+        })
+        .collect();
+    let body = serde_json::json!({
+        "enabled": config.partners.enabled,
+        "partners": partners,
+    });
+    Ok(warp::reply::with_status(
+        create_json_response_with_security_headers(&body, &SecurityHeadersMiddleware::new(config.clone())),
+        warp::http::StatusCode::OK,
+    ))
+}
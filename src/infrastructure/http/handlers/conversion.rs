@@ -0,0 +1,41 @@
+//! Conversion estimate HTTP handlers
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use warp::Reply;
+
+use crate::application::services::{ConversionRequest, ConversionService};
+use crate::config::AppConfig;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+use crate::shared::error::AppError;
+
+/// Body of `POST /convert/estimate-batch`
+#[derive(Debug, Deserialize)]
+pub struct EstimateBatchRequest {
+    pub conversions: Vec<ConversionRequest>,
+}
+
+/// Handle `/convert/estimate-batch` requests
+pub async fn handle_estimate_batch(
+    body: EstimateBatchRequest,
+    service: Arc<ConversionService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let error = |message: String, status| -> Box<dyn Reply> {
+        Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&serde_json::json!({ "error": message }), &security_middleware),
+            status,
+        ))
+    };
+    if !config.conversion_estimates.enabled {
+        return Ok(error("conversion estimates are disabled".to_string(), warp::http::StatusCode::NOT_FOUND));
+    }
+    let response = match service.estimate_batch(body.conversions).await {
+        Ok(batch) => create_json_response_with_security_headers(&batch, &security_middleware),
+        Err(e @ AppError::Validation(_)) => error(e.to_string(), warp::http::StatusCode::BAD_REQUEST),
+        Err(e) => error(e.to_string(), warp::http::StatusCode::BAD_GATEWAY),
+    };
+    Ok(response)
+}
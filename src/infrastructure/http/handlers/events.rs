@@ -0,0 +1,71 @@
+//! Chain event stream handlers
+
+use std::sync::Arc;
+
+use futures::{SinkExt, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+use warp::Reply;
+
+use crate::application::services::ChainEventBus;
+use crate::config::AppConfig;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+
+/// Handle `/api/events` WebSocket upgrades
+pub async fn handle_event_stream(
+    ws: warp::ws::Ws,
+    bus: Arc<ChainEventBus>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let rejection = |status: warp::http::StatusCode, error: &str| -> Box<dyn Reply> {
+        Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&serde_json::json!({ "error": error }), &security_middleware),
+            status,
+        ))
+    };
+    if !config.chain_events.enabled && !config.tx_tracking.enabled {
+        return Ok(rejection(warp::http::StatusCode::NOT_FOUND, "chain events are disabled"));
+    }
+    if !bus.try_open_stream(config.chain_events.max_subscribers) {
+        return Ok(rejection(warp::http::StatusCode::SERVICE_UNAVAILABLE, "too many event subscribers"));
+    }
+
+    let events = bus.subscribe();
+    Ok(Box::new(ws.on_upgrade(move |socket| async move {
+        run_event_stream(socket, events).await;
+        bus.close_stream();
+    })))
+}
+
+/// Forward bus events to one subscriber until either side goes away
+async fn run_event_stream(
+    socket: warp::ws::WebSocket,
+    mut events: tokio::sync::broadcast::Receiver<crate::application::services::ChainEvent>,
+) {
+    let (mut tx, mut rx) = socket.split();
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else { continue };
+                    if tx.send(warp::ws::Message::text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => warn!(skipped, "Event subscriber fell behind"),
+                Err(RecvError::Closed) => break,
+            },
+            message = rx.next() => match message {
+                Some(Ok(message)) if message.is_close() => break,
+                Some(Ok(_)) => {} // subscribers only listen
+                Some(Err(e)) => {
+                    debug!("Event stream error: {}", e);
+                    break;
+                }
+                None => break,
+            },
+        }
+    }
+    let _ = tx.close().await;
+}
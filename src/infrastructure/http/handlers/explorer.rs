@@ -0,0 +1,189 @@
+//! Block explorer HTTP handlers
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use warp::Reply;
+
+use crate::application::services::{
+    CurrencyHistoryService, ExplorerService, ShieldedTreeService, CURRENCY_HISTORY_LIST, SHIELDED_TREE_LIST,
+};
+use crate::config::AppConfig;
+use crate::infrastructure::http::processors::UPSTREAM_CALLS_HEADER;
+use crate::middleware::etag::etag_json_response;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+use crate::shared::error::AppError;
+use crate::shared::pagination::ListQuery;
+
+/// Body of `POST /api/addresses/balances`
+#[derive(Debug, Deserialize)]
+pub struct AddressBalancesRequest {
+    pub addresses: Vec<String>,
+}
+
+/// Handle `/api/block/{hash}/full` requests
+pub async fn handle_full_block(
+    hash: String,
+    service: Arc<ExplorerService>,
+    if_none_match: Option<String>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let response: Box<dyn Reply> = match service.get_full_block(&hash).await {
+        Ok(block) => {
+            // getblock plus one getrawtransaction per transaction, charged by the REST guard
+            let calls = block.get("tx").and_then(|tx| tx.as_array()).map_or(0, Vec::len) + 1;
+            Box::new(warp::reply::with_header(
+                etag_json_response(&block, if_none_match, &security_middleware),
+                UPSTREAM_CALLS_HEADER,
+                calls.to_string(),
+            ))
+        }
+        Err(e) => error_reply(e, &security_middleware),
+    };
+    Ok(response)
+}
+
+/// Handle `/api/addresses/balances` requests
+pub async fn handle_address_balances(
+    body: AddressBalancesRequest,
+    service: Arc<ExplorerService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let calls = body.addresses.len();
+    let response: Box<dyn Reply> = match service.get_address_balances(body.addresses).await {
+        Ok(balances) => Box::new(warp::reply::with_header(
+            create_json_response_with_security_headers(&balances, &security_middleware),
+            UPSTREAM_CALLS_HEADER,
+            calls.to_string(),
+        )),
+        Err(e) => error_reply(e, &security_middleware),
+    };
+    Ok(response)
+}
+
+/// Handle `/api/currency/{id}/history` requests
+pub async fn handle_currency_history(
+    id: String,
+    params: HashMap<String, String>,
+    service: Arc<CurrencyHistoryService>,
+    if_none_match: Option<String>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let history = match ListQuery::parse(&params, &CURRENCY_HISTORY_LIST) {
+        Ok(_) if !config.currency_history.enabled => Ok(None),
+        Ok(query) => service.history(&id, &query).await,
+        Err(e) => Err(e),
+    };
+    let response: Box<dyn Reply> = match history {
+        Ok(Some(history)) => etag_json_response(&history, if_none_match, &security_middleware),
+        Ok(None) => Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(
+                &serde_json::json!({ "error": format!("no history recorded for currency {}", id) }),
+                &security_middleware,
+            ),
+            warp::http::StatusCode::NOT_FOUND,
+        )),
+        Err(e) => error_reply(e, &security_middleware),
+    };
+    Ok(response)
+}
+
+/// Handle `/api/address/{addr}/txs` requests from the embedded index
+#[cfg(feature = "indexer")]
+pub async fn handle_address_txs(
+    address: String,
+    params: HashMap<String, String>,
+    service: Arc<crate::application::services::AddressIndexService>,
+    if_none_match: Option<String>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    if !config.indexer.enabled {
+        return Ok(Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(
+                &serde_json::json!({ "error": "address index is disabled" }),
+                &security_middleware,
+            ),
+            warp::http::StatusCode::NOT_FOUND,
+        )) as Box<dyn Reply>);
+    }
+    let page = match ListQuery::parse(&params, &crate::application::services::ADDRESS_TXS_LIST) {
+        Ok(query) => service.address_txs(&address, &query).await,
+        Err(e) => Err(e),
+    };
+    let response: Box<dyn Reply> = match page {
+        Ok(page) => etag_json_response(&page, if_none_match, &security_middleware),
+        Err(e) => error_reply(e, &security_middleware),
+    };
+    Ok(response)
+}
+
+/// Handle `/api/shielded/tree/{height}` requests
+pub async fn handle_shielded_tree(
+    height: u64,
+    service: Arc<ShieldedTreeService>,
+    if_none_match: Option<String>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let state = match config.shielded_tree.enabled {
+        true => service.tree_state(height).await,
+        false => Ok(None),
+    };
+    let response: Box<dyn Reply> = match state {
+        Ok(Some(state)) => etag_json_response(&state, if_none_match, &security_middleware),
+        Ok(None) => Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(
+                &serde_json::json!({ "error": format!("no tree state at height {}", height) }),
+                &security_middleware,
+            ),
+            warp::http::StatusCode::NOT_FOUND,
+        )),
+        Err(e) => error_reply(e, &security_middleware),
+    };
+    Ok(response)
+}
+
+/// Handle `/api/shielded/tree` range requests
+pub async fn handle_shielded_tree_range(
+    params: HashMap<String, String>,
+    service: Arc<ShieldedTreeService>,
+    if_none_match: Option<String>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    if !config.shielded_tree.enabled {
+        return Ok(Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(
+                &serde_json::json!({ "error": "shielded tree states are disabled" }),
+                &security_middleware,
+            ),
+            warp::http::StatusCode::NOT_FOUND,
+        )) as Box<dyn Reply>);
+    }
+    let page = match ListQuery::parse(&params, &SHIELDED_TREE_LIST) {
+        Ok(query) => service.tree_states(&query).await,
+        Err(e) => Err(e),
+    };
+    let response: Box<dyn Reply> = match page {
+        Ok(page) => etag_json_response(&page, if_none_match, &security_middleware),
+        Err(e) => error_reply(e, &security_middleware),
+    };
+    Ok(response)
+}
+
+fn error_reply(e: AppError, security_middleware: &SecurityHeadersMiddleware) -> Box<dyn Reply> {
+    let status = match e {
+        AppError::Validation(_) => warp::http::StatusCode::BAD_REQUEST,
+        AppError::Rpc(_) => warp::http::StatusCode::BAD_GATEWAY,
+        _ => e.http_status_code(),
+    };
+    Box::new(warp::reply::with_status(
+        create_json_response_with_security_headers(&serde_json::json!({ "error": e.to_string() }), security_middleware),
+        status,
+    ))
+}
@@ -4,13 +4,24 @@
 
 use crate::{
     config::AppConfig,
-    application::use_cases::HealthCheckUseCase,
+    application::{services::{DaemonCapabilities, HealthHistoryService}, use_cases::HealthCheckUseCase},
     infrastructure::adapters::ExternalRpcAdapter,
     middleware::security_headers::{SecurityHeadersMiddleware, create_json_response_with_security_headers},
 };
+use serde::Deserialize;
 use std::sync::Arc;
 use warp::{Reply};
 
+/// Transitions returned by `/health/history` when no `limit` is given
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Query of `GET /health/history`
+#[derive(Debug, Deserialize)]
+pub struct HealthHistoryQuery {
+    /// Most recent transitions to return
+    pub limit: Option<usize>,
+}
+
 /// Handle health check requests
 pub async fn handle_health_request(
     health_use_case: Arc<HealthCheckUseCase>,
@@ -29,6 +40,31 @@ pub async fn handle_health_request(
     Ok(response)
 }
 
+/// Handle `/health/history` requests (404 unless `[health_history]` is enabled)
+pub async fn handle_health_history(
+    query: HealthHistoryQuery,
+    service: Arc<HealthHistoryService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if !config.health_history.enabled {
+        return Err(warp::reject::not_found());
+    }
+    let history = service.history(query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT)).await;
+    Ok(create_json_response_with_security_headers(
+        &history,
+        &SecurityHeadersMiddleware::new(config),
+    ))
+}
+
+/// Handle `/capabilities` requests: daemon version and indexes detected at startup
+pub async fn handle_capabilities(config: AppConfig) -> Result<impl Reply, warp::reject::Rejection> {
+    let capabilities = DaemonCapabilities::current().cloned().unwrap_or_default();
+    Ok(create_json_response_with_security_headers(
+        &capabilities,
+        &SecurityHeadersMiddleware::new(config),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -38,7 +74,7 @@ mod tests {
     }
 
     fn create_test_health_use_case() -> Arc<HealthCheckUseCase> {
-        Arc::new(HealthCheckUseCase)
+        Arc::new(HealthCheckUseCase::default())
     }
 
     #[tokio::test]
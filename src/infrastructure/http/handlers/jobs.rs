@@ -0,0 +1,65 @@
+//! Asynchronous job HTTP handlers
+
+use std::sync::Arc;
+
+use warp::Reply;
+
+use crate::application::services::{JobQuery, JobService};
+use crate::config::AppConfig;
+use crate::infrastructure::http::utils::extract_and_validate_client_ip;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+use crate::shared::error::AppError;
+
+/// Handle `POST /jobs` requests
+pub async fn handle_submit_job(
+    query: JobQuery,
+    client_ip: String,
+    service: Arc<JobService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let error = |message: String, status| -> Box<dyn Reply> {
+        Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&serde_json::json!({ "error": message }), &security_middleware),
+            status,
+        ))
+    };
+    if !config.jobs.enabled {
+        return Ok(error("jobs are disabled".to_string(), warp::http::StatusCode::NOT_FOUND));
+    }
+    let client_ip = extract_and_validate_client_ip(&client_ip, &config);
+    let response: Box<dyn Reply> = match service.submit(&client_ip, query).await {
+        Ok(job) => Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&job, &security_middleware),
+            warp::http::StatusCode::ACCEPTED,
+        )),
+        Err(e @ AppError::Validation(_)) => error(e.to_string(), warp::http::StatusCode::BAD_REQUEST),
+        Err(AppError::RateLimit) => error(
+            format!("at most {} running jobs per client", config.jobs.max_jobs_per_client),
+            warp::http::StatusCode::TOO_MANY_REQUESTS,
+        ),
+        Err(e) => error(e.to_string(), e.http_status_code()),
+    };
+    Ok(response)
+}
+
+/// Handle `GET /jobs/{id}` requests
+pub async fn handle_job(id: String, service: Arc<JobService>, config: AppConfig) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let error = |message: String, status| -> Box<dyn Reply> {
+        Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&serde_json::json!({ "error": message }), &security_middleware),
+            status,
+        ))
+    };
+    let job = match config.jobs.enabled {
+        true => service.job(&id).await,
+        false => Ok(None),
+    };
+    let response = match job {
+        Ok(Some(job)) => create_json_response_with_security_headers(&job, &security_middleware),
+        Ok(None) => error(format!("no job {}", id), warp::http::StatusCode::NOT_FOUND),
+        Err(e) => error(e.to_string(), e.http_status_code()),
+    };
+    Ok(response)
+}
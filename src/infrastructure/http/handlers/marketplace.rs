@@ -0,0 +1,44 @@
+//! Marketplace HTTP handlers
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use warp::Reply;
+
+use crate::application::services::{MarketplaceService, MARKETPLACE_OFFERS_LIST};
+use crate::config::AppConfig;
+use crate::middleware::etag::etag_json_response;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+use crate::shared::error::AppError;
+use crate::shared::pagination::ListQuery;
+
+/// Handle `/marketplace/offers/{name}` requests
+pub async fn handle_marketplace_offers(
+    name: String,
+    params: HashMap<String, String>,
+    service: Arc<MarketplaceService>,
+    if_none_match: Option<String>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let error = |message: String, status| -> Box<dyn Reply> {
+        Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&serde_json::json!({ "error": message }), &security_middleware),
+            status,
+        ))
+    };
+    if !config.marketplace.enabled {
+        return Ok(error("marketplace offers are disabled".to_string(), warp::http::StatusCode::NOT_FOUND));
+    }
+    let offers = match ListQuery::parse(&params, &MARKETPLACE_OFFERS_LIST) {
+        Ok(query) => service.offers(&name, &query).await,
+        Err(e) => Err(e),
+    };
+    let response = match offers {
+        Ok(Some(page)) => etag_json_response(&page, if_none_match, &security_middleware),
+        Ok(None) => error(format!("no currency or identity named {}", name), warp::http::StatusCode::NOT_FOUND),
+        Err(e @ AppError::Validation(_)) => error(e.to_string(), warp::http::StatusCode::BAD_REQUEST),
+        Err(e) => error(e.to_string(), warp::http::StatusCode::BAD_GATEWAY),
+    };
+    Ok(response)
+}
@@ -0,0 +1,30 @@
+//! Mempool HTTP handlers
+
+use std::sync::Arc;
+
+use warp::Reply;
+
+use crate::application::services::MempoolService;
+use crate::config::AppConfig;
+use crate::middleware::etag::etag_json_response;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+
+/// Handle `/mempool/stats` requests from the cached sampler snapshot
+pub async fn handle_mempool_stats(
+    service: Arc<MempoolService>,
+    if_none_match: Option<String>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let response: Box<dyn Reply> = match service.stats().await {
+        Some(stats) => etag_json_response(&stats, if_none_match, &security_middleware),
+        None => Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(
+                &serde_json::json!({ "error": "mempool statistics not yet available" }),
+                &security_middleware,
+            ),
+            warp::http::StatusCode::SERVICE_UNAVAILABLE,
+        )),
+    };
+    Ok(response)
+}
@@ -5,7 +5,9 @@
 use crate::{
     config::AppConfig,
     application::use_cases::GetMetricsUseCase,
-    middleware::security_headers::{SecurityHeadersMiddleware, create_json_response_with_security_headers, add_security_headers_to_response},
+    infrastructure::adapters::{CpuPool, MemoryUsage, ProcessSnapshot, SliMetrics, UpstreamMetrics},
+    infrastructure::http::{listener::ProtocolMetrics, processors::RequestGuards},
+    middleware::{cache::CacheMiddleware, rate_limit::RateLimitMiddleware, etag::{etag_json_response, etag_response}, security_headers::SecurityHeadersMiddleware},
 };
 use std::sync::Arc;
 use warp::{Reply};
@@ -13,13 +15,57 @@ use warp::{Reply};
 /// Handle metrics requests
 pub async fn handle_metrics_request(
     metrics_use_case: Arc<GetMetricsUseCase>,
+    if_none_match: Option<String>,
     config: AppConfig,
+    request_guards: Arc<RequestGuards>,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
 ) -> Result<impl Reply, warp::reject::Rejection> {
-    let metrics_data = metrics_use_case.execute();
+    let mut metrics_data = metrics_use_case.execute();
+    if let Some(obj) = metrics_data.as_object_mut() {
+        obj.insert(
+            "upstreams".to_string(),
+            serde_json::to_value(UpstreamMetrics::global().snapshot()).unwrap_or_default(),
+        );
+        obj.insert(
+            "load_shedding".to_string(),
+            serde_json::to_value(request_guards.load_shedder.metrics()).unwrap_or_default(),
+        );
+        obj.insert(
+            "memory_guard".to_string(),
+            serde_json::to_value(request_guards.memory_guard.metrics()).unwrap_or_default(),
+        );
+        obj.insert(
+            "complexity".to_string(),
+            serde_json::to_value(request_guards.complexity.metrics()).unwrap_or_default(),
+        );
+        obj.insert(
+            "rate_limit".to_string(),
+            serde_json::to_value(rate_limit_middleware.metrics()).unwrap_or_default(),
+        );
+        obj.insert(
+            "rate_limit_exemptions".to_string(),
+            serde_json::to_value(request_guards.rate_limit_exemptions.metrics()).unwrap_or_default(),
+        );
+        obj.insert(
+            "upstream_gate".to_string(),
+            serde_json::to_value(request_guards.upstream.gate.metrics()).unwrap_or_default(),
+        );
+        obj.insert(
+            "negative_cache".to_string(),
+            serde_json::to_value(request_guards.upstream.negative_cache.metrics()).unwrap_or_default(),
+        );
+        obj.insert(
+            "auto_ban".to_string(),
+            serde_json::to_value(request_guards.ban_list.metrics()).unwrap_or_default(),
+        );
+        if let Some(cluster) = request_guards.cluster.as_ref().filter(|_| config.cluster.enabled) {
+            obj.insert("cluster".to_string(), serde_json::to_value(cluster.metrics()).unwrap_or_default());
+        }
+    }
     
-    // Apply security headers only
-    let response = create_json_response_with_security_headers(
+    let response = etag_json_response(
         &metrics_data,
+        if_none_match,
         &SecurityHeadersMiddleware::new(config.clone()),
     );
     
@@ -29,23 +75,64 @@ pub async fn handle_metrics_request(
 /// Handle Prometheus metrics requests
 pub async fn handle_prometheus_request(
     monitoring_adapter: Arc<crate::infrastructure::adapters::MonitoringAdapter>,
+    cache_middleware: Option<Arc<CacheMiddleware>>,
+    if_none_match: Option<String>,
     config: AppConfig,
+    request_guards: Arc<RequestGuards>,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
+    cpu_pool: Arc<CpuPool>,
 ) -> Result<impl Reply, warp::reject::Rejection> {
-    let metrics = monitoring_adapter.get_prometheus_metrics();
+    let mut metrics = monitoring_adapter.get_prometheus_metrics();
+    metrics.push_str(&UpstreamMetrics::global().prometheus_text());
+    metrics.push_str(&request_guards.load_shedder.prometheus_text());
+    metrics.push_str(&request_guards.memory_guard.prometheus_text());
+    metrics.push_str(&MemoryUsage::global().prometheus_text());
+    metrics.push_str(&request_guards.complexity.prometheus_text());
+    metrics.push_str(&rate_limit_middleware.prometheus_text());
+    metrics.push_str(&request_guards.rate_limit_exemptions.prometheus_text());
+    metrics.push_str(&request_guards.tenants.prometheus_text());
+    metrics.push_str(&request_guards.upstream.gate.prometheus_text());
+    metrics.push_str(&request_guards.upstream.negative_cache.prometheus_text());
+    metrics.push_str(&request_guards.ban_list.prometheus_text());
+    metrics.push_str(&ProtocolMetrics::global().prometheus_text());
+    metrics.push_str(&SliMetrics::global().prometheus_text());
+    if let Some(cluster) = request_guards.cluster.as_ref().filter(|_| config.cluster.enabled) {
+        metrics.push_str(&cluster.prometheus_text());
+    }
+    let cache_stats = match &cache_middleware {
+        Some(cache) => Some(cache.get_stats().await),
+        None => None,
+    };
+    metrics.push_str(&ProcessSnapshot::collect().prometheus_text(cache_stats.as_ref()));
+    metrics.push_str(&crate::infrastructure::adapters::allocator::prometheus_text());
+    metrics.push_str(&cpu_pool.prometheus_text());
+    metrics.push_str(&crate::infrastructure::adapters::upstream_pipeline::prometheus_text());
+    metrics.push_str(&crate::infrastructure::adapters::validation_cache::prometheus_text());
     
-    // Apply security headers only
-    let response = add_security_headers_to_response(
-        warp::reply::with_header(
-            warp::reply::with_status(metrics, warp::http::StatusCode::OK),
-            "Content-Type",
-            "text/plain; version=0.0.4; charset=utf-8"
-        ),
+    let response = etag_response(
+        metrics,
+        "text/plain; version=0.0.4; charset=utf-8",
+        if_none_match,
         &SecurityHeadersMiddleware::new(config.clone()),
     );
     
     Ok(response)
 }
 
+/// Handle `/metrics/summary`: cache and coalescing SLIs over 1m/5m/1h windows
+pub async fn handle_metrics_summary_request(
+    if_none_match: Option<String>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::Rejection> {
+    let response = etag_json_response(
+        &SliMetrics::global().summary(),
+        if_none_match,
+        &SecurityHeadersMiddleware::new(config.clone()),
+    );
+
+    Ok(response)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,12 +151,28 @@ mod tests {
         Arc::new(crate::infrastructure::adapters::MonitoringAdapter::new())
     }
 
+    async fn metrics_request(metrics_use_case: Arc<GetMetricsUseCase>, config: AppConfig) -> Result<impl Reply, warp::Rejection> {
+        let request_guards = Arc::new(RequestGuards::new(&config));
+        let rate_limit_middleware = Arc::new(RateLimitMiddleware::new(config.clone()));
+        handle_metrics_request(metrics_use_case, None, config, request_guards, rate_limit_middleware).await
+    }
+
+    async fn prometheus_request(
+        monitoring_adapter: Arc<crate::infrastructure::adapters::MonitoringAdapter>,
+        config: AppConfig,
+    ) -> Result<impl Reply, warp::Rejection> {
+        let request_guards = Arc::new(RequestGuards::new(&config));
+        let rate_limit_middleware = Arc::new(RateLimitMiddleware::new(config.clone()));
+        let cpu_pool = Arc::new(CpuPool::new(&config.server));
+        handle_prometheus_request(monitoring_adapter, None, None, config, request_guards, rate_limit_middleware, cpu_pool).await
+    }
+
     #[tokio::test]
     async fn test_handle_metrics_request_success() {
         let metrics_use_case = create_test_metrics_use_case();
         let config = create_test_config();
 
-        let result = handle_metrics_request(metrics_use_case, config).await;
+        let result = metrics_request(metrics_use_case, config).await;
         
         assert!(result.is_ok());
     }
@@ -83,7 +186,7 @@ mod tests {
         config.server.port = 8081;
         config.server.bind_address = "127.0.0.1".parse().unwrap();
 
-        let result = handle_metrics_request(metrics_use_case, config).await;
+        let result = metrics_request(metrics_use_case, config).await;
         
         assert!(result.is_ok());
     }
@@ -93,7 +196,7 @@ mod tests {
         let metrics_use_case = create_test_metrics_use_case();
         let config = create_test_config();
 
-        let result = handle_metrics_request(metrics_use_case, config).await;
+        let result = metrics_request(metrics_use_case, config).await;
         
         assert!(result.is_ok());
     }
@@ -103,7 +206,7 @@ mod tests {
         let monitoring_adapter = create_test_monitoring_adapter();
         let config = create_test_config();
 
-        let result = handle_prometheus_request(monitoring_adapter, config).await;
+        let result = prometheus_request(monitoring_adapter, config).await;
         
         assert!(result.is_ok());
     }
@@ -113,11 +216,18 @@ mod tests {
         let monitoring_adapter = create_test_monitoring_adapter();
         let config = create_test_config();
 
-        let result = handle_prometheus_request(monitoring_adapter, config).await;
+        let result = prometheus_request(monitoring_adapter, config).await;
         
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_handle_metrics_summary_request_success() {
+        let result = handle_metrics_summary_request(None, create_test_config()).await;
+
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_metrics_use_case_execute() {
         let metrics_use_case = create_test_metrics_use_case();
@@ -160,7 +270,7 @@ mod tests {
         // Disable security headers
         config.security.enable_security_headers = false;
 
-        let result = handle_metrics_request(metrics_use_case, config).await;
+        let result = metrics_request(metrics_use_case, config).await;
         
         assert!(result.is_ok());
     }
@@ -173,7 +283,7 @@ mod tests {
         // Disable security headers
         config.security.enable_security_headers = false;
 
-        let result = handle_prometheus_request(monitoring_adapter, config).await;
+        let result = prometheus_request(monitoring_adapter, config).await;
         
         assert!(result.is_ok());
     }
@@ -7,7 +7,7 @@ use crate::{
     infrastructure::http::{
                  models::{JsonRpcRequest, RequestContext},
         utils::extract_and_validate_client_ip,
-        processors::BaseRequestProcessor,
+        processors::{BaseRequestProcessor, RequestGuards},
         mining_pool::{MiningPoolUtils, MiningPoolResponseHandler},
     },
     middleware::{
@@ -28,16 +28,20 @@ pub async fn handle_mining_pool_request(
     config: AppConfig,
     cache_middleware: Arc<CacheMiddleware>,
     rate_limit_middleware: Arc<RateLimitMiddleware>,
+    request_guards: Arc<RequestGuards>,
 ) -> Result<impl Reply, warp::reject::Rejection> {
     // Extract and validate client IP
     let validated_client_ip = extract_and_validate_client_ip(&client_ip, &config);
     
     // Create request context
-    let context = RequestContext::new(
+    let mut context = RequestContext::new(
         validated_client_ip.clone(),
         request.method.clone(),
         request.params.clone(),
     );
+    if let Some(reason) = request_guards.rate_limit_exemptions.resolve(&validated_client_ip, None, None) {
+        context = context.with_rate_limit_exemption(reason);
+    }
 
     // Log request if enabled
     if config.security.enable_request_logging {
@@ -60,6 +64,7 @@ pub async fn handle_mining_pool_request(
         &context,
         &request,
         &rate_limit_middleware,
+        &request_guards,
         &config,
     ).await {
         return Ok(response);
@@ -203,6 +208,9 @@ mod tests {
             circuit_breaker_timeout: 60,
             requests_per_minute: 100,
             enabled: true,
+            share_cache_ttl_seconds: 300,
+            share_cache_max_entries: 10000,
+            max_batch_size: 50,
         });
         config
     }
@@ -235,6 +243,10 @@ mod tests {
         Arc::new(RateLimitMiddleware::new(create_test_config()))
     }
 
+    fn create_test_request_guards() -> Arc<RequestGuards> {
+        Arc::new(RequestGuards::new(&create_test_config()))
+    }
+
     #[tokio::test]
     async fn test_handle_mining_pool_request_success() {
         let request = create_test_request();
@@ -251,6 +263,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -273,6 +286,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -296,6 +310,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -318,6 +333,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -389,7 +405,7 @@ mod tests {
 
         assert_eq!(context.client_ip, "127.0.0.1");
         assert_eq!(context.method, "submit_share");
-        assert!(context.request_id.len() > 0);
+        assert!(!context.request_id.is_empty());
     }
 
     #[tokio::test]
@@ -412,6 +428,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -441,6 +458,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -470,6 +488,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -499,6 +518,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -528,6 +548,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -557,6 +578,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -586,6 +608,7 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
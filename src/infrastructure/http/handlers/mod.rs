@@ -8,9 +8,42 @@ pub mod health;
 pub mod metrics;
 pub mod mining_pool;
 pub mod payments;
+pub mod mempool;
+pub mod explorer;
+pub mod resolver;
+pub mod conversion;
+pub mod marketplace;
+pub mod portfolio;
+pub mod jobs;
+pub mod reports;
+pub mod admin;
+pub mod events;
+pub mod tracking;
+#[cfg(feature = "status-page")]
+pub mod status;
 
 pub use rpc::handle_rpc_request;
-pub use health::handle_health_request;
-pub use metrics::{handle_metrics_request, handle_prometheus_request};
+pub use health::{handle_capabilities, handle_health_history, handle_health_request};
+pub use metrics::{handle_metrics_request, handle_metrics_summary_request, handle_prometheus_request};
 pub use mining_pool::{handle_mining_pool_request, handle_pool_metrics_request};
-pub use payments::{handle_payment_quote, handle_payment_submit, handle_payment_status};
+pub use payments::{
+    handle_payment_history, handle_payment_quote, handle_payment_receipt, handle_payment_status, handle_payment_submit,
+    handle_payment_token_refresh,
+};
+pub use mempool::handle_mempool_stats;
+pub use explorer::{handle_address_balances, handle_currency_history, handle_full_block, handle_shielded_tree, handle_shielded_tree_range};
+#[cfg(feature = "indexer")]
+pub use explorer::handle_address_txs;
+pub use resolver::handle_resolve;
+pub use conversion::handle_estimate_batch;
+pub use marketplace::handle_marketplace_offers;
+pub use portfolio::handle_portfolio;
+pub use jobs::{handle_job, handle_submit_job};
+pub use reports::handle_report;
+pub use events::handle_event_stream;
+pub use tracking::{handle_track_tx, handle_tracked_tx};
+#[cfg(feature = "status-page")]
+pub use status::handle_status_page;
+#[cfg(feature = "heap-profiling")]
+pub use admin::handle_heap_profile;
+pub use admin::{handle_admin_upstreams, handle_admin_slow_queries, handle_admin_revocations, handle_admin_revoke_user, handle_admin_partners, handle_admin_viewing_keys, handle_admin_refunds, handle_admin_decide_refund, handle_admin_read_only, handle_admin_set_read_only, handle_admin_upgrade, handle_admin_start_upgrade, handle_admin_bans, handle_admin_lift_ban};
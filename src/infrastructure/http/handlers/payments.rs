@@ -4,22 +4,24 @@ use std::sync::Arc;
 
 use warp::Reply;
 
-use crate::application::services::payments_service::{PaymentQuoteRequest, PaymentSubmitRequest, PaymentsService};
+use crate::application::services::payments_service::{PaymentHistoryQuery, PaymentQuoteRequest, PaymentSubmitRequest, PaymentsService};
 use crate::config::AppConfig;
 use crate::infrastructure::http::models::RequestContext;
 use crate::domain::rpc::ClientInfo;
+use crate::shared::error::AppError;
 use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
-use crate::middleware::rate_limit::{RateLimitMiddleware};
+use crate::middleware::rate_limit::RateLimitMiddleware;
 
 pub async fn handle_payment_quote(
     body: PaymentQuoteRequest,
     client_ip: String,
     service: Arc<PaymentsService>,
     config: AppConfig,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
 ) -> Result<impl Reply, warp::reject::Rejection> {
     // Apply per-IP rate limit using global settings
-    let limiter = RateLimitMiddleware::new(config.clone()).create_client_limiter(&client_ip);
-    if let Err(_) = limiter.check_rate_limit(&client_ip).await {
+    let limiter = rate_limit_middleware.create_client_limiter(&client_ip);
+    if limiter.check_rate_limit(&client_ip).await.is_err() {
         let resp = create_json_response_with_security_headers(&serde_json::json!({"error":"Rate limit"}), &SecurityHeadersMiddleware::new(config.clone()));
         return Ok(warp::reply::with_status(resp, warp::http::StatusCode::TOO_MANY_REQUESTS));
     }
@@ -29,6 +31,7 @@ pub async fn handle_payment_quote(
         user_agent: context.user_agent.clone(),
         auth_token: None,
         timestamp: context.timestamp,
+        request_id: Some(context.request_id.clone()),
     };
     let result = service.create_quote(body, &client_info).await;
 
@@ -50,9 +53,10 @@ pub async fn handle_payment_submit(
     client_ip: String,
     service: Arc<PaymentsService>,
     config: AppConfig,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
 ) -> Result<impl Reply, warp::reject::Rejection> {
-    let limiter = RateLimitMiddleware::new(config.clone()).create_client_limiter(&client_ip);
-    if let Err(_) = limiter.check_rate_limit(&client_ip).await {
+    let limiter = rate_limit_middleware.create_client_limiter(&client_ip);
+    if limiter.check_rate_limit(&client_ip).await.is_err() {
         let resp = create_json_response_with_security_headers(&serde_json::json!({"error":"Rate limit"}), &SecurityHeadersMiddleware::new(config.clone()));
         return Ok(warp::reply::with_status(resp, warp::http::StatusCode::TOO_MANY_REQUESTS));
     }
@@ -62,6 +66,7 @@ pub async fn handle_payment_submit(
         user_agent: context.user_agent.clone(),
         auth_token: None,
         timestamp: context.timestamp,
+        request_id: Some(context.request_id.clone()),
     };
     let result = service.submit_raw_transaction(body, &client_info).await;
     let response = match result {
@@ -82,9 +87,10 @@ pub async fn handle_payment_status(
     client_ip: String,
     service: Arc<PaymentsService>,
     config: AppConfig,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
 ) -> Result<impl Reply, warp::reject::Rejection> {
-    let limiter = RateLimitMiddleware::new(config.clone()).create_client_limiter(&client_ip);
-    if let Err(_) = limiter.check_rate_limit(&client_ip).await {
+    let limiter = rate_limit_middleware.create_client_limiter(&client_ip);
+    if limiter.check_rate_limit(&client_ip).await.is_err() {
         let resp = create_json_response_with_security_headers(&serde_json::json!({"error":"Rate limit"}), &SecurityHeadersMiddleware::new(config.clone()));
         return Ok(warp::reply::with_status(resp, warp::http::StatusCode::TOO_MANY_REQUESTS));
     }
@@ -94,6 +100,7 @@ pub async fn handle_payment_status(
         user_agent: context.user_agent.clone(),
         auth_token: None,
         timestamp: context.timestamp,
+        request_id: Some(context.request_id.clone()),
     };
     let result = service.check_status(&payment_id, &client_info).await;
     let response = match result {
@@ -109,4 +116,95 @@ pub async fn handle_payment_status(
     Ok(response)
 }
 
+/// Handle `GET /payments/receipt/{payment_id}`
+pub async fn handle_payment_receipt(
+    payment_id: String,
+    client_ip: String,
+    service: Arc<PaymentsService>,
+    config: AppConfig,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let limiter = rate_limit_middleware.create_client_limiter(&client_ip);
+    if limiter.check_rate_limit(&client_ip).await.is_err() {
+        let resp = create_json_response_with_security_headers(&serde_json::json!({"error":"Rate limit"}), &SecurityHeadersMiddleware::new(config.clone()));
+        return Ok(warp::reply::with_status(resp, warp::http::StatusCode::TOO_MANY_REQUESTS));
+    }
+    let response = match service.receipt(&payment_id).await {
+        Ok(receipt) => warp::reply::with_status(
+            create_json_response_with_security_headers(&receipt, &SecurityHeadersMiddleware::new(config.clone())),
+            warp::http::StatusCode::OK,
+        ),
+        Err(e) => payment_error_reply(&e, &config),
+    };
+    Ok(response)
+}
 
+/// Handle `GET /payments/history?identity=...`
+pub async fn handle_payment_history(
+    query: PaymentHistoryQuery,
+    client_ip: String,
+    auth_header: Option<String>,
+    service: Arc<PaymentsService>,
+    config: AppConfig,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let limiter = rate_limit_middleware.create_client_limiter(&client_ip);
+    if limiter.check_rate_limit(&client_ip).await.is_err() {
+        let resp = create_json_response_with_security_headers(&serde_json::json!({"error":"Rate limit"}), &SecurityHeadersMiddleware::new(config.clone()));
+        return Ok(warp::reply::with_status(resp, warp::http::StatusCode::TOO_MANY_REQUESTS));
+    }
+    let identity = query.identity;
+    let response = match service.history(&identity, auth_header.as_deref()).await {
+        Ok(history) => warp::reply::with_status(
+            create_json_response_with_security_headers(
+                &serde_json::json!({ "identity": identity, "payments": history }),
+                &SecurityHeadersMiddleware::new(config.clone()),
+            ),
+            warp::http::StatusCode::OK,
+        ),
+        Err(e) => payment_error_reply(&e, &config),
+    };
+    Ok(response)
+}
+
+/// Handle `POST /payments/token/refresh`
+pub async fn handle_payment_token_refresh(
+    client_ip: String,
+    auth_header: Option<String>,
+    service: Arc<PaymentsService>,
+    config: AppConfig,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let limiter = rate_limit_middleware.create_client_limiter(&client_ip);
+    if limiter.check_rate_limit(&client_ip).await.is_err() {
+        let resp = create_json_response_with_security_headers(&serde_json::json!({"error":"Rate limit"}), &SecurityHeadersMiddleware::new(config.clone()));
+        return Ok(warp::reply::with_status(resp, warp::http::StatusCode::TOO_MANY_REQUESTS));
+    }
+    let context = RequestContext::new(client_ip.clone(), "payments.token_refresh".to_string(), None);
+    let client_info = ClientInfo {
+        ip_address: context.client_ip.clone(),
+        user_agent: context.user_agent.clone(),
+        auth_token: None,
+        timestamp: context.timestamp,
+        request_id: Some(context.request_id.clone()),
+    };
+    let response = match service.refresh_token(auth_header.as_deref(), &client_info).await {
+        Ok(event) => warp::reply::with_status(
+            create_json_response_with_security_headers(&event, &SecurityHeadersMiddleware::new(config.clone())),
+            warp::http::StatusCode::OK,
+        ),
+        Err(e) => payment_error_reply(&e, &config),
+    };
+    Ok(response)
+}
+
+fn payment_error_reply(error: &AppError, config: &AppConfig) -> warp::reply::WithStatus<Box<dyn Reply>> {
+    let status = match error {
+        AppError::Validation(_) => warp::http::StatusCode::BAD_REQUEST,
+        _ => error.http_status_code(),
+    };
+    warp::reply::with_status(
+        create_json_response_with_security_headers(&serde_json::json!({ "error": error.to_string() }), &SecurityHeadersMiddleware::new(config.clone())),
+        status,
+    )
+}
@@ -0,0 +1,42 @@
+//! Watch-only portfolio HTTP handlers
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use warp::Reply;
+
+use crate::application::services::PortfolioService;
+use crate::config::AppConfig;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+use crate::shared::error::AppError;
+
+/// Body of `POST /api/portfolio`
+#[derive(Debug, Deserialize)]
+pub struct PortfolioRequest {
+    /// Transparent addresses, i-addresses and `name@` identities
+    pub addresses: Vec<String>,
+}
+
+/// Handle `/api/portfolio` requests
+pub async fn handle_portfolio(
+    body: PortfolioRequest,
+    service: Arc<PortfolioService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let error = |message: String, status| -> Box<dyn Reply> {
+        Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&serde_json::json!({ "error": message }), &security_middleware),
+            status,
+        ))
+    };
+    if !config.portfolio.enabled {
+        return Ok(error("portfolio balances are disabled".to_string(), warp::http::StatusCode::NOT_FOUND));
+    }
+    let response = match service.portfolio(body.addresses).await {
+        Ok(portfolio) => create_json_response_with_security_headers(&portfolio, &security_middleware),
+        Err(e @ AppError::Validation(_)) => error(e.to_string(), warp::http::StatusCode::BAD_REQUEST),
+        Err(e) => error(e.to_string(), warp::http::StatusCode::BAD_GATEWAY),
+    };
+    Ok(response)
+}
@@ -0,0 +1,30 @@
+//! Scheduled report HTTP handlers
+
+use std::sync::Arc;
+
+use warp::Reply;
+
+use crate::application::services::ReportService;
+use crate::config::AppConfig;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+
+/// Handle `GET /reports/{name}` requests
+pub async fn handle_report(name: String, service: Arc<ReportService>, config: AppConfig) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let error = |message: String, status| -> Box<dyn Reply> {
+        Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&serde_json::json!({ "error": message }), &security_middleware),
+            status,
+        ))
+    };
+    let report = match config.reports.enabled {
+        true => service.latest(&name).await,
+        false => Ok(None),
+    };
+    let response = match report {
+        Ok(Some(report)) => create_json_response_with_security_headers(&report, &security_middleware),
+        Ok(None) => error(format!("no cached run of report {}", name), warp::http::StatusCode::NOT_FOUND),
+        Err(e) => error(e.to_string(), e.http_status_code()),
+    };
+    Ok(response)
+}
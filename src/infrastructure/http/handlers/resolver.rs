@@ -0,0 +1,35 @@
+//! Name resolution HTTP handlers
+
+use std::sync::Arc;
+
+use warp::Reply;
+
+use crate::application::services::NameResolverService;
+use crate::config::AppConfig;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+use crate::shared::error::AppError;
+
+/// Handle `/resolve/{name}` requests
+pub async fn handle_resolve(
+    name: String,
+    service: Arc<NameResolverService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    let error = |message: String, status| -> Box<dyn Reply> {
+        Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&serde_json::json!({ "error": message }), &security_middleware),
+            status,
+        ))
+    };
+    if !config.name_resolver.enabled {
+        return Ok(error("name resolution is disabled".to_string(), warp::http::StatusCode::NOT_FOUND));
+    }
+    let response = match service.resolve(&name).await {
+        Ok(Some(resolution)) => create_json_response_with_security_headers(&resolution, &security_middleware),
+        Ok(None) => error(format!("no currency or identity named {}", name), warp::http::StatusCode::NOT_FOUND),
+        Err(e @ AppError::Validation(_)) => error(e.to_string(), warp::http::StatusCode::BAD_REQUEST),
+        Err(e) => error(e.to_string(), warp::http::StatusCode::BAD_GATEWAY),
+    };
+    Ok(response)
+}
@@ -7,101 +7,276 @@ use crate::{
     infrastructure::http::{
         models::{JsonRpcRequest, RequestContext},
         utils::extract_and_validate_client_ip,
-        processors::{BaseRequestProcessor, RpcRequestProcessor},
+        processors::{BaseRequestProcessor, RequestGuards, RpcRequestProcessor, SingleFlight},
     },
     application::use_cases::ProcessRpcRequestUseCase,
+    infrastructure::adapters::{
+        Offense, RequestOutcome, SliMetrics,
+    },
     middleware::{
         cache::CacheMiddleware, 
         rate_limit::RateLimitMiddleware, 
     },
 };
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{info, instrument};
 use warp::{Reply};
 
 /// Handle RPC requests optimized for reverse proxy deployment
-#[instrument(skip(rpc_use_case, config, cache_middleware, rate_limit_middleware))]
+#[instrument(skip(api_key_header, rpc_use_case, config, cache_middleware, rate_limit_middleware, request_guards))]
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_rpc_request(
     request: JsonRpcRequest,
     client_ip: String,
     auth_header: Option<String>,
+    api_key_header: Option<String>,
+    content_length: Option<u64>,
     user_agent_header: Option<String>,
+    accept_language_header: Option<String>,
+    cluster_forward_header: Option<String>,
+    debug_validate_header: Option<String>,
+    sign_header: Option<String>,
     rpc_use_case: Arc<ProcessRpcRequestUseCase>,
     config: AppConfig,
     cache_middleware: Arc<CacheMiddleware>,
     rate_limit_middleware: Arc<RateLimitMiddleware>,
+    request_guards: Arc<RequestGuards>,
 ) -> Result<impl Reply, warp::reject::Rejection> {
     // Extract and validate client IP
     let validated_client_ip = extract_and_validate_client_ip(&client_ip, &config);
     
     // Create request context
     let mut context = RequestContext::new(
-        validated_client_ip.clone(),
+        validated_client_ip,
         request.method.clone(),
         request.params.clone(),
     );
     if let Some(ua) = user_agent_header { context = context.with_user_agent(ua); }
     if let Some(auth) = auth_header { context = context.with_auth_token(auth); }
+    if let Some(lang) = accept_language_header {
+        context = context.with_accept_language(&lang, request_guards.message_catalog.as_ref());
+    }
+    let mut context = BaseRequestProcessor::identify_client(context, api_key_header.as_deref(), &request_guards).await;
+    if let Some(signer) = request_guards
+        .response_signer
+        .as_ref()
+        .filter(|signer| signer.applies(&request.method, sign_header.as_deref()))
+    {
+        context = context.with_signed_response(signer.clone());
+    }
 
-    // Log request if enabled
-    if config.security.enable_request_logging {
+    // Log request if enabled; with sampling, requests are logged once they complete
+    let sampler = request_guards.log_sampler.clone();
+    if config.security.enable_request_logging && !sampler.enabled() {
         info!(
             request_id = %context.request_id,
             method = %request.method,
             client_ip = %context.client_ip,
+            client_profile = context.client_profile.as_ref().map_or("", |profile| profile.id.as_str()),
+            tenant = context.tenant.as_ref().map_or("", |tenant| tenant.name.as_str()),
             "Processing RPC request"
         );
     }
 
+    let started = Instant::now();
+    let response = serve_rpc_request(
+        request,
+        &context,
+        content_length,
+        cluster_forward_header,
+        debug_validate_header,
+        rpc_use_case,
+        &config,
+        cache_middleware,
+        rate_limit_middleware,
+        &request_guards,
+    )
+    .await
+    .into_response();
+    sampler.log(&context, response.status(), started.elapsed());
+    Ok(response)
+}
+
+/// Run a JSON-RPC request through validation, limits, the cache and the daemon
+#[allow(clippy::too_many_arguments)]
+async fn serve_rpc_request(
+    request: JsonRpcRequest,
+    context: &RequestContext,
+    content_length: Option<u64>,
+    cluster_forward_header: Option<String>,
+    debug_validate_header: Option<String>,
+    rpc_use_case: Arc<ProcessRpcRequestUseCase>,
+    config: &AppConfig,
+    cache_middleware: Arc<CacheMiddleware>,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
+    guards: &RequestGuards,
+) -> warp::reply::WithStatus<Box<dyn Reply>> {
+    // Banned clients are refused before any other work
+    if let Err(response) = BaseRequestProcessor::check_ban(&request, context, guards, config).await {
+        return response;
+    }
+
+    // Refuse work while memory is short; the body counts as in flight until this returns
+    let _in_flight = match BaseRequestProcessor::check_memory_pressure(&request, context, content_length, guards, config) {
+        Ok(in_flight) => in_flight,
+        Err(response) => return response,
+    };
+
     // Validate request using base processor
-    if let Err(response) = BaseRequestProcessor::validate_request(&request, &context, &config) {
-        return Ok(response);
+    if let Err(response) = BaseRequestProcessor::validate_request(&request, context, config) {
+        record_offense(context, Offense::ValidationFailure, guards).await;
+        return response;
+    }
+
+    // Apply the client profile's method allowlist and size limit
+    if let Err(response) = BaseRequestProcessor::check_client_profile(&request, context, content_length, config) {
+        return response;
+    }
+
+    // Apply the tenant's method allowlist and daily quota
+    if let Err(response) = BaseRequestProcessor::check_tenant(&request, context, guards, config) {
+        return response;
+    }
+
+    // Refuse requests whose params ask for too much daemon work
+    let heavy = match BaseRequestProcessor::check_complexity(&request, context, guards, config) {
+        Ok(heavy) => heavy,
+        Err(response) => return response,
+    };
+
+    // Shed low-priority traffic while the daemon is degraded
+    if let Err(response) = BaseRequestProcessor::check_load_shedding(&request, context, heavy, guards, config) {
+        return response;
+    }
+
+    // Requests forwarded by another replica were already rate limited there
+    let forwarded = guards
+        .cluster
+        .as_ref()
+        .filter(|_| config.cluster.enabled)
+        .is_some_and(|cluster| cluster.is_forwarded(cluster_forward_header.as_deref()));
+    if let Some(cluster) = guards.cluster.as_ref().filter(|_| forwarded) {
+        cluster.record_received();
     }
 
     // Check rate limit using base processor
-    if let Err(response) = BaseRequestProcessor::check_rate_limit(
-        &validated_client_ip,
-        &context,
-        &request,
-        &rate_limit_middleware,
-        &config,
-    ).await {
-        return Ok(response);
+    if !forwarded {
+        if let Err(response) = BaseRequestProcessor::check_rate_limit(
+            &context.client_ip,
+            context,
+            &request,
+            &rate_limit_middleware,
+            guards,
+            config,
+        ).await {
+            return response;
+        }
     }
 
     // Check cache using base processor
     if let Ok(Some(cached_response)) = BaseRequestProcessor::check_cache(
         &request,
-        &context,
+        context,
         &cache_middleware,
-        &config,
+        config,
     ).await {
-        return Ok(cached_response);
+        SliMetrics::global().record(&request.method, RequestOutcome::CacheHit);
+        return cached_response;
     }
 
+    // With sticky routing, let the replica owning the cache key answer
+    if !forwarded {
+        if let Some(response) = BaseRequestProcessor::forward_to_owner(
+            &request,
+            context,
+            &cache_middleware,
+            guards,
+            config,
+        ).await {
+            SliMetrics::global().record(&request.method, RequestOutcome::Forwarded);
+            return response;
+        }
+    }
+
+    // In cluster mode, replicas missing the same key wait for one daemon call
+    let flight_lock = match BaseRequestProcessor::join_single_flight(
+        &request,
+        context,
+        &cache_middleware,
+        guards,
+        config,
+    ).await {
+        SingleFlight::Cached(cached_response) => {
+            SliMetrics::global().record(&request.method, RequestOutcome::Coalesced);
+            return cached_response;
+        }
+        SingleFlight::Proceed(lock) => lock,
+    };
+
+    let outcome = if config.cache.enabled && cache_middleware.should_cache_response(&request.method, 200) {
+        RequestOutcome::CacheMiss
+    } else {
+        RequestOutcome::Uncached
+    };
+    SliMetrics::global().record(&request.method, outcome);
+
     // Process request using RPC processor
-    match RpcRequestProcessor::process_rpc_request(
+    let result = RpcRequestProcessor::process_rpc_request(
         &request,
-        &context,
+        context,
         &rpc_use_case,
         &cache_middleware,
-        &config,
-    ).await {
+        config,
+    ).await;
+    if let Some(lock) = flight_lock {
+        lock.release().await;
+    }
+    match result {
         Ok(infra_response) => {
             // Create success response using RPC processor
-            Ok(RpcRequestProcessor::create_rpc_success_response(&infra_response, &config))
+            let infra_response = infra_response.without_fields(context.hidden_fields());
+            RpcRequestProcessor::create_rpc_success_response(&infra_response, context, config)
         }
         Err(e) => {
-            Ok(RpcRequestProcessor::handle_use_case_error(
-                &e,
-                &request,
-                &context,
-                &config,
-            ))
+            if let Some(offense) = Offense::for_error(&e) {
+                record_offense(context, offense, guards).await;
+            }
+            // Explain mode: attach the validation rules evaluated and the first that failed
+            let explain = debug_validate_header
+                .as_deref()
+                .is_some_and(|value| value.eq_ignore_ascii_case("true") || value == "1");
+            let trace = if explain {
+                RpcRequestProcessor::explain_validation(&request, context, &rpc_use_case).await
+            } else {
+                None
+            };
+            match trace {
+                Some(trace) => RpcRequestProcessor::handle_use_case_error_with_trace(
+                    &e,
+                    &request,
+                    context,
+                    config,
+                    &trace,
+                ),
+                None => RpcRequestProcessor::handle_use_case_error(
+                    &e,
+                    &request,
+                    context,
+                    config,
+                ),
+            }
         }
     }
 }
 
+/// Count a strike toward an automatic ban; exempt clients are never banned
+async fn record_offense(context: &RequestContext, offense: Offense, guards: &RequestGuards) {
+    if context.rate_limit_exemption.is_none() {
+        guards.ban_list.record(&context.client_ip, offense).await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,6 +316,10 @@ mod tests {
         Arc::new(RateLimitMiddleware::new(create_test_config()))
     }
 
+    fn create_test_request_guards() -> Arc<RequestGuards> {
+        Arc::new(RequestGuards::new(&create_test_config()))
+    }
+
     #[tokio::test]
     async fn test_handle_rpc_request_success() {
         let request = create_test_request();
@@ -155,10 +334,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -179,10 +365,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -203,10 +396,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -227,10 +427,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -254,10 +461,17 @@ mod tests {
                 client_ip.to_string(),
                 None,
                 None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
                 rpc_use_case,
                 config,
                 cache_middleware,
                 rate_limit_middleware,
+                create_test_request_guards(),
             ).await;
 
             assert!(result.is_ok());
@@ -284,10 +498,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -309,10 +530,17 @@ mod tests {
                 client_ip.to_string(),
                 None,
                 None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
                 rpc_use_case,
                 config,
                 cache_middleware,
                 rate_limit_middleware,
+                create_test_request_guards(),
             ).await;
 
             assert!(result.is_ok());
@@ -334,10 +562,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -358,10 +593,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -382,10 +624,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -406,10 +655,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -431,10 +687,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -455,10 +718,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -479,10 +749,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -503,10 +780,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -527,10 +811,17 @@ mod tests {
             client_ip.to_string(),
             None,
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
             rpc_use_case,
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         ).await;
 
         assert!(result.is_ok());
@@ -551,7 +842,7 @@ mod tests {
 
         assert_eq!(context.client_ip, "127.0.0.1");
         assert_eq!(context.method, "getinfo");
-        assert!(context.request_id.len() > 0);
+        assert!(!context.request_id.is_empty());
     }
 
     #[test]
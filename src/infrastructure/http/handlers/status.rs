@@ -0,0 +1,271 @@
+//! Status page handler module
+//!
+//! Renders `GET /status`, a single self-contained HTML page (inline CSS, no
+//! scripts or external assets) summarising health, chain height, version,
+//! cache hit rate and request rate for operators without a dashboard.
+
+use crate::{
+    application::{
+        services::{health_history_service::ComponentUptime, ComponentState, HealthHistoryService},
+        use_cases::{GetMetricsUseCase, HealthCheckUseCase},
+    },
+    config::AppConfig,
+    domain::{
+        health::HealthStatus,
+        rpc::{ClientInfo, RpcRequest},
+    },
+    infrastructure::adapters::ExternalRpcAdapter,
+    middleware::{cache::CacheMiddleware, etag::etag_response, security_headers::SecurityHeadersMiddleware},
+};
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::Reply;
+
+/// How long the page waits for `getblockcount` before showing the height as unavailable
+const CHAIN_HEIGHT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Everything the status page reads from
+pub struct StatusPageSources {
+    pub health_use_case: Arc<HealthCheckUseCase>,
+    pub metrics_use_case: Arc<GetMetricsUseCase>,
+    pub rpc: Arc<ExternalRpcAdapter>,
+    pub cache: Arc<CacheMiddleware>,
+    pub health_history: Arc<HealthHistoryService>,
+}
+
+/// Values shown on the status page
+#[derive(Debug, Clone)]
+pub struct StatusView {
+    pub title: String,
+    pub refresh_seconds: u64,
+    pub status: HealthStatus,
+    pub version: String,
+    pub uptime_seconds: u64,
+    /// `None` when the daemon did not answer
+    pub chain_height: Option<u64>,
+    /// `None` when caching is disabled or nothing has been looked up yet
+    pub cache_hit_rate: Option<f64>,
+    pub requests_per_minute: f64,
+    pub total_requests: u64,
+    /// Empty unless `[health_history]` is enabled
+    pub components: Vec<ComponentUptime>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl StatusView {
+    /// Gather the current values from `sources`
+    pub async fn collect(sources: &StatusPageSources, config: &AppConfig) -> Self {
+        let status = match sources.health_use_case.execute(Some(sources.rpc.clone())).await {
+            Ok(health) => health.status,
+            Err(_) => HealthStatus::Unhealthy,
+        };
+        let chain_height = match tokio::time::timeout(CHAIN_HEIGHT_TIMEOUT, sources.rpc.send_request(&block_count_request())).await {
+            Ok(Ok(response)) => response.result.and_then(|height| height.as_u64()),
+            _ => None,
+        };
+        let cache_hit_rate = if config.cache.enabled {
+            sources.cache.get_stats().await.hit_rate()
+        } else {
+            None
+        };
+        let components = if config.health_history.enabled {
+            sources.health_history.history(0).await.components
+        } else {
+            Vec::new()
+        };
+        let metrics = sources.metrics_use_case.execute();
+
+        Self {
+            title: config.status_page.title.clone(),
+            refresh_seconds: config.status_page.refresh_seconds,
+            status,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_seconds: metrics["uptime_seconds"].as_u64().unwrap_or(0),
+            chain_height,
+            cache_hit_rate,
+            requests_per_minute: metrics["requests_per_minute"].as_f64().unwrap_or(0.0),
+            total_requests: metrics["total_requests"].as_u64().unwrap_or(0),
+            components,
+            generated_at: Utc::now(),
+        }
+    }
+}
+
+/// Handle `GET /status` (404 unless `[status_page]` is enabled)
+pub async fn handle_status_page(
+    sources: Arc<StatusPageSources>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    if !config.status_page.enabled {
+        return Err(warp::reject::not_found());
+    }
+    let view = StatusView::collect(&sources, &config).await;
+    Ok(etag_response(
+        render_status_page(&view),
+        "text/html; charset=utf-8",
+        None,
+        &SecurityHeadersMiddleware::new(config),
+    ))
+}
+
+/// Render `view` as a standalone HTML document
+pub fn render_status_page(view: &StatusView) -> String {
+    let status_class = match view.status {
+        HealthStatus::Healthy => "up",
+        HealthStatus::Degraded => "warn",
+        HealthStatus::Unhealthy => "down",
+    };
+    let title = escape_html(&view.title);
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    if view.refresh_seconds > 0 {
+        let _ = writeln!(html, "<meta http-equiv=\"refresh\" content=\"{}\">", view.refresh_seconds);
+    }
+    let _ = writeln!(html, "<title>{}</title>", title);
+    html.push_str(STYLE);
+    html.push_str("</head>\n<body>\n");
+    let _ = writeln!(html, "<h1>{}</h1>", title);
+    let _ = writeln!(html, "<p class=\"status {}\">{}</p>", status_class, view.status);
+
+    html.push_str("<table>\n");
+    let rows = [
+        ("Chain height", view.chain_height.map_or("unavailable".to_string(), |h| h.to_string())),
+        ("Version", escape_html(&view.version)),
+        ("Uptime", format_uptime(view.uptime_seconds)),
+        ("Cache hit rate", view.cache_hit_rate.map_or("n/a".to_string(), |rate| format!("{:.1}%", rate * 100.0))),
+        ("Request rate", format!("{:.2} req/min", view.requests_per_minute)),
+        ("Total requests", view.total_requests.to_string()),
+    ];
+    for (label, value) in rows {
+        let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", label, value);
+    }
+    html.push_str("</table>\n");
+
+    if !view.components.is_empty() {
+        html.push_str("<h2>Components</h2>\n<table>\n<tr><th>Component</th><th>State</th><th>Since</th><th>24h uptime</th></tr>\n");
+        for component in &view.components {
+            let (class, state) = match component.state {
+                ComponentState::Up => ("up", "up"),
+                ComponentState::Down => ("down", "down"),
+            };
+            let uptime = component
+                .uptime_percent
+                .get("24h")
+                .copied()
+                .flatten()
+                .map_or("n/a".to_string(), |percent| format!("{:.2}%", percent));
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"{}\">{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&component.component),
+                class,
+                state,
+                component.since.format("%Y-%m-%d %H:%M:%S UTC"),
+                uptime,
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let _ = writeln!(html, "<footer>Generated {}</footer>", view.generated_at.format("%Y-%m-%d %H:%M:%S UTC"));
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+const STYLE: &str = "<style>\n\
+body{font-family:system-ui,sans-serif;max-width:40rem;margin:2rem auto;padding:0 1rem;color:#222}\n\
+table{border-collapse:collapse;width:100%;margin-bottom:1.5rem}\n\
+th,td{text-align:left;padding:.4rem .6rem;border-bottom:1px solid #ddd}\n\
+.status{display:inline-block;padding:.3rem .8rem;border-radius:4px;font-weight:bold;text-transform:uppercase}\n\
+p.up{background:#d4edda}p.warn{background:#fff3cd}p.down{background:#f8d7da}\n\
+td.up{color:#155724}td.down{color:#721c24}\n\
+footer{color:#777;font-size:.85rem}\n\
+</style>\n";
+
+/// `getblockcount` request sent when rendering the page
+fn block_count_request() -> RpcRequest {
+    RpcRequest {
+        method: "getblockcount".to_string(),
+        parameters: Some(serde_json::json!([])),
+        id: Some(serde_json::json!("status-page")),
+        client_info: ClientInfo {
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: Some("status-page".to_string()),
+            auth_token: None,
+            timestamp: Utc::now(),
+            request_id: None,
+        },
+        upstream: None,
+    }
+}
+
+fn format_uptime(seconds: u64) -> String {
+    format!("{}d {}h {}m", seconds / 86400, (seconds % 86400) / 3600, (seconds % 3600) / 60)
+}
+
+fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn view() -> StatusView {
+        StatusView {
+            title: "Pool <RPC>".to_string(),
+            refresh_seconds: 30,
+            status: HealthStatus::Degraded,
+            version: "1.0.0".to_string(),
+            uptime_seconds: 90061,
+            chain_height: Some(3_100_000),
+            cache_hit_rate: Some(0.875),
+            requests_per_minute: 12.5,
+            total_requests: 42,
+            components: Vec::new(),
+            generated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_status_page() {
+        let html = render_status_page(&view());
+        assert!(html.contains("<title>Pool &lt;RPC&gt;</title>"));
+        assert!(html.contains("<meta http-equiv=\"refresh\" content=\"30\">"));
+        assert!(html.contains("<p class=\"status warn\">degraded</p>"));
+        assert!(html.contains("<td>3100000</td>"));
+        assert!(html.contains("<td>87.5%</td>"));
+        assert!(html.contains("<td>12.50 req/min</td>"));
+        assert!(html.contains("<td>1d 1h 1m</td>"));
+        assert!(!html.contains("Components"));
+        assert!(!html.contains("http://") && !html.contains("https://") && !html.contains("<script"));
+    }
+
+    #[test]
+    fn test_render_components_and_unavailable_values() {
+        let mut view = view();
+        view.refresh_seconds = 0;
+        view.chain_height = None;
+        view.cache_hit_rate = None;
+        view.components = vec![ComponentUptime {
+            component: "daemon".to_string(),
+            state: ComponentState::Down,
+            since: Utc::now(),
+            uptime_percent: BTreeMap::from([("24h".to_string(), Some(99.5))]),
+        }];
+        let html = render_status_page(&view);
+        assert!(!html.contains("http-equiv"));
+        assert!(html.contains("<td>unavailable</td>"));
+        assert!(html.contains("<td>n/a</td>"));
+        assert!(html.contains("<td>daemon</td><td class=\"down\">down</td>"));
+        assert!(html.contains("<td>99.50%</td>"));
+    }
+}
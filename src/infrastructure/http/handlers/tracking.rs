@@ -0,0 +1,66 @@
+//! Transaction tracking handlers
+
+use std::sync::Arc;
+
+use warp::Reply;
+
+use crate::application::services::{TrackTxRequest, TxTrackingService};
+use crate::config::AppConfig;
+use crate::infrastructure::http::utils::extract_and_validate_client_ip;
+use crate::middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware};
+use crate::shared::error::AppError;
+
+/// Handle `POST /track/tx` registrations
+pub async fn handle_track_tx(
+    body: TrackTxRequest,
+    client_ip: String,
+    service: Arc<TxTrackingService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    if !config.tx_tracking.enabled {
+        return Ok(not_found("transaction tracking is disabled", &security_middleware));
+    }
+    let client_ip = extract_and_validate_client_ip(&client_ip, &config);
+    let response: Box<dyn Reply> = match service.register(&client_ip, body).await {
+        Ok(tracked) => Box::new(warp::reply::with_status(
+            create_json_response_with_security_headers(&tracked, &security_middleware),
+            warp::http::StatusCode::ACCEPTED,
+        )),
+        Err(e) => {
+            let status = match e {
+                AppError::Validation(_) => warp::http::StatusCode::BAD_REQUEST,
+                _ => e.http_status_code(),
+            };
+            Box::new(warp::reply::with_status(
+                create_json_response_with_security_headers(&serde_json::json!({ "error": e.to_string() }), &security_middleware),
+                status,
+            ))
+        }
+    };
+    Ok(response)
+}
+
+/// Handle `GET /track/tx/{id}` lookups
+pub async fn handle_tracked_tx(
+    id: String,
+    service: Arc<TxTrackingService>,
+    config: AppConfig,
+) -> Result<impl Reply, warp::reject::Rejection> {
+    let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+    if !config.tx_tracking.enabled {
+        return Ok(not_found("transaction tracking is disabled", &security_middleware));
+    }
+    let response: Box<dyn Reply> = match service.get(&id).await {
+        Some(tracked) => Box::new(create_json_response_with_security_headers(&tracked, &security_middleware)),
+        None => not_found("unknown tracking id", &security_middleware),
+    };
+    Ok(response)
+}
+
+fn not_found(error: &str, security_middleware: &SecurityHeadersMiddleware) -> Box<dyn Reply> {
+    Box::new(warp::reply::with_status(
+        create_json_response_with_security_headers(&serde_json::json!({ "error": error }), security_middleware),
+        warp::http::StatusCode::NOT_FOUND,
+    ))
+}
@@ -0,0 +1,546 @@
+//! Hardened connection handling for the listener
+//!
+//! `warp::serve` runs hyper with its defaults, which leave a standalone
+//! deployment (no nginx in front) open to slow clients: a slowloris client
+//! holds a connection by trickling header bytes, and a client that stops
+//! reading pins its response in memory. Connections are served here with the
+//! limits from `[server]`: request head size and header count, a deadline for
+//! the request head, read and write stall timeouts and an idle timeout for
+//! keep-alive connections.
+//!
+//! HTTP/2 is accepted with prior knowledge (h2c) next to HTTP/1.1 so clients
+//! issuing many concurrent calls can multiplex them over one connection. TLS,
+//! and with it ALPN negotiation, stays with the reverse proxy.
+//!
+//! Once the process starts draining (see [`Drain`]) every listener stops
+//! accepting and open connections are shut down gracefully: in-flight
+//! requests complete, then HTTP/1.1 connections close and HTTP/2 clients get
+//! a GOAWAY.
+
+use crate::config::app_config::ServerConfig;
+use hyper::body::{Body, Incoming};
+use hyper::{Request, Response, Version};
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+#[cfg(unix)]
+use std::path::Path;
+use tokio::time::Sleep;
+use tracing::{debug, warn};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Per-connection limits taken from `[server]`
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionLimits {
+    pub max_header_bytes: usize,
+    pub max_headers: usize,
+    pub header_read_timeout: Duration,
+    pub read_timeout: Duration,
+    pub write_timeout: Duration,
+    pub idle_timeout: Duration,
+    pub http2: bool,
+    pub http2_max_concurrent_streams: u32,
+}
+
+impl ConnectionLimits {
+    pub fn from_config(server: &ServerConfig) -> Self {
+        Self {
+            max_header_bytes: server.max_header_bytes,
+            max_headers: server.max_headers,
+            header_read_timeout: Duration::from_secs(server.header_read_timeout_seconds),
+            read_timeout: Duration::from_secs(server.read_timeout_seconds),
+            write_timeout: Duration::from_secs(server.write_timeout_seconds),
+            idle_timeout: Duration::from_secs(server.idle_timeout_seconds),
+            http2: server.http2_enabled,
+            http2_max_concurrent_streams: server.http2_max_concurrent_streams,
+        }
+    }
+}
+
+/// Accept connections from `listener` and serve each with `service` under `limits`
+pub async fn serve<S, B>(listener: TcpListener, service: S, limits: ConnectionLimits)
+where
+    S: tower_service::Service<Request<Incoming>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = Drain::global().started() => return,
+        };
+        match accepted {
+            Ok((stream, peer)) => spawn_connection(stream, peer.to_string(), service.clone(), limits),
+            Err(e) => accept_failed(e).await,
+        }
+    }
+}
+
+/// Accept connections on a Unix domain socket and serve each like [`serve`]
+#[cfg(unix)]
+pub async fn serve_unix<S, B>(listener: UnixListener, service: S, limits: ConnectionLimits)
+where
+    S: tower_service::Service<Request<Incoming>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = Drain::global().started() => return,
+        };
+        match accepted {
+            Ok((stream, _)) => spawn_connection(stream, "unix".to_string(), service.clone(), limits),
+            Err(e) => accept_failed(e).await,
+        }
+    }
+}
+
+/// A listening socket the server accepts connections on
+pub enum BoundListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl std::fmt::Display for BoundListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoundListener::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) => write!(f, "tcp {}", addr),
+                Err(_) => write!(f, "tcp"),
+            },
+            #[cfg(unix)]
+            BoundListener::Unix(listener) => match listener.local_addr().ok().and_then(|a| a.as_pathname().map(Path::to_path_buf)) {
+                Some(path) => write!(f, "unix {}", path.display()),
+                None => write!(f, "unix"),
+            },
+        }
+    }
+}
+
+/// Bind `addr` with `SO_REUSEPORT`, so a new process can listen on it next to this one
+#[cfg(unix)]
+pub fn bind_tcp_reuse_port(addr: std::net::SocketAddr) -> io::Result<TcpListener> {
+    let socket = if addr.is_ipv4() { tokio::net::TcpSocket::new_v4()? } else { tokio::net::TcpSocket::new_v6()? };
+    socket.set_reuseaddr(true)?;
+    socket.set_reuseport(true)?;
+    socket.bind(addr)?;
+    socket.listen(1024)
+}
+
+/// Serve `service` on every listener until all of them stop
+pub async fn serve_all<S, B>(listeners: Vec<BoundListener>, service: S, limits: ConnectionLimits)
+where
+    S: tower_service::Service<Request<Incoming>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    let tasks = listeners.into_iter().map(|listener| match listener {
+        BoundListener::Tcp(listener) => tokio::spawn(serve(listener, service.clone(), limits)),
+        #[cfg(unix)]
+        BoundListener::Unix(listener) => tokio::spawn(serve_unix(listener, service.clone(), limits)),
+    });
+    futures::future::join_all(tasks.collect::<Vec<_>>()).await;
+}
+
+/// Bind a Unix domain socket at `path` with permission bits `mode`
+///
+/// A socket file left behind by a previous run is removed first; any other
+/// kind of file at `path` is an error rather than being deleted.
+#[cfg(unix)]
+pub fn bind_unix(path: &Path, mode: u32) -> io::Result<UnixListener> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    Ok(listener)
+}
+
+async fn accept_failed(error: io::Error) {
+    // Usually fd exhaustion; back off instead of spinning
+    warn!(error = %error, "Failed to accept connection");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+}
+
+fn spawn_connection<T, S, B>(stream: T, peer: String, service: S, limits: ConnectionLimits)
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: tower_service::Service<Request<Incoming>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let open = InFlight::enter(&Drain::global().connections);
+    let io = TokioIo::new(GuardedStream::new(stream, limits, in_flight.clone()));
+    let handler = hyper::service::service_fn(move |request: Request<Incoming>| {
+        ProtocolMetrics::global().record(request.version());
+        let mut service = service.clone();
+        let guard = InFlight::enter(&in_flight);
+        async move {
+            let response = service.call(request).await;
+            drop(guard);
+            response
+        }
+    });
+    tokio::spawn(async move {
+        let mut builder = auto::Builder::new(TokioExecutor::new());
+        builder
+            .http1()
+            .timer(TokioTimer::new())
+            .header_read_timeout(limits.header_read_timeout)
+            .max_buf_size(limits.max_header_bytes)
+            .max_headers(limits.max_headers)
+            .keep_alive(true);
+        builder
+            .http2()
+            .timer(TokioTimer::new())
+            .max_concurrent_streams(limits.http2_max_concurrent_streams)
+            .max_header_list_size(limits.max_header_bytes as u32);
+        let builder = if limits.http2 { builder } else { builder.http1_only() };
+        let connection = builder.serve_connection(io, handler);
+        tokio::pin!(connection);
+        let result = tokio::select! {
+            result = connection.as_mut() => result,
+            _ = Drain::global().started() => {
+                connection.as_mut().graceful_shutdown();
+                connection.await
+            }
+        };
+        if let Err(e) = result {
+            debug!(peer = %peer, error = %e, "Connection closed with error");
+        }
+        drop(open);
+    });
+}
+
+/// Process-wide drain switch and open connection count
+#[derive(Debug)]
+pub struct Drain {
+    draining: watch::Sender<bool>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl Drain {
+    /// Drain state shared by every listener and connection
+    pub fn global() -> &'static Drain {
+        static DRAIN: OnceLock<Drain> = OnceLock::new();
+        DRAIN.get_or_init(|| Drain { draining: watch::channel(false).0, connections: Arc::new(AtomicUsize::new(0)) })
+    }
+
+    /// Stop accepting and close connections once their requests finish
+    pub fn start(&self) {
+        self.draining.send_replace(true);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.draining.borrow()
+    }
+
+    /// Connections still open
+    pub fn open_connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// Wait until every connection has closed or `timeout` passes; true when none is left
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.open_connections() > 0 {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        true
+    }
+
+    /// Resolves once draining has started
+    async fn started(&self) {
+        let mut draining = self.draining.subscribe();
+        // The sender lives in a static, so the channel never closes
+        let _ = draining.wait_for(|draining| *draining).await;
+    }
+}
+
+/// Requests served per HTTP protocol version
+#[derive(Debug, Default)]
+pub struct ProtocolMetrics {
+    http10: AtomicU64,
+    http11: AtomicU64,
+    http2: AtomicU64,
+}
+
+impl ProtocolMetrics {
+    /// Counters shared by every connection
+    pub fn global() -> &'static ProtocolMetrics {
+        static METRICS: OnceLock<ProtocolMetrics> = OnceLock::new();
+        METRICS.get_or_init(ProtocolMetrics::default)
+    }
+
+    fn record(&self, version: Version) {
+        let counter = match version {
+            Version::HTTP_2 => &self.http2,
+            Version::HTTP_10 => &self.http10,
+            _ => &self.http11,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Request counts by protocol in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP verus_http_requests_by_protocol_total Requests served per HTTP protocol version\n");
+        out.push_str("# TYPE verus_http_requests_by_protocol_total counter\n");
+        for (protocol, counter) in [("http/1.0", &self.http10), ("http/1.1", &self.http11), ("h2", &self.http2)] {
+            out.push_str(&format!(
+                "verus_http_requests_by_protocol_total{{protocol=\"{}\"}} {}\n",
+                protocol,
+                counter.load(Ordering::Relaxed)
+            ));
+        }
+        out
+    }
+}
+
+/// Marks a request as being served on its connection
+struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    fn enter(counter: &Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter.clone())
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Stream that fails reads and writes stalled longer than the configured timeouts
+///
+/// A read waits at most `idle_timeout` when no request is being served on the
+/// connection and `read_timeout` while one is; a write waits at most
+/// `write_timeout`. Any progress resets the deadline.
+struct GuardedStream<S> {
+    inner: S,
+    limits: ConnectionLimits,
+    in_flight: Arc<AtomicUsize>,
+    read_deadline: Option<Pin<Box<Sleep>>>,
+    write_deadline: Option<Pin<Box<Sleep>>>,
+}
+
+impl<S> GuardedStream<S> {
+    fn new(inner: S, limits: ConnectionLimits, in_flight: Arc<AtomicUsize>) -> Self {
+        Self { inner, limits, in_flight, read_deadline: None, write_deadline: None }
+    }
+}
+
+/// Poll `deadline` (armed with `limit` on first use) after the inner stream returned `Pending`
+fn poll_stalled(deadline: &mut Option<Pin<Box<Sleep>>>, limit: Duration, cx: &mut Context<'_>, what: &str) -> Poll<io::Error> {
+    let sleep = deadline.get_or_insert_with(|| Box::pin(tokio::time::sleep(limit)));
+    match sleep.as_mut().poll(cx) {
+        Poll::Ready(()) => {
+            *deadline = None;
+            Poll::Ready(io::Error::new(io::ErrorKind::TimedOut, format!("{} stalled for {:?}", what, limit)))
+        }
+        Poll::Pending => Poll::Pending,
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for GuardedStream<S> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll_read(cx, buf) {
+            Poll::Ready(result) => {
+                this.read_deadline = None;
+                Poll::Ready(result)
+            }
+            Poll::Pending => {
+                let limit = if this.in_flight.load(Ordering::SeqCst) > 0 {
+                    this.limits.read_timeout
+                } else {
+                    this.limits.idle_timeout
+                };
+                poll_stalled(&mut this.read_deadline, limit, cx, "read").map(Err)
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for GuardedStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll_write(cx, buf) {
+            Poll::Ready(result) => {
+                this.write_deadline = None;
+                Poll::Ready(result)
+            }
+            Poll::Pending => poll_stalled(&mut this.write_deadline, this.limits.write_timeout, cx, "write").map(Err),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll_flush(cx) {
+            Poll::Ready(result) => {
+                this.write_deadline = None;
+                Poll::Ready(result)
+            }
+            Poll::Pending => poll_stalled(&mut this.write_deadline, this.limits.write_timeout, cx, "flush").map(Err),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[io::IoSlice<'_>]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll_write_vectored(cx, bufs) {
+            Poll::Ready(result) => {
+                this.write_deadline = None;
+                Poll::Ready(result)
+            }
+            Poll::Pending => poll_stalled(&mut this.write_deadline, this.limits.write_timeout, cx, "write").map(Err),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn limits() -> ConnectionLimits {
+        ConnectionLimits {
+            max_header_bytes: 8192,
+            max_headers: 8,
+            header_read_timeout: Duration::from_secs(1),
+            read_timeout: Duration::from_millis(200),
+            write_timeout: Duration::from_millis(50),
+            idle_timeout: Duration::from_millis(50),
+            http2: true,
+            http2_max_concurrent_streams: 16,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stalled_read_uses_idle_then_read_timeout() {
+        let (client, server) = tokio::io::duplex(64);
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let mut guarded = GuardedStream::new(server, limits(), in_flight.clone());
+        let mut buf = [0u8; 8];
+
+        let err = guarded.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        // While a request is served, the longer read timeout applies
+        let _guard = InFlight::enter(&in_flight);
+        let mut client = client;
+        let reader = tokio::spawn(async move { guarded.read(&mut buf).await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        client.write_all(b"ok").await.unwrap();
+        assert_eq!(reader.await.unwrap().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_write_to_client_that_stopped_reading_times_out() {
+        let (_client, server) = tokio::io::duplex(16);
+        let mut guarded = GuardedStream::new(server, limits(), Arc::new(AtomicUsize::new(0)));
+        let err = guarded.write_all(&[0u8; 64]).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_bind_unix_replaces_stale_socket_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("verus-rpc-listener-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rpc.sock");
+
+        drop(bind_unix(&path, 0o660).unwrap());
+        let listener = bind_unix(&path, 0o600).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        drop(listener);
+
+        let regular = dir.join("not-a-socket");
+        std::fs::write(&regular, b"keep").unwrap();
+        assert!(bind_unix(&regular, 0o660).is_err());
+        assert_eq!(std::fs::read(&regular).unwrap(), b"keep");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_reuse_port_lets_a_second_process_bind() {
+        let first = bind_tcp_reuse_port("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = first.local_addr().unwrap();
+        let second = bind_tcp_reuse_port(addr).unwrap();
+        assert_eq!(second.local_addr().unwrap(), addr);
+        assert!(TcpListener::bind(addr).await.is_err());
+    }
+
+    #[test]
+    fn test_protocol_metrics() {
+        let metrics = ProtocolMetrics::default();
+        metrics.record(Version::HTTP_2);
+        metrics.record(Version::HTTP_2);
+        metrics.record(Version::HTTP_11);
+        let text = metrics.prometheus_text();
+        assert!(text.contains("verus_http_requests_by_protocol_total{protocol=\"h2\"} 2"));
+        assert!(text.contains("verus_http_requests_by_protocol_total{protocol=\"http/1.1\"} 1"));
+        assert!(text.contains("verus_http_requests_by_protocol_total{protocol=\"http/1.0\"} 0"));
+    }
+
+    #[test]
+    fn test_limits_from_config() {
+        let server = crate::config::AppConfig::default().server;
+        let limits = ConnectionLimits::from_config(&server);
+        assert_eq!(limits.max_header_bytes, server.max_header_bytes);
+        assert_eq!(limits.idle_timeout, Duration::from_secs(server.idle_timeout_seconds));
+    }
+}
@@ -88,6 +88,7 @@ mod tests {
             miner_reputation: Some(0.85),
             timestamp: chrono::Utc::now(),
             error,
+            cached: false,
         }
     }
 
@@ -272,6 +273,7 @@ mod tests {
             miner_reputation: None,
             timestamp: chrono::Utc::now(),
             error: None,
+            cached: false,
         };
         let request_id = "test_request_none";
         let context_request_id = "context_none";
@@ -132,7 +132,9 @@ mod tests {
                 user_agent: Some("test_agent".to_string()),
                 auth_token: None,
                 timestamp: chrono::Utc::now(),
+                request_id: None,
             },
+            upstream: None,
         }
     }
 
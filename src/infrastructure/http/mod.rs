@@ -5,12 +5,14 @@
 
 pub mod models;
 pub mod server;
+pub mod listener;
 pub mod utils;
 pub mod responses;
 pub mod handlers;
 pub mod processors;
 pub mod routes;
 pub mod mining_pool;
+pub mod strict_json;
 
 pub use models::{JsonRpcRequest, JsonRpcResponse, JsonRpcError, RequestContext};
 pub use server::HttpServer;
@@ -6,6 +6,12 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use validator::Validate;
+use crate::config::app_config::ClientProfileConfig;
+use crate::infrastructure::adapters::{ExemptionReason, Tenant};
+use crate::middleware::response_signing::ResponseSigner;
+use crate::shared::error::AppError;
+use crate::shared::i18n::{self, MessageCatalog};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// HTTP JSON-RPC request structure (infrastructure concern)
@@ -84,6 +90,23 @@ pub struct RequestContext {
 
     /// Authorization bearer token if provided
     pub auth_token: Option<String>,
+
+    /// Negotiated locale for error messages
+    pub locale: Option<String>,
+
+    /// Catalog the locale was negotiated against
+    pub message_catalog: Option<Arc<MessageCatalog>>,
+
+    /// Client profile matched by API key or JWT subject
+    pub client_profile: Option<ClientProfileConfig>,
+
+    /// Tenant matched by API key or JWT audience
+    pub tenant: Option<Tenant>,
+
+    /// Rate limit exemption matched by network, API key or JWT permission
+    pub rate_limit_exemption: Option<ExemptionReason>,
+    /// Signer for the response, when `[response_signing]` covers the request
+    pub response_signer: Option<Arc<ResponseSigner>>,
 }
 
 /// HTTP rate limit information (infrastructure concern)
@@ -149,6 +172,14 @@ impl JsonRpcResponse {
             id,
         }
     }
+
+    /// Remove dotted field paths from the result
+    pub fn without_fields(mut self, paths: &[String]) -> Self {
+        if let Some(result) = self.result.as_mut() {
+            crate::infrastructure::adapters::client_profiles::remove_fields(result, paths);
+        }
+        self
+    }
 }
 
 impl JsonRpcError {
@@ -203,6 +234,12 @@ impl RequestContext {
             method,
             params,
             auth_token: None,
+            locale: None,
+            message_catalog: None,
+            client_profile: None,
+            tenant: None,
+            rate_limit_exemption: None,
+            response_signer: None,
         }
     }
     
@@ -217,6 +254,52 @@ impl RequestContext {
         self.auth_token = Some(auth_token);
         self
     }
+
+    /// Set the locale negotiated from an Accept-Language header against `catalog`
+    pub fn with_accept_language(mut self, accept_language: &str, catalog: Option<&Arc<MessageCatalog>>) -> Self {
+        self.locale = catalog.and_then(|catalog| catalog.negotiate(accept_language));
+        self.message_catalog = catalog.cloned();
+        self
+    }
+
+    /// Message `key` in this request's locale, or `fallback`
+    pub fn localize(&self, key: &str, args: &[(&str, String)], fallback: &str) -> String {
+        i18n::localize(self.message_catalog.as_deref(), self.locale.as_deref(), key, args, fallback)
+    }
+
+    /// Message for `error` in this request's locale
+    pub fn localize_error(&self, error: &AppError) -> String {
+        i18n::localize_error(self.message_catalog.as_deref(), self.locale.as_deref(), error)
+    }
+
+    /// Apply a client profile's overrides to this request
+    pub fn with_client_profile(mut self, profile: ClientProfileConfig) -> Self {
+        self.client_profile = Some(profile);
+        self
+    }
+
+    /// Apply a tenant's policy to this request
+    pub fn with_tenant(mut self, tenant: Tenant) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Let this request bypass rate limiting and profile budgets
+    pub fn with_rate_limit_exemption(mut self, reason: ExemptionReason) -> Self {
+        self.rate_limit_exemption = Some(reason);
+        self
+    }
+
+    /// Sign the response to this request with `signer`
+    pub fn with_signed_response(mut self, signer: Arc<ResponseSigner>) -> Self {
+        self.response_signer = Some(signer);
+        self
+    }
+
+    /// Dotted paths to strip from results returned to this client
+    pub fn hidden_fields(&self) -> &[String] {
+        self.client_profile.as_ref().map(|profile| profile.hidden_fields.as_slice()).unwrap_or(&[])
+    }
 }
 
 fn default_jsonrpc_version() -> String {
@@ -5,19 +5,37 @@
 
 use crate::{
     config::AppConfig,
+    infrastructure::adapters::{
+        client_profiles, ClusterLock, InFlightRequest, KeyOwner, Offense, TenantRejection,
+    },
     infrastructure::http::{
         models::{JsonRpcRequest, JsonRpcResponse, RequestContext},
+        processors::RequestGuards,
         utils::extract_and_validate_client_ip,
     },
     middleware::{
         cache::CacheMiddleware, 
+        complexity::ComplexityVerdict,
         rate_limit::RateLimitMiddleware, 
         security_headers::{SecurityHeadersMiddleware, create_json_response_with_security_headers},
     },
+    shared::error::AppError,
 };
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{error, info, warn, debug};
 
+/// How often a waiting replica re-checks the cache for another replica's result
+const SINGLE_FLIGHT_POLL: Duration = Duration::from_millis(50);
+
+/// Outcome of joining the cluster-wide single flight for a cache miss
+pub enum SingleFlight {
+    /// Another replica cached the response while we waited
+    Cached(warp::reply::WithStatus<Box<dyn warp::Reply>>),
+    /// Call the daemon, then release the lock (if one was taken) once the response is cached
+    Proceed(Option<ClusterLock>),
+}
+
 /// Base request processor that handles common processing patterns
 pub struct BaseRequestProcessor;
 
@@ -49,6 +67,31 @@ impl BaseRequestProcessor {
         (validated_client_ip, context)
     }
 
+    /// Attach the client profile, tenant and rate limit exemption matching the caller's credentials
+    ///
+    /// The bearer token is verified once, revocation included, and an invalid
+    /// token simply matches nothing; rejecting it is left to authorization.
+    pub async fn identify_client(
+        mut context: RequestContext,
+        api_key_header: Option<&str>,
+        guards: &RequestGuards,
+    ) -> RequestContext {
+        let claims = match context.auth_token.as_deref() {
+            Some(authorization) => guards.authentication.verified_claims(authorization).await.ok(),
+            None => None,
+        };
+        if let Some(profile) = guards.client_profiles.resolve(api_key_header, claims.as_ref()) {
+            context = context.with_client_profile(profile.clone());
+        }
+        if let Some(tenant) = guards.tenants.resolve(api_key_header, claims.as_ref()) {
+            context = context.with_tenant(tenant.clone());
+        }
+        if let Some(reason) = guards.rate_limit_exemptions.resolve(&context.client_ip, api_key_header, claims.as_ref()) {
+            context = context.with_rate_limit_exemption(reason);
+        }
+        context
+    }
+
     /// Validate the request and return error response if validation fails
     pub fn validate_request(
         request: &JsonRpcRequest,
@@ -61,8 +104,9 @@ impl BaseRequestProcessor {
                 error = %e,
                 "Request validation failed"
             );
+            let message = context.localize("invalid_request", &[], "Invalid request");
             return Err(Self::create_error_response_with_security_headers(
-                "Invalid request",
+                &message,
                 &request.id,
                 warp::http::StatusCode::BAD_REQUEST,
                 config,
@@ -71,25 +115,266 @@ impl BaseRequestProcessor {
         Ok(())
     }
 
+    /// Refuse requests from banned clients (403 + Retry-After)
+    ///
+    /// Rate-limit-exempt clients are never checked.
+    pub async fn check_ban(
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        guards: &RequestGuards,
+        config: &AppConfig,
+    ) -> Result<(), warp::reply::WithStatus<Box<dyn warp::Reply>>> {
+        if context.rate_limit_exemption.is_some() {
+            return Ok(());
+        }
+        let Some(ban) = guards.ban_list.check(&context.client_ip).await else {
+            return Ok(());
+        };
+        let until = chrono::DateTime::from_timestamp(ban.expires_at, 0)
+            .map(|until| until.to_rfc3339())
+            .unwrap_or_default();
+        warn!(
+            request_id = %context.request_id,
+            method = %request.method,
+            client_ip = %context.client_ip,
+            banned_until = %until,
+            "Request refused from banned client"
+        );
+        let error = AppError::Banned { until: until.clone(), reason: ban.reason.clone() };
+        let error_response = JsonRpcResponse::error(
+            crate::infrastructure::http::models::JsonRpcError::new(
+                -403,
+                context.localize_error(&error),
+                Some(serde_json::json!({ "banned_until": until, "reason": ban.reason })),
+            ),
+            request.id.clone(),
+        );
+        let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+        let response = create_json_response_with_security_headers(&error_response, &security_middleware);
+        let retry_after = (ban.expires_at - chrono::Utc::now().timestamp()).max(1);
+        let response: Box<dyn warp::Reply> =
+            Box::new(warp::reply::with_header(response, "retry-after", retry_after.to_string()));
+        Err(warp::reply::with_status(response, error.http_status_code()))
+    }
+
+    /// Shed low-priority requests while the daemon is degraded (503 + Retry-After)
+    pub fn check_load_shedding(
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        heavy: bool,
+        guards: &RequestGuards,
+        config: &AppConfig,
+    ) -> Result<(), warp::reply::WithStatus<Box<dyn warp::Reply>>> {
+        let shedder = &guards.load_shedder;
+        if !shedder.should_shed_request(&request.method, context.auth_token.is_some(), heavy) {
+            return Ok(());
+        }
+        warn!(
+            request_id = %context.request_id,
+            method = %request.method,
+            client_ip = %context.client_ip,
+            "Request shed under upstream load"
+        );
+        let message = context.localize(
+            "service_overloaded",
+            &[],
+            "Service temporarily overloaded, retry later",
+        );
+        let error_response = JsonRpcResponse::error(
+            crate::infrastructure::http::models::JsonRpcError::internal_error(&message),
+            request.id.clone(),
+        );
+        let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+        let response = create_json_response_with_security_headers(&error_response, &security_middleware);
+        let response: Box<dyn warp::Reply> = Box::new(warp::reply::with_header(
+            response,
+            "retry-after",
+            shedder.retry_after_seconds().to_string(),
+        ));
+        Err(warp::reply::with_status(response, warp::http::StatusCode::SERVICE_UNAVAILABLE))
+    }
+
+    /// Refuse requests while memory is short; the returned guard counts the body as in flight
+    pub fn check_memory_pressure(
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        content_length: Option<u64>,
+        guards: &RequestGuards,
+        config: &AppConfig,
+    ) -> Result<InFlightRequest, warp::reply::WithStatus<Box<dyn warp::Reply>>> {
+        let guard = &guards.memory_guard;
+        let bytes = content_length.map_or(0, |length| usize::try_from(length).unwrap_or(usize::MAX));
+        if let Some(in_flight) = guard.admit(bytes) {
+            return Ok(in_flight);
+        }
+        warn!(
+            request_id = %context.request_id,
+            method = %request.method,
+            client_ip = %context.client_ip,
+            "Request refused under memory pressure"
+        );
+        let message = context.localize(
+            "service_overloaded",
+            &[],
+            "Service temporarily overloaded, retry later",
+        );
+        let error_response = JsonRpcResponse::error(
+            crate::infrastructure::http::models::JsonRpcError::internal_error(&message),
+            request.id.clone(),
+        );
+        let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+        let response = create_json_response_with_security_headers(&error_response, &security_middleware);
+        let response: Box<dyn warp::Reply> = Box::new(warp::reply::with_header(
+            response,
+            "retry-after",
+            guard.retry_after_seconds().to_string(),
+        ));
+        Err(warp::reply::with_status(response, warp::http::StatusCode::SERVICE_UNAVAILABLE))
+    }
+
+    /// Reject requests over the complexity budget
+    ///
+    /// Returns whether the request is heavy enough to be shed like a
+    /// high-cost method under load.
+    pub fn check_complexity(
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        guards: &RequestGuards,
+        config: &AppConfig,
+    ) -> Result<bool, warp::reply::WithStatus<Box<dyn warp::Reply>>> {
+        let estimator = &guards.complexity;
+        let (score, verdict) = estimator.assess(&request.method, request.params.as_ref());
+        if verdict != ComplexityVerdict::Rejected {
+            return Ok(verdict == ComplexityVerdict::Deprioritized);
+        }
+        let error = AppError::InvalidParameters {
+            method: request.method.clone(),
+            reason: format!("request complexity {} exceeds the budget of {}", score, estimator.max_score()),
+        };
+        warn!(
+            request_id = %context.request_id,
+            method = %request.method,
+            client_ip = %context.client_ip,
+            score,
+            "Request refused over complexity budget"
+        );
+        Err(Self::create_error_response_with_security_headers(
+            &context.localize_error(&error),
+            &request.id,
+            error.http_status_code(),
+            config,
+        ))
+    }
+
+    /// Enforce the matched client profile's method allowlist and body size limit
+    ///
+    /// Clients without a profile are held to `[server] max_request_size`, since
+    /// the route itself accepts bodies up to the largest profile limit.
+    pub fn check_client_profile(
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        content_length: Option<u64>,
+        config: &AppConfig,
+    ) -> Result<(), warp::reply::WithStatus<Box<dyn warp::Reply>>> {
+        let profile = context.client_profile.as_ref();
+        let limit = profile
+            .and_then(|profile| profile.max_request_size)
+            .unwrap_or(config.server.max_request_size);
+        let size = content_length.unwrap_or_default() as usize;
+        let error = if size > limit {
+            AppError::RequestTooLarge { size, limit }
+        } else if profile.is_some_and(|profile| !client_profiles::allows_method(profile, &request.method)) {
+            AppError::MethodNotAllowed { method: request.method.clone() }
+        } else {
+            return Ok(());
+        };
+        warn!(
+            request_id = %context.request_id,
+            client_profile = profile.map_or("", |profile| profile.id.as_str()),
+            error = %error,
+            "Request refused by client policy"
+        );
+        Err(Self::create_error_response_with_security_headers(
+            &context.localize_error(&error),
+            &request.id,
+            error.http_status_code(),
+            config,
+        ))
+    }
+
+    /// Enforce the matched tenant's method allowlist and daily request quota
+    pub fn check_tenant(
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        guards: &RequestGuards,
+        config: &AppConfig,
+    ) -> Result<(), warp::reply::WithStatus<Box<dyn warp::Reply>>> {
+        let Some(tenant) = context.tenant.as_ref() else {
+            return Ok(());
+        };
+        let tenants = &guards.tenants;
+        let error = if !tenant.allows_method(&request.method) {
+            tenants.record_rejected(tenant, TenantRejection::Method);
+            AppError::MethodNotAllowed { method: request.method.clone() }
+        } else if !tenants.admit(tenant) {
+            AppError::RateLimit
+        } else {
+            return Ok(());
+        };
+        warn!(
+            request_id = %context.request_id,
+            tenant = %tenant.name,
+            error = %error,
+            "Request refused by tenant policy"
+        );
+        Err(Self::create_error_response_with_security_headers(
+            &context.localize_error(&error),
+            &request.id,
+            error.http_status_code(),
+            config,
+        ))
+    }
+
     /// Check rate limit and return error response if rate limit is exceeded
+    ///
+    /// A client profile with its own `requests_per_minute` is limited on one
+    /// budget keyed by the profile, whichever address its requests come from;
+    /// otherwise a tenant's `requests_per_minute` is one budget for the tenant.
     pub async fn check_rate_limit(
         client_ip: &str,
         context: &RequestContext,
         request: &JsonRpcRequest,
         rate_limit_middleware: &Arc<RateLimitMiddleware>,
+        guards: &RequestGuards,
         config: &AppConfig,
     ) -> Result<(), warp::reply::WithStatus<Box<dyn warp::Reply>>> {
-        if rate_limit_middleware.is_enabled() {
-            let client_limiter = rate_limit_middleware.create_client_limiter(client_ip);
-            if let Err(e) = client_limiter.check_rate_limit(client_ip).await {
+        // Exempt clients skip both the per-client limit and their profile's budget
+        if let Some(reason) = context.rate_limit_exemption {
+            guards.rate_limit_exemptions.record(reason);
+            debug!(
+                request_id = %context.request_id,
+                client_ip = %client_ip,
+                exemption = reason.as_str(),
+                "Rate limit skipped for exempt client"
+            );
+            return Ok(());
+        }
+        if let Some((client_limiter, key)) = Self::rate_limit_budget(client_ip, context, rate_limit_middleware) {
+            let cost = rate_limit_middleware.method_cost(&request.method);
+            if let Err(e) = client_limiter.check_rate_limit_weighted(&key, cost).await {
                 error!(
                     request_id = %context.request_id,
                     client_ip = %client_ip,
                     error = %e,
                     "Rate limit exceeded"
                 );
+                if let Some(tenant) = context.tenant.as_ref() {
+                    guards.tenants.record_rejected(tenant, TenantRejection::RateLimit);
+                }
+                guards.ban_list.record(client_ip, Offense::RateLimit).await;
+                let message = context.localize_error(&crate::shared::error::AppError::RateLimit);
                 let error_response = JsonRpcResponse::error(
-                    crate::infrastructure::http::models::JsonRpcError::internal_error("Rate limit exceeded"),
+                    crate::infrastructure::http::models::JsonRpcError::internal_error(&message),
                     request.id.clone(),
                 );
                 
@@ -108,6 +393,51 @@ impl BaseRequestProcessor {
         Ok(())
     }
 
+    /// Debit `calls` further calls to `method` from the client's budget, once they were made
+    ///
+    /// Used for endpoints whose daemon fan-out is only known after serving them.
+    pub async fn charge_upstream_calls(
+        client_ip: &str,
+        context: &RequestContext,
+        method: &str,
+        calls: u32,
+        rate_limit_middleware: &Arc<RateLimitMiddleware>,
+    ) {
+        if context.rate_limit_exemption.is_some() || calls == 0 {
+            return;
+        }
+        if let Some((client_limiter, key)) = Self::rate_limit_budget(client_ip, context, rate_limit_middleware) {
+            let cost = rate_limit_middleware.method_cost(method).saturating_mul(calls);
+            client_limiter.debit(&key, cost).await;
+        }
+    }
+
+    /// Limiter and bucket key a client is charged to, if it is rate limited at all
+    fn rate_limit_budget(
+        client_ip: &str,
+        context: &RequestContext,
+        rate_limit_middleware: &Arc<RateLimitMiddleware>,
+    ) -> Option<(crate::middleware::rate_limit::RateLimitState, String)> {
+        let profile_budget = context
+            .client_profile
+            .as_ref()
+            .and_then(|profile| Some((format!("profile:{}", profile.id), profile.requests_per_minute?)))
+            .or_else(|| {
+                let tenant = context.tenant.as_ref()?;
+                Some((format!("tenant:{}", tenant.name), tenant.config.requests_per_minute?))
+            });
+        match profile_budget {
+            Some((key, requests_per_minute)) => Some((
+                rate_limit_middleware.create_client_limiter(client_ip).with_requests_per_minute(requests_per_minute),
+                key,
+            )),
+            None if rate_limit_middleware.is_enabled() => {
+                Some((rate_limit_middleware.create_client_limiter(client_ip), client_ip.to_string()))
+            }
+            None => None,
+        }
+    }
+
     /// Check cache for read-only methods and return cached response if available
     pub async fn check_cache(
         request: &JsonRpcRequest,
@@ -119,28 +449,29 @@ impl BaseRequestProcessor {
             let params = request.params.as_ref().unwrap_or(&serde_json::Value::Null);
             let cache_key = cache_middleware.generate_cache_key(&request.method, params);
             
-            if let Ok(Some(cached_entry)) = cache_middleware.get_cached_response(&cache_key).await {
+            // Memory/Redis first, then the immutable disk tier
+            let cached_data = match cache_middleware.get_cached_response(&cache_key).await {
+                Ok(Some(cached_entry)) => Some(cached_entry.data),
+                _ => cache_middleware.get_immutable(&request.method, params),
+            };
+            if let Some(cached_data) = cached_data {
                 info!(
                     request_id = %context.request_id,
                     method = %request.method,
                     "Cache hit - returning cached response"
                 );
                 
-                // Return cached response as JSON with security headers
-                let cached_response: JsonRpcResponse = serde_json::from_slice(&cached_entry.data)
+                // Return cached response as JSON with security headers, answering with this request's id
+                let mut cached_response: JsonRpcResponse = serde_json::from_slice(&cached_data)
                     .unwrap_or_else(|_| JsonRpcResponse::error(
                         crate::infrastructure::http::models::JsonRpcError::internal_error("Failed to deserialize cached response"),
                         request.id.clone(),
-                    ));
-                
-                let security_middleware = SecurityHeadersMiddleware::new(config.clone());
-                let response = create_json_response_with_security_headers(
-                    &cached_response,
-                    &security_middleware,
-                );
+                    ))
+                    .without_fields(context.hidden_fields());
+                cached_response.id = request.id.clone();
                 
                 return Ok(Some(warp::reply::with_status(
-                    response,
+                    Self::success_reply(&cached_response, context, config),
                     warp::http::StatusCode::OK,
                 )));
             }
@@ -148,6 +479,104 @@ impl BaseRequestProcessor {
         Ok(None)
     }
 
+    /// After a local cache miss with sticky routing, serve the request from the replica owning its cache key
+    ///
+    /// `None` when this replica owns the key, the request is not cacheable, or
+    /// forwarding failed; the caller then serves it locally.
+    pub async fn forward_to_owner(
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        cache_middleware: &Arc<CacheMiddleware>,
+        guards: &RequestGuards,
+        config: &AppConfig,
+    ) -> Option<warp::reply::WithStatus<Box<dyn warp::Reply>>> {
+        let cluster = guards.cluster.as_ref().filter(|cluster| config.cluster.enabled && cluster.sticky_routing())?;
+        if !config.cache.enabled || !cache_middleware.should_cache_response(&request.method, 200) {
+            return None;
+        }
+        let params = request.params.as_ref().unwrap_or(&serde_json::Value::Null);
+        let (node_id, url) = match cluster.owner(&cache_middleware.generate_cache_key(&request.method, params)) {
+            KeyOwner::Local => {
+                cluster.record_owned();
+                return None;
+            }
+            KeyOwner::Remote { node_id, url } => (node_id, url),
+        };
+        let body = serde_json::to_value(request).ok()?;
+        match cluster
+            .forward(&url, &body, &context.client_ip, context.auth_token.as_deref(), context.user_agent.as_deref())
+            .await
+        {
+            Ok(mut response) => {
+                debug!(request_id = %context.request_id, method = %request.method, owner = %node_id, "Served by owning replica");
+                if let Some(result) = response.get_mut("result") {
+                    client_profiles::remove_fields(result, context.hidden_fields());
+                }
+                Some(warp::reply::with_status(
+                    Self::success_reply(&response, context, config),
+                    warp::http::StatusCode::OK,
+                ))
+            }
+            Err(e) => {
+                warn!(request_id = %context.request_id, owner = %node_id, error = %e, "Forwarding to owning replica failed; serving locally");
+                None
+            }
+        }
+    }
+
+    /// After a cache miss in cluster mode, let only one replica fetch the response
+    ///
+    /// The replica that takes the lock proceeds; the others poll the cache for
+    /// up to `[cluster] coalesce_wait_ms` and then proceed without the lock, so
+    /// a crashed or slow leader only costs latency, never an error.
+    pub async fn join_single_flight(
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        cache_middleware: &Arc<CacheMiddleware>,
+        guards: &RequestGuards,
+        config: &AppConfig,
+    ) -> SingleFlight {
+        let cluster = match &guards.cluster {
+            Some(cluster)
+                if config.cluster.enabled
+                    && config.cache.enabled
+                    && cache_middleware.should_cache_response(&request.method, 200) =>
+            {
+                cluster
+            }
+            _ => return SingleFlight::Proceed(None),
+        };
+        let params = request.params.as_ref().unwrap_or(&serde_json::Value::Null);
+        let name = cache_middleware.generate_cache_key(&request.method, params);
+        let deadline = Instant::now() + Duration::from_millis(config.cluster.coalesce_wait_ms);
+        loop {
+            match cluster.try_lock(&name).await {
+                Ok(Some(lock)) => {
+                    // The previous holder may have finished between our cache miss and now
+                    if let Ok(Some(cached)) = Self::check_cache(request, context, cache_middleware, config).await {
+                        lock.release().await;
+                        return SingleFlight::Cached(cached);
+                    }
+                    return SingleFlight::Proceed(Some(lock));
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(request_id = %context.request_id, error = %e, "Single-flight lock unavailable");
+                    return SingleFlight::Proceed(None);
+                }
+            }
+            if Instant::now() >= deadline {
+                debug!(request_id = %context.request_id, method = %request.method, "Single-flight wait expired");
+                return SingleFlight::Proceed(None);
+            }
+            tokio::time::sleep(SINGLE_FLIGHT_POLL).await;
+            if let Ok(Some(cached)) = Self::check_cache(request, context, cache_middleware, config).await {
+                info!(request_id = %context.request_id, method = %request.method, "Coalesced with another replica's call");
+                return SingleFlight::Cached(cached);
+            }
+        }
+    }
+
     /// Create error response with security headers - common pattern used across handlers
     pub fn create_error_response_with_security_headers(
         error_message: &str,
@@ -183,6 +612,7 @@ impl BaseRequestProcessor {
             
             // Serialize response for caching
             if let Ok(response_data) = serde_json::to_vec(response) {
+                cache_middleware.store_immutable(&request.method, params, &response_data);
                 let cache_entry = cache_middleware.create_cache_entry(
                     cache_key,
                     response_data,
@@ -208,6 +638,19 @@ impl BaseRequestProcessor {
         }
     }
 
+    /// JSON reply for a successful response, signed when `[response_signing]` covers the request
+    pub fn success_reply<T: serde::Serialize>(
+        data: &T,
+        context: &RequestContext,
+        config: &AppConfig,
+    ) -> Box<dyn warp::Reply> {
+        let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+        match &context.response_signer {
+            Some(signer) => signer.signed_reply(data, &security_middleware),
+            None => create_json_response_with_security_headers(data, &security_middleware),
+        }
+    }
+
     /// Create success response with security headers
     pub fn create_success_response(
         response: &JsonRpcResponse,
@@ -268,7 +711,7 @@ mod tests {
         assert_eq!(validated_ip, "127.0.0.1");
         assert_eq!(context.client_ip, "127.0.0.1");
         assert_eq!(context.method, "getinfo");
-        assert!(context.request_id.len() > 0);
+        assert!(!context.request_id.is_empty());
     }
 
     #[test]
@@ -335,12 +778,71 @@ mod tests {
             &context,
             &request,
             &rate_limit_middleware,
+            &RequestGuards::new(&config),
             &config,
         ).await;
 
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_client_profile_overrides() {
+        let request = create_test_request();
+        let config = create_test_config();
+        let profile = crate::config::app_config::ClientProfileConfig {
+            id: "enterprise-test".to_string(),
+            permissions: vec![],
+            api_key_hashes: vec![],
+            requests_per_minute: Some(1),
+            allowed_methods: Some(vec!["getinfo".to_string()]),
+            max_request_size: Some(4 * 1024 * 1024),
+            hidden_fields: vec![],
+        };
+        let context = RequestContext::new("127.0.0.1".to_string(), "getinfo".to_string(), None)
+            .with_client_profile(profile.clone());
+
+        let oversized = Some(2 * 1024 * 1024);
+        assert!(BaseRequestProcessor::check_client_profile(&request, &context, oversized, &config).is_ok());
+        let anonymous = RequestContext::new("127.0.0.1".to_string(), "getinfo".to_string(), None);
+        let response = BaseRequestProcessor::check_client_profile(&request, &anonymous, oversized, &config).unwrap_err();
+        assert_eq!(response.into_response().status(), warp::http::StatusCode::PAYLOAD_TOO_LARGE);
+
+        let mut other_method = create_test_request();
+        other_method.method = "getblock".to_string();
+        let response = BaseRequestProcessor::check_client_profile(&other_method, &context, Some(64), &config).unwrap_err();
+        assert_eq!(response.into_response().status(), warp::http::StatusCode::METHOD_NOT_ALLOWED);
+
+        // One budget per profile, whichever address the requests come from
+        let rate_limit_middleware = create_test_rate_limit_middleware();
+        let guards = RequestGuards::new(&config);
+        assert!(BaseRequestProcessor::check_rate_limit("198.51.100.1", &context, &request, &rate_limit_middleware, &guards, &config).await.is_ok());
+        assert!(BaseRequestProcessor::check_rate_limit("198.51.100.2", &context, &request, &rate_limit_middleware, &guards, &config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exempt_client_skips_profile_budget() {
+        let request = create_test_request();
+        let config = create_test_config();
+        let profile = crate::config::app_config::ClientProfileConfig {
+            id: "exempt-test".to_string(),
+            permissions: vec![],
+            api_key_hashes: vec![],
+            requests_per_minute: Some(1),
+            allowed_methods: None,
+            max_request_size: None,
+            hidden_fields: vec![],
+        };
+        let context = RequestContext::new("10.0.0.5".to_string(), "getinfo".to_string(), None)
+            .with_client_profile(profile)
+            .with_rate_limit_exemption(crate::infrastructure::adapters::ExemptionReason::Cidr);
+
+        let rate_limit_middleware = create_test_rate_limit_middleware();
+        let guards = RequestGuards::new(&config);
+        for _ in 0..3 {
+            assert!(BaseRequestProcessor::check_rate_limit("10.0.0.5", &context, &request, &rate_limit_middleware, &guards, &config).await.is_ok());
+        }
+    }
+
     #[tokio::test]
     async fn test_check_cache_disabled() {
         let request = create_test_request();
@@ -0,0 +1,109 @@
+//! Admission state shared by every request
+//!
+//! Client profiles, tenants, rate limit exemptions, load shedding, the memory
+//! guard, complexity scoring, response signing, request log sampling, the
+//! error message catalog, the ban list, the cluster coordinator and the
+//! upstream guards keep tables and counters that every request must see the
+//! same way. The authentication adapter verifies the bearer token that
+//! profiles, tenants and exemptions match on. The server
+//! builds them once from its configuration and hands the same
+//! [`RequestGuards`] to each route that admits requests and to `/metrics`.
+
+use std::sync::Arc;
+
+use tracing::{info, warn};
+
+use crate::config::AppConfig;
+use crate::infrastructure::adapters::{
+    AuthenticationAdapter, BanList, ClientProfiles, ClusterCoordinator, RateLimitExemptions, Tenants,
+    UpstreamGuards,
+};
+use crate::middleware::{
+    complexity::ComplexityEstimator, load_shedding::LoadShedder, memory_guard::MemoryGuard,
+    request_logging::RequestLogSampler, response_signing::ResponseSigner,
+};
+use crate::shared::i18n::{DirectoryCatalogLoader, MessageCatalog};
+
+/// Per-server admission state, built once and shared between routes
+#[derive(Clone)]
+pub struct RequestGuards {
+    pub client_profiles: Arc<ClientProfiles>,
+    pub tenants: Arc<Tenants>,
+    pub rate_limit_exemptions: Arc<RateLimitExemptions>,
+    pub load_shedder: Arc<LoadShedder>,
+    pub memory_guard: Arc<MemoryGuard>,
+    pub complexity: Arc<ComplexityEstimator>,
+    /// Present when `[response_signing]` is enabled
+    pub response_signer: Option<Arc<ResponseSigner>>,
+    pub log_sampler: Arc<RequestLogSampler>,
+    /// Localized error messages, when `[localization]` is enabled and its catalog loads
+    pub message_catalog: Option<Arc<MessageCatalog>>,
+    pub ban_list: Arc<BanList>,
+    /// Present when `[cluster]` is enabled
+    pub cluster: Option<Arc<ClusterCoordinator>>,
+    /// Gate, negative cache and recording shared with the server's daemon adapters
+    pub upstream: UpstreamGuards,
+    /// Verifies bearer tokens, revocation included, before their claims are matched
+    pub authentication: Arc<AuthenticationAdapter>,
+}
+
+impl RequestGuards {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            client_profiles: Arc::new(ClientProfiles::new(config)),
+            tenants: Arc::new(Tenants::new(config)),
+            rate_limit_exemptions: Arc::new(RateLimitExemptions::new(config)),
+            load_shedder: Arc::new(LoadShedder::new(config.load_shedding.clone())),
+            memory_guard: Arc::new(MemoryGuard::new(config.memory_guard.clone())),
+            complexity: Arc::new(ComplexityEstimator::new(config.complexity.clone())),
+            response_signer: ResponseSigner::new(&config.response_signing).map(Arc::new),
+            log_sampler: Arc::new(RequestLogSampler::new(config.logging.sampling.clone())),
+            message_catalog: Self::message_catalog(config),
+            ban_list: Arc::new(BanList::new(&config.auto_ban, None)),
+            cluster: None,
+            upstream: UpstreamGuards::default(),
+            authentication: Arc::new(AuthenticationAdapter::new(Arc::new(config.clone()))),
+        }
+    }
+
+    /// Use `ban_list`, e.g. one sharing bans through Redis
+    pub fn with_ban_list(mut self, ban_list: Arc<BanList>) -> Self {
+        self.ban_list = ban_list;
+        self
+    }
+
+    /// Coordinate with other replicas through `cluster`
+    pub fn with_cluster(mut self, cluster: Option<Arc<ClusterCoordinator>>) -> Self {
+        self.cluster = cluster;
+        self
+    }
+
+    /// Share `upstream` with the server's daemon adapters
+    pub fn with_upstream(mut self, upstream: UpstreamGuards) -> Self {
+        self.upstream = upstream;
+        self
+    }
+
+    /// Verify bearer tokens with `authentication`, e.g. one sharing the server's revocation store
+    pub fn with_authentication(mut self, authentication: Arc<AuthenticationAdapter>) -> Self {
+        self.authentication = authentication;
+        self
+    }
+
+    fn message_catalog(config: &AppConfig) -> Option<Arc<MessageCatalog>> {
+        if !config.localization.enabled {
+            return None;
+        }
+        let loader = DirectoryCatalogLoader::new(config.localization.catalog_dir.clone());
+        match MessageCatalog::from_loader(&loader, &config.localization.default_locale) {
+            Ok(catalog) => {
+                info!(locales = ?catalog.locales(), "Loaded error message catalog");
+                Some(Arc::new(catalog))
+            }
+            Err(e) => {
+                warn!("message catalog unavailable: {} - using English messages", e);
+                None
+            }
+        }
+    }
+}
@@ -4,7 +4,11 @@
 //! across different endpoint handlers.
 
 pub mod base;
+pub mod guards;
+pub mod rest;
 pub mod rpc;
 
-pub use base::BaseRequestProcessor;
+pub use base::{BaseRequestProcessor, SingleFlight};
+pub use guards::RequestGuards;
+pub use rest::{RestGuard, UPSTREAM_CALLS_HEADER};
 pub use rpc::RpcRequestProcessor;
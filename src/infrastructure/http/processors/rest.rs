@@ -0,0 +1,233 @@
+//! Admission checks for the REST facade
+//!
+//! The REST endpoints (`/api/...`, `/resolve`, `/convert`, `/marketplace`,
+//! `/jobs` and `/track/tx`) call the daemon on the caller's behalf, so they pass the same
+//! checks as `POST /`: the ban list, the memory guard, client profile and
+//! tenant allowlists, token validation with the security policy and token
+//! scopes, the complexity budget, load shedding and the rate limit. Each
+//! endpoint is checked as the daemon method it calls. Endpoints whose number
+//! of daemon calls depends on the request report it in
+//! [`UPSTREAM_CALLS_HEADER`]; the calls beyond the first are charged to the
+//! caller's budget once the response is ready, and the header is removed.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use warp::http::HeaderMap;
+use warp::path::FullPath;
+use warp::reply::Response;
+use warp::{Filter, Rejection, Reply};
+
+use crate::application::services::RpcService;
+use crate::config::AppConfig;
+use crate::infrastructure::converters::ModelConverter;
+use crate::infrastructure::http::models::{JsonRpcRequest, RequestContext};
+use crate::infrastructure::http::processors::{BaseRequestProcessor, RequestGuards};
+use crate::infrastructure::http::utils::extract_and_validate_client_ip;
+use crate::middleware::rate_limit::RateLimitMiddleware;
+
+/// Response header a facade handler sets to the number of daemon calls it made
+pub const UPSTREAM_CALLS_HEADER: &str = "x-verus-upstream-calls";
+
+/// Facade path prefixes and the daemon method each endpoint is checked as
+const FACADE_ROUTES: &[(&str, &str)] = &[
+    ("/api/block/", "getblock"),
+    ("/api/addresses/balances", "getaddressbalance"),
+    ("/api/address/", "getaddresstxids"),
+    ("/api/currency/", "getcurrency"),
+    ("/api/shielded/tree", "getsaplingtree"),
+    ("/api/portfolio", "getaddressbalance"),
+    ("/resolve", "getidentity"),
+    ("/convert/", "estimateconversion"),
+    ("/marketplace/", "getoffers"),
+    ("/jobs", "getaddressdeltas"),
+    ("/track/tx", "getrawtransaction"),
+];
+
+/// Applies the `POST /` admission checks to the REST facade
+pub struct RestGuard {
+    config: AppConfig,
+    rpc_service: Arc<RpcService>,
+    rate_limit_middleware: Arc<RateLimitMiddleware>,
+    request_guards: Arc<RequestGuards>,
+}
+
+impl RestGuard {
+    pub fn new(
+        config: AppConfig,
+        rpc_service: Arc<RpcService>,
+        rate_limit_middleware: Arc<RateLimitMiddleware>,
+        request_guards: Arc<RequestGuards>,
+    ) -> Self {
+        Self { config, rpc_service, rate_limit_middleware, request_guards }
+    }
+
+    /// Serve `routes` behind the admission checks, charging reported daemon fan-out
+    pub fn wrap<F, R>(self: Arc<Self>, routes: F) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone
+    where
+        F: Filter<Extract = (R,), Error = Rejection> + Clone + Send + Sync + 'static,
+        R: Reply,
+    {
+        let guard = self.clone();
+        let refusals = warp::path::full().and(warp::header::headers_cloned()).and_then(
+            move |path: FullPath, headers: HeaderMap| {
+                let guard = guard.clone();
+                async move {
+                    match guard.admit(path.as_str(), &headers).await {
+                        Ok(()) => Err(warp::reject::not_found()),
+                        Err(refusal) => Ok(refusal),
+                    }
+                }
+            },
+        );
+        let served = warp::path::full().and(warp::header::headers_cloned()).and(routes).then(
+            move |path: FullPath, headers: HeaderMap, reply: R| {
+                let guard = self.clone();
+                async move {
+                    let mut response = reply.into_response();
+                    let calls = response
+                        .headers_mut()
+                        .remove(UPSTREAM_CALLS_HEADER)
+                        .and_then(|value| value.to_str().ok()?.parse::<u32>().ok());
+                    if let (Some(calls), Some(method)) = (calls, method_for(path.as_str())) {
+                        let context = guard.context(method, &headers);
+                        BaseRequestProcessor::charge_upstream_calls(
+                            &context.client_ip,
+                            &context,
+                            method,
+                            calls.saturating_sub(1),
+                            &guard.rate_limit_middleware,
+                        )
+                        .await;
+                    }
+                    response
+                }
+            },
+        );
+        refusals.or(served).unify()
+    }
+
+    /// Run the admission checks for a facade path; other paths are let through
+    async fn admit(&self, path: &str, headers: &HeaderMap) -> Result<(), Response> {
+        let Some(method) = method_for(path) else {
+            return Ok(());
+        };
+        let config = &self.config;
+        let guards = &self.request_guards;
+        let context = self.context(method, headers).await;
+        let request = JsonRpcRequest::new(method.to_string(), None, Some(json!(path)));
+        let content_length = header(headers, "content-length").and_then(|length| length.parse::<u64>().ok());
+
+        BaseRequestProcessor::check_ban(&request, &context, guards, config).await.map_err(Reply::into_response)?;
+        let _in_flight = BaseRequestProcessor::check_memory_pressure(&request, &context, content_length, guards, config)
+            .map_err(Reply::into_response)?;
+        BaseRequestProcessor::check_client_profile(&request, &context, content_length, config)
+            .map_err(Reply::into_response)?;
+        BaseRequestProcessor::check_tenant(&request, &context, guards, config).map_err(Reply::into_response)?;
+        let domain_request = ModelConverter::to_domain_request(&request, &context).map_err(|e| self.refusal(&request, &context, e))?;
+        self.rpc_service
+            .authorize(&domain_request)
+            .await
+            .map_err(|e| self.refusal(&request, &context, e))?;
+        let heavy = BaseRequestProcessor::check_complexity(&request, &context, guards, config).map_err(Reply::into_response)?;
+        BaseRequestProcessor::check_load_shedding(&request, &context, heavy, guards, config).map_err(Reply::into_response)?;
+        BaseRequestProcessor::check_rate_limit(&context.client_ip, &context, &request, &self.rate_limit_middleware, guards, config)
+            .await
+            .map_err(Reply::into_response)
+    }
+
+    /// Request context for a facade call, identified the same way as `POST /`
+    async fn context(&self, method: &str, headers: &HeaderMap) -> RequestContext {
+        let client_ip = extract_and_validate_client_ip(header(headers, "x-forwarded-for").unwrap_or_default(), &self.config);
+        let mut context = RequestContext::new(client_ip, method.to_string(), None);
+        if let Some(user_agent) = header(headers, "user-agent") {
+            context = context.with_user_agent(user_agent.to_string());
+        }
+        if let Some(auth) = header(headers, "authorization") {
+            context = context.with_auth_token(auth.to_string());
+        }
+        if let Some(lang) = header(headers, "accept-language") {
+            context = context.with_accept_language(lang, self.request_guards.message_catalog.as_ref());
+        }
+        BaseRequestProcessor::identify_client(context, header(headers, "x-api-key"), &self.request_guards).await
+    }
+
+    fn refusal(&self, request: &JsonRpcRequest, context: &RequestContext, error: crate::shared::error::AppError) -> Response {
+        BaseRequestProcessor::create_error_response_with_security_headers(
+            &context.localize_error(&error),
+            &request.id,
+            error.http_status_code(),
+            &self.config,
+        )
+        .into_response()
+    }
+}
+
+/// Daemon method a facade path is checked as
+fn method_for(path: &str) -> Option<&'static str> {
+    FACADE_ROUTES
+        .iter()
+        .find(|(prefix, _)| path.starts_with(prefix))
+        .map(|(_, method)| *method)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::security::SecurityValidator;
+
+    fn guard(config: AppConfig) -> Arc<RestGuard> {
+        let rpc_service = Arc::new(RpcService::new(Arc::new(config.clone()), Arc::new(SecurityValidator::new(Default::default()))));
+        let request_guards = Arc::new(RequestGuards::new(&config));
+        Arc::new(RestGuard::new(config.clone(), rpc_service, Arc::new(RateLimitMiddleware::new(config)), request_guards))
+    }
+
+    fn facade() -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+        warp::path("api")
+            .and(warp::path("block"))
+            .and(warp::path::param::<String>())
+            .and(warp::path("full"))
+            .map(|_hash: String| warp::reply::with_header(warp::reply(), UPSTREAM_CALLS_HEADER, "40").into_response())
+    }
+
+    #[test]
+    fn test_facade_paths_map_to_daemon_methods() {
+        assert_eq!(method_for("/api/block/00ab/full"), Some("getblock"));
+        assert_eq!(method_for("/resolve"), Some("getidentity"));
+        assert_eq!(method_for("/track/tx/3b0c5f0e"), Some("getrawtransaction"));
+        assert_eq!(method_for("/health"), None);
+        assert_eq!(method_for("/"), None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_tokens_are_refused() {
+        let routes = guard(AppConfig::default()).wrap(facade());
+        let response = warp::test::request()
+            .path("/api/block/00ab/full")
+            .header("authorization", "Bearer not-a-token")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_reported_fan_out_is_charged_to_the_caller() {
+        let mut config = AppConfig::default();
+        config.security.development_mode = true;
+        config.security.trusted_proxy_headers = vec!["X-Forwarded-For".to_string()];
+        config.rate_limit.enabled = true;
+        config.rate_limit.requests_per_minute = 20;
+        let routes = guard(config).wrap(facade());
+        let request = || warp::test::request().path("/api/block/00ab/full").header("x-forwarded-for", "192.0.2.91");
+
+        let first = request().reply(&routes).await;
+        assert_eq!(first.status(), warp::http::StatusCode::OK);
+        assert!(first.headers().get(UPSTREAM_CALLS_HEADER).is_none());
+        let second = request().reply(&routes).await;
+        assert_eq!(second.status(), warp::http::StatusCode::TOO_MANY_REQUESTS);
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
         processors::BaseRequestProcessor,
     },
     application::use_cases::ProcessRpcRequestUseCase,
+    domain::validation::ValidationTrace,
     infrastructure::converters::ModelConverter,
     middleware::{
         cache::CacheMiddleware,
@@ -17,7 +18,7 @@ use crate::{
     shared::error::AppError,
 };
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// RPC request processor for handling RPC-specific processing patterns
 pub struct RpcRequestProcessor;
@@ -53,10 +54,13 @@ impl RpcRequestProcessor {
                 e
             })?;
 
-        info!(
-            request_id = %context.request_id,
-            "RPC request processed successfully"
-        );
+        // Sampled request logging reports completions itself
+        if !config.logging.sampling.enabled {
+            info!(
+                request_id = %context.request_id,
+                "RPC request processed successfully"
+            );
+        }
 
         // Convert domain response to infrastructure response
         let infra_response = ModelConverter::to_infrastructure_response(&domain_response);
@@ -119,13 +123,62 @@ impl RpcRequestProcessor {
         );
 
         BaseRequestProcessor::create_error_response_with_security_headers(
-            &error.to_string(),
+            &context.localize_error(error),
             &request.id,
             error.http_status_code(),
             config,
         )
     }
 
+    /// Trace the validation rules evaluated for a request (explain mode)
+    ///
+    /// Returns `None`, with a warning logged, when the caller may not use explain mode.
+    pub async fn explain_validation(
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        rpc_use_case: &Arc<ProcessRpcRequestUseCase>,
+    ) -> Option<ValidationTrace> {
+        let domain_request = ModelConverter::to_domain_request(request, context).ok()?;
+        match rpc_use_case.explain_validation(&domain_request).await {
+            Ok(trace) => Some(trace),
+            Err(e) => {
+                warn!(
+                    request_id = %context.request_id,
+                    error = %e,
+                    "Validation explain mode refused"
+                );
+                None
+            }
+        }
+    }
+
+    /// Handle RPC use case execution errors, attaching a validation trace as `error.data.validation`
+    pub fn handle_use_case_error_with_trace(
+        error: &AppError,
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        config: &AppConfig,
+        trace: &ValidationTrace,
+    ) -> warp::reply::WithStatus<Box<dyn warp::Reply>> {
+        error!(
+            request_id = %context.request_id,
+            error = %error,
+            "RPC request processing failed"
+        );
+
+        let mut rpc_error = JsonRpcError::internal_error(&context.localize_error(error));
+        rpc_error.data = Some(serde_json::json!({ "validation": trace }));
+        let error_response = JsonRpcResponse::error(rpc_error, request.id.clone());
+
+        let security_middleware = SecurityHeadersMiddleware::new(config.clone());
+        let response = create_json_response_with_security_headers(
+            &error_response,
+            &security_middleware,
+        );
+
+        warp::reply::with_status(response, error.http_status_code())
+    }
+
     /// Cache RPC response using base processor
     pub async fn cache_rpc_response(
         request: &JsonRpcRequest,
@@ -146,9 +199,13 @@ impl RpcRequestProcessor {
     /// Create success response for RPC requests
     pub fn create_rpc_success_response(
         response: &JsonRpcResponse,
+        context: &RequestContext,
         config: &AppConfig,
     ) -> warp::reply::WithStatus<Box<dyn warp::Reply>> {
-        BaseRequestProcessor::create_success_response(response, config)
+        warp::reply::with_status(
+            BaseRequestProcessor::success_reply(response, context, config),
+            warp::http::StatusCode::OK,
+        )
     }
 }
 
@@ -421,7 +478,7 @@ mod tests {
         );
         let config = create_test_config();
 
-        let reply = RpcRequestProcessor::create_rpc_success_response(&response, &config);
+        let reply = RpcRequestProcessor::create_rpc_success_response(&response, &create_test_context(), &config);
         let (status, headers) = into_status_and_headers(reply).await;
         assert_eq!(status, StatusCode::OK);
         assert!(headers.get("content-security-policy").is_some());
@@ -435,7 +492,7 @@ mod tests {
         );
         let config = create_test_config();
 
-        let reply = RpcRequestProcessor::create_rpc_success_response(&response, &config);
+        let reply = RpcRequestProcessor::create_rpc_success_response(&response, &create_test_context(), &config);
         let (status, headers) = into_status_and_headers(reply).await;
         assert_eq!(status, StatusCode::OK);
         assert!(headers.get("content-security-policy").is_some());
@@ -500,7 +557,7 @@ mod tests {
         );
         let config = create_test_config();
 
-        let reply = RpcRequestProcessor::create_rpc_success_response(&response, &config);
+        let reply = RpcRequestProcessor::create_rpc_success_response(&response, &create_test_context(), &config);
         let (status, _headers) = into_status_and_headers(reply).await;
         assert_eq!(status, StatusCode::OK);
     }
@@ -513,7 +570,7 @@ mod tests {
         );
         let config = create_test_config();
 
-        let reply = RpcRequestProcessor::create_rpc_success_response(&response, &config);
+        let reply = RpcRequestProcessor::create_rpc_success_response(&response, &create_test_context(), &config);
         let (status, _headers) = into_status_and_headers(reply).await;
         assert_eq!(status, StatusCode::OK);
     }
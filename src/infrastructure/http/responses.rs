@@ -59,6 +59,18 @@ impl ResponseFormatter {
                 JsonRpcError::new(-401, "Authentication failed".to_string(), None),
                 StatusCode::UNAUTHORIZED
             ),
+            AppError::Maintenance(msg) => (
+                JsonRpcError::new(-503, msg.clone(), None),
+                StatusCode::SERVICE_UNAVAILABLE
+            ),
+            AppError::UpstreamInitializing => (
+                JsonRpcError::new(-503, error.to_string(), None),
+                StatusCode::SERVICE_UNAVAILABLE
+            ),
+            AppError::Banned { until, .. } => (
+                JsonRpcError::new(-403, error.to_string(), Some(serde_json::json!({ "banned_until": until }))),
+                StatusCode::FORBIDDEN
+            ),
             AppError::Rpc(msg) => {
                 // Try to parse as JSON-RPC error
                 if let Ok(rpc_error) = serde_json::from_str::<serde_json::Value>(msg) {
@@ -179,6 +191,15 @@ mod tests {
         assert_eq!(response.status(), warp::http::StatusCode::UNAUTHORIZED);
     }
 
+    #[test]
+    fn test_from_app_error_maintenance() {
+        let error = crate::shared::error::AppError::Maintenance("Wallet upgrade".to_string());
+        let id = Some(serde_json::json!(1));
+        let reply = ResponseFormatter::from_app_error(&error, id.clone());
+        let response = reply.into_response();
+        assert_eq!(response.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+    }
+
     #[test]
     fn test_health_response_creation() {
         let status = "healthy";
@@ -0,0 +1,323 @@
+//! Admin routes
+//!
+//! All routes live under `/admin` and require the key of an `[admin]` operator.
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::payments_service::PaymentsService;
+use crate::config::AppConfig;
+use crate::infrastructure::adapters::{AuthenticationAdapter, Handover, RevocationStore};
+use crate::infrastructure::http::{
+    handlers::{
+        admin::RevocationListQuery, handle_admin_bans, handle_admin_decide_refund, handle_admin_lift_ban,
+        handle_admin_partners, handle_admin_read_only, handle_admin_refunds, handle_admin_revocations,
+        handle_admin_revoke_user, handle_admin_set_read_only, handle_admin_slow_queries, handle_admin_start_upgrade,
+        handle_admin_upgrade, handle_admin_upstreams, handle_admin_viewing_keys,
+    },
+    processors::RequestGuards,
+    utils::{with_config, with_request_guards},
+};
+
+pub struct AdminRoutes;
+
+impl AdminRoutes {
+    /// Create all admin routes
+    pub fn create_routes(
+        config: AppConfig,
+        auth: Arc<AuthenticationAdapter>,
+        revocations: Arc<RevocationStore>,
+        handover: Arc<Handover>,
+        request_guards: Arc<RequestGuards>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let upstreams = warp::path("admin")
+            .and(warp::path("upstreams"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_upstreams);
+
+        let slow_queries = warp::path("admin")
+            .and(warp::path("slow-queries"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_slow_queries);
+
+        let partners = warp::path("admin")
+            .and(warp::path("partners"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_partners);
+
+        let viewing_keys = warp::path("admin")
+            .and(warp::path("viewing-keys"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_viewing_keys);
+
+        let list_revocations = warp::path("admin")
+            .and(warp::path("revocations"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(
+                warp::query::<RevocationListQuery>()
+                    .or(warp::any().map(|| RevocationListQuery { limit: None }))
+                    .unify(),
+            )
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(Self::with_revocations(revocations.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_revocations);
+
+        let read_only = warp::path("admin")
+            .and(warp::path("read-only"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(with_request_guards(request_guards.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_read_only);
+
+        let set_read_only = warp::path("admin")
+            .and(warp::path("read-only"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(4 * 1024))
+            .and(warp::body::json())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(with_request_guards(request_guards.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_set_read_only);
+
+        let upgrade = warp::path("admin")
+            .and(warp::path("upgrade"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(Self::with_handover(handover.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_upgrade);
+
+        let start_upgrade = warp::path("admin")
+            .and(warp::path("upgrade"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(Self::with_handover(handover))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_start_upgrade);
+
+        let bans = warp::path("admin")
+            .and(warp::path("bans"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(with_request_guards(request_guards.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_bans);
+
+        let lift_ban = warp::path("admin")
+            .and(warp::path("bans"))
+            .and(warp::path::param::<String>())
+            .and(warp::path::end())
+            .and(warp::delete())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(with_request_guards(request_guards))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_lift_ban);
+
+        let revoke_user = warp::path("admin")
+            .and(warp::path("revocations"))
+            .and(warp::path("user"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(4 * 1024))
+            .and(warp::body::json())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth))
+            .and(Self::with_revocations(revocations))
+            .and(with_config(config))
+            .and_then(handle_admin_revoke_user);
+
+        upstreams
+            .or(slow_queries)
+            .or(partners)
+            .or(viewing_keys)
+            .or(list_revocations)
+            .or(read_only)
+            .or(set_read_only)
+            .or(upgrade)
+            .or(start_upgrade)
+            .or(revoke_user)
+            .or(bans)
+            .or(lift_ban)
+    }
+
+    /// Heap profile route (`GET /debug/heap`)
+    #[cfg(feature = "heap-profiling")]
+    pub fn create_heap_profile_route(
+        config: AppConfig,
+        auth: Arc<AuthenticationAdapter>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("debug")
+            .and(warp::path("heap"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth))
+            .and(with_config(config))
+            .and_then(crate::infrastructure::http::handlers::handle_heap_profile)
+    }
+
+    /// Refund review routes (`GET /admin/refunds`, `POST /admin/refunds/{payment_id}`)
+    pub fn create_refund_routes(
+        config: AppConfig,
+        auth: Arc<AuthenticationAdapter>,
+        payments: Arc<PaymentsService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let list = warp::path("admin")
+            .and(warp::path("refunds"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth.clone()))
+            .and(Self::with_payments(payments.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_admin_refunds);
+
+        let decide = warp::path("admin")
+            .and(warp::path("refunds"))
+            .and(warp::path::param::<String>())
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(4 * 1024))
+            .and(warp::body::json())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_auth(auth))
+            .and(Self::with_payments(payments))
+            .and(with_config(config))
+            .and_then(handle_admin_decide_refund);
+
+        list.or(decide)
+    }
+
+    fn with_payments(
+        payments: Arc<PaymentsService>,
+    ) -> impl Filter<Extract = (Arc<PaymentsService>,), Error = std::convert::Infallible> + Clone {
+        warp::any().map(move || payments.clone())
+    }
+
+    fn with_revocations(
+        revocations: Arc<RevocationStore>,
+    ) -> impl Filter<Extract = (Arc<RevocationStore>,), Error = std::convert::Infallible> + Clone {
+        warp::any().map(move || revocations.clone())
+    }
+
+    fn with_handover(
+        handover: Arc<Handover>,
+        request_guards: Arc<RequestGuards>,
+    ) -> impl Filter<Extract = (Arc<Handover>,), Error = std::convert::Infallible> + Clone {
+        warp::any().map(move || handover.clone())
+    }
+
+    fn with_auth(
+        auth: Arc<AuthenticationAdapter>,
+    ) -> impl Filter<Extract = (Arc<AuthenticationAdapter>,), Error = std::convert::Infallible> + Clone {
+        warp::any().map(move || auth.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_admin_routes_require_token() {
+        let config = AppConfig::default();
+        let auth = Arc::new(AuthenticationAdapter::new(Arc::new(config.clone())));
+        let handover = Arc::new(Handover::new(&config));
+        let guards = Arc::new(RequestGuards::new(&config));
+        let route = AdminRoutes::create_routes(config, auth, Arc::new(RevocationStore::new(None)), handover, guards);
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/admin/slow-queries")
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), warp::http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_revocation_list_requires_admin() {
+        let config = AppConfig::default();
+        let auth = Arc::new(AuthenticationAdapter::new(Arc::new(config.clone())));
+        let handover = Arc::new(Handover::new(&config));
+        let guards = Arc::new(RequestGuards::new(&config));
+        let route = AdminRoutes::create_routes(config, auth, Arc::new(RevocationStore::new(None)), handover, guards);
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/admin/revocations?limit=10")
+            .header("authorization", "Bearer not-a-valid-token")
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), warp::http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_switch_requires_admin() {
+        let config = AppConfig::default();
+        let auth = Arc::new(AuthenticationAdapter::new(Arc::new(config.clone())));
+        let handover = Arc::new(Handover::new(&config));
+        let guards = Arc::new(RequestGuards::new(&config));
+        let route =
+            AdminRoutes::create_routes(config, auth, Arc::new(RevocationStore::new(None)), handover, guards.clone());
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/admin/read-only")
+            .json(&serde_json::json!({ "enabled": true }))
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), warp::http::StatusCode::UNAUTHORIZED);
+        assert!(!guards.upstream.gate.metrics().read_only);
+    }
+
+    #[tokio::test]
+    async fn test_lift_ban_requires_admin() {
+        let config = AppConfig::default();
+        let auth = Arc::new(AuthenticationAdapter::new(Arc::new(config.clone())));
+        let handover = Arc::new(Handover::new(&config));
+        let guards = Arc::new(RequestGuards::new(&config));
+        let route = AdminRoutes::create_routes(config, auth, Arc::new(RevocationStore::new(None)), handover, guards);
+
+        let res = warp::test::request()
+            .method("DELETE")
+            .path("/admin/bans/203.0.113.7")
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), warp::http::StatusCode::UNAUTHORIZED);
+    }
+}
@@ -6,10 +6,17 @@
 use crate::{
     config::AppConfig,
     application::use_cases::{ProcessRpcRequestUseCase, GetMetricsUseCase, HealthCheckUseCase},
+    infrastructure::adapters::{CpuPool, MiningPoolClient},
+    infrastructure::http::processors::RequestGuards,
     infrastructure::http::routes::{
         RpcRoutes, MetricsRoutes, MiningPoolRoutes,
     },
-    middleware::{cache::CacheMiddleware, rate_limit::RateLimitMiddleware},
+    middleware::{
+        cache::CacheMiddleware,
+        etag::etag_json_response,
+        rate_limit::RateLimitMiddleware,
+        security_headers::SecurityHeadersMiddleware,
+    },
 };
 use std::sync::Arc;
 use warp::Filter;
@@ -26,6 +33,9 @@ impl RouteBuilder {
         _health_use_case: Arc<HealthCheckUseCase>,
         cache_middleware: Arc<CacheMiddleware>,
         rate_limit_middleware: Arc<RateLimitMiddleware>,
+        request_guards: Arc<RequestGuards>,
+        mining_pool_client: Arc<MiningPoolClient>,
+        cpu_pool: Arc<CpuPool>,
     ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
         // Build individual route groups
         let rpc_route = RpcRoutes::create_rpc_route(
@@ -33,31 +43,47 @@ impl RouteBuilder {
             rpc_use_case,
             cache_middleware.clone(),
             rate_limit_middleware.clone(),
+            request_guards.clone(),
         );
 
         // Create external RPC adapter for health monitoring
-        let external_rpc = std::sync::Arc::new(crate::infrastructure::adapters::ExternalRpcAdapter::new(std::sync::Arc::new(config.clone())));
+        let external_rpc = std::sync::Arc::new(
+            crate::infrastructure::adapters::ExternalRpcAdapter::new(std::sync::Arc::new(config.clone()))
+                .with_guards(request_guards.upstream.clone()),
+        );
         
         // Create enhanced health route with circuit breaker monitoring
-        let health_route = create_enhanced_health_route(config.clone(), _health_use_case, external_rpc);
+        let health_cache = config.cache.enabled.then(|| cache_middleware.clone());
+        let health_route = create_enhanced_health_route(config.clone(), _health_use_case, external_rpc, health_cache);
 
         let metrics_route = MetricsRoutes::create_metrics_route(
             config.clone(),
             metrics_use_case,
+            request_guards.clone(),
+            rate_limit_middleware.clone(),
         );
 
+        let metrics_summary_route = MetricsRoutes::create_metrics_summary_route(config.clone());
+
         let prometheus_route = MetricsRoutes::create_prometheus_route(
             config.clone(),
+            Some(cache_middleware.clone()),
+            request_guards.clone(),
+            rate_limit_middleware.clone(),
+            cpu_pool,
         );
 
         let mining_pool_route = MiningPoolRoutes::create_mining_pool_route(
             config.clone(),
             cache_middleware,
             rate_limit_middleware,
+            mining_pool_client.clone(),
+            request_guards,
         );
 
         let pool_metrics_route = MiningPoolRoutes::create_pool_metrics_route(
             config,
+            mining_pool_client,
         );
 
         // Payments routes are created in server where dependencies exist and then merged by caller.
@@ -65,6 +91,7 @@ impl RouteBuilder {
         // Combine all routes
         rpc_route
             .or(health_route)
+            .or(metrics_summary_route)
             .or(metrics_route)
             .or(prometheus_route)
             .or(mining_pool_route)
@@ -72,22 +99,36 @@ impl RouteBuilder {
     }
 }
 
+/// Query of `GET /health`
+#[derive(Debug, Default, serde::Deserialize)]
+struct HealthQuery {
+    /// Probe dependencies and report their latencies and last errors
+    #[serde(default)]
+    verbose: bool,
+}
+
 /// Create enhanced health route with circuit breaker monitoring
 fn create_enhanced_health_route(
     config: AppConfig,
     health_use_case: Arc<HealthCheckUseCase>,
     rpc_adapter: Arc<crate::infrastructure::adapters::ExternalRpcAdapter>,
+    cache: Option<Arc<CacheMiddleware>>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     use crate::infrastructure::http::utils::{with_health_use_case, with_config};
     
     warp::path("health")
+        .and(warp::path::end())
         .and(warp::get())
+        .and(warp::query::<HealthQuery>())
         .and(with_health_use_case(health_use_case))
+        .and(warp::header::optional::<String>("if-none-match"))
         .and(with_config(config))
-        .and_then(move |health_use_case, _config| {
+        .and_then(move |query: HealthQuery, health_use_case, if_none_match, config: AppConfig| {
             let rpc_adapter = rpc_adapter.clone();
+            let cache = cache.clone();
             async move {
-                handle_enhanced_health_check(health_use_case, Some(rpc_adapter)).await
+                let middleware = SecurityHeadersMiddleware::new(config);
+                handle_enhanced_health_check(health_use_case, Some(rpc_adapter), query.verbose, cache, if_none_match, &middleware).await
             }
         })
 }
@@ -96,15 +137,26 @@ fn create_enhanced_health_route(
 async fn handle_enhanced_health_check(
     health_use_case: Arc<HealthCheckUseCase>,
     rpc_adapter: Option<Arc<crate::infrastructure::adapters::ExternalRpcAdapter>>,
-) -> Result<impl warp::Reply, warp::Rejection> {
+    verbose: bool,
+    cache: Option<Arc<CacheMiddleware>>,
+    if_none_match: Option<String>,
+    middleware: &SecurityHeadersMiddleware,
+) -> Result<Box<dyn warp::Reply>, warp::Rejection> {
     // Use the enhanced health check with circuit breaker monitoring
-    match health_use_case.execute(rpc_adapter).await {
+    let result = if verbose {
+        health_use_case.execute_verbose(rpc_adapter, cache).await
+    } else {
+        health_use_case.execute(rpc_adapter).await
+    };
+    match result {
         Ok(response) => {
-            let status_code = response.http_status_code();
-            Ok(warp::reply::with_status(
-                warp::reply::json(&response),
-                warp::http::StatusCode::from_u16(status_code).unwrap_or(warp::http::StatusCode::OK),
-            ))
+            let status_code = warp::http::StatusCode::from_u16(response.http_status_code())
+                .unwrap_or(warp::http::StatusCode::OK);
+            // Only healthy responses are revalidated; degraded states and probe latencies are always sent in full
+            if status_code == warp::http::StatusCode::OK && !verbose {
+                return Ok(etag_json_response(&response, if_none_match, middleware));
+            }
+            Ok(Box::new(warp::reply::with_status(warp::reply::json(&response), status_code)))
         }
         Err(_) => {
             // Return a simple error response instead of rejecting
@@ -112,10 +164,10 @@ async fn handle_enhanced_health_check(
                 "error": "Health check failed",
                 "status": "unhealthy"
             });
-            Ok(warp::reply::with_status(
+            Ok(Box::new(warp::reply::with_status(
                 warp::reply::json(&error_response),
                 warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-            ))
+            )))
         }
     }
 }
@@ -148,7 +200,7 @@ mod tests {
     }
 
     fn create_test_health_use_case() -> Arc<HealthCheckUseCase> {
-        Arc::new(HealthCheckUseCase)
+        Arc::new(HealthCheckUseCase::default())
     }
 
     fn create_test_metrics_use_case() -> Arc<GetMetricsUseCase> {
@@ -156,6 +208,18 @@ mod tests {
         Arc::new(GetMetricsUseCase::new(metrics_service))
     }
 
+    fn create_test_request_guards() -> Arc<RequestGuards> {
+        Arc::new(RequestGuards::new(&create_test_config()))
+    }
+
+    fn create_test_mining_pool_client() -> Arc<MiningPoolClient> {
+        Arc::new(MiningPoolClient::from_app_config(&create_test_config()))
+    }
+
+    fn create_test_cpu_pool() -> Arc<CpuPool> {
+        Arc::new(CpuPool::new(&create_test_config().server))
+    }
+
     #[tokio::test]
     async fn test_route_builder_build_routes() {
         let config = create_test_config();
@@ -173,6 +237,9 @@ mod tests {
             health_use_case,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
+            create_test_mining_pool_client(),
+            create_test_cpu_pool(),
         );
         let _ = routes.clone();
     }
@@ -187,34 +254,46 @@ mod tests {
         let rate_limit_middleware = create_test_rate_limit_middleware();
 
         // Test that we can create individual route types
+        let request_guards = create_test_request_guards();
+        let mining_pool_client = create_test_mining_pool_client();
         let _rpc_route = RpcRoutes::create_rpc_route(
             config.clone(),
             rpc_use_case.clone(),
             cache_middleware.clone(),
             rate_limit_middleware.clone(),
+            request_guards.clone(),
         );
 
         // Test enhanced health route with circuit breaker monitoring
         let external_rpc = std::sync::Arc::new(crate::infrastructure::adapters::ExternalRpcAdapter::new(std::sync::Arc::new(config.clone())));
-        let _health_route = create_enhanced_health_route(config.clone(), health_use_case.clone(), external_rpc);
+        let _health_route = create_enhanced_health_route(config.clone(), health_use_case.clone(), external_rpc, None);
 
         let _metrics_route = MetricsRoutes::create_metrics_route(
             config.clone(),
             metrics_use_case.clone(),
+            request_guards.clone(),
+            rate_limit_middleware.clone(),
         );
 
         let _prometheus_route = MetricsRoutes::create_prometheus_route(
             config.clone(),
+            Some(cache_middleware.clone()),
+            request_guards.clone(),
+            rate_limit_middleware.clone(),
+            create_test_cpu_pool(),
         );
 
         let _mining_pool_route = MiningPoolRoutes::create_mining_pool_route(
             config.clone(),
             cache_middleware.clone(),
             rate_limit_middleware.clone(),
+            mining_pool_client.clone(),
+            request_guards.clone(),
         );
 
         let _pool_metrics_route = MiningPoolRoutes::create_pool_metrics_route(
             config.clone(),
+            mining_pool_client.clone(),
         );
 
         let _ = _rpc_route.clone();
@@ -244,6 +323,9 @@ mod tests {
             health_use_case,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
+            create_test_mining_pool_client(),
+            create_test_cpu_pool(),
         );
         let _ = routes.clone();
     }
@@ -254,7 +336,7 @@ mod tests {
         let health_use_case = create_test_health_use_case();
         let external_rpc = std::sync::Arc::new(crate::infrastructure::adapters::ExternalRpcAdapter::new(std::sync::Arc::new(config.clone())));
         
-        let health_route = create_enhanced_health_route(config, health_use_case, external_rpc);
+        let health_route = create_enhanced_health_route(config, health_use_case, external_rpc, None);
         
         // Test that the route can be created and cloned
         let _ = health_route.clone();
@@ -266,7 +348,7 @@ mod tests {
         let health_use_case = create_test_health_use_case();
         let external_rpc = std::sync::Arc::new(crate::infrastructure::adapters::ExternalRpcAdapter::new(std::sync::Arc::new(config.clone())));
         
-        let health_route = create_enhanced_health_route(config, health_use_case, external_rpc);
+        let health_route = create_enhanced_health_route(config, health_use_case, external_rpc, None);
         
         // Test the route with a mock request
         let res = warp::test::request()
@@ -298,13 +380,33 @@ mod tests {
         assert!(daemon.contains_key("status"));
     }
 
+    #[tokio::test]
+    async fn test_verbose_health_reports_dependencies() {
+        let config = create_test_config();
+        let health_use_case = create_test_health_use_case();
+        let external_rpc = std::sync::Arc::new(crate::infrastructure::adapters::ExternalRpcAdapter::new(std::sync::Arc::new(config.clone())));
+        let health_route = create_enhanced_health_route(config, health_use_case, external_rpc, None);
+
+        let res = warp::test::request().method("GET").path("/health?verbose=true").reply(&health_route).await;
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        let daemon = &body["details"]["dependencies"]["daemon"];
+        assert!(daemon["latency_ms"].is_number());
+        // No daemon runs under test, so the probe fails and its error is kept
+        assert_eq!(daemon["status"], "down");
+        assert!(daemon["last_error"].is_string());
+
+        let res = warp::test::request().method("GET").path("/health").reply(&health_route).await;
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert!(body["details"].get("dependencies").is_none());
+    }
+
     #[tokio::test]
     async fn test_enhanced_health_check_handler_success() {
         let health_use_case = create_test_health_use_case();
         let config = Arc::new(create_test_config());
         let external_rpc = Arc::new(crate::infrastructure::adapters::ExternalRpcAdapter::new(config));
         
-        let result = handle_enhanced_health_check(health_use_case, Some(external_rpc)).await;
+        let result = handle_enhanced_health_check(health_use_case, Some(external_rpc), false, None, None, &SecurityHeadersMiddleware::new(AppConfig::default())).await;
         
         assert!(result.is_ok());
         
@@ -318,7 +420,7 @@ mod tests {
     async fn test_enhanced_health_check_handler_without_adapter() {
         let health_use_case = create_test_health_use_case();
         
-        let result = handle_enhanced_health_check(health_use_case, None).await;
+        let result = handle_enhanced_health_check(health_use_case, None, false, None, None, &SecurityHeadersMiddleware::new(AppConfig::default())).await;
         
         assert!(result.is_ok());
     }
@@ -326,13 +428,13 @@ mod tests {
     #[tokio::test]
     async fn test_enhanced_health_check_handler_error_handling() {
         // Create a health use case that will fail
-        let health_use_case = Arc::new(HealthCheckUseCase);
+        let health_use_case = Arc::new(HealthCheckUseCase::default());
         let config = Arc::new(create_test_config());
         let external_rpc = Arc::new(crate::infrastructure::adapters::ExternalRpcAdapter::new(config));
         
         // Mock a scenario where the health check fails
         // This is difficult to test directly, but we can ensure the error handling path exists
-        let result = handle_enhanced_health_check(health_use_case, Some(external_rpc)).await;
+        let result = handle_enhanced_health_check(health_use_case, Some(external_rpc), false, None, None, &SecurityHeadersMiddleware::new(AppConfig::default())).await;
         
         // Should still return a valid response (not a rejection)
         assert!(result.is_ok());
@@ -354,6 +456,9 @@ mod tests {
             health_use_case,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
+            create_test_mining_pool_client(),
+            create_test_cpu_pool(),
         );
 
         // Test that the health route is accessible
@@ -388,6 +493,9 @@ mod tests {
             health_use_case,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
+            create_test_mining_pool_client(),
+            create_test_cpu_pool(),
         );
 
         // Test health route
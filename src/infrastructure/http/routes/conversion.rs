@@ -0,0 +1,64 @@
+//! Conversion estimate routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::ConversionService;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::handle_estimate_batch, utils::with_config};
+
+pub struct ConversionRoutes;
+
+impl ConversionRoutes {
+    /// Create the `POST /convert/estimate-batch` route
+    pub fn create_estimate_batch_route(
+        config: AppConfig,
+        service: Arc<ConversionService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("convert")
+            .and(warp::path("estimate-batch"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(config.server.max_request_size as u64))
+            .and(warp::body::json())
+            .and(warp::any().map(move || service.clone()))
+            .and(with_config(config))
+            .and_then(handle_estimate_batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+
+    fn route(enabled: bool) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let mut config = AppConfig::default();
+        config.conversion_estimates.enabled = enabled;
+        let config_arc = Arc::new(config.clone());
+        let service = Arc::new(ConversionService::new(config_arc.clone(), Arc::new(ExternalRpcAdapter::new(config_arc))));
+        ConversionRoutes::create_estimate_batch_route(config, service)
+    }
+
+    #[tokio::test]
+    async fn test_estimate_batch_rejects_empty_list() {
+        let res = warp::test::request()
+            .method("POST")
+            .path("/convert/estimate-batch")
+            .json(&serde_json::json!({ "conversions": [] }))
+            .reply(&route(true))
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_batch_disabled_is_not_found() {
+        let res = warp::test::request()
+            .method("POST")
+            .path("/convert/estimate-batch")
+            .json(&serde_json::json!({ "conversions": [{ "from": "VRSC", "to": "vETH", "amount": 1.0 }] }))
+            .reply(&route(false))
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,68 @@
+//! Chain event routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::ChainEventBus;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::handle_event_stream, utils::with_config};
+
+pub struct EventRoutes;
+
+impl EventRoutes {
+    /// Create the `/api/events` WebSocket route
+    pub fn create_stream_route(
+        config: AppConfig,
+        bus: Arc<ChainEventBus>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("api")
+            .and(warp::path("events"))
+            .and(warp::path::end())
+            .and(warp::ws())
+            .and(warp::any().map(move || bus.clone()))
+            .and(with_config(config))
+            .and_then(handle_event_stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::{BlockRef, ChainEvent, ReorgEvent};
+
+    #[tokio::test]
+    async fn test_stream_disabled_by_default() {
+        let route = EventRoutes::create_stream_route(AppConfig::default(), Arc::new(ChainEventBus::new(8)));
+        let res = warp::test::request()
+            .path("/api/events")
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_stream_forwards_reorg_events() {
+        let mut config = AppConfig::default();
+        config.chain_events.enabled = true;
+        let bus = Arc::new(ChainEventBus::new(8));
+        let route = EventRoutes::create_stream_route(config, bus.clone());
+        let mut client = warp::test::ws().path("/api/events").handshake(route).await.unwrap();
+
+        let event = ChainEvent::Reorg(ReorgEvent {
+            depth: 1,
+            fork_height: 99,
+            old_tip: BlockRef { height: 100, hash: "a".into() },
+            new_tip: BlockRef { height: 100, hash: "b".into() },
+            detected_at: chrono::Utc::now(),
+        });
+        assert_eq!(bus.publish(event), 1);
+        let message = client.recv().await.unwrap();
+        let value: serde_json::Value = serde_json::from_str(message.to_str().unwrap()).unwrap();
+        assert_eq!(value["type"], "reorg");
+        assert_eq!(value["new_tip"]["hash"], "b");
+    }
+}
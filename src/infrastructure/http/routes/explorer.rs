@@ -0,0 +1,235 @@
+//! Block explorer routes
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::{CurrencyHistoryService, ExplorerService, ShieldedTreeService};
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::{handle_address_balances, handle_currency_history, handle_full_block, handle_shielded_tree, handle_shielded_tree_range}, utils::with_config};
+
+pub struct ExplorerRoutes;
+
+impl ExplorerRoutes {
+    /// Create the `/api/block/{hash}/full` route
+    pub fn create_full_block_route(
+        config: AppConfig,
+        service: Arc<ExplorerService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("api")
+            .and(warp::path("block"))
+            .and(warp::path::param::<String>())
+            .and(warp::path("full"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(Self::with_service(service))
+            .and(warp::header::optional::<String>("if-none-match"))
+            .and(with_config(config))
+            .and_then(handle_full_block)
+    }
+
+    /// Create the `POST /api/addresses/balances` route
+    pub fn create_address_balances_route(
+        config: AppConfig,
+        service: Arc<ExplorerService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("api")
+            .and(warp::path("addresses"))
+            .and(warp::path("balances"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(config.server.max_request_size as u64))
+            .and(warp::body::json())
+            .and(Self::with_service(service))
+            .and(with_config(config))
+            .and_then(handle_address_balances)
+    }
+
+    /// Create the `GET /api/currency/{id}/history` route
+    pub fn create_currency_history_route(
+        config: AppConfig,
+        history: Arc<CurrencyHistoryService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("api")
+            .and(warp::path("currency"))
+            .and(warp::path::param::<String>())
+            .and(warp::path("history"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::any().map(move || history.clone()))
+            .and(warp::header::optional::<String>("if-none-match"))
+            .and(with_config(config))
+            .and_then(handle_currency_history)
+    }
+
+    /// Create the `GET /api/address/{addr}/txs` route served by the embedded index
+    #[cfg(feature = "indexer")]
+    pub fn create_address_txs_route(
+        config: AppConfig,
+        index: Arc<crate::application::services::AddressIndexService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("api")
+            .and(warp::path("address"))
+            .and(warp::path::param::<String>())
+            .and(warp::path("txs"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::any().map(move || index.clone()))
+            .and(warp::header::optional::<String>("if-none-match"))
+            .and(with_config(config))
+            .and_then(crate::infrastructure::http::handlers::handle_address_txs)
+    }
+
+    /// Create the `GET /api/shielded/tree/{height}` and `GET /api/shielded/tree` routes
+    pub fn create_shielded_tree_routes(
+        config: AppConfig,
+        trees: Arc<ShieldedTreeService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let base = warp::path("api").and(warp::path("shielded")).and(warp::path("tree"));
+        let by_height = base
+            .and(warp::path::param::<u64>())
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::any().map({
+                let trees = trees.clone();
+                move || trees.clone()
+            }))
+            .and(warp::header::optional::<String>("if-none-match"))
+            .and(with_config(config.clone()))
+            .and_then(handle_shielded_tree);
+        let range = base
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::any().map(move || trees.clone()))
+            .and(warp::header::optional::<String>("if-none-match"))
+            .and(with_config(config))
+            .and_then(handle_shielded_tree_range);
+        by_height.or(range)
+    }
+
+    /// Create all explorer routes
+    pub fn create_routes(
+        config: AppConfig,
+        service: Arc<ExplorerService>,
+        history: Arc<CurrencyHistoryService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        Self::create_full_block_route(config.clone(), service.clone())
+            .or(Self::create_address_balances_route(config.clone(), service))
+            .or(Self::create_currency_history_route(config, history))
+    }
+
+    fn with_service(
+        service: Arc<ExplorerService>,
+    ) -> impl Filter<Extract = (Arc<ExplorerService>,), Error = std::convert::Infallible> + Clone {
+        warp::any().map(move || service.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+
+    #[tokio::test]
+    async fn test_full_block_rejects_invalid_hash() {
+        let config = AppConfig::default();
+        let config_arc = Arc::new(config.clone());
+        let service = Arc::new(ExplorerService::new(config_arc.clone(), Arc::new(ExternalRpcAdapter::new(config_arc))));
+        let route = ExplorerRoutes::create_full_block_route(config, service);
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/api/block/xyz/full")
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_address_balances_rejects_empty_list() {
+        let config = AppConfig::default();
+        let config_arc = Arc::new(config.clone());
+        let rpc = Arc::new(ExternalRpcAdapter::new(config_arc.clone()));
+        let service = Arc::new(ExplorerService::new(config_arc.clone(), rpc.clone()));
+        let history = Arc::new(CurrencyHistoryService::new(config_arc, rpc));
+        let route = ExplorerRoutes::create_routes(config, service, history);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/addresses/balances")
+            .json(&serde_json::json!({ "addresses": [] }))
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_currency_history_unknown_currency_is_not_found() {
+        let mut config = AppConfig::default();
+        config.currency_history.enabled = true;
+        config.currency_history.currencies = vec!["VRSC".to_string()];
+        let config_arc = Arc::new(config.clone());
+        let history = Arc::new(CurrencyHistoryService::new(config_arc.clone(), Arc::new(ExternalRpcAdapter::new(config_arc))));
+        let route = ExplorerRoutes::create_currency_history_route(config, history);
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/api/currency/Bridge.vETH/history?from=0")
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/api/currency/vrsc/history")
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_shielded_tree_range_requires_min_height() {
+        let mut config = AppConfig::default();
+        let config_arc = Arc::new(config.clone());
+        let trees = Arc::new(ShieldedTreeService::new(config_arc.clone(), Arc::new(ExternalRpcAdapter::new(config_arc))));
+        let route = ExplorerRoutes::create_shielded_tree_routes(config.clone(), trees.clone());
+        let res = warp::test::request().method("GET").path("/api/shielded/tree").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+        let res = warp::test::request().method("GET").path("/api/shielded/tree?min_height=1&sort=-txid").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+
+        config.shielded_tree.enabled = false;
+        let route = ExplorerRoutes::create_shielded_tree_routes(config, trees);
+        let res = warp::test::request().method("GET").path("/api/shielded/tree/100").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+        let res = warp::test::request().method("GET").path("/api/shielded/tree?min_height=1").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(feature = "indexer")]
+    #[tokio::test]
+    async fn test_address_txs_requires_enabled_index() {
+        use crate::application::services::AddressIndexService;
+
+        let mut config = AppConfig::default();
+        let config_arc = Arc::new(config.clone());
+        let index = Arc::new(AddressIndexService::new(config_arc.clone(), Arc::new(ExternalRpcAdapter::new(config_arc))));
+        let route = ExplorerRoutes::create_address_txs_route(config.clone(), index.clone());
+        let res = warp::test::request().method("GET").path("/api/address/RAlice/txs").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+
+        config.indexer.enabled = true;
+        let route = ExplorerRoutes::create_address_txs_route(config, index);
+        let res = warp::test::request().method("GET").path("/api/address/RAlice/txs").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::OK);
+        let res = warp::test::request().method("GET").path("/api/address/RAlice/txs?cursor=5").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+        let res = warp::test::request().method("GET").path("/api/address/RAlice/txs?sort=-txid").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
+}
@@ -5,15 +5,18 @@
 
 use crate::{
     config::AppConfig,
+    infrastructure::adapters::{cluster::FORWARD_HEADER, CpuPool, MiningPoolClient},
     application::use_cases::{ProcessRpcRequestUseCase, GetMetricsUseCase, HealthCheckUseCase},
     infrastructure::http::{
         handlers::{
             handle_rpc_request, handle_metrics_request,
             handle_prometheus_request, handle_mining_pool_request, handle_pool_metrics_request,
         },
-        utils::{with_health_use_case, with_config, with_metrics_use_case, with_prometheus_adapter, with_mining_pool_client, with_cache_middleware, with_rate_limit_middleware, with_rpc_use_case},
+        processors::RequestGuards,
+        utils::{with_health_use_case, with_config, with_metrics_use_case, with_prometheus_adapter, with_mining_pool_client, with_cache_middleware, with_rate_limit_middleware, with_request_guards, with_rpc_use_case},
+        strict_json,
     },
-    middleware::{cache::CacheMiddleware, rate_limit::RateLimitMiddleware},
+    middleware::{cache::CacheMiddleware, rate_limit::RateLimitMiddleware, response_signing::SIGN_REQUEST_HEADER},
 };
 use std::sync::Arc;
 use warp::Filter;
@@ -27,13 +30,26 @@ pub struct FluentRouteBuilder {
     health_use_case: Option<Arc<HealthCheckUseCase>>,
     metrics_use_case: Option<Arc<GetMetricsUseCase>>,
     rpc_adapter: Option<Arc<crate::infrastructure::adapters::ExternalRpcAdapter>>,
+    request_guards: Arc<RequestGuards>,
+    mining_pool_client: Arc<MiningPoolClient>,
+    cpu_pool: Arc<CpuPool>,
+}
+
+impl Default for FluentRouteBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FluentRouteBuilder {
     /// Create a new fluent route builder
     pub fn new() -> Self {
+        let config = AppConfig::default();
         Self {
-            config: AppConfig::default(),
+            request_guards: Arc::new(RequestGuards::new(&config)),
+            mining_pool_client: Arc::new(MiningPoolClient::from_app_config(&config)),
+            cpu_pool: Arc::new(CpuPool::new(&config.server)),
+            config,
             cache_middleware: None,
             rate_limit_middleware: None,
             rpc_use_case: None,
@@ -44,11 +60,35 @@ impl FluentRouteBuilder {
     }
 
     /// Add config to the builder
+    ///
+    /// Request guards, the mining pool client and the CPU pool are rebuilt
+    /// from it; set them afterwards to share existing ones.
     pub fn with_config(mut self, config: AppConfig) -> Self {
+        self.request_guards = Arc::new(RequestGuards::new(&config));
+        self.mining_pool_client = Arc::new(MiningPoolClient::from_app_config(&config));
+        self.cpu_pool = Arc::new(CpuPool::new(&config.server));
         self.config = config;
         self
     }
 
+    /// Share admission state with other routes
+    pub fn with_request_guards(mut self, request_guards: Arc<RequestGuards>) -> Self {
+        self.request_guards = request_guards;
+        self
+    }
+
+    /// Share a mining pool client with other routes
+    pub fn with_mining_pool_client(mut self, mining_pool_client: Arc<MiningPoolClient>) -> Self {
+        self.mining_pool_client = mining_pool_client;
+        self
+    }
+
+    /// Share a CPU pool with other routes
+    pub fn with_cpu_pool(mut self, cpu_pool: Arc<CpuPool>) -> Self {
+        self.cpu_pool = cpu_pool;
+        self
+    }
+
     /// Add cache middleware to the builder
     pub fn with_cache_middleware(mut self, cache_middleware: Arc<CacheMiddleware>) -> Self {
         self.cache_middleware = Some(cache_middleware);
@@ -94,18 +134,27 @@ impl FluentRouteBuilder {
         let rate_limit_middleware = self.rate_limit_middleware.as_ref()
             .ok_or("Rate limit middleware is required for RPC route")?;
 
+        let rejection_config = self.config.clone();
         let route = warp::path::end()
             .and(warp::post())
-            .and(warp::body::content_length_limit(self.config.server.max_request_size as u64))
-            .and(warp::body::json())
+            .and(warp::body::content_length_limit(self.config.rpc_body_limit()))
+            .and(strict_json::rpc_body(&self.config))
             .and(warp::header::<String>("x-forwarded-for"))
             .and(warp::header::optional::<String>("authorization"))
+            .and(warp::header::optional::<String>("x-api-key"))
+            .and(warp::header::optional::<u64>("content-length"))
             .and(warp::header::optional::<String>("user-agent"))
+            .and(warp::header::optional::<String>("accept-language"))
+            .and(warp::header::optional::<String>(FORWARD_HEADER))
+            .and(warp::header::optional::<String>("x-debug-validate"))
+            .and(warp::header::optional::<String>(SIGN_REQUEST_HEADER))
             .and(with_rpc_use_case(rpc_use_case.clone()))
             .and(with_config(self.config.clone()))
             .and(with_cache_middleware(cache_middleware.clone()))
             .and(with_rate_limit_middleware(rate_limit_middleware.clone()))
-            .and_then(handle_rpc_request);
+            .and(with_request_guards(self.request_guards.clone()))
+            .and_then(handle_rpc_request)
+            .recover(move |rejection| strict_json::handle_rejection(rejection, rejection_config.clone()));
 
         Ok(route)
     }
@@ -135,11 +184,16 @@ impl FluentRouteBuilder {
     pub fn build_metrics_route(&self) -> Result<impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone, String> {
         let metrics_use_case = self.metrics_use_case.as_ref()
             .ok_or("Metrics use case is required for metrics route")?;
+        let rate_limit_middleware = self.rate_limit_middleware.clone()
+            .unwrap_or_else(|| Arc::new(RateLimitMiddleware::new(self.config.clone())));
 
         let route = warp::path("metrics")
             .and(warp::get())
             .and(with_metrics_use_case(metrics_use_case.clone()))
+            .and(warp::header::optional::<String>("if-none-match"))
             .and(with_config(self.config.clone()))
+            .and(with_request_guards(self.request_guards.clone()))
+            .and(with_rate_limit_middleware(rate_limit_middleware))
             .and_then(handle_metrics_request);
 
         Ok(route)
@@ -147,10 +201,22 @@ impl FluentRouteBuilder {
 
     /// Build Prometheus route with fluent API
     pub fn build_prometheus_route(&self) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let rate_limit_middleware = self.rate_limit_middleware.clone()
+            .unwrap_or_else(|| Arc::new(RateLimitMiddleware::new(self.config.clone())));
+        let cpu_pool = self.cpu_pool.clone();
+
         warp::path("prometheus")
             .and(warp::get())
             .and(with_prometheus_adapter())
+            .and(warp::any().map({
+                let cache_middleware = self.cache_middleware.clone();
+                move || cache_middleware.clone()
+            }))
+            .and(warp::header::optional::<String>("if-none-match"))
             .and(with_config(self.config.clone()))
+            .and(with_request_guards(self.request_guards.clone()))
+            .and(with_rate_limit_middleware(rate_limit_middleware))
+            .and(warp::any().map(move || cpu_pool.clone()))
             .and_then(handle_prometheus_request)
     }
 
@@ -167,10 +233,11 @@ impl FluentRouteBuilder {
             .and(warp::body::content_length_limit(self.config.server.max_request_size as u64))
             .and(warp::body::json())
             .and(warp::header::<String>("x-forwarded-for"))
-            .and(with_mining_pool_client())
+            .and(with_mining_pool_client(self.mining_pool_client.clone()))
             .and(with_config(self.config.clone()))
             .and(with_cache_middleware(cache_middleware.clone()))
             .and(with_rate_limit_middleware(rate_limit_middleware.clone()))
+            .and(with_request_guards(self.request_guards.clone()))
             .and_then(handle_mining_pool_request);
 
         Ok(route)
@@ -181,7 +248,7 @@ impl FluentRouteBuilder {
         warp::path("pool")
             .and(warp::path("metrics"))
             .and(warp::get())
-            .and(with_mining_pool_client())
+            .and(with_mining_pool_client(self.mining_pool_client.clone()))
             .and(with_config(self.config.clone()))
             .and_then(handle_pool_metrics_request)
     }
@@ -364,7 +431,7 @@ mod tests {
     }
 
     fn create_test_health_use_case() -> Arc<HealthCheckUseCase> {
-        Arc::new(HealthCheckUseCase)
+        Arc::new(HealthCheckUseCase::default())
     }
 
     fn create_test_metrics_use_case() -> Arc<GetMetricsUseCase> {
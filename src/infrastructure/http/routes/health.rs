@@ -0,0 +1,86 @@
+//! Health history routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::HealthHistoryService;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{
+    handlers::{handle_capabilities, handle_health_history, health::HealthHistoryQuery},
+    utils::with_config,
+};
+
+pub struct HealthRoutes;
+
+impl HealthRoutes {
+    /// Create the `GET /health/history` route
+    pub fn create_history_route(
+        config: AppConfig,
+        service: Arc<HealthHistoryService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("health")
+            .and(warp::path("history"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::query::<HealthHistoryQuery>())
+            .and(warp::any().map(move || service.clone()))
+            .and(with_config(config))
+            .and_then(handle_health_history)
+    }
+
+    /// Create the `GET /capabilities` route
+    pub fn create_capabilities_route(
+        config: AppConfig,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("capabilities")
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(with_config(config))
+            .and_then(handle_capabilities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::ComponentState;
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+
+    fn history_route(enabled: bool) -> (Arc<HealthHistoryService>, impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone) {
+        let mut config = AppConfig::default();
+        config.health_history.enabled = enabled;
+        let config_arc = Arc::new(config.clone());
+        let service = Arc::new(HealthHistoryService::new(config_arc.clone(), Arc::new(ExternalRpcAdapter::new(config_arc)), None));
+        (service.clone(), HealthRoutes::create_history_route(config, service))
+    }
+
+    #[tokio::test]
+    async fn test_history_route() {
+        let (service, route) = history_route(true);
+        let now = chrono::Utc::now();
+        service.record("daemon", ComponentState::Up, None, now - chrono::Duration::minutes(5)).await;
+        service.record("daemon", ComponentState::Down, Some("refused".into()), now).await;
+
+        let res = warp::test::request().path("/health/history?limit=1").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["transitions"].as_array().unwrap().len(), 1);
+        assert_eq!(body["transitions"][0]["state"], "down");
+        assert_eq!(body["components"][0]["component"], "daemon");
+        assert!(body["components"][0]["uptime_percent"]["1h"].as_f64().unwrap() > 99.0);
+
+        let (_, disabled) = history_route(false);
+        let res = warp::test::request().path("/health/history").reply(&disabled).await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_route_before_detection() {
+        let route = HealthRoutes::create_capabilities_route(AppConfig::default());
+        let res = warp::test::request().path("/capabilities").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["detected"], false);
+        assert!(body["disabled_methods"].as_array().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+//! Asynchronous job routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::JobService;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::{handle_job, handle_submit_job}, utils::with_config};
+
+pub struct JobRoutes;
+
+impl JobRoutes {
+    /// Create the `POST /jobs` and `GET /jobs/{id}` routes
+    pub fn create_routes(
+        config: AppConfig,
+        service: Arc<JobService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let submit = warp::path("jobs")
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(config.server.max_request_size as u64))
+            .and(warp::body::json())
+            .and(warp::header::<String>("x-forwarded-for"))
+            .and(Self::with_service(service.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_submit_job);
+        let status = warp::path("jobs")
+            .and(warp::path::param::<String>())
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(Self::with_service(service))
+            .and(with_config(config))
+            .and_then(handle_job);
+        submit.or(status)
+    }
+
+    fn with_service(
+        service: Arc<JobService>,
+    ) -> impl Filter<Extract = (Arc<JobService>,), Error = std::convert::Infallible> + Clone {
+        warp::any().map(move || service.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::ExplorerService;
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+    use crate::middleware::cache::CacheMiddleware;
+
+    async fn route(enabled: bool) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let mut config = AppConfig::default();
+        config.jobs.enabled = enabled;
+        let cache = Arc::new(CacheMiddleware::new(&config).await.unwrap());
+        let config_arc = Arc::new(config.clone());
+        let rpc = Arc::new(ExternalRpcAdapter::new(config_arc.clone()));
+        let explorer = Arc::new(ExplorerService::new(config_arc.clone(), rpc.clone()));
+        JobRoutes::create_routes(config, Arc::new(JobService::new(config_arc, explorer, rpc, cache)))
+    }
+
+    #[tokio::test]
+    async fn test_submit_rejects_invalid_query() {
+        let route = route(true).await;
+        let res = warp::test::request()
+            .method("POST")
+            .path("/jobs")
+            .header("x-forwarded-for", "127.0.0.1")
+            .json(&serde_json::json!({ "kind": "full_block", "hash": "xyz" }))
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+
+        let res = warp::test::request().method("GET").path("/jobs/unknown").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_disabled_is_not_found() {
+        let res = warp::test::request()
+            .method("POST")
+            .path("/jobs")
+            .header("x-forwarded-for", "127.0.0.1")
+            .json(&serde_json::json!({ "kind": "address_balances", "addresses": ["RAlice"] }))
+            .reply(&route(false).await)
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,60 @@
+//! Marketplace routes
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::MarketplaceService;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::handle_marketplace_offers, utils::with_config};
+
+pub struct MarketplaceRoutes;
+
+impl MarketplaceRoutes {
+    /// Create the `GET /marketplace/offers/{name}` route
+    pub fn create_offers_route(
+        config: AppConfig,
+        service: Arc<MarketplaceService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("marketplace")
+            .and(warp::path("offers"))
+            .and(warp::path::param::<String>())
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::any().map(move || service.clone()))
+            .and(warp::header::optional::<String>("if-none-match"))
+            .and(with_config(config))
+            .and_then(handle_marketplace_offers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::NameResolverService;
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+
+    fn route(enabled: bool) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let mut config = AppConfig::default();
+        config.marketplace.enabled = enabled;
+        let config_arc = Arc::new(config.clone());
+        let rpc = Arc::new(ExternalRpcAdapter::new(config_arc.clone()));
+        let resolver = Arc::new(NameResolverService::new(config_arc.clone(), rpc.clone()));
+        MarketplaceRoutes::create_offers_route(config, Arc::new(MarketplaceService::new(config_arc, rpc, resolver)))
+    }
+
+    #[tokio::test]
+    async fn test_offers_rejects_invalid_query() {
+        for path in ["/marketplace/offers/VRSC?sort=volume", "/marketplace/offers/VRSC?direction=sideways", "/marketplace/offers/a%20b"] {
+            let res = warp::test::request().method("GET").path(path).reply(&route(true)).await;
+            assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST, "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_offers_disabled_is_not_found() {
+        let res = warp::test::request().method("GET").path("/marketplace/offers/VRSC").reply(&route(false)).await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+}
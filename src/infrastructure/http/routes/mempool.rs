@@ -0,0 +1,56 @@
+//! Mempool routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::MempoolService;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::handle_mempool_stats, utils::with_config};
+
+pub struct MempoolRoutes;
+
+impl MempoolRoutes {
+    /// Create the `/mempool/stats` route
+    pub fn create_stats_route(
+        config: AppConfig,
+        service: Arc<MempoolService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("mempool")
+            .and(warp::path("stats"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(Self::with_service(service))
+            .and(warp::header::optional::<String>("if-none-match"))
+            .and(with_config(config))
+            .and_then(handle_mempool_stats)
+    }
+
+    fn with_service(
+        service: Arc<MempoolService>,
+    ) -> impl Filter<Extract = (Arc<MempoolService>,), Error = std::convert::Infallible> + Clone {
+        warp::any().map(move || service.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+
+    #[tokio::test]
+    async fn test_stats_unavailable_before_first_sample() {
+        let mut config = AppConfig::default();
+        config.mempool.enabled = true;
+        let config_arc = Arc::new(config.clone());
+        let service = Arc::new(MempoolService::new(config_arc.clone(), Arc::new(ExternalRpcAdapter::new(config_arc))));
+        let route = MempoolRoutes::create_stats_route(config, service);
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/mempool/stats")
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
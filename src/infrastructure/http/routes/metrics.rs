@@ -5,10 +5,13 @@
 use crate::{
     config::AppConfig,
     infrastructure::http::{
-        utils::{with_metrics_use_case, with_config, with_prometheus_adapter},
-        handlers::{handle_metrics_request, handle_prometheus_request},
+        utils::{with_metrics_use_case, with_config, with_prometheus_adapter, with_rate_limit_middleware, with_request_guards},
+        handlers::{handle_metrics_request, handle_metrics_summary_request, handle_prometheus_request},
+        processors::RequestGuards,
     },
     application::use_cases::GetMetricsUseCase,
+    infrastructure::adapters::CpuPool,
+    middleware::{cache::CacheMiddleware, rate_limit::RateLimitMiddleware},
 };
 use std::sync::Arc;
 use warp::Filter;
@@ -21,25 +24,52 @@ impl MetricsRoutes {
     pub fn create_metrics_route(
         config: AppConfig,
         metrics_use_case: Arc<GetMetricsUseCase>,
+        request_guards: Arc<RequestGuards>,
+        rate_limit_middleware: Arc<RateLimitMiddleware>,
     ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
         warp::path("metrics")
             .and(warp::get())
             .and(with_metrics_use_case(metrics_use_case))
+            .and(warp::header::optional::<String>("if-none-match"))
             .and(with_config(config))
+            .and(with_request_guards(request_guards))
+            .and(with_rate_limit_middleware(rate_limit_middleware))
             .and_then(handle_metrics_request)
     }
 
     /// Create the Prometheus metrics endpoint route
     pub fn create_prometheus_route(
         config: AppConfig,
+        cache_middleware: Option<Arc<CacheMiddleware>>,
+        request_guards: Arc<RequestGuards>,
+        rate_limit_middleware: Arc<RateLimitMiddleware>,
+        cpu_pool: Arc<CpuPool>,
     ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
         warp::path("metrics")
             .and(warp::path("prometheus"))
             .and(warp::get())
             .and(with_prometheus_adapter())
+            .and(warp::any().map(move || cache_middleware.clone()))
+            .and(warp::header::optional::<String>("if-none-match"))
             .and(with_config(config))
+            .and(with_request_guards(request_guards))
+            .and(with_rate_limit_middleware(rate_limit_middleware))
+            .and(warp::any().map(move || cpu_pool.clone()))
             .and_then(handle_prometheus_request)
     }
+
+    /// Create the windowed SLI summary route
+    pub fn create_metrics_summary_route(
+        config: AppConfig,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("metrics")
+            .and(warp::path("summary"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::optional::<String>("if-none-match"))
+            .and(with_config(config))
+            .and_then(handle_metrics_summary_request)
+    }
 }
 
 #[cfg(test)]
@@ -57,16 +87,29 @@ mod tests {
         Arc::new(GetMetricsUseCase::new(metrics_service))
     }
 
+    fn create_test_metrics_route(
+        config: AppConfig,
+        metrics_use_case: Arc<GetMetricsUseCase>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let request_guards = Arc::new(RequestGuards::new(&config));
+        let rate_limit_middleware = Arc::new(RateLimitMiddleware::new(config.clone()));
+        MetricsRoutes::create_metrics_route(config, metrics_use_case, request_guards, rate_limit_middleware)
+    }
+
+    fn create_test_prometheus_route(config: AppConfig) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let request_guards = Arc::new(RequestGuards::new(&config));
+        let rate_limit_middleware = Arc::new(RateLimitMiddleware::new(config.clone()));
+        let cpu_pool = Arc::new(CpuPool::new(&config.server));
+        MetricsRoutes::create_prometheus_route(config, None, request_guards, rate_limit_middleware, cpu_pool)
+    }
+
     #[test]
     fn test_metrics_routes_create_metrics_route() {
         let config = create_test_config();
         let metrics_use_case = create_test_metrics_use_case();
 
         // This should not panic and should return a valid filter
-        let route = MetricsRoutes::create_metrics_route(
-            config,
-            metrics_use_case,
-        );
+        let route = create_test_metrics_route(config, metrics_use_case);
         let _ = route.clone();
     }
 
@@ -75,7 +118,7 @@ mod tests {
         let config = create_test_config();
 
         // This should not panic and should return a valid filter
-        let route = MetricsRoutes::create_prometheus_route(config);
+        let route = create_test_prometheus_route(config);
         let _ = route.clone();
     }
 
@@ -87,12 +130,9 @@ mod tests {
         let metrics_use_case = create_test_metrics_use_case();
 
         // Should work with different config values
-        let metrics_route = MetricsRoutes::create_metrics_route(
-            config.clone(),
-            metrics_use_case,
-        );
+        let metrics_route = create_test_metrics_route(config.clone(), metrics_use_case);
 
-        let prometheus_route = MetricsRoutes::create_prometheus_route(config);
+        let prometheus_route = create_test_prometheus_route(config);
         let _ = metrics_route.clone();
         let _ = prometheus_route.clone();
     }
@@ -103,12 +143,9 @@ mod tests {
         let metrics_use_case = create_test_metrics_use_case();
 
         // Test that the routes are created with the expected structure
-        let metrics_route = MetricsRoutes::create_metrics_route(
-            config.clone(),
-            metrics_use_case,
-        );
+        let metrics_route = create_test_metrics_route(config.clone(), metrics_use_case);
 
-        let prometheus_route = MetricsRoutes::create_prometheus_route(config);
+        let prometheus_route = create_test_prometheus_route(config);
         let _ = metrics_route.clone();
         let _ = prometheus_route.clone();
     }
@@ -117,7 +154,7 @@ mod tests {
     async fn test_metrics_route_e2e_status_headers_body() {
         let config = create_test_config();
         let metrics_use_case = create_test_metrics_use_case();
-        let route = MetricsRoutes::create_metrics_route(config, metrics_use_case);
+        let route = create_test_metrics_route(config, metrics_use_case);
 
         let res = warp::test::request()
             .method("GET")
@@ -134,7 +171,7 @@ mod tests {
     #[tokio::test]
     async fn test_prometheus_route_e2e_status_headers_content_type() {
         let config = create_test_config();
-        let route = MetricsRoutes::create_prometheus_route(config);
+        let route = create_test_prometheus_route(config);
 
         let res = warp::test::request()
             .method("GET")
@@ -151,4 +188,21 @@ mod tests {
         let text = std::str::from_utf8(res.body()).unwrap();
         assert!(text.contains("# HELP"));
     }
+
+    #[tokio::test]
+    async fn test_metrics_summary_route_reports_windows() {
+        let route = MetricsRoutes::create_metrics_summary_route(create_test_config());
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/metrics/summary")
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), warp::http::StatusCode::OK);
+        let body: Value = serde_json::from_slice(res.body()).unwrap();
+        for window in ["1m", "5m", "1h"] {
+            assert!(body["windows"][window].get("cache_hit_ratio").is_some());
+        }
+    }
 }
@@ -4,9 +4,11 @@
 
 use crate::{
     config::AppConfig,
+    infrastructure::adapters::MiningPoolClient,
     infrastructure::http::{
-        utils::{with_mining_pool_client, with_config, with_cache_middleware, with_rate_limit_middleware},
+        utils::{with_mining_pool_client, with_config, with_cache_middleware, with_rate_limit_middleware, with_request_guards},
         handlers::{handle_mining_pool_request, handle_pool_metrics_request},
+        processors::RequestGuards,
     },
     middleware::{cache::CacheMiddleware, rate_limit::RateLimitMiddleware},
 };
@@ -22,6 +24,8 @@ impl MiningPoolRoutes {
         config: AppConfig,
         cache_middleware: Arc<CacheMiddleware>,
         rate_limit_middleware: Arc<RateLimitMiddleware>,
+        mining_pool_client: Arc<MiningPoolClient>,
+        request_guards: Arc<RequestGuards>,
     ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
         warp::path("pool")
             .and(warp::path("share"))
@@ -29,21 +33,23 @@ impl MiningPoolRoutes {
             .and(warp::body::content_length_limit(config.server.max_request_size as u64))
             .and(warp::body::json())
             .and(warp::header::<String>("x-forwarded-for"))
-            .and(with_mining_pool_client())
+            .and(with_mining_pool_client(mining_pool_client))
             .and(with_config(config))
             .and(with_cache_middleware(cache_middleware))
             .and(with_rate_limit_middleware(rate_limit_middleware))
+            .and(with_request_guards(request_guards))
             .and_then(handle_mining_pool_request)
     }
 
     /// Create the mining pool metrics endpoint route
     pub fn create_pool_metrics_route(
         config: AppConfig,
+        mining_pool_client: Arc<MiningPoolClient>,
     ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
         warp::path("pool")
             .and(warp::path("metrics"))
             .and(warp::get())
-            .and(with_mining_pool_client())
+            .and(with_mining_pool_client(mining_pool_client))
             .and(with_config(config))
             .and_then(handle_pool_metrics_request)
     }
@@ -66,6 +72,14 @@ mod tests {
         Arc::new(RateLimitMiddleware::new(create_test_config()))
     }
 
+    fn create_test_mining_pool_client() -> Arc<MiningPoolClient> {
+        Arc::new(MiningPoolClient::from_app_config(&create_test_config()))
+    }
+
+    fn create_test_request_guards() -> Arc<RequestGuards> {
+        Arc::new(RequestGuards::new(&create_test_config()))
+    }
+
     #[tokio::test]
     async fn test_mining_pool_routes_create_mining_pool_route() {
         let config = create_test_config();
@@ -77,6 +91,8 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_mining_pool_client(),
+            create_test_request_guards(),
         );
         let _ = route.clone();
     }
@@ -86,7 +102,7 @@ mod tests {
         let config = create_test_config();
 
         // This should not panic and should return a valid filter
-        let route = MiningPoolRoutes::create_pool_metrics_route(config);
+        let route = MiningPoolRoutes::create_pool_metrics_route(config, create_test_mining_pool_client());
         let _ = route.clone();
     }
 
@@ -103,9 +119,11 @@ mod tests {
             config.clone(),
             cache_middleware,
             rate_limit_middleware,
+            create_test_mining_pool_client(),
+            create_test_request_guards(),
         );
 
-        let pool_metrics_route = MiningPoolRoutes::create_pool_metrics_route(config);
+        let pool_metrics_route = MiningPoolRoutes::create_pool_metrics_route(config, create_test_mining_pool_client());
         let _ = mining_pool_route.clone();
         let _ = pool_metrics_route.clone();
     }
@@ -121,9 +139,11 @@ mod tests {
             config.clone(),
             cache_middleware,
             rate_limit_middleware,
+            create_test_mining_pool_client(),
+            create_test_request_guards(),
         );
 
-        let pool_metrics_route = MiningPoolRoutes::create_pool_metrics_route(config);
+        let pool_metrics_route = MiningPoolRoutes::create_pool_metrics_route(config, create_test_mining_pool_client());
         let _ = mining_pool_route.clone();
         let _ = pool_metrics_route.clone();
     }
@@ -137,6 +157,8 @@ mod tests {
             config,
             cache_middleware,
             rate_limit_middleware,
+            create_test_mining_pool_client(),
+            create_test_request_guards(),
         );
 
         let req_body = json!({
@@ -172,7 +194,7 @@ mod tests {
     #[tokio::test]
     async fn test_mining_pool_metrics_route_e2e() {
         let config = create_test_config();
-        let route = MiningPoolRoutes::create_pool_metrics_route(config);
+        let route = MiningPoolRoutes::create_pool_metrics_route(config, create_test_mining_pool_client());
 
         let res = warp::test::request()
             .method("GET")
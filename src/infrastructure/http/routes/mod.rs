@@ -9,6 +9,20 @@ pub mod rpc;
 pub mod metrics;
 pub mod mining_pool;
 pub mod payments;
+pub mod mempool;
+pub mod explorer;
+pub mod resolver;
+pub mod conversion;
+pub mod marketplace;
+pub mod portfolio;
+pub mod jobs;
+pub mod reports;
+pub mod admin;
+pub mod events;
+pub mod tracking;
+pub mod health;
+#[cfg(feature = "status-page")]
+pub mod status;
 
 // Re-export commonly used types
 pub use builder::RouteBuilder;
@@ -18,3 +32,17 @@ pub use rpc::RpcRoutes;
 pub use metrics::MetricsRoutes;
 pub use mining_pool::MiningPoolRoutes;
 pub use payments::PaymentsRoutes;
+pub use mempool::MempoolRoutes;
+pub use explorer::ExplorerRoutes;
+pub use resolver::ResolverRoutes;
+pub use conversion::ConversionRoutes;
+pub use marketplace::MarketplaceRoutes;
+pub use portfolio::PortfolioRoutes;
+pub use jobs::JobRoutes;
+pub use reports::ReportRoutes;
+pub use admin::AdminRoutes;
+pub use events::EventRoutes;
+pub use tracking::TrackingRoutes;
+pub use health::HealthRoutes;
+#[cfg(feature = "status-page")]
+pub use status::StatusRoutes;
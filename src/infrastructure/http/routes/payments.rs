@@ -5,7 +5,12 @@ use warp::Filter;
 
 use crate::application::services::payments_service::PaymentsService;
 use crate::config::AppConfig;
-use crate::infrastructure::http::handlers::{handle_payment_quote, handle_payment_status, handle_payment_submit};
+use crate::middleware::rate_limit::RateLimitMiddleware;
+use crate::application::services::payments_service::PaymentHistoryQuery;
+use crate::infrastructure::http::handlers::{
+    handle_payment_history, handle_payment_quote, handle_payment_receipt, handle_payment_status, handle_payment_submit,
+    handle_payment_token_refresh,
+};
 
 pub struct PaymentsRoutes;
 
@@ -13,6 +18,7 @@ impl PaymentsRoutes {
     pub fn create_routes(
         config: AppConfig,
         service: Arc<PaymentsService>,
+        rate_limit_middleware: Arc<RateLimitMiddleware>,
     ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
         let quote = warp::path("payments")
             .and(warp::path("request"))
@@ -22,6 +28,7 @@ impl PaymentsRoutes {
             .and(warp::header::<String>("x-forwarded-for"))
             .and(Self::with_service(service.clone()))
             .and(Self::with_config(config.clone()))
+            .and(Self::with_rate_limit_middleware(rate_limit_middleware.clone()))
             .and_then(handle_payment_quote);
 
         let submit = warp::path("payments")
@@ -32,6 +39,7 @@ impl PaymentsRoutes {
             .and(warp::header::<String>("x-forwarded-for"))
             .and(Self::with_service(service.clone()))
             .and(Self::with_config(config.clone()))
+            .and(Self::with_rate_limit_middleware(rate_limit_middleware.clone()))
             .and_then(handle_payment_submit);
 
         let status = warp::path("payments")
@@ -39,11 +47,47 @@ impl PaymentsRoutes {
             .and(warp::path::param::<String>())
             .and(warp::get())
             .and(warp::header::<String>("x-forwarded-for"))
+            .and(Self::with_service(service.clone()))
+            .and(Self::with_config(config.clone()))
+            .and(Self::with_rate_limit_middleware(rate_limit_middleware.clone()))
+            .and_then(handle_payment_status);
+
+        let receipt = warp::path("payments")
+            .and(warp::path("receipt"))
+            .and(warp::path::param::<String>())
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::header::<String>("x-forwarded-for"))
+            .and(Self::with_service(service.clone()))
+            .and(Self::with_config(config.clone()))
+            .and(Self::with_rate_limit_middleware(rate_limit_middleware.clone()))
+            .and_then(handle_payment_receipt);
+
+        let history = warp::path("payments")
+            .and(warp::path("history"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::query::<PaymentHistoryQuery>())
+            .and(warp::header::<String>("x-forwarded-for"))
+            .and(warp::header::optional::<String>("authorization"))
+            .and(Self::with_service(service.clone()))
+            .and(Self::with_config(config.clone()))
+            .and(Self::with_rate_limit_middleware(rate_limit_middleware.clone()))
+            .and_then(handle_payment_history);
+
+        let token_refresh = warp::path("payments")
+            .and(warp::path("token"))
+            .and(warp::path("refresh"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::header::<String>("x-forwarded-for"))
+            .and(warp::header::optional::<String>("authorization"))
             .and(Self::with_service(service))
             .and(Self::with_config(config))
-            .and_then(handle_payment_status);
+            .and(Self::with_rate_limit_middleware(rate_limit_middleware))
+            .and_then(handle_payment_token_refresh);
 
-        quote.or(submit).or(status)
+        quote.or(submit).or(status).or(receipt).or(history).or(token_refresh)
     }
 
     fn with_service(
@@ -52,6 +96,12 @@ impl PaymentsRoutes {
         warp::any().map(move || service.clone())
     }
 
+    fn with_rate_limit_middleware(
+        rate_limit_middleware: Arc<RateLimitMiddleware>,
+    ) -> impl Filter<Extract = (Arc<RateLimitMiddleware>,), Error = std::convert::Infallible> + Clone {
+        warp::any().map(move || rate_limit_middleware.clone())
+    }
+
     fn with_config(
         config: AppConfig,
     ) -> impl Filter<Extract = (AppConfig,), Error = std::convert::Infallible> + Clone {
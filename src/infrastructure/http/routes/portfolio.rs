@@ -0,0 +1,66 @@
+//! Watch-only portfolio routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::PortfolioService;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::handle_portfolio, utils::with_config};
+
+pub struct PortfolioRoutes;
+
+impl PortfolioRoutes {
+    /// Create the `POST /api/portfolio` route
+    pub fn create_portfolio_route(
+        config: AppConfig,
+        service: Arc<PortfolioService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("api")
+            .and(warp::path("portfolio"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(config.server.max_request_size as u64))
+            .and(warp::body::json())
+            .and(warp::any().map(move || service.clone()))
+            .and(with_config(config))
+            .and_then(handle_portfolio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::NameResolverService;
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+
+    fn route(enabled: bool) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let mut config = AppConfig::default();
+        config.portfolio.enabled = enabled;
+        let config_arc = Arc::new(config.clone());
+        let rpc = Arc::new(ExternalRpcAdapter::new(config_arc.clone()));
+        let resolver = Arc::new(NameResolverService::new(config_arc.clone(), rpc.clone()));
+        PortfolioRoutes::create_portfolio_route(config, Arc::new(PortfolioService::new(config_arc, rpc, resolver)))
+    }
+
+    #[tokio::test]
+    async fn test_portfolio_rejects_empty_list() {
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/portfolio")
+            .json(&serde_json::json!({ "addresses": [] }))
+            .reply(&route(true))
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_portfolio_disabled_is_not_found() {
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/portfolio")
+            .json(&serde_json::json!({ "addresses": ["RAlice"] }))
+            .reply(&route(false))
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,59 @@
+//! Scheduled report routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::ReportService;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::handle_report, utils::with_config};
+
+pub struct ReportRoutes;
+
+impl ReportRoutes {
+    /// Create the `GET /reports/{name}` route
+    pub fn create_report_route(
+        config: AppConfig,
+        service: Arc<ReportService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("reports")
+            .and(warp::path::param::<String>())
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::any().map(move || service.clone()))
+            .and(with_config(config))
+            .and_then(handle_report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::app_config::{ReportConfig, ReportOutput, ReportTemplate};
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+    use crate::middleware::cache::CacheMiddleware;
+
+    #[tokio::test]
+    async fn test_report_not_found_before_first_run() {
+        let mut config = AppConfig::default();
+        config.reports.enabled = true;
+        config.reports.reports = vec![ReportConfig {
+            name: "mempool".to_string(),
+            schedule: "*/5 * * * *".to_string(),
+            template: ReportTemplate::MempoolStats,
+            currencies: Vec::new(),
+            method: None,
+            params: Vec::new(),
+            outputs: vec![ReportOutput::Cache],
+            webhook_url: None,
+        }];
+        let cache = Arc::new(CacheMiddleware::new(&config).await.unwrap());
+        let config_arc = Arc::new(config.clone());
+        let service = Arc::new(ReportService::new(config_arc.clone(), Arc::new(ExternalRpcAdapter::new(config_arc)), cache));
+        let route = ReportRoutes::create_report_route(config, service);
+
+        let res = warp::test::request().method("GET").path("/reports/mempool").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+        let res = warp::test::request().method("POST").path("/reports/mempool").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
@@ -0,0 +1,52 @@
+//! Name resolution routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::NameResolverService;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::handle_resolve, utils::with_config};
+
+pub struct ResolverRoutes;
+
+impl ResolverRoutes {
+    /// Create the `GET /resolve/{name}` route
+    pub fn create_resolve_route(
+        config: AppConfig,
+        service: Arc<NameResolverService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("resolve")
+            .and(warp::path::param::<String>())
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::any().map(move || service.clone()))
+            .and(with_config(config))
+            .and_then(handle_resolve)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+
+    fn route(enabled: bool) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let mut config = AppConfig::default();
+        config.name_resolver.enabled = enabled;
+        let config_arc = Arc::new(config.clone());
+        let service = Arc::new(NameResolverService::new(config_arc.clone(), Arc::new(ExternalRpcAdapter::new(config_arc))));
+        ResolverRoutes::create_resolve_route(config, service)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_requires_enabled_resolver() {
+        let res = warp::test::request().method("GET").path("/resolve/VRSC").reply(&route(false)).await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_rejects_invalid_name() {
+        let res = warp::test::request().method("GET").path("/resolve/a%20b").reply(&route(true)).await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
+}
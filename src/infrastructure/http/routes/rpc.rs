@@ -4,12 +4,15 @@
 
 use crate::{
     config::AppConfig,
+    infrastructure::adapters::cluster::FORWARD_HEADER,
     infrastructure::http::{
-        utils::{with_rpc_use_case, with_config, with_cache_middleware, with_rate_limit_middleware},
+        utils::{with_rpc_use_case, with_config, with_cache_middleware, with_rate_limit_middleware, with_request_guards},
         handlers::handle_rpc_request,
+        processors::RequestGuards,
+        strict_json,
     },
     application::use_cases::ProcessRpcRequestUseCase,
-    middleware::{cache::CacheMiddleware, rate_limit::RateLimitMiddleware},
+    middleware::{cache::CacheMiddleware, rate_limit::RateLimitMiddleware, response_signing::SIGN_REQUEST_HEADER},
 };
 use std::sync::Arc;
 use warp::Filter;
@@ -24,19 +27,29 @@ impl RpcRoutes {
         rpc_use_case: Arc<ProcessRpcRequestUseCase>,
         cache_middleware: Arc<CacheMiddleware>,
         rate_limit_middleware: Arc<RateLimitMiddleware>,
+        request_guards: Arc<RequestGuards>,
     ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let rejection_config = config.clone();
         warp::path::end()
             .and(warp::post())
-            .and(warp::body::content_length_limit(config.server.max_request_size as u64))
-            .and(warp::body::json())
+            .and(warp::body::content_length_limit(config.rpc_body_limit()))
+            .and(strict_json::rpc_body(&config))
             .and(warp::header::<String>("x-forwarded-for"))
             .and(warp::header::optional::<String>("authorization"))
+            .and(warp::header::optional::<String>("x-api-key"))
+            .and(warp::header::optional::<u64>("content-length"))
             .and(warp::header::optional::<String>("user-agent"))
+            .and(warp::header::optional::<String>("accept-language"))
+            .and(warp::header::optional::<String>(FORWARD_HEADER))
+            .and(warp::header::optional::<String>("x-debug-validate"))
+            .and(warp::header::optional::<String>(SIGN_REQUEST_HEADER))
             .and(with_rpc_use_case(rpc_use_case))
             .and(with_config(config))
             .and(with_cache_middleware(cache_middleware))
             .and(with_rate_limit_middleware(rate_limit_middleware))
+            .and(with_request_guards(request_guards))
             .and_then(handle_rpc_request)
+            .recover(move |rejection| strict_json::handle_rejection(rejection, rejection_config.clone()))
     }
 }
 
@@ -60,6 +73,10 @@ mod tests {
         Arc::new(RateLimitMiddleware::new(create_test_config()))
     }
 
+    fn create_test_request_guards() -> Arc<RequestGuards> {
+        Arc::new(RequestGuards::new(&create_test_config()))
+    }
+
     fn create_test_rpc_use_case() -> Arc<ProcessRpcRequestUseCase> {
         let config = Arc::new(create_test_config());
         let security_validator = Arc::new(crate::domain::security::SecurityValidator::new(SecurityPolicy::default()));
@@ -80,6 +97,7 @@ mod tests {
             rpc_use_case,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         );
         let _ = route.clone();
     }
@@ -100,6 +118,7 @@ mod tests {
             rpc_use_case,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         );
         let _ = route.clone();
     }
@@ -116,6 +135,7 @@ mod tests {
             rpc_use_case,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         );
 
         let req_body = json!({
@@ -153,6 +173,7 @@ mod tests {
             rpc_use_case,
             cache_middleware,
             rate_limit_middleware,
+            create_test_request_guards(),
         );
 
         let req_body = json!({
@@ -177,4 +198,39 @@ mod tests {
         assert!(body.get("error").is_some());
         assert!(body.get("id").is_some());
     }
+
+    #[tokio::test]
+    async fn test_rpc_route_explains_validation_failure() {
+        let mut config = create_test_config();
+        config.security.development_mode = true;
+        config.cache.enabled = false;
+        config.rate_limit.enabled = false;
+        let security_validator = Arc::new(crate::domain::security::SecurityValidator::new(SecurityPolicy::default()));
+        let rpc_service = Arc::new(RpcService::new(Arc::new(config.clone()), security_validator));
+        let rpc_use_case = Arc::new(ProcessRpcRequestUseCase::new(rpc_service, Arc::new(MetricsService::new())));
+        let route = RpcRoutes::create_rpc_route(
+            config,
+            rpc_use_case,
+            create_test_cache_middleware().await,
+            create_test_rate_limit_middleware(),
+            create_test_request_guards(),
+        );
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/")
+            .header("x-forwarded-for", "127.0.0.1")
+            .header("x-debug-validate", "true")
+            .json(&json!({"jsonrpc": "2.0", "method": "getblock", "params": [12], "id": 1}))
+            .reply(&route)
+            .await;
+
+        let body: Value = serde_json::from_slice(res.body()).unwrap();
+        let trace = &body["error"]["data"]["validation"];
+        assert_eq!(trace["passed"], false);
+        assert_eq!(trace["failure"]["rule"], "type");
+        assert_eq!(trace["failure"]["parameter_index"], 0);
+        assert_eq!(trace["failure"]["expected"], "string");
+        assert_eq!(trace["failure"]["got"], "number");
+    }
 }
@@ -0,0 +1,70 @@
+//! Status page routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::config::AppConfig;
+use crate::infrastructure::http::{
+    handlers::{handle_status_page, status::StatusPageSources},
+    utils::with_config,
+};
+
+pub struct StatusRoutes;
+
+impl StatusRoutes {
+    /// Create the `GET /status` route
+    pub fn create_status_route(
+        config: AppConfig,
+        sources: Arc<StatusPageSources>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        warp::path("status")
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(warp::any().map(move || sources.clone()))
+            .and(with_config(config))
+            .and_then(handle_status_page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{
+        services::{HealthHistoryService, MetricsService},
+        use_cases::{GetMetricsUseCase, HealthCheckUseCase},
+    };
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+    use crate::middleware::cache::CacheMiddleware;
+
+    async fn status_route(enabled: bool) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let mut config = AppConfig::default();
+        config.status_page.enabled = enabled;
+        config.cache.enabled = false;
+        config.verus.rpc_url = "http://127.0.0.1:9".to_string();
+        let config_arc = Arc::new(config.clone());
+        let rpc = Arc::new(ExternalRpcAdapter::new(config_arc.clone()));
+        let sources = Arc::new(StatusPageSources {
+            health_use_case: Arc::new(HealthCheckUseCase::default()),
+            metrics_use_case: Arc::new(GetMetricsUseCase::new(Arc::new(MetricsService::new()))),
+            rpc: rpc.clone(),
+            cache: Arc::new(CacheMiddleware::new(&config).await.unwrap()),
+            health_history: Arc::new(HealthHistoryService::new(config_arc, rpc, None)),
+        });
+        StatusRoutes::create_status_route(config, sources)
+    }
+
+    #[tokio::test]
+    async fn test_status_route() {
+        let route = status_route(true).await;
+        let res = warp::test::request().path("/status").reply(&route).await;
+        assert_eq!(res.status(), warp::http::StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "text/html; charset=utf-8");
+        let body = String::from_utf8(res.body().to_vec()).unwrap();
+        assert!(body.contains("<h1>Verus RPC Server</h1>"));
+        assert!(body.contains("<td>unavailable</td>"));
+
+        let disabled = status_route(false).await;
+        let res = warp::test::request().path("/status").reply(&disabled).await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+}
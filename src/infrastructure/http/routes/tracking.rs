@@ -0,0 +1,110 @@
+//! Transaction tracking routes
+
+use std::sync::Arc;
+use warp::Filter;
+
+use crate::application::services::TxTrackingService;
+use crate::config::AppConfig;
+use crate::infrastructure::http::{handlers::{handle_track_tx, handle_tracked_tx}, utils::with_config};
+
+pub struct TrackingRoutes;
+
+impl TrackingRoutes {
+    /// Create the `POST /track/tx` and `GET /track/tx/{id}` routes
+    pub fn create_routes(
+        config: AppConfig,
+        service: Arc<TxTrackingService>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let register = warp::path("track")
+            .and(warp::path("tx"))
+            .and(warp::path::end())
+            .and(warp::post())
+            .and(warp::body::content_length_limit(4 * 1024))
+            .and(warp::body::json())
+            .and(warp::header::<String>("x-forwarded-for"))
+            .and(Self::with_service(service.clone()))
+            .and(with_config(config.clone()))
+            .and_then(handle_track_tx);
+
+        let lookup = warp::path("track")
+            .and(warp::path("tx"))
+            .and(warp::path::param::<String>())
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(Self::with_service(service))
+            .and(with_config(config))
+            .and_then(handle_tracked_tx);
+
+        register.or(lookup)
+    }
+
+    fn with_service(
+        service: Arc<TxTrackingService>,
+    ) -> impl Filter<Extract = (Arc<TxTrackingService>,), Error = std::convert::Infallible> + Clone {
+        warp::any().map(move || service.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::ChainEventBus;
+    use crate::infrastructure::adapters::ExternalRpcAdapter;
+
+    fn routes(enabled: bool) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let mut config = AppConfig::default();
+        config.tx_tracking.enabled = enabled;
+        let config_arc = Arc::new(config.clone());
+        let service = Arc::new(TxTrackingService::new(
+            config_arc.clone(),
+            Arc::new(ExternalRpcAdapter::new(config_arc)),
+            Arc::new(ChainEventBus::new(8)),
+        ));
+        TrackingRoutes::create_routes(config, service)
+    }
+
+    #[tokio::test]
+    async fn test_register_and_lookup() {
+        let route = routes(true);
+        let txid = "ab".repeat(32);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/track/tx")
+            .header("x-forwarded-for", "127.0.0.1")
+            .json(&serde_json::json!({ "txid": txid, "confirmations": 6 }))
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::ACCEPTED);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["status"], "pending");
+
+        let res = warp::test::request()
+            .method("GET")
+            .path(&format!("/track/tx/{}", body["id"].as_str().unwrap()))
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::OK);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/track/tx")
+            .header("x-forwarded-for", "127.0.0.1")
+            .json(&serde_json::json!({ "txid": "nothex", "confirmations": 6 }))
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_tracking_is_not_found() {
+        let res = warp::test::request()
+            .method("POST")
+            .path("/track/tx")
+            .header("x-forwarded-for", "127.0.0.1")
+            .json(&serde_json::json!({ "txid": "ab".repeat(32), "confirmations": 1 }))
+            .reply(&routes(false))
+            .await;
+        assert_eq!(res.status(), warp::http::StatusCode::NOT_FOUND);
+    }
+}
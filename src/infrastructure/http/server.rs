@@ -4,19 +4,25 @@
 //! behind a reverse proxy (nginx, Caddy, etc.) that handles SSL, compression, and CORS.
 
 use crate::{
-    config::AppConfig,
+    config::{app_config::RevocationBackend, AppConfig},
     shared::error::{AppError, AppResult},
     infrastructure::http::{
-        routes::{RouteBuilder, PaymentsRoutes},
+        listener::BoundListener,
+        processors::{RequestGuards, RestGuard},
+        routes::{RouteBuilder, PaymentsRoutes, MempoolRoutes, ExplorerRoutes, ResolverRoutes, ConversionRoutes, MarketplaceRoutes, PortfolioRoutes, JobRoutes, ReportRoutes, AdminRoutes, EventRoutes, TrackingRoutes, HealthRoutes},
     },
     application::{
-        services::{RpcService, MetricsService},
+        services::{RpcService, MetricsService, MetricsPersistenceService, HealthHistoryService, MempoolService, ExplorerService, CurrencyHistoryService, ConversionService, MarketplaceService, PortfolioService, JobService, ReportService, NameResolverService, ShieldedTreeService, ChainEvent, ChainEventBus, ChainMonitorService, TxTrackingService, ViewingKeyService, MethodDiscoveryService, CapabilityService, payments_service::PaymentsService},
         use_cases::{ProcessRpcRequestUseCase, GetMetricsUseCase, HealthCheckUseCase},
     },
-    domain::{security::SecurityValidator, validation::DomainValidator, rpc::{RpcRequest, ClientInfo}},
-    infrastructure::adapters::{ExternalRpcAdapter, AuthenticationAdapter, PaymentsStore, TokenIssuerAdapter, RevocationStore},
+    domain::{health::HealthStatus, security::SecurityValidator, validation::DomainValidator},
+    infrastructure::adapters::{systemd, BanList, ClusterCoordinator, CpuPool, DaemonRecording, DaemonWait, Handover, MiningPoolClient, NegativeCache, PartnerUsageRegistry, UpstreamGate, UpstreamGuards, ExternalRpcAdapter, AuthenticationAdapter, ComprehensiveValidator, PaymentsStore, TokenIssuerAdapter, RevocationStore, SessionStore},
     middleware::{
         cache::CacheMiddleware, 
+        cache_warmer::CacheWarmer,
+        canonical_json::CanonicalJsonMiddleware,
+        cors::CorsMiddleware,
+        csrf::CsrfMiddleware,
         rate_limit::RateLimitMiddleware, 
     },
 };
@@ -28,46 +34,115 @@ use warp::{Filter, Reply};
 /// HTTP server implementation optimized for reverse proxy deployment
 pub struct HttpServer {
     config: AppConfig,
+    rpc_service: Arc<RpcService>,
     rpc_use_case: Arc<ProcessRpcRequestUseCase>,
     metrics_use_case: Arc<GetMetricsUseCase>,
     health_use_case: Arc<HealthCheckUseCase>,
     cache_middleware: Arc<CacheMiddleware>,
     rate_limit_middleware: Arc<RateLimitMiddleware>,
+    /// Admission state every request route checks against
+    request_guards: Arc<RequestGuards>,
+    cpu_pool: Arc<CpuPool>,
+    handover: Arc<Handover>,
+    mining_pool_client: Arc<MiningPoolClient>,
+    /// Daemon adapter shared by the REST facade and background services
+    external_rpc: Arc<ExternalRpcAdapter>,
     revocation_store: Arc<RevocationStore>,
-    payments_redis: Option<Arc<ConnectionManager>>,
+    session_store: Option<Arc<SessionStore>>,
+    mempool_service: Arc<MempoolService>,
+    currency_history_service: Arc<CurrencyHistoryService>,
+    name_resolver: Arc<NameResolverService>,
+    shielded_trees: Arc<ShieldedTreeService>,
+    report_service: Arc<ReportService>,
+    payments_service: Arc<PaymentsService>,
+    chain_events: Arc<ChainEventBus>,
+    chain_monitor: Arc<ChainMonitorService>,
+    tx_tracking_service: Arc<TxTrackingService>,
+    metrics_persistence: Arc<MetricsPersistenceService>,
+    health_history: Arc<HealthHistoryService>,
+    /// Daemon was down at startup; wait for it in `run`
+    defer_daemon_startup: bool,
+    #[cfg(feature = "indexer")]
+    address_index_service: Arc<crate::application::services::AddressIndexService>,
 }
 
 impl HttpServer {
     /// Create a new HTTP server instance optimized for reverse proxy deployment
     pub async fn new(config: AppConfig) -> AppResult<Self> {
+        if config.cors.enabled {
+            CorsMiddleware::new(config.clone()).validate_config().map_err(AppError::Config)?;
+        }
+
         // Initialize domain layer
         let security_validator = Arc::new(SecurityValidator::new(Default::default()));
         let _domain_validator = Arc::new(DomainValidator::new());
         
         // Initialize infrastructure layer
         let config_arc = Arc::new(config.clone());
-        let _external_rpc_adapter = Arc::new(ExternalRpcAdapter::new(config_arc.clone()));
-        // Revocation store setup: if cache.enabled, create Redis manager; else memory-only
-        let revocation_store = if config_arc.cache.enabled {
-            match Client::open(config_arc.cache.redis_url.clone()) {
+        // Revocation store setup: Redis per [revocation] backend (auto follows cache.enabled); else memory-only
+        let revocation_uses_redis = match config_arc.revocation.backend {
+            RevocationBackend::Auto => config_arc.cache.enabled,
+            RevocationBackend::Memory => false,
+            RevocationBackend::Redis => true,
+        };
+        // Cluster mode: one Redis connection for every piece of shared state; startup fails without it
+        let cluster = ClusterCoordinator::connect(&config).await?;
+        if cluster.is_some() && config_arc.revocation.backend == RevocationBackend::Memory {
+            tracing::warn!("revocation.backend=memory is ignored in cluster mode");
+        }
+        // Every daemon adapter the server builds shares one gate, negative cache and recording
+        let upstream = UpstreamGuards {
+            // Holds the cluster so `[maintenance] read_only` reaches every replica
+            gate: Arc::new(UpstreamGate::default().with_cluster(cluster.clone())),
+            negative_cache: Arc::new(NegativeCache::new(&config_arc.negative_cache)),
+            recording: DaemonRecording::from_config(&config_arc.recording)?.map(Arc::new),
+        };
+        upstream.gate.configure(&config_arc.maintenance).await?;
+        let _external_rpc_adapter = Arc::new(ExternalRpcAdapter::new(config_arc.clone()).with_guards(upstream.clone()));
+        let revocation_redis = if let Some(cluster) = &cluster {
+            Some(cluster.redis())
+        } else if revocation_uses_redis {
+            let url = config_arc.revocation.redis_url.clone().unwrap_or_else(|| config_arc.cache.redis_url.clone());
+            match Client::open(url) {
                 Ok(client) => match ConnectionManager::new(client).await {
-                    Ok(manager) => Arc::new(RevocationStore::new(Some(Arc::new(manager)))),
-                    Err(e) => { tracing::warn!("revocation redis unavailable: {} - using memory", e); Arc::new(RevocationStore::new(None)) }
+                    Ok(manager) => Some(Arc::new(manager)),
+                    Err(e) => { tracing::warn!("revocation redis unavailable: {} - using memory", e); None }
                 },
-                Err(e) => { tracing::warn!("revocation redis client error: {} - using memory", e); Arc::new(RevocationStore::new(None)) }
+                Err(e) => { tracing::warn!("revocation redis client error: {} - using memory", e); None }
             }
-        } else { Arc::new(RevocationStore::new(None)) };
-        let _auth_adapter = Arc::new(AuthenticationAdapter::new(config_arc.clone()).with_revocation_store(revocation_store.clone()));
+        } else { None };
+        // Bans share the same connection so they apply on every replica
+        let ban_list = Arc::new(BanList::new(&config_arc.auto_ban, revocation_redis.clone()));
+        // Partner tokens are issued by the token service; its usage counts meet ours in Redis
+        PartnerUsageRegistry::global().configure(revocation_redis.clone());
+        // Session store shares the revocation Redis connection so the token service's sessions are visible here
+        if config_arc.session.enabled && revocation_redis.is_none() {
+            return Err(AppError::Config(
+                "session.enabled requires Redis; sessions cannot be shared with the token service".to_string(),
+            ));
+        }
+        let session_store = config_arc
+            .session
+            .enabled
+            .then(|| Arc::new(SessionStore::new(revocation_redis.clone(), &config_arc.session)));
+        let revocation_store = Arc::new(RevocationStore::new(revocation_redis).with_max_listed(config_arc.revocation.max_listed));
+        let auth_adapter = Arc::new(Self::auth_adapter(config_arc.clone(), &revocation_store, &session_store));
 
-        // Optional: import viewing keys at startup
-        if !config_arc.payments.viewing_keys.is_empty() {
-            Self::import_viewing_keys(config_arc.clone(), _external_rpc_adapter.clone()).await.ok();
-        } else if config_arc.payments.require_viewing_key {
-            tracing::warn!("payments.require_viewing_key=true but no viewing_keys configured");
+        // Optional: start without the daemon; its startup steps then run once it answers
+        let defer_daemon_startup = config_arc.daemon_wait.enabled
+            && !DaemonWait::global().probe(&ExternalRpcAdapter::new(config_arc.clone()).with_guards(upstream.clone())).await;
+        if !defer_daemon_startup {
+            Self::daemon_startup(config_arc.clone(), _external_rpc_adapter.clone()).await;
         }
         
         // Initialize application layer
-        let rpc_service = Arc::new(RpcService::new(config_arc.clone(), security_validator));
+        let rpc_service = Arc::new(RpcService::new_with_dependencies(
+            config_arc.clone(),
+            security_validator,
+            Arc::new(ExternalRpcAdapter::new(config_arc.clone()).with_guards(upstream.clone())),
+            auth_adapter.clone(),
+            Arc::new(ComprehensiveValidator::from_config(&config_arc.validation_cache)),
+        ));
         let metrics_service = Arc::new(MetricsService::new());
         
         // Initialize use cases
@@ -75,17 +150,76 @@ impl HttpServer {
             rpc_service.clone(),
             metrics_service.clone(),
         ));
+        let metrics_persistence = Arc::new(MetricsPersistenceService::new(config_arc.clone(), metrics_service.clone()));
+        if let Err(e) = metrics_persistence.restore() {
+            tracing::warn!("metrics snapshot not restored: {}", e);
+        }
         let metrics_use_case = Arc::new(GetMetricsUseCase::new(metrics_service));
-        let health_use_case = Arc::new(HealthCheckUseCase);
+        // Built before the first request so `/health` reports memory pressure from the start
+        let request_guards = Arc::new(
+            RequestGuards::new(&config)
+                .with_ban_list(ban_list)
+                .with_cluster(cluster.clone())
+                .with_upstream(upstream.clone())
+                .with_authentication(auth_adapter),
+        );
+        let health_use_case = Arc::new(
+            HealthCheckUseCase::default()
+                .with_memory_guard(request_guards.memory_guard.clone())
+                .with_upstream_gate(upstream.gate.clone()),
+        );
+        let cpu_pool = Arc::new(CpuPool::new(&config.server));
+        let handover = Arc::new(Handover::new(&config));
+        let mining_pool_client = Arc::new(MiningPoolClient::from_app_config(&config));
+        let mempool_service = Arc::new(MempoolService::new(config_arc.clone(), _external_rpc_adapter.clone()));
+        let currency_history_service = Arc::new(CurrencyHistoryService::new(config_arc.clone(), _external_rpc_adapter.clone()));
+        let name_resolver = Arc::new(NameResolverService::new(config_arc.clone(), _external_rpc_adapter.clone()));
+        let shielded_trees = Arc::new(ShieldedTreeService::new(config_arc.clone(), _external_rpc_adapter.clone()));
+        #[cfg(feature = "indexer")]
+        let address_index_service = Arc::new(crate::application::services::AddressIndexService::new(
+            config_arc.clone(),
+            _external_rpc_adapter.clone(),
+        ));
 
         // Initialize cache middleware
-        let cache_middleware = Arc::new(CacheMiddleware::new(&config).await?);
+        let cache_middleware = CacheMiddleware::new(&config).await?;
+        #[cfg(feature = "disk-cache")]
+        let cache_middleware = if config.disk_cache.enabled {
+            if !config.cache.enabled {
+                tracing::warn!("disk_cache.enabled=true but the response cache is disabled; nothing will be stored");
+            }
+            match crate::infrastructure::adapters::disk_cache::DiskCache::open(&config.disk_cache) {
+                Ok(disk) => cache_middleware.with_disk_cache(Arc::new(disk)),
+                Err(e) => {
+                    tracing::warn!("Disk cache tier disabled: {}", e);
+                    cache_middleware
+                }
+            }
+        } else {
+            cache_middleware
+        };
+        let cache_middleware = Arc::new(cache_middleware);
+        if config.cache.enabled {
+            cache_middleware.install_health();
+        }
+        let report_service = Arc::new(ReportService::new(
+            config_arc.clone(),
+            _external_rpc_adapter.clone(),
+            cache_middleware.clone(),
+        ));
+        let health_history = Arc::new(
+            HealthHistoryService::new(config_arc.clone(), _external_rpc_adapter.clone(), Some(cache_middleware.clone()))
+                .with_mining_pool(mining_pool_client.clone()),
+        );
 
         // Initialize rate limiting middleware
-        let rate_limit_middleware = Arc::new(RateLimitMiddleware::new(config.clone()));
+        // In cluster mode the windows are shared with the other replicas
+        let rate_limit_middleware = Arc::new(RateLimitMiddleware::new(config.clone()).with_cluster(cluster.clone()));
 
         // Prepare payments Redis manager if available
-        let payments_redis = if config_arc.cache.enabled {
+        let payments_redis = if let Some(cluster) = &cluster {
+            Some(cluster.redis())
+        } else if config_arc.cache.enabled {
             match Client::open(config_arc.cache.redis_url.clone()) {
                 Ok(client) => match ConnectionManager::new(client).await {
                     Ok(manager) => Some(Arc::new(manager)),
@@ -95,18 +229,91 @@ impl HttpServer {
             }
         } else { None };
 
+        let payments_store = Arc::new(
+            PaymentsStore::new(payments_redis)
+                .with_retention(config_arc.payments.history_retention_days as u64 * 86400),
+        );
+        let payments_service = Arc::new(PaymentsService::new(
+            config_arc.clone(),
+            crate::application::services::payments_service::PaymentsConfig::default(),
+            _external_rpc_adapter.clone(),
+            payments_store,
+            Arc::new(TokenIssuerAdapter::new(config_arc.clone()).with_cpu_pool(cpu_pool.clone())),
+            revocation_store.clone(),
+        ));
+
+        let chain_events = Arc::new(ChainEventBus::new(256));
+        let chain_monitor = Arc::new(ChainMonitorService::new(
+            config_arc.clone(),
+            _external_rpc_adapter.clone(),
+            chain_events.clone(),
+        ));
+        let tx_tracking_service = Arc::new(TxTrackingService::new(
+            config_arc.clone(),
+            _external_rpc_adapter.clone(),
+            chain_events.clone(),
+        ));
+
         Ok(Self {
             config,
+            rpc_service,
             rpc_use_case,
             metrics_use_case,
             health_use_case,
             cache_middleware,
             rate_limit_middleware,
+            request_guards,
+            cpu_pool,
+            handover,
+            mining_pool_client,
+            external_rpc: _external_rpc_adapter,
             revocation_store,
-            payments_redis,
+            session_store,
+            mempool_service,
+            currency_history_service,
+            name_resolver,
+            shielded_trees,
+            report_service,
+            payments_service,
+            chain_events,
+            chain_monitor,
+            tx_tracking_service,
+            metrics_persistence,
+            health_history,
+            defer_daemon_startup,
+            #[cfg(feature = "indexer")]
+            address_index_service,
         })
     }
 
+    /// Startup steps that need the daemon: viewing key import, capability detection and method discovery
+    async fn daemon_startup(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) {
+        // Optional: import viewing keys at startup
+        if !config.payments.viewing_keys.is_empty() {
+            let usable = ViewingKeyService::new(config.clone(), rpc.clone()).import_all().await;
+            info!(usable, configured = config.payments.viewing_keys.len(), "Viewing keys imported");
+        } else if config.payments.require_viewing_key {
+            tracing::warn!("payments.require_viewing_key=true but no viewing_keys configured");
+        }
+
+        // Detect the daemon version and indexes; methods needing a missing index are disabled
+        if config.capabilities.enabled {
+            CapabilityService::new(config.clone(), rpc.clone()).detect_and_install().await;
+        }
+
+        // Optional: diff the daemon's methods against the registry
+        if config.method_discovery.enabled {
+            if let Err(e) = MethodDiscoveryService::new(config.clone(), rpc.clone()).run().await {
+                tracing::warn!("method discovery failed: {} - serving the built-in registry", e);
+            }
+        }
+    }
+
+    /// Daemon adapter with its own pool and breaker, behind the server's upstream guards
+    fn rpc_adapter(&self) -> Arc<ExternalRpcAdapter> {
+        Arc::new(ExternalRpcAdapter::new(Arc::new(self.config.clone())).with_guards(self.request_guards.upstream.clone()))
+    }
+
     /// Get a reference to the configuration
     pub fn config(&self) -> &AppConfig {
         &self.config
@@ -122,18 +329,226 @@ impl HttpServer {
         let addr: std::net::SocketAddr = addr.parse()
             .map_err(|e| AppError::Config(format!("Invalid server address: {}", e)))?;
 
+        if self.config.mempool.enabled {
+            self.mempool_service.clone().start_sampler();
+        }
+        if self.config.currency_history.enabled {
+            self.currency_history_service.clone().start_sampler();
+        }
+        #[cfg(feature = "indexer")]
+        if self.config.indexer.enabled {
+            self.address_index_service.clone().start_indexer();
+        }
+        #[cfg(not(feature = "indexer"))]
+        if self.config.indexer.enabled {
+            tracing::warn!("indexer.enabled=true but the server was built without the `indexer` feature");
+        }
+        if self.config.chain_events.enabled {
+            self.spawn_reorg_handler();
+            self.chain_monitor.clone().start_monitor();
+        }
+        if self.config.tx_tracking.enabled {
+            self.tx_tracking_service.clone().start_tracker();
+        }
+        if self.config.cache_warmer.enabled {
+            if self.config.cache.enabled {
+                let config = Arc::new(self.config.clone());
+                Arc::new(CacheWarmer::new(config, self.rpc_adapter(), self.cache_middleware.clone())).start();
+            } else {
+                tracing::warn!("cache_warmer.enabled=true but the response cache is disabled");
+            }
+        }
+        if self.config.reports.enabled {
+            self.report_service.clone().start();
+        }
+        if self.config.negative_cache.enabled {
+            self.request_guards.upstream.negative_cache.clone().start_reset_poller(
+                self.rpc_adapter(),
+                std::time::Duration::from_secs(self.config.negative_cache.tip_poll_seconds),
+            );
+        }
+        #[cfg(feature = "disk-cache")]
+        if let Some(disk) = self.cache_middleware.disk_cache() {
+            disk.clone()
+                .start_tip_poller(self.rpc_adapter(), std::time::Duration::from_secs(self.config.disk_cache.tip_poll_seconds));
+        }
+        #[cfg(not(feature = "disk-cache"))]
+        if self.config.disk_cache.enabled {
+            tracing::warn!("disk_cache.enabled=true but the server was built without the `disk-cache` feature");
+        }
+        if self.config.payments.refunds.enabled && self.config.payments.require_viewing_key {
+            tracing::warn!("payments.refunds is ignored with require_viewing_key=true (the wallet cannot spend)");
+        }
+        if self.config.payments.enabled && self.config.payments.address_pool.enabled {
+            if self.config.payments.require_viewing_key {
+                tracing::warn!("payments.address_pool is ignored with require_viewing_key=true (no new addresses are created)");
+            } else {
+                self.payments_service.address_pool().start_refill();
+            }
+        }
+        self.metrics_persistence.clone().start();
+        #[cfg(not(feature = "status-page"))]
+        if self.config.status_page.enabled {
+            tracing::warn!("status_page.enabled=true but the server was built without the `status-page` feature");
+        }
+        #[cfg(not(feature = "wasm-plugins"))]
+        if self.config.wasm_plugins.enabled {
+            tracing::warn!("wasm_plugins.enabled=true but the server was built without the `wasm-plugins` feature");
+        }
+        #[cfg(not(feature = "scripting"))]
+        if self.config.scripting.enabled {
+            tracing::warn!("scripting.enabled=true but the server was built without the `scripting` feature");
+        }
+        #[cfg(feature = "heap-profiling")]
+        if self.config.heap_profiling.enabled {
+            match crate::infrastructure::adapters::allocator::activate_heap_profiling().await {
+                Ok(()) => info!("Heap profiling active; profiles are served at GET /debug/heap"),
+                Err(e) => tracing::warn!("Heap profiling unavailable: {}", e),
+            }
+        }
+        #[cfg(not(feature = "heap-profiling"))]
+        if self.config.heap_profiling.enabled {
+            tracing::warn!("heap_profiling.enabled=true but the server was built without the `heap-profiling` feature");
+        }
+        if let Some(cluster) = self.request_guards.cluster.as_ref().filter(|_| self.config.cluster.enabled) {
+            cluster.start_membership();
+        }
+        if self.config.health_history.enabled {
+            self.health_history.clone().start_prober();
+        }
+        self.revocation_store
+            .clone()
+            .start_cleanup(std::time::Duration::from_secs(self.config.revocation.cleanup_interval_seconds));
+        if let Some(sessions) = &self.session_store {
+            sessions
+                .clone()
+                .start_cleanup(std::time::Duration::from_secs(self.config.revocation.cleanup_interval_seconds));
+        }
+
+        // Daemon was down in `new`: retry it alongside serving, failing the server after max_wait_seconds
+        let daemon_startup = self.defer_daemon_startup.then(|| {
+            let config = Arc::new(self.config.clone());
+            let rpc = self.rpc_adapter();
+            async move {
+                DaemonWait::global().wait(&rpc, &config.daemon_wait).await?;
+                Self::daemon_startup(config, rpc).await;
+                Ok::<(), AppError>(())
+            }
+        });
+
+        let limits = super::listener::ConnectionLimits::from_config(&self.config.server);
+        let listeners = self.bind_listeners(addr).await?;
+        let watchdog_rpc = self.rpc_adapter();
+        let systemd_config = self.config.systemd.clone();
+        let handover = self.handover.clone();
+        let watchdog_health = self.health_use_case.clone();
+        info!(cpu_pool_threads = self.cpu_pool.threads(), "CPU-heavy work offloaded from IO workers");
+        let drain_timeout = std::time::Duration::from_secs(self.config.upgrade.drain_timeout_seconds);
         let routes = self.create_routes();
-        
+
+        for listener in &listeners {
+            info!("Listening on {}", listener);
+        }
         info!("Starting HTTP server (reverse proxy mode)");
-        warp::serve(routes)
-            .run(addr)
-            .await;
+        systemd::notify_ready();
+        Handover::announce_ready();
+        #[cfg(unix)]
+        if handover.status().enabled {
+            handover.listen_for_signal();
+        }
+        if systemd_config.watchdog_enabled {
+            let timeout = std::time::Duration::from_secs(systemd_config.health_check_timeout_seconds);
+            let require_daemon = systemd_config.watchdog_require_daemon;
+            systemd::start_watchdog(move || {
+                let rpc = watchdog_rpc.clone();
+                let health = watchdog_health.clone();
+                async move {
+                    match tokio::time::timeout(timeout, health.execute(Some(rpc))).await {
+                        Ok(Ok(health)) => match health.status {
+                            HealthStatus::Healthy => true,
+                            HealthStatus::Degraded => !require_daemon,
+                            HealthStatus::Unhealthy => false,
+                        },
+                        _ => false,
+                    }
+                }
+            });
+        }
+        let serving = super::listener::serve_all(listeners, warp::service(routes), limits);
+        match daemon_startup {
+            Some(daemon_startup) => {
+                tokio::pin!(serving);
+                tokio::select! {
+                    _ = &mut serving => return Self::finish_drain(drain_timeout).await,
+                    result = daemon_startup => result?,
+                }
+                serving.await;
+            }
+            None => serving.await,
+        }
 
+        Self::finish_drain(drain_timeout).await
+    }
+
+    /// After an upgrade stopped the listeners, give open connections time to finish
+    async fn finish_drain(timeout: std::time::Duration) -> AppResult<()> {
+        let drain = super::listener::Drain::global();
+        if !drain.is_draining() {
+            return Ok(());
+        }
+        if drain.wait_idle(timeout).await {
+            info!("All connections drained, exiting");
+        } else {
+            tracing::warn!("Exiting with {} connection(s) still open after {}s", drain.open_connections(), timeout.as_secs());
+        }
         Ok(())
     }
 
+    /// Sockets passed by systemd socket activation, or the configured TCP and Unix sockets
+    async fn bind_listeners(&self, addr: std::net::SocketAddr) -> AppResult<Vec<BoundListener>> {
+        let activated = systemd::listen_fds()
+            .map_err(|e| AppError::Config(format!("Invalid sockets from systemd: {}", e)))?;
+        if !activated.is_empty() {
+            info!("Using {} socket(s) passed by systemd; bind settings are ignored", activated.len());
+            return Ok(activated);
+        }
+
+        // With upgrades on, the next binary binds the same port while this one still serves
+        #[cfg(unix)]
+        let tcp = if self.config.upgrade.enabled {
+            super::listener::bind_tcp_reuse_port(addr)
+        } else {
+            tokio::net::TcpListener::bind(addr).await
+        };
+        #[cfg(not(unix))]
+        let tcp = tokio::net::TcpListener::bind(addr).await;
+        let tcp = tcp.map_err(|e| AppError::Config(format!("Failed to bind {}: {}", addr, e)))?;
+        let mut listeners = vec![BoundListener::Tcp(tcp)];
+        if let Some(path) = &self.config.server.unix_socket_path {
+            #[cfg(unix)]
+            {
+                let unix = super::listener::bind_unix(std::path::Path::new(path), self.config.server.unix_socket_mode)
+                    .map_err(|e| AppError::Config(format!("Failed to bind Unix socket {}: {}", path, e)))?;
+                listeners.push(BoundListener::Unix(unix));
+            }
+            #[cfg(not(unix))]
+            return Err(AppError::Config(format!("Unix socket {} is not supported on this platform", path)));
+        }
+        Ok(listeners)
+    }
+
     /// Create the application routes optimized for reverse proxy deployment
     fn create_routes(self) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+        let external_rpc = self.external_rpc.clone();
+        #[cfg(feature = "status-page")]
+        let status_sources = Arc::new(crate::infrastructure::http::handlers::status::StatusPageSources {
+            health_use_case: self.health_use_case.clone(),
+            metrics_use_case: self.metrics_use_case.clone(),
+            rpc: external_rpc.clone(),
+            cache: self.cache_middleware.clone(),
+            health_history: self.health_history.clone(),
+        });
+
         let base = RouteBuilder::build_routes(
             self.config.clone(),
             self.rpc_use_case,
@@ -141,51 +556,177 @@ impl HttpServer {
             self.health_use_case,
             self.cache_middleware.clone(),
             self.rate_limit_middleware.clone(),
+            self.request_guards.clone(),
+            self.mining_pool_client,
+            self.cpu_pool.clone(),
         );
 
-        let payments_config = crate::application::services::payments_service::PaymentsConfig::default();
-        let external_rpc = std::sync::Arc::new(ExternalRpcAdapter::new(std::sync::Arc::new(self.config.clone())));
-        let payments_store = std::sync::Arc::new(PaymentsStore::new(self.payments_redis.clone()));
-        let token_issuer = std::sync::Arc::new(TokenIssuerAdapter::new(std::sync::Arc::new(self.config.clone())));
-        let payments_service = std::sync::Arc::new(crate::application::services::payments_service::PaymentsService::new(
-            std::sync::Arc::new(self.config.clone()),
-            payments_config,
+        let payments_routes = PaymentsRoutes::create_routes(
+            self.config.clone(),
+            self.payments_service.clone(),
+            self.rate_limit_middleware.clone(),
+        );
+        // Without the sampler there are no statistics to serve, so `/mempool/stats` is not mounted
+        let mempool_enabled = self.config.mempool.enabled;
+        let mempool_routes = warp::any()
+            .and_then(move || async move {
+                if mempool_enabled {
+                    Ok(())
+                } else {
+                    Err(warp::reject::not_found())
+                }
+            })
+            .untuple_one()
+            .and(MempoolRoutes::create_stats_route(self.config.clone(), self.mempool_service.clone()));
+        let explorer_service = Arc::new(ExplorerService::new(Arc::new(self.config.clone()), external_rpc.clone()));
+        let explorer_routes = ExplorerRoutes::create_routes(
+            self.config.clone(),
+            explorer_service.clone(),
+            self.currency_history_service.clone(),
+        )
+        .or(ExplorerRoutes::create_shielded_tree_routes(self.config.clone(), self.shielded_trees.clone()));
+        #[cfg(feature = "indexer")]
+        let explorer_routes = explorer_routes.or(ExplorerRoutes::create_address_txs_route(
+            self.config.clone(),
+            self.address_index_service.clone(),
+        ));
+        let resolver_routes = ResolverRoutes::create_resolve_route(self.config.clone(), self.name_resolver.clone());
+        let conversion_service = Arc::new(ConversionService::new(Arc::new(self.config.clone()), external_rpc.clone()));
+        let conversion_routes = ConversionRoutes::create_estimate_batch_route(self.config.clone(), conversion_service);
+        let marketplace_service = Arc::new(MarketplaceService::new(
+            Arc::new(self.config.clone()),
             external_rpc.clone(),
-            payments_store,
-            token_issuer,
+            self.name_resolver.clone(),
+        ));
+        let marketplace_routes = MarketplaceRoutes::create_offers_route(self.config.clone(), marketplace_service);
+        let portfolio_service = Arc::new(PortfolioService::new(
+            Arc::new(self.config.clone()),
+            external_rpc.clone(),
+            self.name_resolver.clone(),
+        ));
+        let portfolio_routes = PortfolioRoutes::create_portfolio_route(self.config.clone(), portfolio_service);
+        let job_service = Arc::new(JobService::new(
+            Arc::new(self.config.clone()),
+            explorer_service,
+            external_rpc,
+            self.cache_middleware.clone(),
+        ));
+        let job_routes = JobRoutes::create_routes(self.config.clone(), job_service);
+        // The REST facade calls the daemon for its callers, so it is admitted like `POST /`
+        let rest_guard = Arc::new(RestGuard::new(
+            self.config.clone(),
+            self.rpc_service.clone(),
+            self.rate_limit_middleware.clone(),
+            self.request_guards.clone(),
+        ));
+        let facade_routes = rest_guard.clone().wrap(
+            explorer_routes
+                .or(resolver_routes)
+                .or(conversion_routes)
+                .or(marketplace_routes)
+                .or(portfolio_routes)
+                .or(job_routes),
+        );
+        let report_routes = ReportRoutes::create_report_route(self.config.clone(), self.report_service.clone());
+
+        let admin_auth = Arc::new(Self::auth_adapter(
+            Arc::new(self.config.clone()),
+            &self.revocation_store,
+            &self.session_store,
+        ));
+        #[cfg(feature = "heap-profiling")]
+        let heap_profile_route = AdminRoutes::create_heap_profile_route(self.config.clone(), admin_auth.clone());
+        let admin_routes = AdminRoutes::create_routes(
+            self.config.clone(),
+            admin_auth.clone(),
             self.revocation_store.clone(),
+            self.handover,
+            self.request_guards,
+        )
+        .or(AdminRoutes::create_refund_routes(self.config.clone(), admin_auth, self.payments_service.clone()));
+
+        let event_routes = EventRoutes::create_stream_route(self.config.clone(), self.chain_events.clone());
+        let tracking_routes =
+            rest_guard.wrap(TrackingRoutes::create_routes(self.config.clone(), self.tx_tracking_service.clone()));
+        let health_history_routes = HealthRoutes::create_history_route(self.config.clone(), self.health_history.clone())
+            .or(HealthRoutes::create_capabilities_route(self.config.clone()));
+
+        let routes = base
+            .or(payments_routes)
+            .or(mempool_routes)
+            .or(facade_routes)
+            .or(report_routes)
+            .or(admin_routes)
+            .or(event_routes)
+            .or(tracking_routes)
+            .or(health_history_routes);
+        #[cfg(feature = "heap-profiling")]
+        let routes = routes.or(heap_profile_route);
+        #[cfg(feature = "status-page")]
+        let routes = routes.or(crate::infrastructure::http::routes::StatusRoutes::create_status_route(
+            self.config.clone(),
+            status_sources,
         ));
-        let payments_routes = PaymentsRoutes::create_routes(self.config.clone(), payments_service);
 
-        base.or(payments_routes)
+        // All pass-through unless [canonical_json] / [cors] / [csrf] enabled; CSRF refusals still get CORS headers
+        let routes = Arc::new(CanonicalJsonMiddleware::new(&self.config.canonical_json, self.cpu_pool)).wrap(routes);
+        let csrf = Arc::new(CsrfMiddleware::new(self.config.csrf.clone()));
+        let routes = csrf.clone().guard_filter().or(csrf.token_route()).or(routes);
+        let cors = Arc::new(CorsMiddleware::new(self.config.clone()));
+        cors.clone().preflight_filter().or(cors.wrap(routes))
     }
 
-    /// Import viewing keys from configuration into the wallet (non-fatal on errors)
-    async fn import_viewing_keys(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>) -> AppResult<()> {
-        let rescan = config.payments.viewing_key_rescan.clone();
-        for (idx, vkey) in config.payments.viewing_keys.iter().enumerate() {
-            let client_info = ClientInfo {
-                ip_address: "127.0.0.1".to_string(),
-                user_agent: Some("startup".to_string()),
-                auth_token: None,
-                timestamp: chrono::Utc::now(),
-            };
-            let params = serde_json::Value::Array(vec![
-                serde_json::Value::String(vkey.clone()),
-                serde_json::Value::String(rescan.clone()),
-            ]);
-            let req = RpcRequest::new(
-                "z_importviewingkey".to_string(),
-                Some(params),
-                Some(serde_json::json!(format!("vk_import_{}", idx))),
-                client_info,
-            );
-            match rpc.send_request(&req).await {
-                Ok(_) => tracing::info!("Imported viewing key {}", idx),
-                Err(e) => tracing::warn!("Viewing key import failed ({}): {}", idx, e),
+    /// React to reorgs: drop chain-derived cache entries, roll back the index, re-verify payments
+    fn spawn_reorg_handler(&self) {
+        let mut events = self.chain_events.subscribe();
+        let cache = self.cache_middleware.clone();
+        let resolver = self.name_resolver.clone();
+        let trees = self.shielded_trees.clone();
+        let payments = self.payments_service.clone();
+        #[cfg(feature = "indexer")]
+        let index = self.config.indexer.enabled.then(|| self.address_index_service.clone());
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Reorg handler fell behind");
+                        continue;
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                };
+                let ChainEvent::Reorg(reorg) = event else { continue };
+                match cache.invalidate_chain_state().await {
+                    Ok(removed) => info!(removed, "Invalidated cached chain state after reorg"),
+                    Err(e) => tracing::warn!("Cache invalidation after reorg failed: {}", e),
+                }
+                let dropped = resolver.invalidate().await;
+                info!(dropped, "Dropped cached name resolutions after reorg");
+                let dropped = trees.invalidate_from(reorg.fork_height + 1).await;
+                info!(dropped, "Dropped cached shielded tree states after reorg");
+                #[cfg(feature = "indexer")]
+                if let Some(index) = &index {
+                    if let Err(e) = index.rollback(reorg.fork_height).await {
+                        tracing::warn!("Address index rollback failed: {}", e);
+                    }
+                }
+                let reverified = payments.reverify_after_reorg(reorg.depth).await;
+                info!(depth = reorg.depth, reverified, "Handled chain reorganization");
             }
+        });
+    }
+
+    /// Authentication adapter that enforces revocations and, when enabled, sessions
+    fn auth_adapter(
+        config: Arc<AppConfig>,
+        revocations: &Arc<RevocationStore>,
+        sessions: &Option<Arc<SessionStore>>,
+    ) -> AuthenticationAdapter {
+        let adapter = AuthenticationAdapter::new(config).with_revocation_store(revocations.clone());
+        match sessions {
+            Some(sessions) => adapter.with_session_store(sessions.clone()),
+            None => adapter,
         }
-        Ok(())
     }
 }
 
@@ -0,0 +1,252 @@
+//! Strict JSON-RPC body parsing
+//!
+//! `serde_json` silently keeps the last of duplicate keys and ignores unknown
+//! fields, so a body can mean different things to this proxy, a WAF in front
+//! of it and the daemon behind it. With `[strict_json] enabled = true` the RPC
+//! route parses bodies itself and rejects anything ambiguous:
+//! duplicate keys, unknown top-level fields, `id` values other than a string,
+//! integer or null, scalar `params`, and params nested deeper or wider than
+//! the configured limits.
+
+use crate::{
+    config::{app_config::StrictJsonConfig, AppConfig},
+    infrastructure::http::models::{JsonRpcError, JsonRpcRequest, JsonRpcResponse},
+    middleware::security_headers::{create_json_response_with_security_headers, SecurityHeadersMiddleware},
+};
+use bytes::Bytes;
+use serde::de::{DeserializeSeed, Deserializer, Error as _, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+use std::fmt;
+use warp::{filters::BoxedFilter, http::StatusCode, Filter, Rejection, Reply};
+
+/// Top-level members a JSON-RPC request may carry
+const REQUEST_FIELDS: [&str; 4] = ["jsonrpc", "method", "params", "id"];
+
+/// Why a body was refused
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrictJsonError {
+    /// JSON-RPC error code (-32700 parse error, -32600 invalid request)
+    pub code: i64,
+    pub reason: String,
+    /// Request id, when it could be read
+    pub id: Option<Value>,
+}
+
+impl warp::reject::Reject for StrictJsonError {}
+
+impl StrictJsonError {
+    fn invalid(reason: impl Into<String>, id: Option<Value>) -> Self {
+        Self { code: -32600, reason: reason.into(), id }
+    }
+}
+
+/// Parse and check a JSON-RPC request body under the strict rules
+pub fn parse_request(body: &[u8], limits: &StrictJsonConfig) -> Result<JsonRpcRequest, StrictJsonError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let value = StrictSeed { limits, depth: 1 }
+        .deserialize(&mut deserializer)
+        .and_then(|value| deserializer.end().map(|_| value))
+        .map_err(|e| match e.classify() {
+            serde_json::error::Category::Data => StrictJsonError::invalid(e.to_string(), None),
+            _ => StrictJsonError { code: -32700, reason: e.to_string(), id: None },
+        })?;
+
+    let Value::Object(fields) = value else {
+        return Err(StrictJsonError::invalid("request must be a JSON object", None));
+    };
+    let id = fields.get("id").cloned();
+    if !matches!(&id, None | Some(Value::Null) | Some(Value::String(_)))
+        && !id.as_ref().is_some_and(|id| id.is_i64() || id.is_u64())
+    {
+        return Err(StrictJsonError::invalid("id must be a string, an integer or null", None));
+    }
+    if let Some(unknown) = fields.keys().find(|key| !REQUEST_FIELDS.contains(&key.as_str())) {
+        return Err(StrictJsonError::invalid(format!("unknown field '{}'", unknown), id));
+    }
+    if !matches!(fields.get("jsonrpc"), None | Some(Value::String(_))) {
+        return Err(StrictJsonError::invalid("jsonrpc must be a string", id));
+    }
+    if !matches!(fields.get("method"), Some(Value::String(_))) {
+        return Err(StrictJsonError::invalid("method must be a string", id));
+    }
+    if !matches!(fields.get("params"), None | Some(Value::Array(_)) | Some(Value::Object(_))) {
+        return Err(StrictJsonError::invalid("params must be an array or an object", id));
+    }
+
+    serde_json::from_value(Value::Object(fields)).map_err(|e| StrictJsonError::invalid(e.to_string(), id))
+}
+
+/// Body filter for the RPC route: `warp::body::json()`, or strict parsing when enabled
+pub fn rpc_body(config: &AppConfig) -> BoxedFilter<(JsonRpcRequest,)> {
+    if !config.strict_json.enabled {
+        return warp::body::json().boxed();
+    }
+    let limits = config.strict_json.clone();
+    warp::body::bytes()
+        .and_then(move |body: Bytes| {
+            let result = parse_request(&body, &limits).map_err(warp::reject::custom);
+            async move { result }
+        })
+        .boxed()
+}
+
+/// Turn strict-parsing rejections into JSON-RPC error responses; pass others through
+pub async fn handle_rejection(rejection: Rejection, config: AppConfig) -> Result<impl Reply, Rejection> {
+    let Some(error) = rejection.find::<StrictJsonError>() else {
+        return Err(rejection);
+    };
+    let message = if error.code == -32700 { "Parse error" } else { "Invalid Request" };
+    let response = JsonRpcResponse::error(
+        JsonRpcError::new(error.code, message.to_string(), Some(Value::String(error.reason.clone()))),
+        error.id.clone(),
+    );
+    let security_middleware = SecurityHeadersMiddleware::new(config);
+    Ok(warp::reply::with_status(
+        create_json_response_with_security_headers(&response, &security_middleware),
+        StatusCode::BAD_REQUEST,
+    ))
+}
+
+/// Builds a `Value` while rejecting duplicate keys and enforcing size limits
+struct StrictSeed<'a> {
+    limits: &'a StrictJsonConfig,
+    depth: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for StrictSeed<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StrictSeed<'_> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Value, E> {
+        serde_json::Number::from_f64(v).map(Value::Number).ok_or_else(|| E::custom("non-finite number"))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        if self.depth > self.limits.max_depth {
+            return Err(A::Error::custom(format!("nesting deeper than {}", self.limits.max_depth)));
+        }
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element_seed(StrictSeed { limits: self.limits, depth: self.depth + 1 })? {
+            if items.len() == self.limits.max_array_length {
+                return Err(A::Error::custom(format!("array longer than {}", self.limits.max_array_length)));
+            }
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        if self.depth > self.limits.max_depth {
+            return Err(A::Error::custom(format!("nesting deeper than {}", self.limits.max_depth)));
+        }
+        let mut fields = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if fields.contains_key(&key) {
+                return Err(A::Error::custom(format!("duplicate key '{}'", key)));
+            }
+            if fields.len() == self.limits.max_object_keys {
+                return Err(A::Error::custom(format!("object with more than {} keys", self.limits.max_object_keys)));
+            }
+            let value = map.next_value_seed(StrictSeed { limits: self.limits, depth: self.depth + 1 })?;
+            fields.insert(key, value);
+        }
+        Ok(Value::Object(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> StrictJsonConfig {
+        StrictJsonConfig { enabled: true, max_depth: 4, max_array_length: 3, max_object_keys: 8 }
+    }
+
+    fn reason(body: &str) -> String {
+        parse_request(body.as_bytes(), &limits()).unwrap_err().reason
+    }
+
+    #[test]
+    fn test_accepts_canonical_requests() {
+        let request = parse_request(br#"{"jsonrpc":"2.0","method":"getblock","params":["abc",1],"id":7}"#, &limits()).unwrap();
+        assert_eq!(request.method, "getblock");
+        assert_eq!(request.id, Some(Value::from(7)));
+        assert!(parse_request(br#"{"method":"getinfo","id":"a"}"#, &limits()).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_ambiguous_requests() {
+        assert!(reason(r#"{"method":"getinfo","method":"stop"}"#).contains("duplicate key 'method'"));
+        assert!(reason(r#"{"method":"getinfo","params":[{"a":1,"a":2}]}"#).contains("duplicate key"));
+        assert_eq!(reason(r#"{"method":"getinfo","extra":1}"#), "unknown field 'extra'");
+        assert!(reason(r#"{"method":"getinfo","id":1.5}"#).starts_with("id must be"));
+        assert!(reason(r#"{"method":"getinfo","id":[1]}"#).starts_with("id must be"));
+        assert!(reason(r#"{"method":"getinfo","params":"x"}"#).starts_with("params must be"));
+        assert!(reason(r#"{"method":"getinfo","params":[[[["deep"]]]]}"#).contains("nesting deeper than 4"));
+        assert!(reason(r#"{"method":"getinfo","params":[1,2,3,4]}"#).contains("array longer than 3"));
+        assert_eq!(reason(r#"[{"method":"getinfo"}]"#), "request must be a JSON object");
+
+        let error = parse_request(br#"{"method":"getinfo""#, &limits()).unwrap_err();
+        assert_eq!(error.code, -32700);
+        let error = parse_request(br#"{"method":"getinfo","id":9,"x":1}"#, &limits()).unwrap_err();
+        assert_eq!((error.code, error.id), (-32600, Some(Value::from(9))));
+    }
+
+    #[tokio::test]
+    async fn test_rpc_body_filter() {
+        let mut config = AppConfig::default();
+        config.strict_json.enabled = true;
+        let handler_config = config.clone();
+        let route = rpc_body(&config)
+            .map(|request: JsonRpcRequest| request.method)
+            .recover(move |rejection| handle_rejection(rejection, handler_config.clone()));
+
+        let response = warp::test::request()
+            .method("POST")
+            .body(r#"{"method":"getinfo","method":"stop","id":1}"#)
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["error"]["code"], -32600);
+
+        let response = warp::test::request().method("POST").body(r#"{"method":"getinfo"}"#).reply(&route).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), "getinfo");
+    }
+}
@@ -159,23 +159,9 @@ pub fn with_metrics_use_case(
 
 /// Helper function to inject mining pool client into route
 pub fn with_mining_pool_client(
+    client: Arc<crate::infrastructure::adapters::MiningPoolClient>,
 ) -> impl Filter<Extract = (Arc<crate::infrastructure::adapters::MiningPoolClient>,), Error = std::convert::Infallible> + Clone {
-    warp::any().map(move || {
-        // Create a default config for the mining pool client
-        let mut config = crate::config::AppConfig::default();
-        config.security.mining_pool = Some(crate::config::app_config::MiningPoolConfig {
-            pool_url: "https://test-pool.com".to_string(),
-            api_key: "test-key".to_string(),
-            public_key: "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
-            timeout_seconds: 30,
-            max_retries: 3,
-            circuit_breaker_threshold: 5,
-            circuit_breaker_timeout: 60,
-            requests_per_minute: 100,
-            enabled: true,
-        });
-        Arc::new(crate::infrastructure::adapters::MiningPoolClient::new(Arc::new(config)))
-    })
+    warp::any().map(move || client.clone())
 }
 
 /// Helper function to inject Prometheus adapter into route
@@ -205,3 +191,10 @@ pub fn with_rate_limit_middleware(
 ) -> impl Filter<Extract = (Arc<RateLimitMiddleware>,), Error = std::convert::Infallible> + Clone {
     warp::any().map(move || rate_limit_middleware.clone())
 }
+
+/// Helper function to inject the shared admission state into route
+pub fn with_request_guards(
+    request_guards: Arc<crate::infrastructure::http::processors::RequestGuards>,
+) -> impl Filter<Extract = (Arc<crate::infrastructure::http::processors::RequestGuards>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || request_guards.clone())
+}
@@ -21,6 +21,14 @@ pub mod shared;
 // Middleware layer - Cross-cutting concerns
 pub mod middleware;
 
+// Benchmark harness - Mock daemon and HTTP load driver
+#[cfg(feature = "bench")]
+pub mod bench;
+
+// Client SDK - Typed async client for the proxy (native and wasm32)
+#[cfg(feature = "client")]
+pub use verus_rpc_client as client;
+
 // Re-export main types
 pub use config::AppConfig;
 pub use shared::error::{AppError, AppResult};
@@ -1,8 +1,14 @@
 use verus_rpc_server::{AppConfig, VerusRpcServer};
-use tracing::{error, info};
+use verus_rpc_server::config::ConfigValidator;
+use verus_rpc_server::config::app_config::DaemonAuthMethod;
+use verus_rpc_server::application::services::{FixtureCaptureService, PreflightService};
+use verus_rpc_server::infrastructure::adapters::mock_daemon::load_fixtures;
+use verus_rpc_server::infrastructure::adapters::{runtime, ExternalRpcAdapter, MockDaemon};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{error, info, warn};
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
@@ -29,6 +35,57 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Err(format!("Configuration validation failed: {}", e).into());
     }
 
+    // Settings that contradict each other; `--check` fails on these
+    for issue in ConfigValidator::cross_validate(&config) {
+        warn!("Configuration problem at {}", issue);
+    }
+
+    // The runtime is sized from [server], so it is built once the configuration is known
+    let runtime = runtime::build_runtime(&config.server)?;
+    runtime.block_on(serve(config))
+}
+
+async fn serve(mut config: AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    // Health check mode: query the local /health endpoint and exit 0 if it answers 200
+    if std::env::args().nth(1).as_deref() == Some("healthcheck") {
+        std::process::exit(healthcheck(&config).await);
+    }
+
+    // Fixture capture mode: save live daemon responses for the mock daemon, print a report and exit
+    if std::env::args().nth(1).as_deref() == Some("capture-fixtures") {
+        let config = Arc::new(config);
+        let rpc = Arc::new(ExternalRpcAdapter::new(config.clone()));
+        let report = FixtureCaptureService::new(config, rpc).run().await?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        std::process::exit(report.exit_code());
+    }
+
+    // Mock daemon mode: captured or canned answers for every registered method, no verusd needed
+    let _mock_daemon = if std::env::args().any(|arg| arg == "--mock-daemon") {
+        if !config.security.development_mode {
+            error!("--mock-daemon is a development flag; set security.development_mode = true");
+            return Err("--mock-daemon requires security.development_mode = true".into());
+        }
+        let fixtures = load_fixtures(Path::new(&config.mock_daemon.fixtures_dir))?;
+        let daemon = MockDaemon::start_with_fixtures(std::time::Duration::ZERO, fixtures).await?;
+        warn!(url = %daemon.url(), "Serving canned daemon responses; no verusd is contacted");
+        config.verus.rpc_url = daemon.url();
+        config.verus.auth_method = DaemonAuthMethod::Password;
+        config.verus.rpc_user = "mock".to_string();
+        config.verus.rpc_password = "mock".to_string();
+        Some(daemon)
+    } else {
+        None
+    };
+
+    // Preflight check mode: validate dependencies, print a report and exit
+    if std::env::args().any(|arg| arg == "--check") {
+        info!("Running preflight checks");
+        let report = PreflightService::new(Arc::new(config)).run().await;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        std::process::exit(report.exit_code());
+    }
+
     // Print deployment recommendations
     info!("=== Reverse Proxy Deployment Recommendations ===");
     info!("1. Configure SSL/TLS termination in your reverse proxy (nginx, Caddy, etc.)");
@@ -58,3 +115,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Exit code for the `healthcheck` subcommand, used as a Docker HEALTHCHECK without curl
+///
+/// Healthy and degraded servers answer 200; unhealthy ones, errors and
+/// timeouts exit 1.
+async fn healthcheck(config: &AppConfig) -> i32 {
+    let bind_address = config.server.bind_address;
+    let host = if bind_address.is_unspecified() {
+        "127.0.0.1".to_string()
+    } else if bind_address.is_ipv6() {
+        format!("[{}]", bind_address)
+    } else {
+        bind_address.to_string()
+    };
+    let url = format!("http://{}:{}/health", host, config.server.port);
+    let client = match reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .user_agent("Docker-Health-Check")
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("healthcheck: {}", e);
+            return 1;
+        }
+    };
+    match client.get(&url).send().await {
+        Ok(response) if response.status() == reqwest::StatusCode::OK => 0,
+        Ok(response) => {
+            eprintln!("healthcheck: {} returned {}", url, response.status());
+            1
+        }
+        Err(e) => {
+            eprintln!("healthcheck: {}: {}", url, e);
+            1
+        }
+    }
+}
@@ -5,12 +5,17 @@
 
 use crate::config::AppConfig;
 use crate::infrastructure::adapters::{CacheAdapter, CacheEntry};
+#[cfg(feature = "disk-cache")]
+use crate::infrastructure::adapters::disk_cache::DiskCache;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Cache middleware for HTTP responses
 pub struct CacheMiddleware {
     cache_adapter: Arc<CacheAdapter>,
+    /// Disk tier for immutable responses, when `[disk_cache]` is enabled and opens
+    #[cfg(feature = "disk-cache")]
+    disk: Option<Arc<DiskCache>>,
 }
 
 impl CacheMiddleware {
@@ -21,10 +26,29 @@ impl CacheMiddleware {
             default_ttl: config.cache.default_ttl,
             enabled: config.cache.enabled,
             max_size: config.cache.max_size,
+            redis_timeout_ms: config.cache.redis_timeout_ms,
+            redis_retry_max_seconds: config.cache.redis_retry_max_seconds,
         };
         
         let cache_adapter = Arc::new(CacheAdapter::new(cache_config).await?);
-        Ok(Self { cache_adapter })
+        Ok(Self {
+            cache_adapter,
+            #[cfg(feature = "disk-cache")]
+            disk: None,
+        })
+    }
+
+    /// Back immutable responses with `disk`
+    #[cfg(feature = "disk-cache")]
+    pub fn with_disk_cache(mut self, disk: Arc<DiskCache>) -> Self {
+        self.disk = Some(disk);
+        self
+    }
+
+    /// Disk tier, if one is attached
+    #[cfg(feature = "disk-cache")]
+    pub fn disk_cache(&self) -> Option<&Arc<DiskCache>> {
+        self.disk.as_ref()
     }
 
     /// Check if response should be cached
@@ -53,6 +77,28 @@ impl CacheMiddleware {
         self.cache_adapter.set(entry).await
     }
 
+    /// Immutable response from the disk tier, consulted after a memory/Redis miss
+    #[cfg(feature = "disk-cache")]
+    pub fn get_immutable(&self, method: &str, params: &serde_json::Value) -> Option<Vec<u8>> {
+        self.disk.as_ref()?.get(method, params)
+    }
+
+    #[cfg(not(feature = "disk-cache"))]
+    pub fn get_immutable(&self, _method: &str, _params: &serde_json::Value) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Keep a response in the disk tier if it is immutable
+    #[cfg(feature = "disk-cache")]
+    pub fn store_immutable(&self, method: &str, params: &serde_json::Value, data: &[u8]) {
+        if let Some(disk) = &self.disk {
+            disk.put(method, params, data);
+        }
+    }
+
+    #[cfg(not(feature = "disk-cache"))]
+    pub fn store_immutable(&self, _method: &str, _params: &serde_json::Value, _data: &[u8]) {}
+
     /// Create cache entry
     pub fn create_cache_entry(
         &self,
@@ -78,10 +124,25 @@ impl CacheMiddleware {
         self.cache_adapter.get_stats().await
     }
 
+    /// Report this cache's Redis health on `/health`
+    pub fn install_health(&self) {
+        self.cache_adapter.install_health();
+    }
+
+    /// Whether Redis answers a `PING`
+    pub async fn ping(&self) -> bool {
+        self.cache_adapter.ping().await
+    }
+
     /// Clear cache
     pub async fn clear_cache(&self) -> crate::Result<()> {
         self.cache_adapter.clear().await
     }
+
+    /// Drop cached responses that depend on the active chain (after a reorg)
+    pub async fn invalidate_chain_state(&self) -> crate::Result<usize> {
+        self.cache_adapter.invalidate_methods(CacheAdapter::CHAIN_STATE_METHODS).await
+    }
 }
 
 #[cfg(test)]
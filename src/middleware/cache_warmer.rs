@@ -0,0 +1,169 @@
+//! Background cache warming for hot queries
+//!
+//! With `[cache_warmer] enabled`, the warmer polls `getbestblockhash` and, on
+//! every new block, re-fetches the configured hot queries and stores them
+//! under the same cache keys client requests use. When blocks are slow,
+//! entries are refreshed before `cache.default_ttl` runs out, so the first
+//! request after expiry never waits on the daemon.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use serde_json::{json, Value};
+use tracing::{debug, warn};
+
+use crate::config::AppConfig;
+use crate::domain::rpc::{ClientInfo, RpcRequest};
+use crate::infrastructure::adapters::ExternalRpcAdapter;
+use crate::infrastructure::converters::ModelConverter;
+use crate::middleware::cache::CacheMiddleware;
+use crate::shared::error::{AppError, AppResult};
+
+/// Re-fetches hot queries into the response cache
+pub struct CacheWarmer {
+    config: Arc<AppConfig>,
+    rpc: Arc<ExternalRpcAdapter>,
+    cache: Arc<CacheMiddleware>,
+}
+
+impl CacheWarmer {
+    pub fn new(config: Arc<AppConfig>, rpc: Arc<ExternalRpcAdapter>, cache: Arc<CacheMiddleware>) -> Self {
+        Self { config, rpc, cache }
+    }
+
+    /// Hot queries at the chain tip `tip_hash`, skipping methods the cache does not store
+    pub fn queries(&self, tip_hash: Option<&str>) -> Vec<(String, Value)> {
+        let warmer = &self.config.cache_warmer;
+        let mut queries: Vec<(String, Value)> = warmer.methods.iter().map(|method| (method.clone(), json!([]))).collect();
+        if let Some(hash) = tip_hash.filter(|_| warmer.tip_block) {
+            queries.push(("getblock".to_string(), json!([hash])));
+        }
+        queries.extend(warmer.currencies.iter().map(|currency| ("getcurrency".to_string(), json!([currency]))));
+        queries.retain(|(method, _)| self.cache.should_cache_response(method, 200));
+        queries
+    }
+
+    /// Fetch every hot query and cache the successful responses; returns how many were cached
+    pub async fn warm(&self, tip_hash: Option<&str>) -> usize {
+        stream::iter(self.queries(tip_hash))
+            .map(|(method, params)| async move {
+                match self.warm_query(&method, params).await {
+                    Ok(()) => true,
+                    Err(e) => {
+                        debug!(method = %method, "Cache warming skipped: {}", e);
+                        false
+                    }
+                }
+            })
+            .buffer_unordered(self.config.cache_warmer.max_concurrency.max(1))
+            .filter(|cached| std::future::ready(*cached))
+            .count()
+            .await
+    }
+
+    /// Warm on every new block, and before entries expire when blocks are slow
+    pub fn start(self: Arc<Self>) {
+        let unsupported: Vec<&String> = self
+            .config
+            .cache_warmer
+            .methods
+            .iter()
+            .filter(|method| !self.cache.should_cache_response(method, 200))
+            .collect();
+        if !unsupported.is_empty() {
+            warn!(methods = ?unsupported, "Cache warmer methods are not cacheable and will be skipped");
+        }
+
+        let poll = Duration::from_secs(self.config.cache_warmer.poll_interval_seconds.max(1));
+        // Refresh at 80% of the TTL so warmed entries never lapse
+        let refresh = Duration::from_secs((self.config.cache.default_ttl * 4 / 5).max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(poll);
+            let mut last_tip: Option<String> = None;
+            let mut last_warm: Option<Instant> = None;
+            loop {
+                ticker.tick().await;
+                let tip = match self.call("getbestblockhash", json!([])).await {
+                    Ok(Value::String(hash)) => hash,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        debug!("Cache warmer tip check failed: {}", e);
+                        continue;
+                    }
+                };
+                let new_block = last_tip.as_deref() != Some(tip.as_str());
+                if !new_block && last_warm.is_some_and(|at| at.elapsed() < refresh) {
+                    continue;
+                }
+                let cached = self.warm(Some(&tip)).await;
+                debug!(tip = %tip, new_block, cached, "Warmed hot cache entries");
+                last_tip = Some(tip);
+                last_warm = Some(Instant::now());
+            }
+        });
+    }
+
+    /// Store one query's response exactly as the request path would
+    async fn warm_query(&self, method: &str, params: Value) -> AppResult<()> {
+        let response = self.rpc.send_request(&self.request(method, params.clone())).await?;
+        if response.error.is_some() {
+            return Err(AppError::Rpc(format!("{} returned an error", method)));
+        }
+        let data = serde_json::to_vec(&ModelConverter::to_infrastructure_response(&response))?;
+        let key = self.cache.generate_cache_key(method, &params);
+        let entry = self.cache.create_cache_entry(key, data, "application/json".to_string(), self.config.cache.default_ttl);
+        self.cache.cache_response(entry).await
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        self.rpc
+            .send_request(&self.request(method, params))
+            .await?
+            .result
+            .ok_or_else(|| AppError::Rpc(format!("{} returned no result", method)))
+    }
+
+    fn request(&self, method: &str, params: Value) -> RpcRequest {
+        RpcRequest::new(
+            method.to_string(),
+            Some(params),
+            Some(json!(format!("cache_warmer_{}", method))),
+            ClientInfo {
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: Some("cache-warmer".to_string()),
+                auth_token: None,
+                timestamp: Utc::now(),
+                request_id: None,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_queries_cover_tip_block_and_currencies() {
+        let mut config = AppConfig::default();
+        config.cache.enabled = false;
+        config.cache_warmer.methods = vec!["getinfo".to_string(), "sendrawtransaction".to_string()];
+        config.cache_warmer.currencies = vec!["VRSC".to_string()];
+        let cache = Arc::new(CacheMiddleware::new(&config).await.unwrap());
+        let config = Arc::new(config);
+        let warmer = CacheWarmer::new(config.clone(), Arc::new(ExternalRpcAdapter::new(config)), cache);
+
+        let queries = warmer.queries(Some("00ab"));
+        assert_eq!(
+            queries,
+            vec![
+                ("getinfo".to_string(), json!([])),
+                ("getblock".to_string(), json!(["00ab"])),
+                ("getcurrency".to_string(), json!(["VRSC"])),
+            ]
+        );
+        assert_eq!(warmer.queries(None).len(), 2);
+    }
+}
@@ -0,0 +1,168 @@
+//! Canonical JSON response bodies
+//!
+//! Replicas answering the same daemon result should send the same bytes, so
+//! that signatures, ETags and downstream caches agree across the cluster.
+//! With `[canonical_json]` enabled, JSON bodies on the configured routes are
+//! re-encoded canonically: object keys sorted bytewise, no whitespace, and
+//! floats in their shortest round-trip form with negative zero written as
+//! `0.0`. Routes match the request path exactly, or by prefix when they end
+//! in `*`.
+
+use std::sync::Arc;
+
+use http_body_util::BodyExt;
+use serde_json::Value;
+use warp::{
+    http::{header, StatusCode},
+    path::FullPath,
+    reply::Response,
+    Filter, Rejection, Reply,
+};
+
+use crate::config::app_config::CanonicalJsonConfig;
+use crate::infrastructure::adapters::CpuPool;
+
+/// Re-encodes JSON responses on the configured routes
+pub struct CanonicalJsonMiddleware {
+    routes: Vec<String>,
+    /// Large bodies are re-encoded here instead of on the IO workers
+    cpu_pool: Arc<CpuPool>,
+}
+
+impl CanonicalJsonMiddleware {
+    /// Middleware for `[canonical_json]`; matches no route when disabled
+    pub fn new(config: &CanonicalJsonConfig, cpu_pool: Arc<CpuPool>) -> Self {
+        let routes = if config.enabled { config.routes.clone() } else { Vec::new() };
+        Self { routes, cpu_pool }
+    }
+
+    /// Whether responses for `path` are canonicalized
+    pub fn applies(&self, path: &str) -> bool {
+        self.routes.iter().any(|route| match route.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == route,
+        })
+    }
+
+    /// Canonicalize JSON bodies of `routes` on the configured paths
+    pub fn wrap<F, R>(self: Arc<Self>, routes: F) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone
+    where
+        F: Filter<Extract = (R,), Error = Rejection> + Clone + Send + Sync + 'static,
+        R: Reply,
+    {
+        warp::path::full().and(routes).then(move |path: FullPath, reply: R| {
+            let canonical = self.applies(path.as_str());
+            let cpu_pool = self.cpu_pool.clone();
+            async move {
+                let response = reply.into_response();
+                if canonical {
+                    canonicalize(response, &cpu_pool).await
+                } else {
+                    response
+                }
+            }
+        })
+    }
+}
+
+/// Re-encode a JSON response body; other responses pass through untouched
+async fn canonicalize(response: Response, cpu_pool: &CpuPool) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let bytes = match body.collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    // Parsing and re-encoding large bodies is CPU-bound; keep it off the IO workers
+    let body = cpu_pool
+        .run_sized(bytes.len(), move || match serde_json::from_slice::<Value>(&bytes) {
+            Ok(value) => canonical_json(&value),
+            Err(_) => bytes.to_vec(),
+        })
+        .await;
+    let body = match body {
+        Ok(body) => body,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let mut canonical = body.into_response();
+    *canonical.status_mut() = parts.status;
+    *canonical.headers_mut() = parts.headers;
+    canonical
+}
+
+/// Compact JSON with object keys sorted bytewise and fixed float formatting
+pub fn canonical_json(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push(b'{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                out.extend(serde_json::to_vec(key).unwrap_or_default());
+                out.push(b':');
+                write_canonical(&map[key], out);
+            }
+            out.push(b'}');
+        }
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(b']');
+        }
+        Value::Number(number) if number.as_f64() == Some(0.0) && number.is_f64() => out.extend(b"0.0"),
+        scalar => out.extend(serde_json::to_vec(scalar).unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_json_sorts_keys_and_fixes_floats() {
+        let value = json!({ "result": { "b": 1, "a": [true, null, "x y", -0.0, 1.5, 2.0] }, "id": 7, "error": null });
+        assert_eq!(
+            String::from_utf8(canonical_json(&value)).unwrap(),
+            r#"{"error":null,"id":7,"result":{"a":[true,null,"x y",0.0,1.5,2.0],"b":1}}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wrap_canonicalizes_matching_routes() {
+        let cpu_pool = Arc::new(CpuPool::new(&crate::config::AppConfig::default().server));
+        let middleware = Arc::new(CanonicalJsonMiddleware::new(
+            &CanonicalJsonConfig { enabled: true, routes: vec!["/".to_string(), "/api/*".to_string()] },
+            cpu_pool,
+        ));
+        assert!(middleware.applies("/") && middleware.applies("/api/block/1"));
+        assert!(!middleware.applies("/health"));
+
+        let routes = middleware.wrap(warp::any().map(|| warp::reply::json(&json!({ "z": 1, "a": { "y": [], "b": 2.50 } }))));
+        let response = warp::test::request().path("/api/block/1").reply(&routes).await;
+        assert_eq!(response.body().as_ref(), br#"{"a":{"b":2.5,"y":[]},"z":1}"#);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+}
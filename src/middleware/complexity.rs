@@ -0,0 +1,187 @@
+//! Request complexity scoring
+//!
+//! Some calls cost the daemon far more than others depending on their
+//! params: an address-index lookup over many addresses and a long block
+//! range, or `getexports` over a large count, can occupy it for seconds. Each
+//! request is scored from its method weight and the work its params ask for.
+//! Requests above `max_score` are rejected outright; those above
+//! `deprioritize_score` are served, but shed like high-cost methods while the
+//! daemon is degraded.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::app_config::ComplexityConfig;
+
+/// Address-index methods taking `{"addresses": [...], "start", "end"}` or a single address
+const ADDRESS_METHODS: &[&str] = &[
+    "getaddressbalance",
+    "getaddressdeltas",
+    "getaddressmempool",
+    "getaddresstxids",
+    "getaddressutxos",
+];
+
+/// How a scored request is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComplexityVerdict {
+    /// Within budget
+    Normal,
+    /// Served, but shed first under load
+    Deprioritized,
+    /// Over `max_score`
+    Rejected,
+}
+
+/// Complexity counters for `/metrics`
+#[derive(Debug, Clone, Serialize)]
+pub struct ComplexityMetrics {
+    pub enabled: bool,
+    pub max_score: u64,
+    pub deprioritize_score: u64,
+    pub scored: u64,
+    pub deprioritized: u64,
+    pub rejected: u64,
+}
+
+/// Scores requests against the configured budgets
+pub struct ComplexityEstimator {
+    config: ComplexityConfig,
+    scored: AtomicU64,
+    deprioritized: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl ComplexityEstimator {
+    pub fn new(config: ComplexityConfig) -> Self {
+        Self {
+            config,
+            scored: AtomicU64::new(0),
+            deprioritized: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Budget above which requests are rejected
+    pub fn max_score(&self) -> u64 {
+        self.config.max_score
+    }
+
+    /// Estimated cost of `method(params)`
+    pub fn score(&self, method: &str, params: Option<&Value>) -> u64 {
+        let weight = self.config.method_weights.get(method).copied().unwrap_or(1);
+        weight.saturating_mul(self.units(method, params).max(1))
+    }
+
+    /// Score a request and decide how to handle it; records the decision
+    pub fn assess(&self, method: &str, params: Option<&Value>) -> (u64, ComplexityVerdict) {
+        if !self.config.enabled {
+            return (0, ComplexityVerdict::Normal);
+        }
+        let score = self.score(method, params);
+        self.scored.fetch_add(1, Ordering::Relaxed);
+        let verdict = if score > self.config.max_score {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            ComplexityVerdict::Rejected
+        } else if score > self.config.deprioritize_score {
+            self.deprioritized.fetch_add(1, Ordering::Relaxed);
+            ComplexityVerdict::Deprioritized
+        } else {
+            ComplexityVerdict::Normal
+        };
+        (score, verdict)
+    }
+
+    /// Current counters
+    pub fn metrics(&self) -> ComplexityMetrics {
+        ComplexityMetrics {
+            enabled: self.config.enabled,
+            max_score: self.config.max_score,
+            deprioritize_score: self.config.deprioritize_score,
+            scored: self.scored.load(Ordering::Relaxed),
+            deprioritized: self.deprioritized.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Render complexity counters in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let m = self.metrics();
+        let mut out = String::new();
+        out.push_str("# HELP verus_complexity_scored_total Requests scored against the complexity budgets\n");
+        out.push_str("# TYPE verus_complexity_scored_total counter\n");
+        out.push_str(&format!("verus_complexity_scored_total {}\n", m.scored));
+        out.push_str("# HELP verus_complexity_over_budget_total Requests over a complexity budget, by action taken\n");
+        out.push_str("# TYPE verus_complexity_over_budget_total counter\n");
+        out.push_str(&format!("verus_complexity_over_budget_total{{action=\"deprioritized\"}} {}\n", m.deprioritized));
+        out.push_str(&format!("verus_complexity_over_budget_total{{action=\"rejected\"}} {}\n", m.rejected));
+        out
+    }
+
+    /// Units of work requested by the params, before the method weight
+    fn units(&self, method: &str, params: Option<&Value>) -> u64 {
+        let params = params.and_then(Value::as_array);
+        let param = |index: usize| params.and_then(|params| params.get(index));
+        match method {
+            m if ADDRESS_METHODS.contains(&m) => match param(0) {
+                Some(Value::Object(query)) => {
+                    let addresses = query.get("addresses").and_then(Value::as_array).map_or(1, Vec::len) as u64;
+                    addresses.saturating_mul(self.range_units(query.get("start"), query.get("end")))
+                }
+                _ => 1,
+            },
+            "getexports" => param(2).and_then(Value::as_u64).unwrap_or(1),
+            "getblockhashes" => param(1).and_then(Value::as_u64).unwrap_or(1),
+            "sendcurrency" | "z_sendmany" => param(1).and_then(Value::as_array).map_or(1, Vec::len) as u64,
+            _ => 1,
+        }
+    }
+
+    /// One unit per `blocks_per_unit` blocks of a bounded range; an open range counts as one
+    fn range_units(&self, start: Option<&Value>, end: Option<&Value>) -> u64 {
+        match (start.and_then(Value::as_u64), end.and_then(Value::as_u64)) {
+            (Some(start), Some(end)) => end.saturating_sub(start) / self.config.blocks_per_unit + 1,
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn estimator() -> ComplexityEstimator {
+        ComplexityEstimator::new(ComplexityConfig { enabled: true, ..Default::default() })
+    }
+
+    #[test]
+    fn test_score_follows_params() {
+        let estimator = estimator();
+        assert_eq!(estimator.score("getinfo", None), 1);
+        assert_eq!(estimator.score("getblocktemplate", Some(&json!([{}]))), 50);
+        assert_eq!(estimator.score("getexports", Some(&json!(["VRSC", 1000, 250]))), 250);
+
+        let addresses: Vec<String> = (0..10).map(|i| format!("R{}", i)).collect();
+        let ranged = json!([{ "addresses": addresses, "start": 1, "end": 20001 }]);
+        // 10 addresses x 21 range units x weight 2
+        assert_eq!(estimator.score("getaddressdeltas", Some(&ranged)), 420);
+        assert_eq!(estimator.score("getaddressbalance", Some(&json!(["RAddress"]))), 1);
+    }
+
+    #[test]
+    fn test_budgets_deprioritize_then_reject() {
+        let estimator = estimator();
+        assert_eq!(estimator.assess("getexports", Some(&json!(["VRSC", 0, 50]))).1, ComplexityVerdict::Normal);
+        assert_eq!(estimator.assess("getexports", Some(&json!(["VRSC", 0, 500]))).1, ComplexityVerdict::Deprioritized);
+        assert_eq!(estimator.assess("getexports", Some(&json!(["VRSC", 0, 5000]))).1, ComplexityVerdict::Rejected);
+
+        let metrics = estimator.metrics();
+        assert_eq!((metrics.scored, metrics.deprioritized, metrics.rejected), (3, 1, 1));
+
+        let disabled = ComplexityEstimator::new(ComplexityConfig::default());
+        assert_eq!(disabled.assess("getexports", Some(&json!(["VRSC", 0, 5000]))).1, ComplexityVerdict::Normal);
+    }
+}
@@ -1,13 +1,24 @@
-//! CORS configuration for reverse proxy deployment
+//! CORS configuration and enforcement
 //! 
-//! This module provides CORS configuration validation for deployment
-//! behind a reverse proxy (nginx, Caddy, etc.) that handles CORS.
-//! 
-//! DEPRECATION NOTICE: CORS should be handled by the reverse proxy
-//! for better performance and more flexible configuration.
-
-use crate::config::AppConfig;
+//! By default CORS is left to the reverse proxy (nginx, Caddy, etc.) and this
+//! module only validates the configuration and produces deployment advice.
+//! With `[cors] enabled = true` the server answers preflights and adds CORS
+//! headers itself, for standalone deployments.
+//!
+//! Origins may be exact (`https://wallet.example.com`), `*`, or contain a
+//! wildcard in the host or port (`https://*.example.com`,
+//! `http://localhost:*`). Per-route overrides are matched by longest path
+//! prefix and fall back to the `[security]` lists.
+
+use crate::config::{app_config::CorsRouteConfig, AppConfig};
+use std::sync::Arc;
 use tracing::info;
+use warp::{
+    http::{header, HeaderValue, Method, StatusCode},
+    path::FullPath,
+    reply::Response,
+    Filter, Rejection, Reply,
+};
 
 /// CORS configuration for reverse proxy deployment
 #[derive(Debug, Clone)]
@@ -16,6 +27,10 @@ pub struct CorsConfig {
     pub methods: Vec<String>,
     pub headers: Vec<String>,
     pub reverse_proxy_mode: bool,
+    pub allow_credentials: bool,
+    pub max_age_seconds: u64,
+    pub expose_headers: Vec<String>,
+    pub routes: Vec<CorsRouteConfig>,
 }
 
 impl CorsConfig {
@@ -26,6 +41,10 @@ impl CorsConfig {
             methods,
             headers,
             reverse_proxy_mode: true, // Default to reverse proxy mode
+            allow_credentials: false,
+            max_age_seconds: 3600,
+            expose_headers: Vec::new(),
+            routes: Vec::new(),
         }
     }
 
@@ -35,11 +54,23 @@ impl CorsConfig {
             origins: config.security.cors_origins.clone(),
             methods: config.security.cors_methods.clone(),
             headers: config.security.cors_headers.clone(),
-            reverse_proxy_mode: true, // Always use reverse proxy mode
+            reverse_proxy_mode: !config.cors.enabled,
+            allow_credentials: config.cors.allow_credentials,
+            max_age_seconds: config.cors.max_age_seconds,
+            expose_headers: config.cors.expose_headers.clone(),
+            routes: config.cors.routes.clone(),
         }
     }
 }
 
+/// Origins, methods and headers in effect for one path
+#[derive(Debug, Clone, Copy)]
+struct CorsRule<'a> {
+    origins: &'a [String],
+    methods: &'a [String],
+    headers: &'a [String],
+}
+
 /// CORS middleware for reverse proxy deployment
 pub struct CorsMiddleware {
     config: CorsConfig,
@@ -53,6 +84,8 @@ impl CorsMiddleware {
         if cors_config.reverse_proxy_mode {
             info!("CORS configuration loaded for reverse proxy deployment");
             info!("CORS headers should be configured in the reverse proxy (nginx, Caddy, etc.)");
+        } else {
+            info!(routes = cors_config.routes.len(), "CORS enforced by the server");
         }
 
         Self { config: cors_config }
@@ -93,6 +126,28 @@ impl CorsMiddleware {
             }
         }
 
+        for route in &self.config.routes {
+            if !route.path_prefix.starts_with('/') {
+                return Err(format!("CORS route prefix must start with '/': {}", route.path_prefix));
+            }
+            if let Some(origin) = route.origins.iter().flatten().find(|o| !self.is_valid_origin(o)) {
+                return Err(format!("Invalid CORS origin for {}: {}", route.path_prefix, origin));
+            }
+            if let Some(method) = route.methods.iter().flatten().find(|m| m.parse::<Method>().is_err()) {
+                return Err(format!("Invalid CORS method for {}: {}", route.path_prefix, method));
+            }
+        }
+
+        // Browsers refuse credentialed responses with a wildcard origin
+        if self.config.allow_credentials {
+            let any = std::iter::once(&self.config.origins)
+                .chain(self.config.routes.iter().filter_map(|r| r.origins.as_ref()))
+                .any(|origins| origins.iter().any(|o| o == "*"));
+            if any {
+                return Err("CORS allow_credentials requires explicit origins, not \"*\"".to_string());
+            }
+        }
+
         Ok(())
     }
 
@@ -159,10 +214,189 @@ impl CorsMiddleware {
         headers.push(("Access-Control-Allow-Headers".to_string(), allowed_headers));
 
         // Access-Control-Max-Age
-        headers.push(("Access-Control-Max-Age".to_string(), "3600".to_string()));
+        headers.push(("Access-Control-Max-Age".to_string(), self.config.max_age_seconds.to_string()));
 
         headers
     }
+
+    /// Rule for the longest matching route prefix, falling back to the global lists
+    fn rule_for(&self, path: &str) -> CorsRule<'_> {
+        let route = self
+            .config
+            .routes
+            .iter()
+            .filter(|r| path.starts_with(&r.path_prefix))
+            .max_by_key(|r| r.path_prefix.len());
+        CorsRule {
+            origins: route.and_then(|r| r.origins.as_deref()).unwrap_or(&self.config.origins),
+            methods: route.and_then(|r| r.methods.as_deref()).unwrap_or(&self.config.methods),
+            headers: route.and_then(|r| r.headers.as_deref()).unwrap_or(&self.config.headers),
+        }
+    }
+
+    /// `Access-Control-Allow-Origin` value for a request, if the origin is allowed
+    pub fn allowed_origin(&self, path: &str, origin: &str) -> Option<String> {
+        let origins = self.rule_for(path).origins;
+        if origins.iter().any(|o| o == "*") && !self.config.allow_credentials {
+            return Some("*".to_string());
+        }
+        origins
+            .iter()
+            .any(|pattern| origin_matches(pattern, origin))
+            .then(|| origin.to_string())
+    }
+
+    /// Headers for a preflight, or `None` if the origin, method or headers are not allowed
+    pub fn preflight_headers_for(
+        &self,
+        path: &str,
+        origin: &str,
+        request_method: &str,
+        request_headers: Option<&str>,
+    ) -> Option<Vec<(String, String)>> {
+        let rule = self.rule_for(path);
+        let allow_origin = self.allowed_origin(path, origin)?;
+        if !rule.methods.iter().any(|m| m.eq_ignore_ascii_case(request_method)) {
+            return None;
+        }
+        let any_header = rule.headers.iter().any(|h| h == "*");
+        let headers_ok = request_headers
+            .into_iter()
+            .flat_map(|h| h.split(','))
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .all(|requested| any_header || rule.headers.iter().any(|h| h.eq_ignore_ascii_case(requested)));
+        if !headers_ok {
+            return None;
+        }
+
+        let mut headers = self.origin_headers(allow_origin);
+        headers.push(("Access-Control-Allow-Methods".to_string(), rule.methods.join(", ")));
+        let allow_headers = match (any_header, request_headers) {
+            (true, Some(requested)) => requested.to_string(),
+            _ => rule.headers.join(", "),
+        };
+        headers.push(("Access-Control-Allow-Headers".to_string(), allow_headers));
+        headers.push(("Access-Control-Max-Age".to_string(), self.config.max_age_seconds.to_string()));
+        Some(headers)
+    }
+
+    /// Headers for a non-preflight response to a cross-origin request
+    pub fn response_headers_for(&self, path: &str, origin: &str) -> Vec<(String, String)> {
+        let Some(allow_origin) = self.allowed_origin(path, origin) else {
+            return Vec::new();
+        };
+        let mut headers = self.origin_headers(allow_origin);
+        if !self.config.expose_headers.is_empty() {
+            headers.push(("Access-Control-Expose-Headers".to_string(), self.config.expose_headers.join(", ")));
+        }
+        headers
+    }
+
+    fn origin_headers(&self, allow_origin: String) -> Vec<(String, String)> {
+        let reflected = allow_origin != "*";
+        let mut headers = vec![("Access-Control-Allow-Origin".to_string(), allow_origin)];
+        if reflected {
+            headers.push(("Vary".to_string(), "Origin".to_string()));
+        }
+        if self.config.allow_credentials {
+            headers.push(("Access-Control-Allow-Credentials".to_string(), "true".to_string()));
+        }
+        headers
+    }
+
+    /// Answer CORS preflight requests (`OPTIONS` with `Access-Control-Request-Method`)
+    ///
+    /// Rejects with not-found when CORS is left to the reverse proxy, so
+    /// other routes see the request unchanged.
+    pub fn preflight_filter(self: Arc<Self>) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+        warp::options()
+            .and(warp::path::full())
+            .and(warp::header::optional::<String>("origin"))
+            .and(warp::header::optional::<String>("access-control-request-method"))
+            .and(warp::header::optional::<String>("access-control-request-headers"))
+            .and_then(move |path: FullPath, origin: Option<String>, method: Option<String>, requested: Option<String>| {
+                let cors = self.clone();
+                async move {
+                    let (Some(origin), Some(method)) = (origin, method) else {
+                        return Err(warp::reject::not_found());
+                    };
+                    if cors.config.reverse_proxy_mode {
+                        return Err(warp::reject::not_found());
+                    }
+                    let response = match cors.preflight_headers_for(path.as_str(), &origin, &method, requested.as_deref()) {
+                        Some(headers) => with_headers(StatusCode::NO_CONTENT.into_response(), headers),
+                        None => StatusCode::FORBIDDEN.into_response(),
+                    };
+                    Ok(response)
+                }
+            })
+    }
+
+    /// Add CORS headers to responses from `routes` for allowed cross-origin requests
+    pub fn wrap<F, R>(self: Arc<Self>, routes: F) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone
+    where
+        F: Filter<Extract = (R,), Error = Rejection> + Clone + Send + Sync + 'static,
+        R: Reply,
+    {
+        warp::path::full()
+            .and(warp::header::optional::<String>("origin"))
+            .and(routes)
+            .map(move |path: FullPath, origin: Option<String>, reply: R| {
+                let response = reply.into_response();
+                match origin {
+                    Some(origin) if !self.config.reverse_proxy_mode => {
+                        with_headers(response, self.response_headers_for(path.as_str(), &origin))
+                    }
+                    _ => response,
+                }
+            })
+    }
+}
+
+/// Match an origin against an allowlist entry
+///
+/// `*` in a pattern matches one or more host or port characters, so
+/// `https://*.example.com` matches subdomains but not `https://example.com`
+/// or `https://evil.com/.example.com`.
+pub fn origin_matches(pattern: &str, origin: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    let (pattern, origin) = (pattern.to_ascii_lowercase(), origin.to_ascii_lowercase());
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = origin.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, literal) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        // The wildcard consumes at least one character, never '/' or ':' or '@'
+        let is_host_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '.';
+        let found = (1..=rest.len()).filter(|&n| rest.is_char_boundary(n)).find(|&n| {
+            rest[..n].chars().all(is_host_char)
+                && if last { &rest[n..] == *literal } else { rest[n..].starts_with(literal) }
+        });
+        match found {
+            Some(n) => rest = &rest[n + literal.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+fn with_headers(mut response: Response, headers: Vec<(String, String)>) -> Response {
+    for (name, value) in headers {
+        let Ok(value) = HeaderValue::from_str(&value) else { continue };
+        let Ok(name) = header::HeaderName::from_bytes(name.as_bytes()) else { continue };
+        if name == header::VARY {
+            response.headers_mut().append(name, value);
+        } else {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    response
 }
 
 #[cfg(test)]
@@ -225,4 +459,99 @@ mod tests {
         assert!(!recommendations.is_empty());
         assert!(recommendations.iter().any(|r| r.contains("reverse proxy")));
     }
-} 
\ No newline at end of file
+
+    fn enforcing(origins: &[&str]) -> CorsMiddleware {
+        let mut config = AppConfig::default();
+        config.cors.enabled = true;
+        config.security.cors_origins = origins.iter().map(|o| o.to_string()).collect();
+        CorsMiddleware::new(config)
+    }
+
+    #[test]
+    fn test_origin_wildcards() {
+        assert!(origin_matches("https://*.example.com", "https://wallet.example.com"));
+        assert!(origin_matches("https://*.example.com", "https://a.b.EXAMPLE.com"));
+        assert!(!origin_matches("https://*.example.com", "https://example.com"));
+        assert!(!origin_matches("https://*.example.com", "https://evil.com/.example.com"));
+        assert!(!origin_matches("https://*.example.com", "http://wallet.example.com"));
+        assert!(origin_matches("http://localhost:*", "http://localhost:3000"));
+        assert!(!origin_matches("http://localhost:*", "http://localhost.evil.com"));
+        assert!(origin_matches("https://app.example.com", "https://app.example.com"));
+    }
+
+    #[test]
+    fn test_route_overrides_and_credentials() {
+        let mut config = AppConfig::default();
+        config.cors.enabled = true;
+        config.cors.allow_credentials = true;
+        config.security.cors_origins = vec!["https://*.example.com".to_string()];
+        config.cors.routes = vec![CorsRouteConfig {
+            path_prefix: "/admin".to_string(),
+            origins: Some(vec!["https://ops.example.com".to_string()]),
+            methods: Some(vec!["POST".to_string()]),
+            headers: None,
+        }];
+        let middleware = CorsMiddleware::new(config.clone());
+        assert!(middleware.validate_config().is_ok());
+
+        assert_eq!(middleware.allowed_origin("/", "https://wallet.example.com").as_deref(), Some("https://wallet.example.com"));
+        assert_eq!(middleware.allowed_origin("/admin/revoke", "https://wallet.example.com"), None);
+        assert!(middleware.preflight_headers_for("/admin/revoke", "https://ops.example.com", "GET", None).is_none());
+        let headers = middleware
+            .preflight_headers_for("/admin/revoke", "https://ops.example.com", "POST", Some("content-type, authorization"))
+            .unwrap();
+        assert!(headers.contains(&("Access-Control-Allow-Credentials".to_string(), "true".to_string())));
+        assert!(middleware.preflight_headers_for("/", "https://wallet.example.com", "POST", Some("x-custom")).is_none());
+
+        config.security.cors_origins = vec!["*".to_string()];
+        assert!(CorsMiddleware::new(config).validate_config().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_preflight_and_response_headers() {
+        let cors = Arc::new(enforcing(&["https://wallet.example.com"]));
+        let routes = cors.clone().preflight_filter().or(cors.wrap(warp::path("rpc").map(|| "ok")));
+
+        let response = warp::test::request()
+            .method("OPTIONS")
+            .path("/rpc")
+            .header("origin", "https://wallet.example.com")
+            .header("access-control-request-method", "POST")
+            .header("access-control-request-headers", "Content-Type")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()["access-control-allow-origin"], "https://wallet.example.com");
+        assert_eq!(response.headers()["access-control-max-age"], "3600");
+
+        let response = warp::test::request()
+            .method("OPTIONS")
+            .path("/rpc")
+            .header("origin", "https://evil.example.org")
+            .header("access-control-request-method", "POST")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = warp::test::request()
+            .path("/rpc")
+            .header("origin", "https://wallet.example.com")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.headers()["access-control-allow-origin"], "https://wallet.example.com");
+        assert_eq!(response.headers()["vary"], "Origin");
+    }
+
+    #[tokio::test]
+    async fn test_reverse_proxy_mode_passes_through() {
+        let cors = Arc::new(CorsMiddleware::new(AppConfig::default()));
+        let routes = cors.clone().preflight_filter().or(cors.wrap(warp::path("rpc").map(|| "ok")));
+        let response = warp::test::request()
+            .path("/rpc")
+            .header("origin", "https://wallet.example.com")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("access-control-allow-origin").is_none());
+    }
+}
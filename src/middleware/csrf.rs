@@ -0,0 +1,227 @@
+//! CSRF protection for browser-facing state-changing routes
+//!
+//! Token issuance (`/issue`, `/session`, ...) and payment routes can be called
+//! straight from browsers. When `[csrf] enabled = true`, unsafe requests under
+//! the protected prefixes must carry a custom header (`header` mode) or echo
+//! the `SameSite=Strict` cookie from `GET /csrf` in that header
+//! (`double_submit` mode). Requests with neither `Origin` nor `Cookie` are
+//! treated as machine-to-machine and pass unless `allow_without_origin` is off.
+
+use crate::config::app_config::{CsrfConfig, CsrfMode};
+use crate::middleware::cors::origin_matches;
+use crate::shared::security::constant_time_str_eq;
+use std::sync::Arc;
+use tracing::warn;
+use warp::{
+    http::{header, HeaderMap, Method, StatusCode},
+    path::FullPath,
+    reply::Response,
+    Filter, Rejection, Reply,
+};
+
+/// CSRF guard
+pub struct CsrfMiddleware {
+    config: CsrfConfig,
+}
+
+impl CsrfMiddleware {
+    /// Create a new CSRF guard
+    pub fn new(config: CsrfConfig) -> Self {
+        Self { config }
+    }
+
+    /// Check a request, returning the reason it was refused
+    pub fn check(&self, method: &Method, path: &str, headers: &HeaderMap) -> Result<(), &'static str> {
+        if !self.config.enabled || !is_unsafe(method) || !self.is_protected(path) {
+            return Ok(());
+        }
+        let origin = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok());
+        let has_cookie = headers.contains_key(header::COOKIE);
+        if origin.is_none() && !has_cookie && self.config.allow_without_origin {
+            return Ok(());
+        }
+        if let Some(origin) = origin {
+            if !self.config.trusted_origins.is_empty()
+                && !self.config.trusted_origins.iter().any(|pattern| origin_matches(pattern, origin))
+            {
+                return Err("origin_not_trusted");
+            }
+        }
+
+        let token = headers
+            .get(self.config.header_name.as_str())
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or("csrf_header_missing")?;
+        match self.config.mode {
+            CsrfMode::Header => Ok(()),
+            CsrfMode::DoubleSubmit => {
+                let cookie = cookie_value(headers, &self.config.cookie_name).ok_or("csrf_cookie_missing")?;
+                if constant_time_str_eq(cookie, token) {
+                    Ok(())
+                } else {
+                    Err("csrf_token_mismatch")
+                }
+            }
+        }
+    }
+
+    fn is_protected(&self, path: &str) -> bool {
+        self.config.protected_prefixes.iter().any(|prefix| path.starts_with(prefix.as_str()))
+    }
+
+    /// Fresh token and the `Set-Cookie` value that carries it
+    pub fn issue_token(&self) -> (String, String) {
+        let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        let secure = if self.config.cookie_secure { "; Secure" } else { "" };
+        let cookie = format!("{}={}; Path=/; SameSite=Strict{}", self.config.cookie_name, token, secure);
+        (token, cookie)
+    }
+
+    /// Answer refused requests with `403`; rejects (not-found) everything else so routes see it
+    pub fn guard_filter(self: Arc<Self>) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+        warp::method()
+            .and(warp::path::full())
+            .and(warp::header::headers_cloned())
+            .and_then(move |method: Method, path: FullPath, headers: HeaderMap| {
+                let csrf = self.clone();
+                async move {
+                    match csrf.check(&method, path.as_str(), &headers) {
+                        Ok(()) => Err(warp::reject::not_found()),
+                        Err(reason) => {
+                            warn!(path = path.as_str(), reason, "CSRF check failed");
+                            let body = serde_json::json!({
+                                "error": reason,
+                                "message": format!("CSRF check failed; send the {} header", csrf.config.header_name),
+                            });
+                            Ok(warp::reply::with_status(warp::reply::json(&body), StatusCode::FORBIDDEN).into_response())
+                        }
+                    }
+                }
+            })
+    }
+
+    /// `GET /csrf`: hand out a double-submit token (404 unless enabled in that mode)
+    pub fn token_route(self: Arc<Self>) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+        warp::path("csrf")
+            .and(warp::path::end())
+            .and(warp::get())
+            .and_then(move || {
+                let csrf = self.clone();
+                async move {
+                    if !csrf.config.enabled || csrf.config.mode != CsrfMode::DoubleSubmit {
+                        return Err(warp::reject::not_found());
+                    }
+                    let (token, cookie) = csrf.issue_token();
+                    let body = serde_json::json!({ "token": token, "header": csrf.config.header_name });
+                    let reply = warp::reply::with_header(warp::reply::json(&body), header::SET_COOKIE, cookie);
+                    Ok(warp::reply::with_header(reply, header::CACHE_CONTROL, "no-store").into_response())
+                }
+            })
+    }
+}
+
+fn is_unsafe(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
+
+/// Value of a named cookie from the `Cookie` header(s)
+fn cookie_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard(mode: CsrfMode) -> CsrfMiddleware {
+        CsrfMiddleware::new(CsrfConfig { enabled: true, mode, ..CsrfConfig::default() })
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_header_mode() {
+        let csrf = guard(CsrfMode::Header);
+        let browser = headers(&[("origin", "https://wallet.example.com")]);
+        assert_eq!(csrf.check(&Method::POST, "/payments/submit", &browser), Err("csrf_header_missing"));
+        assert!(csrf.check(&Method::GET, "/payments/status/x", &browser).is_ok());
+        assert!(csrf.check(&Method::POST, "/", &browser).is_ok());
+        assert!(csrf.check(&Method::POST, "/issue", &HeaderMap::new()).is_ok());
+
+        let with_header = headers(&[("origin", "https://wallet.example.com"), ("x-csrf-token", "1")]);
+        assert!(csrf.check(&Method::POST, "/payments/submit", &with_header).is_ok());
+        assert!(CsrfMiddleware::new(CsrfConfig::default()).check(&Method::POST, "/issue", &browser).is_ok());
+    }
+
+    #[test]
+    fn test_double_submit_and_trusted_origins() {
+        let csrf = guard(CsrfMode::DoubleSubmit);
+        let (token, cookie) = csrf.issue_token();
+        assert!(cookie.contains("SameSite=Strict") && cookie.contains("Secure"));
+
+        let matching = headers(&[("cookie", &format!("theme=dark; csrf_token={}", token)), ("x-csrf-token", &token)]);
+        assert!(csrf.check(&Method::POST, "/session", &matching).is_ok());
+        let forged = headers(&[("cookie", &format!("csrf_token={}", token)), ("x-csrf-token", "guess")]);
+        assert_eq!(csrf.check(&Method::POST, "/session", &forged), Err("csrf_token_mismatch"));
+        let no_cookie = headers(&[("origin", "https://a.example.com"), ("x-csrf-token", &token)]);
+        assert_eq!(csrf.check(&Method::POST, "/session", &no_cookie), Err("csrf_cookie_missing"));
+
+        let csrf = CsrfMiddleware::new(CsrfConfig {
+            enabled: true,
+            trusted_origins: vec!["https://*.example.com".to_string()],
+            ..CsrfConfig::default()
+        });
+        let foreign = headers(&[("origin", "https://evil.example.org"), ("x-csrf-token", "1")]);
+        assert_eq!(csrf.check(&Method::POST, "/issue", &foreign), Err("origin_not_trusted"));
+    }
+
+    #[tokio::test]
+    async fn test_guard_filter() {
+        let csrf = Arc::new(guard(CsrfMode::DoubleSubmit));
+        let routes = csrf
+            .clone()
+            .guard_filter()
+            .or(csrf.token_route())
+            .or(warp::path("issue").and(warp::post()).map(|| "issued"));
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/issue")
+            .header("origin", "https://wallet.example.com")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = warp::test::request().path("/csrf").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::SET_COOKIE].to_str().unwrap().starts_with("csrf_token="));
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        let token = body["token"].as_str().unwrap();
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/issue")
+            .header("origin", "https://wallet.example.com")
+            .header("cookie", format!("csrf_token={}", token))
+            .header("x-csrf-token", token)
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), "issued");
+    }
+}
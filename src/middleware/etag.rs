@@ -0,0 +1,112 @@
+//! ETag support for cacheable GET endpoints
+//!
+//! Responses carry a strong ETag derived from the serialized body. When the
+//! client's `If-None-Match` matches, a bodyless 304 is returned instead, so
+//! polling dashboards only download content that actually changed.
+
+use crate::middleware::security_headers::{add_security_headers_to_response, SecurityHeadersMiddleware};
+use sha2::{Digest, Sha256};
+use warp::http::StatusCode;
+
+/// Cache-Control for ETag responses: clients may store them but must revalidate
+const REVALIDATE_CACHE_CONTROL: &str = "private, no-cache";
+
+/// Compute a strong ETag for a response body
+pub fn compute_etag(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    format!("\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// Whether an `If-None-Match` header value matches the given ETag
+///
+/// Uses the weak comparison required for `If-None-Match`, so `W/"x"` matches `"x"`.
+pub fn if_none_match_matches(if_none_match: Option<&str>, etag: &str) -> bool {
+    let header = match if_none_match {
+        Some(header) => header,
+        None => return false,
+    };
+    let etag = etag.trim_start_matches("W/");
+    header
+        .split(',')
+        .map(|candidate| candidate.trim())
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// JSON response with an ETag, or 304 if the client already has it
+pub fn etag_json_response<T: serde::Serialize>(
+    data: &T,
+    if_none_match: Option<String>,
+    middleware: &SecurityHeadersMiddleware,
+) -> Box<dyn warp::Reply> {
+    let body = serde_json::to_string(data).unwrap_or_else(|_| "null".to_string());
+    etag_response(body, "application/json", if_none_match, middleware)
+}
+
+/// Response with the given body and content type, carrying an ETag
+pub fn etag_response(
+    body: String,
+    content_type: &'static str,
+    if_none_match: Option<String>,
+    middleware: &SecurityHeadersMiddleware,
+) -> Box<dyn warp::Reply> {
+    let etag = compute_etag(body.as_bytes());
+
+    let response: Box<dyn warp::Reply> = if if_none_match_matches(if_none_match.as_deref(), &etag) {
+        add_security_headers_to_response(
+            warp::reply::with_header(
+                warp::reply::with_status(warp::reply(), StatusCode::NOT_MODIFIED),
+                "ETag",
+                etag,
+            ),
+            middleware,
+        )
+    } else {
+        add_security_headers_to_response(
+            warp::reply::with_header(
+                warp::reply::with_header(
+                    warp::reply::with_status(body, StatusCode::OK),
+                    "Content-Type",
+                    content_type,
+                ),
+                "ETag",
+                etag,
+            ),
+            middleware,
+        )
+    };
+
+    // Applied after the security headers so it replaces their no-store policy
+    Box::new(warp::reply::with_header(response, "Cache-Control", REVALIDATE_CACHE_CONTROL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use warp::Reply;
+
+    #[test]
+    fn test_if_none_match_matching() {
+        let etag = compute_etag(b"{\"blocks\":1}");
+        assert!(if_none_match_matches(Some(&etag), &etag));
+        assert!(if_none_match_matches(Some(&format!("\"other\", W/{}", etag)), &etag));
+        assert!(if_none_match_matches(Some("*"), &etag));
+        assert!(!if_none_match_matches(Some("\"other\""), &etag));
+        assert!(!if_none_match_matches(None, &etag));
+    }
+
+    #[test]
+    fn test_etag_response_returns_not_modified() {
+        let middleware = SecurityHeadersMiddleware::new(AppConfig::default());
+        let data = serde_json::json!({ "blocks": 1 });
+
+        let first = etag_json_response(&data, None, &middleware).into_response();
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers().get("etag").unwrap().to_str().unwrap().to_string();
+        assert_eq!(first.headers().get("cache-control").unwrap(), REVALIDATE_CACHE_CONTROL);
+
+        let second = etag_json_response(&data, Some(etag.clone()), &middleware).into_response();
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers().get("etag").unwrap().to_str().unwrap(), etag);
+    }
+}
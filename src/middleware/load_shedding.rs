@@ -0,0 +1,279 @@
+//! Adaptive load shedding
+//!
+//! Watches upstream daemon health (p95 latency and error rate from
+//! `UpstreamMetrics`) and, while it is degraded, rejects the lowest-priority
+//! RPC traffic first: anonymous calls to high-cost methods, then all anonymous
+//! calls and all high-cost methods. Levels only step down once both signals
+//! have recovered well below their thresholds, so shedding does not flap.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::app_config::LoadSheddingConfig;
+use crate::infrastructure::adapters::UpstreamMetrics;
+
+/// Shedding level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShedLevel {
+    /// Everything is served
+    Normal,
+    /// Anonymous calls to high-cost methods are shed
+    Elevated,
+    /// All anonymous calls and all high-cost methods are shed
+    Critical,
+}
+
+impl ShedLevel {
+    fn as_metric(self) -> u8 {
+        match self {
+            ShedLevel::Normal => 0,
+            ShedLevel::Elevated => 1,
+            ShedLevel::Critical => 2,
+        }
+    }
+}
+
+/// Load-shedding state for `/metrics`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadSheddingMetrics {
+    pub enabled: bool,
+    pub level: ShedLevel,
+    pub upstream_p95_ms: f64,
+    pub upstream_error_rate: f64,
+    pub shed_anonymous: u64,
+    pub shed_high_cost: u64,
+    pub level_changes: u64,
+}
+
+struct ShedState {
+    level: ShedLevel,
+    last_evaluated: Option<Instant>,
+    last_requests: u64,
+    last_errors: u64,
+    p95_ms: f64,
+    error_rate: f64,
+}
+
+/// Decides which requests to reject while the daemon is degraded
+pub struct LoadShedder {
+    config: LoadSheddingConfig,
+    state: Mutex<ShedState>,
+    shed_anonymous: AtomicU64,
+    shed_high_cost: AtomicU64,
+    level_changes: AtomicU64,
+}
+
+impl LoadShedder {
+    pub fn new(config: LoadSheddingConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(ShedState {
+                level: ShedLevel::Normal,
+                last_evaluated: None,
+                last_requests: 0,
+                last_errors: 0,
+                p95_ms: 0.0,
+                error_rate: 0.0,
+            }),
+            shed_anonymous: AtomicU64::new(0),
+            shed_high_cost: AtomicU64::new(0),
+            level_changes: AtomicU64::new(0),
+        }
+    }
+
+    /// Seconds clients should wait before retrying a shed request
+    pub fn retry_after_seconds(&self) -> u64 {
+        self.config.retry_after_seconds
+    }
+
+    /// Whether a request should be rejected; records the decision
+    pub fn should_shed(&self, method: &str, authenticated: bool) -> bool {
+        self.should_shed_request(method, authenticated, false)
+    }
+
+    /// Like `should_shed`, treating a `heavy` request as high-cost whatever its method
+    pub fn should_shed_request(&self, method: &str, authenticated: bool, heavy: bool) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        let level = self.evaluate();
+        let high_cost = heavy || self.config.high_cost_methods.iter().any(|m| m == method);
+        let shed = match level {
+            ShedLevel::Normal => false,
+            ShedLevel::Elevated => high_cost && !authenticated,
+            ShedLevel::Critical => high_cost || !authenticated,
+        };
+        if shed {
+            if high_cost {
+                self.shed_high_cost.fetch_add(1, Ordering::Relaxed);
+            } else {
+                self.shed_anonymous.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        shed
+    }
+
+    /// Current state and counters
+    pub fn metrics(&self) -> LoadSheddingMetrics {
+        let (level, p95_ms, error_rate) = self
+            .state
+            .lock()
+            .map(|s| (s.level, s.p95_ms, s.error_rate))
+            .unwrap_or((ShedLevel::Normal, 0.0, 0.0));
+        LoadSheddingMetrics {
+            enabled: self.config.enabled,
+            level,
+            upstream_p95_ms: p95_ms,
+            upstream_error_rate: error_rate,
+            shed_anonymous: self.shed_anonymous.load(Ordering::Relaxed),
+            shed_high_cost: self.shed_high_cost.load(Ordering::Relaxed),
+            level_changes: self.level_changes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Render shedding metrics in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let m = self.metrics();
+        let mut out = String::new();
+        out.push_str("# HELP verus_load_shedding_level Current shedding level (0 normal, 1 elevated, 2 critical)\n");
+        out.push_str("# TYPE verus_load_shedding_level gauge\n");
+        out.push_str(&format!("verus_load_shedding_level {}\n", m.level.as_metric()));
+        out.push_str("# HELP verus_load_shed_requests_total Requests rejected by load shedding\n");
+        out.push_str("# TYPE verus_load_shed_requests_total counter\n");
+        out.push_str(&format!("verus_load_shed_requests_total{{reason=\"anonymous\"}} {}\n", m.shed_anonymous));
+        out.push_str(&format!("verus_load_shed_requests_total{{reason=\"high_cost\"}} {}\n", m.shed_high_cost));
+        out.push_str("# HELP verus_load_shedding_level_changes_total Shedding level transitions\n");
+        out.push_str("# TYPE verus_load_shedding_level_changes_total counter\n");
+        out.push_str(&format!("verus_load_shedding_level_changes_total {}\n", m.level_changes));
+        out
+    }
+
+    /// Re-read upstream health at most once per evaluation interval
+    fn evaluate(&self) -> ShedLevel {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return ShedLevel::Normal,
+        };
+        let interval = Duration::from_secs(self.config.evaluation_interval_seconds);
+        if state.last_evaluated.is_some_and(|at| at.elapsed() < interval) {
+            return state.level;
+        }
+        state.last_evaluated = Some(Instant::now());
+
+        let stats = UpstreamMetrics::global().snapshot();
+        let requests: u64 = stats.iter().map(|s| s.requests).sum();
+        let errors: u64 = stats.iter().map(|s| s.errors).sum();
+        let window_requests = requests.saturating_sub(state.last_requests);
+        let window_errors = errors.saturating_sub(state.last_errors);
+        state.last_requests = requests;
+        state.last_errors = errors;
+
+        // An idle upstream has nothing to report; let the latency signal recover
+        state.p95_ms = if window_requests == 0 {
+            0.0
+        } else {
+            stats.iter().map(|s| s.p95_ms).fold(0.0, f64::max)
+        };
+        state.error_rate = if window_requests >= self.config.min_samples {
+            window_errors as f64 / window_requests as f64
+        } else {
+            0.0
+        };
+
+        let next = next_level(state.level, state.p95_ms, state.error_rate, &self.config);
+        if next != state.level {
+            self.level_changes.fetch_add(1, Ordering::Relaxed);
+            if next > state.level {
+                warn!(from = ?state.level, to = ?next, p95_ms = state.p95_ms, error_rate = state.error_rate, "Load shedding escalated");
+            } else {
+                info!(from = ?state.level, to = ?next, p95_ms = state.p95_ms, error_rate = state.error_rate, "Load shedding relaxed");
+            }
+            state.level = next;
+        }
+        state.level
+    }
+}
+
+/// Next shedding level given upstream health, with hysteresis
+///
+/// A level is entered when either signal crosses its threshold (critical at
+/// twice the threshold) and left only when both fall below `recovery_ratio`
+/// of the threshold for that level.
+fn next_level(current: ShedLevel, p95_ms: f64, error_rate: f64, config: &LoadSheddingConfig) -> ShedLevel {
+    let latency = config.p95_latency_ms as f64;
+    let above = |factor: f64| p95_ms >= latency * factor || error_rate >= (config.error_rate * factor).min(1.0);
+    let below = |factor: f64| {
+        p95_ms < latency * factor * config.recovery_ratio && error_rate < config.error_rate * factor * config.recovery_ratio
+    };
+
+    if above(2.0) {
+        return ShedLevel::Critical;
+    }
+    match current {
+        ShedLevel::Critical if !below(2.0) => ShedLevel::Critical,
+        _ if above(1.0) => ShedLevel::Elevated,
+        ShedLevel::Normal => ShedLevel::Normal,
+        _ if below(1.0) => ShedLevel::Normal,
+        _ => ShedLevel::Elevated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LoadSheddingConfig {
+        LoadSheddingConfig { enabled: true, ..Default::default() }
+    }
+
+    #[test]
+    fn test_levels_use_hysteresis() {
+        let config = config();
+        assert_eq!(next_level(ShedLevel::Normal, 100.0, 0.0, &config), ShedLevel::Normal);
+        assert_eq!(next_level(ShedLevel::Normal, 2500.0, 0.0, &config), ShedLevel::Elevated);
+        assert_eq!(next_level(ShedLevel::Normal, 100.0, 0.5, &config), ShedLevel::Critical);
+
+        // Just under the threshold is not enough to recover
+        assert_eq!(next_level(ShedLevel::Elevated, 1500.0, 0.0, &config), ShedLevel::Elevated);
+        assert_eq!(next_level(ShedLevel::Elevated, 900.0, 0.05, &config), ShedLevel::Normal);
+        assert_eq!(next_level(ShedLevel::Critical, 3000.0, 0.0, &config), ShedLevel::Critical);
+        assert_eq!(next_level(ShedLevel::Critical, 1500.0, 0.0, &config), ShedLevel::Elevated);
+    }
+
+    #[test]
+    fn test_lowest_priority_traffic_is_shed_first() {
+        let shedder = LoadShedder::new(config());
+        let set_level = |level| {
+            let mut state = shedder.state.lock().unwrap();
+            state.level = level;
+            state.last_evaluated = Some(Instant::now());
+        };
+
+        set_level(ShedLevel::Elevated);
+        assert!(shedder.should_shed("getblocktemplate", false));
+        assert!(!shedder.should_shed("getblocktemplate", true));
+        assert!(!shedder.should_shed("getblockcount", false));
+        assert!(shedder.should_shed_request("getexports", false, true));
+
+        set_level(ShedLevel::Critical);
+        assert!(shedder.should_shed("getblockcount", false));
+        assert!(shedder.should_shed("getblocktemplate", true));
+        assert!(!shedder.should_shed("getblockcount", true));
+
+        let metrics = shedder.metrics();
+        assert_eq!(metrics.shed_high_cost, 3);
+        assert_eq!(metrics.shed_anonymous, 1);
+    }
+
+    #[test]
+    fn test_disabled_never_sheds() {
+        let shedder = LoadShedder::new(LoadSheddingConfig::default());
+        shedder.state.lock().unwrap().level = ShedLevel::Critical;
+        assert!(!shedder.should_shed("getblocktemplate", false));
+    }
+}
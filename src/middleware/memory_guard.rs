@@ -0,0 +1,261 @@
+//! Memory guardrails
+//!
+//! Measures process memory (resident size from `/proc`, or the bytes tracked
+//! by `MemoryUsage` where procfs is missing) at most once per evaluation
+//! interval and steps through three levels instead of running into the OOM
+//! killer. Past the soft limit the in-memory response cache is cut to
+//! `cache_keep_percent` of its entries, RPC requests are admitted only while
+//! in-flight request bodies stay under `max_in_flight_bytes`, and `/health`
+//! reports degraded. Past the hard limit the cache is emptied and new RPC
+//! requests are refused with 503 until memory falls again. As with load
+//! shedding, a level is left only once memory drops well below its limit.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::app_config::MemoryGuardConfig;
+use crate::infrastructure::adapters::{InFlightRequest, MemoryUsage, ProcessSnapshot};
+
+/// Memory pressure level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryPressure {
+    /// Below the soft limit
+    Normal,
+    /// Past the soft limit: cache shrunk, in-flight bytes capped
+    Elevated,
+    /// Past the hard limit: new RPC requests refused
+    Critical,
+}
+
+impl MemoryPressure {
+    fn as_metric(self) -> u8 {
+        match self {
+            MemoryPressure::Normal => 0,
+            MemoryPressure::Elevated => 1,
+            MemoryPressure::Critical => 2,
+        }
+    }
+}
+
+/// Memory guard state for `/metrics` and `/health`
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryGuardMetrics {
+    pub enabled: bool,
+    pub pressure: MemoryPressure,
+    /// Memory use the levels are decided on
+    pub measured_bytes: u64,
+    pub soft_limit_bytes: u64,
+    pub hard_limit_bytes: u64,
+    /// Approximate bytes per tracked table
+    pub tracked_bytes: BTreeMap<&'static str, usize>,
+    pub refused_requests: u64,
+    pub cache_shrinks: u64,
+    pub pressure_changes: u64,
+}
+
+struct GuardState {
+    pressure: MemoryPressure,
+    last_evaluated: Option<Instant>,
+    measured_bytes: u64,
+}
+
+/// Decides when memory use calls for shedding caches and requests
+pub struct MemoryGuard {
+    config: MemoryGuardConfig,
+    state: Mutex<GuardState>,
+    refused: AtomicU64,
+    cache_shrinks: AtomicU64,
+    pressure_changes: AtomicU64,
+}
+
+impl MemoryGuard {
+    pub fn new(config: MemoryGuardConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(GuardState { pressure: MemoryPressure::Normal, last_evaluated: None, measured_bytes: 0 }),
+            refused: AtomicU64::new(0),
+            cache_shrinks: AtomicU64::new(0),
+            pressure_changes: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether `[memory_guard]` is enabled
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Seconds clients should wait before retrying a refused request
+    pub fn retry_after_seconds(&self) -> u64 {
+        self.config.retry_after_seconds
+    }
+
+    /// Admit a request with a body of `bytes`; its bytes count as in flight until the guard is dropped
+    ///
+    /// `None` when memory pressure refuses the request.
+    pub fn admit(&self, bytes: usize) -> Option<InFlightRequest> {
+        let usage = MemoryUsage::global();
+        if self.config.enabled {
+            let refuse = match self.evaluate() {
+                MemoryPressure::Normal => false,
+                MemoryPressure::Elevated => usage.in_flight_bytes().saturating_add(bytes) > self.config.max_in_flight_bytes,
+                MemoryPressure::Critical => true,
+            };
+            if refuse {
+                self.refused.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        }
+        Some(usage.track_request(bytes))
+    }
+
+    /// Current level, re-measured if the evaluation interval has passed
+    pub fn pressure(&self) -> MemoryPressure {
+        if !self.config.enabled {
+            return MemoryPressure::Normal;
+        }
+        self.evaluate()
+    }
+
+    /// Current state and counters
+    pub fn metrics(&self) -> MemoryGuardMetrics {
+        let (pressure, measured_bytes) = self
+            .state
+            .lock()
+            .map(|s| (s.pressure, s.measured_bytes))
+            .unwrap_or((MemoryPressure::Normal, 0));
+        MemoryGuardMetrics {
+            enabled: self.config.enabled,
+            pressure,
+            measured_bytes,
+            soft_limit_bytes: self.config.soft_limit_bytes,
+            hard_limit_bytes: self.config.hard_limit_bytes,
+            tracked_bytes: MemoryUsage::global().by_component(),
+            refused_requests: self.refused.load(Ordering::Relaxed),
+            cache_shrinks: self.cache_shrinks.load(Ordering::Relaxed),
+            pressure_changes: self.pressure_changes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Render guard metrics in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let m = self.metrics();
+        let mut out = String::new();
+        out.push_str("# HELP verus_memory_pressure Memory pressure level (0 normal, 1 elevated, 2 critical)\n");
+        out.push_str("# TYPE verus_memory_pressure gauge\n");
+        out.push_str(&format!("verus_memory_pressure {}\n", m.pressure.as_metric()));
+        out.push_str("# HELP verus_memory_measured_bytes Memory use the pressure level is decided on\n");
+        out.push_str("# TYPE verus_memory_measured_bytes gauge\n");
+        out.push_str(&format!("verus_memory_measured_bytes {}\n", m.measured_bytes));
+        out.push_str("# HELP verus_memory_refused_requests_total Requests refused under memory pressure\n");
+        out.push_str("# TYPE verus_memory_refused_requests_total counter\n");
+        out.push_str(&format!("verus_memory_refused_requests_total {}\n", m.refused_requests));
+        out.push_str("# HELP verus_memory_cache_shrinks_total Times the response cache was shrunk under memory pressure\n");
+        out.push_str("# TYPE verus_memory_cache_shrinks_total counter\n");
+        out.push_str(&format!("verus_memory_cache_shrinks_total {}\n", m.cache_shrinks));
+        out
+    }
+
+    /// Re-measure memory at most once per evaluation interval
+    fn evaluate(&self) -> MemoryPressure {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return MemoryPressure::Normal,
+        };
+        let interval = Duration::from_secs(self.config.evaluation_interval_seconds);
+        if state.last_evaluated.is_some_and(|at| at.elapsed() < interval) {
+            return state.pressure;
+        }
+        state.last_evaluated = Some(Instant::now());
+        state.measured_bytes =
+            ProcessSnapshot::current_resident_memory().unwrap_or_else(|| MemoryUsage::global().total() as u64);
+
+        let next = next_pressure(state.pressure, state.measured_bytes, &self.config);
+        if next != state.pressure {
+            self.pressure_changes.fetch_add(1, Ordering::Relaxed);
+            if next > state.pressure {
+                warn!(from = ?state.pressure, to = ?next, measured_bytes = state.measured_bytes, "Memory pressure rising");
+                let keep_percent = if next == MemoryPressure::Critical { 0 } else { self.config.cache_keep_percent };
+                MemoryUsage::global().shrink(keep_percent);
+                self.cache_shrinks.fetch_add(1, Ordering::Relaxed);
+            } else {
+                info!(from = ?state.pressure, to = ?next, measured_bytes = state.measured_bytes, "Memory pressure easing");
+            }
+            state.pressure = next;
+        }
+        state.pressure
+    }
+}
+
+/// Next level given measured memory use, with hysteresis
+///
+/// A level is entered when memory reaches its limit and left only once
+/// memory falls below `recovery_ratio` of that limit.
+fn next_pressure(current: MemoryPressure, measured: u64, config: &MemoryGuardConfig) -> MemoryPressure {
+    let measured = measured as f64;
+    let (soft, hard) = (config.soft_limit_bytes as f64, config.hard_limit_bytes as f64);
+    if measured >= hard {
+        return MemoryPressure::Critical;
+    }
+    match current {
+        MemoryPressure::Critical if measured >= hard * config.recovery_ratio => MemoryPressure::Critical,
+        _ if measured >= soft => MemoryPressure::Elevated,
+        MemoryPressure::Normal => MemoryPressure::Normal,
+        _ if measured < soft * config.recovery_ratio => MemoryPressure::Normal,
+        _ => MemoryPressure::Elevated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn config() -> MemoryGuardConfig {
+        MemoryGuardConfig { enabled: true, soft_limit_bytes: 100 * MIB, hard_limit_bytes: 200 * MIB, ..Default::default() }
+    }
+
+    #[test]
+    fn test_pressure_uses_hysteresis() {
+        let config = config();
+        assert_eq!(next_pressure(MemoryPressure::Normal, 50 * MIB, &config), MemoryPressure::Normal);
+        assert_eq!(next_pressure(MemoryPressure::Normal, 120 * MIB, &config), MemoryPressure::Elevated);
+        assert_eq!(next_pressure(MemoryPressure::Normal, 250 * MIB, &config), MemoryPressure::Critical);
+
+        // Just under a limit is not enough to step down
+        assert_eq!(next_pressure(MemoryPressure::Elevated, 95 * MIB, &config), MemoryPressure::Elevated);
+        assert_eq!(next_pressure(MemoryPressure::Elevated, 80 * MIB, &config), MemoryPressure::Normal);
+        assert_eq!(next_pressure(MemoryPressure::Critical, 190 * MIB, &config), MemoryPressure::Critical);
+        assert_eq!(next_pressure(MemoryPressure::Critical, 150 * MIB, &config), MemoryPressure::Elevated);
+    }
+
+    #[test]
+    fn test_admission_tightens_with_pressure() {
+        let guard = MemoryGuard::new(MemoryGuardConfig { max_in_flight_bytes: 64 * 1024, ..config() });
+        let set_pressure = |pressure| {
+            let mut state = guard.state.lock().unwrap();
+            state.pressure = pressure;
+            state.last_evaluated = Some(Instant::now());
+        };
+
+        set_pressure(MemoryPressure::Elevated);
+        let admitted = guard.admit(1024);
+        assert!(admitted.is_some());
+        assert!(guard.admit(usize::MAX / 2).is_none());
+
+        set_pressure(MemoryPressure::Critical);
+        assert!(guard.admit(1).is_none());
+        drop(admitted);
+
+        let disabled = MemoryGuard::new(MemoryGuardConfig::default());
+        disabled.state.lock().unwrap().pressure = MemoryPressure::Critical;
+        assert!(disabled.admit(1024).is_some());
+        assert_eq!(guard.metrics().refused_requests, 2);
+    }
+}
@@ -1,4 +1,12 @@
 pub mod cors;
+pub mod csrf;
 pub mod rate_limit;
 pub mod security_headers;
-pub mod cache; 
\ No newline at end of file
+pub mod cache;
+pub mod canonical_json;
+pub mod cache_warmer;
+pub mod complexity;
+pub mod etag;pub mod load_shedding;
+pub mod memory_guard;
+pub mod request_logging;
+pub mod response_signing;
@@ -1,8 +1,13 @@
 use crate::config::AppConfig;
+use crate::domain::validation::{MethodRegistry, SecurityLevel};
+use crate::infrastructure::adapters::memory_usage::{MemoryUsage, ENTRY_OVERHEAD_BYTES};
+use crate::infrastructure::adapters::ClusterCoordinator;
 use crate::shared::error::AppError;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::Infallible;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tracing::warn;
@@ -14,6 +19,19 @@ pub struct RateLimitConfig {
     pub requests_per_minute: u32,
     pub burst_size: u32,
     pub enabled: bool,
+    /// Maximum keys tracked at once; the least recently seen is evicted beyond this
+    pub max_tracked_keys: usize,
+}
+
+impl RateLimitConfig {
+    fn from_app(config: &crate::config::app_config::RateLimitConfig) -> Self {
+        Self {
+            requests_per_minute: config.requests_per_minute,
+            burst_size: config.burst_size,
+            enabled: config.enabled,
+            max_tracked_keys: config.max_tracked_keys,
+        }
+    }
 }
 
 /// Rate limiting state for a client
@@ -21,26 +39,88 @@ pub struct RateLimitConfig {
 pub struct ClientRateLimit {
     pub requests: u32,
     pub window_start: u64,
+    /// Recency stamp, the key of this client in `ClientTable::recency`
+    stamp: u64,
+}
+
+/// Buckets for the current window, with recency order for LRU eviction
+#[derive(Default)]
+struct ClientTable {
+    clients: HashMap<String, ClientRateLimit>,
+    /// Recency stamp -> key, least recently seen first
+    recency: BTreeMap<u64, String>,
+    next_stamp: u64,
+    window_start: u64,
+}
+
+/// Tracked-key gauges and eviction counters
+#[derive(Default)]
+struct TableCounters {
+    tracked_keys: AtomicUsize,
+    evicted_keys: AtomicU64,
+    expired_keys: AtomicU64,
+    /// Approximate bytes held by the table
+    bytes: Arc<AtomicUsize>,
+}
+
+/// Rate limiter memory usage
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitMetrics {
+    /// Client keys currently holding a bucket
+    pub tracked_keys: usize,
+    /// Keys dropped because `max_tracked_keys` was reached
+    pub evicted_keys: u64,
+    /// Keys dropped because their window ended
+    pub expired_keys: u64,
 }
 
 /// Rate limiting state
 #[derive(Clone)]
 pub struct RateLimitState {
-    clients: Arc<RwLock<HashMap<String, ClientRateLimit>>>,
+    clients: Arc<RwLock<ClientTable>>,
+    counters: Arc<TableCounters>,
     config: RateLimitConfig,
+    /// Shared windows in cluster mode; the local table is the fallback when Redis fails
+    cluster: Option<Arc<ClusterCoordinator>>,
 }
 
 impl RateLimitState {
     /// Create a new rate limiter
     pub fn new(config: RateLimitConfig) -> Self {
         Self {
-            clients: Arc::new(RwLock::new(HashMap::new())),
+            clients: Arc::new(RwLock::new(ClientTable::default())),
+            counters: Arc::new(TableCounters::default()),
             config,
+            cluster: None,
         }
     }
     
+    /// The same buckets with a budget of their own, as set by a client profile
+    pub fn with_requests_per_minute(mut self, requests_per_minute: u32) -> Self {
+        self.config.requests_per_minute = requests_per_minute;
+        self.config.enabled = true;
+        self
+    }
+
     /// Check if request is allowed
     pub async fn check_rate_limit(&self, key: &str) -> Result<(), AppError> {
+        self.check_rate_limit_weighted(key, 1).await
+    }
+    
+    /// Check if a request costing `cost` tokens is allowed
+    pub async fn check_rate_limit_weighted(&self, key: &str, cost: u32) -> Result<(), AppError> {
+        self.take(key, cost, true).await
+    }
+
+    /// Debit `cost` tokens for work already done on the client's behalf
+    ///
+    /// Never refuses; a budget overdrawn this way refuses the client's next
+    /// requests until the window resets.
+    pub async fn debit(&self, key: &str, cost: u32) {
+        let _ = self.take(key, cost, false).await;
+    }
+
+    async fn take(&self, key: &str, cost: u32, refuse: bool) -> Result<(), AppError> {
         if !self.config.enabled {
             return Ok(());
         }
@@ -52,42 +132,116 @@ impl RateLimitState {
         
         let window_start = now - (now % 60); // 1-minute windows
         
-        let mut clients = self.clients.write().await;
+        if let Some(cluster) = &self.cluster {
+            match cluster.add_to_window(key, window_start, 60, cost).await {
+                Ok(total) if refuse && total > self.config.requests_per_minute as u64 => {
+                    // Refused calls are not debited, as with the local buckets
+                    if let Err(e) = cluster.refund_window(key, window_start, cost).await {
+                        warn!("Cluster rate limit refund failed: {}", e);
+                    }
+                    warn!("Rate limit exceeded for key: {} (cost {})", key, cost);
+                    return Err(AppError::RateLimit);
+                }
+                Ok(_) => return Ok(()),
+                Err(e) => warn!("Cluster rate limit unavailable, using local buckets: {}", e),
+            }
+        }
+        
+        let mut guard = self.clients.write().await;
+        let table = &mut *guard;
+        if table.window_start != window_start {
+            // Buckets from an earlier window would be reset on their next use anyway
+            self.counters.expired_keys.fetch_add(table.clients.len() as u64, Ordering::Relaxed);
+            table.clients.clear();
+            table.recency.clear();
+            self.counters.bytes.store(0, Ordering::Relaxed);
+            table.window_start = window_start;
+        }
+        let stamp = table.next_stamp;
+        table.next_stamp += 1;
         
-        if let Some(client) = clients.get_mut(key) {
-            if client.window_start != window_start {
-                // New window, reset counter
-                client.requests = 1;
-                client.window_start = window_start;
-            } else if client.requests >= self.config.requests_per_minute {
+        let result = if let Some(client) = table.clients.get_mut(key) {
+            table.recency.remove(&client.stamp);
+            table.recency.insert(stamp, key.to_string());
+            client.stamp = stamp;
+            if refuse && client.requests.saturating_add(cost) > self.config.requests_per_minute {
                 // Rate limit exceeded
-                warn!("Rate limit exceeded for key: {}", key);
-                return Err(AppError::RateLimit);
+                warn!("Rate limit exceeded for key: {} (cost {})", key, cost);
+                Err(AppError::RateLimit)
             } else {
-                // Increment counter
-                client.requests += 1;
+                // Debit the call's cost
+                client.requests = client.requests.saturating_add(cost);
+                Ok(())
             }
         } else {
-            // New client
-            clients.insert(key.to_string(), ClientRateLimit {
-                requests: 1,
+            // New client; make room by evicting the least recently seen keys
+            while table.clients.len() >= self.config.max_tracked_keys.max(1) {
+                let Some((_, oldest)) = table.recency.pop_first() else { break };
+                table.clients.remove(&oldest);
+                self.counters.evicted_keys.fetch_add(1, Ordering::Relaxed);
+                self.counters.bytes.fetch_sub(key_bytes(&oldest), Ordering::Relaxed);
+            }
+            table.recency.insert(stamp, key.to_string());
+            self.counters.bytes.fetch_add(key_bytes(key), Ordering::Relaxed);
+            table.clients.insert(key.to_string(), ClientRateLimit {
+                requests: cost,
                 window_start,
+                stamp,
             });
+            Ok(())
+        };
+        self.counters.tracked_keys.store(table.clients.len(), Ordering::Relaxed);
+        result
+    }
+    
+    /// Current memory usage of this limiter
+    pub fn metrics(&self) -> RateLimitMetrics {
+        RateLimitMetrics {
+            tracked_keys: self.counters.tracked_keys.load(Ordering::Relaxed),
+            evicted_keys: self.counters.evicted_keys.load(Ordering::Relaxed),
+            expired_keys: self.counters.expired_keys.load(Ordering::Relaxed),
         }
-        
-        Ok(())
     }
+    
+    /// Render limiter metrics in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        let m = self.metrics();
+        let mut out = String::new();
+        out.push_str("# HELP verus_rate_limit_tracked_keys Client keys currently tracked by the rate limiter\n");
+        out.push_str("# TYPE verus_rate_limit_tracked_keys gauge\n");
+        out.push_str(&format!("verus_rate_limit_tracked_keys {}\n", m.tracked_keys));
+        out.push_str("# HELP verus_rate_limit_dropped_keys_total Client keys dropped from the rate limiter\n");
+        out.push_str("# TYPE verus_rate_limit_dropped_keys_total counter\n");
+        out.push_str(&format!("verus_rate_limit_dropped_keys_total{{reason=\"evicted\"}} {}\n", m.evicted_keys));
+        out.push_str(&format!("verus_rate_limit_dropped_keys_total{{reason=\"expired\"}} {}\n", m.expired_keys));
+        out
+    }
+}
+
+/// Approximate bytes one tracked key holds: the key in both maps plus the bucket
+fn key_bytes(key: &str) -> usize {
+    2 * key.len() + std::mem::size_of::<ClientRateLimit>() + ENTRY_OVERHEAD_BYTES
 }
 
 /// Rate limiting middleware for HTTP responses
 pub struct RateLimitMiddleware {
     config: AppConfig,
+    /// Per-client buckets, shared by every route handed this middleware
+    limiter: RateLimitState,
 }
 
 impl RateLimitMiddleware {
     /// Create a new rate limiting middleware
     pub fn new(config: AppConfig) -> Self {
-        Self { config }
+        let limiter = RateLimitState::new(RateLimitConfig::from_app(&config.rate_limit));
+        MemoryUsage::global().register("rate_limit", &limiter.counters.bytes);
+        Self { config, limiter }
+    }
+
+    /// Share the windows with other replicas through `cluster` when `[cluster]` is enabled
+    pub fn with_cluster(mut self, cluster: Option<Arc<ClusterCoordinator>>) -> Self {
+        self.limiter.cluster = cluster.filter(|_| self.config.cluster.enabled);
+        self
     }
     
     /// Get rate limiting configuration
@@ -100,13 +254,37 @@ impl RateLimitMiddleware {
         self.config.rate_limit.enabled
     }
     
-    /// Create a rate limiter for a specific client
+    /// Rate limiter for a specific client (this middleware's per-client limiter)
     pub fn create_client_limiter(&self, _client_ip: &str) -> RateLimitState {
-        RateLimitState::new(RateLimitConfig {
-            requests_per_minute: self.config.rate_limit.requests_per_minute,
-            burst_size: self.config.rate_limit.burst_size,
-            enabled: self.config.rate_limit.enabled,
-        })
+        self.limiter.clone()
+    }
+
+    /// Memory usage of the per-client limiter
+    pub fn metrics(&self) -> RateLimitMetrics {
+        self.limiter.metrics()
+    }
+
+    /// Render the per-client limiter's metrics in Prometheus text format
+    pub fn prometheus_text(&self) -> String {
+        self.limiter.prometheus_text()
+    }
+    
+    /// Tokens a call to `method` debits from the client's budget
+    pub fn method_cost(&self, method: &str) -> u32 {
+        let costs = &self.config.rate_limit.costs;
+        if let Some(cost) = costs.methods.get(method) {
+            return *cost;
+        }
+        static REGISTRY: OnceLock<MethodRegistry> = OnceLock::new();
+        let definition = REGISTRY.get_or_init(MethodRegistry::new).get_method(method);
+        if let Some(cost) = definition.and_then(|m| costs.categories.get(m.category.as_str())) {
+            return *cost;
+        }
+        match definition.map(|m| &m.security_level) {
+            Some(SecurityLevel::High) => costs.high,
+            Some(SecurityLevel::Medium) => costs.medium,
+            Some(SecurityLevel::Low) | None => costs.low,
+        }
     }
 }
 
@@ -128,11 +306,7 @@ pub fn create_method_rate_limiter(
     let method_config = config.security.method_rate_limits.get(method)
         .unwrap_or(&config.rate_limit);
     
-    RateLimitState::new(RateLimitConfig {
-        requests_per_minute: method_config.requests_per_minute,
-        burst_size: method_config.burst_size,
-        enabled: method_config.enabled,
-    })
+    RateLimitState::new(RateLimitConfig::from_app(method_config))
 }
 
 /// Rate limiting error handler
@@ -161,4 +335,101 @@ pub fn handle_rate_limit_error(err: Rejection) -> Result<impl Reply, Infallible>
         })),
         warp::http::StatusCode::INTERNAL_SERVER_ERROR,
     ))
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_weighted_calls_debit_their_cost() {
+        let limiter = RateLimitState::new(RateLimitConfig {
+            requests_per_minute: 10,
+            burst_size: 10,
+            enabled: true,
+            max_tracked_keys: 100,
+        });
+        limiter.check_rate_limit_weighted("client", 6).await.unwrap();
+        assert!(limiter.check_rate_limit_weighted("client", 5).await.is_err());
+        limiter.check_rate_limit_weighted("client", 4).await.unwrap();
+        assert!(limiter.check_rate_limit("client").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_debit_overdraws_the_budget() {
+        let limiter = RateLimitState::new(RateLimitConfig {
+            requests_per_minute: 10,
+            burst_size: 10,
+            enabled: true,
+            max_tracked_keys: 100,
+        });
+        limiter.check_rate_limit("client").await.unwrap();
+        limiter.debit("client", 50).await;
+        assert!(limiter.check_rate_limit("client").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tracked_keys_are_bounded_lru() {
+        let limiter = RateLimitState::new(RateLimitConfig {
+            requests_per_minute: 2,
+            burst_size: 2,
+            enabled: true,
+            max_tracked_keys: 3,
+        });
+        limiter.check_rate_limit("a").await.unwrap();
+        limiter.check_rate_limit("a").await.unwrap();
+        limiter.check_rate_limit("b").await.unwrap();
+        limiter.check_rate_limit("c").await.unwrap();
+        // "a" is refused, which still counts as recent use, so "b" is evicted
+        assert!(limiter.check_rate_limit("a").await.is_err());
+        limiter.check_rate_limit("d").await.unwrap();
+        assert!(limiter.check_rate_limit("a").await.is_err());
+
+        let metrics = limiter.metrics();
+        assert_eq!((metrics.tracked_keys, metrics.evicted_keys), (3, 1));
+        assert_eq!(limiter.counters.bytes.load(Ordering::Relaxed), 3 * key_bytes("a"));
+        assert!(limiter.prometheus_text().contains("verus_rate_limit_tracked_keys 3"));
+    }
+
+    #[tokio::test]
+    async fn test_client_limiter_is_reused_and_expires_old_windows() {
+        let mut config = AppConfig::default();
+        config.rate_limit.requests_per_minute = 1;
+        let middleware = RateLimitMiddleware::new(config);
+        middleware.create_client_limiter("203.0.113.7").check_rate_limit("203.0.113.7").await.unwrap();
+        assert!(middleware.create_client_limiter("203.0.113.7").check_rate_limit("203.0.113.7").await.is_err());
+
+        let limiter = RateLimitState::new(RateLimitConfig::from_app(&AppConfig::default().rate_limit));
+        limiter.check_rate_limit("a").await.unwrap();
+        limiter.clients.write().await.window_start -= 60;
+        limiter.check_rate_limit("b").await.unwrap();
+        assert_eq!((limiter.metrics().tracked_keys, limiter.metrics().expired_keys), (1, 1));
+    }
+
+    #[test]
+    fn test_method_cost_resolution() {
+        let mut config = AppConfig::default();
+        config.rate_limit.costs.methods.insert("getinfo".to_string(), 7);
+        config.rate_limit.costs.medium = 4;
+        let middleware = RateLimitMiddleware::new(config);
+
+        assert_eq!(middleware.method_cost("getinfo"), 7);
+        assert_eq!(middleware.method_cost("getblocktemplate"), 10);
+        assert_eq!(middleware.method_cost("getcurrency"), 4);
+        assert_eq!(middleware.method_cost("sendrawtransaction"), 5);
+        assert_eq!(middleware.method_cost("getblockcount"), 1);
+        assert_eq!(middleware.method_cost("notamethod"), 1);
+    }
+
+    #[test]
+    fn test_category_cost_between_method_and_level() {
+        let mut config = AppConfig::default();
+        config.rate_limit.costs.categories.insert("address_index".to_string(), 8);
+        let middleware = RateLimitMiddleware::new(config);
+
+        assert_eq!(middleware.method_cost("getaddressbalance"), 8);
+        // Per-method weights still win
+        assert_eq!(middleware.method_cost("getaddressdeltas"), 10);
+        assert_eq!(middleware.method_cost("getblockcount"), 1);
+    }
+}
@@ -0,0 +1,139 @@
+//! Sampled request logging
+//!
+//! Logging every request at high request rates costs more than it tells.
+//! With `[logging.sampling]` enabled, each JSON-RPC request is logged once on
+//! completion with its status and duration, and only when it is worth
+//! reading: every error, every request slower than `slow_request_ms`, and one
+//! in `success_sample_rate` of the remaining successes.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tracing::{info, warn};
+use warp::http::StatusCode;
+
+use crate::config::app_config::RequestLogSamplingConfig;
+use crate::infrastructure::http::models::RequestContext;
+
+/// Why a completed request was logged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogReason {
+    Error,
+    Slow,
+    Sampled,
+}
+
+impl LogReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogReason::Error => "error",
+            LogReason::Slow => "slow",
+            LogReason::Sampled => "sampled",
+        }
+    }
+}
+
+/// Decides which completed requests are logged
+pub struct RequestLogSampler {
+    config: RequestLogSamplingConfig,
+    successes: AtomicU64,
+}
+
+impl RequestLogSampler {
+    pub fn new(config: RequestLogSamplingConfig) -> Self {
+        Self {
+            config,
+            successes: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether requests are logged on completion instead of on arrival
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Whether a request that finished with `status` after `duration` is logged
+    pub fn reason(&self, status: StatusCode, duration: Duration) -> Option<LogReason> {
+        if status.is_client_error() || status.is_server_error() {
+            return Some(LogReason::Error);
+        }
+        if duration >= Duration::from_millis(self.config.slow_request_ms) {
+            return Some(LogReason::Slow);
+        }
+        // The first success of every window is logged
+        let seen = self.successes.fetch_add(1, Ordering::Relaxed);
+        (seen % self.config.success_sample_rate == 0).then_some(LogReason::Sampled)
+    }
+
+    /// Log a completed request if it is sampled
+    pub fn log(&self, context: &RequestContext, status: StatusCode, duration: Duration) {
+        if !self.config.enabled {
+            return;
+        }
+        let Some(reason) = self.reason(status, duration) else {
+            return;
+        };
+        let duration_ms = duration.as_millis() as u64;
+        let client_profile = context.client_profile.as_ref().map_or("", |profile| profile.id.as_str());
+        let tenant = context.tenant.as_ref().map_or("", |tenant| tenant.name.as_str());
+        if reason == LogReason::Sampled {
+            info!(
+                request_id = %context.request_id,
+                method = %context.method,
+                client_ip = %context.client_ip,
+                client_profile,
+                tenant,
+                status = status.as_u16(),
+                duration_ms,
+                sample_rate = self.config.success_sample_rate,
+                "RPC request completed"
+            );
+        } else {
+            warn!(
+                request_id = %context.request_id,
+                method = %context.method,
+                client_ip = %context.client_ip,
+                client_profile,
+                tenant,
+                status = status.as_u16(),
+                duration_ms,
+                reason = reason.as_str(),
+                "RPC request completed"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampler(success_sample_rate: u64) -> RequestLogSampler {
+        RequestLogSampler::new(RequestLogSamplingConfig {
+            enabled: true,
+            success_sample_rate,
+            slow_request_ms: 500,
+        })
+    }
+
+    #[test]
+    fn test_errors_and_slow_requests_always_logged() {
+        let sampler = sampler(1000);
+        let fast = Duration::from_millis(5);
+        assert_eq!(sampler.reason(StatusCode::BAD_REQUEST, fast), Some(LogReason::Error));
+        assert_eq!(sampler.reason(StatusCode::SERVICE_UNAVAILABLE, fast), Some(LogReason::Error));
+        assert_eq!(sampler.reason(StatusCode::OK, Duration::from_millis(750)), Some(LogReason::Slow));
+    }
+
+    #[test]
+    fn test_successes_sampled_one_in_n() {
+        let one_in_ten = sampler(10);
+        let logged = (0..100)
+            .filter(|_| one_in_ten.reason(StatusCode::OK, Duration::from_millis(5)).is_some())
+            .count();
+        assert_eq!(logged, 10);
+
+        let everything = sampler(1);
+        assert!((0..5).all(|_| everything.reason(StatusCode::OK, Duration::ZERO) == Some(LogReason::Sampled)));
+    }
+}
@@ -0,0 +1,131 @@
+//! Signed JSON-RPC responses
+//!
+//! Oracles and other consumers relaying daemon data need to prove it came
+//! from this proxy. With `[response_signing]` enabled, successful responses to
+//! the configured methods, and to requests sending `X-Verus-Sign: true` when
+//! `allow_header` is on, carry a detached Ed25519 signature. The body is sent
+//! in the canonical form of [`canonical_json`](super::canonical_json) and the
+//! signature covers `"{timestamp}.{body}"`, so a consumer that re-encodes the
+//! JSON can canonicalize it again before verifying.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use ed25519_dalek::{Signer, SigningKey};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::app_config::ResponseSigningConfig;
+use crate::middleware::canonical_json::canonical_json;
+use crate::middleware::security_headers::{add_security_headers_to_response, SecurityHeadersMiddleware};
+
+/// Request header asking for a signed response
+pub const SIGN_REQUEST_HEADER: &str = "x-verus-sign";
+
+/// Header carrying the hex Ed25519 signature
+pub const SIGNATURE_HEADER: &str = "x-verus-response-signature";
+
+/// Header carrying the Unix timestamp covered by the signature
+pub const TIMESTAMP_HEADER: &str = "x-verus-response-timestamp";
+
+/// Header carrying the hex public key, for consumers to compare with the one they pinned
+pub const KEY_HEADER: &str = "x-verus-response-key";
+
+/// Signs responses with the configured key
+pub struct ResponseSigner {
+    key: SigningKey,
+    public_key: String,
+    methods: HashSet<String>,
+    allow_header: bool,
+}
+
+impl std::fmt::Debug for ResponseSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseSigner").field("public_key", &self.public_key).finish_non_exhaustive()
+    }
+}
+
+impl ResponseSigner {
+    /// Signer for `[response_signing]`; `None` when disabled or the key is not a 32-byte hex seed
+    pub fn new(config: &ResponseSigningConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let seed: [u8; 32] = hex::decode(config.signing_key.as_deref()?.trim()).ok()?.try_into().ok()?;
+        let key = SigningKey::from_bytes(&seed);
+        Some(Self {
+            public_key: hex::encode(key.verifying_key().to_bytes()),
+            key,
+            methods: config.methods.iter().cloned().collect(),
+            allow_header: config.allow_header,
+        })
+    }
+
+    /// Hex public key consumers verify signatures with
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Whether the response to `method` is signed, given the request's `X-Verus-Sign` header
+    pub fn applies(&self, method: &str, sign_header: Option<&str>) -> bool {
+        self.methods.contains(method)
+            || (self.allow_header && sign_header.is_some_and(|value| value.eq_ignore_ascii_case("true") || value == "1"))
+    }
+
+    /// Hex signature over `"{timestamp}.{body}"`
+    pub fn sign(&self, timestamp: i64, body: &[u8]) -> String {
+        let mut message = format!("{}.", timestamp).into_bytes();
+        message.extend_from_slice(body);
+        hex::encode(self.key.sign(&message).to_bytes())
+    }
+
+    /// Canonical JSON reply carrying the signature headers and the security headers
+    pub fn signed_reply<T: Serialize>(&self, data: &T, security: &SecurityHeadersMiddleware) -> Box<dyn warp::Reply> {
+        let body = serde_json::to_value(data).map(|value| canonical_json(&value)).unwrap_or_default();
+        let timestamp = Utc::now().timestamp();
+        let signature = self.sign(timestamp, &body);
+        let reply = warp::reply::with_header(body, "content-type", "application/json");
+        let reply = warp::reply::with_header(reply, SIGNATURE_HEADER, signature);
+        let reply = warp::reply::with_header(reply, TIMESTAMP_HEADER, timestamp.to_string());
+        let reply = warp::reply::with_header(reply, KEY_HEADER, self.public_key.clone());
+        add_security_headers_to_response(reply, security)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier};
+    use serde_json::json;
+
+    fn signer(methods: Vec<String>, allow_header: bool) -> ResponseSigner {
+        ResponseSigner::new(&ResponseSigningConfig {
+            enabled: true,
+            signing_key: Some("11".repeat(32)),
+            methods,
+            allow_header,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_signature_verifies_with_public_key() {
+        let by_method = signer(vec!["getblock".to_string()], false);
+        assert!(by_method.applies("getblock", None));
+        assert!(!by_method.applies("getinfo", Some("true")));
+        assert!(signer(Vec::new(), true).applies("getinfo", Some("true")));
+
+        let body = canonical_json(&json!({ "result": 42, "id": 1 }));
+        let signature = by_method.sign(1_700_000_000, &body);
+        let key = ed25519_dalek::VerifyingKey::from_bytes(&hex::decode(by_method.public_key()).unwrap().try_into().unwrap()).unwrap();
+        let signature = Signature::from_bytes(&hex::decode(signature).unwrap().try_into().unwrap());
+        let mut message = b"1700000000.".to_vec();
+        message.extend_from_slice(&body);
+        assert!(key.verify(&message, &signature).is_ok());
+        message[0] = b'2';
+        assert!(key.verify(&message, &signature).is_err());
+
+        let disabled = ResponseSigningConfig { enabled: false, ..ResponseSigningConfig::default() };
+        assert!(ResponseSigner::new(&disabled).is_none());
+    }
+}
@@ -26,29 +26,45 @@ impl SecurityHeadersMiddleware {
             return headers;
         }
 
+        let settings = &self.config.security_headers;
+
         // Content Security Policy
-        headers.insert(
-            "Content-Security-Policy".to_string(),
-            "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data: https:; font-src 'self' https:; connect-src 'self'; frame-ancestors 'none';".to_string(),
-        );
+        if let Some((name, value)) = self.content_security_policy() {
+            headers.insert(name.to_string(), value);
+        }
 
         // X-Content-Type-Options
         headers.insert("X-Content-Type-Options".to_string(), "nosniff".to_string());
 
         // X-Frame-Options
-        headers.insert("X-Frame-Options".to_string(), "DENY".to_string());
+        if !settings.x_frame_options.is_empty() {
+            headers.insert("X-Frame-Options".to_string(), settings.x_frame_options.clone());
+        }
 
         // X-XSS-Protection
         headers.insert("X-XSS-Protection".to_string(), "1; mode=block".to_string());
 
         // Referrer Policy
-        headers.insert("Referrer-Policy".to_string(), "strict-origin-when-cross-origin".to_string());
+        if !settings.referrer_policy.is_empty() {
+            headers.insert("Referrer-Policy".to_string(), settings.referrer_policy.clone());
+        }
 
         // Permissions Policy
-        headers.insert(
-            "Permissions-Policy".to_string(),
-            "geolocation=(), microphone=(), camera=(), payment=(), usb=(), magnetometer=(), gyroscope=(), accelerometer=()".to_string(),
-        );
+        if !settings.permissions_policy.is_empty() {
+            headers.insert("Permissions-Policy".to_string(), settings.permissions_policy.clone());
+        }
+
+        // Strict Transport Security
+        if settings.hsts_enabled {
+            let mut hsts = format!("max-age={}", settings.hsts_max_age_seconds);
+            if settings.hsts_include_subdomains {
+                hsts.push_str("; includeSubDomains");
+            }
+            if settings.hsts_preload {
+                hsts.push_str("; preload");
+            }
+            headers.insert("Strict-Transport-Security".to_string(), hsts);
+        }
 
         // Cache Control Headers
         headers.insert("Cache-Control".to_string(), "no-store, no-cache, must-revalidate, proxy-revalidate".to_string());
@@ -69,6 +85,25 @@ impl SecurityHeadersMiddleware {
 
         headers
     }
+
+    /// CSP header name (enforcing or report-only) and value with any `report-uri` appended
+    fn content_security_policy(&self) -> Option<(&'static str, String)> {
+        let settings = &self.config.security_headers;
+        let policy = settings.content_security_policy.trim().trim_end_matches(';').trim();
+        if policy.is_empty() {
+            return None;
+        }
+        let mut value = format!("{};", policy);
+        if let Some(uri) = settings.csp_report_uri.as_deref().filter(|u| !u.is_empty()) {
+            value.push_str(&format!(" report-uri {};", uri));
+        }
+        let name = if settings.csp_report_only {
+            "Content-Security-Policy-Report-Only"
+        } else {
+            "Content-Security-Policy"
+        };
+        Some((name, value))
+    }
 }
 
 /// Add security headers to a response using warp's with_header approach
@@ -259,4 +294,25 @@ mod tests {
         assert!(csp.contains("connect-src 'self'"));
         assert!(csp.contains("frame-ancestors 'none'"));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_configured_header_values() {
+        let mut config = AppConfig::default();
+        config.security_headers.csp_report_only = true;
+        config.security_headers.csp_report_uri = Some("https://csp.example.com/report".to_string());
+        config.security_headers.hsts_enabled = true;
+        config.security_headers.hsts_preload = true;
+        config.security_headers.x_frame_options = "SAMEORIGIN".to_string();
+        config.security_headers.referrer_policy = "no-referrer".to_string();
+        config.security_headers.permissions_policy = String::new();
+
+        let headers = SecurityHeadersMiddleware::new(config).get_security_headers();
+        assert!(!headers.contains_key("Content-Security-Policy"));
+        let csp = headers.get("Content-Security-Policy-Report-Only").unwrap();
+        assert!(csp.ends_with("frame-ancestors 'none'; report-uri https://csp.example.com/report;"));
+        assert_eq!(headers.get("Strict-Transport-Security").unwrap(), "max-age=31536000; includeSubDomains; preload");
+        assert_eq!(headers.get("X-Frame-Options").unwrap(), "SAMEORIGIN");
+        assert_eq!(headers.get("Referrer-Policy").unwrap(), "no-referrer");
+        assert!(!headers.contains_key("Permissions-Policy"));
+    }
+}
@@ -0,0 +1,165 @@
+//! Cron schedules
+//!
+//! Five-field cron expressions (`minute hour day-of-month month day-of-week`),
+//! evaluated in UTC. Each field accepts `*`, numbers, ranges (`1-5`), steps
+//! (`*/15`, `0-30/10`) and comma-separated lists; day-of-week runs 0-7 with
+//! both 0 and 7 meaning Sunday. As in classic cron, when both day fields are
+//! restricted a day matches if either does.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+
+use crate::shared::error::AppError;
+
+/// How far ahead `next_after` looks before giving up on an impossible schedule (e.g. `0 0 30 2 *`)
+const SEARCH_YEARS: i32 = 5;
+
+/// A parsed five-field cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    /// First matching minute strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start.with_year(start.year() + SEARCH_YEARS).unwrap_or(start + Duration::days(366 * SEARCH_YEARS as i64));
+        let mut t = start;
+        while t < limit {
+            if !contains(self.months, t.month()) {
+                let (year, month) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.day_matches(t) {
+                t = (t + Duration::days(1)).with_hour(0)?.with_minute(0)?;
+            } else if !contains(self.hours, t.hour()) {
+                t = (t + Duration::hours(1)).with_minute(0)?;
+            } else if !contains(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, t: DateTime<Utc>) -> bool {
+        let day = contains(self.days, t.day());
+        let weekday = contains(self.weekdays, t.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = AppError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(AppError::Validation(format!("cron expression needs 5 fields: {}", expression)));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        // 7 is Sunday as well
+        if contains(weekdays, 7) {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            expression: expression.to_string(),
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            days_restricted: day != "*",
+            weekdays_restricted: weekday != "*",
+        })
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+fn contains(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Bit set of the values a field matches
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, AppError> {
+    let invalid = || AppError::Validation(format!("invalid cron field {:?}, values run {}-{}", field, min, max));
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0).ok_or_else(invalid)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (start.parse().map_err(|_| invalid())?, end.parse().map_err(|_| invalid())?),
+                // `5/10` runs from 5 to the end of the field
+                None => {
+                    let start = range.parse().map_err(|_| invalid())?;
+                    (start, if part.contains('/') { max } else { start })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn test_next_after_steps_through_fields() {
+        let daily: CronSchedule = "0 0 * * *".parse().unwrap();
+        assert_eq!(daily.next_after(at(2026, 10, 16, 12, 30)), Some(at(2026, 10, 17, 0, 0)));
+        assert_eq!(daily.next_after(at(2026, 12, 31, 23, 59)), Some(at(2027, 1, 1, 0, 0)));
+
+        let quarter_hour: CronSchedule = "*/15 9-17 * * 1-5".parse().unwrap();
+        // Friday 17:50 -> Monday 09:00
+        assert_eq!(quarter_hour.next_after(at(2026, 10, 16, 17, 50)), Some(at(2026, 10, 19, 9, 0)));
+        assert_eq!(quarter_hour.next_after(at(2026, 10, 19, 9, 0)), Some(at(2026, 10, 19, 9, 15)));
+
+        // Day of month or Sunday (7)
+        let either: CronSchedule = "30 6 1 * 7".parse().unwrap();
+        assert_eq!(either.next_after(at(2026, 10, 16, 0, 0)), Some(at(2026, 10, 18, 6, 30)));
+        assert!("0 0 30 2 *".parse::<CronSchedule>().unwrap().next_after(at(2026, 1, 1, 0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_expressions() {
+        for expression in ["", "* * * *", "60 * * * *", "* 24 * * *", "* * 0 * *", "*/0 * * * *", "5-1 * * * *", "a * * * *"] {
+            assert!(expression.parse::<CronSchedule>().is_err(), "{}", expression);
+        }
+        assert_eq!("0,30 */6 1-15 1,7 0".parse::<CronSchedule>().unwrap().to_string(), "0,30 */6 1-15 1,7 0");
+    }
+}
@@ -43,6 +43,15 @@ pub enum AppError {
 
     #[error("Request too large: {size} bytes exceeds limit of {limit} bytes")]
     RequestTooLarge { size: usize, limit: usize },
+
+    #[error("Service in maintenance: {0}")]
+    Maintenance(String),
+
+    #[error("Upstream daemon is initializing, retry shortly")]
+    UpstreamInitializing,
+
+    #[error("Client banned until {until}: {reason}")]
+    Banned { until: String, reason: String },
 }
 
 impl AppError {
@@ -70,6 +79,9 @@ impl AppError {
             AppError::RateLimit => (-429, "Rate limit exceeded".to_string()),
             AppError::RequestTooLarge { size, limit } => (-413, format!("Request too large: {} bytes exceeds limit of {} bytes", size, limit)),
             AppError::Authentication(_) => (-401, "Authentication failed".to_string()),
+            AppError::Maintenance(msg) => (-503, msg.clone()),
+            AppError::UpstreamInitializing => (-503, self.to_string()),
+            AppError::Banned { .. } => (-403, self.to_string()),
             _ => (-32603, "Internal error".to_string()),
         };
 
@@ -81,6 +93,51 @@ impl AppError {
         })
     }
 
+    /// Message catalog key for this error
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            AppError::Config(_) => "config_error",
+            AppError::Rpc(_) => "rpc_error",
+            AppError::Http(_) => "http_error",
+            AppError::Json(_) => "parse_error",
+            AppError::Validation(_) => "validation_error",
+            AppError::Security(_) => "security_error",
+            AppError::RateLimit => "rate_limit_exceeded",
+            AppError::MethodNotAllowed { .. } => "method_not_allowed",
+            AppError::InvalidParameters { .. } => "invalid_parameters",
+            AppError::Internal(_) => "internal_error",
+            AppError::Authentication(_) => "authentication_failed",
+            AppError::RequestTooLarge { .. } => "request_too_large",
+            AppError::Maintenance(_) => "maintenance",
+            AppError::UpstreamInitializing => "upstream_initializing",
+            AppError::Banned { .. } => "client_banned",
+        }
+    }
+
+    /// Named arguments available to message catalog templates
+    pub fn message_args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            AppError::Config(detail)
+            | AppError::Rpc(detail)
+            | AppError::Http(detail)
+            | AppError::Json(detail)
+            | AppError::Validation(detail)
+            | AppError::Security(detail)
+            | AppError::Internal(detail)
+            | AppError::Authentication(detail)
+            | AppError::Maintenance(detail) => vec![("detail", detail.clone())],
+            AppError::RateLimit | AppError::UpstreamInitializing => vec![],
+            AppError::MethodNotAllowed { method } => vec![("method", method.clone())],
+            AppError::InvalidParameters { method, reason } => {
+                vec![("method", method.clone()), ("reason", reason.clone())]
+            }
+            AppError::RequestTooLarge { size, limit } => {
+                vec![("size", size.to_string()), ("limit", limit.to_string())]
+            }
+            AppError::Banned { until, reason } => vec![("until", until.clone()), ("reason", reason.clone())],
+        }
+    }
+
     /// Get HTTP status code for this error
     pub fn http_status_code(&self) -> warp::http::StatusCode {
         match self {
@@ -90,6 +147,8 @@ impl AppError {
             AppError::RateLimit => warp::http::StatusCode::TOO_MANY_REQUESTS,
             AppError::RequestTooLarge { .. } => warp::http::StatusCode::PAYLOAD_TOO_LARGE,
             AppError::Authentication(_) => warp::http::StatusCode::UNAUTHORIZED,
+            AppError::Maintenance(_) | AppError::UpstreamInitializing => warp::http::StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Banned { .. } => warp::http::StatusCode::FORBIDDEN,
             AppError::Rpc(_) => warp::http::StatusCode::INTERNAL_SERVER_ERROR,
             _ => warp::http::StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -0,0 +1,204 @@
+//! Error message localization
+//!
+//! This module provides an optional message catalog used to return error
+//! messages in the locale requested via `Accept-Language`. Catalogs are
+//! provided by a pluggable `CatalogLoader`; when no catalog is loaded or a
+//! message has no translation, the original English message is used.
+
+use crate::shared::error::{AppError, AppResult};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Messages keyed by locale, then by message key
+pub type CatalogMessages = HashMap<String, HashMap<String, String>>;
+
+/// Source of localized message templates
+pub trait CatalogLoader: Send + Sync {
+    /// Load all available locales and their message templates
+    fn load(&self) -> AppResult<CatalogMessages>;
+}
+
+/// Loads `<locale>.json` files (flat key -> template objects) from a directory
+pub struct DirectoryCatalogLoader {
+    dir: PathBuf,
+}
+
+impl DirectoryCatalogLoader {
+    /// Create a loader for the given directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl CatalogLoader for DirectoryCatalogLoader {
+    fn load(&self) -> AppResult<CatalogMessages> {
+        let entries = std::fs::read_dir(&self.dir).map_err(|e| {
+            AppError::Config(format!("Failed to read message catalog directory {}: {}", self.dir.display(), e))
+        })?;
+
+        let mut messages = CatalogMessages::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let locale = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(stem) => normalize_locale(stem),
+                None => continue,
+            };
+            let contents = std::fs::read_to_string(&path).map_err(|e| {
+                AppError::Config(format!("Failed to read message catalog {}: {}", path.display(), e))
+            })?;
+            let templates: HashMap<String, String> = serde_json::from_str(&contents).map_err(|e| {
+                AppError::Config(format!("Invalid message catalog {}: {}", path.display(), e))
+            })?;
+            messages.insert(locale, templates);
+        }
+
+        Ok(messages)
+    }
+}
+
+/// Localized message catalog
+#[derive(Debug, Clone, Default)]
+pub struct MessageCatalog {
+    default_locale: String,
+    messages: CatalogMessages,
+}
+
+impl MessageCatalog {
+    /// Build a catalog from a loader
+    pub fn from_loader(loader: &dyn CatalogLoader, default_locale: &str) -> AppResult<Self> {
+        Ok(Self::new(loader.load()?, default_locale))
+    }
+
+    /// Build a catalog from already loaded messages
+    pub fn new(messages: CatalogMessages, default_locale: &str) -> Self {
+        Self {
+            default_locale: normalize_locale(default_locale),
+            messages,
+        }
+    }
+
+    /// Locales available in this catalog
+    pub fn locales(&self) -> Vec<&str> {
+        self.messages.keys().map(|l| l.as_str()).collect()
+    }
+
+    /// Pick the best available locale for an `Accept-Language` header value
+    pub fn negotiate(&self, accept_language: &str) -> Option<String> {
+        let mut ranges: Vec<(String, f32)> = accept_language
+            .split(',')
+            .filter_map(|part| {
+                let mut pieces = part.trim().split(';');
+                let tag = normalize_locale(pieces.next()?.trim());
+                if tag.is_empty() {
+                    return None;
+                }
+                let quality = pieces
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                Some((tag, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect();
+        ranges.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        for (tag, _) in ranges {
+            if tag == "*" {
+                return Some(self.default_locale.clone());
+            }
+            if self.messages.contains_key(&tag) {
+                return Some(tag);
+            }
+            let primary = tag.split('-').next().unwrap_or(&tag);
+            if self.messages.contains_key(primary) {
+                return Some(primary.to_string());
+            }
+        }
+
+        None
+    }
+
+    /// Render a message template for a locale, substituting `{name}` arguments
+    pub fn translate(&self, locale: &str, key: &str, args: &[(&str, String)]) -> Option<String> {
+        let template = self.messages.get(locale)?.get(key)?;
+        let mut message = template.clone();
+        for (name, value) in args {
+            message = message.replace(&format!("{{{}}}", name), value);
+        }
+        Some(message)
+    }
+
+    /// Localize an application error, if a translation exists
+    pub fn localize_error(&self, locale: &str, error: &AppError) -> Option<String> {
+        self.translate(locale, error.message_key(), &error.message_args())
+    }
+}
+
+/// Localize a message by key, falling back to the provided English text
+pub fn localize(
+    catalog: Option<&MessageCatalog>,
+    locale: Option<&str>,
+    key: &str,
+    args: &[(&str, String)],
+    fallback: &str,
+) -> String {
+    locale
+        .and_then(|l| catalog.and_then(|c| c.translate(l, key, args)))
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+/// Localize an application error, falling back to its English message
+pub fn localize_error(catalog: Option<&MessageCatalog>, locale: Option<&str>, error: &AppError) -> String {
+    localize(catalog, locale, error.message_key(), &error.message_args(), &error.to_string())
+}
+
+fn normalize_locale(tag: &str) -> String {
+    tag.trim().replace('_', "-").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_catalog() -> MessageCatalog {
+        let mut messages = CatalogMessages::new();
+        messages.insert(
+            "es".to_string(),
+            HashMap::from([
+                ("rate_limit_exceeded".to_string(), "Límite de solicitudes excedido".to_string()),
+                ("method_not_allowed".to_string(), "Método no permitido: {method}".to_string()),
+            ]),
+        );
+        messages.insert("pt-br".to_string(), HashMap::new());
+        MessageCatalog::new(messages, "en")
+    }
+
+    #[test]
+    fn test_negotiate_prefers_highest_quality() {
+        let catalog = create_test_catalog();
+        assert_eq!(catalog.negotiate("fr;q=0.9, es;q=0.8, en;q=0.1"), Some("es".to_string()));
+        assert_eq!(catalog.negotiate("es-MX, en;q=0.5"), Some("es".to_string()));
+        assert_eq!(catalog.negotiate("pt-BR"), Some("pt-br".to_string()));
+        assert_eq!(catalog.negotiate("de, fr"), None);
+        assert_eq!(catalog.negotiate("es;q=0"), None);
+    }
+
+    #[test]
+    fn test_localize_error_with_args() {
+        let catalog = create_test_catalog();
+        let error = AppError::MethodNotAllowed { method: "stop".to_string() };
+        assert_eq!(catalog.localize_error("es", &error), Some("Método no permitido: stop".to_string()));
+        assert_eq!(catalog.localize_error("pt-br", &error), None);
+    }
+
+    #[test]
+    fn test_localize_falls_back_to_english() {
+        let error = AppError::RateLimit;
+        assert_eq!(localize_error(None, Some("es"), &error), "Rate limit exceeded");
+        assert_eq!(localize_error(Some(&create_test_catalog()), None, &error), "Rate limit exceeded");
+        assert_eq!(localize_error(Some(&create_test_catalog()), Some("es"), &error), "Límite de solicitudes excedido");
+    }
+}
@@ -3,12 +3,21 @@
 //! This module contains shared utilities, error handling, logging,
 //! metrics, and validation that are used across the application.
 
+pub mod cron;
 pub mod error;
+pub mod i18n;
 pub mod logging;
 pub mod metrics;
+pub mod network;
+pub mod pagination;
+pub mod security;
 pub mod validation;
 
+pub use cron::CronSchedule;
 pub use error::{AppError, AppResult};
+pub use i18n::{CatalogLoader, DirectoryCatalogLoader, MessageCatalog};
 pub use logging::LoggingUtils;
 pub use metrics::MetricsUtils;
+pub use network::IpCidr;
+pub use pagination::{ListQuery, ListSpec, Page};
 pub use validation::ValidationUtils; 
\ No newline at end of file
@@ -0,0 +1,89 @@
+//! IP network matching for address-based policies
+
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// IP network in CIDR notation (`10.0.0.0/8`, `fd00::/8`); a bare address is a single host
+///
+/// IPv4 networks also match IPv4-mapped IPv6 addresses (`::ffff:10.0.0.1`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpCidr {
+    /// Whether `ip` lies in this network
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => prefix_matches(u32::from(network).into(), u32::from(ip).into(), self.prefix, 32),
+            (IpAddr::V6(network), IpAddr::V6(ip)) => prefix_matches(u128::from(network), u128::from(ip), self.prefix, 128),
+            (IpAddr::V4(_), IpAddr::V6(ip)) => ip.to_ipv4_mapped().is_some_and(|ip| self.contains(IpAddr::V4(ip))),
+            (IpAddr::V6(_), IpAddr::V4(_)) => false,
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid CIDR: {}", value);
+        let (address, prefix) = match value.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (value, None),
+        };
+        let network: IpAddr = address.trim().parse().map_err(|_| invalid())?;
+        let bits = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.trim().parse::<u8>().ok().filter(|prefix| *prefix <= bits).ok_or_else(invalid)?,
+            None => bits,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+fn prefix_matches(network: u128, ip: u128, prefix: u8, bits: u8) -> bool {
+    if prefix == 0 {
+        return true;
+    }
+    let shift = u32::from(bits - prefix);
+    network >> shift == ip >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cidr_matching() {
+        let private: IpCidr = "10.0.0.0/8".parse().unwrap();
+        assert!(private.contains(ip("10.200.1.2")));
+        assert!(!private.contains(ip("11.0.0.1")));
+        assert!(private.contains(ip("::ffff:10.0.0.1")));
+
+        let host: IpCidr = "192.0.2.7".parse().unwrap();
+        assert_eq!(host.to_string(), "192.0.2.7/32");
+        assert!(host.contains(ip("192.0.2.7")));
+        assert!(!host.contains(ip("192.0.2.8")));
+
+        let ula: IpCidr = "fd00::/8".parse().unwrap();
+        assert!(ula.contains(ip("fd12::1")));
+        assert!(!ula.contains(ip("10.0.0.1")));
+        assert!("0.0.0.0/0".parse::<IpCidr>().unwrap().contains(ip("203.0.113.9")));
+
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("internal".parse::<IpCidr>().is_err());
+    }
+}
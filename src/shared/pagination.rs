@@ -0,0 +1,219 @@
+//! Pagination, sorting and filtering for REST list endpoints
+//!
+//! List endpoints share one set of query parameters:
+//! - `limit`: items per page
+//! - `offset`: items to skip, or `cursor`: `next_cursor` of the previous page (not both)
+//! - `sort`: a sort field, prefixed with `-` for descending order (`sort=-height`)
+//! - field filters declared by the endpoint, such as `min_height=100`
+//!
+//! and answer with a [`Page`] carrying `items`, `total` and `next_cursor`.
+//! Lists are series kept in ascending order of a sequence key (a height or a
+//! position in an append-only log). A cursor is the key of the last item
+//! returned, so it stays valid as new items are appended.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::shared::error::{AppError, AppResult};
+
+/// Query parameters a list endpoint accepts beyond `limit`, `offset`, `cursor` and `sort`
+#[derive(Debug, Clone, Copy)]
+pub struct ListSpec {
+    /// Fields accepted by `sort`; all follow the series order. The first is the default.
+    pub sort_fields: &'static [&'static str],
+    /// Default sort direction
+    pub descending: bool,
+    /// Field filters
+    pub filters: &'static [&'static str],
+}
+
+/// Where a page starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Position {
+    Start,
+    Offset(usize),
+    Cursor(String),
+}
+
+/// Parsed list query
+#[derive(Debug, Clone)]
+pub struct ListQuery {
+    pub limit: Option<usize>,
+    pub position: Position,
+    pub sort: String,
+    pub descending: bool,
+    filters: HashMap<String, String>,
+}
+
+/// One page of a list
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Items matching the filters, across all pages
+    pub total: usize,
+    /// Pass as `cursor` to fetch the next page; `null` on the last page
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page { items: self.items.into_iter().map(f).collect(), total: self.total, next_cursor: self.next_cursor }
+    }
+}
+
+impl ListQuery {
+    /// Parse query parameters against an endpoint's spec
+    pub fn parse(params: &HashMap<String, String>, spec: &ListSpec) -> AppResult<Self> {
+        let mut query = Self {
+            limit: None,
+            position: Position::Start,
+            sort: spec.sort_fields.first().copied().unwrap_or_default().to_string(),
+            descending: spec.descending,
+            filters: HashMap::new(),
+        };
+        for (name, value) in params {
+            match name.as_str() {
+                "limit" => {
+                    let limit = value.parse::<usize>().ok().filter(|limit| *limit > 0);
+                    query.limit = Some(limit.ok_or_else(|| AppError::Validation(format!("invalid limit: {}", value)))?);
+                }
+                "offset" => {
+                    let offset = value
+                        .parse::<usize>()
+                        .map_err(|_| AppError::Validation(format!("invalid offset: {}", value)))?;
+                    query.position = Position::Offset(offset);
+                }
+                "cursor" => query.position = Position::Cursor(value.clone()),
+                "sort" => {
+                    let (field, descending) = match value.strip_prefix('-') {
+                        Some(field) => (field, true),
+                        None => (value.as_str(), false),
+                    };
+                    if !spec.sort_fields.contains(&field) {
+                        return Err(AppError::Validation(format!(
+                            "cannot sort by {}; supported: {}",
+                            field,
+                            spec.sort_fields.join(", ")
+                        )));
+                    }
+                    query.sort = field.to_string();
+                    query.descending = descending;
+                }
+                _ if spec.filters.contains(&name.as_str()) => {
+                    query.filters.insert(name.clone(), value.clone());
+                }
+                _ => {
+                    return Err(AppError::Validation(format!(
+                        "unknown query parameter: {}; filters: {}",
+                        name,
+                        spec.filters.join(", ")
+                    )));
+                }
+            }
+        }
+        if params.contains_key("offset") && params.contains_key("cursor") {
+            return Err(AppError::Validation("offset and cursor cannot be combined".to_string()));
+        }
+        Ok(query)
+    }
+
+    /// Requested page size, or `default`, capped at `max`
+    pub fn limit(&self, default: usize, max: usize) -> usize {
+        self.limit.unwrap_or(default).min(max).max(1)
+    }
+
+    /// Value of a field filter, if given
+    pub fn filter<V: FromStr>(&self, name: &str) -> AppResult<Option<V>> {
+        self.filters
+            .get(name)
+            .map(|value| value.parse().map_err(|_| AppError::Validation(format!("invalid {}: {}", name, value))))
+            .transpose()
+    }
+
+    /// Page through filtered `items`, held in ascending order of `seq`
+    ///
+    /// `seq` must strictly increase along `items`; it is the cursor.
+    pub fn page<T>(&self, mut items: Vec<T>, limit: usize, seq: impl Fn(&T) -> u64) -> AppResult<Page<T>> {
+        let total = items.len();
+        let start = match &self.position {
+            Position::Start => 0,
+            Position::Offset(offset) => *offset,
+            Position::Cursor(cursor) => {
+                let after = cursor
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|key| items.binary_search_by_key(&key, &seq).ok())
+                    .ok_or_else(|| AppError::Validation(format!("invalid cursor: {}", cursor)))?;
+                if self.descending { total - after } else { after + 1 }
+            }
+        };
+        if self.descending {
+            items.reverse();
+        }
+        let start = start.min(total);
+        let end = start.saturating_add(limit).min(total);
+        let next_cursor = (end < total).then(|| seq(&items[end - 1]).to_string());
+        Ok(Page { items: items.drain(start..end).collect(), total, next_cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: ListSpec = ListSpec { sort_fields: &["height"], descending: true, filters: &["min_height"] };
+
+    fn query(pairs: &[(&str, &str)]) -> AppResult<ListQuery> {
+        let params = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        ListQuery::parse(&params, &SPEC)
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let parsed = query(&[("limit", "5"), ("sort", "height"), ("min_height", "10")]).unwrap();
+        assert_eq!(parsed.limit(100, 50), 5);
+        assert!(!parsed.descending);
+        assert_eq!(parsed.filter::<u64>("min_height").unwrap(), Some(10));
+        assert_eq!(parsed.filter::<u64>("max_height").unwrap(), None);
+
+        assert!(query(&[("limit", "0")]).is_err());
+        assert!(query(&[("sort", "-time")]).is_err());
+        assert!(query(&[("colour", "red")]).is_err());
+        assert!(query(&[("offset", "1"), ("cursor", "3")]).is_err());
+        assert!(query(&[("min_height", "x")]).unwrap().filter::<u64>("min_height").is_err());
+    }
+
+    #[test]
+    fn test_cursor_pages_follow_sort_order() {
+        let items = vec![10u64, 20, 30, 40, 50];
+        let newest = query(&[]).unwrap();
+        let first = newest.page(items.clone(), 2, |h| *h).unwrap();
+        assert_eq!(first.items, vec![50, 40]);
+        assert_eq!(first.total, 5);
+        assert_eq!(first.next_cursor.as_deref(), Some("40"));
+
+        let cursor = first.next_cursor.unwrap();
+        let second = query(&[("cursor", cursor.as_str())]).unwrap().page(items.clone(), 3, |h| *h).unwrap();
+        assert_eq!(second.items, vec![30, 20, 10]);
+        assert_eq!(second.next_cursor, None);
+
+        let oldest = query(&[("sort", "height"), ("cursor", "20")]).unwrap().page(items.clone(), 2, |h| *h).unwrap();
+        assert_eq!(oldest.items, vec![30, 40]);
+        assert_eq!(oldest.next_cursor.as_deref(), Some("40"));
+
+        assert!(query(&[("cursor", "25")]).unwrap().page(items, 2, |h| *h).is_err());
+    }
+
+    #[test]
+    fn test_offset_pages() {
+        let items = vec![1u64, 2, 3];
+        let page = query(&[("offset", "1")]).unwrap().page(items.clone(), 1, |h| *h).unwrap();
+        assert_eq!(page.items, vec![2]);
+        assert_eq!(page.next_cursor.as_deref(), Some("2"));
+        let past_end = query(&[("offset", "9")]).unwrap().page(items, 1, |h| *h).unwrap();
+        assert!(past_end.items.is_empty());
+        assert_eq!(past_end.total, 3);
+    }
+}
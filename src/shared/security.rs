@@ -0,0 +1,212 @@
+//! Constant-time credential handling
+//!
+//! Comparisons of secrets and client-supplied credentials (CSRF tokens, PoW
+//! solutions, configured keys), Ed25519 signature checks (partner and pool
+//! signatures) and HMAC signing (outgoing webhooks) go through this module,
+//! so an early-exit `==` cannot leak how many leading bytes matched. The
+//! `test_credential_comparisons_use_this_module` test scans the source tree
+//! to keep it that way.
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+/// Compare two byte strings in time independent of their contents
+///
+/// Only the lengths are compared early; they are not secret for any of the
+/// values handled here (fixed-size tokens, hashes and keys).
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(diff) == 0
+}
+
+/// [`constant_time_eq`] for strings
+pub fn constant_time_str_eq(a: &str, b: &str) -> bool {
+    constant_time_eq(a.as_bytes(), b.as_bytes())
+}
+
+/// Hex SHA-256 of an API key, the form `api_key_hashes` settings hold
+pub fn api_key_hash(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Lowercase configured `api_key_hashes` in place, warning about entries that can never match
+pub fn normalize_api_key_hashes(hashes: &mut [String], owner: &str) {
+    for hash in hashes {
+        *hash = hash.to_ascii_lowercase();
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            warn!("{} has an API key hash that is not hex SHA-256; it will never match", owner);
+        }
+    }
+}
+
+/// Whether `hash` is one of `expected`
+///
+/// Every entry is compared, so timing does not reveal which one matched.
+pub fn api_key_hash_matches(expected: &[String], hash: &str) -> bool {
+    expected.iter().fold(false, |found, candidate| constant_time_str_eq(candidate, hash) | found)
+}
+
+/// First of `entries` whose `hashes` include the hash of `key`
+pub fn find_by_api_key<'a, T>(
+    entries: impl IntoIterator<Item = &'a T>,
+    key: &str,
+    hashes: impl Fn(&T) -> &[String],
+) -> Option<&'a T> {
+    let hash = api_key_hash(key);
+    entries.into_iter().find(|entry| api_key_hash_matches(hashes(entry), &hash))
+}
+
+/// Why an Ed25519 signature was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureError {
+    /// Not 64 hex-encoded bytes
+    Malformed,
+    /// Well-formed but does not verify
+    Invalid,
+}
+
+/// Verify a hex-encoded Ed25519 signature over `message`
+pub fn verify_ed25519_hex(key: &VerifyingKey, message: &[u8], signature_hex: &str) -> Result<(), SignatureError> {
+    let bytes = hex::decode(signature_hex).map_err(|_| SignatureError::Malformed)?;
+    let bytes = <[u8; 64]>::try_from(bytes.as_slice()).map_err(|_| SignatureError::Malformed)?;
+    key.verify(message, &Signature::from_bytes(&bytes)).map_err(|_| SignatureError::Invalid)
+}
+
+/// Hex-encoded HMAC-SHA256 (RFC 2104) of `message` under `key`
+pub fn hmac_sha256_hex(key: &[u8], message: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::path::Path;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret-token", b"secret-token"));
+        assert!(!constant_time_eq(b"secret-token", b"secret-tokeN"));
+        assert!(!constant_time_eq(b"secret", b"secret-token"));
+        assert!(constant_time_str_eq("", ""));
+    }
+
+    #[test]
+    fn test_find_by_api_key() {
+        let mut hashes = vec![api_key_hash("first").to_uppercase(), "not-a-hash".to_string()];
+        normalize_api_key_hashes(&mut hashes, "Test entry");
+        assert_eq!(hashes[0], api_key_hash("first"));
+
+        let entries = [("a", hashes), ("b", vec![api_key_hash("second")])];
+        let found = |key| find_by_api_key(&entries, key, |(_, hashes)| hashes.as_slice()).map(|(name, _)| *name);
+        assert_eq!(found("first"), Some("a"));
+        assert_eq!(found("second"), Some("b"));
+        assert_eq!(found("third"), None);
+        assert!(!api_key_hash_matches(&[], &api_key_hash("first")));
+    }
+
+    #[test]
+    fn test_verify_ed25519_hex() {
+        let signing = SigningKey::from_bytes(&[7u8; 32]);
+        let signature = hex::encode(signing.sign(b"message").to_bytes());
+        let key = signing.verifying_key();
+        assert_eq!(verify_ed25519_hex(&key, b"message", &signature), Ok(()));
+        assert_eq!(verify_ed25519_hex(&key, b"tampered", &signature), Err(SignatureError::Invalid));
+        assert_eq!(verify_ed25519_hex(&key, b"message", "abcd"), Err(SignatureError::Malformed));
+        assert_eq!(verify_ed25519_hex(&key, b"message", "not hex"), Err(SignatureError::Malformed));
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231_vectors() {
+        assert_eq!(
+            hmac_sha256_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Keys longer than a block are hashed first
+        assert_eq!(
+            hmac_sha256_hex(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First"),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    /// Identifiers that name secrets or client-supplied credentials
+    const CREDENTIAL_NAMES: [&str; 8] = ["signature", "solution", "secret", "api_key", "csrf", "hmac", "password", "cookie"];
+
+    /// Conversions that may trail the value being compared
+    const CONVERSIONS: [&str; 7] = ["as_str", "as_bytes", "as_ref", "to_string", "clone", "trim", "as_deref"];
+
+    /// Value an operand of `==`/`!=` names, e.g. `solution` for `proof.solution.as_str()`
+    fn compared_value(operand: &str, from_end: bool) -> Option<String> {
+        let is_expr = |c: char| c.is_ascii_alphanumeric() || "_.:()&*".contains(c);
+        let operand = operand.trim();
+        let operand: String = if from_end {
+            let tail: Vec<char> = operand.chars().rev().take_while(|c| is_expr(*c)).collect();
+            tail.into_iter().rev().collect()
+        } else {
+            operand.chars().take_while(|c| is_expr(*c)).collect()
+        };
+        operand
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .rfind(|word| !word.is_empty() && !CONVERSIONS.contains(word))
+            .map(str::to_string)
+    }
+
+    /// Lint: production code must not compare credentials with `==`/`!=`, verify
+    /// signatures directly, or carry its own constant-time helper
+    #[test]
+    fn test_credential_comparisons_use_this_module() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut violations = Vec::new();
+        let mut pending = vec![root.clone()];
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(&dir).unwrap().flatten() {
+                let path = entry.path();
+                // src/tests is not compiled; this file is the one allowed place
+                if path.is_dir() {
+                    if path != root.join("tests") {
+                        pending.push(path);
+                    }
+                    continue;
+                }
+                if path.extension().is_none_or(|ext| ext != "rs") || path.ends_with("shared/security.rs") {
+                    continue;
+                }
+                let source = std::fs::read_to_string(&path).unwrap();
+                let production = source.split("#[cfg(test)]").next().unwrap_or_default();
+                for (number, line) in production.lines().enumerate() {
+                    let code = line.split("//").next().unwrap_or_default();
+                    let words: Vec<&str> = code.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).collect();
+                    let direct_verify = words.contains(&"Verifier") || code.contains("fn constant_time_eq");
+                    let credential_compare = ["==", "!="].iter().any(|op| {
+                        code.match_indices(op).any(|(at, _)| {
+                            [compared_value(&code[..at], true), compared_value(&code[at + 2..], false)]
+                                .into_iter()
+                                .flatten()
+                                .filter(|value| value.starts_with(|c: char| c.is_ascii_lowercase()))
+                                .any(|value| CREDENTIAL_NAMES.iter().any(|name| value.contains(name)))
+                        })
+                    });
+                    if direct_verify || credential_compare {
+                        violations.push(format!("{}:{}: {}", path.display(), number + 1, line.trim()));
+                    }
+                }
+            }
+        }
+        assert!(violations.is_empty(), "use shared::security for credentials:\n{}", violations.join("\n"));
+    }
+
+    #[test]
+    fn test_compared_value() {
+        assert_eq!(compared_value("if hash != proof.solution.as_str()", true).as_deref(), Some("solution"));
+        assert_eq!(compared_value(" proof.solution {", false).as_deref(), Some("solution"));
+        assert_eq!(compared_value(" CsrfMode::DoubleSubmit {", false).as_deref(), Some("DoubleSubmit"));
+        assert_eq!(compared_value("if signature_bytes.len()", true).as_deref(), Some("len"));
+    }
+}
@@ -101,6 +101,7 @@ pub mod fixtures {
             parameters: Some(params),
             id: Some(serde_json::json!(1)),
             client_info: test_client_info(),
+            upstream: None,
         }
     }
 
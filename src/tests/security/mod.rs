@@ -335,6 +335,7 @@ pub mod tests {
             requests_per_minute: 5,
             burst_size: 2,
             enabled: true,
+            max_tracked_keys: 1000,
         };
         
         let state = RateLimitState::new(config);
@@ -362,6 +363,7 @@ pub mod tests {
             requests_per_minute: 3,
             burst_size: 1,
             enabled: true,
+            max_tracked_keys: 1000,
         };
         
         let state = RateLimitState::new(config);
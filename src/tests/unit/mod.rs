@@ -69,6 +69,7 @@ pub mod domain {
             parameters: Some(serde_json::json!([])),
             id: Some(serde_json::json!(1)),
             client_info,
+            upstream: None,
         };
 
         assert_eq!(request.method, "getinfo");
@@ -125,7 +126,7 @@ pub mod application {
         app_config.payments.session_ttl_minutes = 5;
         app_config.payments.require_viewing_key = false;
         app_config.payments.tiers = vec![crate::config::app_config::PaymentTierConfig {
-            id: "basic".into(), amount_vrsc: 1.0, description: None, permissions: vec!["read".into()]
+            id: "basic".into(), amount_vrsc: 1.0, description: None, permissions: vec!["read".into()], accepted_currencies: vec![]
         }];
 
         let app_config = Arc::new(app_config);
@@ -145,7 +146,7 @@ pub mod application {
 
         // We cannot reach a real verusd in unit tests, so just validate request shaping logic
         // Ensure config reading and validation paths do not panic
-        let req = PaymentQuoteRequest { tier_id: "basic".into(), address_type: None };
+        let req = PaymentQuoteRequest { tier_id: "basic".into(), address_type: None, currency: None, identity: None, refund_address: None, webhook_url: None };
         // We expect a failure from RPC call; the important part is that pre-RPC validation passes
         let quote_res = svc.create_quote(req, &client_info).await;
         // Allow either RPC failure or success depending on environment, but not a validation error for tier or type
@@ -158,7 +159,7 @@ pub mod application {
 
         // Submit path should validate hex and session
         // Without a session, expect validation error: unknown payment_id
-        let submit_res = svc.submit_raw_transaction(PaymentSubmitRequest { payment_id: "nope".into(), rawtx_hex: "ab".repeat(60) }, &client_info).await;
+        let submit_res = svc.submit_raw_transaction(PaymentSubmitRequest { payment_id: "nope".into(), rawtx_hex: "ab".repeat(60), refund_address: None, identity_signature: None }, &client_info).await;
         assert!(submit_res.is_err());
     }
 
@@ -175,7 +176,7 @@ pub mod application {
 
     #[tokio::test]
     async fn test_health_check_use_case() {
-        let use_case = HealthCheckUseCase;
+        let use_case = HealthCheckUseCase::default();
         let health_data = use_case.execute();
         
         assert!(health_data.is_object());
@@ -449,6 +450,7 @@ pub mod middleware {
             requests_per_minute: 10,
             burst_size: 5,
             enabled: true,
+            max_tracked_keys: 1000,
         };
         let state = RateLimitState::new(config);
         